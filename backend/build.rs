@@ -0,0 +1,9 @@
+fn main() {
+    // Generated gRPC code is only needed with the `grpc` feature
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/reviews.proto").expect("proto/reviews.proto compiles");
+    }
+}
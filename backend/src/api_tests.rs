@@ -82,6 +82,7 @@ mod tests {
 
         assert_eq!(response_json["error"], "validation_error");
         assert!(response_json["message"].as_str().unwrap().contains("title"));
+        assert!(response_json["code"].as_str().unwrap().starts_with("E_VALIDATION_TITLE_"));
     }
 
     #[tokio::test]
@@ -267,6 +268,133 @@ mod tests {
         assert_eq!(response_json["result"]["failed"].as_array().unwrap().len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_bulk_upload_templates_round_trip() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/templates", temp_path));
+
+        let app = create_app();
+
+        for (format, content_type) in [("csv", "text/csv"), ("jsonl", "application/x-ndjson")] {
+            let request = Request::builder()
+                .method("GET")
+                .uri(format!("/reviews/templates/{}", format))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(response.headers()["content-type"].to_str().unwrap().starts_with(content_type));
+            assert!(response.headers()["content-disposition"].to_str().unwrap().contains("attachment"));
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let template = String::from_utf8(body.to_vec()).unwrap();
+            assert!(template.starts_with("# "), "Template should lead with validation notes");
+
+            // The untouched template uploads cleanly
+            let upload_request = Request::builder()
+                .method("POST")
+                .uri("/reviews/bulk")
+                .header("content-type", "application/json")
+                .body(Body::from(json!(template).to_string()))
+                .unwrap();
+
+            let upload_response = app.clone().oneshot(upload_request).await.unwrap();
+            assert_eq!(upload_response.status(), StatusCode::OK);
+
+            let body = axum::body::to_bytes(upload_response.into_body(), usize::MAX).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(response_json["result"]["successful"], 2);
+            assert_eq!(response_json["result"]["failed"].as_array().unwrap().len(), 0);
+        }
+
+        // Unknown format
+        let request = Request::builder()
+            .method("GET")
+            .uri("/reviews/templates/xlsx")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_reviews_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/export", temp_path));
+
+        let app = create_app();
+
+        let reviews = json!([
+            {"title": "First review", "body": "The first exported review.", "product_id": "prod_1", "rating": 5},
+            {"title": "Second review", "body": "The second exported review.", "product_id": "prod_2", "rating": 2}
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/reviews/export")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"].to_str().unwrap().starts_with("application/x-ndjson"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["title"], "Second review");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/reviews/export?format=csv")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-disposition"].to_str().unwrap().contains("reviews_export.csv"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv_text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(csv_text.starts_with("id,title,body,product_id,rating"));
+        assert_eq!(csv_text.lines().count(), 3);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/reviews/export?format=arrow")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/vnd.apache.arrow.stream");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        // A schema message, then record batches, then the end-of-stream marker
+        assert_eq!(body[..4], [0xFF; 4]);
+        assert_eq!(body[body.len() - 8..], [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]);
+        assert!(body.windows(13).any(|window| window == b"Second review"));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/reviews/export?format=xml")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn test_bulk_upload_empty_data() {
         // Set up temporary directory for testing
@@ -298,37 +426,23 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_search_reviews_endpoint() {
+    async fn test_list_reviews_pagination() {
         // Set up temporary directory for testing
         let temp_dir = TempDir::new().unwrap();
         let temp_path = temp_dir.path().to_str().unwrap();
-        env::set_var("DATA_DIR", format!("{}/search_test", temp_path));
+        env::set_var("DATA_DIR", format!("{}/list_reviews", temp_path));
 
         let app = create_app();
 
-        // First, add some reviews to search through
-        let reviews_to_add = vec![
-            json!({
-                "title": "Amazing smartphone",
-                "body": "This phone has excellent camera quality and fast performance. Great value for money.",
-                "product_id": "phone_001",
-                "rating": 5
-            }),
-            json!({
-                "title": "Good laptop",
-                "body": "Decent performance for work. Battery life could be better but overall satisfied.",
-                "product_id": "laptop_001", 
+        let reviews_to_add: Vec<_> = (1..=5)
+            .map(|i| json!({
+                "title": format!("Listed review {}", i),
+                "body": "This review exists to exercise pagination.",
+                "product_id": "list_001",
                 "rating": 4
-            }),
-            json!({
-                "title": "Poor quality headphones",
-                "body": "Sound quality is terrible and they broke after one week. Not recommended.",
-                "product_id": "headphones_001",
-                "rating": 1
-            })
-        ];
+            }))
+            .collect();
 
-        // Add reviews using bulk upload
         let bulk_request = Request::builder()
             .method("POST")
             .uri("/reviews/bulk")
@@ -339,198 +453,4253 @@ mod tests {
         let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
         assert_eq!(bulk_response.status(), StatusCode::OK);
 
-        // Now test search functionality
-        let search_data = json!({
-            "query": "camera quality",
-            "limit": 10
-        });
-
-        let search_request = Request::builder()
-            .method("POST")
-            .uri("/search")
-            .header("content-type", "application/json")
-            .body(Body::from(search_data.to_string()))
+        // Request the second page of two
+        let request = Request::builder()
+            .method("GET")
+            .uri("/reviews?page=2&page_size=2")
+            .body(Body::empty())
             .unwrap();
 
-        let search_response = app.oneshot(search_request).await.unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
 
-        assert_eq!(search_response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
 
-        let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
         assert_eq!(response_json["success"], true);
-        assert_eq!(response_json["query"], "camera quality");
-        assert_eq!(response_json["search_type"], "text_similarity");
-        
-        let results = response_json["results"].as_array().unwrap();
-        assert!(results.len() > 0, "Should find at least one matching review");
-        
-        // The smartphone review should be the top result due to "camera quality" match
-        let top_result = &results[0];
-        assert!(top_result["review"]["title"].as_str().unwrap().contains("smartphone"));
-        assert!(top_result["similarity_score"].as_f64().unwrap() > 0.0);
+        assert_eq!(response_json["page"], 2);
+        assert_eq!(response_json["page_size"], 2);
+        assert_eq!(response_json["total_reviews"], 5);
+        assert_eq!(response_json["total_pages"], 3);
+
+        let reviews = response_json["reviews"].as_array().unwrap();
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0]["title"], "Listed review 3");
+        assert_eq!(reviews[1]["title"], "Listed review 4");
+
+        // Invalid page size is rejected
+        let invalid_request = Request::builder()
+            .method("GET")
+            .uri("/reviews?page_size=0")
+            .body(Body::empty())
+            .unwrap();
+
+        let invalid_response = app.oneshot(invalid_request).await.unwrap();
+        assert_eq!(invalid_response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_search_reviews_validation_error() {
-        // Set up temporary directory for testing
+    async fn test_random_reviews_sampling() {
         let temp_dir = TempDir::new().unwrap();
         let temp_path = temp_dir.path().to_str().unwrap();
-        env::set_var("DATA_DIR", format!("{}/search_validation", temp_path));
+        env::set_var("DATA_DIR", format!("{}/random_reviews", temp_path));
 
         let app = create_app();
 
-        // Create search request with empty query
-        let invalid_search_data = json!({
-            "query": "",
-            "limit": 10
-        });
-
-        let request = Request::builder()
+        let reviews_to_add: Vec<_> = (1..=6)
+            .map(|i| json!({
+                "title": format!("Sampled review {}", i),
+                "body": "This review exists to exercise sampling.",
+                "product_id": "sample_001",
+                "rating": 3
+            }))
+            .collect();
+        let bulk_request = Request::builder()
             .method("POST")
-            .uri("/search")
+            .uri("/reviews/bulk")
             .header("content-type", "application/json")
-            .body(Body::from(invalid_search_data.to_string()))
+            .body(Body::from(json!(reviews_to_add).to_string()))
             .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
 
-        let response = app.oneshot(request).await.unwrap();
+        let sample = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let (status, first) = sample("/v1/reviews/random?count=3&seed=42").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first["count"], 3);
+        assert_eq!(first["seed"], 42);
+        let titles: std::collections::HashSet<&str> =
+            first["reviews"].as_array().unwrap().iter().map(|review| review["title"].as_str().unwrap()).collect();
+        assert_eq!(titles.len(), 3);
 
-        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
-        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        // The same seed draws the same sample; asking for more than exist returns them all
+        let (_, again) = sample("/v1/reviews/random?count=3&seed=42").await;
+        assert_eq!(again["reviews"], first["reviews"]);
+        let (_, all) = sample("/v1/reviews/random?count=50").await;
+        assert_eq!(all["count"], 6);
+        assert!(all["seed"].is_u64());
 
-        assert_eq!(response_json["error"], "validation_error");
-        assert!(response_json["message"].as_str().unwrap().contains("query"));
+        let (status, _) = sample("/v1/reviews/random?count=101").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_search_reviews_no_results() {
-        // Set up temporary directory for testing
+    async fn test_reviews_meta_fingerprints_the_dataset() {
         let temp_dir = TempDir::new().unwrap();
         let temp_path = temp_dir.path().to_str().unwrap();
-        env::set_var("DATA_DIR", format!("{}/search_no_results", temp_path));
+        env::set_var("DATA_DIR", format!("{}/reviews_meta", temp_path));
 
         let app = create_app();
+        let meta_request = |method: &str, etag: Option<&str>| {
+            let mut builder = Request::builder().method(method).uri("/v1/reviews/meta");
+            if let Some(etag) = etag {
+                builder = builder.header("if-none-match", etag);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        let create = |title: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/reviews")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({
+                    "title": title,
+                    "body": "Keeps coffee hot for the whole morning.",
+                    "product_id": "flask_1",
+                    "rating": 5
+                }).to_string()))
+                .unwrap()
+        };
 
-        // Search without adding any reviews first
-        let search_data = json!({
-            "query": "nonexistent product",
-            "limit": 10
-        });
-
-        let request = Request::builder()
-            .method("POST")
-            .uri("/search")
-            .header("content-type", "application/json")
-            .body(Body::from(search_data.to_string()))
-            .unwrap();
-
-        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(app.clone().oneshot(create("Good flask")).await.unwrap().status(), StatusCode::OK);
 
+        let response = app.clone().oneshot(meta_request("GET", None)).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
-
+        let etag = response.headers()["etag"].to_str().unwrap().to_string();
+        assert!(response.headers().contains_key("last-modified"));
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
-        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let meta: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(meta["meta"]["review_count"], 1);
+        assert_eq!(etag, format!("\"{}\"", meta["meta"]["content_hash"].as_str().unwrap()));
 
-        assert_eq!(response_json["success"], true);
-        assert_eq!(response_json["total_results"], 0);
-        assert_eq!(response_json["results"].as_array().unwrap().len(), 0);
+        // HEAD carries the same headers, and a matching ETag is answered with 304
+        let response = app.clone().oneshot(meta_request("HEAD", None)).await.unwrap();
+        assert_eq!(response.headers()["etag"], etag.as_str());
+        let response = app.clone().oneshot(meta_request("GET", Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // A write changes the fingerprint
+        assert_eq!(app.clone().oneshot(create("Great flask")).await.unwrap().status(), StatusCode::OK);
+        let response = app.oneshot(meta_request("GET", Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()["etag"], etag.as_str());
     }
 
     #[tokio::test]
-    async fn test_search_reviews_ranking() {
+    async fn test_get_review_by_id_endpoint() {
         // Set up temporary directory for testing
         let temp_dir = TempDir::new().unwrap();
         let temp_path = temp_dir.path().to_str().unwrap();
-        env::set_var("DATA_DIR", format!("{}/search_ranking", temp_path));
+        env::set_var("DATA_DIR", format!("{}/get_review", temp_path));
 
         let app = create_app();
 
-        // Add reviews with different relevance to query "fast performance"
-        let reviews_to_add = vec![
-            json!({
-                "title": "Fast performance laptop", // Exact match in title
-                "body": "This laptop delivers amazing speed and efficiency.",
-                "product_id": "laptop_002",
-                "rating": 5
-            }),
-            json!({
-                "title": "Good computer",
-                "body": "Has fast performance and great build quality.", // Exact match in body
-                "product_id": "computer_001",
-                "rating": 4
-            }),
-            json!({
-                "title": "Slow device",
-                "body": "This device is quite slow and not recommended.", // No match
-                "product_id": "device_001",
-                "rating": 2
-            })
-        ];
+        let review_data = json!({
+            "title": "Sturdy backpack",
+            "body": "Survived a month of daily commuting without a scratch.",
+            "product_id": "bag_001",
+            "rating": 5
+        });
 
-        // Add reviews using bulk upload
-        let bulk_request = Request::builder()
+        let create_request = Request::builder()
             .method("POST")
-            .uri("/reviews/bulk")
+            .uri("/reviews")
             .header("content-type", "application/json")
-            .body(Body::from(json!(reviews_to_add).to_string()))
+            .body(Body::from(review_data.to_string()))
             .unwrap();
 
-        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
-        assert_eq!(bulk_response.status(), StatusCode::OK);
-
-        // Search for "fast performance"
-        let search_data = json!({
-            "query": "fast performance",
-            "limit": 10
-        });
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        let body = axum::body::to_bytes(create_response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let review_id = created["review_id"].as_str().unwrap();
 
-        let search_request = Request::builder()
-            .method("POST")
-            .uri("/search")
-            .header("content-type", "application/json")
-            .body(Body::from(search_data.to_string()))
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/reviews/{}", review_id))
+            .body(Body::empty())
             .unwrap();
 
-        let search_response = app.oneshot(search_request).await.unwrap();
-        assert_eq!(search_response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(request).await.unwrap();
 
-        let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
-        let results = response_json["results"].as_array().unwrap();
-        assert!(results.len() >= 2, "Should find at least 2 matching reviews");
+        assert_eq!(response_json["review"]["id"], review_id);
+        assert_eq!(response_json["review"]["title"], "Sturdy backpack");
 
-        // Results should be ranked by similarity score (descending)
-        let first_score = results[0]["similarity_score"].as_f64().unwrap();
-        let second_score = results[1]["similarity_score"].as_f64().unwrap();
-        assert!(first_score >= second_score, "Results should be ranked by similarity score");
+        // Unknown id
+        let missing_request = Request::builder()
+            .method("GET")
+            .uri("/reviews/does-not-exist")
+            .body(Body::empty())
+            .unwrap();
 
-        // The laptop with exact title match should have highest score
-        let top_result = &results[0];
-        assert!(top_result["review"]["title"].as_str().unwrap().contains("Fast performance"));
+        let missing_response = app.oneshot(missing_request).await.unwrap();
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(missing_response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["error"], "not_found");
     }
 
     #[tokio::test]
-    async fn test_health_check_endpoint() {
+    async fn test_batch_get_reviews_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/batch_get", temp_path));
+
         let app = create_app();
 
-        let request = Request::builder()
-            .method("GET")
-            .uri("/health")
-            .body(Body::empty())
+        let reviews = json!([
+            {"title": "First review", "body": "The first review in the batch.", "product_id": "prod_1", "rating": 5},
+            {"title": "Second review", "body": "The second review in the batch.", "product_id": "prod_1", "rating": 3}
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews.to_string()))
             .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
 
-        let response = app.oneshot(request).await.unwrap();
-
+        // Indices come back in request order, with null for unknown positions
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/batch")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"indices": [1, 7, 0]}).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
-
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["reviews"][0]["title"], "Second review");
+        assert!(response_json["reviews"][1].is_null());
+        assert_eq!(response_json["reviews"][2]["title"], "First review");
+        assert_eq!(response_json["total_requested"], 3);
+        assert_eq!(response_json["total_found"], 2);
 
-        assert_eq!(response_json["status"], "healthy");
+        // The same reviews can be fetched by id
+        let first_id = response_json["reviews"][2]["id"].clone();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/batch")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"ids": [first_id, "missing"]}).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["reviews"][0]["title"], "First review");
+        assert!(response_json["reviews"][1].is_null());
+
+        // Giving both lists is rejected
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/batch")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"ids": ["a"], "indices": [0]}).to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_reviews_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/batch_create", temp_path));
+
+        let app = create_app();
+
+        let post = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/reviews/batch")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // One invalid review rejects the whole batch, and every problem is reported
+        let rejected = json!([
+            {"title": "Kettle", "body": "Boils water quickly and quietly.", "product_id": "kettle", "rating": 4},
+            {"title": "Toaster", "body": "Browns evenly on both sides.", "product_id": "toaster", "rating": 9},
+            {"title": "", "body": "Keeps coffee hot all morning.", "product_id": "flask", "rating": 5}
+        ]);
+        let response = app.clone().oneshot(post(rejected)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error = read_json(response).await;
+        assert_eq!(error["code"], "E_VALIDATION_RATING_INVALID");
+        assert_eq!(error["details"]["errors"][0]["index"], 1);
+        assert_eq!(error["details"]["errors"][1]["index"], 2);
+
+        let list = Request::builder().method("GET").uri("/v1/reviews").body(Body::empty()).unwrap();
+        assert_eq!(read_json(app.clone().oneshot(list).await.unwrap()).await["total_reviews"], 0);
+
+        // A valid batch is stored in request order
+        let accepted = json!([
+            {"title": "Kettle", "body": "Boils water quickly and quietly.", "product_id": "kettle", "rating": 4},
+            {"title": "Toaster", "body": "Browns evenly on both sides.", "product_id": "toaster", "rating": 3}
+        ]);
+        let response = app.clone().oneshot(post(accepted)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let created = read_json(response).await;
+        assert_eq!(created["reviews"][0]["vector_index"], 0);
+        assert_eq!(created["reviews"][1]["title"], "Toaster");
+        assert_eq!(created["embedding_status"], "pending");
+
+        // An object body is still a lookup
+        let response = app.clone().oneshot(post(json!({"indices": [1]}))).await.unwrap();
+        assert_eq!(read_json(response).await["reviews"][0]["id"], created["reviews"][1]["id"]);
+
+        assert_eq!(app.oneshot(post(json!([]))).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_reviews_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/bulk_delete", temp_path));
+
+        let app = create_app();
+
+        let reviews = json!([
+            {"title": "Kettle one", "body": "Boils water quickly and quietly.", "product_id": "kettle", "rating": 4},
+            {"title": "Kettle two", "body": "The handle gets hot after a while.", "product_id": "kettle", "rating": 2},
+            {"title": "Toaster one", "body": "Browns both sides of the bread evenly.", "product_id": "toaster", "rating": 5}
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
+
+        let request = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/reviews/bulk-delete")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // A filter matching nothing leaves everything in place
+        let response = app.clone().oneshot(request(json!({"to": "2000-01-01T00:00:00Z"}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_json(response).await["deleted"], 0);
+
+        let response = app.clone().oneshot(request(json!({"product_id": "kettle"}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let deleted = read_json(response).await;
+        assert_eq!(deleted["deleted"], 2);
+        assert_eq!(deleted["not_found"], json!([]));
+
+        // Already deleted and unknown ids are reported, not failed
+        let kettle_id = deleted["deleted_ids"][0].clone();
+        let list = app.clone().oneshot(Request::builder().uri("/v1/reviews").body(Body::empty()).unwrap()).await.unwrap();
+        let toaster_id = read_json(list).await["reviews"][0]["id"].clone();
+        let response = app
+            .clone()
+            .oneshot(request(json!({"ids": [toaster_id, kettle_id, "missing", "missing"]})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let deleted = read_json(response).await;
+        assert_eq!(deleted["deleted"], 1);
+        assert_eq!(deleted["not_found"], json!([kettle_id, "missing"]));
+
+        // Lines are tombstoned, not removed, so vector slots stay aligned
+        let contents = std::fs::read_to_string(format!("{}/bulk_delete/reviews.jsonl", temp_path)).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+
+        // An empty filter, or ids mixed with a filter, is refused
+        let response = app.clone().oneshot(request(json!({}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.oneshot(request(json!({"ids": ["a"], "product_id": "kettle"}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_delete_review_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/delete_review", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = vec![
+            json!({
+                "title": "Noisy blender",
+                "body": "The motor is loud but it crushes ice without trouble.",
+                "product_id": "blender_001",
+                "rating": 3
+            }),
+            json!({
+                "title": "Quiet blender",
+                "body": "Barely audible and still crushes ice in seconds.",
+                "product_id": "blender_002",
+                "rating": 5
+            })
+        ];
+
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        // Find the id of the first review via the listing
+        let list_request = Request::builder()
+            .method("GET")
+            .uri("/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let list_response = app.clone().oneshot(list_request).await.unwrap();
+        let body = axum::body::to_bytes(list_response.into_body(), usize::MAX).await.unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let review_id = listed["reviews"][0]["id"].as_str().unwrap().to_string();
+
+        let delete_request = Request::builder()
+            .method("DELETE")
+            .uri(format!("/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+
+        let delete_response = app.clone().oneshot(delete_request).await.unwrap();
+        assert_eq!(delete_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(delete_response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["success"], true);
+        assert_eq!(response_json["vector_index"], 0);
+        assert!(response_json["deleted_at"].is_string());
+
+        // The review is gone from lookup, listing and search
+        let get_request = Request::builder()
+            .method("GET")
+            .uri(format!("/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+        let get_response = app.clone().oneshot(get_request).await.unwrap();
+        assert_eq!(get_response.status(), StatusCode::NOT_FOUND);
+
+        let list_request = Request::builder()
+            .method("GET")
+            .uri("/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let list_response = app.clone().oneshot(list_request).await.unwrap();
+        let body = axum::body::to_bytes(list_response.into_body(), usize::MAX).await.unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listed["total_reviews"], 1);
+
+        let search_request = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"query": "blender crushes ice"}).to_string()))
+            .unwrap();
+        let search_response = app.clone().oneshot(search_request).await.unwrap();
+        let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+        let searched: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let results = searched["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["review"]["title"], "Quiet blender");
+
+        // New reviews still get the next dense vector index
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Replacement blender",
+                "body": "Bought another one after the first broke.",
+                "product_id": "blender_001",
+                "rating": 4
+            }).to_string()))
+            .unwrap();
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        let body = axum::body::to_bytes(create_response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["vector_index"], 2);
+
+        // Deleting again is a 404
+        let delete_again = Request::builder()
+            .method("DELETE")
+            .uri(format!("/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+        let delete_again_response = app.oneshot(delete_again).await.unwrap();
+        assert_eq!(delete_again_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_list_product_reviews() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/product_reviews", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = vec![
+            json!({"title": "Decent lamp", "body": "Bright enough for reading at night.", "product_id": "lamp_001", "rating": 3}),
+            json!({"title": "Other product", "body": "This review is for a different lamp.", "product_id": "lamp_002", "rating": 1}),
+            json!({"title": "Perfect lamp", "body": "Warm light and a sturdy base.", "product_id": "lamp_001", "rating": 5}),
+            json!({"title": "Flickering lamp", "body": "Started flickering after a week.", "product_id": "lamp_001", "rating": 2})
+        ];
+
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/products/lamp_001/reviews?sort=rating&order=desc&page=1&page_size=2")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["total_reviews"], 3);
+        assert_eq!(response_json["total_pages"], 2);
+        let reviews = response_json["reviews"].as_array().unwrap();
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0]["rating"], 5);
+        assert_eq!(reviews[1]["rating"], 3);
+
+        // Unknown products are an empty list, invalid sort fields are rejected
+        let empty_request = Request::builder()
+            .method("GET")
+            .uri("/products/unknown/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let empty_response = app.clone().oneshot(empty_request).await.unwrap();
+        let body = axum::body::to_bytes(empty_response.into_body(), usize::MAX).await.unwrap();
+        let empty_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(empty_json["total_reviews"], 0);
+
+        let invalid_request = Request::builder()
+            .method("GET")
+            .uri("/products/lamp_001/reviews?sort=title")
+            .body(Body::empty())
+            .unwrap();
+        let invalid_response = app.oneshot(invalid_request).await.unwrap();
+        assert_eq!(invalid_response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_product_stats_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/product_stats", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = vec![
+            json!({"title": "Solid desk", "body": "Sturdy and easy to assemble.", "product_id": "desk_001", "rating": 4}),
+            json!({"title": "Wobbly desk", "body": "One leg was shorter than the others.", "product_id": "desk_001", "rating": 2}),
+            json!({"title": "Other product", "body": "This review is for a chair instead.", "product_id": "chair_001", "rating": 5})
+        ];
+
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/products/desk_001/stats")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let stats = &response_json["stats"];
+        assert_eq!(stats["review_count"], 2);
+        assert_eq!(stats["average_rating"], 3.0);
+        assert_eq!(stats["rating_histogram"], json!({"1": 0, "2": 1, "3": 0, "4": 1, "5": 0}));
+        assert!(stats["first_review_at"].is_string());
+        assert!(stats["last_review_at"].is_string());
+
+        let missing_request = Request::builder()
+            .method("GET")
+            .uri("/products/unknown/stats")
+            .body(Body::empty())
+            .unwrap();
+        let missing_response = app.oneshot(missing_request).await.unwrap();
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_service_stats_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/service_stats", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = vec![
+            json!({"title": "Good mug", "body": "Keeps coffee warm for an hour.", "product_id": "mug_001", "rating": 4}),
+            json!({"title": "Good plate", "body": "Survives the dishwasher just fine.", "product_id": "plate_001", "rating": 5}),
+            json!({"title": "Another mug", "body": "The handle is a bit small for me.", "product_id": "mug_001", "rating": 3})
+        ];
+
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/stats")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let stats = &response_json["stats"];
+        assert_eq!(stats["total_reviews"], 3);
+        assert_eq!(stats["total_products"], 2);
+        let per_day = stats["reviews_per_day"].as_array().unwrap();
+        assert_eq!(per_day.len(), 30);
+        assert_eq!(per_day.last().unwrap()["count"], 3);
+
+        let health = &response_json["index_health"];
+        assert_eq!(health["jsonl_lines"], 3);
+        assert_eq!(health["in_sync"], true);
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document() {
+        let app = create_app();
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/openapi.json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(document["paths"]["/v1/reviews"]["post"].is_object());
+        assert!(document["paths"]["/v1/reviews/{id}"]["delete"].is_object());
+        assert!(document["paths"]["/v1/search"]["post"].is_object());
+        for schema in ["ReviewData", "SearchRequest", "ErrorResponse", "BulkUploadResult"] {
+            assert!(document["components"]["schemas"][schema].is_object(), "missing schema {}", schema);
+        }
+
+        let docs_request = Request::builder()
+            .method("GET")
+            .uri("/docs/")
+            .body(Body::empty())
+            .unwrap();
+        let docs_response = app.oneshot(docs_request).await.unwrap();
+        assert_eq!(docs_response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_versioned_and_unversioned_routes() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/versioning", temp_path));
+
+        let app = create_app();
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("deprecation").is_none());
+
+        // The unversioned path still works but is flagged with its successor
+        let request = Request::builder()
+            .method("GET")
+            .uri("/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(response.headers()["link"], "</v1/reviews>; rel=\"successor-version\"");
+    }
+
+    #[tokio::test]
+    async fn test_reviews_are_embedded_after_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/embedding_status", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let review = json!({
+            "title": "Sturdy tent",
+            "body": "Stayed dry through two nights of heavy rain.",
+            "product_id": "tent_1",
+            "rating": 5
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review.to_string()))
+            .unwrap();
+        let created = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(created["embedding_status"], "pending");
+
+        // Search does not wait for the vector
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/search?q=tent%20rain")
+            .body(Body::empty())
+            .unwrap();
+        let results = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(results["total_results"], 1);
+
+        // The background worker runs only in main(); run one batch by hand
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 1);
+
+        let review_id = created["review_id"].as_str().unwrap();
+        let request = Request::builder()
+            .uri(format!("/v1/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+        let fetched = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(fetched["embedding_status"], "embedded");
+
+        let request = Request::builder().uri("/v1/stats").body(Body::empty()).unwrap();
+        let stats = read_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(stats["index_health"]["vector_index_entries"], 1);
+        assert_eq!(stats["index_health"]["pending_embeddings"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_job_can_be_polled() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/reindex", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let review = json!({
+            "title": "Warm sleeping bag",
+            "body": "Comfortable down to freezing on a windy ridge.",
+            "product_id": "bag_1",
+            "rating": 4
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        // A truncated index is what a crash mid-write leaves behind
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        std::fs::write(&data_paths.reviews_index, [0u8; 7]).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/admin/reindex")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let started = read_json(response).await;
+        assert_eq!(started["job"]["type"], "reindex");
+        let job_id = started["job"]["id"].as_str().unwrap().to_string();
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let request = Request::builder()
+                .uri(format!("/v1/jobs/{}", job_id))
+                .body(Body::empty())
+                .unwrap();
+            job = read_json(app.clone().oneshot(request).await.unwrap()).await["job"].clone();
+            if job["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], "completed");
+        assert_eq!(job["successful"], 1);
+
+        let request = Request::builder().uri("/v1/stats").body(Body::empty()).unwrap();
+        let stats = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(stats["index_health"]["vector_index_entries"], 1);
+
+        let request = Request::builder().uri("/v1/jobs/missing").body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_admin_compact_drops_deleted_reviews() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/admin_compact", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let reviews = json!([
+            { "title": "Kept review", "body": "This one stays after compaction.", "product_id": "p1", "rating": 4 },
+            { "title": "Deleted review", "body": "This one is tombstoned first.", "product_id": "p1", "rating": 2 }
+        ]);
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        let request = Request::builder().uri("/v1/reviews").body(Body::empty()).unwrap();
+        let listed = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        let deleted_id = listed["reviews"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|review| review["title"] == "Deleted review")
+            .unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let request = Request::builder()
+            .method("DELETE")
+            .uri(format!("/v1/reviews/{}", deleted_id))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/admin/compact")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let job_id = read_json(response).await["job"]["id"].as_str().unwrap().to_string();
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let request = Request::builder()
+                .uri(format!("/v1/jobs/{}", job_id))
+                .body(Body::empty())
+                .unwrap();
+            job = read_json(app.clone().oneshot(request).await.unwrap()).await["job"].clone();
+            if job["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], "completed", "{}", job);
+
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        let stored = std::fs::read_to_string(&data_paths.reviews_jsonl).unwrap();
+        assert_eq!(stored.lines().count(), 1);
+        assert!(stored.contains("Kept review"));
+    }
+
+    #[tokio::test]
+    async fn test_projection_plots_embedded_reviews() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/projection", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let reviews: Vec<_> = ["Quiet blender", "Loud blender", "Soft pillow", "Firm pillow"]
+            .iter()
+            .enumerate()
+            .map(|(i, title)| json!({
+                "title": title,
+                "body": "Written to give the projection something to spread out.",
+                "product_id": if i < 2 { "blender_1" } else { "pillow_1" },
+                "rating": i + 2
+            }))
+            .collect();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews).to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        // Nothing is plotted before it is embedded
+        let request = Request::builder().uri("/v1/analyze/projection").body(Body::empty()).unwrap();
+        let projection = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(projection["sample_size"], 0);
+
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 4);
+
+        let request = Request::builder()
+            .uri("/v1/analyze/projection?sample=3&seed=9")
+            .body(Body::empty())
+            .unwrap();
+        let projection = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(projection["method"], "pca");
+        assert_eq!(projection["seed"], 9);
+        assert_eq!(projection["sample_size"], 3);
+        let points = projection["points"].as_array().unwrap();
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|point| point["x"].is_f64() && point["product_id"].is_string()));
+        assert!(projection["explained_variance"][0].as_f64().unwrap() > 0.0);
+
+        let request = Request::builder()
+            .uri("/v1/analyze/projection?sample=0")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_events_stream_new_reviews() {
+        use futures_util::StreamExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/events", temp_path));
+
+        let app = create_app();
+
+        let request = Request::builder().uri("/v1/events").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut events = response.into_body().into_data_stream();
+
+        let review = json!({
+            "title": "Bright lamp",
+            "body": "Lights the whole desk without any glare.",
+            "product_id": "lamp_1",
+            "rating": 4
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review.to_string()))
+            .unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), events.next())
+            .await
+            .expect("no event was sent")
+            .unwrap()
+            .unwrap();
+        let frame = String::from_utf8(frame.to_vec()).unwrap();
+        assert!(frame.starts_with("event: review_created\n"), "{}", frame);
+        let data: serde_json::Value =
+            serde_json::from_str(frame.lines().find_map(|line| line.strip_prefix("data: ")).unwrap()).unwrap();
+        assert_eq!(data["type"], "review_created");
+        assert_eq!(data["product_id"], "lamp_1");
+    }
+
+    #[tokio::test]
+    async fn test_search_session_debounces_and_caches() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_session", temp_path));
+
+        let app = create_app();
+        let review = json!({
+            "title": "Bright lamp",
+            "body": "Lights the whole desk without any glare.",
+            "product_id": "lamp_1",
+            "rating": 4
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/v1/ws/search", address))
+            .await
+            .unwrap();
+
+        async fn next_reply<S>(socket: &mut S) -> serde_json::Value
+        where
+            S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+        {
+            let frame = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+                .await
+                .expect("no reply was sent")
+                .unwrap()
+                .unwrap();
+            serde_json::from_str(frame.to_text().unwrap()).unwrap()
+        }
+
+        // Only the query typed last is searched
+        for query in ["br", "bright", "bright lamp"] {
+            socket.send(Message::Text(query.to_string())).await.unwrap();
+        }
+        let reply = next_reply(&mut socket).await;
+        assert_eq!(reply["type"], "results");
+        assert_eq!((reply["seq"].as_u64(), reply["cached"].as_bool()), (Some(3), Some(false)));
+        assert_eq!(reply["query"], "bright lamp");
+        assert_eq!(reply["results"][0]["review"]["product_id"], "lamp_1");
+
+        socket.send(Message::Text(json!({ "query": "bright lamp" }).to_string())).await.unwrap();
+        let reply = next_reply(&mut socket).await;
+        assert_eq!((reply["seq"].as_u64(), reply["cached"].as_bool()), (Some(4), Some(true)));
+
+        socket.send(Message::Text("{\"query\":".to_string())).await.unwrap();
+        let reply = next_reply(&mut socket).await;
+        assert_eq!(reply["type"], "error");
+        assert_eq!(reply["seq"], 5);
+        assert_eq!(reply["code"], "E_MALFORMED_JSON");
+    }
+
+    #[tokio::test]
+    async fn test_update_review_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/update_review", temp_path));
+
+        let app = create_app();
+
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Grate toaster",
+                "body": "Toasts evenly on both sides, very hapy with it.",
+                "product_id": "toaster_001",
+                "rating": 4
+            }).to_string()))
+            .unwrap();
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        let body = axum::body::to_bytes(create_response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let review_id = created["review_id"].as_str().unwrap().to_string();
+
+        let update_request = Request::builder()
+            .method("PUT")
+            .uri(format!("/reviews/{}", review_id))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Great toaster",
+                "body": "Toasts evenly on both sides, very happy with it.",
+                "product_id": "toaster_001",
+                "rating": 5
+            }).to_string()))
+            .unwrap();
+        let update_response = app.clone().oneshot(update_request).await.unwrap();
+        assert_eq!(update_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(update_response.into_body(), usize::MAX).await.unwrap();
+        let updated: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(updated["review"]["id"], review_id.as_str());
+        assert_eq!(updated["review"]["title"], "Great toaster");
+        assert_eq!(updated["review"]["vector_index"], 0);
+        assert_eq!(updated["review"]["needs_reembedding"], true);
+        assert!(updated["review"]["updated_at"].is_string());
+
+        let get_request = Request::builder()
+            .method("GET")
+            .uri(format!("/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+        let get_response = app.clone().oneshot(get_request).await.unwrap();
+        let body = axum::body::to_bytes(get_response.into_body(), usize::MAX).await.unwrap();
+        let fetched: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(fetched["review"]["rating"], 5);
+
+        // Invalid data is rejected before touching storage
+        let invalid_request = Request::builder()
+            .method("PUT")
+            .uri(format!("/reviews/{}", review_id))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Great toaster",
+                "body": "Short",
+                "product_id": "toaster_001",
+                "rating": 5
+            }).to_string()))
+            .unwrap();
+        let invalid_response = app.clone().oneshot(invalid_request).await.unwrap();
+        assert_eq!(invalid_response.status(), StatusCode::BAD_REQUEST);
+
+        let missing_request = Request::builder()
+            .method("PUT")
+            .uri("/reviews/missing")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Great toaster",
+                "body": "Toasts evenly on both sides.",
+                "product_id": "toaster_001",
+                "rating": 5
+            }).to_string()))
+            .unwrap();
+        let missing_response = app.oneshot(missing_request).await.unwrap();
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_upload_history_and_rollback() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/upload_history", temp_path));
+
+        let app = create_app();
+
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({
+                "title": "Single review",
+                "body": "Added on its own, outside of any upload.",
+                "product_id": "kettle_001",
+                "rating": 4
+            }).to_string()))
+            .unwrap();
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        assert_eq!(create_response.status(), StatusCode::OK);
+
+        let reviews_to_add = vec![
+            json!({
+                "title": "Kettle boils fast",
+                "body": "Boils a full litre in under two minutes.",
+                "product_id": "kettle_001",
+                "rating": 5
+            }),
+            json!({
+                "title": "Bad",
+                "body": "Too short rating is invalid",
+                "product_id": "kettle_001",
+                "rating": 9
+            })
+        ];
+
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk?file_name=kettles.json")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(bulk_response.into_body(), usize::MAX).await.unwrap();
+        let uploaded: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let job_id = uploaded["job_id"].as_str().unwrap().to_string();
+        assert!(uploaded["batch_id"].is_string());
+
+        // The upload shows up in the history with its counts
+        let jobs_request = Request::builder()
+            .method("GET")
+            .uri("/jobs?type=bulk_upload&page=1&page_size=10")
+            .body(Body::empty())
+            .unwrap();
+        let jobs_response = app.clone().oneshot(jobs_request).await.unwrap();
+        assert_eq!(jobs_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(jobs_response.into_body(), usize::MAX).await.unwrap();
+        let history: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(history["total_jobs"], 1);
+        let job = &history["jobs"][0];
+        assert_eq!(job["id"], job_id.as_str());
+        assert_eq!(job["type"], "bulk_upload");
+        assert_eq!(job["status"], "completed");
+        assert_eq!(job["file_name"], "kettles.json");
+        assert_eq!(job["successful"], 1);
+        assert_eq!(job["failed"], 1);
+
+        // Rolling back removes only the batch's reviews
+        let rollback_request = Request::builder()
+            .method("POST")
+            .uri(format!("/jobs/{}/rollback", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let rollback_response = app.clone().oneshot(rollback_request).await.unwrap();
+        assert_eq!(rollback_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(rollback_response.into_body(), usize::MAX).await.unwrap();
+        let rolled_back: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rolled_back["reviews_rolled_back"], 1);
+        assert_eq!(rolled_back["job"]["status"], "rolled_back");
+
+        let list_request = Request::builder()
+            .method("GET")
+            .uri("/reviews")
+            .body(Body::empty())
+            .unwrap();
+        let list_response = app.clone().oneshot(list_request).await.unwrap();
+        let body = axum::body::to_bytes(list_response.into_body(), usize::MAX).await.unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listed["total_reviews"], 1);
+        assert_eq!(listed["reviews"][0]["title"], "Single review");
+
+        // A second rollback conflicts, an unknown job is a 404
+        let again_request = Request::builder()
+            .method("POST")
+            .uri(format!("/jobs/{}/rollback", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let again_response = app.clone().oneshot(again_request).await.unwrap();
+        assert_eq!(again_response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(again_response.into_body(), usize::MAX).await.unwrap();
+        let conflict_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(conflict_json["code"], "E_CONFLICT");
+
+        let missing_request = Request::builder()
+            .method("POST")
+            .uri("/jobs/missing/rollback")
+            .body(Body::empty())
+            .unwrap();
+        let missing_response = app.oneshot(missing_request).await.unwrap();
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_check_duplicate_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/check_duplicate", temp_path));
+
+        let app = create_app();
+
+        let review_data = json!({
+            "title": "Solid headphones",
+            "body": "Comfortable fit and the noise cancelling works well on flights.",
+            "product_id": "headphones_002",
+            "rating": 4,
+            "reviewer_id": "client_123"
+        });
+
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review_data.to_string()))
+            .unwrap();
+
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        assert_eq!(create_response.status(), StatusCode::OK);
+
+        // Near-identical draft from the same client
+        let draft = json!({
+            "title": "Solid headphones",
+            "body": "Comfortable fit, and the noise cancelling works well on flights!",
+            "reviewer_id": "client_123"
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/reviews/check-duplicate")
+            .header("content-type", "application/json")
+            .body(Body::from(draft.to_string()))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["is_duplicate"], true);
+        assert_eq!(response_json["matching_review"]["reviewer_id"], "client_123");
+
+        // Unrelated draft is not flagged
+        let unrelated = json!({
+            "body": "Terrible battery and the case cracked within a week.",
+            "reviewer_id": "client_123"
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/reviews/check-duplicate")
+            .header("content-type", "application/json")
+            .body(Body::from(unrelated.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["is_duplicate"], false);
+        assert!(response_json["matching_review"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_endpoint() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_test", temp_path));
+
+        let app = create_app();
+
+        // First, add some reviews to search through
+        let reviews_to_add = vec![
+            json!({
+                "title": "Amazing smartphone",
+                "body": "This phone has excellent camera quality and fast performance. Great value for money.",
+                "product_id": "phone_001",
+                "rating": 5
+            }),
+            json!({
+                "title": "Good laptop",
+                "body": "Decent performance for work. Battery life could be better but overall satisfied.",
+                "product_id": "laptop_001", 
+                "rating": 4
+            }),
+            json!({
+                "title": "Poor quality headphones",
+                "body": "Sound quality is terrible and they broke after one week. Not recommended.",
+                "product_id": "headphones_001",
+                "rating": 1
+            })
+        ];
+
+        // Add reviews using bulk upload
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        // Now test search functionality
+        let search_data = json!({
+            "query": "camera quality",
+            "limit": 10
+        });
+
+        let search_request = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(search_data.to_string()))
+            .unwrap();
+
+        let search_response = app.oneshot(search_request).await.unwrap();
+
+        assert_eq!(search_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["success"], true);
+        assert_eq!(response_json["query"], "camera quality");
+        assert_eq!(response_json["search_type"], "vector_similarity");
+        
+        let results = response_json["results"].as_array().unwrap();
+        assert!(!results.is_empty(), "Should find at least one matching review");
+        
+        // The smartphone review should be the top result due to "camera quality" match
+        let top_result = &results[0];
+        assert!(top_result["review"]["title"].as_str().unwrap().contains("smartphone"));
+        assert!(top_result["similarity_score"].as_f64().unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_validation_error() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_validation", temp_path));
+
+        let app = create_app();
+
+        // Create search request with empty query
+        let invalid_search_data = json!({
+            "query": "",
+            "limit": 10
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(invalid_search_data.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["error"], "validation_error");
+        assert!(response_json["message"].as_str().unwrap().contains("query"));
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_no_results() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_no_results", temp_path));
+
+        let app = create_app();
+
+        // Search without adding any reviews first
+        let search_data = json!({
+            "query": "nonexistent product",
+            "limit": 10
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(search_data.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["success"], true);
+        assert_eq!(response_json["total_results"], 0);
+        assert_eq!(response_json["results"].as_array().unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_ranking() {
+        // Set up temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_ranking", temp_path));
+
+        let app = create_app();
+
+        // Add reviews with different relevance to query "fast performance"
+        let reviews_to_add = vec![
+            json!({
+                "title": "Fast performance laptop", // Exact match in title
+                "body": "This laptop delivers amazing speed and efficiency.",
+                "product_id": "laptop_002",
+                "rating": 5
+            }),
+            json!({
+                "title": "Good computer",
+                "body": "Has fast performance and great build quality.", // Exact match in body
+                "product_id": "computer_001",
+                "rating": 4
+            }),
+            json!({
+                "title": "Slow device",
+                "body": "This device is quite slow and not recommended.", // No match
+                "product_id": "device_001",
+                "rating": 2
+            })
+        ];
+
+        // Add reviews using bulk upload
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(json!(reviews_to_add).to_string()))
+            .unwrap();
+
+        let bulk_response = app.clone().oneshot(bulk_request).await.unwrap();
+        assert_eq!(bulk_response.status(), StatusCode::OK);
+
+        // Search for "fast performance"
+        let search_data = json!({
+            "query": "fast performance",
+            "limit": 10
+        });
+
+        let search_request = Request::builder()
+            .method("POST")
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(search_data.to_string()))
+            .unwrap();
+
+        let search_response = app.oneshot(search_request).await.unwrap();
+        assert_eq!(search_response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let results = response_json["results"].as_array().unwrap();
+        assert!(results.len() >= 2, "Should find at least 2 matching reviews");
+
+        // Results should be ranked by similarity score (descending)
+        let first_score = results[0]["similarity_score"].as_f64().unwrap();
+        let second_score = results[1]["similarity_score"].as_f64().unwrap();
+        assert!(first_score >= second_score, "Results should be ranked by similarity score");
+
+        // The laptop with exact title match should have highest score
+        let top_result = &results[0];
+        assert!(top_result["review"]["title"].as_str().unwrap().contains("Fast performance"));
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_cjk() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_cjk", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = json!([
+            // "Battery life is very long, charging is fast"
+            { "title": "电池续航", "body": "电池续航很长，充电也很快。", "product_id": "phone_001", "rating": 5 },
+            // "The screen is bright"
+            { "title": "画面", "body": "画面がとても明るいです。", "product_id": "phone_001", "rating": 4 },
+            { "title": "Battery", "body": "The battery lasts two days.", "product_id": "phone_002", "rating": 4 }
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews_to_add.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
+
+        // Neither query appears verbatim in a review, but their words do, so the review
+        // outranks those that only carry the rating bonus
+        for (query, title) in [("电池的续航怎么样", "电池续航"), ("明るい画面", "画面")] {
+            let search_request = Request::builder()
+                .method("POST")
+                .uri("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "query": query }).to_string()))
+                .unwrap();
+            let search_response = app.clone().oneshot(search_request).await.unwrap();
+            let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let results = response_json["results"].as_array().unwrap();
+            assert_eq!(results[0]["review"]["title"], title, "{}: {}", query, response_json);
+            assert!(results[0]["similarity_score"].as_f64().unwrap() > results[1]["similarity_score"].as_f64().unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_folds_diacritics() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_diacritics", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = json!([
+            { "title": "Café au lait", "body": "Smooth and creamy, no bitterness.", "product_id": "coffee_001", "rating": 3 },
+            { "title": "Plain tea", "body": "Nothing special about this one.", "product_id": "tea_001", "rating": 3 }
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews_to_add.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
+
+        // Folding applies to the query as well as to the reviews
+        for query in ["cafe", "CAFÉ AU LAIT", "crème café"] {
+            let search_request = Request::builder()
+                .method("POST")
+                .uri("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "query": query }).to_string()))
+                .unwrap();
+            let search_response = app.clone().oneshot(search_request).await.unwrap();
+            let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let results = response_json["results"].as_array().unwrap();
+            assert_eq!(results.len(), 1, "{}: {}", query, response_json);
+            assert_eq!(results[0]["review"]["title"], "Café au lait");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_matches_quantities() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_quantities", temp_path));
+
+        let app = create_app();
+
+        let reviews_to_add = json!([
+            { "title": "10h battery", "body": "Gets me through a whole shift.", "product_id": "phone_001", "rating": 3 },
+            { "title": "Slow charger", "body": "Takes 3 hours to fill a 5,000mAh pack.", "product_id": "charger_001", "rating": 3 }
+        ]);
+        let bulk_request = Request::builder()
+            .method("POST")
+            .uri("/reviews/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(reviews_to_add.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(bulk_request).await.unwrap().status(), StatusCode::OK);
+
+        for (query, expected) in [
+            ("battery lasts 10 hours", "10h battery"),
+            ("ten hrs", "10h battery"),
+            ("5000 mah", "Slow charger"),
+        ] {
+            let search_request = Request::builder()
+                .method("POST")
+                .uri("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "query": query }).to_string()))
+                .unwrap();
+            let search_response = app.clone().oneshot(search_request).await.unwrap();
+            let body = axum::body::to_bytes(search_response.into_body(), usize::MAX).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let results = response_json["results"].as_array().unwrap();
+            assert_eq!(results[0]["review"]["title"], expected, "{}: {}", query, response_json);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_personalized_by_session_clicks() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_personalized", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Glass kettle", "body": "Looks nice on the counter.", "product_id": "glass_kettle", "rating": 3 },
+            { "title": "Steel kettle", "body": "Boils quickly and quietly.", "product_id": "steel_kettle", "rating": 3 },
+            { "title": "Another kettle", "body": "Sturdy handle, no rust yet.", "product_id": "steel_kettle", "rating": 3 }
+        ]);
+        let (status, _) = send("POST", "/reviews/bulk".to_string(), reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, listed) = send("GET", "/v1/reviews".to_string(), json!(null)).await;
+        let clicked_id = listed["reviews"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|review| review["title"] == "Steel kettle")
+            .unwrap()["id"]
+            .clone();
+
+        let click = json!({ "session_id": "shopper-1", "review_id": clicked_id });
+        let (status, recorded) = send("POST", "/v1/search/clicks".to_string(), click).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(recorded["clicks"], 1);
+
+        // Every review matches equally; the clicked product's reviews move to the top
+        let search = json!({ "query": "kettle", "session_id": "shopper-1" });
+        let (_, personalized) = send("POST", "/v1/search".to_string(), search).await;
+        assert_eq!(personalized["personalized"], true);
+        let products: Vec<&str> = personalized["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["review"]["product_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(products, vec!["steel_kettle", "steel_kettle", "glass_kettle"]);
+
+        // Opting out ranks as if there were no session
+        let search = json!({ "query": "kettle", "session_id": "shopper-1", "personalize": false });
+        let (_, plain) = send("POST", "/v1/search".to_string(), search).await;
+        assert!(plain.get("personalized").is_none());
+        assert_eq!(plain["results"][2]["similarity_score"], 1.0);
+
+        // A personalized GET must not land in a shared cache
+        let request = Request::builder().uri("/v1/search?q=kettle&session_id=shopper-1").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["cache-control"], "private, no-store");
+
+        let (_, cleared) = send("DELETE", "/v1/search/clicks/shopper-1".to_string(), json!(null)).await;
+        assert_eq!(cleared["cleared"], 1);
+
+        let click = json!({ "session_id": "shopper-1", "review_id": "missing" });
+        let (status, _) = send("POST", "/v1/search/clicks".to_string(), click).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let click = json!({ "session_id": " ", "review_id": clicked_id });
+        let (status, error) = send("POST", "/v1/search/clicks".to_string(), click).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_SESSION_ID_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_embedded_reviews_by_vector_similarity() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/search_vector", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Sturdy tent", "body": "Stayed dry through heavy rain all weekend.", "product_id": "tent_1", "rating": 5 },
+            { "title": "Small tent", "body": "Heavy rain got in at the seams.", "product_id": "tent_2", "rating": 2 },
+            { "title": "Kettle", "body": "Boils water quickly, lid feels cheap.", "product_id": "kettle", "rating": 3 }
+        ]);
+        let (status, _) = send("POST", "/reviews/bulk".to_string(), reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The background worker runs only in main(); embed by hand
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 3);
+
+        let search = json!({ "query": "tent heavy rain" });
+        let (_, response) = send("POST", "/v1/search".to_string(), search.clone()).await;
+        assert_eq!(response["search_type"], "vector_similarity");
+        let top = &response["results"][0];
+        assert!(top["review"]["product_id"].as_str().unwrap().starts_with("tent_"));
+        // Nothing in common with the kettle review, so it is not a match
+        assert_eq!(response["total_results"], 2);
+
+        // The score is the cosine similarity of the two embeddings
+        use crate::embedding::Embedder;
+        let query_vector = embedder.embed("tent heavy rain");
+        let review_vector = embedder.embed(&format!("{}\n{}", top["review"]["title"].as_str().unwrap(), top["review"]["body"].as_str().unwrap()));
+        let cosine: f32 = query_vector.iter().zip(&review_vector).map(|(a, b)| a * b).sum();
+        assert!((top["similarity_score"].as_f64().unwrap() as f32 - cosine).abs() < 1e-4);
+
+        // Filters apply to the hydrated neighbours
+        let filtered = json!({ "query": "tent heavy rain", "filters": { "product_id": "tent_2" } });
+        let (_, response) = send("POST", "/v1/search".to_string(), filtered).await;
+        assert_eq!(response["total_results"], 1);
+        assert_eq!(response["results"][0]["review"]["product_id"], "tent_2");
+
+        // A review without a vector yet is still found by keyword
+        let pending = json!({ "title": "Rain fly", "body": "Fits the tent and sheds heavy rain.", "product_id": "fly", "rating": 4 });
+        let (_, created) = send("POST", "/v1/reviews".to_string(), pending).await;
+        assert_eq!(created["embedding_status"], "pending");
+        let (_, response) = send("POST", "/v1/search".to_string(), search.clone()).await;
+        let products: Vec<&str> = response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["review"]["product_id"].as_str().unwrap())
+            .collect();
+        assert!(products.contains(&"fly"), "{:?}", products);
+
+        // Deleted reviews' vectors are skipped
+        let tent_id = top["review"]["id"].as_str().unwrap().to_string();
+        let (status, _) = send("DELETE", format!("/v1/reviews/{}", tent_id), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, response) = send("POST", "/v1/search".to_string(), search).await;
+        assert!(response["results"].as_array().unwrap().iter().all(|hit| hit["review"]["id"] != tent_id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_curation_rules_pin_and_boost_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/curation_rules", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Kettle", "body": "Recalled: the lid pops open.", "product_id": "recalled_kettle", "rating": 3 },
+            { "title": "Kettle", "body": "Boils quickly and quietly.", "product_id": "steel_kettle", "rating": 3 },
+            { "title": "Toaster", "body": "Browns the bread evenly.", "product_id": "toaster", "rating": 3 }
+        ]);
+        let (status, _) = send("POST", "/reviews/bulk".to_string(), reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, listed) = send("GET", "/v1/reviews".to_string(), json!(null)).await;
+        let toaster_id = listed["reviews"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|review| review["product_id"] == "toaster")
+            .unwrap()["id"]
+            .clone();
+
+        let sink = json!({
+            "action": { "type": "boost", "target": { "product_id": "recalled_kettle" }, "weight": -1.0 },
+            "note": "Product recall"
+        });
+        let (status, sink) = send("POST", "/v1/admin/curation/rules".to_string(), sink).await;
+        assert_eq!(status, StatusCode::OK);
+        let pin = json!({
+            "scope": { "query": "Kettle" },
+            "action": { "type": "pin", "review_id": toaster_id, "position": 1 }
+        });
+        let (status, pin) = send("POST", "/v1/admin/curation/rules".to_string(), pin).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, response) = send("POST", "/v1/search".to_string(), json!({ "query": "kettle" })).await;
+        let products: Vec<&str> = response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["review"]["product_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(products, vec!["toaster", "steel_kettle", "recalled_kettle"]);
+        assert_eq!(response["applied_rules"], json!([sink["rule"]["id"], pin["rule"]["id"]]));
+
+        // The organic ranking is one flag away
+        let (_, organic) = send("POST", "/v1/search".to_string(), json!({ "query": "kettle", "curate": false })).await;
+        assert!(organic.get("applied_rules").is_none());
+        assert!(organic["results"].as_array().unwrap().iter().all(|hit| hit["review"]["product_id"] != "toaster"));
+
+        let (_, rules) = send("GET", "/v1/admin/curation/rules".to_string(), json!(null)).await;
+        assert_eq!(rules["rules"].as_array().unwrap().len(), 2);
+        let pin_id = pin["rule"]["id"].as_str().unwrap();
+        let (status, _) = send("DELETE", format!("/v1/admin/curation/rules/{}", pin_id), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, error) = send("DELETE", format!("/v1/admin/curation/rules/{}", pin_id), json!(null)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "E_CURATION_RULE_NOT_FOUND");
+
+        // A pin must be scoped to a query or product
+        let unscoped = json!({ "action": { "type": "pin", "review_id": toaster_id, "position": 1 } });
+        let (status, error) = send("POST", "/v1/admin/curation/rules".to_string(), unscoped).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_CURATION_RULE_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_blocklist_blocks_ingestion_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/blocklist", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Kettle", "body": "Recalled: the lid pops open.", "product_id": "recalled_kettle", "rating": 3 },
+            { "title": "Kettle", "body": "Best kettle ever, buy now!", "product_id": "steel_kettle", "rating": 5, "reviewer_id": "spammer" },
+            { "title": "Kettle", "body": "Boils quickly and quietly.", "product_id": "steel_kettle", "rating": 4 }
+        ]);
+        let (status, _) = send("POST", "/reviews/bulk".to_string(), reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+        // Embed two of them, so both the vector and the keyword candidates are filtered
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 2).unwrap(), 2);
+
+        let recall = json!({ "kind": "product", "value": "recalled_kettle", "reason": "Product recall" });
+        let (status, recall) = send("POST", "/v1/admin/blocklist".to_string(), recall).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(recall["entry"]["scope"], "all");
+        let spam = json!({ "kind": "reviewer", "value": "spammer", "scope": "search" });
+        let (status, _) = send("POST", "/v1/admin/blocklist".to_string(), spam.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, error) = send("POST", "/v1/admin/blocklist".to_string(), spam).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error["code"], "E_CONFLICT");
+
+        let (_, response) = send("POST", "/v1/search".to_string(), json!({ "query": "kettle" })).await;
+        let bodies: Vec<&str> = response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["review"]["body"].as_str().unwrap())
+            .collect();
+        assert_eq!(bodies, vec!["Boils quickly and quietly."]);
+
+        // Ingestion refuses the blocked product but not the search-only reviewer
+        let blocked = json!({ "title": "Kettle", "body": "Still recalled.", "product_id": "recalled_kettle", "rating": 2 });
+        let (status, error) = send("POST", "/v1/reviews".to_string(), blocked.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error["code"], "E_BLOCKLISTED");
+        assert_eq!(error["details"]["field"], "product_id");
+        let (status, error) = send("POST", "/v1/reviews/batch".to_string(), json!([blocked.clone()])).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error["details"]["errors"][0]["index"], 0);
+        let (_, upload) = send("POST", "/reviews/bulk".to_string(), json!([blocked])).await;
+        assert_eq!(upload["result"]["successful"], 0);
+        let spammer = json!({ "title": "Kettle", "body": "Buy it now!", "product_id": "steel_kettle", "rating": 5, "reviewer_id": "spammer" });
+        let (status, _) = send("POST", "/v1/reviews".to_string(), spammer).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Removing an entry lifts it, and both changes are audited
+        let recall_id = recall["entry"]["id"].as_str().unwrap();
+        let (status, _) = send("DELETE", format!("/v1/admin/blocklist/{}", recall_id), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, error) = send("DELETE", format!("/v1/admin/blocklist/{}", recall_id), json!(null)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "E_BLOCKLIST_ENTRY_NOT_FOUND");
+        let (_, response) = send("POST", "/v1/search".to_string(), json!({ "query": "kettle" })).await;
+        assert_eq!(response["total_results"], 2);
+
+        let (_, audit) = send("GET", "/v1/admin/blocklist/audit".to_string(), json!(null)).await;
+        let actions: Vec<&str> = audit["records"].as_array().unwrap().iter().map(|record| record["action"].as_str().unwrap()).collect();
+        assert_eq!(actions, vec!["removed", "added", "added"]);
+        assert_eq!(audit["records"][0]["entry"]["value"], "recalled_kettle");
+        let (_, listed) = send("GET", "/v1/admin/blocklist".to_string(), json!(null)).await;
+        assert_eq!(listed["entries"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_serves_reviews_from_memory() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/sandbox", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+        env::set_var("SANDBOX_MODE", "true");
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Tent", "body": "Stayed dry through a night of heavy rain.", "product_id": "tent", "rating": 5 },
+            { "title": "Kettle", "body": "Boils water quickly and quietly.", "product_id": "kettle", "rating": 4 }
+        ]);
+        let (status, added) = send("POST", "/v1/sandboxes/api-demo/reviews".to_string(), reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(added["sandbox"]["review_count"], 2);
+        let single = json!({ "title": "Tent", "body": "Poles snapped in the wind.", "product_id": "tent", "rating": 1 });
+        let (_, added) = send("POST", "/v1/sandboxes/api-demo/reviews".to_string(), single).await;
+        assert_eq!(added["reviews"][0]["vector_index"], 2);
+
+        let search = json!({ "query": "tent in heavy rain", "filters": { "min_rating": 4 } });
+        let (status, response) = send("POST", "/v1/sandboxes/api-demo/search".to_string(), search.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["search_type"], "vector_similarity");
+        assert_eq!(response["results"][0]["review"]["product_id"], "tent");
+        assert!(response["results"].as_array().unwrap().iter().all(|hit| hit["review"]["rating"] != 1));
+
+        // Nothing reaches the data directory or the shared index
+        assert!(!std::path::Path::new(&data_dir).join("reviews.jsonl").exists());
+        let (_, shared) = send("POST", "/v1/search".to_string(), search.clone()).await;
+        assert_eq!(shared["total_results"], 0);
+
+        let (status, error) = send("POST", "/v1/sandboxes/other-demo/search".to_string(), search.clone()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "E_SANDBOX_NOT_FOUND");
+        let (status, error) = send("POST", "/v1/sandboxes/bad.id/reviews".to_string(), json!([])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_SANDBOX_ID_INVALID");
+
+        let (_, listed) = send("GET", "/v1/sandboxes/api-demo".to_string(), json!(null)).await;
+        assert_eq!(listed["reviews"].as_array().unwrap().len(), 3);
+        let (status, deleted) = send("DELETE", "/v1/sandboxes/api-demo".to_string(), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(deleted["reviews_dropped"], 3);
+        let (status, _) = send("GET", "/v1/sandboxes/api-demo".to_string(), json!(null)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_recording_captures_the_configured_client() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/recording", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+        env::set_var("RECORD_CLIENT", "recording-test");
+
+        let app = create_app();
+        let send = |client_id: Option<&'static str>, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .header("authorization", "Bearer top-secret");
+                if let Some(client_id) = client_id {
+                    request = request.header("x-client-id", client_id);
+                }
+                let response = app.oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let review = json!({ "title": "Recorded review", "body": "Recorded while debugging a client.", "product_id": "rec_001", "rating": 4 });
+        let (status, created) = send(Some("recording-test"), "/v1/reviews", review.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(Some("recording-test"), "/v1/search", json!({ "query": "" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(Some("someone-else"), "/v1/reviews", review.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(None, "/v1/reviews", review).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Only the configured client's requests are recorded, credentials redacted
+        let recording = std::fs::read_to_string(format!("{}/recordings/recording-test.jsonl", data_dir)).unwrap();
+        let exchanges: Vec<serde_json::Value> = recording.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(exchanges.len(), 2);
+        assert_eq!(exchanges[0]["method"], "POST");
+        assert_eq!(exchanges[0]["uri"], "/v1/reviews");
+        assert_eq!(exchanges[0]["request_headers"]["authorization"], "[redacted]");
+        assert_eq!(exchanges[0]["request_body"]["product_id"], "rec_001");
+        assert_eq!(exchanges[0]["status"], 200);
+        assert_eq!(exchanges[0]["response_body"], created);
+        assert_eq!(exchanges[1]["status"], 400);
+        assert!(!std::path::Path::new(&format!("{}/recordings/someone-else.jsonl", data_dir)).exists());
+
+        env::remove_var("RECORD_CLIENT");
+    }
+
+    #[tokio::test]
+    async fn test_data_subject_export_and_erasure() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/data_subject", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .header("idempotency-key", format!("key-{}", uuid::Uuid::new_v4()))
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let mut review_ids = Vec::new();
+        for (title, reviewer_id) in [("Loud fan", "subject_1"), ("Quiet fan", "subject_1"), ("Fine fan", "bystander")] {
+            let review = json!({ "title": title, "body": "The fan runs all night long.", "product_id": "fan_001", "rating": 4, "reviewer_id": reviewer_id });
+            let (status, created) = send("POST", "/v1/reviews".to_string(), review).await;
+            assert_eq!(status, StatusCode::OK);
+            review_ids.push(created["review_id"].as_str().unwrap().to_string());
+        }
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        let embedder = crate::embedding::default_embedder();
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 3).unwrap(), 3);
+        let boost = json!({ "action": { "type": "boost", "target": { "reviewer_id": "subject_1" }, "weight": 0.5 } });
+        let (status, _) = send("POST", "/v1/admin/curation/rules".to_string(), boost).await;
+        assert_eq!(status, StatusCode::OK);
+        let block = json!({ "kind": "reviewer", "value": "subject_1", "scope": "search" });
+        let (status, _) = send("POST", "/v1/admin/blocklist".to_string(), block).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, exported) = send("GET", "/v1/admin/data-subjects/subject_1".to_string(), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        let export = &exported["export"];
+        assert_eq!(export["reviews"].as_array().unwrap().len(), 2);
+        assert_eq!(export["curation_rules"].as_array().unwrap().len(), 1);
+        assert_eq!(export["blocklist_entries"].as_array().unwrap().len(), 1);
+        assert_eq!(export["blocklist_audit"].as_array().unwrap().len(), 1);
+        // The creates were sent with idempotency keys, so their requests are remembered
+        assert_eq!(export["idempotency_records"].as_array().unwrap().len(), 2);
+
+        let (status, erased) = send("DELETE", "/v1/admin/data-subjects/subject_1".to_string(), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        let receipt = &erased["receipt"];
+        assert_eq!((receipt["reviews"].clone(), receipt["vectors"].clone()), (json!(2), json!(2)));
+        assert_eq!(receipt["curation_rules"], 1);
+        assert_eq!(receipt["blocklist_entries"], 1);
+        assert_eq!(receipt["idempotency_records"], 2);
+        assert_eq!(receipt["subject_sha256"], crate::data_subject::subject_hash("subject_1"));
+
+        // Nothing names the reviewer any more; their neighbour is untouched
+        let (_, exported) = send("GET", "/v1/admin/data-subjects/subject_1".to_string(), json!(null)).await;
+        for list in ["reviews", "curation_rules", "blocklist_entries", "blocklist_audit", "idempotency_records"] {
+            assert!(exported["export"][list].as_array().unwrap().is_empty(), "{} not erased", list);
+        }
+        let stored = std::fs::read_to_string(&data_paths.reviews_jsonl).unwrap();
+        assert!(!stored.contains("subject_1") && !stored.contains("Loud fan"));
+        let audit = std::fs::read_to_string(&data_paths.blocklist_audit_jsonl).unwrap();
+        assert!(!audit.contains("subject_1") && audit.contains("[erased]"));
+        let vectors = crate::vectors::VectorStore::new(&data_paths.reviews_index, crate::embedding::EMBEDDING_DIM);
+        assert!(vectors.read(0).unwrap().unwrap().iter().all(|value| *value == 0.0));
+        assert!(vectors.read(2).unwrap().unwrap().iter().any(|value| *value != 0.0));
+        let (status, _) = send("GET", format!("/v1/reviews/{}", review_ids[0]), json!(null)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send("GET", format!("/v1/reviews/{}", review_ids[2]), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, receipts) = send("GET", "/v1/admin/erasures".to_string(), json!(null)).await;
+        assert_eq!(receipts["receipts"].as_array().unwrap().len(), 1);
+        assert_eq!(receipts["receipts"][0]["id"], receipt["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_with_offset_and_cursor() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/search_pages", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let ids = |response: &serde_json::Value| -> Vec<String> {
+            response["results"].as_array().unwrap().iter().map(|hit| hit["review"]["id"].as_str().unwrap().to_string()).collect()
+        };
+
+        let reviews_to_add: Vec<serde_json::Value> = (0..5)
+            .map(|i| json!({ "title": "Battery", "body": format!("Battery issues, report number {}.", i), "product_id": "phone_001", "rating": 3 }))
+            .collect();
+        let (status, _) = send("POST", "/reviews/bulk".to_string(), json!(reviews_to_add)).await;
+        assert_eq!(status, StatusCode::OK);
+        // Embed some, so pages span vector and keyword matches
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 3).unwrap(), 3);
+
+        let (_, everything) = send("POST", "/v1/search".to_string(), json!({ "query": "battery issues", "limit": 5 })).await;
+        assert_eq!(everything["total_results"], 5);
+        assert!(everything.get("next_cursor").is_none());
+
+        // Cursor pages walk the same ranking without gaps or repeats
+        let (_, first) = send("POST", "/v1/search".to_string(), json!({ "query": "battery issues", "limit": 2 })).await;
+        assert_eq!(first["offset"], 0);
+        let cursor = first["next_cursor"].as_str().unwrap().to_string();
+        let (_, second) = send("POST", "/v1/search".to_string(), json!({ "query": "battery issues", "limit": 2, "cursor": cursor })).await;
+        assert_eq!(second["offset"], 2);
+        let cursor = second["next_cursor"].as_str().unwrap().to_string();
+        let (_, third) = send("GET", format!("/v1/search?q=battery%20issues&limit=2&cursor={}", cursor), json!(null)).await;
+        assert_eq!(third["total_results"], 1);
+        assert!(third.get("next_cursor").is_none());
+        assert_eq!([ids(&first), ids(&second), ids(&third)].concat(), ids(&everything));
+
+        let (_, skipped) = send("GET", "/v1/search?q=battery%20issues&limit=2&offset=3".to_string(), json!(null)).await;
+        assert_eq!(ids(&skipped), ids(&everything)[3..]);
+
+        // A cursor only continues the search it came from
+        let search = json!({ "query": "camera", "cursor": first["next_cursor"] });
+        let (status, error) = send("POST", "/v1/search".to_string(), search).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_PAGINATION_INVALID");
+        let (status, _) = send("POST", "/v1/search".to_string(), json!({ "query": "battery", "offset": 995, "limit": 10 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_similar_reviews() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/similar_reviews", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let titles = |response: &serde_json::Value| -> Vec<String> {
+            response["results"].as_array().unwrap().iter().map(|hit| hit["review"]["title"].as_str().unwrap().to_string()).collect()
+        };
+
+        let mut ids = Vec::new();
+        for (title, body, product_id) in [
+            ("Battery drains", "The battery drains overnight even when idle.", "phone_001"),
+            ("Screen", "Lovely bright screen with vivid colours.", "phone_001"),
+            ("Battery woes", "The battery drains overnight, even when idle, every day.", "phone_002"),
+            ("Drains too", "Battery drains overnight when idle.", "phone_001"),
+        ] {
+            let review = json!({ "title": title, "body": body, "product_id": product_id, "rating": 3 });
+            let (status, created) = send("/v1/reviews".to_string(), review).await;
+            assert_eq!(status, StatusCode::OK);
+            ids.push(created["review_id"].as_str().unwrap().to_string());
+        }
+        // The first three are embedded; the last is still pending
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 3).unwrap(), 3);
+
+        let (status, similar) = send(format!("/v1/reviews/{}/similar", ids[0]), json!({ "limit": 2 })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(similar["embedding_status"], "embedded");
+        assert_eq!(similar["total_results"], 2);
+        // Never the review itself; pending reviews are candidates too
+        let mut found = titles(&similar);
+        found.sort();
+        assert_eq!(found, ["Battery woes", "Drains too"]);
+
+        // A pending review is compared by its text, and filters apply
+        let filters = json!({ "filters": { "product_id": "phone_001" } });
+        let (status, similar) = send(format!("/v1/reviews/{}/similar", ids[3]), filters).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(similar["embedding_status"], "pending");
+        assert_eq!(titles(&similar)[0], "Battery drains");
+        assert!(!titles(&similar).contains(&"Battery woes".to_string()));
+
+        let (status, _) = send(format!("/v1/reviews/{}/similar", ids[0]), json!({ "limit": 0 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, error) = send("/v1/reviews/no-such-review/similar".to_string(), json!({})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "E_REVIEW_NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_search_ranking_weights() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/ranking_weights", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let titles = |response: &serde_json::Value| -> Vec<String> {
+            response["results"].as_array().unwrap().iter().map(|hit| hit["review"]["title"].as_str().unwrap().to_string()).collect()
+        };
+
+        // Not embedded, so both are ranked by keyword
+        let reviews_to_add = json!([
+            { "title": "Kettle", "body": "Works fine and looks good.", "product_id": "kettle_001", "rating": 3 },
+            { "title": "Solid build", "body": "The handle stays cool to the touch.", "product_id": "kettle_001", "rating": 3 }
+        ]);
+        let (status, _) = send("POST", "/v1/reviews/bulk", reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, by_default) = send("POST", "/v1/search", json!({ "query": "kettle handle" })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(titles(&by_default), ["Kettle", "Solid build"]);
+
+        // Out-of-range weights are clamped rather than refused
+        let ranking = json!({ "title_weight": -3, "body_weight": 0.7 });
+        let (status, by_body) = send("POST", "/v1/search", json!({ "query": "kettle handle", "ranking": ranking })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(titles(&by_body), ["Solid build", "Kettle"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_schema_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/schema_versions", temp_path));
+
+        let app = create_app();
+        let send = |uri: &'static str, accept: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .header("accept", accept)
+                    .body(Body::from(json!({ "query": "kettle", "limit": 3 }).to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, headers, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let (status, headers, v1) = send("/v1/search", "application/json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers["api-version"], "1");
+        assert_eq!(v1["success"], true);
+        assert_eq!(v1["limit"], 3);
+
+        let (status, headers, v2) = send("/v2/search", "application/json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers["api-version"], "2");
+        assert!(v2.get("success").is_none());
+        assert!(v2.get("limit").is_none());
+        assert_eq!(v2["page"], json!({ "offset": 0, "limit": 3, "count": 0 }));
+
+        // The header asks for v2 on a v1 path, and names it in the content type
+        let (_, headers, negotiated) = send("/v1/search", "application/vnd.semantic-search.v2+json").await;
+        assert_eq!(headers["api-version"], "2");
+        assert_eq!(headers["content-type"], "application/vnd.semantic-search.v2+json");
+        assert_eq!(negotiated, v2);
+
+        let (status, _, error) = send("/v1/search", "application/vnd.semantic-search.v9+json").await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(error["code"], "E_VALIDATION_API_VERSION_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_search_reviews_get_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/search_get", temp_path));
+
+        let app = create_app();
+
+        let review = json!({
+            "title": "Amazing smartphone",
+            "body": "This phone has excellent camera quality and fast performance.",
+            "product_id": "phone_001",
+            "rating": 5
+        });
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/json")
+            .body(Body::from(review.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(create_request).await.unwrap().status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/search?q=camera%20quality&limit=5")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["query"], "camera quality");
+        assert_eq!(response_json["limit"], 5);
+        assert_eq!(response_json["total_results"], 1);
+
+        // Filters narrow the candidates before ranking; a search finding nothing would be relaxed
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/search?q=camera&filters=product_id:phone_001,max_rating:4&fallback=false")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["cache-control"], "public, max-age=30");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response_json["total_results"], 0);
+        assert_eq!(response_json["filters"]["max_rating"], 4);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/search?q=camera&filters=colour:red")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // A missing query gets the same validation error as the POST form
+        let request = Request::builder()
+            .method("GET")
+            .uri("/v1/search")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_by_product_and_date() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/search_filters", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews_to_add = json!([
+            { "title": "Battery", "body": "Battery issues after a week.", "product_id": "phone_001", "rating": 2 },
+            { "title": "Battery", "body": "Battery issues from day one.", "product_id": "phone_002", "rating": 1 },
+            { "title": "Battery", "body": "No battery issues at all.", "product_id": "phone_001", "rating": 5 }
+        ]);
+        let (status, _) = send("/reviews/bulk", reviews_to_add).await;
+        assert_eq!(status, StatusCode::OK);
+        // Embed two of them, so both the vector and the keyword candidates are filtered
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 2).unwrap(), 2);
+
+        let search = json!({ "query": "battery issues", "filters": { "product_id": "phone_001", "max_rating": 3 } });
+        let (status, found) = send("/v1/search", search).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(found["total_results"], 1);
+        assert_eq!(found["results"][0]["review"]["product_id"], "phone_001");
+        assert_eq!(found["results"][0]["review"]["rating"], 2);
+
+        let today = chrono::Utc::now().date_naive();
+        let search = json!({ "query": "battery issues", "filters": { "product_id": "phone_001", "date_from": format!("{}T00:00:00Z", today) } });
+        let (_, found) = send("/v1/search", search).await;
+        assert_eq!(found["total_results"], 2);
+        let search =
+            json!({ "query": "battery issues", "filters": { "date_to": "2020-01-01T00:00:00Z" }, "fallback": false });
+        let (_, found) = send("/v1/search", search).await;
+        assert_eq!(found["total_results"], 0);
+
+        let search = json!({ "query": "battery issues", "filters": { "date_from": "2024-02-01T00:00:00Z", "date_to": "2024-01-01T00:00:00Z" } });
+        let (status, error) = send("/v1/search", search).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FILTERS_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/health", temp_path));
+
+        let app = create_app();
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["status"], "healthy");
         assert_eq!(response_json["service"], "semantic-search-backend");
+        assert_eq!(response_json["ready"], true);
+        assert_eq!(response_json["checks"]["storage"], "ok");
+        assert_eq!(response_json["checks"]["vector_index"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_liveness_and_readiness_probes() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let data_dir = format!("{}/probes", temp_path);
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let probe = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let (status, ready) = probe("/health/ready").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ready["ready"], true);
+        for check in ["storage", "embedding", "vector_index", "canaries"] {
+            assert_eq!(ready["checks"][check], "ok", "{}", ready);
+        }
+
+        // A torn vector index takes the server out of service but leaves it alive
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        std::fs::write(&data_paths.reviews_index, [0u8; 3]).unwrap();
+        let (status, ready) = probe("/v1/health/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(ready["checks"]["storage"], "ok");
+        assert_ne!(ready["checks"]["vector_index"], "ok");
+
+        let (status, live) = probe("/health/live").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(live["status"], "alive");
+    }
+
+    #[tokio::test]
+    async fn test_json_body_rejections_are_error_responses() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/json_rejections", temp_path));
+
+        let app = create_app();
+
+        let cases = [
+            (Some("application/json"), "{\"title\": ", StatusCode::BAD_REQUEST, "E_MALFORMED_JSON"),
+            (None, "{}", StatusCode::UNSUPPORTED_MEDIA_TYPE, "E_UNSUPPORTED_MEDIA_TYPE"),
+            (
+                Some("application/json"),
+                "{\"body\": \"Missing its title entirely\", \"product_id\": \"prod_123\", \"rating\": 4}",
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "E_VALIDATION_TITLE_MISSING",
+            ),
+        ];
+
+        for (content_type, body, expected_status, expected_code) in cases {
+            let mut request = Request::builder().method("POST").uri("/v1/reviews");
+            if let Some(content_type) = content_type {
+                request = request.header("content-type", content_type);
+            }
+            let response = app.clone().oneshot(request.body(Body::from(body)).unwrap()).await.unwrap();
+            assert_eq!(response.status(), expected_status);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(response_json["code"], expected_code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_review_idempotency_key_replays() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/idempotency", temp_path));
+
+        let app = create_app();
+
+        let review = |title: &str| {
+            json!({
+                "title": title,
+                "body": "Retried after the connection dropped mid-request.",
+                "product_id": "prod_123",
+                "rating": 4
+            })
+        };
+        let request = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/reviews")
+                .header("content-type", "application/json")
+                .header("idempotency-key", "retry-abc")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let first = app.clone().oneshot(request(review("Flaky network"))).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert!(first.headers().get("idempotent-replayed").is_none());
+        let first = read_json(first).await;
+
+        let replay = app.clone().oneshot(request(review("Flaky network"))).await.unwrap();
+        assert_eq!(replay.status(), StatusCode::OK);
+        assert_eq!(replay.headers()["idempotent-replayed"], "true");
+        assert_eq!(read_json(replay).await, first);
+
+        let reused = app.clone().oneshot(request(review("A different review"))).await.unwrap();
+        assert_eq!(reused.status(), StatusCode::CONFLICT);
+        assert_eq!(read_json(reused).await["code"], "E_CONFLICT");
+
+        // Only the first request stored a review
+        let list = Request::builder().method("GET").uri("/v1/reviews").body(Body::empty()).unwrap();
+        let listing = read_json(app.oneshot(list).await.unwrap()).await;
+        assert_eq!(listing["total_reviews"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_content_negotiation() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/msgpack", temp_path));
+
+        let app = create_app();
+
+        let review = json!({
+            "title": "Packs small",
+            "body": "Folds down to the size of a paperback.",
+            "product_id": "tent",
+            "rating": 4
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews")
+            .header("content-type", "application/msgpack")
+            .header("accept", "application/msgpack")
+            .body(Body::from(rmp_serde::to_vec_named(&review).unwrap()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/msgpack");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(created["success"], true);
+        assert_eq!(created["vector_index"], 0);
+
+        // A MessagePack body with a JSON answer, as without an Accept header
+        let search = json!({ "query": "paperback" });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/search")
+            .header("content-type", "application/msgpack")
+            .body(Body::from(rmp_serde::to_vec_named(&search).unwrap()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let results: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results["results"][0]["review"]["title"], "Packs small");
+
+        // Undecodable bodies get an error in the format the client asked for
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/reviews/bulk")
+            .header("content-type", "application/msgpack")
+            .header("accept", "application/msgpack")
+            .body(Body::from(vec![0xc1]))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "E_MALFORMED_MSGPACK");
+    }
+
+    #[tokio::test]
+    async fn test_webhook_registration_endpoints() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/webhooks", temp_path));
+
+        let app = create_app();
+
+        let register = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/webhooks")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(register(json!({ "url": "https://example.com/hooks", "events": ["review.deleted"] })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let registered: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let webhook_id = registered["webhook"]["id"].as_str().unwrap().to_string();
+        assert_eq!(registered["webhook"]["events"], json!(["review.deleted"]));
+        assert!(registered["webhook"]["secret"].as_str().unwrap().starts_with("whsec_"));
+
+        let response = app.clone().oneshot(register(json!({ "url": "https://example.com", "events": ["review.voted"] }))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "E_VALIDATION_WEBHOOK_INVALID");
+
+        // Listings leave the secret out
+        let request = Request::builder().uri("/v1/webhooks").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listed["webhooks"].as_array().unwrap().len(), 1);
+        assert_eq!(listed["webhooks"][0]["id"], webhook_id.as_str());
+        assert!(listed["webhooks"][0].get("secret").is_none());
+
+        let delete = |id: &str| Request::builder().method("DELETE").uri(format!("/v1/webhooks/{}", id)).body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(delete(&webhook_id)).await.unwrap().status(), StatusCode::OK);
+        let response = app.oneshot(delete(&webhook_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "E_WEBHOOK_NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_graphql_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/graphql", temp_path));
+
+        let app = create_app();
+
+        for (title, product_id, rating) in [("Loud but fast", "kettle", 3), ("Fast and quiet", "kettle", 5), ("Sharp", "knife", 4)] {
+            let review = json!({
+                "title": title,
+                "body": "Used it every day for a month.",
+                "product_id": product_id,
+                "rating": rating
+            });
+            let request = Request::builder()
+                .method("POST")
+                .uri("/v1/reviews")
+                .header("content-type", "application/json")
+                .body(Body::from(review.to_string()))
+                .unwrap();
+            assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+        }
+
+        let graphql = |query: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/graphql")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "query": query }).to_string()))
+                .unwrap()
+        };
+
+        // A listing, product aggregates and a search in one request, with only the fields asked for
+        let query = r#"{
+            reviews(pageSize: 2) { totalCount items { title } }
+            product(id: "kettle") {
+                reviewCount
+                averageRating
+                ratingHistogram { rating count }
+                reviews(sort: "rating", pageSize: 1) { items { title rating } }
+            }
+            missing: product(id: "toaster") { reviewCount }
+            search(query: "fast", productId: "kettle") { totalResults results { score review { title } } }
+        }"#;
+        let response = app.clone().oneshot(graphql(query)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(result.get("errors").is_none(), "{}", result);
+        let data = &result["data"];
+        assert_eq!(data["reviews"]["totalCount"], 3);
+        assert_eq!(data["reviews"]["items"], json!([{ "title": "Loud but fast" }, { "title": "Fast and quiet" }]));
+        assert_eq!(data["product"]["reviewCount"], 2);
+        assert_eq!(data["product"]["averageRating"], 4.0);
+        assert_eq!(data["product"]["ratingHistogram"][4], json!({ "rating": 5, "count": 1 }));
+        assert_eq!(data["product"]["reviews"]["items"], json!([{ "title": "Fast and quiet", "rating": 5 }]));
+        assert!(data["missing"].is_null());
+        assert_eq!(data["search"]["totalResults"], 2);
+        assert!(data["search"]["results"][0]["score"].as_f64().unwrap() > 0.0);
+        assert!(data["search"]["results"][0]["review"].get("body").is_none());
+
+        // Invalid arguments keep the REST error code
+        let response = app.clone().oneshot(graphql("{ reviews(pageSize: 1000) { totalCount } }")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(result["errors"][0]["extensions"]["code"], "E_VALIDATION_PAGINATION_INVALID");
+
+        // Queries nested too deeply are refused before they run
+        let nested = "{ reviews { items { product { reviews { items { product { reviews { items { product { id } } } } } } } } } }";
+        let response = app.oneshot(graphql(nested)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(result["data"].is_null());
+        assert!(!result["errors"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_vote_review_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/vote_review", temp_path));
+
+        let app = create_app();
+
+        let post = |uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let review = json!({
+            "title": "Battery lasts all week",
+            "body": "Charged it on Monday and it was still going on Sunday.",
+            "product_id": "prod_123",
+            "rating": 5
+        });
+        let created = read_json(app.clone().oneshot(post("/v1/reviews".to_string(), review)).await.unwrap()).await;
+        let review_id = created["review_id"].as_str().unwrap().to_string();
+
+        for helpful in [true, true, false] {
+            let uri = format!("/v1/reviews/{}/vote", review_id);
+            let response = app.clone().oneshot(post(uri, json!({ "helpful": helpful }))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/v1/reviews/{}", review_id))
+            .body(Body::empty())
+            .unwrap();
+        let fetched = read_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(fetched["review"]["helpful_votes"], 2);
+        assert_eq!(fetched["review"]["unhelpful_votes"], 1);
+
+        let response = app
+            .oneshot(post("/v1/reviews/missing/vote".to_string(), json!({ "helpful": true })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_upload_provenance_and_export_by_license() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/provenance", temp_path));
+
+        let app = create_app();
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        // Rows without their own provenance take the upload's
+        let reviews = json!([
+            {"title": "Imported kettle", "body": "Boils quickly and quietly.", "product_id": "kettle", "rating": 5},
+            {"title": "Imported toaster", "body": "Toasts both sides evenly.", "product_id": "toaster", "rating": 4,
+             "license": "CC0-1.0"}
+        ]);
+        let (status, _) = send(
+            "POST",
+            "/v1/reviews/bulk?source=Example%20Reviews&license=CC-BY-4.0&attribution=Example%20Reviews%2C%20CC%20BY%204.0",
+            Some(reviews),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // An attribution license with nothing to attribute is refused per row
+        let (status, body) = send(
+            "POST",
+            "/v1/reviews/bulk?license=ODbL-1.0",
+            Some(json!([{"title": "Unattributed", "body": "Nobody to credit for this.", "product_id": "kettle", "rating": 3}])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let uploaded: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(uploaded["result"]["successful"], 0);
+        assert!(uploaded["result"]["failed"][0]["error"].as_str().unwrap().contains("attribution"));
+
+        let (status, body) = send("POST", "/v1/reviews", Some(json!({
+            "title": "Own review", "body": "Written here, not imported.", "product_id": "kettle", "rating": 4,
+            "license": "CC-BY-SA-4.0"
+        })))
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("E_VALIDATION_ATTRIBUTION_MISSING"));
+
+        let (status, body) = send("GET", "/v1/reviews/export?license=cc-by-4.0", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let exported: Vec<serde_json::Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0]["title"], "Imported kettle");
+        assert_eq!(exported[0]["source"], "Example Reviews");
+        assert_eq!(exported[0]["attribution"], "Example Reviews, CC BY 4.0");
+
+        let (_, body) = send("GET", "/v1/reviews/export?format=csv&license=CC0-1.0,none", None).await;
+        assert!(body.lines().next().unwrap().ends_with("source,license,attribution"));
+        assert_eq!(body.lines().count(), 2);
+        assert!(body.contains("Imported toaster"));
+
+        let (status, body) = send("GET", "/v1/reviews/export?license=CC%20BY", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("E_VALIDATION_LICENSE_INVALID"));
+    }
+
+    #[tokio::test]
+    async fn test_search_suggestions() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/suggest", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let suggest = |prefix: &str| {
+            let uri = format!("/v1/search/suggest?prefix={}", prefix);
+            async move {
+                let (status, response) = send("GET", &uri, serde_json::Value::Null).await;
+                assert_eq!(status, StatusCode::OK);
+                response["suggestions"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|suggestion| suggestion["text"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Great battery life", "body": "Lasts two days on a charge.", "product_id": "phone_001", "rating": 5 },
+            { "title": "Great battery", "body": "Charges fast and lasts long.", "product_id": "phone_002", "rating": 4 },
+            { "title": "Great bargain", "body": "Cheap and works fine so far.", "product_id": "recalled_phone", "rating": 4 }
+        ]);
+        let (status, _) = send("POST", "/v1/reviews/bulk", reviews).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(suggest("great%20ba").await, ["great battery", "great bargain", "great battery life"]);
+        // A trailing space asks for the next word
+        assert_eq!(suggest("Great%20").await, ["great battery", "great bargain", "great battery life"]);
+        assert_eq!(suggest("batt").await, ["battery", "battery life"]);
+
+        // Reviews added later are indexed incrementally; blocked ones drop out
+        let review = json!({ "title": "Great bass", "body": "Punchy speaker for the price.", "product_id": "speaker", "rating": 5 });
+        assert_eq!(send("POST", "/v1/reviews", review).await.0, StatusCode::OK);
+        let recall = json!({ "kind": "product", "value": "recalled_phone", "scope": "search" });
+        assert_eq!(send("POST", "/v1/admin/blocklist", recall).await.0, StatusCode::OK);
+        assert_eq!(suggest("great%20bas").await, ["great bass"]);
+        assert!(!suggest("great%20ba").await.contains(&"great bargain".to_string()));
+
+        // A query searched twice with results becomes a suggestion
+        for _ in 0..2 {
+            let (status, _) = send("POST", "/v1/search", json!({ "query": "long lasting battery" })).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (_, response) = send("GET", "/v1/search/suggest?prefix=long&limit=1", serde_json::Value::Null).await;
+        assert_eq!(response["suggestions"][0]["text"], "long lasting battery");
+        assert_eq!(response["suggestions"][0]["searches"], 2);
+
+        let (status, error) = send("GET", "/v1/search/suggest?prefix=%20", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_QUERY_MISSING");
+        let (status, _) = send("GET", "/v1/search/suggest?prefix=great&limit=0", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_corrects_typos() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/typos", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Great battery", "body": "The battery life is excellent.", "product_id": "phone_001", "rating": 5 },
+            { "title": "Sharp screen", "body": "Bright display, average battery.", "product_id": "phone_002", "rating": 4 },
+            { "title": "Comfortable headphones", "body": "Clear sound all day.", "product_id": "audio_001", "rating": 5 }
+        ]);
+        let (status, _) = send("POST", "/v1/reviews/bulk", reviews).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, response) = send("POST", "/v1/search", json!({ "query": "batery lif" })).await;
+        assert_eq!(status, StatusCode::OK);
+        // "lif" is too short to guess at
+        assert_eq!(response["query"], "batery lif");
+        assert_eq!(response["corrected_query"], "battery lif");
+        assert_eq!(response["results"][0]["review"]["product_id"], "phone_001");
+
+        let (_, response) = send("GET", "/v1/search?q=comfortible%20headfones", serde_json::Value::Null).await;
+        assert_eq!(response["corrected_query"], "comfortable headphones");
+        assert_eq!(response["results"][0]["review"]["product_id"], "audio_001");
+
+        // Correctly spelled queries and opted-out ones are searched as typed
+        let (_, response) = send("POST", "/v1/search", json!({ "query": "battery life" })).await;
+        assert!(response.get("corrected_query").is_none());
+        let (_, response) = send("POST", "/v1/search", json!({ "query": "batery", "correct_typos": false })).await;
+        assert!(response.get("corrected_query").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_live_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/live", temp_path));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Kettle", "body": "Boils fast.", "product_id": "kettle_001", "rating": 5 },
+            { "title": "Toaster", "body": "Even browning.", "product_id": "toaster_001", "rating": 4 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+        assert_eq!(send("POST", "/v1/search", json!({ "query": "kettle" })).await.0, StatusCode::OK);
+        let (status, error) = send("POST", "/v1/search", json!({ "query": "" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Counters are process-wide, so other tests may have added to them
+        let (status, metrics) = send("GET", "/v1/stats/live", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(metrics["reviews_stored"].as_u64().unwrap() >= 2);
+        assert!(metrics["reviews_per_sec"].as_f64().unwrap() > 0.0);
+        assert!(metrics["searches"].as_u64().unwrap() >= 1);
+        assert!(metrics["queues"]["embedding_interactive"].is_u64());
+        let recorded = metrics["recent_errors"].as_array().unwrap().iter().any(|recent| {
+            recent["path"] == "/v1/search" && recent["status"] == 400 && recent["code"] == error["code"]
+        });
+        assert!(recorded, "{}", metrics);
+
+        // The stream sends a sample as soon as it opens
+        let request = Request::builder().uri("/v1/stats/live/stream").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut frames = response.into_body().into_data_stream();
+        let first = futures_util::StreamExt::next(&mut frames).await.unwrap().unwrap();
+        let first = String::from_utf8_lossy(&first);
+        assert!(first.starts_with("event: metrics") && first.contains("\"reviews_stored\""), "{}", first);
+    }
+
+    #[tokio::test]
+    async fn test_search_synonyms_and_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("synonyms");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Great smartphone", "body": "Fast and light.", "product_id": "phone_001", "rating": 3 },
+            { "title": "Quiet kettle", "body": "Boils in a minute.", "product_id": "kettle_001", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+        let search = json!({ "query": "mobile", "correct_typos": false });
+        let (_, response) = send("POST", "/v1/search", search.clone()).await;
+        assert_eq!(response["total_results"], 0);
+
+        // Edits take effect on reload
+        std::fs::write(data_dir.join("synonyms.txt"), "# Devices\nphone = smartphone = mobile\n").unwrap();
+        let (status, reloaded) = send("POST", "/v1/admin/synonyms/reload", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reloaded["synonyms"]["groups"], 1);
+        assert_eq!(reloaded["synonyms"]["terms"], 3);
+        let (_, response) = send("POST", "/v1/search", search.clone()).await;
+        assert_eq!(response["total_results"], 1);
+        assert_eq!(response["results"][0]["review"]["product_id"], "phone_001");
+
+        // A broken file is refused and the loaded groups stay in use
+        std::fs::write(data_dir.join("synonyms.txt"), "phone = smartphone\nlaptop\n").unwrap();
+        let (status, error) = send("POST", "/v1/admin/synonyms/reload", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_SYNONYMS_INVALID");
+        let (_, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(response["total_results"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_str().unwrap();
+        env::set_var("DATA_DIR", format!("{}/request_ids", temp_path));
+
+        let app = create_app();
+        let request_id = |header: Option<&str>| {
+            let mut request = Request::builder().uri("/v1/health").header("x-client-id", "acme");
+            if let Some(value) = header {
+                request = request.header("x-request-id", value);
+            }
+            let request = request.body(Body::empty()).unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                response.headers()["x-request-id"].to_str().unwrap().to_string()
+            }
+        };
+
+        // A client's id is kept, so its logs and ours line up
+        assert_eq!(request_id(Some("checkout-42")).await, "checkout-42");
+
+        // Otherwise, or when it is unusable, each request gets a fresh one
+        let generated = request_id(None).await;
+        assert!(uuid::Uuid::parse_str(&generated).is_ok(), "{}", generated);
+        assert_ne!(request_id(None).await, generated);
+        let replaced = request_id(Some(&"x".repeat(200))).await;
+        assert!(uuid::Uuid::parse_str(&replaced).is_ok(), "{}", replaced);
+    }
+
+    #[tokio::test]
+    async fn test_effective_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("config");
+        env::set_var("DATA_DIR", &data_dir);
+        // Unparseable, so every test still runs with the default timeout
+        env::set_var("LOCK_TIMEOUT_MS", "soon");
+
+        let app = create_app();
+        let request = Request::builder().uri("/v1/admin/config").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        env::remove_var("LOCK_TIMEOUT_MS");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let config: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let setting = |name: &str| {
+            config["settings"].as_array().unwrap().iter().find(|setting| setting["name"] == name).unwrap().clone()
+        };
+        assert_eq!(setting("DATA_DIR")["source"], "environment");
+        let lock_timeout = setting("LOCK_TIMEOUT_MS");
+        assert_eq!(lock_timeout["source"], "invalid");
+        assert_eq!(lock_timeout["value"], crate::lock::DEFAULT_LOCK_TIMEOUT_MS.to_string());
+        assert_eq!(lock_timeout["ignored_value"], "soon");
+
+        let reviews = config["data_paths"].as_array().unwrap().iter().find(|path| path["name"] == "reviews").unwrap();
+        assert_eq!(reviews["path"], data_dir.join("reviews.jsonl").display().to_string());
+        assert_eq!(reviews["exists"], false);
+        assert_eq!(config["index"]["dimension"], crate::embedding::EMBEDDING_DIM);
+        assert_eq!(config["index"]["generation"], 0);
+        let grpc = config["features"].as_array().unwrap().iter().find(|feature| feature["name"] == "grpc").unwrap();
+        assert_eq!(grpc["enabled"], cfg!(feature = "grpc"));
+    }
+
+    #[tokio::test]
+    async fn test_lock_status_and_break() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("lock");
+        env::set_var("DATA_DIR", &data_dir);
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str| {
+            let app = app.clone();
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let (status, lock) = send("GET", "/v1/admin/lock").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(lock["state"], "free");
+        assert_eq!(lock["stale"], false);
+
+        // Held by this server: never broken, even with force
+        let held = crate::lock::FileLock::acquire(data_dir.join(".lock"), "bulk_upload").unwrap();
+        let (_, lock) = send("GET", "/v1/admin/lock").await;
+        assert_eq!(lock["state"], "exclusive");
+        assert_eq!(lock["holder"]["operation"], "bulk_upload");
+        assert_eq!(lock["holder_alive"], true);
+        let (status, error) = send("POST", "/v1/admin/lock/break?force=true").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error["code"], "E_CONFLICT");
+        drop(held);
+
+        let (status, outcome) = send("POST", "/v1/admin/lock/break").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(outcome["broken"], false);
+        assert_eq!(outcome["previous"]["state"], "free");
+    }
+
+    #[tokio::test]
+    async fn test_search_group_by_product() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("group_by_product"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Kettle boils fast", "body": "The kettle is quick.", "product_id": "kettle_001", "rating": 3 },
+            { "title": "Noisy kettle", "body": "This kettle whistles.", "product_id": "kettle_001", "rating": 3 },
+            { "title": "Kettle lid broke", "body": "Weak hinge.", "product_id": "kettle_002", "rating": 3 },
+            { "title": "Good toaster", "body": "Even browning.", "product_id": "toaster_001", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        let (_, ungrouped) = send("POST", "/v1/search", json!({ "query": "kettle", "limit": 1 })).await;
+        assert_eq!(ungrouped["results"][0].get("product_hits"), None);
+        let (status, grouped) = send("POST", "/v1/search", json!({ "query": "kettle", "group_by_product": true })).await;
+        assert_eq!(status, StatusCode::OK);
+        let hits: std::collections::HashMap<&str, u64> = grouped["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| (hit["review"]["product_id"].as_str().unwrap(), hit["product_hits"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(grouped["total_results"], 2);
+        assert_eq!(hits, [("kettle_001", 2), ("kettle_002", 1)].into_iter().collect());
+
+        // Pages count products, and a cursor only continues the grouped search
+        let (_, first) = send("GET", "/v1/search?q=kettle&limit=1&group_by_product=true", serde_json::Value::Null).await;
+        let cursor = first["next_cursor"].as_str().unwrap();
+        let (_, second) = send("GET", &format!("/v1/search?q=kettle&limit=1&group_by_product=true&cursor={}", cursor), serde_json::Value::Null).await;
+        assert_eq!(second["total_results"], 1);
+        assert_ne!(second["results"][0]["review"]["product_id"], first["results"][0]["review"]["product_id"]);
+        assert!(second["next_cursor"].is_null());
+        let (status, _) = send("GET", &format!("/v1/search?q=kettle&limit=1&cursor={}", cursor), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_field_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("fields"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let review = json!({ "title": "Quiet kettle", "body": "Boils in a minute.", "product_id": "kettle_001", "rating": 3 });
+        assert_eq!(send("POST", "/v1/reviews", review).await.0, StatusCode::OK);
+
+        let (status, search) = send("POST", "/v1/search?fields=id,title,score", json!({ "query": "kettle" })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(search["total_results"], 1);
+        let hit = search["results"][0].as_object().unwrap();
+        assert_eq!(hit.keys().collect::<Vec<_>>(), ["review", "similarity_score"]);
+        let review = hit["review"].as_object().unwrap();
+        assert_eq!(review.keys().collect::<Vec<_>>(), ["id", "title"]);
+        assert_eq!(review["title"], "Quiet kettle");
+
+        let (_, listing) = send("GET", "/v1/products/kettle_001/reviews?fields=rating", serde_json::Value::Null).await;
+        assert_eq!(listing["reviews"], json!([{ "rating": 3 }]));
+        assert_eq!(listing["total_reviews"], 1);
+
+        let (status, error) = send("GET", "/v1/search?q=kettle&fields=id,secret", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FIELDS_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_search_within_field() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("search_fields");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let products = |response: &serde_json::Value| -> Vec<String> {
+            response["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["review"]["product_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let reviews = json!([
+            { "title": "Kettle review", "body": "Heats water quickly.", "product_id": "in_title", "rating": 3 },
+            { "title": "Great purchase", "body": "This kettle is quiet.", "product_id": "in_body", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        // Keyword scoring of pending reviews
+        let search = |fields: serde_json::Value| json!({ "query": "kettle", "fields": fields });
+        let (_, both) = send("POST", "/v1/search", search(json!([]))).await;
+        assert_eq!(both["total_results"], 2);
+        let (_, title) = send("POST", "/v1/search", search(json!(["title"]))).await;
+        assert_eq!(products(&title), ["in_title"]);
+        let (_, body) = send("GET", "/v1/search?q=kettle&search_fields=body", serde_json::Value::Null).await;
+        assert_eq!(products(&body), ["in_body"]);
+        let (status, error) = send("GET", "/v1/search?q=kettle&search_fields=colour", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FIELDS_INVALID");
+
+        // Vector scoring of embedded reviews, against the field's own vector
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 2);
+        let (_, title) = send("POST", "/v1/search", search(json!(["title"]))).await;
+        assert_eq!(products(&title)[0], "in_title");
+        let (_, body) = send("POST", "/v1/search", search(json!(["body"]))).await;
+        assert_eq!(products(&body)[0], "in_body");
+    }
+
+    #[tokio::test]
+    async fn test_similarity_matrix() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("similarity_matrix");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method("POST")
+                .uri("/v1/analyze/similarity-matrix")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        data_paths.ensure_directories().unwrap();
+        let storage = crate::storage::JsonlStorage::new(&data_paths.reviews_jsonl);
+        let texts = [
+            ("Battery life", "The battery lasts two full days."),
+            ("Battery life", "The battery lasts two full days."),
+            ("Cracked screen", "The screen cracked after one drop."),
+        ];
+        let reviews: Vec<crate::models::ReviewMetadata> = texts
+            .iter()
+            .enumerate()
+            .map(|(index, (title, body))| {
+                crate::models::ReviewData {
+                    title: title.to_string(),
+                    body: body.to_string(),
+                    product_id: "phone".to_string(),
+                    rating: 4,
+                    reviewer_id: None,
+                    source: None,
+                    license: None,
+                    attribution: None,
+                }
+                .to_metadata(index)
+                .unwrap()
+            })
+            .collect();
+        storage.append_reviews(&reviews).unwrap();
+        let ids: Vec<String> = reviews.iter().map(|review| review.id.clone()).collect();
+
+        // The first two are embedded by the worker, the third on the spot
+        let embedder = crate::embedding::default_embedder();
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 2).unwrap(), 2);
+        let (status, response) = send(json!({ "ids": ids })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["review_ids"], json!(ids));
+        assert_eq!(response["pending"], json!([ids[2]]));
+        let matrix: Vec<Vec<f64>> = serde_json::from_value(response["matrix"].clone()).unwrap();
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix[0][0], 1.0);
+        assert!((matrix[0][1] - 1.0).abs() < 1e-6);
+        assert!(matrix[0][2] < matrix[0][1]);
+        assert_eq!(matrix[2][0], matrix[0][2]);
+
+        let (status, error) = send(json!({ "ids": [ids[0], ids[0]] })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_BATCH_INVALID");
+        let (status, _) = send(json!({ "ids": [ids[0], "missing"] })).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_search_phrases_and_exclusions() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("query_syntax");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let products = |response: &serde_json::Value| -> Vec<String> {
+            response["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["review"]["product_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let reviews = json!([
+            { "title": "Battery life is great", "body": "The battery life lasts days, charger included.", "product_id": "with_charger", "rating": 3 },
+            { "title": "Battery life", "body": "Long battery life, two days easily.", "product_id": "phrase", "rating": 3 },
+            { "title": "Battery", "body": "The life of this battery is short.", "product_id": "no_phrase", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+        let search = json!({ "query": "\"battery life\" -charger" });
+
+        // Keyword scoring of pending reviews
+        let (status, response) = send("POST", "/v1/search", search.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(products(&response), ["phrase"]);
+        assert_eq!(
+            response["parsed_query"],
+            json!({ "text": "battery life", "phrases": ["battery life"], "excluded": ["charger"] })
+        );
+        let (_, plain) = send("GET", "/v1/search?q=battery%20life", serde_json::Value::Null).await;
+        assert_eq!(plain["total_results"], 3);
+        assert!(plain.get("parsed_query").is_none());
+
+        // Vector scoring of embedded reviews is filtered the same way
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 3);
+        let (_, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(products(&response), ["phrase"]);
+
+        let (status, error) = send("POST", "/v1/search", json!({ "query": "-charger" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["message"].as_str().unwrap().contains("excluded"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_search() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("batch_search"));
+
+        let app = create_app();
+        let send = |uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Quiet kettle", "body": "The kettle boils quickly and quietly.", "product_id": "kettle", "rating": 3 },
+            { "title": "Even toaster", "body": "The toaster browns both sides evenly.", "product_id": "toaster", "rating": 3 }
+        ]);
+        assert_eq!(send("/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        let searches = json!([
+            { "query": "kettle" },
+            { "query": "kettle", "limit": 0 },
+            { "limit": 5 },
+            { "query": "toaster", "limit": 1 }
+        ]);
+        let (status, batch) = send("/v1/search/batch", searches).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((batch["succeeded"].as_u64(), batch["failed"].as_u64()), (Some(2), Some(2)));
+        let results = batch["results"].as_array().unwrap();
+        let indices: Vec<u64> = results.iter().map(|result| result["index"].as_u64().unwrap()).collect();
+        assert_eq!(indices, [0, 1, 2, 3]);
+        assert_eq!(results[0]["status"], 200);
+        assert_eq!(results[0]["response"]["results"][0]["review"]["product_id"], "kettle");
+        assert_eq!(results[1]["status"], 400);
+        assert_eq!(results[1]["error"]["code"], "E_VALIDATION_LIMIT_INVALID");
+        assert_eq!(results[2]["status"], 400);
+        assert!(results[2].get("response").is_none());
+        assert_eq!(results[3]["response"]["results"][0]["review"]["product_id"], "toaster");
+
+        let (status, error) = send("/v1/search/batch", json!([])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_BATCH_INVALID");
+        let (status, _) = send("/v1/search/batch", json!({ "query": "kettle" })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_search_analytics() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("search_analytics");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Quiet kettle", "body": "The kettle boils quickly and quietly.", "product_id": "kettle", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk".to_string(), reviews).await.0, StatusCode::OK);
+        for query in ["kettle", "Kettle ", "zebra"] {
+            let (status, _) = send("POST", "/v1/search".to_string(), json!({ "query": query })).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        assert_eq!(send("POST", "/v1/search".to_string(), json!({ "query": "kettle", "limit": 0 })).await.0, StatusCode::BAD_REQUEST);
+        assert!(data_dir.join("queries.jsonl").exists());
+
+        let (status, analytics) = send("GET", "/v1/analytics/searches".to_string(), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        // Only searches that ran are logged
+        assert_eq!(analytics["total_searches"], 3);
+        assert_eq!(analytics["zero_result_searches"], 1);
+        assert_eq!(analytics["top_queries"][0], json!({ "query": "kettle", "searches": 2, "zero_result_searches": 0 }));
+        assert_eq!(analytics["zero_result_queries"], json!([{ "query": "zebra", "searches": 1, "zero_result_searches": 1 }]));
+        assert!(analytics["latency_ms"]["p99"].as_f64().unwrap() >= analytics["latency_ms"]["p50"].as_f64().unwrap());
+
+        let (_, analytics) = send("GET", "/v1/analytics/searches?to=2020-01-01".to_string(), serde_json::Value::Null).await;
+        assert_eq!(analytics["total_searches"], 0);
+        assert!(analytics["latency_ms"].is_null());
+        let uri = "/v1/analytics/searches?from=2030-01-02&to=2030-01-01".to_string();
+        assert_eq!(send("GET", uri, serde_json::Value::Null).await.0, StatusCode::BAD_REQUEST);
+        let uri = "/v1/analytics/searches?limit=0".to_string();
+        assert_eq!(send("GET", uri, serde_json::Value::Null).await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_by_sentiment() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("search_by_sentiment"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let products = |response: &serde_json::Value| -> Vec<String> {
+            response["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|result| result["review"]["product_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let reviews = json!([
+            { "title": "Fast shipping", "body": "Shipping was quick and the box arrived perfect.", "product_id": "happy", "rating": 3 },
+            { "title": "Shipping problems", "body": "Shipping was slow and the box arrived damaged.", "product_id": "unhappy", "rating": 3 },
+            { "title": "Shipping", "body": "Shipping took four days by courier.", "product_id": "plain", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        let search = json!({ "query": "shipping", "filters": { "sentiment": "negative" } });
+        let (status, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(products(&response), ["unhappy"]);
+        assert_eq!(response["results"][0]["review"]["sentiment"], "negative");
+        assert_eq!(response["filters"]["sentiment"], "negative");
+
+        let (_, response) = send("GET", "/v1/search?q=shipping&filters=sentiment:positive", serde_json::Value::Null).await;
+        assert_eq!(products(&response), ["happy"]);
+        let (status, error) = send("GET", "/v1/search?q=shipping&filters=sentiment:angry", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FILTERS_INVALID");
+        let (_, response) = send("GET", "/v1/search?q=shipping", serde_json::Value::Null).await;
+        assert_eq!(response["total_results"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_explain() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("search_explain");
+        env::set_var("DATA_DIR", &data_dir);
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let by_product = |response: &serde_json::Value, product_id: &str| -> serde_json::Value {
+            response["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|result| result["review"]["product_id"] == product_id)
+                .unwrap()
+                .clone()
+        };
+
+        let reviews = json!([
+            { "title": "Kettle", "body": "Boils quickly and quietly.", "product_id": "steel_kettle", "rating": 4 },
+            { "title": "Lid", "body": "The kettle lid pops open.", "product_id": "recalled_kettle", "rating": 3 },
+            { "title": "Toaster", "body": "Browns the bread evenly.", "product_id": "toaster", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        // Keyword scoring of pending reviews, part by part
+        let (status, response) = send("POST", "/v1/search", json!({ "query": "kettle", "explain": true })).await;
+        assert_eq!(status, StatusCode::OK);
+        let steel = by_product(&response, "steel_kettle");
+        let explanation = &steel["explanation"];
+        assert_eq!(explanation["match"], "keyword");
+        assert_eq!(explanation["keyword"]["phrase_match"], 1.0);
+        assert_eq!(explanation["keyword"]["term_matches"], json!([{ "term": "kettle", "field": "title", "score": 0.8 }]));
+        assert!((explanation["keyword"]["rating_boost"].as_f64().unwrap() - 0.1).abs() < 1e-6);
+        assert_eq!(explanation["match_score"], steel["similarity_score"]);
+        let recalled = by_product(&response, "recalled_kettle");
+        assert_eq!(recalled["explanation"]["keyword"]["term_matches"][0]["field"], "body");
+
+        let (_, plain) = send("POST", "/v1/search", json!({ "query": "kettle" })).await;
+        assert!(plain["results"][0].get("explanation").is_none());
+
+        // Curation shows up as the step that moved the result
+        let sink = json!({ "action": { "type": "boost", "target": { "product_id": "recalled_kettle" }, "weight": -0.5 } });
+        assert_eq!(send("POST", "/v1/admin/curation/rules", sink).await.0, StatusCode::OK);
+        let toaster_id = crate::storage::JsonlStorage::new(data_dir.join("reviews.jsonl"))
+            .read_live_reviews()
+            .unwrap()
+            .into_iter()
+            .find(|review| review.product_id == "toaster")
+            .unwrap()
+            .id;
+        let pin = json!({ "scope": { "query": "kettle" }, "action": { "type": "pin", "review_id": toaster_id, "position": 1 } });
+        let (_, pin) = send("POST", "/v1/admin/curation/rules", pin).await;
+        let (_, response) = send("POST", "/v1/search", json!({ "query": "kettle", "explain": true })).await;
+        assert_eq!(response["results"][0]["review"]["product_id"], "toaster");
+        assert_eq!(response["results"][0]["explanation"]["match"], "pinned");
+        assert_eq!(response["results"][0]["explanation"]["pinned_by"], pin["rule"]["id"]);
+        assert_eq!(by_product(&response, "recalled_kettle")["explanation"]["curation_boost"], -0.5);
+
+        // Vector scoring once embedded, with the helpfulness blend
+        let embedder = crate::embedding::default_embedder();
+        let data_paths = crate::storage::DataPaths::new(&data_dir);
+        assert_eq!(crate::embedding_worker::embed_pending(&data_paths, &embedder, 10).unwrap(), 3);
+        let search = json!({ "query": "kettle", "explain": true, "rank_by_helpfulness": true, "curate": false });
+        let (_, response) = send("POST", "/v1/search", search).await;
+        let explanation = &by_product(&response, "steel_kettle")["explanation"];
+        assert_eq!(explanation["match"], "vector");
+        assert!(explanation["vector_similarity"].as_f64().unwrap() > 0.0);
+        assert!(explanation.get("keyword").is_none());
+        assert_eq!(explanation["helpfulness"], json!({ "value": 0.0, "weight": 0.2 }));
+    }
+
+    #[tokio::test]
+    async fn test_search_by_language() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("search_by_language"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let products = |response: &serde_json::Value| -> Vec<String> {
+            response["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|result| result["review"]["product_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        // "Kettle boils fast" in Thai, beside English reviews mentioning the same model
+        let reviews = json!([
+            { "title": "กาต้มน้ำ K200", "body": "กาต้มน้ำเดือดเร็วมาก", "product_id": "thai", "rating": 5 },
+            { "title": "Kettle K200", "body": "The K200 kettle boils fast.", "product_id": "english", "rating": 5 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        // The query types the tone mark inside sara am and carries a zero-width space
+        let search = json!({ "query": "กาต้ม\u{200B}น\u{0E4D}\u{0E49}\u{0E32}", "filters": { "language": "th" } });
+        let (status, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(products(&response), ["thai"]);
+        assert_eq!(response["results"][0]["review"]["language"], "th");
+        assert_eq!(response["filters"]["language"], "th");
+
+        let (_, response) = send("GET", "/v1/search?q=K200&filters=language:en", serde_json::Value::Null).await;
+        assert_eq!(products(&response), ["english"]);
+        let (_, response) = send("GET", "/v1/search?q=K200", serde_json::Value::Null).await;
+        assert_eq!(response["total_results"], 2);
+        let (status, error) = send("GET", "/v1/search?q=K200&filters=language:xx", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FILTERS_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_keyword_search_skips_stopwords_and_stems() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("keyword_stemming"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Batteries die quickly", "body": "Both batteries drain within a day.", "product_id": "batteries", "rating": 2 },
+            { "title": "Is the charger included", "body": "The charger is in the box.", "product_id": "charger", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        // Unembedded reviews are matched by keyword: "the" and "is" match nothing, and
+        // "battery" finds "batteries"
+        let search = json!({ "query": "the battery is", "explain": true });
+        let (status, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["total_results"], 1);
+        let result = &response["results"][0];
+        assert_eq!(result["review"]["product_id"], "batteries");
+        assert_eq!(result["explanation"]["keyword"]["term_matches"][0]["term"], "battery");
+        assert_eq!(result["explanation"]["keyword"]["coverage"], 0.5);
+
+        // A query of stopwords alone still matches on them
+        let (_, response) = send("POST", "/v1/search", json!({ "query": "is the" })).await;
+        assert_eq!(response["results"][0]["review"]["product_id"], "charger");
+    }
+
+    #[tokio::test]
+    async fn test_ask_product_question() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("ask_product"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Quick kettle", "body": "Looks smart on the counter. It boils a full jug in three minutes.", "product_id": "kettle", "rating": 5 },
+            { "title": "Loud but fine", "body": "The lid sticks a little.\nBoiling water takes about four minutes and it is loud.", "product_id": "kettle", "rating": 3 },
+            { "title": "Toaster boils nothing", "body": "It toasts bread evenly in two minutes.", "product_id": "toaster", "rating": 4 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        let question = json!({ "question": "How many minutes does it take to boil?" });
+        let (status, response) = send("POST", "/v1/products/kettle/ask", question).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["product_id"], "kettle");
+        let sources = response["sources"].as_array().unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0]["number"], 1);
+        // Each review's best sentence is the one about boiling, not its title or the lid
+        for source in sources {
+            let best = source["sentences"][0]["text"].as_str().unwrap();
+            assert!(best.contains("boils a full jug") || best.contains("Boiling water takes"), "{}", best);
+            assert!(source["sentences"].as_array().unwrap().len() <= 2);
+        }
+        assert!(response["context"].as_str().unwrap().starts_with("[1] \""));
+        let answer = &response["answer"];
+        assert!(answer["text"].as_str().unwrap().contains("minutes"));
+        let citations = answer["citations"].as_array().unwrap();
+        assert!(!citations.is_empty() && citations.iter().all(|number| number == 1 || number == 2));
+
+        let question = json!({ "question": "boil time", "limit": 1, "sentences_per_review": 1, "summarize": false });
+        let (_, response) = send("POST", "/v1/products/kettle/ask", question).await;
+        assert_eq!(response["sources"].as_array().unwrap().len(), 1);
+        assert_eq!(response["sources"][0]["sentences"].as_array().unwrap().len(), 1);
+        assert!(response.get("answer").is_none());
+
+        let (status, error) = send("POST", "/v1/products/kettle/ask", json!({ "question": " " })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_QUERY_MISSING");
+        let (status, error) = send("POST", "/v1/products/kettle/ask", json!({ "question": "boil", "limit": 0 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_LIMIT_INVALID");
+        let (status, _) = send("POST", "/v1/products/blender/ask", json!({ "question": "boil" })).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_search_falls_back_when_nothing_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("search_fallback"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+
+        let reviews = json!([
+            { "title": "Kettle", "body": "Boils fast and pours cleanly.", "product_id": "kettle_001", "rating": 3 },
+            { "title": "Toaster", "body": "Browns both sides evenly.", "product_id": "toaster_001", "rating": 3 }
+        ]);
+        assert_eq!(send("POST", "/v1/reviews/bulk", reviews).await.0, StatusCode::OK);
+
+        // No kettle review for the toaster: the filter is dropped and the response says so
+        let search = json!({ "query": "kettle", "filters": { "product_id": "toaster_001" } });
+        let (status, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["results"][0]["review"]["product_id"], "kettle_001");
+        assert_eq!(response["query"], "kettle");
+        assert_eq!(response["fallback"]["relaxations"], json!(["dropped_filters"]));
+        assert_eq!(response["fallback"]["dropped_filters"]["product_id"], "toaster_001");
+
+        // A threshold nothing reaches is lowered: half the words match, in the body
+        let (_, response) = send("GET", "/v1/search?q=pours%20handle&min_score=1", serde_json::Value::Null).await;
+        assert_eq!(response["total_results"], 1);
+        assert_eq!(response["fallback"]["relaxations"], json!(["lowered_threshold"]));
+        assert_eq!(response["fallback"]["dropped_min_score"], 1.0);
+
+        // Results found as asked, and searches opting out, are not relaxed
+        let (_, response) = send("GET", "/v1/search?q=kettle", serde_json::Value::Null).await;
+        assert!(response.get("fallback").is_none());
+        let search = json!({ "query": "kettle", "filters": { "product_id": "toaster_001" }, "fallback": false });
+        let (_, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(response["total_results"], 0);
+        assert!(response.get("fallback").is_none());
+
+        let (status, error) = send("GET", "/v1/search?q=kettle&min_score=2", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_FILTERS_INVALID");
+    }
+
+    #[tokio::test]
+    async fn test_near_duplicate_reviews_are_flagged_at_ingest() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("duplicate_ingest"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let review = |body: &str, product_id: &str| {
+            json!({ "title": "Battery", "body": body, "product_id": product_id, "rating": 4 })
+        };
+
+        let create = |review| send("POST", "/v1/reviews".to_string(), review);
+        let (status, original) = create(review("Lasts two full days.", "phone_001")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(original.get("duplicate_of").is_none());
+
+        // Resubmitted with different punctuation: stored, but flagged with the review it repeats
+        let (status, repeated) = create(review("lasts two FULL days!", "phone_001")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(repeated["duplicate_of"], original["review_id"]);
+        let uri = format!("/v1/reviews/{}", repeated["review_id"].as_str().unwrap());
+        let (_, stored) = send("GET", uri, serde_json::Value::Null).await;
+        assert_eq!(stored["review"]["duplicate_of"], original["review_id"]);
+
+        // The same words about another product are not a duplicate
+        let (_, other) = create(review("Lasts two full days.", "phone_002")).await;
+        assert!(other.get("duplicate_of").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_merges_collections() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", temp_dir.path().join("collections"));
+
+        let app = create_app();
+        let send = |method: &'static str, uri: &str, body: serde_json::Value| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+            }
+        };
+        let review = |title: &str| {
+            json!({ "title": title, "body": "Boils water fast.", "product_id": "kettle", "rating": 3 })
+        };
+
+        let (status, created) = send("POST", "/v1/collections", json!({ "name": "thailand" })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(created["collection"]["review_count"], 0);
+        assert_eq!(send("POST", "/v1/collections", json!({ "name": "thailand" })).await.0, StatusCode::CONFLICT);
+        let (status, error) = send("POST", "/v1/collections", json!({ "name": "th/bkk" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "E_VALIDATION_COLLECTION_INVALID");
+
+        let (status, _) = send("POST", "/v1/reviews/bulk", json!([review("Kettle from the US store")])).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, uploaded) =
+            send("POST", "/v1/collections/thailand/reviews", json!([review("Kettle from the Thai store")])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(uploaded["result"]["successful"], 1);
+        let (status, error) = send("POST", "/v1/collections/japan/reviews", json!([review("Kettle")])).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "E_COLLECTION_NOT_FOUND");
+
+        let (_, listed) = send("GET", "/v1/collections", serde_json::Value::Null).await;
+        assert_eq!(
+            listed["collections"],
+            json!([{ "name": "default", "review_count": 1 }, { "name": "thailand", "review_count": 1 }])
+        );
+
+        // Without collections only the default one is searched, and results don't name it
+        let (_, response) = send("POST", "/v1/search", json!({ "query": "kettle" })).await;
+        assert_eq!(response["total_results"], 1);
+        assert!(response["results"][0].get("collection").is_none());
+
+        // Every collection, merged, each result saying where it came from
+        let (status, response) = send("GET", "/v1/search?q=kettle&collections=*", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut found: Vec<&str> =
+            response["results"].as_array().unwrap().iter().map(|hit| hit["collection"].as_str().unwrap()).collect();
+        found.sort();
+        assert_eq!(found, ["default", "thailand"]);
+
+        let search = json!({ "query": "kettle", "collections": ["thailand"], "limit": 1 });
+        let (_, response) = send("POST", "/v1/search", search).await;
+        assert_eq!(response["results"][0]["review"]["title"], "Kettle from the Thai store");
+        assert!(response.get("next_cursor").is_none());
+
+        let search = json!({ "query": "kettle", "collections": ["japan"] });
+        assert_eq!(send("POST", "/v1/search", search).await.0, StatusCode::NOT_FOUND);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,129 @@
+//! Many searches in one request, for evaluation tooling that replays hundreds of
+//! queries against the index.
+//!
+//! Each entry is searched exactly as a `POST /search` body would be, fanned out to the
+//! shards on a coordinator, and succeeds or fails on its own: a malformed or invalid
+//! entry is reported at its index and the rest still run. At most
+//! `SEARCH_BATCH_CONCURRENCY` searches run at once, so a large batch queues behind
+//! itself instead of taking every blocking thread from other requests.
+
+use crate::extract;
+use crate::fanout::{self, ShardConfig};
+use crate::models::*;
+use axum::http::StatusCode;
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// Most searches one batch may hold
+pub const MAX_BATCH_SEARCHES: usize = 500;
+
+/// Searches of a batch run at once when `SEARCH_BATCH_CONCURRENCY` is not set
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Upper bound on `SEARCH_BATCH_CONCURRENCY`
+const MAX_BATCH_CONCURRENCY: usize = 64;
+
+/// Searches of one batch run at once, from `SEARCH_BATCH_CONCURRENCY`
+pub fn concurrency() -> usize {
+    std::env::var("SEARCH_BATCH_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|concurrency| *concurrency > 0)
+        .map_or(DEFAULT_BATCH_CONCURRENCY, |concurrency| concurrency.min(MAX_BATCH_CONCURRENCY))
+}
+
+/// Outcome of one search of a batch: its response, or the error a single search would have returned
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct BatchSearchResult {
+    /// Position of the search in the request
+    pub index: usize,
+    /// Status a single `POST /search` would have answered with
+    pub status: u16,
+    /// The search response, as from `POST /search`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// Response of `POST /search/batch`
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct BatchSearchResponse {
+    pub success: bool,
+    /// One entry per search, in request order
+    pub results: Vec<BatchSearchResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+pub fn validate(searches: &[Value]) -> Result<(), ValidationError> {
+    if searches.is_empty() || searches.len() > MAX_BATCH_SEARCHES {
+        return Err(ValidationError::InvalidValue {
+            field: "searches".to_string(),
+            reason: format!("must contain between 1 and {} searches", MAX_BATCH_SEARCHES),
+        });
+    }
+    Ok(())
+}
+
+/// Parse and run one search of a batch
+async fn search(shards: Option<ShardConfig>, search: Value) -> Result<Value, (StatusCode, ErrorResponse)> {
+    let search_request: SearchRequest =
+        extract::from_value(search).map_err(|e| (StatusCode::BAD_REQUEST, ErrorResponse::from(e)))?;
+    let response = match shards {
+        Some(config) => fanout::search(&config, search_request).await.map(serde_json::to_value),
+        None => match tokio::task::spawn_blocking(move || crate::run_search(search_request)).await {
+            Ok(result) => result.map(serde_json::to_value),
+            Err(e) => {
+                tracing::error!("Batch search task failed: {}", e);
+                let error_response = ErrorResponse::from(AppError::Concurrency {
+                    message: "Search task failed".to_string(),
+                });
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, error_response));
+            }
+        },
+    };
+    match response {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, ErrorResponse::from(AppError::from(e)))),
+        Err((status, error)) => Err((status, error.0)),
+    }
+}
+
+/// Run every search of a batch, `concurrency()` at a time, answering in request order
+pub async fn run(shards: Option<ShardConfig>, searches: Vec<Value>) -> BatchSearchResponse {
+    let results: Vec<BatchSearchResult> = stream::iter(searches.into_iter().enumerate())
+        .map(|(index, entry)| {
+            let shards = shards.clone();
+            async move {
+                match search(shards, entry).await {
+                    Ok(response) => BatchSearchResult {
+                        index,
+                        status: StatusCode::OK.as_u16(),
+                        response: Some(response),
+                        error: None,
+                    },
+                    Err((status, error)) => BatchSearchResult {
+                        index,
+                        status: status.as_u16(),
+                        response: None,
+                        error: Some(error),
+                    },
+                }
+            }
+        })
+        .buffered(concurrency())
+        .collect()
+        .await;
+
+    let failed = results.iter().filter(|result| result.error.is_some()).count();
+    BatchSearchResponse {
+        success: true,
+        succeeded: results.len() - failed,
+        failed,
+        results,
+    }
+}
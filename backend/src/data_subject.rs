@@ -0,0 +1,274 @@
+//! Data subject requests: export and erasure of everything stored about one reviewer.
+//!
+//! A reviewer is known only by the `reviewer_id` their client sends, so that is what a
+//! request names. It reaches: reviews (deleted ones included), curation boosts that
+//! target the reviewer, blocklist entries and their audit records, idempotency records
+//! and recorded exchanges (see `recording`) whose bodies carry the id.
+//!
+//! Erasure cannot be undone. Reviews keep their lines, so vector slots stay aligned, but
+//! are tombstoned with their title, body and reviewer_id cleared and their vectors
+//! zeroed; compaction drops the lines later. Boosts and blocklist entries naming the
+//! reviewer are deleted, audit records have the id overwritten, and idempotency records
+//! and recorded exchanges carrying it are dropped. Each erasure appends a receipt to
+//! erasure_receipts.jsonl with what was erased. The receipt holds a SHA-256 hash of
+//! the id, never the id itself, so it can later be matched to the request it answers.
+//!
+//! On a sharded coordinator every shard erases its own data first (see `routing`), and
+//! the coordinator's receipt adds up theirs and names each shard's receipt.
+
+use crate::blocklist::{BlockKind, BlocklistAuditRecord, BlocklistEntry, BlocklistStorage};
+use crate::curation::{CurationAction, CurationRule, CurationStorage};
+use crate::embedding::EMBEDDING_DIM;
+use crate::idempotency::{IdempotencyRecord, IdempotencyStore};
+use crate::models::*;
+use crate::recording::{self, RecordedExchange};
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use utoipa::ToSchema;
+
+/// Written in place of an erased reviewer_id in the blocklist audit log
+pub const ERASED: &str = "[erased]";
+
+/// Everything stored about a reviewer
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct SubjectExport {
+    pub reviewer_id: String,
+    pub exported_at: DateTime<Utc>,
+    /// Their reviews, deleted ones included, in storage order
+    pub reviews: Vec<ReviewMetadata>,
+    /// Boost rules targeting them
+    pub curation_rules: Vec<CurationRule>,
+    pub blocklist_entries: Vec<BlocklistEntry>,
+    pub blocklist_audit: Vec<BlocklistAuditRecord>,
+    /// Remembered requests (see `Idempotency-Key`) carrying their id
+    #[schema(value_type = Vec<Object>)]
+    pub idempotency_records: Vec<IdempotencyRecord>,
+    /// Recorded requests and responses carrying their id
+    #[schema(value_type = Vec<Object>)]
+    pub recorded_exchanges: Vec<RecordedExchange>,
+}
+
+/// Proof that an erasure ran, and what it removed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ErasureReceipt {
+    pub id: String,
+    /// Hex SHA-256 of the erased reviewer_id
+    pub subject_sha256: String,
+    pub erased_at: DateTime<Utc>,
+    pub reviews: usize,
+    /// Vector slots zeroed; reviews not yet embedded have none
+    pub vectors: usize,
+    pub curation_rules: usize,
+    pub blocklist_entries: usize,
+    pub blocklist_audit_records: usize,
+    pub idempotency_records: usize,
+    pub recorded_exchanges: usize,
+    /// On a coordinator, the receipt id each shard issued, by node; the counts above include theirs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub shard_receipts: BTreeMap<String, String>,
+}
+
+/// Check a reviewer_id named by a request
+pub fn validate_reviewer_id(reviewer_id: &str) -> Result<(), ValidationError> {
+    if reviewer_id.trim().is_empty() {
+        return Err(ValidationError::InvalidValue {
+            field: "reviewer_id".to_string(),
+            reason: "must not be empty".to_string(),
+        });
+    }
+    // Longer ids are refused at ingestion, so nothing could be stored under them
+    if reviewer_id.len() > 100 {
+        return Err(ValidationError::TooLong {
+            field: "reviewer_id".to_string(),
+            max_length: 100,
+        });
+    }
+    Ok(())
+}
+
+/// Hex SHA-256 of a reviewer_id, as kept in receipts
+pub fn subject_hash(reviewer_id: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(reviewer_id.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Whether a stored body carries the reviewer: a `reviewer_id` field with their id at any
+/// depth, or text (a JSONL or CSV upload) with a reviewer_id field or column and their id
+fn mentions(value: &Value, reviewer_id: &str) -> bool {
+    match value {
+        Value::Object(fields) => fields.iter().any(|(name, field)| {
+            (name == "reviewer_id" && field.as_str() == Some(reviewer_id)) || mentions(field, reviewer_id)
+        }),
+        Value::Array(items) => items.iter().any(|item| mentions(item, reviewer_id)),
+        Value::String(text) => text.contains("reviewer_id") && text.contains(reviewer_id),
+        _ => false,
+    }
+}
+
+fn boosts(rule: &CurationRule, reviewer_id: &str) -> bool {
+    matches!(&rule.action, CurationAction::Boost { target, .. } if target.reviewer_id.as_deref() == Some(reviewer_id))
+}
+
+fn names_reviewer(entry: &BlocklistEntry, reviewer_id: &str) -> bool {
+    entry.kind == BlockKind::Reviewer && entry.value == reviewer_id
+}
+
+/// Collect everything stored about `reviewer_id`
+pub fn export(data_paths: &DataPaths, reviewer_id: &str) -> Result<SubjectExport, AppError> {
+    validate_reviewer_id(reviewer_id).map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let _lock = FileLock::acquire_shared(&data_paths.lock_file, "data_subject_export")?;
+    let blocklist = BlocklistStorage::new(data_paths);
+    Ok(SubjectExport {
+        reviewer_id: reviewer_id.to_string(),
+        exported_at: Utc::now(),
+        reviews: JsonlStorage::new(&data_paths.reviews_jsonl)
+            .read_all_reviews()?
+            .into_iter()
+            .filter(|review| review.reviewer_id.as_deref() == Some(reviewer_id))
+            .collect(),
+        curation_rules: CurationStorage::new(&data_paths.curation_jsonl)
+            .read_all()?
+            .into_iter()
+            .filter(|rule| boosts(rule, reviewer_id))
+            .collect(),
+        blocklist_entries: blocklist.read_all()?.into_iter().filter(|entry| names_reviewer(entry, reviewer_id)).collect(),
+        blocklist_audit: blocklist
+            .read_audit()?
+            .into_iter()
+            .filter(|record| names_reviewer(&record.entry, reviewer_id))
+            .collect(),
+        idempotency_records: IdempotencyStore::new(&data_paths.idempotency_jsonl)
+            .read_all()?
+            .into_iter()
+            .filter(|record| mentions(&record.request, reviewer_id) || mentions(&record.response, reviewer_id))
+            .collect(),
+        recorded_exchanges: recording::find_exchanges(data_paths, |exchange| {
+            mentions(&exchange.request_body, reviewer_id) || mentions(&exchange.response_body, reviewer_id)
+        })?,
+    })
+}
+
+/// Irreversibly erase everything stored about `reviewer_id` and record a receipt. On a
+/// coordinator, `shard_receipts` are the receipts its shards issued for the same erasure,
+/// and the recorded receipt counts what they erased too.
+pub fn erase(
+    data_paths: &DataPaths,
+    reviewer_id: &str,
+    shard_receipts: &BTreeMap<String, ErasureReceipt>,
+) -> Result<ErasureReceipt, AppError> {
+    validate_reviewer_id(reviewer_id).map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let _lock = FileLock::acquire(&data_paths.lock_file, "data_subject_erasure")?;
+    let erased_at = Utc::now();
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let mut slots = Vec::new();
+    storage.rewrite_reviews(|mut review| {
+        if review.reviewer_id.as_deref() == Some(reviewer_id) {
+            review.title = String::new();
+            review.body = String::new();
+            review.reviewer_id = None;
+            review.deleted_at = review.deleted_at.or(Some(erased_at));
+            review.needs_reembedding = false;
+            slots.push(review.vector_index);
+        }
+        Some(review)
+    })?;
+
+    let vector_store = VectorStore::new(&data_paths.reviews_index, EMBEDDING_DIM);
+    let stored = vector_store.len()?;
+    let zeroed: Vec<usize> = slots.iter().copied().filter(|&slot| slot < stored).collect();
+    for &slot in &zeroed {
+        vector_store.write_slots(slot, &[vec![0.0; EMBEDDING_DIM]])?;
+    }
+
+    let curation = CurationStorage::new(&data_paths.curation_jsonl);
+    let mut curation_rules = 0;
+    for rule in curation.read_all()?.iter().filter(|rule| boosts(rule, reviewer_id)) {
+        curation_rules += usize::from(curation.remove(&rule.id)?.is_some());
+    }
+
+    let (blocklist_entries, blocklist_audit_records) =
+        BlocklistStorage::new(data_paths).erase_value(BlockKind::Reviewer, reviewer_id, ERASED)?;
+    let idempotency_records = IdempotencyStore::new(&data_paths.idempotency_jsonl)
+        .remove_where(|record| mentions(&record.request, reviewer_id) || mentions(&record.response, reviewer_id))?
+        .len();
+    let recorded_exchanges = recording::remove_exchanges(data_paths, |exchange| {
+        mentions(&exchange.request_body, reviewer_id) || mentions(&exchange.response_body, reviewer_id)
+    })?;
+
+    let mut receipt = ErasureReceipt {
+        id: uuid::Uuid::new_v4().to_string(),
+        subject_sha256: subject_hash(reviewer_id),
+        erased_at,
+        reviews: slots.len(),
+        vectors: zeroed.len(),
+        curation_rules,
+        blocklist_entries,
+        blocklist_audit_records,
+        idempotency_records,
+        recorded_exchanges,
+        shard_receipts: BTreeMap::new(),
+    };
+    for (node, shard_receipt) in shard_receipts {
+        receipt.reviews += shard_receipt.reviews;
+        receipt.vectors += shard_receipt.vectors;
+        receipt.curation_rules += shard_receipt.curation_rules;
+        receipt.blocklist_entries += shard_receipt.blocklist_entries;
+        receipt.blocklist_audit_records += shard_receipt.blocklist_audit_records;
+        receipt.idempotency_records += shard_receipt.idempotency_records;
+        receipt.recorded_exchanges += shard_receipt.recorded_exchanges;
+        receipt.shard_receipts.insert(node.clone(), shard_receipt.id.clone());
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&data_paths.erasure_receipts_jsonl)?;
+    writeln!(file, "{}", serde_json::to_string(&receipt)?)?;
+    file.flush()?;
+
+    tracing::info!("Erased data subject {} (receipt {})", receipt.subject_sha256, receipt.id);
+    Ok(receipt)
+}
+
+/// Every erasure receipt, oldest first
+pub fn read_receipts(data_paths: &DataPaths) -> Result<Vec<ErasureReceipt>, AppError> {
+    if !data_paths.erasure_receipts_jsonl.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(std::fs::File::open(&data_paths.erasure_receipts_jsonl)?);
+    let mut receipts = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            receipts.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(receipts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mentions_finds_the_reviewer_in_stored_bodies() {
+        assert!(mentions(&json!({ "review": { "reviewer_id": "client_1" } }), "client_1"));
+        assert!(mentions(&json!([{ "title": "A", "reviewer_id": "client_1" }]), "client_1"));
+        assert!(!mentions(&json!({ "reviewer_id": "client_12" }), "client_1"));
+        // Another field holding the same text is not the reviewer
+        assert!(!mentions(&json!({ "product_id": "client_1" }), "client_1"));
+        assert!(mentions(&json!("title,body,product_id,rating,reviewer_id\nA,B,p,5,client_1"), "client_1"));
+
+        assert_eq!(subject_hash("client_1").len(), 64);
+        assert_ne!(subject_hash("client_1"), subject_hash("client_2"));
+    }
+}
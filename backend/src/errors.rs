@@ -0,0 +1,50 @@
+//! How errors from the core services reach HTTP clients.
+//!
+//! The services in `semantic_search_core` fail with an `AppError` and know nothing of
+//! status codes; handlers pick the status here, so the same failure answers the same
+//! way on every endpoint, and over gRPC through the status it maps from.
+
+use crate::lock::LOCK_RETRY_AFTER_SECS;
+use crate::models::*;
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+    Json,
+};
+
+/// The status an error is answered with
+pub fn status(error: &AppError) -> StatusCode {
+    match error {
+        AppError::Validation(_) | AppError::MalformedJson { .. } | AppError::MalformedMsgpack { .. } => {
+            StatusCode::BAD_REQUEST
+        }
+        AppError::Blocked { .. } => StatusCode::FORBIDDEN,
+        AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+        AppError::RequestTimeout { .. } => StatusCode::REQUEST_TIMEOUT,
+        AppError::Conflict { .. } | AppError::Duplicate { .. } => StatusCode::CONFLICT,
+        AppError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        AppError::UnsupportedMediaType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        AppError::LockTimeout { .. } | AppError::Concurrency { .. } | AppError::ShardsUnavailable { .. } => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        AppError::CorpusFull { .. } => StatusCode::INSUFFICIENT_STORAGE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// An error as a handler returns it: its status and `ErrorResponse` body
+pub fn respond(error: AppError) -> (StatusCode, Json<ErrorResponse>) {
+    (status(&error), Json(ErrorResponse::from(error)))
+}
+
+/// Response mapper giving every 503 a `Retry-After` hint. Lock timeouts are the usual
+/// cause, and the lock is normally free again within a second.
+pub async fn add_retry_after(mut response: Response) -> Response {
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE && !response.headers().contains_key(header::RETRY_AFTER) {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(LOCK_RETRY_AFTER_SECS));
+    }
+    response
+}
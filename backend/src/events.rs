@@ -0,0 +1,135 @@
+//! Live notifications of review changes, streamed to dashboards as Server-Sent Events
+//! and delivered to registered webhooks (see `webhooks`).
+//!
+//! Events are broadcast in memory, so a subscriber only hears about writes handled by
+//! the server process it is connected to. A subscriber that falls more than
+//! `EVENT_CHANNEL_CAPACITY` events behind is sent a `lagged` event with the number it
+//! missed and should refetch what it displays.
+
+use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::convert::Infallible;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Events buffered per subscriber before the slowest starts missing them
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A change dashboards may want to show
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReviewEvent {
+    ReviewCreated {
+        review_id: String,
+        product_id: String,
+        rating: u8,
+        vector_index: usize,
+        timestamp: DateTime<Utc>,
+    },
+    ReviewDeleted {
+        review_id: String,
+        product_id: String,
+        vector_index: usize,
+        deleted_at: DateTime<Utc>,
+    },
+    BulkUploadCompleted {
+        job_id: Option<String>,
+        batch_id: Option<String>,
+        successful: usize,
+        failed: usize,
+    },
+}
+
+impl ReviewEvent {
+    /// SSE event name, so clients can listen for one kind with `addEventListener`
+    fn name(&self) -> &'static str {
+        match self {
+            ReviewEvent::ReviewCreated { .. } => "review_created",
+            ReviewEvent::ReviewDeleted { .. } => "review_deleted",
+            ReviewEvent::BulkUploadCompleted { .. } => "bulk_upload_completed",
+        }
+    }
+
+    fn to_sse(&self) -> Event {
+        Event::default()
+            .event(self.name())
+            .json_data(self)
+            .unwrap_or_else(|_| Event::default().event(self.name()))
+    }
+}
+
+/// Broadcast channel of review events; clones share the channel
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ReviewEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Send an event to every current subscriber. Nobody listening is not an error.
+    pub fn publish(&self, event: ReviewEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Events published from now on
+    pub fn receiver(&self) -> broadcast::Receiver<ReviewEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Events published from now on, as SSE events
+    pub fn subscribe(&self) -> impl futures_util::Stream<Item = Result<Event, Infallible>> {
+        futures_util::stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            let event = match receiver.recv().await {
+                Ok(event) => event.to_sse(),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    Event::default().event("lagged").data(missed.to_string())
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            Some((Ok(event), receiver))
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_CHANNEL_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn created(review_id: &str) -> ReviewEvent {
+        ReviewEvent::ReviewCreated {
+            review_id: review_id.to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 4,
+            vector_index: 0,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_is_told_what_it_missed() {
+        let bus = EventBus::new(2);
+        let events = bus.subscribe();
+        futures_util::pin_mut!(events);
+
+        for id in ["a", "b", "c"] {
+            bus.publish(created(id));
+        }
+
+        let lagged = format!("{:?}", events.next().await.unwrap().unwrap());
+        assert!(lagged.contains("lagged"), "{}", lagged);
+        let next = format!("{:?}", events.next().await.unwrap().unwrap());
+        assert!(next.contains("review_created") && next.contains("\\\"b\\\""), "{}", next);
+    }
+}
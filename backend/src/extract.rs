@@ -0,0 +1,114 @@
+use crate::idempotency::{self, IDEMPOTENCY_KEY_HEADER};
+use crate::limits::RequestLimits;
+use crate::models::*;
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+/// JSON body extractor whose rejections are ErrorResponses rather than axum's plain-text
+/// bodies, so clients can handle malformed JSON the same way as any other error
+pub struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(ApiJson(value)),
+            Err(rejection) => {
+                let status = rejection.status();
+                tracing::debug!("Rejected JSON body: {}", rejection.body_text());
+                Err((status, Json(ErrorResponse::from(rejection_error(rejection)))))
+            }
+        }
+    }
+}
+
+/// The application error describing why a JSON body was rejected
+fn rejection_error(rejection: JsonRejection) -> AppError {
+    match rejection {
+        JsonRejection::MissingJsonContentType(_) => AppError::UnsupportedMediaType {
+            message: "Expected a request body with `Content-Type: application/json`".to_string(),
+        },
+        JsonRejection::BytesRejection(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            AppError::PayloadTooLarge {
+                limit_bytes: RequestLimits::from_env().max_body_bytes,
+            }
+        }
+        JsonRejection::JsonDataError(rejection) => {
+            let message = rejection.body_text();
+            // A missing required field gets the same error as an empty one
+            match missing_field(&message) {
+                Some(field) => AppError::Validation(ValidationError::MissingField { field }),
+                None => AppError::MalformedJson { message },
+            }
+        }
+        rejection => AppError::MalformedJson {
+            message: rejection.body_text(),
+        },
+    }
+}
+
+/// Deserialize part of a body that was accepted as raw JSON, with the same errors as
+/// `ApiJson` gives for a whole body
+pub fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, AppError> {
+    serde_json::from_value(value).map_err(|e| {
+        let message = e.to_string();
+        match missing_field(&message) {
+            Some(field) => AppError::Validation(ValidationError::MissingField { field }),
+            None => AppError::MalformedJson { message },
+        }
+    })
+}
+
+/// The request's idempotency key, if it sent one
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, ValidationError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value.to_str().map_err(|_| ValidationError::InvalidValue {
+        field: "idempotency_key".to_string(),
+        reason: "must be visible ASCII".to_string(),
+    })?;
+    idempotency::validate_key(key).map(Some)
+}
+
+/// The field named by serde's "missing field `name`" message
+fn missing_field(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once("missing field `")?;
+    let (field, _) = rest.split_once('`')?;
+    Some(field.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_field_is_extracted_from_serde_message() {
+        let message = "Failed to deserialize the JSON body into the target type: missing field `title` at line 1 column 20";
+        assert_eq!(missing_field(message).as_deref(), Some("title"));
+        assert_eq!(missing_field("invalid type: string \"5\", expected u8"), None);
+    }
+
+    #[test]
+    fn test_idempotency_key_header_is_validated() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers).unwrap(), None);
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, " retry-1 ".parse().unwrap());
+        assert_eq!(idempotency_key(&headers).unwrap().as_deref(), Some("retry-1"));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "x".repeat(idempotency::MAX_IDEMPOTENCY_KEY_LENGTH + 1).parse().unwrap());
+        assert!(idempotency_key(&headers).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! Coordinator mode for sharded deployments: a search is scattered to every shard node
+//! and the per-shard top-k lists are gathered into one.
+//!
+//! A node becomes a coordinator when `SHARD_NODES` lists the shards' base URLs. It
+//! forwards each search to `/v1/search` on every shard with `x-search-scope: local`,
+//! which makes the receiver search only its own data, so a coordinator can list itself
+//! as a shard without looping. The shard list comes from `routing::ShardRouter`, which
+//! starts from `SHARD_NODES` and can be rebalanced at runtime.
+//!
+//! Similarity scores are absolute (0-1 per review, not relative to the other results),
+//! so hits from different shards are comparable as they are. Rescaling each shard's
+//! list, e.g. min-max, would be wrong: every shard's best hit would score 1.0. A shard
+//! whose `search_type` differs from the majority scores on another scale and is left
+//! out, as if it had failed.
+//!
+//! A shard that errors or misses `SHARD_TIMEOUT_MS` doesn't fail the search: the rest
+//! are merged and the response is flagged `partial`. Only when no shard answers is the
+//! search an error.
+//!
+//! A search that finds nothing is relaxed here, on the merged results, so shards are
+//! asked not to relax their own: one shard's fallback would drown out the others' hits.
+
+use crate::blocklist::{BlockScope, Blocklist};
+use crate::curation::Curation;
+use crate::models::*;
+use crate::storage::DataPaths;
+use crate::{errors, fallback, personalization, synonyms};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use utoipa::ToSchema;
+
+/// Request header that keeps a search on the receiving node
+pub const SEARCH_SCOPE_HEADER: &str = "x-search-scope";
+
+/// Longest a coordinator waits for a shard when `SHARD_TIMEOUT_MS` is not set
+pub const DEFAULT_SHARD_TIMEOUT_MS: u64 = 2000;
+
+/// Shard nodes to fan searches out to
+#[derive(Clone, Debug)]
+pub struct ShardConfig {
+    /// Base URLs such as `http://shard-1:8000`
+    pub nodes: Vec<String>,
+    pub timeout: Duration,
+}
+
+impl ShardConfig {
+    /// Coordinator configuration from `SHARD_NODES` (comma-separated base URLs) and
+    /// `SHARD_TIMEOUT_MS`; `None` when the node isn't a coordinator
+    pub fn from_env() -> Option<Self> {
+        let nodes: Vec<String> = std::env::var("SHARD_NODES")
+            .ok()?
+            .split(',')
+            .map(|node| node.trim().trim_end_matches('/').to_string())
+            .filter(|node| !node.is_empty())
+            .collect();
+        if nodes.is_empty() {
+            return None;
+        }
+        let timeout_ms = std::env::var("SHARD_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_SHARD_TIMEOUT_MS);
+        Some(ShardConfig {
+            nodes,
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+}
+
+/// Whether a coordinator sent this request, so it must be handled on this node
+pub fn is_scoped_local(headers: &HeaderMap) -> bool {
+    headers
+        .get(SEARCH_SCOPE_HEADER)
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"local"))
+}
+
+/// A shard left out of the merged results
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct ShardFailure {
+    pub node: String,
+    pub error: String,
+}
+
+/// How the shards answered a coordinated search
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ShardSummary {
+    pub total: usize,
+    pub responded: usize,
+    /// True when any shard is missing from the results
+    pub partial: bool,
+    pub failures: Vec<ShardFailure>,
+}
+
+/// Search response from a coordinator: the merged results plus per-shard status
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct CoordinatedSearchResponse {
+    #[serde(flatten)]
+    pub response: SearchResponse,
+    pub shards: ShardSummary,
+}
+
+pub fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Scatter `search_request` to every shard and gather the best `limit` hits, relaxing
+/// the search if none is found
+pub async fn search(
+    config: &ShardConfig,
+    search_request: SearchRequest,
+) -> Result<CoordinatedSearchResponse, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = search_request.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let coordinated = search_once(config, search_request.clone()).await?;
+    if !fallback::applies(&search_request, &coordinated.response) {
+        return Ok(coordinated);
+    }
+    let data_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let synonyms = synonyms::current(&DataPaths::new(&data_dir));
+    for (relaxed, relaxation) in fallback::steps(&search_request, &synonyms) {
+        let mut relaxed_coordinated = search_once(config, relaxed).await?;
+        if !relaxed_coordinated.response.results.is_empty() {
+            fallback::finish(&mut relaxed_coordinated.response, &search_request, relaxation);
+            return Ok(relaxed_coordinated);
+        }
+    }
+    Ok(coordinated)
+}
+
+/// Scatter `search_request` to every shard and gather the best `limit` hits, exactly as asked
+async fn search_once(
+    config: &ShardConfig,
+    search_request: SearchRequest,
+) -> Result<CoordinatedSearchResponse, (StatusCode, Json<ErrorResponse>)> {
+
+    // The coordinator holds the session's clicks and its own curation rules, so it
+    // personalizes and curates the merged results from a wider window and shards rank
+    // without either
+    let limit = search_request.get_limit();
+    let depth = search_request.ranking_depth();
+    let profile = search_request
+        .personalization_session()
+        .and_then(|session_id| personalization::Profile::load(session_id, Instant::now()));
+    let data_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let loaded = Curation::load_shared(&data_paths, &search_request)
+        .and_then(|curation| Ok((curation, Blocklist::load_shared(&data_paths, BlockScope::Search)?)));
+    let (curation, blocklist) = loaded.map_err(errors::respond)?;
+    // Each shard ranks from the top down to the window, a page at a time, and the
+    // coordinator cuts the requested page from the merged ranking
+    let window = match profile {
+        Some(_) => depth * personalization::RERANK_WINDOW,
+        None => depth,
+    }
+    .max(curation.window(depth))
+    .min(MAX_SEARCH_DEPTH);
+    let mut shard_request = search_request.clone();
+    shard_request.session_id = None;
+    shard_request.curate = Some(false);
+    shard_request.cursor = None;
+    shard_request.fallback = Some(false);
+
+    let mut shards = JoinSet::new();
+    for (position, node) in config.nodes.iter().enumerate() {
+        let (node, search_request, timeout) = (node.clone(), shard_request.clone(), config.timeout);
+        shards.spawn(async move {
+            let answer = query_shard(&node, search_request, window, timeout).await;
+            (position, node, answer)
+        });
+    }
+    let mut answers = Vec::with_capacity(config.nodes.len());
+    while let Some(joined) = shards.join_next().await {
+        match joined {
+            Ok(answer) => answers.push(answer),
+            Err(e) => tracing::error!("Shard query task failed: {}", e),
+        }
+    }
+    answers.sort_by_key(|(position, _, _)| *position);
+
+    let (responses, mut failures) = split_answers(answers);
+    if responses.is_empty() {
+        let reasons: Vec<String> = failures.iter().map(|failure| format!("{}: {}", failure.node, failure.error)).collect();
+        let error_response = ErrorResponse::from(AppError::ShardsUnavailable {
+            message: reasons.join("; "),
+        });
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+    }
+
+    // Merge only shards scoring on the most common scale
+    let search_type = majority_search_type(&responses);
+    let mut comparable = Vec::with_capacity(responses.len());
+    for (node, response) in responses {
+        if response.search_type == search_type {
+            comparable.push(response);
+        } else {
+            failures.push(ShardFailure {
+                node,
+                error: format!("scores not comparable: search_type {} instead of {}", response.search_type, search_type),
+            });
+        }
+    }
+
+    // Shards expand from their own vocabularies; report every term used
+    let mut expanded_terms: Vec<String> = Vec::new();
+    for term in comparable.iter().flat_map(|response| &response.expanded_terms) {
+        if !expanded_terms.contains(term) {
+            expanded_terms.push(term.clone());
+        }
+    }
+
+    // Shards correct typos from their own vocabularies too; report the first correction
+    let corrected_query = comparable.iter().find_map(|response| response.corrected_query.clone());
+
+    // Shards leave out what their own blocklists hold; the coordinator applies its own too
+    let mut results = merge_results(comparable.into_iter().map(|response| response.results), window);
+    results.retain(|result| !blocklist.blocks(&result.review));
+    if let Some(profile) = &profile {
+        profile.rerank(&mut results);
+    }
+
+    // A pin never shows a blocked review or one the filters or query operators rule out
+    let parsed_query = crate::query_syntax::ParsedQuery::parse(&search_request.query);
+    let mut pinned = Vec::new();
+    for review_id in curation.pinned_ids() {
+        if let Some(review) = find_review(config, review_id).await {
+            if !blocklist.blocks(&review)
+                && search_request.filters.as_ref().is_none_or(|filters| filters.matches(&review))
+                && parsed_query.admits(&review, &search_request.fields)
+            {
+                pinned.push(review);
+            }
+        }
+    }
+    // Shards group their own products; a pin from another place in a product's ranking
+    // is counted as one more hit
+    let applied_rules = crate::search::grouped_curation(&search_request, &curation, &mut results, &pinned, depth);
+    let (results, next_cursor) = search_request.page(results);
+    for failure in &failures {
+        tracing::warn!("Shard {} left out of search results: {}", failure.node, failure.error);
+    }
+
+    Ok(CoordinatedSearchResponse {
+        response: SearchResponse {
+            success: true,
+            total_results: results.len(),
+            limit,
+            offset: search_request.get_offset(),
+            next_cursor,
+            query: search_request.query,
+            results,
+            filters: search_request.filters,
+            search_type,
+            corrected_query,
+            expanded_terms,
+            personalized: profile.is_some(),
+            applied_rules,
+            parsed_query: Some(parsed_query).filter(crate::query_syntax::ParsedQuery::has_operators),
+            fallback: None,
+        },
+        shards: ShardSummary {
+            total: config.nodes.len(),
+            responded: config.nodes.len() - failures.len(),
+            partial: !failures.is_empty(),
+            failures,
+        },
+    })
+}
+
+type ShardAnswer = (usize, String, Result<SearchResponse, String>);
+
+fn split_answers(answers: Vec<ShardAnswer>) -> (Vec<(String, SearchResponse)>, Vec<ShardFailure>) {
+    let mut responses = Vec::new();
+    let mut failures = Vec::new();
+    for (_, node, answer) in answers {
+        match answer {
+            Ok(response) => responses.push((node, response)),
+            Err(error) => failures.push(ShardFailure { node, error }),
+        }
+    }
+    (responses, failures)
+}
+
+/// The search type most shards report, ties going to the first shard listed
+fn majority_search_type(responses: &[(String, SearchResponse)]) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, response) in responses {
+        *counts.entry(response.search_type.as_str()).or_default() += 1;
+    }
+    let most = counts.values().copied().max().unwrap_or(0);
+    responses
+        .iter()
+        .map(|(_, response)| response.search_type.as_str())
+        .find(|search_type| counts[search_type] == most)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A shard's best `window` hits, fetched a page of at most `MAX_SEARCH_LIMIT` at a time
+async fn query_shard(node: &str, mut search_request: SearchRequest, window: usize, timeout: Duration) -> Result<SearchResponse, String> {
+    search_request.offset = Some(0);
+    search_request.limit = Some(window.min(MAX_SEARCH_LIMIT));
+    let mut ranked = query_shard_page(node, &search_request, timeout).await?;
+    while ranked.next_cursor.is_some() && ranked.results.len() < window {
+        search_request.offset = Some(ranked.results.len());
+        search_request.limit = Some((window - ranked.results.len()).min(MAX_SEARCH_LIMIT));
+        let page = query_shard_page(node, &search_request, timeout).await?;
+        ranked.results.extend(page.results);
+        ranked.next_cursor = page.next_cursor;
+    }
+    Ok(ranked)
+}
+
+async fn query_shard_page(node: &str, search_request: &SearchRequest, timeout: Duration) -> Result<SearchResponse, String> {
+    let response = client()
+        .post(format!("{}/v1/search", node))
+        .header(SEARCH_SCOPE_HEADER, "local")
+        .timeout(timeout)
+        .json(search_request)
+        .send()
+        .await
+        .map_err(|e| describe(e, timeout))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let message = match response.json::<ErrorResponse>().await {
+            Ok(error_response) => error_response.message,
+            Err(_) => "no error details".to_string(),
+        };
+        return Err(format!("HTTP {}: {}", status.as_u16(), message));
+    }
+    response.json::<SearchResponse>().await.map_err(|e| describe(e, timeout))
+}
+
+/// A live review from whichever shard stores it, for coordinator features that only
+/// know a review's id. Shards that fail or time out count as not having it.
+pub async fn find_review(config: &ShardConfig, review_id: &str) -> Option<ReviewMetadata> {
+    #[derive(serde::Deserialize)]
+    struct Found {
+        review: ReviewMetadata,
+    }
+
+    let mut shards = JoinSet::new();
+    for node in &config.nodes {
+        let url = format!("{}/v1/reviews/{}", node, review_id);
+        let timeout = config.timeout;
+        shards.spawn(async move {
+            let response = client().get(url).timeout(timeout).send().await.ok()?;
+            match response.status().is_success() {
+                true => response.json::<Found>().await.ok().map(|found| found.review),
+                false => None,
+            }
+        });
+    }
+    while let Some(joined) = shards.join_next().await {
+        if let Ok(Some(review)) = joined {
+            return Some(review);
+        }
+    }
+    None
+}
+
+fn describe(error: reqwest::Error, timeout: Duration) -> String {
+    if error.is_timeout() {
+        format!("no answer within {} ms", timeout.as_millis())
+    } else {
+        error.to_string()
+    }
+}
+
+/// Best `limit` hits across shards, highest score first. A review returned by more
+/// than one shard (e.g. a replica) is kept once, with its best score.
+pub fn merge_results(shard_results: impl IntoIterator<Item = Vec<SearchResult>>, limit: usize) -> Vec<SearchResult> {
+    let mut best: HashMap<String, SearchResult> = HashMap::new();
+    for result in shard_results.into_iter().flatten() {
+        match best.get(&result.review.id) {
+            Some(kept) if kept.similarity_score >= result.similarity_score => {}
+            _ => {
+                best.insert(result.review.id.clone(), result);
+            }
+        }
+    }
+
+    let mut merged: Vec<SearchResult> = best.into_values().collect();
+    // Ties break on id so the order doesn't depend on which shard answered first
+    merged.sort_by(SearchResult::rank_order);
+    merged.truncate(limit);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+
+    fn result(id: &str, similarity_score: f32) -> SearchResult {
+        let mut review = ReviewData {
+            title: "Shard".to_string(),
+            body: "A review body long enough to store.".to_string(),
+            product_id: "shard".to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+        .to_metadata(0)
+        .unwrap();
+        review.id = id.to_string();
+        SearchResult { review, similarity_score, product_hits: None, explanation: None, collection: None }
+    }
+
+    /// A fake shard answering every search with `results` after `delay`
+    async fn shard(results: Vec<SearchResult>, search_type: &'static str, delay: Duration) -> String {
+        let app = Router::new().route(
+            "/v1/search",
+            post(move || async move {
+                tokio::time::sleep(delay).await;
+                Json(SearchResponse {
+                    success: true,
+                    query: "kettle".to_string(),
+                    total_results: results.len(),
+                    results,
+                    limit: 10,
+                    offset: 0,
+                    next_cursor: None,
+                    filters: None,
+                    search_type: search_type.to_string(),
+                    corrected_query: None,
+                    expanded_terms: Vec::new(),
+                    personalized: false,
+                    applied_rules: Vec::new(),
+                    parsed_query: None,
+                    fallback: None,
+                })
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_scatter_gather_merges_and_flags_partial_results() {
+        let config = ShardConfig {
+            nodes: vec![
+                shard(vec![result("a", 0.9), result("c", 0.4)], "text_similarity", Duration::ZERO).await,
+                shard(vec![result("b", 0.7), result("a", 0.6)], "text_similarity", Duration::ZERO).await,
+                shard(vec![result("slow", 1.0)], "text_similarity", Duration::from_secs(5)).await,
+                shard(vec![result("other", 1.0)], "vector_similarity", Duration::ZERO).await,
+            ],
+            timeout: Duration::from_millis(200),
+        };
+        let search_request = SearchRequest {
+            query: "kettle".to_string(),
+            limit: Some(3),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+
+        let coordinated = search(&config, search_request).await.unwrap();
+        let ids: Vec<&str> = coordinated.response.results.iter().map(|hit| hit.review.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(coordinated.response.results[0].similarity_score, 0.9);
+        assert_eq!(coordinated.response.search_type, "text_similarity");
+
+        let shards = coordinated.shards;
+        assert!(shards.partial);
+        assert_eq!((shards.total, shards.responded), (4, 2));
+        assert_eq!(shards.failures[0].node, config.nodes[2]);
+        assert!(shards.failures[0].error.starts_with("no answer within 200 ms"), "{}", shards.failures[0].error);
+        assert!(shards.failures[1].error.starts_with("scores not comparable"));
+
+        // With every shard down the search fails
+        let config = ShardConfig {
+            nodes: vec!["http://127.0.0.1:9".to_string()],
+            timeout: Duration::from_millis(200),
+        };
+        let search_request = SearchRequest {
+            query: "kettle".to_string(),
+            limit: None,
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+        let (status, error) = search(&config, search_request).await.unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.0.code, crate::error_catalog::ErrorCode::ShardsUnavailable);
+    }
+}
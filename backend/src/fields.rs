@@ -0,0 +1,215 @@
+//! Field selection for the search and listing routes, so a client that only shows a
+//! title and a score (an autocomplete box, an embedded widget) doesn't download every
+//! review body.
+//!
+//! `?fields=id,title,score` names the review fields to keep. Every review in the
+//! response's `reviews` or `results` array is cut down to those; a search hit keeps
+//! its `review` object with the selected fields, plus `similarity_score` when `score`
+//! is asked for and `product_hits` when that is. The rest of the response (totals,
+//! cursors, echoed filters) is left as it is. Handlers know nothing of this: the
+//! middleware edits their JSON responses on the way out, before MessagePack encoding.
+
+use crate::models::*;
+use axum::{
+    body::Body,
+    extract::{Query, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::IntoParams;
+
+/// Review fields a client may select
+pub const REVIEW_FIELDS: &[&str] = &[
+    "id",
+    "title",
+    "body",
+    "product_id",
+    "rating",
+    "timestamp",
+    "vector_index",
+    "reviewer_id",
+    "source",
+    "license",
+    "attribution",
+    "updated_at",
+    "helpful_votes",
+    "unhelpful_votes",
+    "embedding_status",
+];
+
+/// Search hit fields a client may select, besides the review's
+pub const HIT_FIELDS: &[&str] = &["score", "similarity_score", "product_hits"];
+
+/// Arrays of the response whose entries are cut down
+const SELECTED_ARRAYS: &[&str] = &["reviews", "results"];
+
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQuery {
+    /// Comma-separated fields to return for each review, e.g. `id,title,score`; all when absent
+    pub fields: Option<String>,
+}
+
+/// The fields a request selected
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSelection {
+    fields: Vec<String>,
+}
+
+impl FieldSelection {
+    pub fn parse(fields: &str) -> Result<Self, ValidationError> {
+        let mut selected: Vec<String> = Vec::new();
+        for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            if !REVIEW_FIELDS.contains(&field) && !HIT_FIELDS.contains(&field) {
+                return Err(ValidationError::InvalidValue {
+                    field: "fields".to_string(),
+                    reason: format!(
+                        "unknown field {:?}; expected any of {}, {}",
+                        field,
+                        REVIEW_FIELDS.join(", "),
+                        HIT_FIELDS.join(", ")
+                    ),
+                });
+            }
+            if !selected.iter().any(|known| known == field) {
+                selected.push(field.to_string());
+            }
+        }
+        if selected.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "fields".to_string(),
+                reason: "name at least one field".to_string(),
+            });
+        }
+        Ok(FieldSelection { fields: selected })
+    }
+
+    fn selects(&self, field: &str) -> bool {
+        self.fields.iter().any(|selected| selected == field)
+    }
+
+    /// The selected fields of one review object
+    fn review(&self, review: Map<String, Value>) -> Value {
+        Value::Object(review.into_iter().filter(|(key, _)| self.selects(key)).collect())
+    }
+
+    /// Cut down every entry of the response's review arrays
+    pub fn apply(&self, response: &mut Value) {
+        for name in SELECTED_ARRAYS {
+            let Some(Value::Array(entries)) = response.get_mut(*name) else {
+                continue;
+            };
+            for entry in entries.iter_mut() {
+                let Value::Object(object) = std::mem::take(entry) else {
+                    continue;
+                };
+                *entry = match object.get("review") {
+                    Some(Value::Object(_)) => self.hit(object),
+                    _ => self.review(object),
+                };
+            }
+        }
+    }
+
+    /// A search hit: its review cut down, its score and hit count kept if selected
+    fn hit(&self, mut hit: Map<String, Value>) -> Value {
+        let mut selected = Map::new();
+        if let Some(Value::Object(review)) = hit.remove("review") {
+            selected.insert("review".to_string(), self.review(review));
+        }
+        for (key, value) in hit {
+            let wanted = match key.as_str() {
+                "similarity_score" => self.selects("score") || self.selects("similarity_score"),
+                key => self.selects(key),
+            };
+            if wanted {
+                selected.insert(key, value);
+            }
+        }
+        Value::Object(selected)
+    }
+}
+
+/// Middleware: apply `?fields=` to successful JSON responses
+pub async fn select(request: Request, next: Next) -> Response {
+    let fields = Query::<FieldsQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.fields);
+    let Some(fields) = fields else {
+        return next.run(request).await;
+    };
+    let selection = match FieldSelection::parse(&fields) {
+        Ok(selection) => selection,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
+        }
+    };
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read a response body for field selection: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(mut value) => {
+            selection.apply(&mut value);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(value.to_string()))
+        }
+        Err(_) => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_selection_cuts_reviews_and_hits() {
+        let selection = FieldSelection::parse("id, title,score,title").unwrap();
+        let mut search = json!({
+            "success": true,
+            "total_results": 1,
+            "results": [{
+                "review": { "id": "r1", "title": "Quiet", "body": "Long text", "rating": 5 },
+                "similarity_score": 0.9,
+                "product_hits": 3
+            }]
+        });
+        selection.apply(&mut search);
+        assert_eq!(
+            search,
+            json!({
+                "success": true,
+                "total_results": 1,
+                "results": [{ "review": { "id": "r1", "title": "Quiet" }, "similarity_score": 0.9 }]
+            })
+        );
+
+        let mut listing = json!({ "reviews": [{ "id": "r1", "title": "Quiet", "body": "Long text" }], "page": 1 });
+        FieldSelection::parse("body").unwrap().apply(&mut listing);
+        assert_eq!(listing, json!({ "reviews": [{ "body": "Long text" }], "page": 1 }));
+
+        assert!(FieldSelection::parse("id,password").unwrap_err().to_string().contains("password"));
+        assert!(FieldSelection::parse(" , ").is_err());
+    }
+}
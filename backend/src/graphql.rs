@@ -0,0 +1,435 @@
+//! GraphQL endpoint (`/graphql`) for clients that want to pick their fields, e.g. only
+//! titles and scores, or combine a listing with product aggregates in one request.
+//!
+//! Reads in one request share a snapshot of the live reviews, taken under the shared
+//! data lock the first time a field needs it, so a listing and the aggregates beside
+//! it always agree. `search` runs the same search as `POST /search`, including the
+//! shard fan-out on a coordinator; the other fields read this node's data only.
+
+use crate::analytics;
+use crate::explain::ScoreExplanation;
+use crate::language;
+use crate::sentiment;
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::AppState;
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Enum, ErrorExtensions, Object, Schema, SimpleObject, ID,
+};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::OnceCell;
+
+/// Deepest nesting a query may use, e.g. `reviews { items { product { reviews { ... } } } }`
+pub const MAX_QUERY_DEPTH: usize = 8;
+
+/// Most fields a query may resolve, counting list items as the list's page size
+pub const MAX_QUERY_COMPLEXITY: usize = 2000;
+
+pub type ReviewSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// The schema, built once per process
+pub fn schema() -> &'static ReviewSchema {
+    static SCHEMA: OnceLock<ReviewSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(MAX_QUERY_DEPTH)
+            .limit_complexity(MAX_QUERY_COMPLEXITY)
+            .finish()
+    })
+}
+
+/// Run one GraphQL request against this node's data
+pub async fn execute(state: AppState, request: async_graphql::Request) -> async_graphql::Response {
+    schema().execute(request.data(state).data(Snapshot::default())).await
+}
+
+/// Live reviews read once per request
+#[derive(Default)]
+struct Snapshot {
+    reviews: OnceCell<Arc<Vec<ReviewMetadata>>>,
+}
+
+impl Snapshot {
+    async fn reviews(ctx: &Context<'_>) -> async_graphql::Result<Arc<Vec<ReviewMetadata>>> {
+        let snapshot = ctx.data_unchecked::<Snapshot>();
+        let reviews = snapshot.reviews.get_or_try_init(|| async { read_live_reviews().map(Arc::new) }).await?;
+        Ok(reviews.clone())
+    }
+}
+
+fn read_live_reviews() -> async_graphql::Result<Vec<ReviewMetadata>> {
+    let data_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    data_paths.ensure_directories().map_err(app_error)?;
+
+    let _lock = FileLock::acquire_shared(&data_paths.lock_file, "graphql").map_err(app_error)?;
+    JsonlStorage::new(&data_paths.reviews_jsonl).read_live_reviews().map_err(app_error)
+}
+
+/// A GraphQL error carrying the API error code in `extensions.code`
+fn api_error(error_response: ErrorResponse) -> async_graphql::Error {
+    let code = serde_json::to_value(error_response.code)
+        .ok()
+        .and_then(|code| code.as_str().map(str::to_string))
+        .unwrap_or_default();
+    async_graphql::Error::new(error_response.message).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+fn app_error(error: AppError) -> async_graphql::Error {
+    api_error(ErrorResponse::from(error))
+}
+
+fn validation_error(error: ValidationError) -> async_graphql::Error {
+    app_error(AppError::Validation(error))
+}
+
+/// Page `items` after validating the pagination arguments
+fn paginate(reviews: Vec<ReviewMetadata>, pagination: &ListReviewsQuery) -> ReviewPage {
+    let page_size = pagination.get_page_size();
+    let total_count = reviews.len();
+    ReviewPage {
+        items: reviews.into_iter().skip(pagination.offset()).take(page_size).map(Review).collect(),
+        page: pagination.get_page(),
+        page_size,
+        total_count,
+        total_pages: total_count.div_ceil(page_size),
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A live review by id
+    async fn review(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<Review>> {
+        let reviews = Snapshot::reviews(ctx).await?;
+        Ok(reviews.iter().find(|review| review.id == *id).cloned().map(Review))
+    }
+
+    /// Live reviews in storage order
+    #[graphql(complexity = "page_size.unwrap_or(20) * child_complexity")]
+    async fn reviews(
+        &self,
+        ctx: &Context<'_>,
+        page: Option<usize>,
+        page_size: Option<usize>,
+    ) -> async_graphql::Result<ReviewPage> {
+        let pagination = ListReviewsQuery { page, page_size };
+        pagination.validate().map_err(validation_error)?;
+        let reviews = Snapshot::reviews(ctx).await?;
+        Ok(paginate(reviews.to_vec(), &pagination))
+    }
+
+    /// A product with at least one live review
+    async fn product(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<Product>> {
+        let product = Product::load(ctx, id.to_string()).await?;
+        Ok((!product.reviews.is_empty()).then_some(product))
+    }
+
+    /// Same search as `POST /search`
+    #[graphql(complexity = "limit.unwrap_or(10) * child_complexity")]
+    #[allow(clippy::too_many_arguments)]
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        cursor: Option<String>,
+        product_id: Option<String>,
+        min_rating: Option<u8>,
+        max_rating: Option<u8>,
+        date_from: Option<DateTime<Utc>>,
+        date_to: Option<DateTime<Utc>>,
+        sentiment: Option<Sentiment>,
+        language: Option<Language>,
+        #[graphql(default)] rank_by_helpfulness: bool,
+        #[graphql(default)] group_by_product: bool,
+        #[graphql(default)] explain: bool,
+        min_score: Option<f32>,
+        fallback: Option<bool>,
+        collections: Option<Vec<String>>,
+    ) -> async_graphql::Result<SearchResults> {
+        let filters = SearchFilters {
+            product_id,
+            min_rating,
+            max_rating,
+            date_from,
+            date_to,
+            sentiment: sentiment.map(Into::into),
+            language: language.map(Into::into),
+        };
+        let filters = (filters != SearchFilters::default()).then_some(filters);
+        let search_request = SearchRequest {
+            query,
+            limit,
+            offset,
+            cursor,
+            filters,
+            rank_by_helpfulness,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product,
+            fields: Vec::new(),
+            explain,
+            min_score,
+            fallback,
+            collections: collections.unwrap_or_default(),
+        };
+
+        let state = ctx.data_unchecked::<AppState>();
+        let to_error = |(_, Json(error_response)): (StatusCode, Json<ErrorResponse>)| api_error(error_response);
+        let (response, partial) = match state.shards.search_config(&HeaderMap::new()) {
+            Some(shard_config) => {
+                let coordinated = crate::fanout::search(&shard_config, search_request).await.map_err(to_error)?;
+                (coordinated.response, coordinated.shards.partial)
+            }
+            None => {
+                let response = tokio::task::spawn_blocking(move || crate::run_search(search_request))
+                    .await
+                    .map_err(|e| async_graphql::Error::new(e.to_string()))?
+                    .map_err(to_error)?;
+                (response, false)
+            }
+        };
+
+        Ok(SearchResults {
+            total_results: response.total_results,
+            next_cursor: response.next_cursor,
+            search_type: response.search_type,
+            partial,
+            fallback: response.fallback.map(async_graphql::Json),
+            results: response
+                .results
+                .into_iter()
+                .map(|hit| SearchResult {
+                    score: hit.similarity_score,
+                    product_hits: hit.product_hits,
+                    explanation: hit.explanation.map(async_graphql::Json),
+                    collection: hit.collection,
+                    review: Review(hit.review),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// A stored review
+pub struct Review(ReviewMetadata);
+
+#[Object]
+impl Review {
+    async fn id(&self) -> ID {
+        ID(self.0.id.clone())
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn body(&self) -> &str {
+        &self.0.body
+    }
+
+    async fn product_id(&self) -> &str {
+        &self.0.product_id
+    }
+
+    async fn rating(&self) -> u8 {
+        self.0.rating
+    }
+
+    async fn reviewer_id(&self) -> Option<&str> {
+        self.0.reviewer_id.as_deref()
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.0.updated_at
+    }
+
+    async fn vector_index(&self) -> usize {
+        self.0.vector_index
+    }
+
+    async fn helpful_votes(&self) -> u32 {
+        self.0.helpful_votes
+    }
+
+    async fn unhelpful_votes(&self) -> u32 {
+        self.0.unhelpful_votes
+    }
+
+    /// Lower bound of the Wilson interval on the helpful share of votes
+    async fn helpfulness(&self) -> f64 {
+        self.0.helpfulness()
+    }
+
+    /// How the text reads; classified now for reviews stored untagged
+    async fn sentiment(&self) -> Sentiment {
+        sentiment::of(&self.0).into()
+    }
+
+    /// Language of the text, detected now for reviews stored untagged; null when unknown
+    async fn language(&self) -> Option<Language> {
+        language::of(&self.0).map(Into::into)
+    }
+
+    /// Id of the review of the same product this one nearly repeats, flagged at ingest
+    async fn duplicate_of(&self) -> Option<ID> {
+        self.0.duplicate_of.clone().map(ID)
+    }
+
+    /// The reviewed product, with its aggregates
+    async fn product(&self, ctx: &Context<'_>) -> async_graphql::Result<Product> {
+        Product::load(ctx, self.0.product_id.clone()).await
+    }
+}
+
+/// How a review's text reads
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "sentiment::Sentiment")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+/// The language a review is written in
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "language::Language")]
+pub enum Language {
+    English,
+    Thai,
+    Chinese,
+    Japanese,
+    Korean,
+}
+
+/// A product and its live reviews
+pub struct Product {
+    id: String,
+    reviews: Vec<ReviewMetadata>,
+    stats: analytics::ReviewStats,
+}
+
+impl Product {
+    async fn load(ctx: &Context<'_>, id: String) -> async_graphql::Result<Self> {
+        let reviews: Vec<ReviewMetadata> =
+            Snapshot::reviews(ctx).await?.iter().filter(|review| review.product_id == id).cloned().collect();
+        let stats = analytics::review_stats(&reviews);
+        Ok(Product { id, reviews, stats })
+    }
+}
+
+/// Reviews with one rating
+#[derive(SimpleObject)]
+pub struct RatingCount {
+    pub rating: u8,
+    pub count: usize,
+}
+
+#[Object]
+impl Product {
+    async fn id(&self) -> ID {
+        ID(self.id.clone())
+    }
+
+    async fn review_count(&self) -> usize {
+        self.stats.review_count
+    }
+
+    /// Null without reviews
+    async fn average_rating(&self) -> Option<f64> {
+        self.stats.average_rating
+    }
+
+    /// Counts for every rating 1-5, including zeros
+    async fn rating_histogram(&self) -> Vec<RatingCount> {
+        self.stats
+            .rating_histogram
+            .iter()
+            .map(|(rating, count)| RatingCount { rating: *rating, count: *count })
+            .collect()
+    }
+
+    async fn first_review_at(&self) -> Option<DateTime<Utc>> {
+        self.stats.first_review_at
+    }
+
+    async fn last_review_at(&self) -> Option<DateTime<Utc>> {
+        self.stats.last_review_at
+    }
+
+    /// The product's reviews, sorted as by `GET /products/{id}/reviews`
+    #[graphql(complexity = "page_size.unwrap_or(20) * child_complexity")]
+    async fn reviews(
+        &self,
+        sort: Option<String>,
+        order: Option<String>,
+        page: Option<usize>,
+        page_size: Option<usize>,
+    ) -> async_graphql::Result<ReviewPage> {
+        let product_query = ProductReviewsQuery { sort, order, page, page_size };
+        product_query.validate().map_err(validation_error)?;
+        let mut reviews = self.reviews.clone();
+        product_query.sort_reviews(&mut reviews);
+        Ok(paginate(reviews, &product_query.pagination()))
+    }
+}
+
+/// One page of reviews
+#[derive(SimpleObject)]
+pub struct ReviewPage {
+    pub items: Vec<Review>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_count: usize,
+    pub total_pages: usize,
+}
+
+/// A search hit
+#[derive(SimpleObject)]
+pub struct SearchResult {
+    /// Similarity score, 0-1
+    pub score: f32,
+    /// With `groupByProduct`, how many of the product's reviews matched
+    pub product_hits: Option<usize>,
+    /// With `explain`, how the score was reached, as in the REST response
+    pub explanation: Option<async_graphql::Json<ScoreExplanation>>,
+    /// With `collections`, the collection the review belongs to
+    pub collection: Option<String>,
+    pub review: Review,
+}
+
+/// Search hits, best first
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    pub total_results: usize,
+    /// Pass as `cursor` for the next page; null on the last page
+    pub next_cursor: Option<String>,
+    pub search_type: String,
+    /// Set by a coordinator when some shards did not answer
+    pub partial: bool,
+    /// How the search was relaxed, when it found nothing as asked
+    pub fallback: Option<async_graphql::Json<crate::fallback::SearchFallback>>,
+}
+
+#[ComplexObject]
+impl SearchResults {
+    /// True when nothing matched
+    async fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
@@ -0,0 +1,409 @@
+//! gRPC server alongside the HTTP API (the `grpc` feature), for internal services that
+//! would rather not pay for JSON on the wire.
+//!
+//! Each RPC calls the same service as its HTTP endpoint: the core ingest service for
+//! creates and bulk uploads, through the same wrappers that count them and publish
+//! their events, and `run_search` (or the shard fan-out on a coordinator) for
+//! searches. Validation, locking, idempotency, embedding and events therefore behave
+//! identically. The rate limiter and request body limit are HTTP middleware and do
+//! not apply here; tonic's own 4 MiB message limit does.
+//!
+//! Errors map to gRPC status codes, with the API's error code in the `error-code`
+//! trailer so callers can branch on the same codes as HTTP clients.
+
+use crate::models::*;
+use crate::storage::DataPaths;
+use crate::{errors, extract, ingest, routing, AppState};
+use axum::http::{HeaderMap, HeaderValue, Method};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::net::SocketAddr;
+use tonic::{Code, Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("reviews.v1");
+}
+
+use proto::review_service_server::{ReviewService, ReviewServiceServer};
+
+/// Port of the gRPC server when `GRPC_PORT` is not set
+pub const DEFAULT_GRPC_PORT: u16 = 50051;
+
+/// Port of the gRPC server, from `GRPC_PORT`
+pub fn port() -> u16 {
+    std::env::var("GRPC_PORT").ok().and_then(|port| port.parse().ok()).unwrap_or(DEFAULT_GRPC_PORT)
+}
+
+/// Serve gRPC on `GRPC_PORT` in the background, sharing `state` with the HTTP API
+pub fn spawn(state: AppState) {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port()));
+    println!("🔌 gRPC ReviewService listening on {}", addr);
+
+    tokio::spawn(async move {
+        let server = tonic::transport::Server::builder()
+            .add_service(ReviewServiceServer::new(ReviewGrpc { state }))
+            .serve(addr);
+        if let Err(e) = server.await {
+            tracing::error!("gRPC server stopped: {}", e);
+        }
+    });
+}
+
+pub struct ReviewGrpc {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl ReviewService for ReviewGrpc {
+    async fn create_review(
+        &self,
+        request: Request<proto::CreateReviewRequest>,
+    ) -> Result<Response<proto::CreateReviewResponse>, Status> {
+        let request = request.into_inner();
+        let review_data = review_data(request.review.ok_or_else(|| missing_field("review"))?);
+
+        let mut headers = HeaderMap::new();
+        if let Some(key) = request.idempotency_key {
+            let key = HeaderValue::from_str(&key)
+                .map_err(|_| Status::invalid_argument("idempotency_key must be visible ASCII"))?;
+            headers.insert(crate::idempotency::IDEMPOTENCY_KEY_HEADER, key);
+        }
+
+        let idempotency_key = extract::idempotency_key(&headers)
+            .map_err(|e| to_status(errors::respond(AppError::Validation(e))))?;
+        review_data
+            .validate()
+            .map_err(|e| to_status(errors::respond(AppError::Validation(e))))?;
+
+        // A coordinator relays the review to the shard owning its product, as over HTTP
+        let (created, replayed): (CreatedReview, bool) =
+            match self.state.shards.write_owner(&headers, &review_data.product_id) {
+                Some(owner) => {
+                    let body = serde_json::json!(review_data);
+                    let response = routing::forward(&owner, Method::POST, "/reviews", &headers, Some(&body))
+                        .await
+                        .map_err(to_status)?;
+                    let replayed = response.headers().contains_key(crate::idempotency::IDEMPOTENT_REPLAYED_HEADER);
+                    (response_body(response).await?, replayed)
+                }
+                None => {
+                    let created = crate::store_review(&self.state, &data_paths(), &review_data, idempotency_key)
+                        .map_err(|e| to_status(errors::respond(e)))?;
+                    let replayed = matches!(created, ingest::Created::Replayed { .. });
+                    let response = serde_json::from_value(created.response().clone())
+                        .map_err(|e| Status::internal(e.to_string()))?;
+                    (response, replayed)
+                }
+            };
+
+        Ok(Response::new(proto::CreateReviewResponse {
+            review_id: created.review_id,
+            vector_index: created.vector_index as u64,
+            timestamp: created.timestamp.to_rfc3339(),
+            replayed,
+        }))
+    }
+
+    async fn bulk_upload(
+        &self,
+        request: Request<proto::BulkUploadRequest>,
+    ) -> Result<Response<proto::BulkUploadResponse>, Status> {
+        let request = request.into_inner();
+        let reviews: Vec<ReviewData> = request.reviews.into_iter().map(review_data).collect();
+        let upload_query = BulkUploadQuery {
+            file_name: request.file_name,
+            source: request.source,
+            license: request.license,
+            attribution: request.attribution,
+        };
+
+        // A coordinator splits the upload by owning shard, as over HTTP. With more than one
+        // shard there is a job per shard, so the response names none.
+        let headers = HeaderMap::new();
+        if let Some(parts) = self.state.shards.split_upload(&headers, &reviews) {
+            let routed = routing::forward_upload(parts, "/reviews/bulk", &upload_query, &headers)
+                .await
+                .map_err(|e| to_status(errors::respond(e)))?;
+            let (job_id, batch_id) = match routed.shards.as_slice() {
+                [shard] => (shard.job_id.clone().unwrap_or_default(), shard.batch_id.clone()),
+                _ => (String::new(), None),
+            };
+            return Ok(Response::new(proto::BulkUploadResponse {
+                total_processed: routed.result.total_processed as u64,
+                successful: routed.result.successful as u64,
+                failed: routed.result.failed.into_iter().map(bulk_error).collect(),
+                job_id,
+                batch_id,
+            }));
+        }
+
+        let uploaded = crate::store_upload(&self.state, &data_paths(), reviews, upload_query)
+            .map_err(|e| to_status(errors::respond(e)))?;
+
+        Ok(Response::new(proto::BulkUploadResponse {
+            total_processed: uploaded.result.total_processed as u64,
+            successful: uploaded.result.successful as u64,
+            failed: uploaded.result.failed.into_iter().map(bulk_error).collect(),
+            job_id: uploaded.job_id.unwrap_or_default(),
+            batch_id: uploaded.batch_id,
+        }))
+    }
+
+    async fn search(&self, request: Request<proto::SearchRequest>) -> Result<Response<proto::SearchResponse>, Status> {
+        let request = request.into_inner();
+        let filters = request
+            .filters
+            .map(search_filters)
+            .transpose()
+            .map_err(|error| to_status(errors::respond(AppError::Validation(error))))?;
+        let search_request = SearchRequest {
+            query: request.query,
+            limit: request.limit.map(|limit| limit as usize),
+            offset: request.offset.map(|offset| offset as usize),
+            cursor: request.cursor,
+            filters,
+            rank_by_helpfulness: request.rank_by_helpfulness,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: request.min_score,
+            fallback: request.fallback,
+            collections: request.collections,
+        };
+
+        let (response, partial) = match self.state.shards.search_config(&HeaderMap::new()) {
+            Some(shard_config) => {
+                let coordinated = crate::fanout::search(&shard_config, search_request).await.map_err(to_status)?;
+                (coordinated.response, coordinated.shards.partial)
+            }
+            None => {
+                let response = tokio::task::spawn_blocking(move || crate::run_search(search_request))
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?
+                    .map_err(to_status)?;
+                (response, false)
+            }
+        };
+
+        Ok(Response::new(proto::SearchResponse {
+            results: response
+                .results
+                .into_iter()
+                .map(|hit| proto::SearchHit {
+                    review: Some(review(hit.review)),
+                    similarity_score: hit.similarity_score,
+                    collection: hit.collection,
+                })
+                .collect(),
+            search_type: response.search_type,
+            partial,
+            next_cursor: response.next_cursor,
+            relaxations: response
+                .fallback
+                .into_iter()
+                .flat_map(|fallback| fallback.relaxations)
+                .map(|relaxation| relaxation.name().to_string())
+                .collect(),
+        }))
+    }
+}
+
+/// The fields of the create response the RPC returns
+#[derive(Deserialize)]
+struct CreatedReview {
+    review_id: String,
+    vector_index: usize,
+    timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// The data directory the HTTP API serves
+fn data_paths() -> DataPaths {
+    let data_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    DataPaths::new(&data_dir)
+}
+
+/// Out-of-range ratings stay out of range, so validation rejects them as over HTTP
+fn clamp_rating(rating: u32) -> u8 {
+    rating.min(u8::MAX as u32) as u8
+}
+
+fn review_data(input: proto::ReviewInput) -> ReviewData {
+    ReviewData {
+        title: input.title,
+        body: input.body,
+        product_id: input.product_id,
+        rating: clamp_rating(input.rating),
+        reviewer_id: input.reviewer_id,
+        source: input.source,
+        license: input.license,
+        attribution: input.attribution,
+    }
+}
+
+fn review(review: ReviewMetadata) -> proto::Review {
+    proto::Review {
+        id: review.id,
+        title: review.title,
+        body: review.body,
+        product_id: review.product_id,
+        rating: review.rating as u32,
+        timestamp: review.timestamp.to_rfc3339(),
+        vector_index: review.vector_index as u64,
+        reviewer_id: review.reviewer_id,
+        source: review.source,
+        license: review.license,
+        attribution: review.attribution,
+        helpful_votes: review.helpful_votes,
+        unhelpful_votes: review.unhelpful_votes,
+        sentiment: review.sentiment.map(|sentiment| sentiment.name().to_string()),
+        language: review.language.map(|language| language.code().to_string()),
+        duplicate_of: review.duplicate_of,
+    }
+}
+
+fn bulk_error(failure: BulkError) -> proto::BulkError {
+    proto::BulkError {
+        line_number: failure.line_number as u64,
+        error: failure.error,
+    }
+}
+
+fn search_filters(filters: proto::SearchFilters) -> Result<SearchFilters, ValidationError> {
+    Ok(SearchFilters {
+        product_id: filters.product_id,
+        min_rating: filters.min_rating.map(clamp_rating),
+        max_rating: filters.max_rating.map(clamp_rating),
+        date_from: filters.date_from.map(|date| SearchFilters::parse_date("date_from", &date, false)).transpose()?,
+        date_to: filters.date_to.map(|date| SearchFilters::parse_date("date_to", &date, true)).transpose()?,
+        sentiment: filters.sentiment.map(|sentiment| SearchFilters::parse_sentiment(&sentiment)).transpose()?,
+        language: filters.language.map(|language| SearchFilters::parse_language(&language)).transpose()?,
+    })
+}
+
+fn missing_field(field: &str) -> Status {
+    to_status(errors::respond(AppError::Validation(ValidationError::MissingField {
+        field: field.to_string(),
+    })))
+}
+
+/// Decode the owning shard's JSON response to a relayed create, which may be an error
+async fn response_body<T: DeserializeOwned>(response: axum::response::Response) -> Result<T, Status> {
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+    if !status.is_success() {
+        return Err(match serde_json::from_slice::<ErrorResponse>(&bytes) {
+            Ok(error_response) => to_status((status, Json(error_response))),
+            Err(_) => Status::unknown(format!("HTTP {}", status)),
+        });
+    }
+    serde_json::from_slice(&bytes).map_err(|e| Status::internal(e.to_string()))
+}
+
+/// gRPC status for an HTTP error, carrying the API error code as metadata
+fn to_status((status, Json(error_response)): (axum::http::StatusCode, Json<ErrorResponse>)) -> Status {
+    let code = match status.as_u16() {
+        400 | 413 | 415 | 422 => Code::InvalidArgument,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        408 => Code::DeadlineExceeded,
+        429 | 507 => Code::ResourceExhausted,
+        503 => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    let mut grpc_status = Status::new(code, error_response.message);
+    if let Ok(error_code) = serde_json::to_value(error_response.code) {
+        if let Some(value) = error_code.as_str().and_then(|value| value.parse().ok()) {
+            grpc_status.metadata_mut().insert("error-code", value);
+        }
+    }
+    grpc_status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use tempfile::TempDir;
+
+    fn input(title: &str, rating: u32) -> proto::ReviewInput {
+        proto::ReviewInput {
+            title: title.to_string(),
+            body: "Boils a full kettle in two minutes.".to_string(),
+            product_id: "kettle".to_string(),
+            rating,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rpcs_share_the_http_storage_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("DATA_DIR", format!("{}/grpc", temp_dir.path().to_str().unwrap()));
+        let service = ReviewGrpc { state: AppState::default() };
+
+        let created = service
+            .create_review(Request::new(proto::CreateReviewRequest {
+                review: Some(input("Fast kettle", 5)),
+                idempotency_key: Some("grpc-1".to_string()),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((created.vector_index, created.replayed), (0, false));
+
+        let uploaded = service
+            .bulk_upload(Request::new(proto::BulkUploadRequest {
+                reviews: vec![input("Quiet kettle", 4), input("Broken", 9)],
+                file_name: None,
+                source: None,
+                license: None,
+                attribution: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((uploaded.total_processed, uploaded.successful), (2, 1));
+        assert_eq!(uploaded.failed[0].line_number, 2);
+
+        let found = service
+            .search(Request::new(proto::SearchRequest {
+                query: "kettle".to_string(),
+                limit: Some(10),
+                filters: None,
+                rank_by_helpfulness: false,
+                offset: None,
+                cursor: None,
+                min_score: None,
+                fallback: None,
+                collections: Vec::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(found.results.len(), 2);
+        assert!(!found.partial);
+
+        // Errors keep the HTTP error code
+        let status = service
+            .create_review(Request::new(proto::CreateReviewRequest {
+                review: Some(input("No", 5)),
+                idempotency_key: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.metadata().get("error-code").unwrap(), "E_VALIDATION_TITLE_SHORT");
+    }
+}
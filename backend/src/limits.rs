@@ -0,0 +1,129 @@
+use crate::models::*;
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use std::time::Duration;
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
+
+/// Largest accepted request body when `MAX_REQUEST_BODY_BYTES` is not set; sized for bulk uploads
+pub const DEFAULT_MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Longest a handler may run when `REQUEST_TIMEOUT_SECS` is not set
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Body size and handler time limits applied to every route
+#[derive(Clone, Copy, Debug)]
+pub struct RequestLimits {
+    pub max_body_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl RequestLimits {
+    /// Limits from `MAX_REQUEST_BODY_BYTES` and `REQUEST_TIMEOUT_SECS`, falling back to the defaults
+    pub fn from_env() -> Self {
+        let max_body_bytes = std::env::var("MAX_REQUEST_BODY_BYTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+        let timeout_secs = std::env::var("REQUEST_TIMEOUT_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+        RequestLimits {
+            max_body_bytes,
+            timeout: Duration::from_secs(timeout_secs),
+        }
+    }
+
+    /// Wrap every route of `router` in the body limit and timeout. Bodies over the limit are
+    /// refused before they are buffered, and both failures come back as an ErrorResponse.
+    pub fn apply(self, router: Router) -> Router {
+        router
+            // axum's own 2 MB extractor limit would otherwise undercut the configured one
+            .layer(DefaultBodyLimit::disable())
+            .layer(RequestBodyLimitLayer::new(self.max_body_bytes))
+            .layer(TimeoutLayer::new(self.timeout))
+            .layer(middleware::map_response_with_state(self, map_limit_errors))
+    }
+}
+
+/// Replace the plain-text or empty 413 and 408 responses from the limit layers with an ErrorResponse
+async fn map_limit_errors(State(limits): State<RequestLimits>, response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+
+    let error = match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge {
+            limit_bytes: limits.max_body_bytes,
+        },
+        StatusCode::REQUEST_TIMEOUT => AppError::RequestTimeout {
+            timeout_secs: limits.timeout.as_secs(),
+        },
+        _ => return response,
+    };
+
+    tracing::warn!("Request rejected: {}", error);
+    (response.status(), Json(ErrorResponse::from(error))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Request, routing::post};
+    use tower::ServiceExt;
+
+    async fn error_body(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_limits_return_error_responses() {
+        let limits = RequestLimits {
+            max_body_bytes: 16,
+            timeout: Duration::from_millis(50),
+        };
+        let app = limits.apply(
+            Router::new()
+                .route("/echo", post(|body: String| async move { body }))
+                .route(
+                    "/slow",
+                    post(|| async {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        "done"
+                    }),
+                ),
+        );
+
+        let request = |uri: &str, body: &'static str| {
+            Request::builder().method("POST").uri(uri).body(Body::from(body)).unwrap()
+        };
+
+        let response = app.clone().oneshot(request("/echo", "small")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request("/echo", "this body is well over sixteen bytes"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let error = error_body(response).await;
+        assert_eq!(error["code"], "E_PAYLOAD_TOO_LARGE");
+        assert_eq!(error["details"]["limit_bytes"], 16);
+
+        let response = app.oneshot(request("/slow", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(error_body(response).await["code"], "E_REQUEST_TIMEOUT");
+    }
+}
@@ -0,0 +1,263 @@
+//! Live activity for the operator dashboard: reviews stored and searches run per
+//! second, the latest error responses and the depth of the work queues, so a bulk
+//! import can be watched as it lands and is embedded.
+//!
+//! Counters live in memory for this process since startup, like the lock metrics, so
+//! on a multi-process deployment each process reports its own traffic. Rates are
+//! averaged over the last `RATE_WINDOW_SECS` seconds. `GET /stats/live` returns one
+//! sample; `GET /stats/live/stream` sends a `metrics` event with a fresh sample every
+//! `STREAM_INTERVAL_MS`.
+
+use crate::embedding_worker;
+use crate::lock;
+use crate::storage::DataPaths;
+use axum::body::{Body, HttpBody};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{sse::Event, Response};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Mutex;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Seconds that rates are averaged over
+pub const RATE_WINDOW_SECS: i64 = 10;
+
+/// Error responses kept for the dashboard, newest first
+pub const RECENT_ERRORS: usize = 20;
+
+/// Time between samples on the stream
+pub const STREAM_INTERVAL_MS: u64 = 1000;
+
+/// Error bodies larger than this are not read for their code
+const MAX_ERROR_BODY: u64 = 16 * 1024;
+
+/// An error response the service sent
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    /// Error catalog code from the body, e.g. `E_RATE_LIMITED`, when it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// Work waiting to be done
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct QueueDepths {
+    /// Reviews waiting for their first embedding
+    pub embedding_interactive: usize,
+    /// Reviews waiting to be re-embedded (edits and reindexing)
+    pub embedding_backfill: usize,
+    /// Bulk uploads being stored right now
+    pub uploads_in_progress: u64,
+    /// Readers in this process holding the data lock
+    pub lock_readers: u64,
+    /// Whether a writer holds the data lock
+    pub lock_writer: bool,
+}
+
+/// One sample of live activity
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct LiveMetrics {
+    pub sampled_at: DateTime<Utc>,
+    pub reviews_per_sec: f64,
+    pub searches_per_sec: f64,
+    pub errors_per_sec: f64,
+    /// Totals since the process started
+    pub reviews_stored: u64,
+    pub searches: u64,
+    pub errors: u64,
+    pub queues: QueueDepths,
+    /// Newest first
+    pub recent_errors: Vec<RecentError>,
+}
+
+/// Events counted in one-second buckets over the rate window
+#[derive(Debug, Default)]
+struct Rate {
+    buckets: VecDeque<(i64, u64)>,
+    total: u64,
+}
+
+impl Rate {
+    const fn new() -> Self {
+        Rate { buckets: VecDeque::new(), total: 0 }
+    }
+
+    fn add(&mut self, second: i64, count: u64) {
+        self.total += count;
+        match self.buckets.back_mut() {
+            Some((last, bucket)) if *last == second => *bucket += count,
+            _ => self.buckets.push_back((second, count)),
+        }
+        self.expire(second);
+    }
+
+    fn expire(&mut self, now: i64) {
+        while self.buckets.front().is_some_and(|(second, _)| *second <= now - RATE_WINDOW_SECS) {
+            self.buckets.pop_front();
+        }
+    }
+
+    fn per_sec(&mut self, now: i64) -> f64 {
+        self.expire(now);
+        self.buckets.iter().map(|(_, count)| count).sum::<u64>() as f64 / RATE_WINDOW_SECS as f64
+    }
+}
+
+struct Activity {
+    reviews: Rate,
+    searches: Rate,
+    errors: Rate,
+    recent_errors: VecDeque<RecentError>,
+    uploads_in_progress: u64,
+}
+
+static ACTIVITY: Mutex<Activity> = Mutex::new(Activity {
+    reviews: Rate::new(),
+    searches: Rate::new(),
+    errors: Rate::new(),
+    recent_errors: VecDeque::new(),
+    uploads_in_progress: 0,
+});
+
+fn with_activity<T>(update: impl FnOnce(&mut Activity) -> T) -> T {
+    // Counters are best effort; a poisoned mutex still holds usable ones
+    let mut activity = ACTIVITY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    update(&mut activity)
+}
+
+/// Count reviews just stored
+pub fn record_reviews(count: usize) {
+    if count > 0 {
+        with_activity(|activity| activity.reviews.add(Utc::now().timestamp(), count as u64));
+    }
+}
+
+/// Count a search run
+pub fn record_search() {
+    with_activity(|activity| activity.searches.add(Utc::now().timestamp(), 1));
+}
+
+fn record_error(error: RecentError) {
+    with_activity(|activity| {
+        activity.errors.add(error.at.timestamp(), 1);
+        activity.recent_errors.push_front(error);
+        activity.recent_errors.truncate(RECENT_ERRORS);
+    });
+}
+
+/// Marks a bulk upload in progress until dropped
+pub struct UploadInProgress(());
+
+impl UploadInProgress {
+    pub fn start() -> Self {
+        with_activity(|activity| activity.uploads_in_progress += 1);
+        UploadInProgress(())
+    }
+}
+
+impl Drop for UploadInProgress {
+    fn drop(&mut self) {
+        with_activity(|activity| activity.uploads_in_progress = activity.uploads_in_progress.saturating_sub(1));
+    }
+}
+
+/// A sample of live activity, with the queue depths of `data_paths`
+pub fn sample(data_paths: &DataPaths) -> LiveMetrics {
+    let now = Utc::now();
+    let embedding = embedding_worker::queue_metrics(data_paths);
+    let locks = lock::lock_metrics();
+    with_activity(|activity| LiveMetrics {
+        sampled_at: now,
+        reviews_per_sec: activity.reviews.per_sec(now.timestamp()),
+        searches_per_sec: activity.searches.per_sec(now.timestamp()),
+        errors_per_sec: activity.errors.per_sec(now.timestamp()),
+        reviews_stored: activity.reviews.total,
+        searches: activity.searches.total,
+        errors: activity.errors.total,
+        queues: QueueDepths {
+            embedding_interactive: embedding.interactive_depth,
+            embedding_backfill: embedding.backfill_depth,
+            uploads_in_progress: activity.uploads_in_progress,
+            lock_readers: locks.active_readers,
+            lock_writer: locks.current_holder.is_some(),
+        },
+        recent_errors: activity.recent_errors.iter().cloned().collect(),
+    })
+}
+
+/// Samples every `STREAM_INTERVAL_MS`, the first at once, as SSE `metrics` events
+pub fn stream(data_paths: DataPaths) -> impl futures_util::Stream<Item = Result<Event, Infallible>> {
+    let mut interval = tokio::time::interval(Duration::from_millis(STREAM_INTERVAL_MS));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    futures_util::stream::unfold((interval, data_paths), |(mut interval, data_paths)| async move {
+        interval.tick().await;
+        let sample = sample(&data_paths);
+        let event = Event::default()
+            .event("metrics")
+            .json_data(&sample)
+            .unwrap_or_else(|_| Event::default().event("metrics"));
+        Some((Ok(event), (interval, data_paths)))
+    })
+}
+
+/// Middleware recording every error response, with its catalog code when the body
+/// is a JSON error
+pub async fn track(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let (code, body) = match body.size_hint().upper() {
+        Some(size) if size <= MAX_ERROR_BODY => match axum::body::to_bytes(body, MAX_ERROR_BODY as usize).await {
+            Ok(bytes) => {
+                let code = serde_json::from_slice::<serde_json::Value>(&bytes)
+                    .ok()
+                    .and_then(|error| error.get("code")?.as_str().map(str::to_string));
+                (code, Body::from(bytes))
+            }
+            Err(_) => (None, Body::empty()),
+        },
+        _ => (None, body),
+    };
+    record_error(RecentError {
+        at: Utc::now(),
+        method,
+        path,
+        status: status.as_u16(),
+        code,
+    });
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_average_over_the_window() {
+        let mut rate = Rate::new();
+        rate.add(100, 30);
+        rate.add(100, 10);
+        rate.add(105, 20);
+        assert_eq!(rate.per_sec(105), 6.0);
+        assert_eq!(rate.total, 60);
+
+        // The first second leaves the window, then everything does
+        assert_eq!(rate.per_sec(110), 2.0);
+        assert_eq!(rate.per_sec(200), 0.0);
+        assert_eq!(rate.total, 60);
+    }
+}
@@ -0,0 +1,151 @@
+//! Log output, readable in a terminal during development and machine-parseable in
+//! production.
+//!
+//! `LOG_FORMAT` picks how lines are written: `pretty` (several colored lines per event,
+//! with source locations), `compact` (one colored line per event) or `json` (one JSON
+//! object per line, for log shippers). Without it the format follows `APP_ENV`: `json`
+//! in `production` and `staging`, `compact` in `test`, `pretty` otherwise. Color is only
+//! used when stdout is a terminal and `NO_COLOR` is unset.
+//!
+//! Every request is handled inside a `request` span carrying its `request_id` and
+//! `tenant`, so every line logged while serving it names both. The id is the client's
+//! `X-Request-Id` when it sends a usable one, otherwise a fresh UUID, and is returned in
+//! the response's `X-Request-Id`. The tenant is the client's `X-Client-Id`.
+
+use crate::recording::CLIENT_ID_HEADER;
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::io::IsTerminal;
+use tracing::Instrument;
+
+/// Environment variable naming the log format
+pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// Environment variable naming the deployment environment, which picks the default format
+pub const APP_ENV: &str = "APP_ENV";
+
+/// Header carrying the id of a request, both ways
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id or tenant taken from a client
+const MAX_CLIENT_VALUE_LENGTH: usize = 128;
+
+/// How log lines are written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Compact,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Some(LogFormat::Pretty),
+            "compact" => Some(LogFormat::Compact),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Compact => "compact",
+            LogFormat::Json => "json",
+        }
+    }
+
+    /// Format used in a deployment environment when `LOG_FORMAT` is unset
+    pub fn for_environment(environment: &str) -> Self {
+        match environment.trim().to_ascii_lowercase().as_str() {
+            "production" | "prod" | "staging" => LogFormat::Json,
+            "test" => LogFormat::Compact,
+            _ => LogFormat::Pretty,
+        }
+    }
+
+    /// The configured format, and the `LOG_FORMAT` value if it was not understood
+    pub fn from_env() -> (Self, Option<String>) {
+        let default = LogFormat::for_environment(&std::env::var(APP_ENV).unwrap_or_default());
+        match std::env::var(LOG_FORMAT_ENV) {
+            Ok(value) => match LogFormat::parse(&value) {
+                Some(format) => (format, None),
+                None => (default, Some(value)),
+            },
+            Err(_) => (default, None),
+        }
+    }
+}
+
+/// Whether log lines are colored: stdout is a terminal and `NO_COLOR` is unset
+pub fn colors_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Install the global subscriber in the configured format, at INFO and above
+pub fn init() {
+    let (format, unknown) = LogFormat::from_env();
+    let builder = tracing_subscriber::fmt().with_ansi(colors_enabled());
+    match format {
+        LogFormat::Pretty => builder.pretty().init(),
+        LogFormat::Compact => builder.compact().init(),
+        // Event fields at the top level, the request's fields under "span"
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).with_span_list(false).init(),
+    }
+    if let Some(value) = unknown {
+        tracing::warn!("Ignoring unknown {} {:?}; expected pretty, compact or json", LOG_FORMAT_ENV, value);
+    }
+}
+
+/// A header value a client sent, if it is short printable ASCII
+fn client_value<'a>(request: &'a Request, header: &str) -> Option<&'a str> {
+    let value = request.headers().get(header)?.to_str().ok()?;
+    let usable = !value.is_empty()
+        && value.len() <= MAX_CLIENT_VALUE_LENGTH
+        && value.bytes().all(|byte| byte.is_ascii_graphic());
+    usable.then_some(value)
+}
+
+/// Middleware running each request inside its `request` span and returning its id
+pub async fn request_span(request: Request, next: Next) -> Response {
+    let request_id = client_value(&request, REQUEST_ID_HEADER)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        tenant = tracing::field::Empty,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    if let Some(tenant) = client_value(&request, CLIENT_ID_HEADER) {
+        span.record("tenant", tenant);
+    }
+
+    let mut response = next.run(request).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_names_and_environment_defaults() {
+        assert_eq!(LogFormat::parse(" JSON "), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse("compact"), Some(LogFormat::Compact));
+        assert_eq!(LogFormat::parse("logfmt"), None);
+
+        assert_eq!(LogFormat::for_environment("Production"), LogFormat::Json);
+        assert_eq!(LogFormat::for_environment("staging"), LogFormat::Json);
+        assert_eq!(LogFormat::for_environment("test"), LogFormat::Compact);
+        assert_eq!(LogFormat::for_environment("development"), LogFormat::Pretty);
+        assert_eq!(LogFormat::for_environment(""), LogFormat::Pretty);
+    }
+}
@@ -1,47 +1,205 @@
 use axum::{
-    extract::Json as ExtractJson,
-    http::StatusCode,
-    response::Json,
+    body::Body,
+    extract::{ws::WebSocketUpgrade, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
     routing::{get, post},
     Router,
 };
 use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::convert::Infallible;
 use std::env;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
+#[cfg(test)]
 mod api_tests;
-mod file_demo;
-mod models;
-mod storage;
+mod batch_search;
+mod config;
+mod data_subject;
+mod errors;
+mod events;
+mod extract;
+mod fields;
+mod fanout;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod graphql;
+mod limits;
+mod live_metrics;
+mod logging;
+mod msgpack;
+mod openapi;
+mod rate_limit;
+mod recording;
+mod routing;
+mod search_session;
+mod versioning;
+mod webhooks;
 
+// The transport-free services, under the same paths as this crate's own modules
+use semantic_search_core::{
+    analytics, ask, blocklist, canary, collections, compaction, corpus_limits, curation, dataset_meta, duplicates,
+    embedding, embedding_queue, embedding_worker, error_catalog, expansion, explain, export, fallback, health,
+    idempotency, ingest, integrity, jobs, language, lock, models, personalization, projection, query_log,
+    query_syntax, reindex, sampling, sandbox, search, sentiment, similar, similarity_matrix, spelling, storage,
+    suggest, synonyms, templates, tokenizer, vectors,
+};
+
+use corpus_limits::Eviction;
+use embedding::EmbeddingStatus;
+use events::{EventBus, ReviewEvent};
+use extract::ApiJson;
+use jobs::JobStorage;
+use limits::RequestLimits;
+use rate_limit::RateLimiter;
+use routing::ShardRouter;
 use models::*;
 use storage::*;
+use vectors::VectorStore;
 
 #[tokio::main]
 async fn main() {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+    // Initialize tracing in the format LOG_FORMAT (or APP_ENV) asks for
+    logging::init();
+
+    // `replay <recording> [target]` compares a recorded client session against a running server
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("replay") {
+        std::process::exit(recording::replay_command(&args).await);
+    }
+
+    // Several processes may share one data directory; that is only safe where file locks work
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    if let Err(e) = data_paths.ensure_directories().and_then(|_| lock::verify_lock_support(&data_paths.lock_file)) {
+        tracing::error!("Refusing to start: {}", e);
+        std::process::exit(1);
+    }
+
+    // A crash can leave the review file out of step with its vector slots; repair before serving
+    match integrity::repair(&data_paths) {
+        Ok(report) if report.is_clean() => {}
+        Ok(report) => tracing::warn!(
+            "Repaired review file: dropped {} bytes of a partial line, renumbered {} reviews, cut {} orphaned vectors",
+            report.torn_tail_bytes,
+            report.renumbered,
+            report.orphaned_vectors
+        ),
+        Err(e) => {
+            tracing::error!("Refusing to start: review file check failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Known-good searches must still find their reviews before we report ready
+    if let Err(e) = canary::run(&data_paths, "startup") {
+        tracing::error!("Refusing to start: canary suite in {} is unusable: {}", canary::CANARY_FILE_ENV, e);
+        std::process::exit(1);
+    }
+
+    // A broken synonym file would quietly search without the operator's synonyms
+    match synonyms::reload(&data_paths) {
+        Ok(summary) if summary.groups > 0 => tracing::info!("Loaded {} synonym groups", summary.groups),
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!("Refusing to start: synonym file in {} is unusable: {}", data_paths.data_dir.display(), e);
+            std::process::exit(1);
+        }
+    }
+
+    // Show what every setting resolved to, so a misconfiguration is obvious before the first request
+    match config::effective(&data_paths) {
+        Ok(effective) => config::announce(&effective),
+        Err(e) => tracing::warn!("Could not resolve the effective configuration: {}", e),
+    }
+
+    // Reviews are stored first and embedded in the background
+    embedding_worker::spawn(data_paths.data_dir.clone(), Arc::new(embedding::default_embedder()));
 
     // Build our application with routes
-    let app = create_app();
+    let state = AppState::from_env();
+    webhooks::spawn(data_paths.data_dir.clone(), &state.events);
+    if sandbox::enabled() {
+        sandbox::spawn_gc();
+    }
+    #[cfg(feature = "grpc")]
+    grpc::spawn(state.clone());
+    let app = create_app_with_state(state);
 
-    // Run it with hyper on localhost:8000
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8000));
+    // Run it with hyper on 0.0.0.0, port 8000 unless PORT says otherwise
+    let addr = SocketAddr::from(([0, 0, 0, 0], config::port()));
     println!("🚀 Semantic Search Backend listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // Connection info gives the rate limiter each client's IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
+}
+
+/// Prefix of the current API version
+const API_VERSION_PREFIX: &str = "/v1";
+
+/// State shared by every handler that needs more than the data directory
+#[derive(Clone, Default)]
+struct AppState {
+    events: EventBus,
+    /// Shard routing table; empty unless `SHARD_NODES` makes this node a coordinator
+    shards: ShardRouter,
+}
+
+impl AppState {
+    fn from_env() -> Self {
+        AppState {
+            events: EventBus::default(),
+            shards: ShardRouter::from_env(),
+        }
+    }
 }
 
+/// The app with its own state, for tests
+#[cfg(test)]
 fn create_app() -> Router {
-    Router::new()
-        .route("/health", get(health_check))
-        .route("/reviews", post(create_review))
-        .route("/reviews/bulk", post(bulk_upload))
-        .route("/search", post(search_reviews))
+    create_app_with_state(AppState::from_env())
+}
+
+fn create_app_with_state(state: AppState) -> Router {
+    // One limiter for both route trees, so versioned and unversioned paths share a budget
+    let rate_limiter = RateLimiter::from_env();
+
+    let app = Router::new()
+        .nest(API_VERSION_PREFIX, api_routes(&rate_limiter).with_state(state.clone()))
+        // Same handlers; versioning::negotiate rewrites their bodies to the v2 schema
+        .nest(versioning::V2_PREFIX, api_routes(&rate_limiter).with_state(state.clone()))
+        // Unversioned paths predate /v1 and stay routable for existing clients
+        .merge(
+            api_routes(&rate_limiter)
+                .with_state(state)
+                .layer(middleware::from_fn(mark_unversioned_deprecated)),
+        )
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", openapi::ApiDoc::openapi()))
+        .layer(middleware::from_fn(versioning::negotiate));
+    // Inside the limits, so recording never buffers a body the limits would refuse
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let app = match recording::Recorder::from_env(&DataPaths::new(&data_dir)) {
+        Some(recorder) => recorder.apply(app),
+        None => app,
+    };
+
+    RequestLimits::from_env()
+        .apply(app)
+        .layer(middleware::map_response(errors::add_retry_after))
+        .layer(middleware::from_fn(live_metrics::track))
         .layer(
             ServiceBuilder::new().layer(
                 CorsLayer::new()
@@ -50,39 +208,293 @@ fn create_app() -> Router {
                     .allow_headers(Any),
             ),
         )
+        // Outermost, so everything logged for a request carries its id
+        .layer(middleware::from_fn(logging::request_span))
+}
+
+fn api_routes(rate_limiter: &RateLimiter) -> Router<AppState> {
+    let rate_limited = || middleware::from_fn_with_state(rate_limiter.clone(), rate_limit::enforce);
+    let msgpack = || middleware::from_fn(msgpack::negotiate);
+    let selectable = || middleware::from_fn(fields::select);
+
+    let routes = Router::new()
+        .route("/health", get(health_check))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+        .route("/events", get(stream_events))
+        .route(
+            "/reviews",
+            post(create_review)
+                .get(list_reviews)
+                .route_layer(selectable())
+                .route_layer(rate_limited())
+                .route_layer(msgpack()),
+        )
+        .route("/reviews/bulk", post(bulk_upload).route_layer(msgpack()))
+        .route("/reviews/batch", post(batch_reviews))
+        .route("/reviews/bulk-delete", post(bulk_delete_reviews))
+        .route("/reviews/check-duplicate", post(check_duplicate))
+        .route("/reviews/export", get(export_reviews))
+        .route("/reviews/random", get(random_reviews).route_layer(selectable()))
+        .route("/reviews/meta", get(get_reviews_meta))
+        .route("/reviews/templates/:format", get(download_template))
+        .route("/reviews/:id", get(get_review).put(update_review).delete(delete_review))
+        .route("/reviews/:id/vote", post(vote_review).route_layer(rate_limited()))
+        .route(
+            "/reviews/:id/similar",
+            post(similar_reviews).route_layer(selectable()).route_layer(rate_limited()),
+        )
+        .route("/products/:product_id/reviews", get(list_product_reviews).route_layer(selectable()))
+        .route("/products/:product_id/stats", get(get_product_stats))
+        .route("/products/:product_id/ask", post(ask_product).route_layer(rate_limited()))
+        .route(
+            "/search",
+            post(search_reviews)
+                .get(search_reviews_by_query)
+                .route_layer(selectable())
+                .route_layer(rate_limited())
+                .route_layer(msgpack()),
+        )
+        .route("/stats", get(get_service_stats))
+        .route("/stats/live", get(get_live_metrics))
+        .route("/stats/live/stream", get(stream_live_metrics))
+        .route("/analytics/searches", get(get_search_analytics))
+        .route("/analyze/projection", get(get_projection))
+        .route("/analyze/similarity-matrix", post(similarity_matrix))
+        .route("/search/batch", post(search_batch).route_layer(rate_limited()))
+        .route("/collections", get(list_collections).post(create_collection))
+        .route("/collections/:name/reviews", post(add_collection_reviews).route_layer(msgpack()))
+        .route("/search/suggest", get(suggest_search))
+        .route("/search/clicks", post(report_click))
+        .route("/search/clicks/:session_id", axum::routing::delete(clear_clicks))
+        .route("/ws/search", get(search_session_socket))
+        .route("/graphql", post(graphql_query).route_layer(rate_limited()).get(graphiql))
+        .route("/webhooks", post(register_webhook).get(list_webhooks))
+        .route("/webhooks/:id", axum::routing::delete(delete_webhook))
+        .route("/admin/shards", get(get_shard_table))
+        .route("/admin/shards/rebalance", post(rebalance_shards))
+        .route("/admin/reindex", post(start_reindex))
+        .route("/admin/compact", post(admin_compact))
+        .route("/admin/curation/rules", post(create_curation_rule).get(list_curation_rules))
+        .route("/admin/curation/rules/:id", axum::routing::delete(delete_curation_rule))
+        .route("/admin/blocklist", post(create_blocklist_entry).get(list_blocklist))
+        .route("/admin/blocklist/audit", get(list_blocklist_audit))
+        .route("/admin/blocklist/:id", axum::routing::delete(delete_blocklist_entry))
+        .route("/admin/data-subjects/:reviewer_id", get(export_data_subject).delete(erase_data_subject))
+        .route("/admin/erasures", get(list_erasure_receipts))
+        .route("/admin/synonyms/reload", post(reload_synonyms))
+        .route("/admin/config", get(get_effective_config))
+        .route("/admin/lock", get(get_lock_status))
+        .route("/admin/lock/break", post(break_lock))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/compaction", post(start_compaction))
+        .route("/jobs/:id", get(get_job))
+        .route("/jobs/:id/pause", post(pause_job))
+        .route("/jobs/:id/resume", post(resume_job))
+        .route("/jobs/:id/rollback", post(rollback_job));
+
+    // Demo namespaces are served only where an operator turned them on
+    if !sandbox::enabled() {
+        return routes;
+    }
+    routes
+        .route("/sandboxes/:id", get(get_sandbox).delete(delete_sandbox))
+        .route("/sandboxes/:id/reviews", post(add_sandbox_reviews).route_layer(rate_limited()))
+        .route("/sandboxes/:id/search", post(search_sandbox).route_layer(rate_limited()))
+}
+
+/// Flag responses served from an unversioned path as deprecated (RFC 8594 style)
+/// and point clients at the same resource under the current version.
+async fn mark_unversioned_deprecated(request: axum::extract::Request, next: Next) -> Response {
+    let successor = format!("<{}{}>; rel=\"successor-version\"", API_VERSION_PREFIX, request.uri().path());
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/events",
+    tag = "system",
+    responses(
+        (status = 200, description = "Server-Sent Events stream of `review_created` and `bulk_upload_completed` events", body = ReviewEvent, content_type = "text/event-stream")
+    )
+)]
+async fn stream_events(
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<sse::Event, Infallible>>> {
+    // Comment frames keep idle connections open through proxies
+    Sse::new(state.events.subscribe()).keep_alive(KeepAlive::default())
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/health",
+    tag = "system",
+    responses(
+        (status = 200, description = "Service status with readiness checks; `status` is \"degraded\" when a check fails", body = Object)
+    )
+)]
 async fn health_check() -> Json<Value> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let readiness = health::readiness(&data_paths);
+    let ready = readiness.is_ready();
+
     Json(json!({
-        "status": "healthy",
+        "status": if ready { "healthy" } else { "degraded" },
         "service": "semantic-search-backend",
-        "version": "0.1.0"
+        "version": "0.1.0",
+        "ready": ready,
+        "checks": readiness.checks_json()
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/health/live",
+    tag = "system",
+    responses(
+        (status = 200, description = "The server is up and answering requests; no dependencies are checked", body = Object)
+    )
+)]
+async fn liveness() -> Json<Value> {
+    Json(json!({ "status": "alive" }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/health/ready",
+    tag = "system",
+    responses(
+        (status = 200, description = "Every readiness check passed", body = Object),
+        (status = 503, description = "A readiness check failed; `checks` holds its error", body = Object)
+    )
+)]
+async fn readiness() -> (StatusCode, Json<Value>) {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let readiness = health::readiness(&data_paths);
+    let ready = readiness.is_ready();
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        status,
+        Json(json!({
+            "ready": ready,
+            "checks": readiness.checks_json()
+        })),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews",
+    tag = "reviews",
+    request_body = ReviewData,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key; retries with the same key and body replay the original response")
+    ),
+    responses(
+        (status = 200, description = "Review created, or the original response replayed for a repeated Idempotency-Key", body = openapi::CreateReviewResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Product or reviewer is blocklisted", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key already used for a different review", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 507, description = "Corpus size limit reached and the eviction policy refuses writes", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator the owning shard is unreachable", body = ErrorResponse)
+    )
+)]
 async fn create_review(
-    ExtractJson(review_data): ExtractJson<ReviewData>,
-) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(review_data): ApiJson<ReviewData>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let idempotency_key = match extract::idempotency_key(&headers) {
+        Ok(key) => key,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
     // Validate the review data
     if let Err(validation_error) = review_data.validate() {
         let error_response = ErrorResponse::from(AppError::Validation(validation_error));
         return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
 
-    // Initialize data paths and storage
+    // A coordinator stores nothing itself; the shard owning the product does
+    if let Some(owner) = state.shards.write_owner(&headers, &review_data.product_id) {
+        return routing::forward(&owner, Method::POST, "/reviews", &headers, Some(&json!(review_data))).await;
+    }
+
     let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
     let data_paths = DataPaths::new(&data_dir);
+    match store_review(&state, &data_paths, &review_data, idempotency_key).map_err(errors::respond)? {
+        ingest::Created::Replayed { response } => {
+            Ok(([(idempotency::IDEMPOTENT_REPLAYED_HEADER, "true")], Json(response)).into_response())
+        }
+        ingest::Created::Stored { response, .. } => Ok(Json(response).into_response()),
+    }
+}
 
-    // Ensure directories exist
-    if let Err(e) = data_paths.ensure_directories() {
-        let error_response = ErrorResponse::from(e);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+/// Store one review through the ingest service, then count and announce it. Shared by
+/// `POST /reviews` and the gRPC `CreateReview`.
+fn store_review(
+    state: &AppState,
+    data_paths: &DataPaths,
+    review_data: &ReviewData,
+    idempotency_key: Option<String>,
+) -> Result<ingest::Created, AppError> {
+    let created = ingest::create_review(data_paths, review_data, idempotency_key)?;
+    if let ingest::Created::Stored { review, eviction, .. } = &created {
+        live_metrics::record_reviews(1);
+        publish_evicted(state, data_paths, eviction);
+        state.events.publish(ReviewEvent::ReviewCreated {
+            review_id: review.id.clone(),
+            product_id: review.product_id.clone(),
+            rating: review.rating,
+            vector_index: review.vector_index,
+            timestamp: review.timestamp,
+        });
+    }
+    Ok(created)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/reviews",
+    tag = "reviews",
+    params(ListReviewsQuery, fields::FieldsQuery),
+    responses(
+        (status = 200, description = "A page of live reviews", body = openapi::ReviewPageResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_reviews(
+    Query(list_query): Query<ListReviewsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Validate pagination parameters
+    if let Err(validation_error) = list_query.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
 
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
     let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
 
-    // Get current review count to determine vector index
-    let vector_index = match jsonl_storage.count_reviews() {
+    let total_reviews = match jsonl_storage.count_live_reviews() {
         Ok(count) => count,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
@@ -90,218 +502,453 @@ async fn create_review(
         }
     };
 
-    // Convert to metadata with generated ID and timestamp
-    let review_metadata = match review_data.to_metadata(vector_index) {
-        Ok(metadata) => metadata,
+    let reviews = match jsonl_storage.read_reviews_page(list_query.offset(), list_query.get_page_size()) {
+        Ok(reviews) => reviews,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
             return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
     };
 
-    // Acquire file lock for concurrent safety
-    let _lock = match FileLock::acquire(&data_paths.lock_file) {
-        Ok(lock) => lock,
+    let page_size = list_query.get_page_size();
+    let total_pages = total_reviews.div_ceil(page_size);
+
+    Ok(Json(json!({
+        "success": true,
+        "reviews": reviews,
+        "page": list_query.get_page(),
+        "page_size": page_size,
+        "total_reviews": total_reviews,
+        "total_pages": total_pages
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/reviews/random",
+    tag = "reviews",
+    params(RandomReviewsQuery, fields::FieldsQuery),
+    responses(
+        (status = 200, description = "A uniform random sample of live reviews", body = openapi::RandomReviewsResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn random_reviews(
+    Query(random_query): Query<RandomReviewsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = random_query.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    let seed = random_query.seed.unwrap_or_else(sampling::SampleRng::random_seed);
+    let mut rng = sampling::SampleRng::new(seed);
+    let reviews = match jsonl_storage.sample_live_reviews(random_query.get_count(), &mut rng) {
+        Ok(reviews) => reviews,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
-            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
     };
 
-    // Store the review metadata in JSONL file
-    if let Err(e) = jsonl_storage.append_review(&review_metadata) {
-        let error_response = ErrorResponse::from(e);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-    }
-
-    // TODO: Generate embedding and store in vector index (Task 6 & 7)
-    // For now, we'll just log that the vector would be stored
-    tracing::info!(
-        "Review stored successfully. Vector index {} would be stored in reviews.index",
-        vector_index
-    );
-
-    // Return success response
     Ok(Json(json!({
         "success": true,
-        "message": "Review created successfully",
-        "review_id": review_metadata.id,
-        "vector_index": vector_index,
-        "timestamp": review_metadata.timestamp
+        "reviews": reviews,
+        "count": reviews.len(),
+        "seed": seed
     })))
 }
 
-async fn bulk_upload(
-    ExtractJson(bulk_data): ExtractJson<Value>,
-) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+#[utoipa::path(
+    get,
+    path = "/v1/reviews/meta",
+    tag = "reviews",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; answered with 304 if the data is unchanged")
+    ),
+    responses(
+        (status = 200, description = "Review count, last-modified time and content hash of the review file; also answers HEAD", body = openapi::DatasetMetaResponse),
+        (status = 304, description = "The data still matches the If-None-Match ETag"),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "A write held the data lock past the lock timeout", body = ErrorResponse)
+    )
+)]
+async fn get_reviews_meta(headers: HeaderMap) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
     // Initialize data paths and storage
     let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
     let data_paths = DataPaths::new(&data_dir);
-    
-    // Ensure directories exist
     if let Err(e) = data_paths.ensure_directories() {
         let error_response = ErrorResponse::from(e);
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
-    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
-
-    // Acquire file lock for concurrent safety
-    let _lock = match FileLock::acquire(&data_paths.lock_file) {
+    let lock = match FileLock::acquire_shared(&data_paths.lock_file, "reviews_meta") {
         Ok(lock) => lock,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
             return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
         }
     };
+    let meta = match dataset_meta::dataset_meta(&data_paths.reviews_jsonl) {
+        Ok(meta) => meta,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+    drop(lock);
 
-    // Get current review count to determine starting vector index
-    let starting_vector_index = match jsonl_storage.count_reviews() {
-        Ok(count) => count,
+    let etag = meta.etag();
+    let mut response_headers = vec![
+        (header::ETAG, etag.clone()),
+        // Always revalidate; the ETag makes that cheap
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+    if let Some(last_modified) = meta.last_modified {
+        response_headers.push((header::LAST_MODIFIED, last_modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string()));
+    }
+
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"));
+    let mut response = if unchanged {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Json(json!({
+            "success": true,
+            "meta": meta
+        }))
+        .into_response()
+    };
+    for (name, value) in response_headers {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/products/{product_id}/reviews",
+    tag = "products",
+    params(("product_id" = String, Path, description = "Product identifier"), ProductReviewsQuery, fields::FieldsQuery),
+    responses(
+        (status = 200, description = "A sorted page of the product's reviews", body = openapi::ReviewPageResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_product_reviews(
+    Path(product_id): Path<String>,
+    Query(product_query): Query<ProductReviewsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Validate sort and pagination parameters
+    if let Err(validation_error) = product_query.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    let mut reviews = match jsonl_storage.get_reviews_by_product(&product_id) {
+        Ok(reviews) => reviews,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
             return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
     };
 
-    // Parse bulk data - support both array format and JSONL format
-    let review_data_list: Vec<ReviewData> = match parse_bulk_data(&bulk_data) {
+    product_query.sort_reviews(&mut reviews);
+
+    let pagination = product_query.pagination();
+    let page_size = pagination.get_page_size();
+    let total_reviews = reviews.len();
+    let page: Vec<ReviewMetadata> = reviews
+        .into_iter()
+        .skip(pagination.offset())
+        .take(page_size)
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "product_id": product_id,
+        "reviews": page,
+        "sort": product_query.get_sort(),
+        "order": product_query.get_order(),
+        "page": pagination.get_page(),
+        "page_size": page_size,
+        "total_reviews": total_reviews,
+        "total_pages": total_reviews.div_ceil(page_size)
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/products/{product_id}/stats",
+    tag = "products",
+    params(("product_id" = String, Path, description = "Product identifier")),
+    responses(
+        (status = 200, description = "Aggregate statistics for the product", body = Object),
+        (status = 404, description = "Product without reviews not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn get_product_stats(
+    Path(product_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    let reviews = match jsonl_storage.get_reviews_by_product(&product_id) {
         Ok(reviews) => reviews,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
     };
 
-    if review_data_list.is_empty() {
-        let error_response = ErrorResponse::from(AppError::Validation(
-            ValidationError::InvalidValue {
-                field: "reviews".to_string(),
-                reason: "No valid reviews found in bulk data".to_string(),
-            }
-        ));
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    if reviews.is_empty() {
+        let error_response = ErrorResponse::from(AppError::NotFound {
+            resource: "Product".to_string(),
+            id: product_id,
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
     }
 
-    // Process each review and collect results
-    let mut successful_reviews = Vec::new();
-    let mut failed_reviews = Vec::new();
-    let mut current_vector_index = starting_vector_index;
+    Ok(Json(json!({
+        "success": true,
+        "product_id": product_id,
+        "stats": analytics::review_stats(&reviews)
+    })))
+}
 
-    for (line_number, review_data) in review_data_list.iter().enumerate() {
-        match process_single_review(review_data, current_vector_index) {
-            Ok(metadata) => {
-                successful_reviews.push(metadata);
-                current_vector_index += 1;
-            }
+#[utoipa::path(
+    post,
+    path = "/v1/products/{product_id}/ask",
+    tag = "products",
+    params(("product_id" = String, Path, description = "Product identifier")),
+    request_body = ask::AskRequest,
+    responses(
+        (status = 200, description = "The reviews that best answer the question, their supporting sentences, a numbered context for an LLM and an extractive answer", body = ask::AskResponse),
+        (status = 400, description = "Missing or overlong question, or invalid limit or filters", body = ErrorResponse),
+        (status = 404, description = "Product without live reviews", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn ask_product(
+    Path(product_id): Path<String>,
+    ApiJson(ask_request): ApiJson<ask::AskRequest>,
+) -> Result<Json<ask::AskResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match ask::ask(&data_paths, &product_id, &ask_request) {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/stats",
+    tag = "system",
+    responses(
+        (status = 200, description = "Service-wide statistics, index health, embedding queue and data lock metrics", body = Object),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn get_service_stats() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    // Read under the shared lock when no writer has it so the counts come from one
+    // consistent state, but never queue behind a writer just to report statistics
+    let lock = if data_paths.data_dir.exists() {
+        match FileLock::try_acquire(&data_paths.lock_file, "service_stats") {
+            Ok(lock) => lock,
             Err(e) => {
-                failed_reviews.push(BulkError {
-                    line_number: line_number + 1,
-                    error: e.to_string(),
-                    data: Some(serde_json::to_value(review_data).unwrap_or(Value::Null)),
-                });
+                let error_response = ErrorResponse::from(e);
+                return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
             }
         }
-    }
+    } else {
+        None
+    };
+    let write_in_progress = lock.is_none() && data_paths.data_dir.exists();
 
-    // Store all successful reviews in batch
-    if !successful_reviews.is_empty() {
-        if let Err(e) = jsonl_storage.append_reviews(&successful_reviews) {
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
+    let snapshot = jsonl_storage.read_all_reviews().and_then(|reviews| {
+        let vector_entries = if vector_store.exists() { Some(vector_store.len()?) } else { None };
+        Ok((reviews, vector_entries))
+    });
+    let (all_reviews, vector_entries) = match snapshot {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
             let error_response = ErrorResponse::from(e);
             return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
+    };
+    drop(lock);
 
-        // TODO: Generate embeddings and store in vector index (Task 6 & 7)
-        tracing::info!(
-            "Bulk upload: {} reviews stored successfully. Vector indices {}-{} would be stored in reviews.index",
-            successful_reviews.len(),
-            starting_vector_index,
-            current_vector_index - 1
-        );
-    }
+    let jsonl_lines = all_reviews.len();
+    let pending_embeddings = all_reviews
+        .iter()
+        .filter(|review| EmbeddingStatus::of(review, vector_entries.unwrap_or(0)) == EmbeddingStatus::Pending)
+        .count();
+    let live_reviews: Vec<ReviewMetadata> = all_reviews.into_iter().filter(|review| !review.is_deleted()).collect();
+    let deleted_reviews = jsonl_lines - live_reviews.len();
 
-    // Create bulk upload result
-    let bulk_result = BulkUploadResult {
-        total_processed: review_data_list.len(),
-        successful: successful_reviews.len(),
-        failed: failed_reviews,
-    };
+    let index_health = analytics::IndexHealth::new(jsonl_lines, deleted_reviews, vector_entries, pending_embeddings);
 
-    // Return success response with detailed results
     Ok(Json(json!({
         "success": true,
-        "message": format!("Bulk upload completed: {} successful, {} failed", 
-                          bulk_result.successful, bulk_result.failed.len()),
-        "result": bulk_result,
-        "starting_vector_index": starting_vector_index,
-        "ending_vector_index": current_vector_index - 1
+        "stats": analytics::service_stats(&live_reviews, chrono::Utc::now()),
+        "index_health": index_health,
+        "embedding_queue": embedding_worker::queue_metrics(&data_paths),
+        "locks": lock::lock_metrics(),
+        "write_in_progress": write_in_progress
     })))
 }
 
-/// Parse bulk data from various formats (JSON array, JSONL, etc.)
-fn parse_bulk_data(bulk_data: &Value) -> Result<Vec<ReviewData>, AppError> {
-    match bulk_data {
-        // Handle JSON array format: [{"title": "...", ...}, ...]
-        Value::Array(reviews) => {
-            let mut parsed_reviews = Vec::new();
-            for review_value in reviews {
-                match serde_json::from_value::<ReviewData>(review_value.clone()) {
-                    Ok(review) => parsed_reviews.push(review),
-                    Err(e) => {
-                        return Err(AppError::Serialization(e));
-                    }
-                }
-            }
-            Ok(parsed_reviews)
-        }
-        // Handle single object wrapped in array
-        Value::Object(_) => {
-            match serde_json::from_value::<ReviewData>(bulk_data.clone()) {
-                Ok(review) => Ok(vec![review]),
-                Err(e) => Err(AppError::Serialization(e)),
-            }
-        }
-        // Handle string format (JSONL)
-        Value::String(jsonl_content) => {
-            let mut parsed_reviews = Vec::new();
-            for (line_num, line) in jsonl_content.lines().enumerate() {
-                let line = line.trim();
-                if line.is_empty() {
-                    continue;
-                }
-                match serde_json::from_str::<ReviewData>(line) {
-                    Ok(review) => parsed_reviews.push(review),
-                    Err(e) => {
-                        return Err(AppError::Validation(ValidationError::InvalidValue {
-                            field: format!("line_{}", line_num + 1),
-                            reason: format!("Invalid JSON: {}", e),
-                        }));
-                    }
-                }
-            }
-            Ok(parsed_reviews)
-        }
-        _ => Err(AppError::Validation(ValidationError::InvalidValue {
-            field: "bulk_data".to_string(),
-            reason: "Expected JSON array, object, or JSONL string".to_string(),
-        })),
+#[utoipa::path(
+    get,
+    path = "/v1/stats/live",
+    tag = "system",
+    responses(
+        (status = 200, description = "Reviews and searches per second, recent errors and queue depths of this process", body = live_metrics::LiveMetrics)
+    )
+)]
+async fn get_live_metrics() -> Json<live_metrics::LiveMetrics> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    Json(live_metrics::sample(&DataPaths::new(&data_dir)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/analytics/searches",
+    tag = "system",
+    params(query_log::SearchAnalyticsQuery),
+    responses(
+        (status = 200, description = "Top queries, zero-result queries and latency percentiles of the searches this process logged in the window", body = query_log::SearchAnalytics),
+        (status = 400, description = "Invalid window or limit", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn get_search_analytics(
+    Query(analytics_query): Query<query_log::SearchAnalyticsQuery>,
+) -> Result<Json<query_log::SearchAnalytics>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    tokio::task::spawn_blocking(move || query_log::analyze(&data_paths, &analytics_query))
+        .await
+        .map_err(|e| errors::respond(AppError::Concurrency { message: format!("Analytics task failed: {}", e) }))?
+        .map(Json)
+        .map_err(errors::respond)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/config",
+    tag = "system",
+    responses(
+        (status = 200, description = "Settings as this process resolved them, with secrets redacted; data paths, the vector index and enabled features", body = config::EffectiveConfig),
+        (status = 500, description = "Job history or vector index unreadable", body = ErrorResponse)
+    )
+)]
+async fn get_effective_config() -> Result<Json<config::EffectiveConfig>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    config::effective(&DataPaths::new(&data_dir))
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from(e))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/lock",
+    tag = "system",
+    responses(
+        (status = 200, description = "Whether the data lock is free or held, by whom, and whether the holder is still running", body = lock::LockStatus),
+        (status = 500, description = "Lock file unreadable", body = ErrorResponse)
+    )
+)]
+async fn get_lock_status() -> Result<Json<lock::LockStatus>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    lock::inspect(&DataPaths::new(&data_dir).lock_file)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::from(e))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/lock/break",
+    tag = "system",
+    params(lock::BreakLockQuery),
+    responses(
+        (status = 200, description = "The lock's status before, and whether it was broken; a free lock or one held by readers is left alone", body = openapi::LockBreakResponse),
+        (status = 409, description = "The writer holding the lock is still running and force was not given, or it is this server", body = ErrorResponse),
+        (status = 500, description = "Lock file could not be replaced", body = ErrorResponse)
+    )
+)]
+async fn break_lock(
+    Query(query): Query<lock::BreakLockQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    match lock::break_lock(&DataPaths::new(&data_dir).lock_file, query.force) {
+        Ok((previous, broken)) => Ok(Json(json!({
+            "success": true,
+            "broken": broken,
+            "previous": previous
+        }))),
+        Err(e) => Err(errors::respond(e)),
     }
 }
 
-/// Process a single review and convert to metadata
-fn process_single_review(review_data: &ReviewData, vector_index: usize) -> Result<ReviewMetadata, AppError> {
-    // Validate the review data
-    review_data.validate()?;
-    
-    // Convert to metadata with generated ID and timestamp
-    review_data.to_metadata(vector_index)
+#[utoipa::path(
+    get,
+    path = "/v1/stats/live/stream",
+    tag = "system",
+    responses(
+        (status = 200, description = "Server-Sent Events stream of `metrics` events, one live metrics sample per second", body = live_metrics::LiveMetrics, content_type = "text/event-stream")
+    )
+)]
+async fn stream_live_metrics() -> Sse<impl futures_util::Stream<Item = Result<sse::Event, Infallible>>> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    Sse::new(live_metrics::stream(DataPaths::new(&data_dir))).keep_alive(KeepAlive::default())
 }
 
-async fn search_reviews(
-    ExtractJson(search_request): ExtractJson<SearchRequest>,
+#[utoipa::path(
+    get,
+    path = "/v1/analyze/projection",
+    tag = "analysis",
+    params(projection::ProjectionQuery),
+    responses(
+        (status = 200, description = "2D PCA coordinates of a sample of embedded reviews", body = openapi::ProjectionResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "A write held the data lock past the lock timeout", body = ErrorResponse)
+    )
+)]
+async fn get_projection(
+    Query(projection_query): Query<projection::ProjectionQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate the search request
-    if let Err(validation_error) = search_request.validate() {
+    if let Err(validation_error) = projection_query.validate() {
         let error_response = ErrorResponse::from(AppError::Validation(validation_error));
         return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
@@ -309,113 +956,1978 @@ async fn search_reviews(
     // Initialize data paths and storage
     let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
     let data_paths = DataPaths::new(&data_dir);
-    
-    // Ensure directories exist
     if let Err(e) = data_paths.ensure_directories() {
         let error_response = ErrorResponse::from(e);
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
-
     let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
 
-    // Read all reviews for text-based search (TODO: Replace with vector search in Tasks 6 & 7)
-    let all_reviews = match jsonl_storage.read_all_reviews() {
-        Ok(reviews) => reviews,
+    let seed = projection_query.seed.unwrap_or_else(sampling::SampleRng::random_seed);
+    let mut rng = sampling::SampleRng::new(seed);
+
+    // Hold the lock shared so the sampled slots aren't rewritten before they are read
+    let lock = match FileLock::acquire_shared(&data_paths.lock_file, "projection") {
+        Ok(lock) => lock,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+    };
+    // Only reviews whose stored vector matches their text are plotted
+    let sample = vector_store.len().and_then(|stored| {
+        let reviews = jsonl_storage.sample_reviews_where(projection_query.get_sample(), &mut rng, |review| {
+            !review.is_deleted() && EmbeddingStatus::of(review, stored) == EmbeddingStatus::Embedded
+        })?;
+        let slots: Vec<usize> = reviews.iter().map(|review| review.vector_index).collect();
+        Ok((reviews, vector_store.read_slots(&slots)?))
+    });
+    let (reviews, vectors) = match sample {
+        Ok(sample) => sample,
         Err(e) => {
             let error_response = ErrorResponse::from(e);
             return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
         }
     };
+    drop(lock);
 
-    // Perform text-based similarity search (placeholder for vector search)
-    let search_results = perform_text_search(&search_request.query, &all_reviews, search_request.get_limit());
-
-    // TODO: Generate query embedding and search vector index (Tasks 6 & 7)
-    tracing::info!(
-        "Search performed for query: '{}', found {} results",
-        search_request.query,
-        search_results.len()
-    );
+    let (reviews, vectors): (Vec<ReviewMetadata>, Vec<Vec<f32>>) = reviews
+        .into_iter()
+        .zip(vectors)
+        .filter_map(|(review, vector)| Some((review, vector?)))
+        .unzip();
+    let (coordinates, explained_variance) = projection::pca_2d(&vectors);
 
-    // Return search results
     Ok(Json(json!({
         "success": true,
-        "query": search_request.query,
-        "results": search_results,
-        "total_results": search_results.len(),
-        "limit": search_request.get_limit(),
-        "search_type": "text_similarity" // Will be "vector_similarity" after Tasks 6 & 7
+        "method": "pca",
+        "seed": seed,
+        "sample_size": reviews.len(),
+        "explained_variance": explained_variance,
+        "points": projection::points(&reviews, &coordinates)
     })))
 }
 
-/// Perform text-based similarity search (placeholder for vector search)
-fn perform_text_search(query: &str, reviews: &[ReviewMetadata], limit: usize) -> Vec<SearchResult> {
-    let query_lower = query.to_lowercase();
-    let query_words: Vec<&str> = query_lower.split_whitespace().collect();
-    
-    if query_words.is_empty() {
-        return Vec::new();
+#[utoipa::path(
+    post,
+    path = "/v1/analyze/similarity-matrix",
+    tag = "analysis",
+    request_body = similarity_matrix::SimilarityMatrixRequest,
+    responses(
+        (status = 200, description = "Pairwise cosine similarity of the reviews, in request order", body = similarity_matrix::SimilarityMatrixResponse),
+        (status = 400, description = "Fewer than 2, more than 200 or repeated ids", body = ErrorResponse),
+        (status = 404, description = "A review not found or deleted", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn similarity_matrix(
+    ApiJson(matrix_request): ApiJson<similarity_matrix::SimilarityMatrixRequest>,
+) -> Result<Json<similarity_matrix::SimilarityMatrixResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match similarity_matrix::compute(&data_paths, &matrix_request) {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(errors::respond(e)),
     }
+}
 
-    let mut scored_reviews: Vec<(ReviewMetadata, f32)> = reviews
-        .iter()
-        .map(|review| {
-            let score = calculate_text_similarity(&query_lower, &query_words, review);
-            (review.clone(), score)
-        })
-        .filter(|(_, score)| *score > 0.0) // Only include reviews with some similarity
-        .collect();
+#[utoipa::path(
+    get,
+    path = "/v1/reviews/{id}",
+    tag = "reviews",
+    params(("id" = String, Path, description = "Review UUID")),
+    responses(
+        (status = 200, description = "The review", body = openapi::ReviewResponse),
+        (status = 404, description = "Review not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn get_review(
+    Path(review_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
 
-    // Sort by similarity score in descending order
-    scored_reviews.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
 
-    // Take top results up to limit
-    scored_reviews
-        .into_iter()
-        .take(limit)
-        .map(|(review, score)| SearchResult {
-            review,
-            similarity_score: score,
-        })
-        .collect()
-}
-
-/// Calculate text-based similarity score between query and review
-fn calculate_text_similarity(query_lower: &str, query_words: &[&str], review: &ReviewMetadata) -> f32 {
-    let title_lower = review.title.to_lowercase();
-    let body_lower = review.body.to_lowercase();
-    let combined_text = format!("{} {}", title_lower, body_lower);
-    
-    let mut score = 0.0;
-    let total_words = query_words.len() as f32;
-    
-    // Exact phrase matching (highest weight)
-    if combined_text.contains(query_lower) {
-        score += 1.0;
-    }
-    
-    // Individual word matching
-    let mut word_matches = 0;
-    for word in query_words {
-        if combined_text.contains(word) {
-            word_matches += 1;
-            
-            // Higher weight for title matches
-            if title_lower.contains(word) {
-                score += 0.8;
-            } else {
-                score += 0.5;
-            }
+    match jsonl_storage.get_review_by_id(&review_id) {
+        Ok(Some(review)) if !review.is_deleted() => {
+            let stored_slots = vector_store.len().unwrap_or(0);
+            Ok(Json(json!({
+                "success": true,
+                "embedding_status": EmbeddingStatus::of(&review, stored_slots),
+                "review": review
+            })))
+        }
+        Ok(_) => {
+            let error_response = ErrorResponse::from(AppError::NotFound {
+                resource: "Review".to_string(),
+                id: review_id,
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
         }
     }
-    
-    // Bonus for high word match ratio
-    let word_match_ratio = word_matches as f32 / total_words;
-    score += word_match_ratio * 0.5;
-    
-    // Bonus for rating (slight preference for higher-rated reviews)
-    score += (review.rating as f32 - 3.0) * 0.1;
-    
-    // Normalize score to 0-1 range
-    score.min(1.0).max(0.0)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/{id}/similar",
+    tag = "search",
+    params(("id" = String, Path, description = "Review to find similar reviews for"), fields::FieldsQuery),
+    request_body = similar::SimilarReviewsRequest,
+    responses(
+        (status = 200, description = "The most similar other reviews, best first", body = similar::SimilarReviewsResponse),
+        (status = 400, description = "Invalid limit or filters", body = ErrorResponse),
+        (status = 404, description = "Review not found or deleted", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn similar_reviews(
+    Path(review_id): Path<String>,
+    ApiJson(similar_request): ApiJson<similar::SimilarReviewsRequest>,
+) -> Result<Json<similar::SimilarReviewsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match similar::find_similar(&data_paths, &review_id, &similar_request) {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/batch",
+    tag = "reviews",
+    request_body(content = Object, description = "A BatchGetRequest object to look reviews up, or an array of up to 100 ReviewData to create them all-or-nothing"),
+    responses(
+        (status = 200, description = "Lookup: reviews in request order, null where not found or deleted. Create: a BatchCreateResponse with every review created", body = openapi::BatchGetResponse),
+        (status = 400, description = "Invalid request; for a create, `details.errors` lists every invalid review and nothing is stored", body = ErrorResponse),
+        (status = 403, description = "A review's product or reviewer is blocklisted; nothing is stored", body = ErrorResponse),
+        (status = 500, description = "Storage error; for a create, nothing is stored", body = ErrorResponse),
+        (status = 507, description = "Create: corpus size limit reached and the eviction policy refuses writes; nothing is stored", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn batch_reviews(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<Value>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    // An array creates reviews; an object is a lookup, as before creates were added
+    let parsed = match body {
+        Value::Array(_) => extract::from_value(body).map(BatchBody::Create),
+        body => extract::from_value(body).map(BatchBody::Lookup),
+    };
+    match parsed {
+        Ok(BatchBody::Create(reviews)) => match batch_owner(&state.shards, &headers, &reviews)? {
+            Some(owner) => {
+                routing::forward(&owner, Method::POST, "/reviews/batch", &headers, Some(&json!(reviews))).await
+            }
+            None => batch_create_reviews(state, reviews).map(IntoResponse::into_response),
+        },
+        Ok(BatchBody::Lookup(batch_request)) => batch_get_reviews(batch_request).map(IntoResponse::into_response),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::BAD_REQUEST, Json(error_response)))
+        }
+    }
+}
+
+/// The shard a coordinator sends a batch create to. All-or-nothing can't span shards,
+/// so every review in the batch must belong to the same one.
+fn batch_owner(
+    shards: &ShardRouter,
+    headers: &HeaderMap,
+    reviews: &[ReviewData],
+) -> Result<Option<String>, (StatusCode, Json<ErrorResponse>)> {
+    let owners: HashSet<Option<String>> = reviews
+        .iter()
+        .map(|review_data| shards.write_owner(headers, &review_data.product_id))
+        .collect();
+    match owners.len() {
+        0 => Ok(None),
+        1 => Ok(owners.into_iter().next().flatten()),
+        shard_count => {
+            let error_response = ErrorResponse::from(AppError::Validation(ValidationError::InvalidValue {
+                field: "reviews".to_string(),
+                reason: format!(
+                    "products belong to {} different shards; an all-or-nothing batch must stay on one shard",
+                    shard_count
+                ),
+            }));
+            Err((StatusCode::BAD_REQUEST, Json(error_response)))
+        }
+    }
+}
+
+/// The two request shapes `POST /reviews/batch` accepts
+enum BatchBody {
+    Create(Vec<ReviewData>),
+    Lookup(BatchGetRequest),
+}
+
+/// Create every review or none through the ingest service, then count and announce them
+fn batch_create_reviews(
+    state: AppState,
+    reviews: Vec<ReviewData>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let created = match ingest::create_reviews(&data_paths, &reviews) {
+        Ok(created) => created,
+        Err(ingest::BatchCreateError::Failed(e)) => return Err(errors::respond(e)),
+        Err(ingest::BatchCreateError::Rejected { problem, errors }) => {
+            // The first failure sets the status and code; `details.errors` lists them all
+            let listed: Vec<Value> = errors
+                .iter()
+                .map(|(position, e)| match e {
+                    AppError::Duplicate { review_id, .. } => {
+                        json!({ "index": position, "error": e.to_string(), "review_id": review_id })
+                    }
+                    _ => json!({ "index": position, "error": e.to_string() }),
+                })
+                .collect();
+            let (position, first) = errors.into_iter().next().expect("a rejected batch lists its errors");
+            let (status, Json(mut error_response)) = errors::respond(first);
+            error_response.message = format!("Review {} is {}: {}", position, problem, error_response.message);
+            error_response.details.get_or_insert_with(|| json!({}))["errors"] = json!(listed);
+            return Err((status, Json(error_response)));
+        }
+    };
+
+    live_metrics::record_reviews(created.reviews.len());
+    publish_evicted(&state, &data_paths, &created.eviction);
+    for review in &created.reviews {
+        state.events.publish(ReviewEvent::ReviewCreated {
+            review_id: review.id.clone(),
+            product_id: review.product_id.clone(),
+            rating: review.rating,
+            vector_index: review.vector_index,
+            timestamp: review.timestamp,
+        });
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "message": format!("Created {} reviews", created.reviews.len()),
+        "reviews": created.reviews,
+        "embedding_status": EmbeddingStatus::Pending
+    })))
+}
+
+/// Look reviews up by id or vector index
+fn batch_get_reviews(
+    batch_request: BatchGetRequest,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = batch_request.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    // Vector indices are line numbers, so they resolve in a single pass over the file
+    let lookup = match (&batch_request.ids, &batch_request.indices) {
+        (Some(ids), _) => ids
+            .iter()
+            .map(|id| jsonl_storage.get_review_by_id(id))
+            .collect::<Result<Vec<_>, _>>(),
+        (None, Some(indices)) => jsonl_storage.get_reviews_by_indices(indices),
+        (None, None) => Ok(Vec::new()),
+    };
+
+    let reviews: Vec<Option<ReviewMetadata>> = match lookup {
+        Ok(reviews) => reviews
+            .into_iter()
+            .map(|review| review.filter(|review| !review.is_deleted()))
+            .collect(),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+    let total_found = reviews.iter().flatten().count();
+
+    Ok(Json(json!({
+        "success": true,
+        "reviews": reviews,
+        "total_requested": reviews.len(),
+        "total_found": total_found
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/reviews/{id}",
+    tag = "reviews",
+    params(("id" = String, Path, description = "Review UUID")),
+    request_body = ReviewData,
+    responses(
+        (status = 200, description = "Review updated and flagged for re-embedding", body = openapi::ReviewResponse),
+        (status = 400, description = "Invalid request, or on a coordinator a product_id owned by another shard", body = ErrorResponse),
+        (status = 403, description = "Product or reviewer is blocklisted", body = ErrorResponse),
+        (status = 404, description = "Review not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator the owning shard is unreachable", body = ErrorResponse)
+    )
+)]
+async fn update_review(
+    State(state): State<AppState>,
+    Path(review_id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(review_data): ApiJson<ReviewData>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = review_data.validate() {
+        return Err(errors::respond(AppError::Validation(validation_error)));
+    }
+
+    // A coordinator stores nothing itself; the shard owning the review's product does
+    if let Some(owner) = state.shards.review_owner(&headers, &review_id).await.map_err(errors::respond)? {
+        if state.shards.write_owner(&headers, &review_data.product_id).as_deref() != Some(owner.as_str()) {
+            return Err(errors::respond(AppError::Validation(ValidationError::InvalidValue {
+                field: "product_id".to_string(),
+                reason: "belongs to a different shard than the review; reviews can't move between shards"
+                    .to_string(),
+            })));
+        }
+        let path = format!("/reviews/{}", review_id);
+        return routing::forward(&owner, Method::PUT, &path, &headers, Some(&json!(review_data))).await;
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let updated_review = ingest::update_review(&data_paths, &review_id, &review_data).map_err(errors::respond)?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Review updated successfully",
+        "review": updated_review,
+        "embedding_status": EmbeddingStatus::Pending
+    }))
+    .into_response())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/reviews/{id}",
+    tag = "reviews",
+    params(("id" = String, Path, description = "Review UUID")),
+    responses(
+        (status = 200, description = "Review tombstoned", body = Object),
+        (status = 404, description = "Review not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator the owning shard is unreachable", body = ErrorResponse)
+    )
+)]
+async fn delete_review(
+    State(state): State<AppState>,
+    Path(review_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(owner) = state.shards.review_owner(&headers, &review_id).await.map_err(errors::respond)? {
+        return routing::forward(&owner, Method::DELETE, &format!("/reviews/{}", review_id), &headers, None).await;
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let deleted_review = ingest::delete_review(&data_paths, &review_id).map_err(errors::respond)?;
+    publish_deleted(&state, &deleted_review);
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Review deleted successfully",
+        "review_id": deleted_review.id,
+        "vector_index": deleted_review.vector_index,
+        "deleted_at": deleted_review.deleted_at
+    }))
+    .into_response())
+}
+
+/// Announce a tombstoned review to event subscribers and webhooks
+fn publish_deleted(state: &AppState, review: &ReviewMetadata) {
+    state.events.publish(ReviewEvent::ReviewDeleted {
+        review_id: review.id.clone(),
+        product_id: review.product_id.clone(),
+        vector_index: review.vector_index,
+        deleted_at: review.deleted_at.unwrap_or_else(chrono::Utc::now),
+    });
+}
+
+/// Announce reviews evicted under the corpus limits, compacting once tombstones pile up
+fn publish_evicted(state: &AppState, data_paths: &DataPaths, eviction: &Eviction) {
+    for review in &eviction.evicted {
+        publish_deleted(state, review);
+    }
+    if eviction.compact {
+        corpus_limits::reclaim_space(data_paths.data_dir.clone());
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/bulk-delete",
+    tag = "reviews",
+    request_body = BulkDeleteRequest,
+    responses(
+        (status = 200, description = "Matching reviews tombstoned; lists requested ids that were not found. A coordinator deletes on every shard and merges the results", body = Object),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator a shard did not answer", body = ErrorResponse)
+    )
+)]
+async fn bulk_delete_reviews(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(delete_request): ApiJson<BulkDeleteRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Ids and filters can match reviews on any shard, so a coordinator asks them all
+    if let Some(shard_config) = state.shards.search_config(&headers) {
+        delete_request.validate().map_err(|e| errors::respond(e.into()))?;
+        return routing::forward_bulk_delete(&shard_config.nodes, &delete_request, &headers).await.map(Json);
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    let bulk_deleted = ingest::bulk_delete(&data_paths, &delete_request).map_err(errors::respond)?;
+    for review in &bulk_deleted.deleted {
+        publish_deleted(&state, review);
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "message": format!("Deleted {} reviews", bulk_deleted.deleted.len()),
+        "deleted": bulk_deleted.deleted.len(),
+        "deleted_ids": bulk_deleted.deleted.iter().map(|review| &review.id).collect::<Vec<_>>(),
+        "not_found": bulk_deleted.not_found
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/{id}/vote",
+    tag = "reviews",
+    params(("id" = String, Path, description = "Review UUID")),
+    request_body = VoteRequest,
+    responses(
+        (status = 200, description = "Vote counted; returns the review's vote totals", body = Object),
+        (status = 404, description = "Review not found", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator the owning shard is unreachable", body = ErrorResponse)
+    )
+)]
+async fn vote_review(
+    State(state): State<AppState>,
+    Path(review_id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(vote): ApiJson<VoteRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(owner) = state.shards.review_owner(&headers, &review_id).await.map_err(errors::respond)? {
+        let path = format!("/reviews/{}/vote", review_id);
+        return routing::forward(&owner, Method::POST, &path, &headers, Some(&json!(vote))).await;
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let review = ingest::vote_review(&data_paths, &review_id, vote.helpful).map_err(errors::respond)?;
+
+    Ok(Json(json!({
+        "success": true,
+        "review_id": review.id,
+        "helpful_votes": review.helpful_votes,
+        "unhelpful_votes": review.unhelpful_votes,
+        "helpfulness": review.helpfulness()
+    }))
+    .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/reviews/templates/{format}",
+    tag = "bulk",
+    params(("format" = String, Path, description = "Template format: csv or jsonl")),
+    responses(
+        (status = 200, description = "Template file attachment", content_type = "text/plain", body = String),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn download_template(
+    Path(format): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let template_format = match templates::TemplateFormat::parse(&format) {
+        Ok(template_format) => template_format,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    let content = match templates::render(template_format) {
+        Ok(content) => content,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, template_format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", template_format.file_name()),
+            ),
+        ],
+        content,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/reviews/export",
+    tag = "reviews",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "All live reviews, or those under the given licenses, streamed as JSONL, CSV or an Arrow IPC stream with their vectors", body = String, content_type = "application/x-ndjson"),
+        (status = 400, description = "Unsupported format or invalid license", body = ErrorResponse)
+    )
+)]
+async fn export_reviews(
+    Query(export_query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let export_format = match export::ExportFormat::parse(export_query.format.as_deref()) {
+        Ok(export_format) => export_format,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+    let licenses = match export::LicenseFilter::parse(export_query.license.as_deref()) {
+        Ok(licenses) => licenses,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    // The body has no known length, so it is sent with chunked transfer encoding
+    let chunks = export::stream_export(data_paths.data_dir, export_format, licenses);
+    let body = Body::from_stream(futures_util::stream::unfold(chunks, |mut chunks| async move {
+        chunks.recv().await.map(|chunk| (chunk, chunks))
+    }));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, export_format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", export_format.file_name()),
+            ),
+        ],
+        body,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/check-duplicate",
+    tag = "reviews",
+    request_body = DuplicateCheckRequest,
+    responses(
+        (status = 200, description = "Duplicate check result", body = Object),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn check_duplicate(
+    ApiJson(check_request): ApiJson<DuplicateCheckRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Validate the draft
+    if let Err(validation_error) = check_request.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+    let all_reviews = match jsonl_storage.read_live_reviews() {
+        Ok(reviews) => reviews,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    let duplicate = duplicates::find_duplicate(&check_request, &all_reviews, duplicates::DUPLICATE_THRESHOLD);
+
+    Ok(Json(json!({
+        "success": true,
+        "is_duplicate": duplicate.is_some(),
+        "similarity_score": duplicate.map(|(_, score)| score),
+        "matching_review": duplicate.map(|(review, _)| review),
+        "threshold": duplicates::DUPLICATE_THRESHOLD
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/reviews/bulk",
+    tag = "bulk",
+    params(BulkUploadQuery),
+    request_body(content = Object, description = "JSON array of ReviewData, a single ReviewData object, or a JSONL/CSV string"),
+    responses(
+        (status = 200, description = "Bulk upload processed, possibly with per-row failures. On a coordinator each shard stores its products' reviews, and vector indices, job_id and batch_id are reported per shard under `shards`", body = openapi::BulkUploadResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 413, description = "Payload over the request body limit", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 507, description = "Corpus size limit reached and the eviction policy refuses writes", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn bulk_upload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(upload_query): Query<BulkUploadQuery>,
+    ApiJson(bulk_data): ApiJson<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = upload_query.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Parse bulk data - support both array format and JSONL format
+    let reviews = match ingest::parse_bulk_data(&bulk_data) {
+        Ok(reviews) => reviews,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    // A coordinator stores nothing itself; each shard stores the reviews of the products it owns
+    if let Some(parts) = state.shards.split_upload(&headers, &reviews) {
+        let routed = routing::forward_upload(parts, "/reviews/bulk", &upload_query, &headers)
+            .await
+            .map_err(errors::respond)?;
+        return Ok(Json(json!({
+            "success": true,
+            "message": format!("Bulk upload completed: {} successful, {} failed",
+                              routed.result.successful, routed.result.failed.len()),
+            "result": routed.result,
+            "shards": routed.shards,
+            "embedding_status": EmbeddingStatus::Pending
+        })));
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let uploaded = store_upload(&state, &DataPaths::new(&data_dir), reviews, upload_query).map_err(errors::respond)?;
+
+    // Return success response with detailed results
+    Ok(Json(json!({
+        "success": true,
+        "message": format!("Bulk upload completed: {} successful, {} failed", 
+                          uploaded.result.successful, uploaded.result.failed.len()),
+        "result": uploaded.result,
+        "starting_vector_index": uploaded.starting_vector_index,
+        "ending_vector_index": uploaded.ending_vector_index,
+        "job_id": uploaded.job_id,
+        "batch_id": uploaded.batch_id,
+        "embedding_status": EmbeddingStatus::Pending
+    })))
+}
+
+/// Store an upload through the ingest service, then count and announce it. Shared by
+/// `POST /reviews/bulk` and the gRPC `BulkUpload`.
+fn store_upload(
+    state: &AppState,
+    data_paths: &DataPaths,
+    reviews: Vec<ReviewData>,
+    upload_query: BulkUploadQuery,
+) -> Result<ingest::BulkUploaded, AppError> {
+    let _upload = live_metrics::UploadInProgress::start();
+    let uploaded = ingest::bulk_upload(data_paths, reviews, upload_query)?;
+
+    live_metrics::record_reviews(uploaded.result.successful);
+    publish_evicted(state, data_paths, &uploaded.eviction);
+    state.events.publish(ReviewEvent::BulkUploadCompleted {
+        job_id: uploaded.job_id.clone(),
+        batch_id: uploaded.batch_id.clone(),
+        successful: uploaded.result.successful,
+        failed: uploaded.result.failed.len(),
+    });
+    Ok(uploaded)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs",
+    tag = "bulk",
+    params(ListJobsQuery),
+    responses(
+        (status = 200, description = "A page of recorded jobs, newest first", body = openapi::JobPageResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_jobs(
+    Query(list_query): Query<ListJobsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Validate pagination parameters
+    let pagination = list_query.pagination();
+    if let Err(validation_error) = pagination.validate() {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // Initialize data paths and storage
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let job_storage = JobStorage::new(&data_paths.jobs_jsonl);
+
+    let page_size = pagination.get_page_size();
+    let (jobs, total_jobs) = match job_storage.list_jobs(list_query.job_type, pagination.offset(), page_size) {
+        Ok(result) => result,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "jobs": jobs,
+        "page": pagination.get_page(),
+        "page_size": page_size,
+        "total_jobs": total_jobs,
+        "total_pages": total_jobs.div_ceil(page_size)
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    tag = "bulk",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "The job, with progress while it runs", body = openapi::JobResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn get_job(Path(job_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match JobStorage::new(&data_paths.jobs_jsonl).get_job(&job_id) {
+        Ok(Some(job)) => Ok(Json(json!({
+            "success": true,
+            "job": job
+        }))),
+        Ok(None) => {
+            let error_response = ErrorResponse::from(AppError::NotFound {
+                resource: "Job".to_string(),
+                id: job_id,
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/compaction",
+    tag = "bulk",
+    responses(
+        (status = 202, description = "Compaction started; poll the jobs list for progress", body = Object),
+        (status = 409, description = "A compaction is already running", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn start_compaction() -> Result<(StatusCode, Json<Value>), (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+
+    let job = match compaction::start(data_dir.into()) {
+        Ok(job) => job,
+        Err(e @ AppError::Conflict { .. }) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e @ (AppError::LockTimeout { .. } | AppError::Concurrency { .. })) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    tracing::info!("Started compaction job {}", job.id);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "success": true,
+            "message": "Compaction started",
+            "job": job
+        })),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/compact",
+    tag = "system",
+    responses(
+        (status = 202, description = "Compaction started; poll GET /jobs/{id} for progress", body = openapi::JobResponse),
+        (status = 409, description = "A compaction is already running", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn admin_compact() -> Result<(StatusCode, Json<Value>), (StatusCode, Json<ErrorResponse>)> {
+    // Same job as POST /jobs/compaction, next to the other admin maintenance endpoints
+    start_compaction().await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/{id}/pause",
+    tag = "bulk",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Job will pause before its next chunk", body = Object),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 409, description = "Job is not running in this server process", body = ErrorResponse)
+    )
+)]
+async fn pause_job(Path(job_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    set_job_paused(job_id, true)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/{id}/resume",
+    tag = "bulk",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Paused job resumed", body = Object),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 409, description = "Job is not running in this server process", body = ErrorResponse)
+    )
+)]
+async fn resume_job(Path(job_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    set_job_paused(job_id, false)
+}
+
+/// Shared body of the pause and resume handlers
+fn set_job_paused(job_id: String, paused: bool) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    if compaction::set_paused(&job_id, paused) {
+        tracing::info!("Job {} {}", job_id, if paused { "pausing" } else { "resuming" });
+        return Ok(Json(json!({
+            "success": true,
+            "job_id": job_id,
+            "paused": paused
+        })));
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let error = match JobStorage::new(&data_paths.jobs_jsonl).get_job(&job_id) {
+        Ok(Some(job)) => (
+            StatusCode::CONFLICT,
+            AppError::Conflict {
+                message: format!("Job {} is not running in this server process", job.id),
+            },
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            AppError::NotFound {
+                resource: "Job".to_string(),
+                id: job_id,
+            },
+        ),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    Err((error.0, Json(ErrorResponse::from(error.1))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/{id}/rollback",
+    tag = "bulk",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Reviews from the job tombstoned. A coordinator asks every shard, and the one that recorded the job rolls it back", body = Object),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 409, description = "Job already rolled back", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator a shard that may hold the job did not answer", body = ErrorResponse)
+    )
+)]
+async fn rollback_job(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(job_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Each shard records its part of a routed upload as its own job
+    if let Some(shard_config) = state.shards.search_config(&headers) {
+        return routing::forward_rollback(&shard_config.nodes, &job_id, &headers).await.map(Json);
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    let rolled_back = jobs::rollback(&data_paths, &job_id).map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "message": format!("Rolled back {} reviews", rolled_back.reviews_rolled_back),
+        "job": rolled_back.job,
+        "reviews_rolled_back": rolled_back.reviews_rolled_back
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/search",
+    tag = "search",
+    params(fields::FieldsQuery),
+    request_body = SearchRequest,
+    responses(
+        (status = 200, description = "Matching reviews, best first. A coordinator (`SHARD_NODES` set) merges every shard's results and adds `shards`, see CoordinatedSearchResponse", body = SearchResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "A write held the data lock past the lock timeout, or no shard answered", body = ErrorResponse)
+    )
+)]
+async fn search_reviews(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(search_request): ApiJson<SearchRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(shard_config) = state.shards.search_config(&headers) {
+        let coordinated = fanout::search(&shard_config, search_request).await?;
+        return Ok(Json(coordinated).into_response());
+    }
+    run_search(search_request).map(|response| Json(response).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/search/batch",
+    tag = "search",
+    request_body(content = Vec<SearchRequest>, description = "Up to 500 search bodies, as for POST /search"),
+    responses(
+        (status = 200, description = "One result per search, in request order; each holds the search response or the error it failed with", body = batch_search::BatchSearchResponse),
+        (status = 400, description = "Not an array of 1-500 searches", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; a batch counts as one request", body = ErrorResponse)
+    )
+)]
+async fn search_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(searches): ApiJson<Vec<Value>>,
+) -> Result<Json<batch_search::BatchSearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = batch_search::validate(&searches) {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    let response = batch_search::run(state.shards.search_config(&headers), searches).await;
+    tracing::info!("Batch of {} searches run, {} failed", response.results.len(), response.failed);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/search",
+    tag = "search",
+    params(SearchQuery, fields::FieldsQuery),
+    responses(
+        (status = 200, description = "Matching reviews, best first; cacheable for a short time unless partial or personalized. A coordinator adds `shards` as for POST", body = SearchResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "A write held the data lock past the lock timeout, or no shard answered", body = ErrorResponse)
+    )
+)]
+async fn search_reviews_by_query(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(search_query): Query<SearchQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let search_request = match SearchRequest::try_from(search_query) {
+        Ok(search_request) => search_request,
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    // Rankings personalized for one session must not be served to another
+    let cache_control = match search_request.personalization_session() {
+        Some(_) => "private, no-store".to_string(),
+        None => format!("public, max-age={}", SEARCH_CACHE_MAX_AGE_SECS),
+    };
+    if let Some(shard_config) = state.shards.search_config(&headers) {
+        let coordinated = fanout::search(&shard_config, search_request).await?;
+        // A partial answer shouldn't outlive the slow shard
+        let cache_control = if coordinated.shards.partial { "no-store".to_string() } else { cache_control };
+        return Ok(([(header::CACHE_CONTROL, cache_control)], Json(coordinated)).into_response());
+    }
+
+    let response = run_search(search_request)?;
+    Ok(([(header::CACHE_CONTROL, cache_control)], Json(response)).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/webhooks",
+    tag = "webhooks",
+    request_body = webhooks::RegisterWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered; the response is the only place its secret is shown", body = openapi::WebhookRegisteredResponse),
+        (status = 400, description = "Invalid URL, event list or secret", body = ErrorResponse),
+        (status = 409, description = "Webhook limit reached", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn register_webhook(
+    ApiJson(register_request): ApiJson<webhooks::RegisterWebhookRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match webhooks::register(&data_paths, register_request) {
+        Ok(webhook) => {
+            tracing::info!("Registered webhook {} for {:?}", webhook.id, webhook.events);
+            Ok(Json(json!({
+                "success": true,
+                "webhook": webhook
+            })))
+        }
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Registered webhooks, oldest first, without their secrets", body = openapi::WebhookListResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_webhooks() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match webhooks::WebhookStorage::new(&data_paths.webhooks_jsonl).read_all() {
+        Ok(registered) => Ok(Json(json!({
+            "success": true,
+            "webhooks": registered.iter().map(webhooks::Webhook::view).collect::<Vec<_>>()
+        }))),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Webhook removed; deliveries already under way still finish", body = Object),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn delete_webhook(Path(webhook_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    if let Err(e) = data_paths.ensure_directories() {
+        let error_response = ErrorResponse::from(e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let _lock = match FileLock::acquire(&data_paths.lock_file, "delete_webhook") {
+        Ok(lock) => lock,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+    };
+
+    match webhooks::WebhookStorage::new(&data_paths.webhooks_jsonl).remove(&webhook_id) {
+        Ok(Some(webhook)) => Ok(Json(json!({
+            "success": true,
+            "message": "Webhook deleted successfully",
+            "webhook_id": webhook.id
+        }))),
+        Ok(None) => {
+            let error_response = ErrorResponse::from(AppError::NotFound {
+                resource: "Webhook".to_string(),
+                id: webhook_id,
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/curation/rules",
+    tag = "curation",
+    request_body = curation::CreateCurationRuleRequest,
+    responses(
+        (status = 200, description = "Rule stored; it applies to searches from now on", body = openapi::CurationRuleResponse),
+        (status = 400, description = "Invalid scope, pin or boost", body = ErrorResponse),
+        (status = 409, description = "Rule limit reached", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn create_curation_rule(
+    ApiJson(create_request): ApiJson<curation::CreateCurationRuleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match curation::create(&data_paths, create_request) {
+        Ok(rule) => {
+            tracing::info!("Created curation rule {}: {:?}", rule.id, rule.action);
+            Ok(Json(json!({
+                "success": true,
+                "rule": rule
+            })))
+        }
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/curation/rules",
+    tag = "curation",
+    responses(
+        (status = 200, description = "Curation rules, oldest first", body = openapi::CurationRuleListResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_curation_rules() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match curation::CurationStorage::new(&data_paths.curation_jsonl).read_all() {
+        Ok(rules) => Ok(Json(json!({
+            "success": true,
+            "rules": rules
+        }))),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/curation/rules/{id}",
+    tag = "curation",
+    params(("id" = String, Path, description = "Curation rule id")),
+    responses(
+        (status = 200, description = "Rule removed", body = Object),
+        (status = 404, description = "Curation rule not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn delete_curation_rule(Path(rule_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    if let Err(e) = data_paths.ensure_directories() {
+        let error_response = ErrorResponse::from(e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let _lock = match FileLock::acquire(&data_paths.lock_file, "delete_curation_rule") {
+        Ok(lock) => lock,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+    };
+
+    match curation::CurationStorage::new(&data_paths.curation_jsonl).remove(&rule_id) {
+        Ok(Some(rule)) => Ok(Json(json!({
+            "success": true,
+            "message": "Curation rule deleted successfully",
+            "rule_id": rule.id
+        }))),
+        Ok(None) => {
+            let error_response = ErrorResponse::from(AppError::NotFound {
+                resource: "Curation rule".to_string(),
+                id: rule_id,
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/blocklist",
+    tag = "blocklist",
+    request_body = blocklist::CreateBlocklistEntryRequest,
+    responses(
+        (status = 200, description = "Entry stored and audited; it is enforced from now on", body = openapi::BlocklistEntryResponse),
+        (status = 400, description = "Invalid entry", body = ErrorResponse),
+        (status = 409, description = "Value already blocklisted, or entry limit reached", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn create_blocklist_entry(
+    ApiJson(create_request): ApiJson<blocklist::CreateBlocklistEntryRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match blocklist::create(&data_paths, create_request) {
+        Ok(entry) => Ok(Json(json!({
+            "success": true,
+            "entry": entry
+        }))),
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/blocklist",
+    tag = "blocklist",
+    responses(
+        (status = 200, description = "Blocklist entries, oldest first", body = openapi::BlocklistResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_blocklist() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match blocklist::BlocklistStorage::new(&data_paths).read_all() {
+        Ok(entries) => Ok(Json(json!({
+            "success": true,
+            "entries": entries
+        }))),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/blocklist/audit",
+    tag = "blocklist",
+    responses(
+        (status = 200, description = "Every blocklist change, newest first", body = openapi::BlocklistAuditResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_blocklist_audit() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match blocklist::BlocklistStorage::new(&data_paths).read_audit() {
+        Ok(mut records) => {
+            records.reverse();
+            Ok(Json(json!({
+                "success": true,
+                "records": records
+            })))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/blocklist/{id}",
+    tag = "blocklist",
+    params(("id" = String, Path, description = "Blocklist entry id")),
+    responses(
+        (status = 200, description = "Entry removed and the removal audited", body = Object),
+        (status = 404, description = "Blocklist entry not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn delete_blocklist_entry(Path(entry_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    if let Err(e) = data_paths.ensure_directories() {
+        let error_response = ErrorResponse::from(e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let _lock = match FileLock::acquire(&data_paths.lock_file, "delete_blocklist_entry") {
+        Ok(lock) => lock,
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+    };
+
+    match blocklist::BlocklistStorage::new(&data_paths).remove(&entry_id) {
+        Ok(Some(entry)) => Ok(Json(json!({
+            "success": true,
+            "message": "Blocklist entry deleted successfully",
+            "entry_id": entry.id
+        }))),
+        Ok(None) => {
+            let error_response = ErrorResponse::from(AppError::NotFound {
+                resource: "Blocklist entry".to_string(),
+                id: entry_id,
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/data-subjects/{reviewer_id}",
+    tag = "privacy",
+    params(("reviewer_id" = String, Path, description = "Reviewer whose data to export")),
+    responses(
+        (status = 200, description = "Everything stored about the reviewer; empty lists when nothing is", body = openapi::DataSubjectExportResponse),
+        (status = 400, description = "Invalid reviewer_id", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn export_data_subject(Path(reviewer_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    let export = data_subject::export(&data_paths, &reviewer_id).map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "export": export
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/data-subjects/{reviewer_id}",
+    tag = "privacy",
+    params(("reviewer_id" = String, Path, description = "Reviewer whose data to erase")),
+    responses(
+        (status = 200, description = "Reviewer erased irreversibly; the receipt is also stored. A coordinator erases on every shard first, and its receipt adds up theirs", body = openapi::ErasureReceiptResponse),
+        (status = 400, description = "Invalid reviewer_id", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator a shard did not confirm the erasure; nothing is receipted", body = ErrorResponse)
+    )
+)]
+async fn erase_data_subject(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(reviewer_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    // A coordinator's receipt is issued only once every shard has erased the reviewer
+    let shard_receipts = match state.shards.search_config(&headers) {
+        Some(shard_config) => {
+            data_subject::validate_reviewer_id(&reviewer_id).map_err(|e| errors::respond(e.into()))?;
+            routing::forward_erasure(&shard_config.nodes, &reviewer_id, &headers).await?
+        }
+        None => Default::default(),
+    };
+    let receipt = data_subject::erase(&data_paths, &reviewer_id, &shard_receipts).map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "receipt": receipt
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/erasures",
+    tag = "privacy",
+    responses(
+        (status = 200, description = "Erasure receipts, oldest first", body = openapi::ErasureReceiptsResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn list_erasure_receipts() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match data_subject::read_receipts(&data_paths) {
+        Ok(receipts) => Ok(Json(json!({
+            "success": true,
+            "receipts": receipts
+        }))),
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/synonyms/reload",
+    tag = "search",
+    responses(
+        (status = 200, description = "Synonym file reloaded; its groups apply to searches from now on", body = openapi::SynonymsReloadResponse),
+        (status = 400, description = "Synonym file over 1 MiB or malformed; the groups already loaded stay in use", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn reload_synonyms() -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match synonyms::reload(&data_paths) {
+        Ok(summary) => {
+            tracing::info!("Reloaded {} synonym groups", summary.groups);
+            Ok(Json(json!({
+                "success": true,
+                "synonyms": summary
+            })))
+        }
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/graphql",
+    tag = "graphql",
+    request_body = openapi::GraphQLRequest,
+    responses(
+        (status = 200, description = "Query result; failed fields are null with an entry in `errors` (validation errors keep their E_* code in `extensions.code`)", body = openapi::GraphQLResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse)
+    )
+)]
+async fn graphql_query(
+    State(state): State<AppState>,
+    request: async_graphql_axum::GraphQLRequest,
+) -> async_graphql_axum::GraphQLResponse {
+    graphql::execute(state, request.into_inner()).await.into()
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/graphql",
+    tag = "graphql",
+    responses(
+        (status = 200, description = "GraphiQL explorer for the schema", content_type = "text/html")
+    )
+)]
+async fn graphiql() -> axum::response::Html<String> {
+    axum::response::Html(async_graphql::http::GraphiQLSource::build().endpoint("/v1/graphql").finish())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/reindex",
+    tag = "system",
+    responses(
+        (status = 202, description = "Reindex started; poll GET /jobs/{id} for progress", body = openapi::JobResponse),
+        (status = 409, description = "A reindex is already running", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn start_reindex() -> Result<(StatusCode, Json<Value>), (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+
+    let job = match reindex::start(data_dir.into()) {
+        Ok(job) => job,
+        Err(e @ AppError::Conflict { .. }) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e @ (AppError::LockTimeout { .. } | AppError::Concurrency { .. })) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error_response)));
+        }
+        Err(e) => {
+            let error_response = ErrorResponse::from(e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    tracing::info!("Started reindex job {}", job.id);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "success": true,
+            "message": "Reindex started",
+            "job": job
+        })),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/shards",
+    tag = "system",
+    params(routing::ShardTableQuery),
+    responses(
+        (status = 200, description = "Shard nodes of the write routing table and the share of products each owns; `coordinator` is false when SHARD_NODES is unset", body = openapi::ShardTableResponse)
+    )
+)]
+async fn get_shard_table(
+    State(state): State<AppState>,
+    Query(table_query): Query<routing::ShardTableQuery>,
+) -> Json<Value> {
+    Json(json!({
+        "success": true,
+        "table": state.shards.view(table_query.product_id.as_deref())
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/shards/rebalance",
+    tag = "system",
+    request_body = routing::RebalanceRequest,
+    responses(
+        (status = 200, description = "New routing table and the share of products whose owning shard changed; stored reviews are not moved", body = openapi::RebalanceResponse),
+        (status = 400, description = "Invalid node list", body = ErrorResponse)
+    )
+)]
+async fn rebalance_shards(
+    State(state): State<AppState>,
+    ApiJson(rebalance_request): ApiJson<routing::RebalanceRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    match state.shards.rebalance(rebalance_request) {
+        Ok((table, moved_share)) => {
+            tracing::info!(
+                "Shard routing table is now version {} over {} nodes; {:.1}% of products changed shard",
+                table.version,
+                table.nodes.len(),
+                moved_share * 100.0
+            );
+            Ok(Json(json!({
+                "success": true,
+                "table": table,
+                "moved_share": moved_share
+            })))
+        }
+        Err(validation_error) => {
+            let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+            Err((StatusCode::BAD_REQUEST, Json(error_response)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/search/suggest",
+    tag = "search",
+    params(suggest::SuggestQuery),
+    responses(
+        (status = 200, description = "Completions of the prefix from review titles and popular searches", body = suggest::SuggestResponse),
+        (status = 400, description = "Missing or overlong prefix, or invalid limit", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn suggest_search(
+    Query(suggest_query): Query<suggest::SuggestQuery>,
+) -> Result<Json<suggest::SuggestResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    match suggest::suggest(&data_paths, &suggest_query) {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(errors::respond(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/search/clicks",
+    tag = "search",
+    request_body = ClickReport,
+    responses(
+        (status = 200, description = "Click recorded; later searches with this session_id boost similar reviews", body = Object),
+        (status = 400, description = "Invalid session id", body = ErrorResponse),
+        (status = 404, description = "Review not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse)
+    )
+)]
+async fn report_click(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(click): ApiJson<ClickReport>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = personalization::validate_session_id(&click.session_id) {
+        let error_response = ErrorResponse::from(AppError::Validation(validation_error));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // A coordinator personalizes its merged results, so it looks the review up on the shards
+    let review = match state.shards.search_config(&headers) {
+        Some(shard_config) => fanout::find_review(&shard_config, &click.review_id).await,
+        None => {
+            let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+            let data_paths = DataPaths::new(&data_dir);
+            match JsonlStorage::new(&data_paths.reviews_jsonl).get_review_by_id(&click.review_id) {
+                Ok(review) => review.filter(|review| !review.is_deleted()),
+                Err(e) => {
+                    let error_response = ErrorResponse::from(e);
+                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+                }
+            }
+        }
+    };
+    let Some(review) = review else {
+        let error_response = ErrorResponse::from(AppError::NotFound {
+            resource: "Review".to_string(),
+            id: click.review_id,
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let clicks = personalization::record_click(&click.session_id, &review, std::time::Instant::now());
+    Ok(Json(json!({
+        "success": true,
+        "session_id": click.session_id,
+        "clicks": clicks
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/search/clicks/{session_id}",
+    tag = "search",
+    params(("session_id" = String, Path, description = "Session whose clicks to forget")),
+    responses(
+        (status = 200, description = "Session forgotten; `cleared` is the number of clicks it held (0 for an unknown session)", body = Object)
+    )
+)]
+async fn clear_clicks(Path(session_id): Path<String>) -> Json<Value> {
+    let cleared = personalization::clear(&session_id);
+    Json(json!({
+        "success": true,
+        "session_id": session_id,
+        "cleared": cleared
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/ws/search",
+    tag = "search",
+    responses(
+        (status = 101, description = "WebSocket session; send query strings or SearchRequest JSON, receive debounced results tagged with `seq`"),
+        (status = 400, description = "Not a WebSocket upgrade request")
+    )
+)]
+async fn search_session_socket(upgrade: WebSocketUpgrade) -> Response {
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+
+    upgrade
+        .max_message_size(search_session::MAX_SEARCH_MESSAGE_BYTES)
+        .on_upgrade(move |socket| search_session::run(socket, data_paths))
+}
+
+/// How long clients and proxies may reuse a `GET /search` response
+const SEARCH_CACHE_MAX_AGE_SECS: u32 = 30;
+
+
+#[utoipa::path(
+    post,
+    path = "/v1/sandboxes/{id}/reviews",
+    tag = "sandboxes",
+    params(("id" = String, Path, description = "Sandbox id: 1-64 letters, digits, '-' or '_'")),
+    request_body(content = Object, description = "A ReviewData object, or an array of them added all-or-nothing"),
+    responses(
+        (status = 200, description = "Reviews added and embedded; the sandbox is created if new. Only served with SANDBOX_MODE=true", body = openapi::SandboxReviewsResponse),
+        (status = 400, description = "Invalid sandbox id or review", body = ErrorResponse),
+        (status = 409, description = "Sandbox full, or too many sandboxes open", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse)
+    )
+)]
+async fn add_sandbox_reviews(
+    Path(sandbox_id): Path<String>,
+    ApiJson(body): ApiJson<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let reviews: Vec<ReviewData> = match body {
+        Value::Array(_) => extract::from_value(body),
+        body => extract::from_value(body).map(|review| vec![review]),
+    }
+    .map_err(errors::respond)?;
+
+    let (info, created) = sandbox::store()
+        .add_reviews(&sandbox_id, &reviews, std::time::Instant::now())
+        .map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "sandbox": info,
+        "reviews": created
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/sandboxes/{id}/search",
+    tag = "sandboxes",
+    params(("id" = String, Path, description = "Sandbox id")),
+    request_body = SearchRequest,
+    responses(
+        (status = 200, description = "The sandbox's reviews by vector similarity; filters apply, helpfulness, personalization and curation don't", body = SearchResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "No such sandbox, or it expired", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded; see Retry-After", body = ErrorResponse)
+    )
+)]
+async fn search_sandbox(
+    Path(sandbox_id): Path<String>,
+    ApiJson(search_request): ApiJson<SearchRequest>,
+) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    sandbox::store()
+        .search(&sandbox_id, search_request, std::time::Instant::now())
+        .map(Json)
+        .map_err(errors::respond)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/sandboxes/{id}",
+    tag = "sandboxes",
+    params(("id" = String, Path, description = "Sandbox id")),
+    responses(
+        (status = 200, description = "The sandbox and its reviews, oldest first", body = openapi::SandboxReviewsResponse),
+        (status = 404, description = "No such sandbox, or it expired", body = ErrorResponse)
+    )
+)]
+async fn get_sandbox(Path(sandbox_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let (info, reviews) = sandbox::store()
+        .reviews(&sandbox_id, std::time::Instant::now())
+        .map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "sandbox": info,
+        "reviews": reviews
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/sandboxes/{id}",
+    tag = "sandboxes",
+    params(("id" = String, Path, description = "Sandbox id")),
+    responses(
+        (status = 200, description = "Sandbox dropped", body = Object),
+        (status = 404, description = "No such sandbox, or it expired", body = ErrorResponse)
+    )
+)]
+async fn delete_sandbox(Path(sandbox_id): Path<String>) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let dropped = sandbox::store().remove(&sandbox_id).map_err(errors::respond)?;
+    Ok(Json(json!({
+        "success": true,
+        "message": "Sandbox deleted successfully",
+        "sandbox_id": sandbox_id,
+        "reviews_dropped": dropped
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/collections",
+    tag = "collections",
+    responses(
+        (status = 200, description = "Collections, default first. A coordinator lists those on any shard, with the reviews all shards hold in each", body = openapi::CollectionListResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable, or on a coordinator a shard did not answer", body = ErrorResponse)
+    )
+)]
+async fn list_collections(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let collections = match state.shards.search_config(&headers) {
+        Some(shard_config) => routing::forward_list_collections(&shard_config.nodes, &headers).await?,
+        None => {
+            let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+            collections::list(&DataPaths::new(&data_dir)).map_err(errors::respond)?
+        }
+    };
+    Ok(Json(json!({
+        "success": true,
+        "collections": collections
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/collections",
+    tag = "collections",
+    request_body = collections::CreateCollectionRequest,
+    responses(
+        (status = 200, description = "Collection created, empty. A coordinator creates it on every shard that lacks it", body = openapi::CollectionResponse),
+        (status = 400, description = "Invalid collection name", body = ErrorResponse),
+        (status = 409, description = "Collection exists, or the collection limit is reached", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "On a coordinator, a shard did not answer", body = ErrorResponse)
+    )
+)]
+async fn create_collection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(create_request): ApiJson<collections::CreateCollectionRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    let collection = match state.shards.search_config(&headers) {
+        Some(shard_config) => routing::forward_create_collection(&shard_config.nodes, &create_request, &headers).await?,
+        None => {
+            let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+            collections::create(&DataPaths::new(&data_dir), create_request).map_err(errors::respond)?
+        }
+    };
+    Ok(Json(json!({
+        "success": true,
+        "collection": collection
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/collections/{name}/reviews",
+    tag = "collections",
+    params(("name" = String, Path, description = "Collection name, or `default`"), BulkUploadQuery),
+    request_body(content = Object, description = "Reviews in any format POST /reviews/bulk takes"),
+    responses(
+        (status = 200, description = "Upload processed, maybe with row failures. On a coordinator each shard stores its products' reviews in its own copy of the collection, reported under `shards`", body = openapi::BulkUploadResponse),
+        (status = 400, description = "Invalid collection name or request", body = ErrorResponse),
+        (status = 404, description = "No such collection", body = ErrorResponse),
+        (status = 413, description = "Payload over the request body limit", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 507, description = "Corpus size limit reached", body = ErrorResponse),
+        (status = 503, description = "Data lock unavailable", body = ErrorResponse)
+    )
+)]
+async fn add_collection_reviews(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(upload_query): Query<BulkUploadQuery>,
+    ApiJson(bulk_data): ApiJson<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    collections::validate_name(&name).map_err(|e| errors::respond(e.into()))?;
+    let reviews = ingest::parse_bulk_data(&bulk_data).map_err(errors::respond)?;
+
+    // As for POST /reviews/bulk, each shard stores the reviews of the products it owns
+    if let Some(parts) = state.shards.split_upload(&headers, &reviews) {
+        let path = format!("/collections/{}/reviews", name);
+        let routed = routing::forward_upload(parts, &path, &upload_query, &headers).await.map_err(errors::respond)?;
+        return Ok(Json(json!({
+            "success": true,
+            "message": format!(
+                "Upload to collection {} completed: {} successful, {} failed",
+                name,
+                routed.result.successful,
+                routed.result.failed.len()
+            ),
+            "collection": name,
+            "result": routed.result,
+            "shards": routed.shards,
+            "embedding_status": EmbeddingStatus::Pending
+        })));
+    }
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = collections::data_paths(&DataPaths::new(&data_dir), &name).map_err(errors::respond)?;
+    let uploaded = store_upload(&state, &data_paths, reviews, upload_query).map_err(errors::respond)?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": format!(
+            "Upload to collection {} completed: {} successful, {} failed",
+            name,
+            uploaded.result.successful,
+            uploaded.result.failed.len()
+        ),
+        "collection": name,
+        "result": uploaded.result,
+        "starting_vector_index": uploaded.starting_vector_index,
+        "ending_vector_index": uploaded.ending_vector_index,
+        "job_id": uploaded.job_id,
+        "batch_id": uploaded.batch_id,
+        "embedding_status": EmbeddingStatus::Pending
+    })))
+}
+
+/// Shared implementation of the POST and GET search endpoints: the search service,
+/// with the searches counted and logged and failures given their status
+fn run_search(search_request: SearchRequest) -> Result<SearchResponse, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(validation_error) = search_request.validate() {
+        return Err(errors::respond(AppError::Validation(validation_error)));
+    }
+    live_metrics::record_search();
+
+    let data_dir = env::var("DATA_DIR").unwrap_or_else(|_| "backend/data".to_string());
+    let data_paths = DataPaths::new(&data_dir);
+    let query = search_request.query.clone();
+    let started = std::time::Instant::now();
+    let response = search::run(&data_paths, search_request).map_err(errors::respond)?;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    query_log::record(&data_paths, &query_log::QueryLogEntry::of(&query, &response, latency_ms));
+    Ok(response)
 }
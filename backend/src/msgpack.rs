@@ -0,0 +1,159 @@
+//! MessagePack content negotiation for the high-throughput routes (`/reviews`,
+//! `/reviews/bulk`, `/search`).
+//!
+//! Handlers only speak JSON. This middleware sits in front of them: a MessagePack
+//! request body is transcoded to JSON before the handler sees it, and a JSON response
+//! is transcoded to MessagePack when the client's `Accept` header prefers it. Errors
+//! follow the same rule, so a MessagePack client never has to parse JSON.
+
+use crate::models::*;
+use axum::{
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+/// Media type of MessagePack bodies
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Whether a media type (parameters allowed) names MessagePack, including the older
+/// `application/x-msgpack` spelling
+fn is_msgpack(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or("").trim();
+    essence.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE) || essence.eq_ignore_ascii_case("application/x-msgpack")
+}
+
+/// Whether an `Accept` header asks for MessagePack over JSON. Only an explicit
+/// MessagePack range counts; wildcards keep the JSON default.
+pub fn prefers_msgpack(accept: &str) -> bool {
+    let (mut msgpack, mut json) = (0.0f32, 0.0f32);
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let media_type = parts.next().unwrap_or("").trim();
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.parse().ok())
+            .unwrap_or(1.0);
+        if is_msgpack(media_type) {
+            msgpack = msgpack.max(quality);
+        } else if media_type.eq_ignore_ascii_case("application/json") {
+            json = json.max(quality);
+        }
+    }
+    msgpack > 0.0 && msgpack >= json
+}
+
+/// Middleware: accept MessagePack request bodies and answer in MessagePack when asked
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let respond_msgpack = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(prefers_msgpack);
+
+    let request = match decode_request(request).await {
+        Ok(request) => request,
+        Err((status, error)) => {
+            let response = (status, Json(ErrorResponse::from(error))).into_response();
+            return finish(response, respond_msgpack).await;
+        }
+    };
+
+    let response = next.run(request).await;
+    finish(response, respond_msgpack).await
+}
+
+/// Swap a MessagePack body for the equivalent JSON; other requests pass through
+async fn decode_request(request: Request) -> Result<Request, (StatusCode, AppError)> {
+    let is_msgpack_body = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(is_msgpack);
+    if !is_msgpack_body {
+        return Ok(request);
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match Bytes::from_request(Request::from_parts(parts.clone(), body), &()).await {
+        Ok(bytes) => bytes,
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            let error = AppError::PayloadTooLarge {
+                limit_bytes: crate::limits::RequestLimits::from_env().max_body_bytes,
+            };
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, error));
+        }
+        Err(rejection) => {
+            let error = AppError::MalformedMsgpack { message: rejection.body_text() };
+            return Err((StatusCode::BAD_REQUEST, error));
+        }
+    };
+
+    let value: serde_json::Value = rmp_serde::from_slice(&bytes).map_err(|e| {
+        let error = AppError::MalformedMsgpack { message: e.to_string() };
+        (StatusCode::BAD_REQUEST, error)
+    })?;
+    let json = serde_json::to_vec(&value).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, AppError::from(e)))?;
+
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(json.len()));
+    Ok(Request::from_parts(parts, Body::from(json)))
+}
+
+/// Transcode a JSON response to MessagePack if the client asked for it
+async fn finish(response: Response, respond_msgpack: bool) -> Response {
+    let (mut parts, body) = response.into_parts();
+    // Caches must not hand a JSON response to a MessagePack client or the reverse
+    parts.headers.append(header::VARY, HeaderValue::from_static("accept"));
+
+    let is_json = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !respond_msgpack || !is_json {
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read a response body for MessagePack encoding: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let encoded = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .map_err(|e| e.to_string())
+        .and_then(|value| rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()));
+    match encoded {
+        Ok(encoded) => {
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(MSGPACK_CONTENT_TYPE));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(encoded))
+        }
+        Err(e) => {
+            // Still a valid response, just not in the preferred format
+            tracing::warn!("Sending JSON instead of MessagePack: {}", e);
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_header_negotiation() {
+        assert!(prefers_msgpack("application/msgpack"));
+        assert!(prefers_msgpack("application/x-msgpack, */*;q=0.1"));
+        assert!(prefers_msgpack("application/json;q=0.5, application/msgpack"));
+        assert!(!prefers_msgpack("application/json, application/msgpack;q=0.9"));
+        assert!(!prefers_msgpack("application/msgpack;q=0"));
+        assert!(!prefers_msgpack("*/*"));
+        assert!(!prefers_msgpack(""));
+    }
+}
@@ -0,0 +1,482 @@
+//! OpenAPI document for the HTTP API, served at /openapi.json with Swagger UI at /docs.
+//!
+//! Handlers build their success bodies with `json!`, so the response shapes
+//! below exist only to describe those bodies in the generated document.
+
+use crate::analytics::*;
+use crate::ask::{AskRequest, AskResponse, AskSource, ExtractiveAnswer, SupportingSentence};
+use crate::batch_search::{BatchSearchResponse, BatchSearchResult};
+use crate::blocklist::{AuditAction, BlockKind, BlockScope, BlocklistAuditRecord, BlocklistEntry, CreateBlocklistEntryRequest};
+use crate::collections::{CollectionInfo, CreateCollectionRequest};
+use crate::config::{DataPath, EffectiveConfig, Feature, IndexInfo, Setting, SettingSource};
+use crate::curation::{BoostTarget, CreateCurationRuleRequest, CurationAction, CurationRule, RuleScope};
+use crate::data_subject::{ErasureReceipt, SubjectExport};
+use crate::dataset_meta::DatasetMeta;
+use crate::embedding::EmbeddingStatus;
+use crate::embedding_queue::EmbeddingQueueMetrics;
+use crate::events::ReviewEvent;
+use crate::explain::{Blend, KeywordScore, MatchKind, ScoreExplanation, TermMatch};
+use crate::fallback::{Relaxation, SearchFallback};
+use crate::fanout::{CoordinatedSearchResponse, ShardFailure, ShardSummary};
+use crate::error_catalog::ErrorCode;
+use crate::language::Language;
+use crate::live_metrics::{LiveMetrics, QueueDepths, RecentError};
+use crate::lock::{LockHolder, LockMetrics, LockState, LockStatus};
+use crate::models::*;
+use crate::projection::ProjectionPoint;
+use crate::query_log::{LatencyPercentiles, QueryCount, SearchAnalytics};
+use crate::routing::{RebalanceRequest, ShardAssignment, ShardTableView};
+use crate::sandbox::SandboxInfo;
+use crate::sentiment::Sentiment;
+use crate::similar::{SimilarReviewsRequest, SimilarReviewsResponse};
+use crate::similarity_matrix::{SimilarityMatrixRequest, SimilarityMatrixResponse};
+use crate::suggest::{SuggestResponse, Suggestion};
+use crate::synonyms::SynonymsSummary;
+use crate::webhooks::{RegisterWebhookRequest, Webhook, WebhookView};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+/// Body of a successful `POST /reviews`
+#[derive(Serialize, ToSchema)]
+pub struct CreateReviewResponse {
+    pub success: bool,
+    pub message: String,
+    pub review_id: String,
+    pub vector_index: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Body of a single-review response
+#[derive(Serialize, ToSchema)]
+pub struct ReviewResponse {
+    pub success: bool,
+    pub review: ReviewMetadata,
+}
+
+/// Body of a `GET /reviews/random` sample
+#[derive(Serialize, ToSchema)]
+pub struct RandomReviewsResponse {
+    pub success: bool,
+    /// Sampled reviews in random order; fewer than requested if the store is smaller
+    pub reviews: Vec<ReviewMetadata>,
+    pub count: usize,
+    /// Pass back as `seed` to draw the same sample again
+    pub seed: u64,
+}
+
+/// Body of a `GET /analyze/projection`
+#[derive(Serialize, ToSchema)]
+pub struct ProjectionResponse {
+    pub success: bool,
+    /// Always `pca`
+    pub method: String,
+    /// Pass back as `seed` to project the same sample again
+    pub seed: u64,
+    pub sample_size: usize,
+    /// Share of the sample's variance along x and along y
+    pub explained_variance: [f32; 2],
+    pub points: Vec<ProjectionPoint>,
+}
+
+/// Body of a `GET /reviews/meta`
+#[derive(Serialize, ToSchema)]
+pub struct DatasetMetaResponse {
+    pub success: bool,
+    pub meta: DatasetMeta,
+}
+
+/// Body of a `POST /reviews/batch` lookup
+#[derive(Serialize, ToSchema)]
+pub struct BatchGetResponse {
+    pub success: bool,
+    /// One entry per requested id or index, `null` where the review is missing or deleted
+    pub reviews: Vec<Option<ReviewMetadata>>,
+    pub total_requested: usize,
+    pub total_found: usize,
+}
+
+/// Body of a `POST /reviews/batch` create
+#[derive(Serialize, ToSchema)]
+pub struct BatchCreateResponse {
+    pub success: bool,
+    pub message: String,
+    /// The created reviews in request order, with their ids and vector indices
+    pub reviews: Vec<ReviewMetadata>,
+    pub embedding_status: EmbeddingStatus,
+}
+
+/// Body of `GET /admin/shards`
+#[derive(Serialize, ToSchema)]
+pub struct ShardTableResponse {
+    pub success: bool,
+    pub table: ShardTableView,
+}
+
+/// Body of `POST /admin/shards/rebalance`
+#[derive(Serialize, ToSchema)]
+pub struct RebalanceResponse {
+    pub success: bool,
+    pub table: ShardTableView,
+    /// Share of products whose owning shard changed
+    pub moved_share: f64,
+}
+
+/// Body of a successful `POST /webhooks`
+#[derive(Serialize, ToSchema)]
+pub struct WebhookRegisteredResponse {
+    pub success: bool,
+    pub webhook: Webhook,
+}
+
+/// Body of `GET /webhooks`
+#[derive(Serialize, ToSchema)]
+pub struct WebhookListResponse {
+    pub success: bool,
+    pub webhooks: Vec<WebhookView>,
+}
+
+/// Body of a successful `POST /admin/curation/rules`
+#[derive(Serialize, ToSchema)]
+pub struct CurationRuleResponse {
+    pub success: bool,
+    pub rule: CurationRule,
+}
+
+/// Body of `GET /admin/curation/rules`
+#[derive(Serialize, ToSchema)]
+pub struct CurationRuleListResponse {
+    pub success: bool,
+    pub rules: Vec<CurationRule>,
+}
+
+/// Body of a successful `POST /admin/blocklist`
+#[derive(Serialize, ToSchema)]
+pub struct BlocklistEntryResponse {
+    pub success: bool,
+    pub entry: BlocklistEntry,
+}
+
+/// Body of `GET /admin/blocklist`
+#[derive(Serialize, ToSchema)]
+pub struct BlocklistResponse {
+    pub success: bool,
+    pub entries: Vec<BlocklistEntry>,
+}
+
+/// Body of `GET /admin/blocklist/audit`
+#[derive(Serialize, ToSchema)]
+pub struct BlocklistAuditResponse {
+    pub success: bool,
+    pub records: Vec<BlocklistAuditRecord>,
+}
+
+/// Body of `GET /admin/data-subjects/{reviewer_id}`
+#[derive(Serialize, ToSchema)]
+pub struct DataSubjectExportResponse {
+    pub success: bool,
+    pub export: SubjectExport,
+}
+
+/// Body of `DELETE /admin/data-subjects/{reviewer_id}`
+#[derive(Serialize, ToSchema)]
+pub struct ErasureReceiptResponse {
+    pub success: bool,
+    pub receipt: ErasureReceipt,
+}
+
+/// Body of `GET /admin/erasures`
+#[derive(Serialize, ToSchema)]
+pub struct ErasureReceiptsResponse {
+    pub success: bool,
+    pub receipts: Vec<ErasureReceipt>,
+}
+
+/// Body of `POST /admin/lock/break`
+#[derive(Serialize, ToSchema)]
+pub struct LockBreakResponse {
+    pub success: bool,
+    pub broken: bool,
+    pub previous: LockStatus,
+}
+
+/// Body of `POST /admin/synonyms/reload`
+#[derive(Serialize, ToSchema)]
+pub struct SynonymsReloadResponse {
+    pub success: bool,
+    pub synonyms: SynonymsSummary,
+}
+
+/// Body of `POST /sandboxes/{id}/reviews` and `GET /sandboxes/{id}`
+#[derive(Serialize, ToSchema)]
+pub struct SandboxReviewsResponse {
+    pub success: bool,
+    pub sandbox: SandboxInfo,
+    /// The reviews added, or all the sandbox holds
+    pub reviews: Vec<ReviewMetadata>,
+}
+
+/// Body of a successful `POST /collections`
+#[derive(Serialize, ToSchema)]
+pub struct CollectionResponse {
+    pub success: bool,
+    pub collection: CollectionInfo,
+}
+
+/// Body of `GET /collections`
+#[derive(Serialize, ToSchema)]
+pub struct CollectionListResponse {
+    pub success: bool,
+    pub collections: Vec<CollectionInfo>,
+}
+
+/// Body of `POST /graphql`
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    /// Query document, e.g. `{ search(query: "kettle") { results { score review { title } } } }`
+    pub query: String,
+    pub operation_name: Option<String>,
+    #[schema(value_type = Object)]
+    pub variables: Option<serde_json::Value>,
+}
+
+/// Response of `POST /graphql`; `errors[].extensions.code` carries the API error code
+#[derive(Serialize, ToSchema)]
+pub struct GraphQLResponse {
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+    #[schema(value_type = Vec<Object>)]
+    pub errors: Option<Vec<serde_json::Value>>,
+}
+
+/// Body of a paginated review listing
+#[derive(Serialize, ToSchema)]
+pub struct ReviewPageResponse {
+    pub success: bool,
+    pub reviews: Vec<ReviewMetadata>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_reviews: usize,
+    pub total_pages: usize,
+}
+
+/// Body of a processed `POST /reviews/bulk`
+#[derive(Serialize, ToSchema)]
+pub struct BulkUploadResponse {
+    pub success: bool,
+    pub message: String,
+    pub result: BulkUploadResult,
+    pub starting_vector_index: usize,
+    /// `null` when no review was stored
+    pub ending_vector_index: Option<usize>,
+    pub job_id: Option<String>,
+    pub batch_id: Option<String>,
+}
+
+/// Body of a paginated job listing
+#[derive(Serialize, ToSchema)]
+pub struct JobPageResponse {
+    pub success: bool,
+    pub jobs: Vec<JobRecord>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_jobs: usize,
+    pub total_pages: usize,
+}
+
+/// Body of a single job lookup
+#[derive(Serialize, ToSchema)]
+pub struct JobResponse {
+    pub success: bool,
+    pub job: JobRecord,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Semantic Search Backend", description = "Product review storage and semantic search"),
+    paths(
+        crate::health_check,
+        crate::liveness,
+        crate::readiness,
+        crate::stream_events,
+        crate::create_review,
+        crate::list_reviews,
+        crate::get_review,
+        crate::batch_reviews,
+        crate::bulk_delete_reviews,
+        crate::update_review,
+        crate::delete_review,
+        crate::vote_review,
+        crate::similar_reviews,
+        crate::check_duplicate,
+        crate::export_reviews,
+        crate::random_reviews,
+        crate::get_reviews_meta,
+        crate::bulk_upload,
+        crate::download_template,
+        crate::list_product_reviews,
+        crate::get_product_stats,
+        crate::ask_product,
+        crate::search_reviews,
+        crate::search_reviews_by_query,
+        crate::search_batch,
+        crate::list_collections,
+        crate::create_collection,
+        crate::add_collection_reviews,
+        crate::search_session_socket,
+        crate::suggest_search,
+        crate::report_click,
+        crate::clear_clicks,
+        crate::register_webhook,
+        crate::list_webhooks,
+        crate::delete_webhook,
+        crate::create_curation_rule,
+        crate::list_curation_rules,
+        crate::delete_curation_rule,
+        crate::create_blocklist_entry,
+        crate::list_blocklist,
+        crate::list_blocklist_audit,
+        crate::delete_blocklist_entry,
+        crate::export_data_subject,
+        crate::erase_data_subject,
+        crate::list_erasure_receipts,
+        crate::reload_synonyms,
+        crate::add_sandbox_reviews,
+        crate::search_sandbox,
+        crate::get_sandbox,
+        crate::delete_sandbox,
+        crate::graphql_query,
+        crate::graphiql,
+        crate::get_shard_table,
+        crate::rebalance_shards,
+        crate::start_reindex,
+        crate::admin_compact,
+        crate::get_service_stats,
+        crate::get_live_metrics,
+        crate::get_search_analytics,
+        crate::get_effective_config,
+        crate::get_lock_status,
+        crate::break_lock,
+        crate::stream_live_metrics,
+        crate::get_projection,
+        crate::similarity_matrix,
+        crate::list_jobs,
+        crate::get_job,
+        crate::start_compaction,
+        crate::pause_job,
+        crate::resume_job,
+        crate::rollback_job,
+    ),
+    components(schemas(
+        ReviewData,
+        ReviewMetadata,
+        SearchRequest,
+        SearchFilters,
+        Sentiment,
+        Language,
+        RankingWeights,
+        SearchResponse,
+        SearchFallback,
+        Relaxation,
+        CoordinatedSearchResponse,
+        ShardSummary,
+        ShardFailure,
+        ShardTableView,
+        ShardAssignment,
+        RebalanceRequest,
+        RegisterWebhookRequest,
+        Webhook,
+        WebhookView,
+        CreateCurationRuleRequest,
+        CurationRule,
+        CurationAction,
+        RuleScope,
+        BoostTarget,
+        CreateBlocklistEntryRequest,
+        BlocklistEntry,
+        BlockKind,
+        BlockScope,
+        BlocklistAuditRecord,
+        AuditAction,
+        SubjectExport,
+        ErasureReceipt,
+        SandboxInfo,
+        CreateCollectionRequest,
+        CollectionInfo,
+        BatchGetRequest,
+        BulkDeleteRequest,
+        VoteRequest,
+        SearchResult,
+        ScoreExplanation,
+        MatchKind,
+        KeywordScore,
+        TermMatch,
+        Blend,
+        SimilarReviewsRequest,
+        SimilarReviewsResponse,
+        AskRequest,
+        AskResponse,
+        AskSource,
+        SupportingSentence,
+        ExtractiveAnswer,
+        SimilarityMatrixRequest,
+        BatchSearchResponse,
+        BatchSearchResult,
+        SimilarityMatrixResponse,
+        SuggestResponse,
+        Suggestion,
+        SynonymsSummary,
+        DuplicateCheckRequest,
+        BulkUploadResult,
+        BulkError,
+        ErrorResponse,
+        ErrorCode,
+        BatchCreateResponse,
+        JobRecord,
+        JobType,
+        JobStatus,
+        JobProgress,
+        EmbeddingStatus,
+        EmbeddingQueueMetrics,
+        ReviewEvent,
+        ProjectionPoint,
+        DatasetMeta,
+        ReviewStats,
+        ServiceStats,
+        DailyCount,
+        IndexHealth,
+        LockMetrics,
+        LiveMetrics,
+        QueueDepths,
+        RecentError,
+        SearchAnalytics,
+        LatencyPercentiles,
+        QueryCount,
+        EffectiveConfig,
+        Setting,
+        SettingSource,
+        DataPath,
+        IndexInfo,
+        Feature,
+        LockHolder,
+        LockState,
+        LockStatus,
+    )),
+    tags(
+        (name = "reviews", description = "Create, read, update and delete reviews"),
+        (name = "bulk", description = "Bulk uploads, templates and upload history"),
+        (name = "products", description = "Per-product listings and statistics"),
+        (name = "search", description = "Review search"),
+        (name = "webhooks", description = "Callbacks on review lifecycle events"),
+        (name = "curation", description = "Pinned results and boost rules applied when ranking searches"),
+        (name = "blocklist", description = "Products and reviewers kept out of ingestion and search, with an audit log"),
+        (name = "privacy", description = "Export and irreversible erasure of a reviewer's data, for data subject requests"),
+        (name = "collections", description = "Named review sets, e.g. per marketplace, searched alone or together"),
+        (name = "sandboxes", description = "Throwaway in-memory review sets for demos; served only with SANDBOX_MODE=true"),
+        (name = "graphql", description = "GraphQL queries over reviews, products and search"),
+        (name = "system", description = "Health and service statistics"),
+        (name = "analysis", description = "Views of the embedding space for the admin dashboard"),
+    )
+)]
+pub struct ApiDoc;
@@ -0,0 +1,157 @@
+use crate::models::*;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Requests per minute allowed per client when `RATE_LIMIT_PER_MINUTE` is not set
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 120;
+
+/// Above this many tracked clients, idle buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Token bucket for one client: holds up to a minute's worth of requests and refills continuously
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Per-client-IP token bucket limiter, shared by every route it is layered on
+#[derive(Clone)]
+pub struct RateLimiter {
+    requests_per_minute: u32,
+    buckets: Arc<Mutex<HashMap<IpAddr, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `requests_per_minute` per client; 0 disables limiting
+    pub fn new(requests_per_minute: u32) -> Self {
+        RateLimiter {
+            requests_per_minute,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Limit from `RATE_LIMIT_PER_MINUTE`, falling back to the default when unset or invalid
+    pub fn from_env() -> Self {
+        let requests_per_minute = std::env::var("RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE);
+        Self::new(requests_per_minute)
+    }
+
+    pub fn requests_per_minute(&self) -> u32 {
+        self.requests_per_minute
+    }
+
+    /// Take one request from the client's bucket, or return how many seconds to wait
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), u64> {
+        if self.requests_per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = f64::from(self.requests_per_minute);
+        let refill_per_sec = capacity / 60.0;
+
+        // A poisoned map only loses bucket state; keep serving rather than failing requests
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() > MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| now.duration_since(bucket.last_refill).as_secs() < 60);
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / refill_per_sec).ceil() as u64)
+        }
+    }
+}
+
+/// Middleware rejecting requests over the limit with 429, a `Retry-After` header and an ErrorResponse
+pub async fn enforce(State(limiter): State<RateLimiter>, request: Request, next: Next) -> Response {
+    // Requests that did not come through a socket (e.g. in-process tests) share one bucket
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match limiter.check(client, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after_secs) => {
+            tracing::warn!("Rate limit exceeded for {} on {}", client, request.uri().path());
+            let error_response = ErrorResponse::from(AppError::RateLimited { retry_after_secs });
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                Json(error_response),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(2);
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let start = Instant::now();
+
+        assert!(limiter.check(client, start).is_ok());
+        assert!(limiter.check(client, start).is_ok());
+        assert_eq!(limiter.check(client, start), Err(30));
+
+        // Other clients have their own bucket
+        assert!(limiter.check(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), start).is_ok());
+
+        // Two per minute refills one request every 30 seconds
+        assert!(limiter.check(client, start + Duration::from_secs(30)).is_ok());
+        assert!(limiter.check(client, start + Duration::from_secs(30)).is_err());
+
+        assert!(RateLimiter::new(0).check(client, start).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_enforce_returns_429_with_retry_after() {
+        let app = Router::new().route(
+            "/search",
+            get(|| async { "ok" }).route_layer(middleware::from_fn_with_state(RateLimiter::new(1), enforce)),
+        );
+
+        let request = || Request::builder().uri("/search").body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(request()).await.unwrap().status(), StatusCode::OK);
+
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"], "rate_limited");
+        assert_eq!(error["details"]["retry_after_secs"], 60);
+    }
+}
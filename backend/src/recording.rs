@@ -0,0 +1,490 @@
+//! Record/replay mode, for debugging client integrations.
+//!
+//! With `RECORD_CLIENT` set, every request carrying that id in `X-Client-Id` (or
+//! every request, for `*`) is written with its response to
+//! `recordings/{client}.jsonl` in the data directory. Credentials are redacted
+//! before anything is written: the `Authorization`, `Cookie` and API key headers,
+//! and JSON fields such as webhook secrets.
+//!
+//! `semantic-search-backend replay <recording> [target]` sends the recorded
+//! requests, in order, to a server built from another revision and reports where
+//! its responses differ: status codes, and added, missing or changed JSON fields.
+//! Fields that differ on every run (ids, timestamps) are not compared.
+
+use crate::models::AppError;
+use crate::storage::DataPaths;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Header naming the client a request comes from
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Recording file for requests without a client id, when recording every client
+const ANONYMOUS_CLIENT: &str = "anonymous";
+
+/// Longest client id that names a recording file
+const MAX_CLIENT_ID_LENGTH: usize = 64;
+
+/// Written in place of a credential
+const REDACTED: &str = "[redacted]";
+
+/// Headers never written to a recording
+const SECRET_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key"];
+
+/// JSON fields never written to a recording, at any depth
+const SECRET_FIELDS: &[&str] = &["secret", "password", "token", "api_key", "authorization"];
+
+/// JSON fields replay does not compare, because they differ on every run
+const VOLATILE_FIELDS: &[&str] = &["id", "review_id", "job_id", "batch_id", "request_id", "timestamp", "vector_index", "expires_in_secs"];
+
+/// Where replay sends requests when no target is given
+const DEFAULT_TARGET: &str = "http://localhost:8000";
+
+/// One recorded request and the response it got
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub recorded_at: DateTime<Utc>,
+    pub client_id: String,
+    pub method: String,
+    /// Path and query string
+    pub uri: String,
+    pub request_headers: BTreeMap<String, String>,
+    /// JSON bodies as JSON, other text as a string; null when empty or binary
+    pub request_body: Value,
+    pub status: u16,
+    pub response_body: Value,
+}
+
+/// Records the configured client's traffic into the data directory
+#[derive(Clone)]
+pub struct Recorder {
+    /// Client id to record, or `*` for every client
+    client: String,
+    dir: PathBuf,
+    /// Serializes appends, so concurrent exchanges never interleave within a line
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl Recorder {
+    /// Recorder for `RECORD_CLIENT`, writing into the data directory; `None` when recording is off
+    pub fn from_env(data_paths: &DataPaths) -> Option<Self> {
+        let client = std::env::var("RECORD_CLIENT").ok()?.trim().to_string();
+        if client.is_empty() {
+            return None;
+        }
+        let dir = recordings_dir(data_paths);
+        tracing::warn!("Recording requests from client '{}' to {}", client, dir.display());
+        Some(Recorder {
+            client,
+            dir,
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Wrap every route of `router` in the recorder
+    pub fn apply(self, router: Router) -> Router {
+        router.layer(middleware::from_fn_with_state(self, record))
+    }
+
+    /// Recording file for a request's client id, or `None` when the client isn't recorded
+    fn file_for(&self, client_id: Option<&str>) -> Option<(String, PathBuf)> {
+        let client_id = match client_id {
+            Some(id) if self.client == "*" || self.client == id => id,
+            None if self.client == "*" => ANONYMOUS_CLIENT,
+            _ => return None,
+        };
+        // The id becomes a file name, so only plain ids are recorded
+        let plain = !client_id.is_empty()
+            && client_id.len() <= MAX_CLIENT_ID_LENGTH
+            && client_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        plain.then(|| (client_id.to_string(), self.dir.join(format!("{}.jsonl", client_id))))
+    }
+
+    fn append(&self, path: &Path, exchange: &RecordedExchange) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(exchange)?;
+        line.push('\n');
+        let _guard = self.write_lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    }
+}
+
+/// Where the recordings of a data directory are written
+pub fn recordings_dir(data_paths: &DataPaths) -> PathBuf {
+    data_paths.data_dir.join("recordings")
+}
+
+/// The recording files in the data directory
+fn recording_files(data_paths: &DataPaths) -> Result<Vec<PathBuf>, AppError> {
+    let dir = recordings_dir(data_paths);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "jsonl") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Every recorded exchange matching `predicate`, across all recordings
+pub fn find_exchanges<P>(data_paths: &DataPaths, predicate: P) -> Result<Vec<RecordedExchange>, AppError>
+where
+    P: Fn(&RecordedExchange) -> bool,
+{
+    let mut found = Vec::new();
+    for path in recording_files(data_paths)? {
+        let text = std::fs::read_to_string(&path)?;
+        found.extend(
+            text.lines()
+                .filter_map(|line| serde_json::from_str::<RecordedExchange>(line).ok())
+                .filter(|exchange| predicate(exchange)),
+        );
+    }
+    Ok(found)
+}
+
+/// Rewrite the recordings without the exchanges matching `predicate`, returning how many
+/// were dropped. Lines that don't parse are kept as they are.
+pub fn remove_exchanges<P>(data_paths: &DataPaths, predicate: P) -> Result<usize, AppError>
+where
+    P: Fn(&RecordedExchange) -> bool,
+{
+    let mut removed = 0;
+    for path in recording_files(data_paths)? {
+        let text = std::fs::read_to_string(&path)?;
+        let mut kept = String::with_capacity(text.len());
+        let mut dropped = 0;
+        for line in text.lines() {
+            match serde_json::from_str::<RecordedExchange>(line) {
+                Ok(exchange) if predicate(&exchange) => dropped += 1,
+                _ => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+            }
+        }
+        if dropped > 0 {
+            let temp_path = path.with_extension("jsonl.tmp");
+            std::fs::write(&temp_path, kept)?;
+            std::fs::rename(&temp_path, &path)?;
+            removed += dropped;
+        }
+    }
+    Ok(removed)
+}
+
+/// Middleware: buffer the request and response of recorded clients and append them to their recording
+async fn record(State(recorder): State<Recorder>, request: Request, next: Next) -> Response {
+    let client_id = request.headers().get(CLIENT_ID_HEADER).and_then(|value| value.to_str().ok());
+    // Upgrades (the events WebSocket) never finish, so they can't be recorded
+    let target = match request.headers().contains_key(header::UPGRADE) {
+        true => None,
+        false => recorder.file_for(client_id),
+    };
+    let Some((client_id, path)) = target else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    // The request body limit wraps this layer, so an oversized body fails here
+    let Ok(request_bytes) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    let method = parts.method.to_string();
+    let uri = parts.uri.path_and_query().map(|uri| uri.to_string()).unwrap_or_else(|| parts.uri.path().to_string());
+    let request_headers = sanitize_headers(&parts.headers);
+    let request_body = body_value(&request_bytes);
+
+    let response = next.run(Request::from_parts(parts, Body::from(request_bytes))).await;
+    // Event streams stay open, so they are served without being recorded
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    if is_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let response_bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for recording: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let exchange = RecordedExchange {
+        recorded_at: Utc::now(),
+        client_id,
+        method,
+        uri,
+        request_headers,
+        request_body,
+        status: parts.status.as_u16(),
+        response_body: body_value(&response_bytes),
+    };
+    if let Err(e) = recorder.append(&path, &exchange) {
+        tracing::error!("Failed to record request to {}: {}", path.display(), e);
+    }
+
+    Response::from_parts(parts, Body::from(response_bytes))
+}
+
+/// Headers as text, with credentials redacted. Binary values are dropped.
+fn sanitize_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let name = name.as_str();
+            let value = match SECRET_HEADERS.contains(&name) {
+                true => REDACTED.to_string(),
+                false => value.to_str().ok()?.to_string(),
+            };
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+/// A body as JSON (redacted) when it parses, otherwise as text; null when empty or binary
+fn body_value(bytes: &[u8]) -> Value {
+    if bytes.is_empty() {
+        return Value::Null;
+    }
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut json) => {
+            redact(&mut json);
+            json
+        }
+        Err(_) => std::str::from_utf8(bytes).map_or(Value::Null, |text| Value::String(text.to_string())),
+    }
+}
+
+/// Replace the value of every secret field, at any depth
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.to_ascii_lowercase().as_str()) && !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Differences between a recorded and a replayed JSON body, one line per JSON path.
+/// Volatile fields and any field named in `ignored` are skipped.
+pub fn diff(recorded: &Value, replayed: &Value, ignored: &[String]) -> Vec<String> {
+    let mut differences = Vec::new();
+    diff_at("$", recorded, replayed, ignored, &mut differences);
+    differences
+}
+
+fn diff_at(path: &str, recorded: &Value, replayed: &Value, ignored: &[String], differences: &mut Vec<String>) {
+    match (recorded, replayed) {
+        (Value::Object(before), Value::Object(after)) => {
+            let skipped = |name: &str| VOLATILE_FIELDS.contains(&name) || name.ends_with("_at") || ignored.iter().any(|i| i == name);
+            for (name, value) in before.iter().filter(|(name, _)| !skipped(name)) {
+                let field_path = format!("{}.{}", path, name);
+                match after.get(name) {
+                    Some(replayed) => diff_at(&field_path, value, replayed, ignored, differences),
+                    None => differences.push(format!("{}: missing", field_path)),
+                }
+            }
+            for name in after.keys().filter(|name| !before.contains_key(*name) && !skipped(name)) {
+                differences.push(format!("{}.{}: added", path, name));
+            }
+        }
+        (Value::Array(before), Value::Array(after)) => {
+            if before.len() != after.len() {
+                differences.push(format!("{}: {} items, was {}", path, after.len(), before.len()));
+            }
+            for (index, (value, replayed)) in before.iter().zip(after).enumerate() {
+                diff_at(&format!("{}[{}]", path, index), value, replayed, ignored, differences);
+            }
+        }
+        (before, after) if before != after => differences.push(format!("{}: {}, was {}", path, after, before)),
+        _ => {}
+    }
+}
+
+/// Replay options from the command line
+#[derive(Debug, PartialEq)]
+struct ReplayArgs {
+    recording: PathBuf,
+    target: String,
+    ignored: Vec<String>,
+}
+
+/// Parse `replay <recording> [target] [--ignore field,field]`, without the program name
+fn parse_args(args: &[String]) -> Result<ReplayArgs, String> {
+    let mut positional = Vec::new();
+    let mut ignored = Vec::new();
+    let mut args = args.iter().skip(1); // "replay"
+    while let Some(arg) = args.next() {
+        if arg == "--ignore" {
+            let fields = args.next().ok_or("--ignore needs a comma-separated list of fields")?;
+            ignored.extend(fields.split(',').map(str::trim).filter(|f| !f.is_empty()).map(str::to_string));
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+
+    match positional.as_slice() {
+        [recording] => Ok(ReplayArgs {
+            recording: PathBuf::from(recording),
+            target: DEFAULT_TARGET.to_string(),
+            ignored,
+        }),
+        [recording, target] => Ok(ReplayArgs {
+            recording: PathBuf::from(recording),
+            target: target.trim_end_matches('/').to_string(),
+            ignored,
+        }),
+        _ => Err("usage: semantic-search-backend replay <recording.jsonl> [target-url] [--ignore field,field]".to_string()),
+    }
+}
+
+/// Run the `replay` command; returns the process exit code (0 when every response matched)
+pub async fn replay_command(args: &[String]) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(usage) => {
+            eprintln!("{}", usage);
+            return 2;
+        }
+    };
+    let contents = match std::fs::read_to_string(&args.recording) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", args.recording.display(), e);
+            return 2;
+        }
+    };
+
+    let client = reqwest::Client::new();
+    let (mut matched, mut differed) = (0, 0);
+    for (line_number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let exchange: RecordedExchange = match serde_json::from_str(line) {
+            Ok(exchange) => exchange,
+            Err(e) => {
+                eprintln!("line {}: not a recorded exchange: {}", line_number + 1, e);
+                return 2;
+            }
+        };
+
+        let label = format!("{} {}", exchange.method, exchange.uri);
+        match replay(&client, &args.target, &exchange, &args.ignored).await {
+            Ok(differences) if differences.is_empty() => {
+                matched += 1;
+                println!("ok    {}", label);
+            }
+            Ok(differences) => {
+                differed += 1;
+                println!("DIFF  {}", label);
+                for difference in differences {
+                    println!("        {}", difference);
+                }
+            }
+            Err(e) => {
+                differed += 1;
+                println!("FAIL  {}: {}", label, e);
+            }
+        }
+    }
+
+    println!("{} matched, {} differed", matched, differed);
+    if differed == 0 { 0 } else { 1 }
+}
+
+/// Send one recorded request to `target` and compare the response with the recorded one
+async fn replay(client: &reqwest::Client, target: &str, exchange: &RecordedExchange, ignored: &[String]) -> Result<Vec<String>, String> {
+    let method = reqwest::Method::from_bytes(exchange.method.as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = client.request(method, format!("{}{}", target, exchange.uri));
+    for (name, value) in &exchange.request_headers {
+        // The client sets these for the new connection and body
+        if !matches!(name.as_str(), "host" | "content-length") && value != REDACTED {
+            request = request.header(name, value);
+        }
+    }
+    request = match &exchange.request_body {
+        Value::Null => request,
+        Value::String(text) => request.body(text.clone()),
+        json => request.body(json.to_string()),
+    };
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status().as_u16();
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+
+    let mut differences = Vec::new();
+    if status != exchange.status {
+        differences.push(format!("status: {}, was {}", status, exchange.status));
+    }
+    differences.extend(diff(&exchange.response_body, &body_value(&bytes), ignored));
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_credentials_and_diffs_schema_changes() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer abc".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+        let headers = sanitize_headers(&headers);
+        assert_eq!(headers["authorization"], REDACTED);
+        assert_eq!(headers["content-type"], "application/json");
+
+        let body = body_value(br#"{"url": "https://example.com/hook", "secret": "s3cret", "nested": [{"token": "t"}]}"#);
+        assert_eq!(body["secret"], REDACTED);
+        assert_eq!(body["nested"][0]["token"], REDACTED);
+        assert_eq!(body["url"], "https://example.com/hook");
+        assert_eq!(body_value(b""), Value::Null);
+        assert_eq!(body_value(b"plain text"), json!("plain text"));
+
+        let recorded = json!({ "id": "a", "created_at": "then", "total": 2, "items": [{ "rating": 5 }], "old": true });
+        let replayed = json!({ "id": "b", "created_at": "now", "total": 2, "items": [{ "rating": "5" }], "new": 1 });
+        assert_eq!(diff(&recorded, &replayed, &[]), vec!["$.items[0].rating: \"5\", was 5", "$.old: missing", "$.new: added"]);
+        assert_eq!(diff(&recorded, &replayed, &["old".to_string(), "new".to_string()]).len(), 1);
+
+        let args: Vec<String> = ["replay", "client.jsonl", "http://staging:8000/", "--ignore", "total"].iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            parse_args(&args),
+            Ok(ReplayArgs {
+                recording: PathBuf::from("client.jsonl"),
+                target: "http://staging:8000".to_string(),
+                ignored: vec!["total".to_string()],
+            })
+        );
+        assert!(parse_args(&["replay".to_string()]).is_err());
+    }
+}
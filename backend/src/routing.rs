@@ -0,0 +1,1048 @@
+//! Write routing for sharded deployments: each product is owned by one shard, chosen by
+//! consistent hashing of its `product_id`, so all reviews of a product live together.
+//!
+//! Every shard node gets `vnodes_per_node` points on a 64-bit ring (SHA-256 of
+//! `node#i`); a product belongs to the first point at or after its own hash. Adding or
+//! removing a node moves only the products in the arcs that node gains or loses,
+//! roughly `1 / nodes` of them, instead of reshuffling nearly everything as
+//! `hash % nodes` would.
+//!
+//! The table starts from `SHARD_NODES` and lives in memory. Rebalancing replaces it
+//! on this coordinator only, and does not move reviews that are already stored.
+//!
+//! Updates, deletes and votes name a review rather than a product, so the coordinator
+//! asks the shards for the review first and routes by its product. A bulk upload, to
+//! the default collection or a named one, is split by owner, each shard stores its
+//! part, and the per-shard results are merged.
+//!
+//! Writes that no single owner holds go to every shard and the answers are merged: a
+//! bulk delete, whose ids and filters can match reviews anywhere; a rollback, since
+//! each shard keeps its own job history; a data subject erasure; and creating a
+//! collection. These fail as a whole when any shard does not answer, and each is safe
+//! to retry.
+
+use crate::data_subject::ErasureReceipt;
+use crate::errors;
+use crate::fanout::{self, ShardConfig};
+use crate::models::*;
+use semantic_search_core::collections::{self, CollectionInfo, CreateCollectionRequest};
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::Response,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+
+/// Ring points per shard node when `SHARD_VNODES` is not set
+pub const DEFAULT_VNODES_PER_NODE: usize = 64;
+
+/// Most ring points a rebalance may give each node
+pub const MAX_VNODES_PER_NODE: usize = 1024;
+
+/// Response header naming the shard that handled a routed write
+pub const SHARD_NODE_HEADER: &str = "x-shard-node";
+
+/// Position on the ring: the first eight bytes of the key's SHA-256
+fn ring_hash(key: &str) -> u64 {
+    let digest = Sha256::digest(key.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digest is 32 bytes"))
+}
+
+/// Consistent hash ring over the shard nodes
+#[derive(Clone, Debug)]
+pub struct HashRing {
+    nodes: Vec<String>,
+    vnodes_per_node: usize,
+    /// Sorted ring positions and the index of the node owning each
+    points: Vec<(u64, usize)>,
+}
+
+impl HashRing {
+    pub fn new(nodes: Vec<String>, vnodes_per_node: usize) -> Self {
+        let mut points: Vec<(u64, usize)> = nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(owner, node)| (0..vnodes_per_node).map(move |i| (ring_hash(&format!("{}#{}", node, i)), owner)))
+            .collect();
+        points.sort_unstable();
+        HashRing { nodes, vnodes_per_node, points }
+    }
+
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    /// The node owning `key`
+    pub fn owner(&self, key: &str) -> Option<&str> {
+        self.owner_of_hash(ring_hash(key))
+    }
+
+    fn owner_of_hash(&self, hash: u64) -> Option<&str> {
+        let position = self.points.partition_point(|(point, _)| *point < hash);
+        // Past the last point the ring wraps around to the first
+        let (_, owner) = self.points.get(position).or_else(|| self.points.first())?;
+        Some(&self.nodes[*owner])
+    }
+
+    /// Share of the hash space each node owns, in node order
+    pub fn shares(&self) -> Vec<f64> {
+        let mut shares = vec![0.0; self.nodes.len()];
+        for (i, (point, owner)) in self.points.iter().enumerate() {
+            // A point owns the arc back to the point before it
+            let previous = if i == 0 { self.points[self.points.len() - 1].0 } else { self.points[i - 1].0 };
+            shares[*owner] += arc_share(previous, *point);
+        }
+        if self.points.len() == 1 {
+            shares[self.points[0].1] = 1.0;
+        }
+        shares
+    }
+
+    /// Share of the hash space that changes owner going from `self` to `other`
+    pub fn moved_share(&self, other: &HashRing) -> f64 {
+        let mut boundaries: Vec<u64> = self.points.iter().chain(&other.points).map(|(point, _)| *point).collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        if boundaries.is_empty() {
+            return 0.0;
+        }
+        if boundaries.len() == 1 {
+            return if self.owner_of_hash(boundaries[0]) == other.owner_of_hash(boundaries[0]) { 0.0 } else { 1.0 };
+        }
+
+        // Between consecutive boundaries neither ring changes owner
+        let mut moved = 0.0;
+        for (i, boundary) in boundaries.iter().enumerate() {
+            let previous = if i == 0 { boundaries[boundaries.len() - 1] } else { boundaries[i - 1] };
+            if self.owner_of_hash(*boundary) != other.owner_of_hash(*boundary) {
+                moved += arc_share(previous, *boundary);
+            }
+        }
+        moved
+    }
+}
+
+/// Length of the ring arc `(from, to]` as a share of the whole ring
+fn arc_share(from: u64, to: u64) -> f64 {
+    to.wrapping_sub(from) as f64 / 2f64.powi(64)
+}
+
+/// The current ring and when it last changed
+#[derive(Clone, Debug)]
+struct RoutingTable {
+    ring: HashRing,
+    timeout: Duration,
+    version: u64,
+    updated_at: DateTime<Utc>,
+}
+
+/// Write routing state shared by every handler; empty unless this node is a coordinator
+#[derive(Clone, Default)]
+pub struct ShardRouter {
+    table: Arc<RwLock<Option<RoutingTable>>>,
+}
+
+/// Ring points per shard from `SHARD_VNODES`, falling back to the default when unset or out of range
+pub fn vnodes_per_node() -> usize {
+    std::env::var("SHARD_VNODES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|vnodes| (1..=MAX_VNODES_PER_NODE).contains(vnodes))
+        .unwrap_or(DEFAULT_VNODES_PER_NODE)
+}
+
+impl ShardRouter {
+    /// Router over the shards in `SHARD_NODES`, with `SHARD_VNODES` points each
+    pub fn from_env() -> Self {
+        let table = ShardConfig::from_env().map(|config| RoutingTable {
+            ring: HashRing::new(config.nodes, vnodes_per_node()),
+            timeout: config.timeout,
+            version: 1,
+            updated_at: Utc::now(),
+        });
+        ShardRouter {
+            table: Arc::new(RwLock::new(table)),
+        }
+    }
+
+    fn with_table<R>(&self, f: impl FnOnce(&RoutingTable) -> R) -> Option<R> {
+        self.table.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref().map(f)
+    }
+
+    /// Shards to fan a search out to, unless this request is already scoped to this node
+    pub fn search_config(&self, headers: &HeaderMap) -> Option<ShardConfig> {
+        if fanout::is_scoped_local(headers) {
+            return None;
+        }
+        self.with_table(|table| ShardConfig {
+            nodes: table.ring.nodes().to_vec(),
+            timeout: table.timeout,
+        })
+    }
+
+    /// The shard a write for `product_id` belongs on, unless this request is already
+    /// scoped to this node
+    pub fn write_owner(&self, headers: &HeaderMap, product_id: &str) -> Option<String> {
+        if fanout::is_scoped_local(headers) {
+            return None;
+        }
+        self.with_table(|table| table.ring.owner(product_id).map(str::to_string)).flatten()
+    }
+
+    /// The shard a write to the stored review `review_id` belongs on, unless this request
+    /// is already scoped to this node. Only the shards know the review's product, so they
+    /// are asked for it first; a review no reachable shard has is not found.
+    pub async fn review_owner(&self, headers: &HeaderMap, review_id: &str) -> Result<Option<String>, AppError> {
+        let Some(config) = self.search_config(headers) else {
+            return Ok(None);
+        };
+        let review = fanout::find_review(&config, review_id).await.ok_or_else(|| AppError::NotFound {
+            resource: "Review".to_string(),
+            id: review_id.to_string(),
+        })?;
+        Ok(self.write_owner(headers, &review.product_id))
+    }
+
+    /// A bulk upload's reviews grouped by owning shard, each with its 1-based line in the
+    /// upload, unless this request is already scoped to this node
+    pub fn split_upload(&self, headers: &HeaderMap, reviews: &[ReviewData]) -> Option<UploadParts> {
+        self.search_config(headers)?;
+        let mut parts = UploadParts::new();
+        for (line, review_data) in reviews.iter().enumerate() {
+            let owner = self.write_owner(headers, &review_data.product_id)?;
+            parts.entry(owner).or_default().push((line + 1, review_data.clone()));
+        }
+        Some(parts)
+    }
+
+    /// The table as shown by `GET /admin/shards`, with the owner of `product_id` if given
+    pub fn view(&self, product_id: Option<&str>) -> ShardTableView {
+        let view = self.with_table(|table| ShardTableView::of(table, product_id));
+        view.unwrap_or(ShardTableView {
+            coordinator: false,
+            version: 0,
+            updated_at: None,
+            vnodes_per_node: 0,
+            nodes: Vec::new(),
+            owner: None,
+        })
+    }
+
+    /// Replace the ring, returning the share of products whose owner changed
+    pub fn rebalance(&self, request: RebalanceRequest) -> Result<(ShardTableView, f64), ValidationError> {
+        request.validate()?;
+        let mut guard = self.table.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let vnodes_per_node = request
+            .vnodes_per_node
+            .or(guard.as_ref().map(|table| table.ring.vnodes_per_node))
+            .unwrap_or(DEFAULT_VNODES_PER_NODE);
+        let ring = HashRing::new(request.nodes.iter().map(|node| node.trim_end_matches('/').to_string()).collect(), vnodes_per_node);
+
+        let moved_share = guard.as_ref().map_or(1.0, |table| table.ring.moved_share(&ring));
+        let table = RoutingTable {
+            ring,
+            timeout: guard
+                .as_ref()
+                .map_or(Duration::from_millis(fanout::DEFAULT_SHARD_TIMEOUT_MS), |table| table.timeout),
+            version: guard.as_ref().map_or(1, |table| table.version + 1),
+            updated_at: Utc::now(),
+        };
+        let view = ShardTableView::of(&table, None);
+        *guard = Some(table);
+        Ok((view, moved_share))
+    }
+}
+
+/// Query for `GET /admin/shards`
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ShardTableQuery {
+    /// Also report the shard owning this product
+    pub product_id: Option<String>,
+}
+
+/// Body of `POST /admin/shards/rebalance`
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct RebalanceRequest {
+    /// Base URLs of every shard node after the rebalance
+    pub nodes: Vec<String>,
+    /// Ring points per node; defaults to the current setting
+    pub vnodes_per_node: Option<usize>,
+}
+
+impl RebalanceRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.nodes.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "nodes".to_string(),
+                reason: "must list at least one shard node".to_string(),
+            });
+        }
+        for node in &self.nodes {
+            if !node.starts_with("http://") || node.len() <= "http://".len() {
+                return Err(ValidationError::InvalidValue {
+                    field: "nodes".to_string(),
+                    reason: format!("\"{}\" is not an http:// base URL", node),
+                });
+            }
+        }
+        let mut unique = self.nodes.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != self.nodes.len() {
+            return Err(ValidationError::InvalidValue {
+                field: "nodes".to_string(),
+                reason: "lists a node more than once".to_string(),
+            });
+        }
+        if let Some(vnodes) = self.vnodes_per_node {
+            if vnodes == 0 || vnodes > MAX_VNODES_PER_NODE {
+                return Err(ValidationError::InvalidValue {
+                    field: "vnodes_per_node".to_string(),
+                    reason: format!("must be between 1 and {}", MAX_VNODES_PER_NODE),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One shard node and the share of products it owns
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ShardAssignment {
+    pub node: String,
+    pub share: f64,
+}
+
+/// The routing table as reported by the admin endpoints
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ShardTableView {
+    /// False when `SHARD_NODES` is unset and writes are stored locally
+    pub coordinator: bool,
+    /// Bumped on every rebalance
+    pub version: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub vnodes_per_node: usize,
+    pub nodes: Vec<ShardAssignment>,
+    /// Shard owning the product asked about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+impl ShardTableView {
+    fn of(table: &RoutingTable, product_id: Option<&str>) -> Self {
+        let nodes = table
+            .ring
+            .nodes()
+            .iter()
+            .zip(table.ring.shares())
+            .map(|(node, share)| ShardAssignment { node: node.clone(), share })
+            .collect();
+        ShardTableView {
+            coordinator: true,
+            version: table.version,
+            updated_at: Some(table.updated_at),
+            vnodes_per_node: table.ring.vnodes_per_node,
+            nodes,
+            owner: product_id.and_then(|product_id| table.ring.owner(product_id)).map(str::to_string),
+        }
+    }
+}
+
+/// Reviews of a bulk upload by owning shard, each with its 1-based line in the upload
+pub type UploadParts = BTreeMap<String, Vec<(usize, ReviewData)>>;
+
+/// A write request to `node`, scoped so the shard stores it instead of routing it again
+fn shard_request(node: &str, method: Method, path: &str, headers: &HeaderMap) -> reqwest::RequestBuilder {
+    let mut request = fanout::client()
+        .request(method, format!("{}/v1{}", node, path))
+        .header(fanout::SEARCH_SCOPE_HEADER, "local");
+    if let Some(key) = headers.get(crate::idempotency::IDEMPOTENCY_KEY_HEADER) {
+        request = request.header(crate::idempotency::IDEMPOTENCY_KEY_HEADER, key.as_bytes());
+    }
+    request
+}
+
+/// Send a write to the shard that owns it and relay the shard's answer as is
+pub async fn forward(
+    node: &str,
+    method: Method,
+    path: &str,
+    headers: &HeaderMap,
+    body: Option<&Value>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let mut request = shard_request(node, method, path, headers);
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+
+    let unavailable = |e: reqwest::Error| {
+        let error_response = ErrorResponse::from(AppError::ShardsUnavailable {
+            message: format!("{}: {}", node, e),
+        });
+        (StatusCode::SERVICE_UNAVAILABLE, Json(error_response))
+    };
+    let shard_response = request.send().await.map_err(unavailable)?;
+    let status = shard_response.status();
+    let relayed: Vec<(&str, HeaderValue)> = [header::CONTENT_TYPE.as_str(), crate::idempotency::IDEMPOTENT_REPLAYED_HEADER]
+        .into_iter()
+        .filter_map(|name| {
+            let value = shard_response.headers().get(name)?;
+            Some((name, HeaderValue::from_bytes(value.as_bytes()).ok()?))
+        })
+        .collect();
+    let bytes = shard_response.bytes().await.map_err(unavailable)?;
+
+    let mut response = Response::new(Body::from(bytes));
+    *response.status_mut() = StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    for (name, value) in relayed {
+        response.headers_mut().insert(name, value);
+    }
+    if let Ok(node) = HeaderValue::from_str(node) {
+        response.headers_mut().insert(SHARD_NODE_HEADER, node);
+    }
+    Ok(response)
+}
+
+/// One shard's part of a routed bulk upload
+#[derive(Clone, Debug, Serialize)]
+pub struct ShardUpload {
+    pub node: String,
+    pub successful: usize,
+    pub starting_vector_index: Option<usize>,
+    pub ending_vector_index: Option<usize>,
+    /// The part's entry in the shard's job history
+    pub job_id: Option<String>,
+    pub batch_id: Option<String>,
+    /// Why the shard stored none of the part
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A bulk upload once every shard has answered for its part
+#[derive(Clone, Debug, Serialize)]
+pub struct RoutedUpload {
+    pub result: BulkUploadResult,
+    pub shards: Vec<ShardUpload>,
+}
+
+/// Store each part of a split bulk upload on its shard, by posting it to `path`, and
+/// merge what they report, with failures at their lines in the whole upload. A shard
+/// that is unreachable or refuses its part fails that part's reviews, as a bad row
+/// would; the other shards keep theirs. Each shard records its part as its own job.
+pub async fn forward_upload(
+    parts: UploadParts,
+    path: &str,
+    upload_query: &BulkUploadQuery,
+    headers: &HeaderMap,
+) -> Result<RoutedUpload, AppError> {
+    #[derive(Deserialize)]
+    struct Stored {
+        result: BulkUploadResult,
+        starting_vector_index: usize,
+        ending_vector_index: Option<usize>,
+        job_id: Option<String>,
+        batch_id: Option<String>,
+    }
+
+    upload_query.validate()?;
+    if parts.is_empty() {
+        return Err(AppError::Validation(ValidationError::InvalidValue {
+            field: "reviews".to_string(),
+            reason: "No valid reviews found in bulk data".to_string(),
+        }));
+    }
+
+    let mut shards = tokio::task::JoinSet::new();
+    for (node, part) in parts {
+        let request = shard_request(&node, Method::POST, path, headers).query(upload_query);
+        shards.spawn(async move {
+            let reviews: Vec<&ReviewData> = part.iter().map(|(_, review_data)| review_data).collect();
+            let answer = match request.json(&reviews).send().await {
+                Ok(response) if response.status().is_success() => {
+                    response.json::<Stored>().await.map_err(|e| format!("{}: {}", node, e))
+                }
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let message = match response.json::<ErrorResponse>().await {
+                        Ok(error_response) => error_response.message,
+                        Err(_) => "no error details".to_string(),
+                    };
+                    Err(format!("{}: HTTP {}: {}", node, status, message))
+                }
+                Err(e) => Err(format!("{}: {}", node, e)),
+            };
+            (node, part, answer)
+        });
+    }
+
+    let mut result = BulkUploadResult {
+        total_processed: 0,
+        successful: 0,
+        failed: Vec::new(),
+    };
+    let mut shard_uploads = Vec::new();
+    while let Some(joined) = shards.join_next().await {
+        let Ok((node, part, answer)) = joined else {
+            continue;
+        };
+        result.total_processed += part.len();
+        match answer {
+            Ok(stored) => {
+                result.successful += stored.result.successful;
+                // The shard numbers lines within its part
+                result.failed.extend(stored.result.failed.into_iter().map(|mut error| {
+                    error.line_number = part.get(error.line_number.wrapping_sub(1)).map_or(0, |(line, _)| *line);
+                    error
+                }));
+                shard_uploads.push(ShardUpload {
+                    node,
+                    successful: stored.result.successful,
+                    starting_vector_index: Some(stored.starting_vector_index),
+                    ending_vector_index: stored.ending_vector_index,
+                    job_id: stored.job_id,
+                    batch_id: stored.batch_id,
+                    error: None,
+                });
+            }
+            Err(error) => {
+                tracing::warn!("Bulk upload part of {} reviews failed on {}", part.len(), error);
+                result.failed.extend(part.into_iter().map(|(line_number, review_data)| BulkError {
+                    line_number,
+                    error: error.clone(),
+                    data: serde_json::to_value(review_data).ok(),
+                }));
+                shard_uploads.push(ShardUpload {
+                    node,
+                    successful: 0,
+                    starting_vector_index: None,
+                    ending_vector_index: None,
+                    job_id: None,
+                    batch_id: None,
+                    error: Some(error),
+                });
+            }
+        }
+    }
+    result.failed.sort_by_key(|error| error.line_number);
+    shard_uploads.sort_by(|a, b| a.node.cmp(&b.node));
+
+    Ok(RoutedUpload {
+        result,
+        shards: shard_uploads,
+    })
+}
+
+/// `value` as one URL path segment
+fn path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// One shard's answer to a write sent to every shard: its status and JSON body, or why
+/// it gave none
+#[derive(Debug)]
+pub struct ShardAnswer {
+    pub node: String,
+    pub answer: Result<(StatusCode, Value), String>,
+}
+
+impl ShardAnswer {
+    /// The body of a successful answer
+    fn success(&self) -> Option<&Value> {
+        match &self.answer {
+            Ok((status, body)) if status.is_success() => Some(body),
+            _ => None,
+        }
+    }
+
+    fn status(&self) -> Option<StatusCode> {
+        self.answer.as_ref().ok().map(|(status, _)| *status)
+    }
+
+    /// A failed answer as this node's error response: the shard's own error, or
+    /// `ShardsUnavailable` when it gave none
+    fn failure(&self) -> (StatusCode, Json<ErrorResponse>) {
+        let unavailable = |message: String| {
+            errors::respond(AppError::ShardsUnavailable {
+                message: format!("{}: {}", self.node, message),
+            })
+        };
+        match &self.answer {
+            Ok((status, body)) => match serde_json::from_value::<ErrorResponse>(body.clone()) {
+                Ok(error_response) => (*status, Json(error_response)),
+                Err(_) => unavailable(format!("HTTP {}", status.as_u16())),
+            },
+            Err(message) => unavailable(message.clone()),
+        }
+    }
+
+    /// The body of a successful answer, or the answer as this node's error response
+    fn into_result(self) -> Result<Value, (StatusCode, Json<ErrorResponse>)> {
+        match self.answer {
+            Ok((status, body)) if status.is_success() => Ok(body),
+            _ => Err(self.failure()),
+        }
+    }
+}
+
+/// Send the same write to each of `nodes` and collect their answers in node order
+pub async fn forward_all(
+    nodes: &[String],
+    method: Method,
+    path: &str,
+    headers: &HeaderMap,
+    body: Option<&Value>,
+) -> Vec<ShardAnswer> {
+    let mut shards = tokio::task::JoinSet::new();
+    for node in nodes {
+        let mut request = shard_request(node, method.clone(), path, headers);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let node = node.clone();
+        shards.spawn(async move {
+            let answer = match request.send().await {
+                Ok(response) => {
+                    let status = StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+                    response.json::<Value>().await.map(|body| (status, body)).map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            ShardAnswer { node, answer }
+        });
+    }
+
+    let mut answers = Vec::new();
+    while let Some(joined) = shards.join_next().await {
+        if let Ok(answer) = joined {
+            answers.push(answer);
+        }
+    }
+    // A shard whose task died still counts as one that did not answer
+    for node in nodes {
+        if !answers.iter().any(|answer| &answer.node == node) {
+            answers.push(ShardAnswer {
+                node: node.clone(),
+                answer: Err("no answer".to_string()),
+            });
+        }
+    }
+    answers.sort_by(|a, b| a.node.cmp(&b.node));
+    answers
+}
+
+/// Run a bulk delete on every shard and merge what they deleted, in the shape of a
+/// local bulk delete. A shard failing fails the request; the others have tombstoned
+/// their matches already, so a retry reports those ids as not found.
+pub async fn forward_bulk_delete(
+    nodes: &[String],
+    delete_request: &BulkDeleteRequest,
+    headers: &HeaderMap,
+) -> Result<Value, (StatusCode, Json<ErrorResponse>)> {
+    let body = serde_json::to_value(delete_request).map_err(|e| errors::respond(e.into()))?;
+    let mut deleted_ids = Vec::new();
+    for shard in forward_all(nodes, Method::POST, "/reviews/bulk-delete", headers, Some(&body)).await {
+        let deleted = shard.into_result()?;
+        let ids = deleted["deleted_ids"].as_array().into_iter().flatten().filter_map(Value::as_str);
+        deleted_ids.extend(ids.map(str::to_string));
+    }
+
+    let deleted: HashSet<&str> = deleted_ids.iter().map(String::as_str).collect();
+    let mut reported = HashSet::new();
+    let not_found: Vec<&str> = delete_request
+        .ids
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|id| !deleted.contains(id) && reported.insert(*id))
+        .collect();
+    Ok(json!({
+        "success": true,
+        "message": format!("Deleted {} reviews", deleted_ids.len()),
+        "deleted": deleted_ids.len(),
+        "deleted_ids": deleted_ids,
+        "not_found": not_found
+    }))
+}
+
+/// Roll a job back on the shard that recorded it. Each shard keeps its own job history,
+/// so every shard is asked and the one holding the job answers; it is not found only
+/// when every shard says so.
+pub async fn forward_rollback(
+    nodes: &[String],
+    job_id: &str,
+    headers: &HeaderMap,
+) -> Result<Value, (StatusCode, Json<ErrorResponse>)> {
+    let path = format!("/jobs/{}/rollback", path_segment(job_id));
+    let answers = forward_all(nodes, Method::POST, &path, headers, None).await;
+    if let Some(rolled_back) = answers.iter().find_map(ShardAnswer::success) {
+        return Ok(rolled_back.clone());
+    }
+    // A shard that failed some other way may be the one holding the job
+    match answers.iter().find(|shard| shard.status() != Some(StatusCode::NOT_FOUND)) {
+        Some(shard) => Err(shard.failure()),
+        None => Err(errors::respond(AppError::NotFound {
+            resource: "Job".to_string(),
+            id: job_id.to_string(),
+        })),
+    }
+}
+
+/// Erase a data subject on every shard, returning each shard's receipt by node. Any
+/// shard not confirming fails the request, so no receipt is issued while the subject's
+/// reviews may survive somewhere; erasing again is harmless.
+pub async fn forward_erasure(
+    nodes: &[String],
+    reviewer_id: &str,
+    headers: &HeaderMap,
+) -> Result<BTreeMap<String, ErasureReceipt>, (StatusCode, Json<ErrorResponse>)> {
+    let path = format!("/admin/data-subjects/{}", path_segment(reviewer_id));
+    let mut receipts = BTreeMap::new();
+    for shard in forward_all(nodes, Method::DELETE, &path, headers, None).await {
+        let node = shard.node.clone();
+        let mut erased = shard.into_result()?;
+        let receipt = serde_json::from_value(erased["receipt"].take()).map_err(|e| {
+            errors::respond(AppError::ShardsUnavailable {
+                message: format!("{}: {}", node, e),
+            })
+        })?;
+        receipts.insert(node, receipt);
+    }
+    Ok(receipts)
+}
+
+/// Every shard's collections, by node
+async fn shard_collections(
+    nodes: &[String],
+    headers: &HeaderMap,
+) -> Result<Vec<(String, Vec<CollectionInfo>)>, (StatusCode, Json<ErrorResponse>)> {
+    let mut listed = Vec::new();
+    for shard in forward_all(nodes, Method::GET, "/collections", headers, None).await {
+        let node = shard.node.clone();
+        let mut listing = shard.into_result()?;
+        let collections = serde_json::from_value(listing["collections"].take()).map_err(|e| {
+            errors::respond(AppError::ShardsUnavailable {
+                message: format!("{}: {}", node, e),
+            })
+        })?;
+        listed.push((node, collections));
+    }
+    Ok(listed)
+}
+
+/// The collections on any shard, default first, each with the live reviews all the
+/// shards hold in it
+pub async fn forward_list_collections(
+    nodes: &[String],
+    headers: &HeaderMap,
+) -> Result<Vec<CollectionInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let mut review_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, collections) in shard_collections(nodes, headers).await? {
+        for collection in collections {
+            *review_counts.entry(collection.name).or_default() += collection.review_count;
+        }
+    }
+    let mut merged: Vec<CollectionInfo> = review_counts
+        .into_iter()
+        .map(|(name, review_count)| CollectionInfo { name, review_count })
+        .collect();
+    merged.sort_by_key(|collection| collection.name != collections::DEFAULT_COLLECTION);
+    Ok(merged)
+}
+
+/// Create a collection on every shard that lacks it, so a retry after a partial failure
+/// completes it; it is a conflict only when every shard has it already
+pub async fn forward_create_collection(
+    nodes: &[String],
+    create_request: &CreateCollectionRequest,
+    headers: &HeaderMap,
+) -> Result<CollectionInfo, (StatusCode, Json<ErrorResponse>)> {
+    collections::validate_name(&create_request.name).map_err(|e| errors::respond(e.into()))?;
+    let mut missing = Vec::new();
+    let mut review_count = 0;
+    for (node, collections) in shard_collections(nodes, headers).await? {
+        match collections.into_iter().find(|collection| collection.name == create_request.name) {
+            Some(collection) => review_count += collection.review_count,
+            None => missing.push(node),
+        }
+    }
+    if missing.is_empty() {
+        return Err(errors::respond(AppError::Conflict {
+            message: format!("Collection {} already exists", create_request.name),
+        }));
+    }
+
+    let body = json!(create_request);
+    for shard in forward_all(&missing, Method::POST, "/collections", headers, Some(&body)).await {
+        shard.into_result()?;
+    }
+    Ok(CollectionInfo {
+        name: create_request.name.clone(),
+        review_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::{get, post}, Router};
+
+    fn nodes(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("http://shard-{}:8000", i)).collect()
+    }
+
+    #[test]
+    fn test_ring_spreads_products_and_moves_few_on_growth() {
+        let ring = HashRing::new(nodes(3), DEFAULT_VNODES_PER_NODE);
+        assert_eq!(ring.owner("prod_123"), HashRing::new(nodes(3), DEFAULT_VNODES_PER_NODE).owner("prod_123"));
+
+        let shares = ring.shares();
+        assert!((shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(shares.iter().all(|share| (0.2..0.46).contains(share)), "{:?}", shares);
+
+        // A fourth node takes about a quarter of the products, all from the others
+        let grown = HashRing::new(nodes(4), DEFAULT_VNODES_PER_NODE);
+        let moved = ring.moved_share(&grown);
+        assert!((0.15..0.35).contains(&moved), "{}", moved);
+        assert!((moved - grown.shares()[3]).abs() < 1e-9);
+        let stayed = (0..1000)
+            .map(|i| format!("prod_{}", i))
+            .filter(|product| ring.owner(product) == grown.owner(product))
+            .count();
+        assert!((650..850).contains(&stayed), "{}", stayed);
+
+        assert_eq!(ring.moved_share(&ring), 0.0);
+        assert_eq!(HashRing::new(nodes(1), 1).shares(), vec![1.0]);
+    }
+
+    #[test]
+    fn test_rebalance_validates_and_bumps_version() {
+        let router = ShardRouter::default();
+        assert!(!router.view(None).coordinator);
+        assert!(router.write_owner(&HeaderMap::new(), "prod_123").is_none());
+
+        let duplicate = RebalanceRequest { nodes: vec!["http://a".to_string(), "http://a".to_string()], vnodes_per_node: None };
+        assert!(router.rebalance(duplicate).is_err());
+
+        let (view, moved) = router.rebalance(RebalanceRequest { nodes: nodes(2), vnodes_per_node: Some(16) }).unwrap();
+        assert_eq!((view.version, view.vnodes_per_node, moved), (1, 16, 1.0));
+        let (view, _) = router.rebalance(RebalanceRequest { nodes: nodes(3), vnodes_per_node: None }).unwrap();
+        assert_eq!((view.version, view.vnodes_per_node, view.nodes.len()), (2, 16, 3));
+
+        let owner = router.write_owner(&HeaderMap::new(), "prod_123").unwrap();
+        assert_eq!(router.view(Some("prod_123")).owner, Some(owner));
+
+        // Writes a coordinator forwarded are stored where they land
+        let mut scoped = HeaderMap::new();
+        scoped.insert(fanout::SEARCH_SCOPE_HEADER, HeaderValue::from_static("local"));
+        assert!(router.write_owner(&scoped, "prod_123").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forward_relays_the_shard_response() {
+        let app = Router::new().route(
+            "/v1/reviews",
+            post(|headers: HeaderMap| async move {
+                let scope = headers.get(fanout::SEARCH_SCOPE_HEADER).unwrap().to_str().unwrap().to_string();
+                (StatusCode::CREATED, Json(serde_json::json!({ "scope": scope })))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let response = forward(&node, Method::POST, "/reviews", &HeaderMap::new(), Some(&json!({}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[SHARD_NODE_HEADER], node.as_str());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["scope"], "local");
+
+        let (status, _) = forward("http://127.0.0.1:9", Method::POST, "/reviews", &HeaderMap::new(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    fn review(product_id: &str) -> ReviewData {
+        ReviewData {
+            title: "Kettle".to_string(),
+            body: "Boils quickly.".to_string(),
+            product_id: product_id.to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    /// A shard storing one review, of prod_123, that fails the second review of every bulk upload
+    async fn fake_shard() -> String {
+        let app = Router::new()
+            .route(
+                "/v1/reviews/:id",
+                get(|Path(id): Path<String>| async move {
+                    match id.as_str() {
+                        "stored" => Ok(Json(json!({ "review": review("prod_123").to_metadata(0).unwrap() }))),
+                        _ => Err(StatusCode::NOT_FOUND),
+                    }
+                }),
+            )
+            .route(
+                "/v1/reviews/bulk",
+                post(|Json(reviews): Json<Vec<Value>>| async move {
+                    let failed: Vec<Value> = reviews
+                        .iter()
+                        .skip(1)
+                        .take(1)
+                        .map(|data| json!({ "line_number": 2, "error": "invalid", "data": data }))
+                        .collect();
+                    let successful = reviews.len() - failed.len();
+                    Json(json!({
+                        "result": { "total_processed": reviews.len(), "successful": successful, "failed": failed },
+                        "starting_vector_index": 0,
+                        "ending_vector_index": 0,
+                        "job_id": "job",
+                        "batch_id": "batch"
+                    }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        node
+    }
+
+    #[tokio::test]
+    async fn test_review_writes_and_uploads_route_to_owning_shards() {
+        let node = fake_shard().await;
+        let router = ShardRouter::default();
+        let headers = HeaderMap::new();
+        assert!(router.review_owner(&headers, "stored").await.unwrap().is_none());
+        assert!(router.split_upload(&headers, &[review("prod_123")]).is_none());
+
+        router.rebalance(RebalanceRequest { nodes: vec![node.clone()], vnodes_per_node: None }).unwrap();
+        assert_eq!(router.review_owner(&headers, "stored").await.unwrap(), Some(node.clone()));
+        assert!(matches!(router.review_owner(&headers, "missing").await, Err(AppError::NotFound { .. })));
+
+        let reviews: Vec<ReviewData> = (0..4).map(|i| review(&format!("prod_{}", i))).collect();
+        let parts = router.split_upload(&headers, &reviews).unwrap();
+        let lines: Vec<usize> = parts[&node].iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 2, 3, 4]);
+
+        // Failures come back at their lines in the whole upload, and an unreachable shard fails its part
+        let mut parts = UploadParts::new();
+        parts.insert(node.clone(), vec![(3, review("prod_1")), (5, review("prod_2"))]);
+        parts.insert("http://127.0.0.1:9".to_string(), vec![(1, review("prod_3"))]);
+        let upload_query = BulkUploadQuery { source: Some("import".to_string()), ..Default::default() };
+        let merged = forward_upload(parts, "/reviews/bulk", &upload_query, &headers).await.unwrap();
+        assert_eq!((merged.result.total_processed, merged.result.successful), (3, 1));
+        let lines: Vec<usize> = merged.result.failed.iter().map(|error| error.line_number).collect();
+        assert_eq!(lines, vec![1, 5]);
+        let job_ids: Vec<Option<&str>> = merged.shards.iter().map(|shard| shard.job_id.as_deref()).collect();
+        assert_eq!(job_ids, vec![Some("job"), None]);
+        assert!(merged.shards[1].error.is_some());
+        assert!(forward_upload(UploadParts::new(), "/reviews/bulk", &upload_query, &headers).await.is_err());
+    }
+
+    /// A shard holding the review `holds`, the job `job_<holds>`, and with "a" the collection `us`
+    async fn fan_out_shard(holds: &'static str) -> String {
+        let app = Router::new()
+            .route(
+                "/v1/reviews/bulk-delete",
+                post(move |Json(request): Json<BulkDeleteRequest>| async move {
+                    let ids = request.ids.into_iter().flatten();
+                    let deleted: Vec<String> = ids.filter(|id| id == holds).take(1).collect();
+                    Json(json!({ "deleted": deleted.len(), "deleted_ids": deleted }))
+                }),
+            )
+            .route(
+                "/v1/jobs/:id/rollback",
+                post(move |Path(id): Path<String>| async move {
+                    if id == format!("job_{}", holds) {
+                        return Ok(Json(json!({ "success": true, "reviews_rolled_back": 1 })));
+                    }
+                    let not_found = AppError::NotFound { resource: "Job".to_string(), id };
+                    Err((StatusCode::NOT_FOUND, Json(ErrorResponse::from(not_found))))
+                }),
+            )
+            .route(
+                "/v1/admin/data-subjects/:id",
+                axum::routing::delete(move || async move {
+                    let counts = ["reviews", "vectors", "curation_rules", "blocklist_entries"]
+                        .into_iter()
+                        .chain(["blocklist_audit_records", "idempotency_records", "recorded_exchanges"]);
+                    let id = format!("receipt_{}", holds);
+                    let mut receipt = json!({ "id": id, "subject_sha256": "", "erased_at": Utc::now() });
+                    for count in counts {
+                        receipt[count] = json!(1);
+                    }
+                    Json(json!({ "success": true, "receipt": receipt }))
+                }),
+            )
+            .route(
+                "/v1/collections",
+                get(move || async move {
+                    let mut collections = vec![json!({ "name": "default", "review_count": 1 })];
+                    if holds == "a" {
+                        collections.push(json!({ "name": "us", "review_count": 2 }));
+                    }
+                    Json(json!({ "success": true, "collections": collections }))
+                })
+                .post(|| async { Json(json!({ "success": true })) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        node
+    }
+
+    #[tokio::test]
+    async fn test_writes_without_one_owner_go_to_every_shard() {
+        let nodes = vec![fan_out_shard("a").await, fan_out_shard("b").await];
+        let with_unreachable = [nodes.clone(), vec!["http://127.0.0.1:9".to_string()]].concat();
+        let headers = HeaderMap::new();
+
+        let ids = ["a", "b", "missing", "a"].map(str::to_string).to_vec();
+        let delete_request = BulkDeleteRequest { ids: Some(ids), ..Default::default() };
+        let deleted = forward_bulk_delete(&nodes, &delete_request, &headers).await.unwrap();
+        assert_eq!((deleted["deleted"].clone(), deleted["not_found"].clone()), (json!(2), json!(["missing"])));
+        let (status, _) = forward_bulk_delete(&with_unreachable, &delete_request, &headers).await.unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        // Only the shard that recorded a job rolls it back
+        assert_eq!(forward_rollback(&nodes, "job_b", &headers).await.unwrap()["reviews_rolled_back"], 1);
+        assert_eq!(forward_rollback(&nodes, "job_c", &headers).await.unwrap_err().0, StatusCode::NOT_FOUND);
+        let (status, _) = forward_rollback(&with_unreachable, "job_c", &headers).await.unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        // No receipt unless every shard erased the subject
+        let receipts = forward_erasure(&nodes, "reviewer/1", &headers).await.unwrap();
+        let ids: Vec<&str> = receipts.values().map(|receipt| receipt.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"receipt_a") && ids.contains(&"receipt_b"));
+        assert!(forward_erasure(&with_unreachable, "reviewer/1", &headers).await.is_err());
+
+        let collections = forward_list_collections(&nodes, &headers).await.unwrap();
+        let counts: Vec<(&str, usize)> = collections.iter().map(|c| (c.name.as_str(), c.review_count)).collect();
+        assert_eq!(counts, vec![("default", 2), ("us", 2)]);
+        let us = CreateCollectionRequest { name: "us".to_string() };
+        assert_eq!(forward_create_collection(&nodes, &us, &headers).await.unwrap().review_count, 2);
+        let default = CreateCollectionRequest { name: "default".to_string() };
+        assert_eq!(forward_create_collection(&nodes, &default, &headers).await.unwrap_err().0, StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,234 @@
+//! Interactive search over a WebSocket, for search-as-you-type clients.
+//!
+//! Each text frame is a query: either a bare query string or a JSON `SearchRequest`.
+//! Frames that arrive within `SEARCH_DEBOUNCE` of each other replace one another, so
+//! only the query the user paused on is searched. Every reply carries the `seq` of the
+//! frame it answers (frames are numbered from 1), letting clients drop answers to
+//! queries they have moved past. Results are cached per connection until the review
+//! file changes, so backspacing to an earlier query is answered without a search.
+
+use crate::models::*;
+use crate::storage::DataPaths;
+use axum::extract::ws::{Message, WebSocket};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Quiet period after the last frame before its query is searched
+pub const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Distinct searches remembered per connection
+pub const SESSION_CACHE_CAPACITY: usize = 32;
+
+/// Largest frame a client may send; a query is at most 500 characters
+pub const MAX_SEARCH_MESSAGE_BYTES: usize = 16 * 1024;
+
+/// A frame sent back to the client
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionReply {
+    Results {
+        seq: u64,
+        cached: bool,
+        #[serde(flatten)]
+        response: Box<SearchResponse>,
+    },
+    Error {
+        seq: u64,
+        #[serde(flatten)]
+        error: ErrorResponse,
+    },
+}
+
+/// Length and modification time of the review file; any write changes one of them
+type Fingerprint = Option<(u64, SystemTime)>;
+
+fn fingerprint(reviews_jsonl: &Path) -> Fingerprint {
+    let metadata = std::fs::metadata(reviews_jsonl).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Search responses of one connection, keyed by the request that produced them
+#[derive(Default)]
+pub struct SessionCache {
+    fingerprint: Fingerprint,
+    responses: HashMap<String, SearchResponse>,
+    order: VecDeque<String>,
+}
+
+impl SessionCache {
+    /// Forget everything if the review file changed since the cache was filled
+    pub fn validate(&mut self, fingerprint: Fingerprint) {
+        if fingerprint != self.fingerprint {
+            self.responses.clear();
+            self.order.clear();
+            self.fingerprint = fingerprint;
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SearchResponse> {
+        self.responses.get(key)
+    }
+
+    /// Remember a response, evicting the oldest once full
+    pub fn insert(&mut self, key: String, response: SearchResponse) {
+        if self.responses.insert(key.clone(), response).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > SESSION_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.responses.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Read a frame as a search: JSON if it looks like an object, otherwise the query itself
+pub fn parse_query(text: &str) -> Result<SearchRequest, AppError> {
+    if text.trim_start().starts_with('{') {
+        return serde_json::from_str(text).map_err(|e| AppError::MalformedJson { message: e.to_string() });
+    }
+    Ok(SearchRequest {
+        query: text.to_string(),
+        limit: None,
+        offset: None,
+        cursor: None,
+        filters: None,
+        rank_by_helpfulness: false,
+        ranking: None,
+        session_id: None,
+        personalize: None,
+        curate: None,
+        correct_typos: None,
+        group_by_product: false,
+        fields: Vec::new(),
+        explain: false,
+        min_score: None,
+        fallback: None,
+        collections: Vec::new(),
+    })
+}
+
+/// Serve one connection until the client closes it
+pub async fn run(mut socket: WebSocket, data_paths: DataPaths) {
+    let mut cache = SessionCache::default();
+    let mut seq = 0;
+    let mut pending: Option<SearchRequest> = None;
+
+    loop {
+        let frame = if pending.is_some() {
+            match tokio::time::timeout(SEARCH_DEBOUNCE, socket.recv()).await {
+                Ok(frame) => frame,
+                Err(_) => {
+                    let Some(search_request) = pending.take() else { continue };
+                    let reply = search(&mut cache, &data_paths, seq, search_request).await;
+                    if send(&mut socket, &reply).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+            }
+        } else {
+            socket.recv().await
+        };
+
+        match frame {
+            Some(Ok(Message::Text(text))) => {
+                seq += 1;
+                match parse_query(&text) {
+                    Ok(search_request) => pending = Some(search_request),
+                    Err(e) => {
+                        pending = None;
+                        let reply = SessionReply::Error { seq, error: ErrorResponse::from(e) };
+                        if send(&mut socket, &reply).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            // Pings are answered by axum; binary frames carry no query
+            Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Binary(_))) => {}
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+        }
+    }
+}
+
+async fn search(cache: &mut SessionCache, data_paths: &DataPaths, seq: u64, search_request: SearchRequest) -> SessionReply {
+    cache.validate(fingerprint(&data_paths.reviews_jsonl));
+    let key = serde_json::to_string(&search_request).unwrap_or_default();
+    if let Some(response) = cache.get(&key) {
+        return SessionReply::Results { seq, cached: true, response: Box::new(response.clone()) };
+    }
+
+    // Fingerprint before searching, so a write during the search invalidates its result
+    let before = fingerprint(&data_paths.reviews_jsonl);
+    match tokio::task::spawn_blocking(move || crate::run_search(search_request)).await {
+        Ok(Ok(response)) => {
+            if before == fingerprint(&data_paths.reviews_jsonl) {
+                cache.insert(key, response.clone());
+            }
+            SessionReply::Results { seq, cached: false, response: Box::new(response) }
+        }
+        Ok(Err((_, error))) => SessionReply::Error { seq, error: error.0 },
+        Err(e) => SessionReply::Error {
+            seq,
+            error: ErrorResponse::from(AppError::FileOperation(std::io::Error::other(e.to_string()))),
+        },
+    }
+}
+
+async fn send(socket: &mut WebSocket, reply: &SessionReply) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(reply).unwrap_or_default();
+    socket.send(Message::Text(text)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(query: &str) -> SearchResponse {
+        SearchResponse {
+            success: true,
+            query: query.to_string(),
+            results: Vec::new(),
+            total_results: 0,
+            limit: 10,
+            offset: 0,
+            next_cursor: None,
+            filters: None,
+            search_type: "text_similarity".to_string(),
+            corrected_query: None,
+            expanded_terms: Vec::new(),
+            personalized: false,
+            applied_rules: Vec::new(),
+            parsed_query: None,
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_query_accepts_plain_text_and_json() {
+        assert_eq!(parse_query("quiet fan").unwrap().query, "quiet fan");
+        let search_request = parse_query(r#"{"query": "fan", "limit": 3}"#).unwrap();
+        assert_eq!((search_request.query.as_str(), search_request.limit), ("fan", Some(3)));
+        assert!(parse_query("{not json").is_err());
+    }
+
+    #[test]
+    fn test_session_cache_evicts_oldest_and_clears_on_write() {
+        let mut cache = SessionCache::default();
+        let written = Some((10, SystemTime::UNIX_EPOCH));
+        cache.validate(written);
+        for n in 0..=SESSION_CACHE_CAPACITY {
+            cache.insert(n.to_string(), response(&n.to_string()));
+        }
+        assert!(cache.get("0").is_none());
+        assert_eq!(cache.get("1").unwrap().query, "1");
+
+        cache.validate(written);
+        assert!(cache.get("1").is_some());
+        cache.validate(Some((20, SystemTime::UNIX_EPOCH)));
+        assert!(cache.get("1").is_none());
+    }
+}
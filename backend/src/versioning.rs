@@ -0,0 +1,225 @@
+//! Response schema versions.
+//!
+//! Handlers answer in the v1 schema. A client asks for v2 with the `/v2` path
+//! prefix, or on any path with `Accept: application/vnd.semantic-search.v2+json`
+//! (the header wins over the prefix). This middleware then rewrites the v1 body
+//! into the v2 shape, so both schemas are served by one set of handlers and
+//! existing clients, old frontend builds included, keep getting v1 during a rollout.
+//!
+//! v2 differs from v1 in two ways:
+//! - No `success` field; the status code says whether a request succeeded
+//! - Search responses score hits as `score` and group `offset`, `limit`, the result
+//!   count and `next_cursor` under `page`
+
+use crate::models::*;
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{Map, Value};
+
+/// Path prefix serving the v2 schema
+pub const V2_PREFIX: &str = "/v2";
+
+/// Response header naming the schema version of the body
+pub const VERSION_HEADER: &str = "api-version";
+
+/// Vendor media type naming a schema version, e.g. `application/vnd.semantic-search.v2+json`
+const VENDOR_PREFIX: &str = "application/vnd.semantic-search.v";
+
+/// Response schema versions, oldest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    fn from_number(number: &str) -> Option<Self> {
+        match number {
+            "1" => Some(ApiVersion::V1),
+            "2" => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+
+    fn number(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "1",
+            ApiVersion::V2 => "2",
+        }
+    }
+
+    fn media_type(self) -> String {
+        format!("{}{}+json", VENDOR_PREFIX, self.number())
+    }
+}
+
+/// The newest version an `Accept` header asks for with a vendor media type.
+/// `Ok(None)` when it names none. When it names only unknown versions, `Err`, unless
+/// it also accepts another type (`*/*`, `application/json`), which then gets v1.
+pub fn requested_version(accept: &str) -> Result<Option<ApiVersion>, String> {
+    let mut named = Vec::new();
+    let mut accepts_other = false;
+    for range in accept.split(',') {
+        let media_type = range.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match media_type.strip_prefix(VENDOR_PREFIX).and_then(|rest| rest.strip_suffix("+json")) {
+            Some(number) => named.push(number.to_string()),
+            None => accepts_other |= !media_type.is_empty(),
+        }
+    }
+
+    match named.iter().filter_map(|number| ApiVersion::from_number(number)).max() {
+        Some(version) => Ok(Some(version)),
+        None if named.is_empty() || accepts_other => Ok(None),
+        None => Err(format!("unsupported version {}; supported versions are 1 and 2", named.join(", "))),
+    }
+}
+
+/// Middleware: work out the requested version and rewrite v1 responses to it
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let accept = request.headers().get(header::ACCEPT).and_then(|value| value.to_str().ok()).unwrap_or("");
+    let by_header = match requested_version(accept) {
+        Ok(version) => version,
+        Err(reason) => {
+            let error = AppError::Validation(ValidationError::InvalidValue {
+                field: "accept".to_string(),
+                reason,
+            });
+            return (StatusCode::NOT_ACCEPTABLE, Json(ErrorResponse::from(error))).into_response();
+        }
+    };
+    let path = request.uri().path().to_string();
+    let by_prefix = (path == V2_PREFIX || path.starts_with(&format!("{}/", V2_PREFIX))).then_some(ApiVersion::V2);
+    let version = by_header.or(by_prefix).unwrap_or(ApiVersion::V1);
+
+    let response = next.run(request).await;
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert(VERSION_HEADER, HeaderValue::from_static(version.number()));
+    parts.headers.append(header::VARY, HeaderValue::from_static("accept"));
+
+    let content_type = parts.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).unwrap_or("");
+    let is_json = content_type.starts_with("application/json");
+    let is_msgpack = content_type.starts_with(crate::msgpack::MSGPACK_CONTENT_TYPE);
+    if version == ApiVersion::V1 || !(is_json || is_msgpack) {
+        if by_header.is_some() && is_json {
+            parts.headers.insert(header::CONTENT_TYPE, vendor_content_type(version));
+        }
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read a response body for version {}: {}", version.number(), e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let decoded = match is_json {
+        true => serde_json::from_slice::<Value>(&bytes).map_err(|e| e.to_string()),
+        false => rmp_serde::from_slice::<Value>(&bytes).map_err(|e| e.to_string()),
+    };
+    let mut value = match decoded {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!("Sending an unconverted body for version {}: {}", version.number(), e);
+            return Response::from_parts(parts, Body::from(bytes));
+        }
+    };
+    to_v2(&path, &mut value);
+
+    let encoded = match is_json {
+        true => serde_json::to_vec(&value).map_err(|e| e.to_string()),
+        false => rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()),
+    };
+    match encoded {
+        Ok(encoded) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            if by_header.is_some() && is_json {
+                parts.headers.insert(header::CONTENT_TYPE, vendor_content_type(version));
+            }
+            Response::from_parts(parts, Body::from(encoded))
+        }
+        Err(e) => {
+            tracing::error!("Failed to encode a version {} body: {}", version.number(), e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn vendor_content_type(version: ApiVersion) -> HeaderValue {
+    HeaderValue::from_str(&version.media_type()).unwrap_or(HeaderValue::from_static("application/json"))
+}
+
+/// Rewrite a v1 body served at `path` into the v2 schema
+pub fn to_v2(path: &str, body: &mut Value) {
+    let Value::Object(fields) = body else {
+        return;
+    };
+    fields.remove("success");
+
+    // Error bodies on search paths have no results and pass unchanged
+    if path.ends_with("/search") && fields.contains_key("results") {
+        if let Some(Value::Array(results)) = fields.get_mut("results") {
+            for hit in results.iter_mut().filter_map(Value::as_object_mut) {
+                if let Some(score) = hit.remove("similarity_score") {
+                    hit.insert("score".to_string(), score);
+                }
+            }
+        }
+
+        let mut page = Map::new();
+        for (v1, v2) in [("offset", "offset"), ("limit", "limit"), ("total_results", "count"), ("next_cursor", "next_cursor")] {
+            if let Some(value) = fields.remove(v1) {
+                page.insert(v2.to_string(), value);
+            }
+        }
+        fields.insert("page".to_string(), Value::Object(page));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_negotiates_version_and_rewrites_search() {
+        assert_eq!(requested_version("application/json"), Ok(None));
+        assert_eq!(requested_version("application/vnd.semantic-search.v2+json"), Ok(Some(ApiVersion::V2)));
+        assert_eq!(
+            requested_version("application/vnd.semantic-search.v1+json, application/vnd.semantic-search.v2+json;q=0.5"),
+            Ok(Some(ApiVersion::V2))
+        );
+        assert_eq!(requested_version("application/vnd.semantic-search.v9+json, */*"), Ok(None));
+        assert!(requested_version("application/vnd.semantic-search.v9+json").is_err());
+
+        let mut search = json!({
+            "success": true,
+            "query": "kettle",
+            "results": [{ "review": { "id": "a" }, "similarity_score": 0.9 }],
+            "total_results": 1,
+            "limit": 10,
+            "offset": 0,
+            "search_type": "vector_similarity"
+        });
+        to_v2("/v2/search", &mut search);
+        assert_eq!(
+            search,
+            json!({
+                "query": "kettle",
+                "results": [{ "review": { "id": "a" }, "score": 0.9 }],
+                "page": { "offset": 0, "limit": 10, "count": 1 },
+                "search_type": "vector_similarity"
+            })
+        );
+
+        let mut created = json!({ "success": true, "review": { "id": "a" } });
+        to_v2("/v2/reviews", &mut created);
+        assert_eq!(created, json!({ "review": { "id": "a" } }));
+    }
+}
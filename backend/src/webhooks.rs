@@ -0,0 +1,485 @@
+//! Webhooks: signed HTTP callbacks on review lifecycle events.
+//!
+//! Registrations live in webhooks.jsonl in the data directory, so every process sharing
+//! it delivers to the same endpoints. Each process delivers the events of the writes it
+//! handles, taken from its `EventBus`, so an event is sent once however many processes
+//! run. A delivery is a POST of a JSON envelope, signed with HMAC-SHA256 under the
+//! webhook's secret. Network errors, timeouts, 408, 429 and 5xx answers are retried with
+//! exponential backoff; other answers end the delivery. Pending retries are held in
+//! memory, so a restart drops them.
+
+use crate::events::{EventBus, ReviewEvent};
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Events a webhook can subscribe to
+pub const WEBHOOK_EVENTS: [&str; 3] = ["review.created", "review.deleted", "bulk_upload.completed"];
+
+/// Registrations allowed per data directory
+pub const MAX_WEBHOOKS: usize = 50;
+
+/// Longest accepted callback URL
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 2048;
+
+/// Header carrying `sha256=<hex HMAC of "{timestamp}.{body}">`
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+/// Header carrying the Unix time the signature was made, for replay protection
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "x-webhook-event";
+/// Header carrying the delivery id, the same on every attempt
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+
+/// How long one attempt may take
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A registered callback
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Subscribed events
+    pub events: Vec<String>,
+    /// HMAC key for the signature header; only returned on registration
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    fn subscribes_to(&self, event: &str) -> bool {
+        self.events.iter().any(|subscribed| subscribed == event)
+    }
+
+    /// The webhook as listed, without its secret
+    pub fn view(&self) -> WebhookView {
+        WebhookView {
+            id: self.id.clone(),
+            url: self.url.clone(),
+            events: self.events.clone(),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// A registered callback as listed
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct WebhookView {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body of `POST /webhooks`
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct RegisterWebhookRequest {
+    /// http or https URL to POST events to
+    pub url: String,
+    /// Events to send; all of them when omitted
+    pub events: Option<Vec<String>>,
+    /// HMAC key of 16 to 256 characters; one is generated when omitted
+    pub secret: Option<String>,
+}
+
+impl RegisterWebhookRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let url = reqwest::Url::parse(&self.url).map_err(|e| ValidationError::InvalidValue {
+            field: "url".to_string(),
+            reason: e.to_string(),
+        })?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ValidationError::InvalidValue {
+                field: "url".to_string(),
+                reason: "must be an http or https URL".to_string(),
+            });
+        }
+        if self.url.len() > MAX_WEBHOOK_URL_LENGTH {
+            return Err(ValidationError::TooLong {
+                field: "url".to_string(),
+                max_length: MAX_WEBHOOK_URL_LENGTH,
+            });
+        }
+
+        if let Some(events) = &self.events {
+            let unknown = events.iter().find(|event| !WEBHOOK_EVENTS.contains(&event.as_str()));
+            if events.is_empty() || unknown.is_some() {
+                return Err(ValidationError::InvalidValue {
+                    field: "events".to_string(),
+                    reason: format!("must be a non-empty list of {}", WEBHOOK_EVENTS.join(", ")),
+                });
+            }
+        }
+
+        if let Some(secret) = &self.secret {
+            if !(16..=256).contains(&secret.chars().count()) {
+                return Err(ValidationError::InvalidValue {
+                    field: "secret".to_string(),
+                    reason: "must be between 16 and 256 characters".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// JSONL file of webhook registrations
+pub struct WebhookStorage {
+    file_path: PathBuf,
+}
+
+impl WebhookStorage {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Read every registration, oldest first
+    pub fn read_all(&self) -> Result<Vec<Webhook>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(File::open(&self.file_path)?);
+        let mut webhooks = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                webhooks.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(webhooks)
+    }
+
+    /// Append a registration. The caller must hold the FileLock.
+    pub fn append(&self, webhook: &Webhook) -> Result<(), AppError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.file_path)?;
+        writeln!(file, "{}", serde_json::to_string(webhook)?)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Atomically drop the registration with `id`, returning it. The caller must hold the FileLock.
+    pub fn remove(&self, id: &str) -> Result<Option<Webhook>, AppError> {
+        let (removed, kept): (Vec<Webhook>, Vec<Webhook>) =
+            self.read_all()?.into_iter().partition(|webhook| webhook.id == id);
+        if removed.is_empty() {
+            return Ok(None);
+        }
+
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for webhook in &kept {
+                writeln!(writer, "{}", serde_json::to_string(webhook)?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, &self.file_path)?;
+
+        Ok(removed.into_iter().next())
+    }
+}
+
+/// Validate and store a registration
+pub fn register(data_paths: &DataPaths, request: RegisterWebhookRequest) -> Result<Webhook, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let storage = WebhookStorage::new(&data_paths.webhooks_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "register_webhook")?;
+    if storage.read_all()?.len() >= MAX_WEBHOOKS {
+        return Err(AppError::Conflict {
+            message: format!("At most {} webhooks can be registered", MAX_WEBHOOKS),
+        });
+    }
+
+    let webhook = Webhook {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: request.url,
+        events: request
+            .events
+            .unwrap_or_else(|| WEBHOOK_EVENTS.iter().map(|event| event.to_string()).collect()),
+        secret: request
+            .secret
+            .unwrap_or_else(|| format!("whsec_{}", uuid::Uuid::new_v4().simple())),
+        created_at: Utc::now(),
+    };
+    storage.append(&webhook)?;
+    Ok(webhook)
+}
+
+/// Webhook event name of a bus event
+pub fn event_name(event: &ReviewEvent) -> &'static str {
+    match event {
+        ReviewEvent::ReviewCreated { .. } => "review.created",
+        ReviewEvent::ReviewDeleted { .. } => "review.deleted",
+        ReviewEvent::BulkUploadCompleted { .. } => "bulk_upload.completed",
+    }
+}
+
+/// One event on its way to one webhook
+#[derive(Clone, Debug)]
+pub struct Delivery {
+    pub id: String,
+    pub event: &'static str,
+    /// The JSON envelope sent on every attempt
+    pub body: String,
+}
+
+impl Delivery {
+    pub fn new(event: &ReviewEvent) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut data = serde_json::to_value(event).unwrap_or(Value::Null);
+        if let Some(fields) = data.as_object_mut() {
+            fields.remove("type");
+        }
+        let body = json!({
+            "id": id,
+            "event": event_name(event),
+            "created_at": Utc::now(),
+            "data": data
+        })
+        .to_string();
+        Delivery {
+            id,
+            event: event_name(event),
+            body,
+        }
+    }
+}
+
+/// `sha256=<hex>` signature of a body sent at `timestamp`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// How often and how patiently a delivery is retried
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubles for each one after
+    pub first_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            first_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Send a delivery until the endpoint accepts it, gives a final answer, or attempts run
+/// out. Returns the number of attempts made and whether the last one succeeded.
+pub async fn deliver(webhook: &Webhook, delivery: &Delivery, policy: RetryPolicy) -> (u32, bool) {
+    let mut delay = policy.first_delay;
+    for attempt in 1..=policy.max_attempts {
+        let timestamp = Utc::now().timestamp();
+        let sent = crate::fanout::client()
+            .post(&webhook.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(&webhook.secret, timestamp, &delivery.body))
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(EVENT_HEADER, delivery.event)
+            .header(DELIVERY_HEADER, &delivery.id)
+            .body(delivery.body.clone())
+            .send()
+            .await;
+
+        let retry = match sent {
+            Ok(response) if response.status().is_success() => return (attempt, true),
+            Ok(response) => {
+                let status = response.status();
+                tracing::warn!(
+                    "Webhook {} answered {} to delivery {} (attempt {})",
+                    webhook.id,
+                    status,
+                    delivery.id,
+                    attempt
+                );
+                status.is_server_error() || matches!(status.as_u16(), 408 | 429)
+            }
+            Err(e) => {
+                tracing::warn!("Webhook {} delivery {} failed (attempt {}): {}", webhook.id, delivery.id, attempt, e);
+                true
+            }
+        };
+        if !retry {
+            return (attempt, false);
+        }
+        if attempt < policy.max_attempts {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    (policy.max_attempts, false)
+}
+
+/// Deliver the events published on `events` to the webhooks registered in `data_dir`,
+/// until the process exits
+pub fn spawn(data_dir: PathBuf, events: &EventBus) {
+    let mut receiver = events.receiver();
+    tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::error!("Webhook delivery fell behind; {} events were not delivered", missed);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+
+            let data_dir = data_dir.clone();
+            let webhooks = tokio::task::spawn_blocking(move || {
+                let data_paths = DataPaths::new(data_dir);
+                let _lock = FileLock::acquire_shared(&data_paths.lock_file, "read_webhooks")?;
+                WebhookStorage::new(&data_paths.webhooks_jsonl).read_all()
+            })
+            .await;
+            let webhooks = match webhooks {
+                Ok(Ok(webhooks)) => webhooks,
+                Ok(Err(e)) => {
+                    tracing::error!("Could not read webhooks; {} event not delivered: {}", event_name(&event), e);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::error!("Webhook lookup task failed: {}", e);
+                    continue;
+                }
+            };
+
+            for webhook in webhooks.into_iter().filter(|webhook| webhook.subscribes_to(event_name(&event))) {
+                let delivery = Delivery::new(&event);
+                tokio::spawn(async move {
+                    match deliver(&webhook, &delivery, RetryPolicy::default()).await {
+                        (attempts, true) => {
+                            tracing::info!("Delivered {} {} to webhook {} in {} attempts", delivery.event, delivery.id, webhook.id, attempts)
+                        }
+                        (attempts, false) => {
+                            tracing::error!("Gave up on {} {} to webhook {} after {} attempts", delivery.event, delivery.id, webhook.id, attempts)
+                        }
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    fn request(url: &str) -> RegisterWebhookRequest {
+        RegisterWebhookRequest {
+            url: url.to_string(),
+            events: None,
+            secret: None,
+        }
+    }
+
+    #[test]
+    fn test_register_validates_and_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        for bad in [
+            request("ftp://example.com/hook"),
+            request("not a url"),
+            RegisterWebhookRequest { events: Some(vec!["review.updated".to_string()]), ..request("https://example.com") },
+            RegisterWebhookRequest { secret: Some("short".to_string()), ..request("https://example.com") },
+        ] {
+            assert!(matches!(register(&data_paths, bad), Err(AppError::Validation(_))));
+        }
+
+        let webhook = register(&data_paths, request("https://example.com/hook")).unwrap();
+        assert_eq!(webhook.events, WEBHOOK_EVENTS);
+        assert!(webhook.secret.starts_with("whsec_"));
+
+        let storage = WebhookStorage::new(&data_paths.webhooks_jsonl);
+        assert_eq!(storage.read_all().unwrap()[0].secret, webhook.secret);
+        assert_eq!(storage.remove(&webhook.id).unwrap().unwrap().id, webhook.id);
+        assert!(storage.remove(&webhook.id).unwrap().is_none());
+        assert!(storage.read_all().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed_and_retried() {
+        // An endpoint that fails the first attempt and records every request
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(|State(received): State<Received>, headers: HeaderMap, body: String| async move {
+                    let mut received = received.lock().unwrap();
+                    received.push((headers, body));
+                    if received.len() == 1 { axum::http::StatusCode::SERVICE_UNAVAILABLE } else { axum::http::StatusCode::NO_CONTENT }
+                }),
+            )
+            .route("/gone", post(|| async { axum::http::StatusCode::GONE }))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let webhook = |path: &str| Webhook {
+            id: "wh_1".to_string(),
+            url: format!("http://{}{}", addr, path),
+            events: vec!["review.deleted".to_string()],
+            secret: "0123456789abcdef".to_string(),
+            created_at: Utc::now(),
+        };
+        let event = ReviewEvent::ReviewDeleted {
+            review_id: "r1".to_string(),
+            product_id: "p1".to_string(),
+            vector_index: 3,
+            deleted_at: Utc::now(),
+        };
+        let delivery = Delivery::new(&event);
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            first_delay: Duration::from_millis(10),
+        };
+
+        assert_eq!(deliver(&webhook("/hook"), &delivery, policy).await, (2, true));
+        let received = received.lock().unwrap().clone();
+        let (headers, body) = &received[1];
+        assert_eq!(headers[EVENT_HEADER], "review.deleted");
+        assert_eq!(headers[DELIVERY_HEADER], received[0].0[DELIVERY_HEADER]);
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[SIGNATURE_HEADER], sign("0123456789abcdef", timestamp, body).as_str());
+
+        let envelope: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(envelope["event"], "review.deleted");
+        assert_eq!(envelope["data"]["review_id"], "r1");
+        assert!(envelope["data"].get("type").is_none());
+
+        // A client error is final
+        assert_eq!(deliver(&webhook("/gone"), &delivery, policy).await, (1, false));
+    }
+}
@@ -0,0 +1,150 @@
+//! Two server processes writing to one data directory at once.
+//!
+//! Supported deployment model: any number of server processes may share a data
+//! directory on a local filesystem. Writers serialize through the `.lock` file, so the
+//! review file must stay line-atomic and vector indices must stay dense (line N holds
+//! vector index N) however the processes interleave.
+
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const WRITERS_PER_SERVER: usize = 4;
+const REVIEWS_PER_WRITER: usize = 10;
+const BULK_REVIEWS: usize = 15;
+
+/// A backend process, killed when dropped
+struct Server {
+    child: Child,
+    port: u16,
+}
+
+impl Server {
+    fn start(data_dir: &str) -> Server {
+        let port = free_port();
+        let child = Command::new(env!("CARGO_BIN_EXE_semantic-search-backend"))
+            .env("DATA_DIR", data_dir)
+            .env("PORT", port.to_string())
+            .env("RATE_LIMIT_PER_MINUTE", "0")
+            .env("LOCK_TIMEOUT_MS", "30000")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start backend");
+        let server = Server { child, port };
+        server.wait_until_ready();
+        server
+    }
+
+    fn wait_until_ready(&self) {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while Instant::now() < deadline {
+            if let Ok((200, _)) = request(self.port, "GET", "/v1/health", None) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("backend on port {} did not become ready", self.port);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// Minimal HTTP/1.1 client: one request per connection, returning status and JSON body
+fn request(port: u16, method: &str, path: &str, body: Option<&Value>) -> std::io::Result<(u16, Value)> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+    stream.set_read_timeout(Some(Duration::from_secs(60)))?;
+
+    let body = body.map(Value::to_string).unwrap_or_default();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .unwrap_or(0);
+    let json = response
+        .split_once("\r\n\r\n")
+        .and_then(|(_, body)| serde_json::from_str(body).ok())
+        .unwrap_or(Value::Null);
+    Ok((status, json))
+}
+
+fn review(title: String) -> Value {
+    json!({
+        "title": title,
+        "body": "Written concurrently by one of two backend processes.",
+        "product_id": "prod_multi_process",
+        "rating": 4
+    })
+}
+
+#[test]
+fn test_two_processes_write_one_data_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().to_str().unwrap().to_string();
+    let servers = [Server::start(&data_dir), Server::start(&data_dir)];
+    let ports: Vec<u16> = servers.iter().map(|server| server.port).collect();
+
+    let mut handles = Vec::new();
+    for (server_number, &port) in ports.iter().enumerate() {
+        for writer in 0..WRITERS_PER_SERVER {
+            handles.push(thread::spawn(move || {
+                for n in 0..REVIEWS_PER_WRITER {
+                    let title = format!("Server {} writer {} review {}", server_number, writer, n);
+                    let (status, body) = request(port, "POST", "/v1/reviews", Some(&review(title))).unwrap();
+                    assert_eq!(status, 200, "create failed: {}", body);
+                }
+            }));
+        }
+
+        // A multi-line append from each process, racing the single creates
+        handles.push(thread::spawn(move || {
+            let reviews: Vec<Value> = (0..BULK_REVIEWS)
+                .map(|n| review(format!("Server {} bulk review {}", server_number, n)))
+                .collect();
+            let (status, body) = request(port, "POST", "/v1/reviews/bulk", Some(&json!(reviews))).unwrap();
+            assert_eq!(status, 200, "bulk upload failed: {}", body);
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let contents = std::fs::read_to_string(temp_dir.path().join("reviews.jsonl")).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    let expected = ports.len() * (WRITERS_PER_SERVER * REVIEWS_PER_WRITER + BULK_REVIEWS);
+    assert_eq!(lines.len(), expected);
+
+    for (line_number, line) in lines.iter().enumerate() {
+        let review: Value = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("line {} is not a whole review ({}): {}", line_number, e, line));
+        assert_eq!(review["vector_index"], line_number, "vector indices must be dense");
+    }
+
+    // Both processes see the same data
+    for port in ports {
+        let (_, stats) = request(port, "GET", "/v1/stats", None).unwrap();
+        assert_eq!(stats["stats"]["total_reviews"], expected);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::models::*;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use utoipa::ToSchema;
+
+/// Number of days covered by the service-wide daily review counts
+pub const DAILY_WINDOW_DAYS: i64 = 30;
+
+/// Aggregate statistics over a set of reviews
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReviewStats {
+    pub review_count: usize,
+    pub average_rating: Option<f64>, // None when there are no reviews
+    pub rating_histogram: BTreeMap<u8, usize>, // Counts for every rating 1-5, including zeros
+    pub first_review_at: Option<DateTime<Utc>>,
+    pub last_review_at: Option<DateTime<Utc>>,
+}
+
+/// Reviews created on one UTC day
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DailyCount {
+    pub date: NaiveDate,
+    pub count: usize,
+}
+
+/// Service-wide data growth statistics
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceStats {
+    pub total_reviews: usize,
+    pub total_products: usize,
+    pub reviews_per_day: Vec<DailyCount>, // Oldest first, one entry per day including zeros
+}
+
+/// Consistency between the JSONL file and the vector index
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct IndexHealth {
+    pub jsonl_lines: usize,
+    pub deleted_reviews: usize,
+    pub vector_index_exists: bool,
+    pub vector_index_entries: Option<usize>, // None until the index file exists
+    pub pending_embeddings: usize, // Reviews queued for the embedding worker
+    pub in_sync: bool,
+}
+
+impl IndexHealth {
+    /// Every JSONL line (including tombstones) should own exactly one vector slot.
+    /// Pending embeddings are expected to catch up; more vectors than lines never should.
+    pub fn new(
+        jsonl_lines: usize,
+        deleted_reviews: usize,
+        vector_index_entries: Option<usize>,
+        pending_embeddings: usize,
+    ) -> Self {
+        Self {
+            jsonl_lines,
+            deleted_reviews,
+            vector_index_exists: vector_index_entries.is_some(),
+            vector_index_entries,
+            pending_embeddings,
+            in_sync: vector_index_entries.unwrap_or(0) + pending_embeddings >= jsonl_lines
+                && vector_index_entries.unwrap_or(0) <= jsonl_lines,
+        }
+    }
+}
+
+/// Totals and per-day counts for the `DAILY_WINDOW_DAYS` days ending at `now`.
+/// Deleted reviews are expected to be filtered out by the caller.
+pub fn service_stats(reviews: &[ReviewMetadata], now: DateTime<Utc>) -> ServiceStats {
+    let today = now.date_naive();
+    let first_day = today - Duration::days(DAILY_WINDOW_DAYS - 1);
+
+    let mut daily: BTreeMap<NaiveDate, usize> = (0..DAILY_WINDOW_DAYS)
+        .map(|offset| (first_day + Duration::days(offset), 0))
+        .collect();
+    for review in reviews {
+        if let Some(count) = daily.get_mut(&review.timestamp.date_naive()) {
+            *count += 1;
+        }
+    }
+
+    let products: HashSet<&str> = reviews.iter().map(|review| review.product_id.as_str()).collect();
+
+    ServiceStats {
+        total_reviews: reviews.len(),
+        total_products: products.len(),
+        reviews_per_day: daily
+            .into_iter()
+            .map(|(date, count)| DailyCount { date, count })
+            .collect(),
+    }
+}
+
+/// Compute count, average rating, rating histogram and timestamp range.
+/// Deleted reviews are expected to be filtered out by the caller.
+pub fn review_stats(reviews: &[ReviewMetadata]) -> ReviewStats {
+    let mut rating_histogram: BTreeMap<u8, usize> = (1..=5).map(|rating| (rating, 0)).collect();
+    for review in reviews {
+        *rating_histogram.entry(review.rating).or_default() += 1;
+    }
+
+    let rating_sum: u64 = reviews.iter().map(|review| review.rating as u64).sum();
+    let average_rating = (!reviews.is_empty()).then(|| rating_sum as f64 / reviews.len() as f64);
+
+    ReviewStats {
+        review_count: reviews.len(),
+        average_rating,
+        rating_histogram,
+        first_review_at: reviews.iter().map(|review| review.timestamp).min(),
+        last_review_at: reviews.iter().map(|review| review.timestamp).max(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_review(rating: u8, seconds: i64) -> ReviewMetadata {
+        ReviewMetadata {
+            id: format!("rev_{}", seconds),
+            title: "Review".to_string(),
+            body: "Review body".to_string(),
+            product_id: "prod_123".to_string(),
+            rating,
+            timestamp: DateTime::<Utc>::from_timestamp(seconds, 0).unwrap(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_review_stats() {
+        let reviews = vec![create_review(5, 300), create_review(4, 100), create_review(5, 200)];
+        let stats = review_stats(&reviews);
+
+        assert_eq!(stats.review_count, 3);
+        assert!((stats.average_rating.unwrap() - 14.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.rating_histogram.values().copied().collect::<Vec<_>>(), vec![0, 0, 0, 1, 2]);
+        assert_eq!(stats.first_review_at.unwrap().timestamp(), 100);
+        assert_eq!(stats.last_review_at.unwrap().timestamp(), 300);
+    }
+
+    #[test]
+    fn test_service_stats_daily_window() {
+        let now = DateTime::<Utc>::from_timestamp(40 * 86_400 + 3_600, 0).unwrap();
+        let mut other_product = create_review(3, 40 * 86_400);
+        other_product.product_id = "prod_456".to_string();
+        let reviews = vec![
+            create_review(5, 40 * 86_400 + 60), // today
+            other_product,                      // today
+            create_review(4, 11 * 86_400),      // first day of the window
+            create_review(4, 10 * 86_400),      // just outside the window
+        ];
+
+        let stats = service_stats(&reviews, now);
+        assert_eq!(stats.total_reviews, 4);
+        assert_eq!(stats.total_products, 2);
+        assert_eq!(stats.reviews_per_day.len(), DAILY_WINDOW_DAYS as usize);
+        assert_eq!(stats.reviews_per_day.first().unwrap().count, 1);
+        assert_eq!(stats.reviews_per_day.last().unwrap().count, 2);
+        assert_eq!(stats.reviews_per_day.iter().map(|day| day.count).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_index_health() {
+        assert!(IndexHealth::new(3, 1, None, 3).in_sync);
+        assert!(IndexHealth::new(3, 1, Some(3), 0).in_sync);
+        assert!(IndexHealth::new(3, 0, Some(2), 1).in_sync);
+        assert!(!IndexHealth::new(3, 0, Some(2), 0).in_sync);
+        assert!(!IndexHealth::new(3, 0, Some(4), 0).in_sync);
+    }
+
+    #[test]
+    fn test_review_stats_empty() {
+        let stats = review_stats(&[]);
+
+        assert_eq!(stats.review_count, 0);
+        assert!(stats.average_rating.is_none());
+        assert_eq!(stats.rating_histogram.len(), 5);
+        assert!(stats.first_review_at.is_none());
+    }
+}
@@ -0,0 +1,372 @@
+//! Questions about a product, answered from its reviews: `POST /products/{id}/ask`.
+//!
+//! Retrieval-augmented answering with no language model in the service. The question
+//! is run as a search restricted to the product, ranked like `POST /search` (vector
+//! similarity, keyword matching for reviews not yet embedded, blocklisted reviews left
+//! out) but without personalization or curation. Each retrieved review's sentences are
+//! then scored against the question, half by embedding similarity and half by the share
+//! of the question's keyword terms (stemmed, stopwords dropped) they contain, and its
+//! best sentences are kept as the evidence for an answer.
+//!
+//! The response is laid out for a downstream LLM: `context` numbers each source review
+//! with its supporting sentences, ready to place in a prompt, and `sources` carries the
+//! same evidence as data, so an answer can cite `[2]`. Without an LLM, the bundled
+//! extractive summarizer answers by taking the best-scoring sentences across reviews,
+//! skipping any that repeat one already taken.
+
+use crate::embedding::{self, Embedder};
+use crate::models::*;
+use crate::search;
+use crate::storage::{DataPaths, JsonlStorage};
+use crate::tokenizer;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+pub const DEFAULT_ASK_LIMIT: usize = 5;
+pub const MAX_ASK_LIMIT: usize = 20;
+pub const DEFAULT_SENTENCES_PER_REVIEW: usize = 2;
+pub const MAX_SENTENCES_PER_REVIEW: usize = 10;
+const MAX_QUESTION_LENGTH: usize = 500;
+
+/// Sentences in a summarized answer, at most
+const SUMMARY_SENTENCES: usize = 3;
+
+/// Share of keyword terms a sentence must have in common with one already in the
+/// answer to count as repeating it
+const REPEAT_OVERLAP: f32 = 0.8;
+
+/// Body of `POST /products/{id}/ask`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct AskRequest {
+    /// The question, in natural language
+    pub question: String,
+    /// Reviews to retrieve, 1-20; default 5
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Supporting sentences kept per review, 1-10; default 2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentences_per_review: Option<usize>,
+    /// Restrictions on the reviews retrieved, as in a search; the product is the path's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<SearchFilters>,
+    /// Whether to answer with the bundled extractive summarizer; default true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarize: Option<bool>,
+}
+
+impl AskRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.question.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "question".to_string(),
+            });
+        }
+        if self.question.len() > MAX_QUESTION_LENGTH {
+            return Err(ValidationError::TooLong {
+                field: "question".to_string(),
+                max_length: MAX_QUESTION_LENGTH,
+            });
+        }
+        let in_range = |value: Option<usize>, max: usize| value.is_none_or(|value| (1..=max).contains(&value));
+        if !in_range(self.limit, MAX_ASK_LIMIT) {
+            return Err(ValidationError::InvalidValue {
+                field: "limit".to_string(),
+                reason: format!("must be between 1 and {}", MAX_ASK_LIMIT),
+            });
+        }
+        if !in_range(self.sentences_per_review, MAX_SENTENCES_PER_REVIEW) {
+            return Err(ValidationError::InvalidValue {
+                field: "sentences_per_review".to_string(),
+                reason: format!("must be between 1 and {}", MAX_SENTENCES_PER_REVIEW),
+            });
+        }
+        if let Some(filters) = &self.filters {
+            filters.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_ASK_LIMIT)
+    }
+
+    pub fn get_sentences_per_review(&self) -> usize {
+        self.sentences_per_review.unwrap_or(DEFAULT_SENTENCES_PER_REVIEW)
+    }
+}
+
+/// A sentence of a review that bears on the question
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SupportingSentence {
+    pub text: String,
+    /// Relevance to the question, 0-1: the mean of its embedding similarity and the
+    /// share of the question's terms it contains
+    pub score: f32,
+}
+
+/// A retrieved review and its supporting sentences
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct AskSource {
+    /// The review's number in `context` and `answer.citations`, from 1
+    pub number: usize,
+    pub review_id: String,
+    pub title: String,
+    pub rating: u8,
+    pub timestamp: DateTime<Utc>,
+    /// The review's search score for the question
+    pub relevance: f32,
+    /// Best first; empty when no sentence shares anything with the question
+    pub sentences: Vec<SupportingSentence>,
+}
+
+/// The bundled summarizer's answer
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExtractiveAnswer {
+    /// The chosen sentences, best first
+    pub text: String,
+    /// For each sentence in `text`, the number of the source it came from
+    pub citations: Vec<usize>,
+}
+
+/// Response of `POST /products/{id}/ask`
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct AskResponse {
+    pub success: bool,
+    pub product_id: String,
+    pub question: String,
+    /// The question as searched, when typo correction changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_question: Option<String>,
+    /// Most relevant first
+    pub sources: Vec<AskSource>,
+    /// The sources as numbered plain text, for a prompt
+    pub context: String,
+    /// Absent when not asked for, or when no sentence bears on the question
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<ExtractiveAnswer>,
+}
+
+/// Retrieve the reviews of `product_id` that best answer the question, with their
+/// supporting sentences
+pub fn ask(data_paths: &DataPaths, product_id: &str, request: &AskRequest) -> Result<AskResponse, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    if !storage.get_reviews_by_product(product_id)?.iter().any(|review| !review.is_deleted()) {
+        return Err(AppError::NotFound {
+            resource: "Product".to_string(),
+            id: product_id.to_string(),
+        });
+    }
+
+    let filters = SearchFilters {
+        product_id: Some(product_id.to_string()),
+        ..request.filters.clone().unwrap_or_default()
+    };
+    let search_request = SearchRequest {
+        query: request.question.clone(),
+        limit: Some(request.get_limit()),
+        offset: None,
+        cursor: None,
+        filters: Some(filters),
+        rank_by_helpfulness: false,
+        ranking: None,
+        session_id: None,
+        personalize: Some(false),
+        curate: Some(false),
+        correct_typos: None,
+        group_by_product: false,
+        fields: Vec::new(),
+        explain: false,
+        min_score: None,
+        // Dropping the product filter would answer from other products' reviews
+        fallback: Some(false),
+        collections: Vec::new(),
+    };
+    let response = search::run(data_paths, search_request)?;
+
+    // Sentences are scored against what was searched, typos corrected
+    let question = response.corrected_query.as_deref().unwrap_or(&request.question);
+    let embedder = embedding::default_embedder();
+    let question_vector = embedder.embed(question);
+    let question_terms = terms(question);
+
+    let sources: Vec<AskSource> = response
+        .results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let mut sentences: Vec<SupportingSentence> = sentences(&result.review)
+                .into_iter()
+                .map(|text| {
+                    let score = sentence_score(&text, &question_vector, &question_terms, &embedder);
+                    SupportingSentence { text, score }
+                })
+                .filter(|sentence| sentence.score > 0.0)
+                .collect();
+            // Stable, so equally relevant sentences stay in reading order
+            sentences.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+            sentences.truncate(request.get_sentences_per_review());
+
+            let review = result.review;
+            AskSource {
+                number: index + 1,
+                review_id: review.id,
+                title: review.title,
+                rating: review.rating,
+                timestamp: review.timestamp,
+                relevance: result.similarity_score,
+                sentences,
+            }
+        })
+        .collect();
+
+    let answer = match request.summarize.unwrap_or(true) {
+        true => summarize(&sources),
+        false => None,
+    };
+    Ok(AskResponse {
+        success: true,
+        product_id: product_id.to_string(),
+        question: request.question.clone(),
+        corrected_question: response.corrected_query,
+        context: context(&sources),
+        sources,
+        answer,
+    })
+}
+
+/// A review's sentences: its title, then its body split after `.`, `!` or `?` (or
+/// their full-width forms) followed by a space, and at line breaks
+pub fn sentences(review: &ReviewMetadata) -> Vec<String> {
+    let mut sentences = vec![review.title.trim().to_string()];
+    let mut current = String::new();
+    let mut chars = review.body.chars().peekable();
+    while let Some(c) = chars.next() {
+        let ends = match c {
+            '\n' => true,
+            '。' | '！' | '？' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|next| next.is_whitespace()),
+            _ => false,
+        };
+        current.push(c);
+        if ends {
+            sentences.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    sentences.push(current.trim().to_string());
+    sentences.retain(|sentence| sentence.chars().any(char::is_alphanumeric));
+    sentences
+}
+
+/// The keyword terms of a text, as the keyword scorer compares them
+fn terms(text: &str) -> HashSet<String> {
+    tokenizer::keyword_terms(&tokenizer::normalize(text)).into_iter().collect()
+}
+
+fn sentence_score(
+    text: &str,
+    question_vector: &[f32],
+    question_terms: &HashSet<String>,
+    embedder: &impl Embedder,
+) -> f32 {
+    let vector = embedder.embed(text);
+    let similarity: f32 = question_vector.iter().zip(&vector).map(|(a, b)| a * b).sum();
+    let coverage = match question_terms.len() {
+        0 => 0.0,
+        total => question_terms.intersection(&terms(text)).count() as f32 / total as f32,
+    };
+    ((similarity.clamp(0.0, 1.0) + coverage) / 2.0).min(1.0)
+}
+
+/// The sources as numbered plain text: each review's title, rating and supporting
+/// sentences. Sources without one are left out.
+fn context(sources: &[AskSource]) -> String {
+    sources
+        .iter()
+        .filter(|source| !source.sentences.is_empty())
+        .map(|source| {
+            let sentences: Vec<String> =
+                source.sentences.iter().map(|sentence| format!("- {}", sentence.text)).collect();
+            format!("[{}] \"{}\" ({}/5)\n{}", source.number, source.title, source.rating, sentences.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The bundled summarizer: the best supporting sentences across sources, leaving out
+/// those that repeat one taken, up to `SUMMARY_SENTENCES`
+pub fn summarize(sources: &[AskSource]) -> Option<ExtractiveAnswer> {
+    let mut candidates: Vec<(usize, &SupportingSentence)> = sources
+        .iter()
+        .flat_map(|source| source.sentences.iter().map(move |sentence| (source.number, sentence)))
+        .collect();
+    candidates.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut taken: Vec<(usize, &str, HashSet<String>)> = Vec::new();
+    for (number, sentence) in candidates {
+        if taken.len() == SUMMARY_SENTENCES {
+            break;
+        }
+        let sentence_terms = terms(&sentence.text);
+        let repeats = taken.iter().any(|(_, _, taken_terms)| {
+            let shared = sentence_terms.intersection(taken_terms).count() as f32;
+            let smaller = sentence_terms.len().min(taken_terms.len()).max(1) as f32;
+            shared / smaller >= REPEAT_OVERLAP
+        });
+        if !repeats {
+            taken.push((number, &sentence.text, sentence_terms));
+        }
+    }
+
+    if taken.is_empty() {
+        return None;
+    }
+    Some(ExtractiveAnswer {
+        text: taken.iter().map(|(_, text, _)| *text).collect::<Vec<_>>().join(" "),
+        citations: taken.iter().map(|(number, _, _)| *number).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(number: usize, sentences: &[(&str, f32)]) -> AskSource {
+        AskSource {
+            number,
+            review_id: format!("review_{}", number),
+            title: "Title".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            relevance: 0.5,
+            sentences: sentences
+                .iter()
+                .map(|(text, score)| SupportingSentence {
+                    text: text.to_string(),
+                    score: *score,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_takes_the_best_sentences_without_repeats() {
+        let sources = [
+            source(1, &[("The battery lasts two days.", 0.9), ("Charging takes an hour.", 0.4)]),
+            source(2, &[("Battery lasts 2 days!", 0.8), ("The case scratches easily.", 0.2)]),
+        ];
+        let answer = summarize(&sources).unwrap();
+        // The second review's battery sentence repeats the first's
+        assert_eq!(answer.text, "The battery lasts two days. Charging takes an hour. The case scratches easily.");
+        assert_eq!(answer.citations, [1, 1, 2]);
+        assert!(summarize(&[source(1, &[])]).is_none());
+
+        let context = context(&sources);
+        let first = "[1] \"Title\" (4/5)\n- The battery lasts two days.\n- Charging takes an hour.\n\n[2]";
+        assert!(context.starts_with(first));
+    }
+}
@@ -0,0 +1,442 @@
+//! Blocklist: products and reviewers admins have shut out.
+//!
+//! An entry names a product_id or reviewer_id and the stages it is enforced at:
+//! ingestion, where creates, batch creates, bulk uploads and updates of a matching
+//! review are refused with 403 `E_BLOCKLISTED`, and search, where matching reviews are
+//! dropped from the candidates before ranking, so they never take a slot on the page.
+//! Reviews already stored stay stored; deleting the entry makes them searchable again.
+//!
+//! Entries live in blocklist.jsonl in the data directory. Every change is also appended
+//! to blocklist_audit.jsonl, which is only rewritten to erase a reviewer on a data subject
+//! request (see `data_subject`), so the audit trail outlives the entries it describes. On a sharded deployment each shard enforces its own list at
+//! ingestion, and the coordinator filters the merged search results with its own.
+
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, SlotBitmap};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Entries allowed per data directory
+pub const MAX_BLOCKLIST_ENTRIES: usize = 10_000;
+
+/// Longest accepted reason
+pub const MAX_REASON_LENGTH: usize = 500;
+
+/// What an entry's value is compared against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Product,
+    Reviewer,
+}
+
+impl BlockKind {
+    fn field(self) -> &'static str {
+        match self {
+            BlockKind::Product => "product_id",
+            BlockKind::Reviewer => "reviewer_id",
+        }
+    }
+}
+
+/// Stages an entry is enforced at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockScope {
+    Ingestion,
+    Search,
+    #[default]
+    All,
+}
+
+impl BlockScope {
+    fn covers(self, stage: BlockScope) -> bool {
+        self == BlockScope::All || self == stage
+    }
+}
+
+/// A stored blocklist entry
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BlocklistEntry {
+    pub id: String,
+    pub kind: BlockKind,
+    /// The product_id or reviewer_id blocked, matched exactly
+    pub value: String,
+    #[serde(default)]
+    pub scope: BlockScope,
+    /// Why the entry exists, for other admins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body of `POST /admin/blocklist`
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct CreateBlocklistEntryRequest {
+    pub kind: BlockKind,
+    pub value: String,
+    /// Defaults to `all`
+    #[serde(default)]
+    pub scope: BlockScope,
+    pub reason: Option<String>,
+}
+
+impl CreateBlocklistEntryRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.value.trim().is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "value".to_string(),
+                reason: "must not be empty".to_string(),
+            });
+        }
+        // Longer ids can't be stored, so could never match
+        if self.value.len() > 100 {
+            return Err(ValidationError::TooLong {
+                field: "value".to_string(),
+                max_length: 100,
+            });
+        }
+        if self.reason.as_ref().is_some_and(|reason| reason.chars().count() > MAX_REASON_LENGTH) {
+            return Err(ValidationError::TooLong {
+                field: "reason".to_string(),
+                max_length: MAX_REASON_LENGTH,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A change to the blocklist
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Added,
+    Removed,
+}
+
+/// One line of blocklist_audit.jsonl
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BlocklistAuditRecord {
+    pub action: AuditAction,
+    /// The entry as it was added or removed
+    pub entry: BlocklistEntry,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Append one JSON line. The caller must hold the FileLock.
+fn append_line<T: Serialize>(file_path: &Path, record: &T) -> Result<(), AppError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(file_path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    file.flush()?;
+    Ok(())
+}
+
+fn read_lines<T: serde::de::DeserializeOwned>(file_path: &Path) -> Result<Vec<T>, AppError> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(records)
+}
+
+/// Atomically replace a file with one JSON line per record. The caller must hold the FileLock.
+fn write_lines<T: Serialize>(file_path: &Path, records: &[T]) -> Result<(), AppError> {
+    let temp_path = file_path.with_extension("jsonl.tmp");
+    {
+        let file = File::create(&temp_path)?;
+        let mut writer = BufWriter::new(&file);
+        for record in records {
+            writeln!(writer, "{}", serde_json::to_string(record)?)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+    }
+    std::fs::rename(&temp_path, file_path)?;
+    Ok(())
+}
+
+/// JSONL file of blocklist entries, with its audit log alongside
+pub struct BlocklistStorage {
+    file_path: PathBuf,
+    audit_path: PathBuf,
+}
+
+impl BlocklistStorage {
+    pub fn new(data_paths: &DataPaths) -> Self {
+        Self {
+            file_path: data_paths.blocklist_jsonl.clone(),
+            audit_path: data_paths.blocklist_audit_jsonl.clone(),
+        }
+    }
+
+    /// Read every entry, oldest first
+    pub fn read_all(&self) -> Result<Vec<BlocklistEntry>, AppError> {
+        read_lines(&self.file_path)
+    }
+
+    /// Read the audit log, oldest first
+    pub fn read_audit(&self) -> Result<Vec<BlocklistAuditRecord>, AppError> {
+        read_lines(&self.audit_path)
+    }
+
+    /// Append an entry and its audit record. The caller must hold the FileLock.
+    pub fn append(&self, entry: &BlocklistEntry) -> Result<(), AppError> {
+        append_line(&self.file_path, entry)?;
+        self.audit(AuditAction::Added, entry)
+    }
+
+    /// Atomically drop the entry with `id`, returning it, and audit the removal. The
+    /// caller must hold the FileLock.
+    pub fn remove(&self, id: &str) -> Result<Option<BlocklistEntry>, AppError> {
+        let (removed, kept): (Vec<BlocklistEntry>, Vec<BlocklistEntry>) =
+            self.read_all()?.into_iter().partition(|entry| entry.id == id);
+        let Some(removed) = removed.into_iter().next() else {
+            return Ok(None);
+        };
+        write_lines(&self.file_path, &kept)?;
+
+        self.audit(AuditAction::Removed, &removed)?;
+        Ok(Some(removed))
+    }
+
+    /// Erase a value from the blocklist for a data subject request: drop the entries
+    /// blocking it and overwrite it with `replacement` in the audit log, the one time
+    /// the log is rewritten. Each removal is audited with the replacement as its value.
+    /// Returns the entries dropped and the audit records redacted. The caller must hold
+    /// the FileLock.
+    pub fn erase_value(&self, kind: BlockKind, value: &str, replacement: &str) -> Result<(usize, usize), AppError> {
+        let names = |entry: &BlocklistEntry| entry.kind == kind && entry.value == value;
+        let (removed, kept): (Vec<BlocklistEntry>, Vec<BlocklistEntry>) = self.read_all()?.into_iter().partition(names);
+        let mut audit = self.read_audit()?;
+        let mut redacted = 0;
+        for record in audit.iter_mut().filter(|record| names(&record.entry)) {
+            record.entry.value = replacement.to_string();
+            redacted += 1;
+        }
+
+        if !removed.is_empty() {
+            write_lines(&self.file_path, &kept)?;
+        }
+        if redacted > 0 {
+            write_lines(&self.audit_path, &audit)?;
+        }
+        for mut entry in removed.iter().cloned() {
+            entry.value = replacement.to_string();
+            self.audit(AuditAction::Removed, &entry)?;
+        }
+        Ok((removed.len(), redacted))
+    }
+
+    fn audit(&self, action: AuditAction, entry: &BlocklistEntry) -> Result<(), AppError> {
+        tracing::info!(
+            "Blocklist {:?}: {} {} ({:?}, entry {})",
+            action,
+            entry.kind.field(),
+            entry.value,
+            entry.scope,
+            entry.id
+        );
+        append_line(
+            &self.audit_path,
+            &BlocklistAuditRecord {
+                action,
+                entry: entry.clone(),
+                recorded_at: Utc::now(),
+            },
+        )
+    }
+}
+
+/// Validate and store an entry
+pub fn create(data_paths: &DataPaths, request: CreateBlocklistEntryRequest) -> Result<BlocklistEntry, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let storage = BlocklistStorage::new(data_paths);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "create_blocklist_entry")?;
+    let entries = storage.read_all()?;
+    if entries.len() >= MAX_BLOCKLIST_ENTRIES {
+        return Err(AppError::Conflict {
+            message: format!("At most {} blocklist entries can be stored", MAX_BLOCKLIST_ENTRIES),
+        });
+    }
+    if let Some(existing) = entries.iter().find(|entry| entry.kind == request.kind && entry.value == request.value) {
+        return Err(AppError::Conflict {
+            message: format!("{} {} is already blocklisted by entry {}", request.kind.field(), request.value, existing.id),
+        });
+    }
+
+    let entry = BlocklistEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: request.kind,
+        value: request.value,
+        scope: request.scope,
+        reason: request.reason,
+        created_at: Utc::now(),
+    };
+    storage.append(&entry)?;
+    Ok(entry)
+}
+
+/// The products and reviewers blocked at one stage
+#[derive(Clone, Debug, Default)]
+pub struct Blocklist {
+    products: HashSet<String>,
+    reviewers: HashSet<String>,
+}
+
+impl Blocklist {
+    /// Entries enforced at `stage`, read from the data directory. Readers should hold
+    /// the FileLock.
+    pub fn load(data_paths: &DataPaths, stage: BlockScope) -> Result<Blocklist, AppError> {
+        let entries = BlocklistStorage::new(data_paths).read_all()?;
+        Ok(Blocklist::for_stage(entries, stage))
+    }
+
+    /// `load` under the shared lock, for callers that don't hold it
+    pub fn load_shared(data_paths: &DataPaths, stage: BlockScope) -> Result<Blocklist, AppError> {
+        if !data_paths.blocklist_jsonl.exists() {
+            return Ok(Blocklist::default());
+        }
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "load_blocklist")?;
+        Blocklist::load(data_paths, stage)
+    }
+
+    pub fn for_stage(entries: Vec<BlocklistEntry>, stage: BlockScope) -> Blocklist {
+        let mut blocklist = Blocklist::default();
+        for entry in entries.into_iter().filter(|entry| entry.scope.covers(stage)) {
+            match entry.kind {
+                BlockKind::Product => blocklist.products.insert(entry.value),
+                BlockKind::Reviewer => blocklist.reviewers.insert(entry.value),
+            };
+        }
+        blocklist
+    }
+
+    fn blocked_field(&self, product_id: &str, reviewer_id: Option<&String>) -> Option<(BlockKind, String)> {
+        if self.products.contains(product_id) {
+            return Some((BlockKind::Product, product_id.to_string()));
+        }
+        reviewer_id
+            .filter(|reviewer_id| self.reviewers.contains(*reviewer_id))
+            .map(|reviewer_id| (BlockKind::Reviewer, reviewer_id.clone()))
+    }
+
+    /// Whether a stored review is blocked
+    pub fn blocks(&self, review: &ReviewMetadata) -> bool {
+        self.blocks_fields(&review.product_id, review.reviewer_id.as_ref())
+    }
+
+    /// Whether a review with this product and reviewer is blocked
+    pub fn blocks_fields(&self, product_id: &str, reviewer_id: Option<&String>) -> bool {
+        self.blocked_field(product_id, reviewer_id).is_some()
+    }
+
+    /// Refuse a review about to be stored if it is blocked
+    pub fn check(&self, review_data: &ReviewData) -> Result<(), AppError> {
+        match self.blocked_field(&review_data.product_id, review_data.reviewer_id.as_ref()) {
+            Some((kind, value)) => Err(AppError::Blocked {
+                field: kind.field().to_string(),
+                value,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Drop blocked reviews from `reviews` and mark their vector slots in `excluded`,
+    /// so a vector search skips them too
+    pub fn remove_blocked(&self, reviews: &mut Vec<ReviewMetadata>, excluded: &mut SlotBitmap) {
+        if self.products.is_empty() && self.reviewers.is_empty() {
+            return;
+        }
+        reviews.retain(|review| match self.blocks(review) {
+            true => {
+                excluded.set(review.vector_index);
+                false
+            }
+            false => true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn review_data(product_id: &str, reviewer_id: Option<&str>) -> ReviewData {
+        ReviewData {
+            title: "Kettle".to_string(),
+            body: "Boils water quickly.".to_string(),
+            product_id: product_id.to_string(),
+            rating: 4,
+            reviewer_id: reviewer_id.map(str::to_string),
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    fn request(kind: BlockKind, value: &str, scope: BlockScope) -> CreateBlocklistEntryRequest {
+        CreateBlocklistEntryRequest {
+            kind,
+            value: value.to_string(),
+            scope,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_entries_are_enforced_per_stage_and_audited() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        let product = create(&data_paths, request(BlockKind::Product, "recalled", BlockScope::All)).unwrap();
+        create(&data_paths, request(BlockKind::Reviewer, "spammer", BlockScope::Search)).unwrap();
+        assert!(matches!(
+            create(&data_paths, request(BlockKind::Product, "recalled", BlockScope::Search)),
+            Err(AppError::Conflict { .. })
+        ));
+        assert!(create(&data_paths, request(BlockKind::Product, " ", BlockScope::All)).is_err());
+
+        let ingestion = Blocklist::load(&data_paths, BlockScope::Ingestion).unwrap();
+        assert!(matches!(
+            ingestion.check(&review_data("recalled", None)),
+            Err(AppError::Blocked { ref field, .. }) if field == "product_id"
+        ));
+        assert!(ingestion.check(&review_data("kettle", Some("spammer"))).is_ok());
+
+        let search = Blocklist::load_shared(&data_paths, BlockScope::Search).unwrap();
+        let mut reviews: Vec<ReviewMetadata> = [("recalled", None), ("kettle", Some("spammer")), ("kettle", None)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (product_id, reviewer_id))| review_data(product_id, reviewer_id).to_metadata(index).unwrap())
+            .collect();
+        let mut excluded = SlotBitmap::default();
+        search.remove_blocked(&mut reviews, &mut excluded);
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].vector_index, 2);
+        assert!(excluded.contains(0) && excluded.contains(1) && !excluded.contains(2));
+
+        let storage = BlocklistStorage::new(&data_paths);
+        assert_eq!(storage.remove(&product.id).unwrap().unwrap().value, "recalled");
+        assert!(storage.remove(&product.id).unwrap().is_none());
+        let actions: Vec<AuditAction> = storage.read_audit().unwrap().iter().map(|record| record.action).collect();
+        assert_eq!(actions, vec![AuditAction::Added, AuditAction::Added, AuditAction::Removed]);
+        assert!(Blocklist::load(&data_paths, BlockScope::Ingestion).unwrap().check(&review_data("recalled", None)).is_ok());
+    }
+}
@@ -0,0 +1,281 @@
+//! Canary queries: searches with known right answers, run at startup and after every
+//! compaction so a relevance regression shows up as a failed readiness check instead
+//! of in user reports.
+//!
+//! The suite is a JSON array in the file named by `CANARY_QUERIES_FILE`; without it
+//! no canaries run. Each entry names review ids that must all appear within the top
+//! `within_top` results. Failures are logged as errors and make `/health` report
+//! `degraded` until a later run passes.
+
+use crate::models::*;
+use crate::search;
+use crate::storage::DataPaths;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable naming the canary suite file
+pub const CANARY_FILE_ENV: &str = "CANARY_QUERIES_FILE";
+
+/// Results searched for expected reviews when a canary doesn't say
+pub const DEFAULT_WITHIN_TOP: usize = 5;
+
+/// One canary: a search and the reviews it must rank highly
+#[derive(Clone, Debug, Deserialize)]
+pub struct CanaryQuery {
+    /// Label for logs and the health check; defaults to the query
+    pub name: Option<String>,
+    pub query: String,
+    #[serde(default)]
+    pub filters: Option<SearchFilters>,
+    /// Review ids that must all be in the top `within_top` results
+    pub expected: Vec<String>,
+    pub within_top: Option<usize>,
+}
+
+impl CanaryQuery {
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.query)
+    }
+
+    fn within_top(&self) -> usize {
+        self.within_top.unwrap_or(DEFAULT_WITHIN_TOP)
+    }
+
+    fn search_request(&self) -> SearchRequest {
+        SearchRequest {
+            query: self.query.clone(),
+            limit: Some(self.within_top()),
+            offset: None,
+            cursor: None,
+            filters: self.filters.clone(),
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            // A relaxed search could find the expected review where the real one no longer does
+            fallback: Some(false),
+            collections: Vec::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.expected.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "expected".to_string(),
+                reason: format!("canary \"{}\" must expect at least one review id", self.label()),
+            });
+        }
+        self.search_request().validate()
+    }
+}
+
+/// A canary that did not find what it expected
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CanaryFailure {
+    pub name: String,
+    /// Expected ids absent from the top results
+    pub missing: Vec<String>,
+    /// Set when the search itself failed
+    pub error: Option<String>,
+}
+
+/// Outcome of one run of the suite
+#[derive(Clone, Debug, Serialize)]
+pub struct CanaryReport {
+    /// What caused the run, e.g. `startup` or `compaction`
+    pub trigger: String,
+    pub ran_at: DateTime<Utc>,
+    pub passed: usize,
+    pub failures: Vec<CanaryFailure>,
+}
+
+impl CanaryReport {
+    pub fn is_passing(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+fn reports() -> &'static Mutex<HashMap<PathBuf, CanaryReport>> {
+    static REPORTS: OnceLock<Mutex<HashMap<PathBuf, CanaryReport>>> = OnceLock::new();
+    REPORTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The configured suite, or `None` when `CANARY_QUERIES_FILE` is unset
+pub fn load_suite() -> Result<Option<Vec<CanaryQuery>>, AppError> {
+    let Ok(path) = std::env::var(CANARY_FILE_ENV) else {
+        return Ok(None);
+    };
+    let suite: Vec<CanaryQuery> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    for canary in &suite {
+        canary.validate()?;
+    }
+    Ok(Some(suite))
+}
+
+/// Run every canary through `search`
+pub fn evaluate<F>(suite: &[CanaryQuery], trigger: &str, search: F) -> CanaryReport
+where
+    F: Fn(SearchRequest) -> Result<SearchResponse, ErrorResponse>,
+{
+    let mut failures = Vec::new();
+    for canary in suite {
+        let failure = match search(canary.search_request()) {
+            Ok(response) => {
+                let missing: Vec<String> = canary
+                    .expected
+                    .iter()
+                    .filter(|id| !response.results.iter().any(|result| &result.review.id == *id))
+                    .cloned()
+                    .collect();
+                (!missing.is_empty()).then(|| CanaryFailure {
+                    name: canary.label().to_string(),
+                    missing,
+                    error: None,
+                })
+            }
+            Err(error) => Some(CanaryFailure {
+                name: canary.label().to_string(),
+                missing: canary.expected.clone(),
+                error: Some(error.message),
+            }),
+        };
+        failures.extend(failure);
+    }
+
+    CanaryReport {
+        trigger: trigger.to_string(),
+        ran_at: Utc::now(),
+        passed: suite.len() - failures.len(),
+        failures,
+    }
+}
+
+/// Run the configured suite against the live search and record the report for the
+/// health check. Errors only if the suite file can't be read or is invalid.
+pub fn run(data_paths: &DataPaths, trigger: &str) -> Result<Option<CanaryReport>, AppError> {
+    let Some(suite) = load_suite()? else {
+        return Ok(None);
+    };
+
+    let report = evaluate(&suite, trigger, |search_request| {
+        search::run(data_paths, search_request).map_err(ErrorResponse::from)
+    });
+    if report.is_passing() {
+        tracing::info!("All {} canary queries passed ({})", report.passed, trigger);
+    } else {
+        for failure in &report.failures {
+            tracing::error!(
+                "Canary query \"{}\" failed after {}: missing {:?}{}",
+                failure.name,
+                trigger,
+                failure.missing,
+                failure.error.as_ref().map(|error| format!(" ({})", error)).unwrap_or_default()
+            );
+        }
+    }
+
+    reports()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(data_paths.reviews_jsonl.clone(), report.clone());
+    Ok(Some(report))
+}
+
+/// Readiness check: `Err` naming the failed canaries of the last run, if any failed
+pub fn check(data_paths: &DataPaths) -> Result<(), String> {
+    let reports = reports().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match reports.get(&data_paths.reviews_jsonl) {
+        Some(report) if !report.is_passing() => {
+            let names: Vec<&str> = report.failures.iter().map(|failure| failure.name.as_str()).collect();
+            Err(format!(
+                "{} of {} canary queries failed after {}: {}",
+                report.failures.len(),
+                report.failures.len() + report.passed,
+                report.trigger,
+                names.join(", ")
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn response_with(ids: &[&str]) -> SearchResponse {
+        let results = ids
+            .iter()
+            .map(|id| {
+                let mut review = ReviewData {
+                    title: "Canary".to_string(),
+                    body: "A review body long enough to store.".to_string(),
+                    product_id: "canary".to_string(),
+                    rating: 4,
+                    reviewer_id: None,
+                    source: None,
+                    license: None,
+                    attribution: None,
+                }
+                .to_metadata(0)
+                .unwrap();
+                review.id = id.to_string();
+                SearchResult { review, similarity_score: 1.0, product_hits: None, explanation: None, collection: None }
+            })
+            .collect::<Vec<_>>();
+        SearchResponse {
+            success: true,
+            query: String::new(),
+            total_results: results.len(),
+            results,
+            limit: DEFAULT_WITHIN_TOP,
+            offset: 0,
+            next_cursor: None,
+            filters: None,
+            search_type: "text_similarity".to_string(),
+            corrected_query: None,
+            expanded_terms: Vec::new(),
+            personalized: false,
+            applied_rules: Vec::new(),
+            parsed_query: None,
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reports_missing_expectations() {
+        let suite: Vec<CanaryQuery> = serde_json::from_value(serde_json::json!([
+            { "name": "kettles", "query": "kettle", "expected": ["a", "b"] },
+            { "query": "toaster", "expected": ["c"], "within_top": 1 }
+        ]))
+        .unwrap();
+
+        let report = evaluate(&suite, "startup", |search_request| match search_request.query.as_str() {
+            "kettle" => Ok(response_with(&["b", "x", "a"])),
+            _ => Ok(response_with(&["x"])),
+        });
+        assert_eq!(report.passed, 1);
+        assert_eq!(
+            report.failures,
+            vec![CanaryFailure { name: "toaster".to_string(), missing: vec!["c".to_string()], error: None }]
+        );
+
+        // The failed run makes the data directory unready until a run passes
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        assert!(check(&data_paths).is_ok());
+        reports().lock().unwrap().insert(data_paths.reviews_jsonl.clone(), report);
+        let error = check(&data_paths).unwrap_err();
+        assert!(error.starts_with("1 of 2 canary queries failed after startup: toaster"), "{}", error);
+    }
+}
@@ -0,0 +1,320 @@
+//! Collections: named review sets, such as one per marketplace, searched alone or
+//! together.
+//!
+//! The data directory's own reviews are the `default` collection. Every other collection
+//! is a data directory of its own under `collections/<name>/`, with its own reviews.jsonl,
+//! index, embedding queue and lock. Blocklist entries, curation rules and synonyms are
+//! kept per data directory too, so those managed through the admin endpoints apply to
+//! the default collection.
+//!
+//! A search naming `collections` (`*` for every one) ranks each collection down to the
+//! end of the page and merges the rankings by score, each result saying which
+//! collection it came from. The merged ranking is then grouped by product and paged as
+//! a single search's would be.
+
+use crate::lock::FileLock;
+use crate::models::*;
+use crate::search;
+use crate::storage::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+/// The collection held in the data directory itself
+pub const DEFAULT_COLLECTION: &str = "default";
+
+/// Stands for every collection in a search's `collections`
+pub const ALL_COLLECTIONS: &str = "*";
+
+/// Longest collection name accepted
+pub const MAX_COLLECTION_NAME_LENGTH: usize = 64;
+
+/// Collections that can be created besides the default one
+pub const MAX_COLLECTIONS: usize = 100;
+
+/// Request to create a collection
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateCollectionRequest {
+    pub name: String,
+}
+
+/// A collection and the reviews it holds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CollectionInfo {
+    pub name: String,
+    pub review_count: usize,
+}
+
+/// Check a collection name: letters, digits, `-` and `_`
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if name.is_empty() || name.len() > MAX_COLLECTION_NAME_LENGTH || !valid_chars {
+        return Err(ValidationError::InvalidValue {
+            field: "collection".to_string(),
+            reason: format!("must be 1-{} letters, digits, '-' or '_'", MAX_COLLECTION_NAME_LENGTH),
+        });
+    }
+    Ok(())
+}
+
+/// Check a search's `collections`: names, or `*`
+pub fn validate_selection(collections: &[String]) -> Result<(), ValidationError> {
+    for collection in collections.iter().filter(|collection| *collection != ALL_COLLECTIONS) {
+        validate_name(collection).map_err(|_| ValidationError::InvalidValue {
+            field: "collections".to_string(),
+            reason: format!("{:?} is not a collection name or '{}'", collection, ALL_COLLECTIONS),
+        })?;
+    }
+    Ok(())
+}
+
+fn not_found(name: &str) -> AppError {
+    AppError::NotFound {
+        resource: "Collection".to_string(),
+        id: name.to_string(),
+    }
+}
+
+fn collections_dir(root: &DataPaths) -> PathBuf {
+    root.data_dir.join("collections")
+}
+
+/// The data directory of an existing collection
+pub fn data_paths(root: &DataPaths, name: &str) -> Result<DataPaths, AppError> {
+    validate_name(name)?;
+    if name == DEFAULT_COLLECTION {
+        return Ok(DataPaths::new(&root.data_dir));
+    }
+    let dir = collections_dir(root).join(name);
+    match dir.is_dir() {
+        true => Ok(DataPaths::new(dir)),
+        false => Err(not_found(name)),
+    }
+}
+
+/// Every collection's name, the default one first and the rest in order
+pub fn names(root: &DataPaths) -> Result<Vec<String>, AppError> {
+    let mut names = Vec::new();
+    let dir = collections_dir(root);
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && name != DEFAULT_COLLECTION && validate_name(&name).is_ok() {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    names.insert(0, DEFAULT_COLLECTION.to_string());
+    Ok(names)
+}
+
+/// The collections a search names, each once, in the order named; `*` adds all of them
+pub fn resolve(root: &DataPaths, collections: &[String]) -> Result<Vec<(String, DataPaths)>, AppError> {
+    let mut selected: Vec<String> = Vec::new();
+    for collection in collections {
+        let named = match collection.as_str() {
+            ALL_COLLECTIONS => names(root)?,
+            name => vec![name.to_string()],
+        };
+        for name in named {
+            if !selected.contains(&name) {
+                selected.push(name);
+            }
+        }
+    }
+    selected
+        .into_iter()
+        .map(|name| data_paths(root, &name).map(|data_paths| (name, data_paths)))
+        .collect()
+}
+
+/// Create an empty collection
+pub fn create(root: &DataPaths, request: CreateCollectionRequest) -> Result<CollectionInfo, AppError> {
+    validate_name(&request.name)?;
+    let dir = collections_dir(root);
+    std::fs::create_dir_all(&dir)?;
+    if request.name == DEFAULT_COLLECTION || dir.join(&request.name).exists() {
+        return Err(AppError::Conflict {
+            message: format!("Collection {} already exists", request.name),
+        });
+    }
+    if names(root)?.len() > MAX_COLLECTIONS {
+        return Err(AppError::Conflict {
+            message: format!("At most {} collections can be created", MAX_COLLECTIONS),
+        });
+    }
+    // Two requests for one name can race past the check; only one creates the directory
+    std::fs::create_dir(dir.join(&request.name)).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => AppError::Conflict {
+            message: format!("Collection {} already exists", request.name),
+        },
+        _ => AppError::FileOperation(e),
+    })?;
+    tracing::info!("Created collection {}", request.name);
+    Ok(CollectionInfo {
+        name: request.name,
+        review_count: 0,
+    })
+}
+
+/// Every collection with the number of reviews it holds
+pub fn list(root: &DataPaths) -> Result<Vec<CollectionInfo>, AppError> {
+    let mut collections = Vec::new();
+    for name in names(root)? {
+        let data_paths = data_paths(root, &name)?;
+        let review_count = match data_paths.reviews_jsonl.exists() {
+            true => {
+                let _lock = FileLock::acquire_shared(&data_paths.lock_file, "list_collections")?;
+                JsonlStorage::new(&data_paths.reviews_jsonl).read_live_reviews()?.len()
+            }
+            false => 0,
+        };
+        collections.push(CollectionInfo { name, review_count });
+    }
+    Ok(collections)
+}
+
+/// Search every collection `search_request` names and merge the rankings. Each
+/// collection is searched as asked but for paging, down to the end of the page.
+pub(crate) fn search(root: &DataPaths, search_request: SearchRequest) -> Result<SearchResponse, AppError> {
+    let selected = resolve(root, &search_request.collections)?;
+    let depth = search_request.ranking_depth();
+    let mut collection_request = SearchRequest {
+        cursor: None,
+        fallback: Some(false),
+        collections: Vec::new(),
+        ..search_request.clone()
+    };
+
+    let mut responses = Vec::with_capacity(selected.len());
+    let mut results = Vec::new();
+    for (name, data_paths) in &selected {
+        // A page of at most `MAX_SEARCH_LIMIT` at a time, until the collection runs out
+        let mut ranked: Vec<SearchResult> = Vec::new();
+        let response = loop {
+            collection_request.offset = Some(ranked.len());
+            collection_request.limit = Some((depth - ranked.len()).min(MAX_SEARCH_LIMIT));
+            let mut page = search::search_once(data_paths, collection_request.clone())?;
+            ranked.append(&mut page.results);
+            if page.next_cursor.is_none() || ranked.len() >= depth {
+                break page;
+            }
+        };
+        results.extend(ranked.into_iter().map(|result| SearchResult {
+            collection: Some(name.clone()),
+            ..result
+        }));
+        responses.push(response);
+    }
+
+    results.sort_by(SearchResult::rank_order);
+    if search_request.group_by_product {
+        results = SearchResult::group_by_product(results);
+    }
+    results.truncate(depth);
+    let (results, next_cursor) = search_request.page(results);
+
+    // Collections correct and expand from their own vocabularies; report the first
+    // correction and every term used
+    let mut expanded_terms: Vec<String> = Vec::new();
+    let mut applied_rules: Vec<String> = Vec::new();
+    for response in &responses {
+        for term in &response.expanded_terms {
+            if !expanded_terms.contains(term) {
+                expanded_terms.push(term.clone());
+            }
+        }
+        for rule in &response.applied_rules {
+            if !applied_rules.contains(rule) {
+                applied_rules.push(rule.clone());
+            }
+        }
+    }
+    let first = responses.first();
+
+    tracing::info!(
+        "Search performed for query: '{}' across {} collections, found {} results",
+        search_request.query,
+        selected.len(),
+        results.len()
+    );
+
+    Ok(SearchResponse {
+        success: true,
+        total_results: results.len(),
+        limit: search_request.get_limit(),
+        offset: search_request.get_offset(),
+        next_cursor,
+        corrected_query: responses.iter().find_map(|response| response.corrected_query.clone()),
+        personalized: responses.iter().any(|response| response.personalized),
+        parsed_query: first.and_then(|response| response.parsed_query.clone()),
+        search_type: first.map_or_else(|| "vector_similarity".to_string(), |response| response.search_type.clone()),
+        query: search_request.query,
+        results,
+        filters: search_request.filters,
+        expanded_terms,
+        applied_rules,
+        fallback: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn review(title: &str) -> ReviewData {
+        ReviewData {
+            title: title.to_string(),
+            body: "Heats a litre of water in two minutes.".to_string(),
+            product_id: "kettle".to_string(),
+            rating: 3,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    fn search_for(query: &str, collections: &[&str]) -> SearchRequest {
+        serde_json::from_value(serde_json::json!({ "query": query, "collections": collections })).unwrap()
+    }
+
+    #[test]
+    fn test_collections_are_created_resolved_and_searched_together() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = DataPaths::new(temp_dir.path());
+        create(&root, CreateCollectionRequest { name: "us".to_string() }).unwrap();
+        create(&root, CreateCollectionRequest { name: "th".to_string() }).unwrap();
+        assert!(matches!(
+            create(&root, CreateCollectionRequest { name: "us".to_string() }),
+            Err(AppError::Conflict { .. })
+        ));
+        assert!(create(&root, CreateCollectionRequest { name: "../data".to_string() }).is_err());
+        assert_eq!(names(&root).unwrap(), ["default", "th", "us"]);
+
+        let selected = resolve(&root, &["us".to_string(), "*".to_string()]).unwrap();
+        let selected: Vec<&str> = selected.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(selected, ["us", "default", "th"]);
+        assert!(matches!(resolve(&root, &["eu".to_string()]), Err(AppError::NotFound { .. })));
+        assert!(validate_selection(&["*".to_string(), "us".to_string()]).is_ok());
+        assert!(validate_selection(&["us,th".to_string()]).is_err());
+
+        for (name, title) in [("us", "Kettle boils fast"), ("th", "Kettle pours well")] {
+            let data_paths = data_paths(&root, name).unwrap();
+            JsonlStorage::new(&data_paths.reviews_jsonl)
+                .append_review(&review(title).to_metadata(0).unwrap())
+                .unwrap();
+        }
+        let response = search(&root, search_for("kettle", &["*"])).unwrap();
+        let mut found: Vec<&str> = response.results.iter().filter_map(|result| result.collection.as_deref()).collect();
+        found.sort();
+        assert_eq!(found, ["th", "us"]);
+        let response = search(&root, search_for("kettle", &["th"])).unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].review.title, "Kettle pours well");
+        assert_eq!(list(&root).unwrap()[2], CollectionInfo { name: "us".to_string(), review_count: 1 });
+    }
+}
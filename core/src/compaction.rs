@@ -0,0 +1,329 @@
+//! Review file compaction: drops tombstoned lines and renumbers vector slots densely.
+//!
+//! Compaction runs as a background job recorded in jobs.jsonl. It works from a snapshot
+//! without holding the data lock, reporting progress as it goes, and only takes the
+//! exclusive lock to swap the compacted file in. If any write changed the review file
+//! in the meantime the job fails rather than lose that write; run it again.
+//!
+//! Renumbering moves vector slots, so the swap empties reviews.index and the embedding
+//! worker rebuilds it from the compacted file.
+
+use crate::canary;
+use crate::embedding::EMBEDDING_DIM;
+use crate::embedding_worker;
+use crate::jobs::JobStorage;
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Reviews processed between progress updates and pause checks
+pub const COMPACTION_CHUNK_SIZE: usize = 500;
+
+/// How often a paused job checks whether it was resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Pause switch for a job running in this process
+#[derive(Default)]
+struct JobControl {
+    paused: AtomicBool,
+}
+
+fn running_jobs() -> &'static Mutex<HashMap<String, Arc<JobControl>>> {
+    static RUNNING_JOBS: OnceLock<Mutex<HashMap<String, Arc<JobControl>>>> = OnceLock::new();
+    RUNNING_JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Record a compaction job and start it in the background. Fails with a conflict if
+/// this process is already compacting.
+pub fn start(data_dir: PathBuf) -> Result<JobRecord, AppError> {
+    let data_paths = DataPaths::new(&data_dir);
+    data_paths.ensure_directories()?;
+
+    let control = Arc::new(JobControl::default());
+    let job = {
+        let mut running = running_jobs().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !running.is_empty() {
+            return Err(AppError::Conflict {
+                message: "A compaction is already running".to_string(),
+            });
+        }
+
+        let _lock = FileLock::acquire(&data_paths.lock_file, "start_compaction")?;
+        let total = JsonlStorage::new(&data_paths.reviews_jsonl).count_reviews()?;
+        let now = Utc::now();
+        let job = JobRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            job_type: JobType::Compaction,
+            status: JobStatus::Running,
+            created_at: now,
+            file_name: None,
+            total_processed: 0,
+            successful: 0,
+            failed: 0,
+            batch_id: None,
+            rolled_back_at: None,
+            progress: Some(JobProgress::new(total, now)),
+            error: None,
+        };
+        JobStorage::new(&data_paths.jobs_jsonl).append_job(&job)?;
+
+        running.insert(job.id.clone(), control.clone());
+        job
+    };
+
+    let mut background_job = job.clone();
+    tokio::task::spawn_blocking(move || {
+        run(&data_paths, &mut background_job, &control);
+        running_jobs()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&background_job.id);
+    });
+
+    Ok(job)
+}
+
+/// Pause or resume a job running in this process. Returns `false` if it is not running here.
+pub fn set_paused(job_id: &str, paused: bool) -> bool {
+    let running = running_jobs().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match running.get(job_id) {
+        Some(control) => {
+            control.paused.store(paused, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Run a compaction to the end, recording the outcome on the job
+fn run(data_paths: &DataPaths, job: &mut JobRecord, control: &JobControl) {
+    match compact(data_paths, job, control) {
+        Ok(kept) => {
+            job.status = JobStatus::Completed;
+            job.successful = kept;
+            tracing::info!(
+                "Compaction {} finished: kept {} of {} lines",
+                job.id,
+                kept,
+                job.total_processed
+            );
+        }
+        Err(e) => {
+            tracing::error!("Compaction {} failed: {}", job.id, e);
+            job.status = JobStatus::Failed;
+            job.error = Some(e.to_string());
+        }
+    }
+
+    if let Err(e) = save_job(data_paths, job) {
+        tracing::error!("Failed to record the outcome of compaction {}: {}", job.id, e);
+    }
+
+    // Compaction rewrites every line, so check relevance hasn't moved
+    if job.status == JobStatus::Completed {
+        if let Err(e) = canary::run(data_paths, "compaction") {
+            tracing::error!("Canary queries could not run after compaction {}: {}", job.id, e);
+        }
+    }
+}
+
+/// Write the compacted file from a snapshot, then swap it in under the exclusive lock.
+/// Returns the number of reviews kept.
+fn compact(data_paths: &DataPaths, job: &mut JobRecord, control: &JobControl) -> Result<usize, AppError> {
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let (reviews, snapshot_len) = {
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "compaction_snapshot")?;
+        (storage.read_all_reviews()?, file_len(&data_paths.reviews_jsonl)?)
+    };
+
+    let mut progress = JobProgress::new(reviews.len(), Utc::now());
+    let mut active = Duration::ZERO;
+    let temp_path = data_paths.reviews_jsonl.with_extension("jsonl.compact");
+    let file = File::create(&temp_path)?;
+    let mut writer = BufWriter::new(&file);
+    let mut kept = 0;
+
+    for (chunk_number, chunk) in reviews.chunks(COMPACTION_CHUNK_SIZE).enumerate() {
+        wait_while_paused(data_paths, job, control)?;
+
+        let chunk_started = Instant::now();
+        for review in chunk.iter().filter(|review| !review.is_deleted()) {
+            let mut review = review.clone();
+            review.vector_index = kept;
+            writeln!(writer, "{}", serde_json::to_string(&review)?)?;
+            kept += 1;
+        }
+        active += chunk_started.elapsed();
+
+        let processed = (chunk_number * COMPACTION_CHUNK_SIZE + chunk.len()).min(reviews.len());
+        progress.advance(processed, active.as_secs_f64(), Utc::now());
+        job.total_processed = processed;
+        job.successful = kept;
+        job.progress = Some(progress.clone());
+        save_job(data_paths, job)?;
+    }
+
+    writer.flush()?;
+    drop(writer);
+    file.sync_all()?;
+
+    let _lock = FileLock::acquire(&data_paths.lock_file, "compaction_swap")?;
+    if file_len(&data_paths.reviews_jsonl)? != snapshot_len {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(AppError::Conflict {
+            message: "Reviews changed while compacting; nothing was replaced, run the compaction again".to_string(),
+        });
+    }
+    std::fs::rename(&temp_path, &data_paths.reviews_jsonl)?;
+    // Reindex now so the reclaimed slots drop out of the excluded-slot bitmap
+    storage.rebuild_id_index()?;
+
+    // Every kept review may have moved slot; the embedding worker rebuilds the vectors
+    VectorStore::new(&data_paths.reviews_index, EMBEDDING_DIM).truncate(0)?;
+    embedding_worker::notify();
+
+    Ok(kept)
+}
+
+/// Block while the job is paused, recording the paused state so the jobs API shows it
+fn wait_while_paused(data_paths: &DataPaths, job: &mut JobRecord, control: &JobControl) -> Result<(), AppError> {
+    if !control.paused.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    job.status = JobStatus::Paused;
+    save_job(data_paths, job)?;
+    tracing::info!("Compaction {} paused", job.id);
+
+    while control.paused.load(Ordering::SeqCst) {
+        std::thread::sleep(PAUSE_POLL_INTERVAL);
+    }
+
+    job.status = JobStatus::Running;
+    save_job(data_paths, job)?;
+    tracing::info!("Compaction {} resumed", job.id);
+    Ok(())
+}
+
+fn save_job(data_paths: &DataPaths, job: &JobRecord) -> Result<(), AppError> {
+    let _lock = FileLock::acquire(&data_paths.lock_file, "compaction_progress")?;
+    JobStorage::new(&data_paths.jobs_jsonl).update_job(job)
+}
+
+fn file_len(path: &std::path::Path) -> Result<u64, AppError> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn review(id: &str, vector_index: usize, deleted: bool) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Compaction review".to_string(),
+            body: "A review body long enough to store.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: deleted.then(Utc::now),
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_compaction_pauses_and_drops_tombstones() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+        storage
+            .append_reviews(&[review("a", 0, false), review("b", 1, true), review("c", 2, false)])
+            .unwrap();
+        assert!(storage.excluded_vector_slots().unwrap().contains(1));
+
+        let mut job = JobRecord {
+            id: "compaction_1".to_string(),
+            job_type: JobType::Compaction,
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            file_name: None,
+            total_processed: 0,
+            successful: 0,
+            failed: 0,
+            batch_id: None,
+            rolled_back_at: None,
+            progress: None,
+            error: None,
+        };
+        let job_storage = JobStorage::new(&data_paths.jobs_jsonl);
+        job_storage.append_job(&job).unwrap();
+
+        // Start paused, check the pause is visible, then resume
+        let control = Arc::new(JobControl::default());
+        control.paused.store(true, Ordering::SeqCst);
+        let worker = {
+            let control = control.clone();
+            let data_dir = temp_dir.path().to_path_buf();
+            std::thread::spawn(move || {
+                run(&DataPaths::new(data_dir), &mut job, &control);
+            })
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while job_storage.get_job("compaction_1").unwrap().unwrap().status != JobStatus::Paused {
+            assert!(Instant::now() < deadline, "job never reported being paused");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        control.paused.store(false, Ordering::SeqCst);
+        worker.join().unwrap();
+
+        let finished = job_storage.get_job("compaction_1").unwrap().unwrap();
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!(finished.successful, 2);
+        let progress = finished.progress.unwrap();
+        assert_eq!((progress.processed, progress.total, progress.eta_secs), (3, 3, Some(0)));
+
+        let reviews = storage.read_all_reviews().unwrap();
+        let ids: Vec<(&str, usize)> = reviews.iter().map(|r| (r.id.as_str(), r.vector_index)).collect();
+        assert_eq!(ids, vec![("a", 0), ("c", 1)]);
+        assert!(storage.get_review_by_id("c").unwrap().is_some());
+        // The tombstone's slot was reclaimed, so nothing is excluded any more
+        assert_eq!(storage.excluded_vector_slots().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_progress_estimates_time_left() {
+        let now = Utc::now();
+        let mut progress = JobProgress::new(1000, now);
+        assert_eq!(progress.eta_secs, None);
+
+        progress.advance(250, 5.0, now);
+        assert_eq!(progress.throughput_per_sec, Some(50.0));
+        assert_eq!(progress.eta_secs, Some(15));
+    }
+}
@@ -0,0 +1,281 @@
+//! Corpus size limits, so a single-node deployment's review file and index stop
+//! growing where the operator says.
+//!
+//! `MAX_REVIEWS` caps the live reviews stored and `MAX_CORPUS_BYTES` the bytes they
+//! take in reviews.jsonl; unset or 0 means no limit. Tombstoned lines are not counted.
+//! When a create, batch create or bulk upload would go over a limit, `CORPUS_EVICTION`
+//! decides what happens: `refuse` (the default) rejects the whole request with 507
+//! `E_CORPUS_FULL`, `oldest` tombstones the oldest live reviews, by timestamp, until the
+//! new ones fit. A request holding more than a limit on its own is refused either way.
+//!
+//! Evicted reviews are tombstoned like deletes, under the same lock as the write, and
+//! announced as `review_deleted` events. Once tombstoned lines make up half the review
+//! file a compaction job is started to reclaim the space.
+
+use crate::compaction;
+use crate::models::*;
+use crate::storage::JsonlStorage;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// What happens to a write that would go over a limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    #[default]
+    Refuse,
+    Oldest,
+}
+
+impl EvictionPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "refuse" => Some(EvictionPolicy::Refuse),
+            "oldest" => Some(EvictionPolicy::Oldest),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EvictionPolicy::Refuse => "refuse",
+            EvictionPolicy::Oldest => "oldest",
+        }
+    }
+}
+
+/// Limits on the live corpus, read from the environment
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CorpusLimits {
+    pub max_reviews: Option<usize>,
+    pub max_bytes: Option<u64>,
+    pub policy: EvictionPolicy,
+}
+
+/// Reviews tombstoned to make room for a write
+#[derive(Debug, Default)]
+pub struct Eviction {
+    pub evicted: Vec<ReviewMetadata>,
+    /// Tombstoned lines are now at least half the review file
+    pub compact: bool,
+}
+
+/// Bytes a review takes in reviews.jsonl, newline included
+fn stored_bytes(review: &ReviewMetadata) -> u64 {
+    serde_json::to_vec(review).map_or(0, |line| line.len() as u64 + 1)
+}
+
+fn limit<T: std::str::FromStr + PartialEq + Default>(name: &str) -> Option<T> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|limit| *limit != T::default())
+}
+
+impl CorpusLimits {
+    pub fn from_env() -> Self {
+        CorpusLimits {
+            max_reviews: limit("MAX_REVIEWS"),
+            max_bytes: limit("MAX_CORPUS_BYTES"),
+            policy: std::env::var("CORPUS_EVICTION")
+                .ok()
+                .and_then(|value| EvictionPolicy::parse(&value))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.max_reviews.is_some() || self.max_bytes.is_some()
+    }
+
+    fn full(&self, reason: String) -> AppError {
+        AppError::CorpusFull {
+            reason,
+            max_reviews: self.max_reviews,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    /// Which of the live reviews to evict so `incoming` fits, oldest first, or why it cannot
+    fn plan(&self, live: &[ReviewMetadata], incoming: &[ReviewMetadata]) -> Result<Vec<usize>, AppError> {
+        let incoming_bytes: u64 = incoming.iter().map(stored_bytes).sum();
+        if self.max_reviews.is_some_and(|max| incoming.len() > max) {
+            return Err(self.full(format!(
+                "the request holds {} reviews, more than the limit of {}",
+                incoming.len(),
+                self.max_reviews.unwrap_or_default()
+            )));
+        }
+        if self.max_bytes.is_some_and(|max| incoming_bytes > max) {
+            return Err(self.full(format!(
+                "the request holds {} bytes of reviews, more than the limit of {}",
+                incoming_bytes,
+                self.max_bytes.unwrap_or_default()
+            )));
+        }
+
+        let mut reviews = live.len() + incoming.len();
+        let mut bytes = live.iter().map(stored_bytes).sum::<u64>() + incoming_bytes;
+        let over = |reviews: usize, bytes: u64| {
+            self.max_reviews.is_some_and(|max| reviews > max) || self.max_bytes.is_some_and(|max| bytes > max)
+        };
+        if !over(reviews, bytes) {
+            return Ok(Vec::new());
+        }
+        if self.policy == EvictionPolicy::Refuse {
+            return Err(self.full(format!(
+                "storing {} more reviews would exceed the limit of {}",
+                incoming.len(),
+                match (self.max_reviews, self.max_bytes) {
+                    (Some(max_reviews), Some(max_bytes)) => format!("{} reviews or {} bytes", max_reviews, max_bytes),
+                    (Some(max_reviews), None) => format!("{} reviews", max_reviews),
+                    (None, max_bytes) => format!("{} bytes", max_bytes.unwrap_or_default()),
+                }
+            )));
+        }
+
+        let mut oldest: Vec<usize> = (0..live.len()).collect();
+        oldest.sort_by_key(|&position| (live[position].timestamp, live[position].vector_index));
+        let mut evicted = Vec::new();
+        for position in oldest {
+            if !over(reviews, bytes) {
+                break;
+            }
+            reviews -= 1;
+            bytes -= stored_bytes(&live[position]);
+            evicted.push(position);
+        }
+        Ok(evicted)
+    }
+
+    /// Make room for `incoming` before it is appended, tombstoning the oldest live reviews
+    /// if the policy allows. The caller must hold the FileLock.
+    pub fn make_room(&self, storage: &JsonlStorage, incoming: &[ReviewMetadata]) -> Result<Eviction, AppError> {
+        if !self.is_limited() || incoming.is_empty() {
+            return Ok(Eviction::default());
+        }
+
+        let live = storage.read_live_reviews()?;
+        let evict = self.plan(&live, incoming)?;
+        if evict.is_empty() {
+            return Ok(Eviction::default());
+        }
+
+        let ids: HashSet<&str> = evict.iter().map(|&position| live[position].id.as_str()).collect();
+        let evicted = storage.delete_reviews_where(|review| ids.contains(review.id.as_str()))?;
+        let live_after = live.len() - evicted.len() + incoming.len();
+        let tombstoned = storage.count_reviews()? + incoming.len() - live_after;
+        tracing::info!(
+            "Corpus limit reached: evicted {} oldest reviews to store {} new ones",
+            evicted.len(),
+            incoming.len()
+        );
+        Ok(Eviction {
+            evicted,
+            compact: tombstoned >= live_after,
+        })
+    }
+}
+
+/// Start a compaction job once the write holding the lock finishes; a compaction
+/// already running is left to finish
+pub fn reclaim_space(data_dir: PathBuf) {
+    tokio::task::spawn_blocking(move || match compaction::start(data_dir) {
+        Ok(job) => tracing::info!("Started compaction job {} to reclaim evicted reviews", job.id),
+        Err(AppError::Conflict { .. }) => {}
+        Err(e) => tracing::warn!("Could not start compaction after eviction: {}", e),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn review(id: &str, vector_index: usize, minutes_ago: i64) -> ReviewMetadata {
+        let mut review = ReviewData {
+            title: format!("Review {}", id),
+            body: "A review body long enough to be valid.".to_string(),
+            product_id: "prod_1".to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+        .to_metadata(vector_index)
+        .unwrap();
+        review.id = id.to_string();
+        review.timestamp = Utc::now() - Duration::minutes(minutes_ago);
+        review
+    }
+
+    #[test]
+    fn test_eviction_plans_follow_the_policy() {
+        // Stored out of timestamp order: "b" is the oldest
+        let live = vec![review("a", 0, 10), review("b", 1, 30), review("c", 2, 20)];
+        let incoming = vec![review("d", 3, 0), review("e", 4, 0)];
+
+        let unlimited = CorpusLimits::default();
+        assert!(unlimited.plan(&live, &incoming).unwrap().is_empty());
+
+        let oldest = CorpusLimits {
+            max_reviews: Some(4),
+            policy: EvictionPolicy::Oldest,
+            ..CorpusLimits::default()
+        };
+        assert_eq!(oldest.plan(&live, &incoming).unwrap(), vec![1]);
+
+        let refuse = CorpusLimits { policy: EvictionPolicy::Refuse, ..oldest };
+        let error = refuse.plan(&live, &incoming).unwrap_err();
+        assert!(matches!(error, AppError::CorpusFull { max_reviews: Some(4), .. }), "{}", error);
+        assert!(refuse.plan(&live[..2], &incoming).unwrap().is_empty());
+
+        // A request larger than the limit is refused even when evicting
+        let tiny = CorpusLimits { max_reviews: Some(1), ..oldest };
+        assert!(tiny.plan(&live, &incoming).is_err());
+
+        // Bytes: room for the two new reviews only
+        let two_reviews = incoming.iter().map(stored_bytes).sum::<u64>() + 10;
+        let by_bytes = CorpusLimits {
+            max_bytes: Some(two_reviews),
+            policy: EvictionPolicy::Oldest,
+            ..CorpusLimits::default()
+        };
+        assert_eq!(by_bytes.plan(&live, &incoming).unwrap(), vec![1, 2, 0]);
+
+        assert_eq!(EvictionPolicy::parse(" Oldest "), Some(EvictionPolicy::Oldest));
+        assert_eq!(EvictionPolicy::parse("lru"), None);
+    }
+
+    #[test]
+    fn test_make_room_tombstones_the_oldest_reviews() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let storage = JsonlStorage::new(temp_dir.path().join("reviews.jsonl"));
+        storage.append_reviews(&[review("a", 0, 30), review("b", 1, 20), review("c", 2, 10)]).unwrap();
+        let limits = CorpusLimits {
+            max_reviews: Some(3),
+            policy: EvictionPolicy::Oldest,
+            ..CorpusLimits::default()
+        };
+
+        let eviction = limits.make_room(&storage, &[review("d", 3, 0)]).unwrap();
+        assert_eq!(eviction.evicted.iter().map(|review| review.id.as_str()).collect::<Vec<_>>(), ["a"]);
+        assert!(eviction.evicted[0].is_deleted());
+        assert!(!eviction.compact);
+        storage.append_reviews(&[review("d", 3, 0)]).unwrap();
+        assert_eq!(storage.count_live_reviews().unwrap(), 3);
+
+        // Two more: three tombstoned lines against three live reviews
+        let incoming = [review("e", 4, 0), review("f", 5, 0)];
+        let eviction = limits.make_room(&storage, &incoming).unwrap();
+        assert_eq!(eviction.evicted.iter().map(|review| review.id.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+        assert!(eviction.compact);
+        storage.append_reviews(&incoming).unwrap();
+
+        let error = ErrorResponse::from(CorpusLimits { policy: EvictionPolicy::Refuse, ..limits }
+            .make_room(&storage, &[review("g", 6, 0)])
+            .unwrap_err());
+        assert_eq!(error.code, crate::error_catalog::ErrorCode::CorpusFull);
+        assert_eq!(error.details.unwrap()["max_reviews"], 3);
+    }
+}
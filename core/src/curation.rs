@@ -0,0 +1,513 @@
+//! Curation: rules admins set to override the organic ranking of searches.
+//!
+//! A pin shows one review at a fixed position in the results of a query or of a
+//! product's searches, whatever its similarity. A boost adds a weight to the scores of a
+//! product's or a reviewer's reviews, e.g. -1 to sink a recalled product. Rules live in
+//! curation.jsonl in the data directory, so every process sharing it ranks alike.
+//!
+//! Rules are applied in the ranking stage, after personalization: boosts over a window
+//! of `BOOST_WINDOW` times the limit, so boosted reviews can move up into the page, then
+//! pins. A search with `curate: false` ranks without rules, to compare against the
+//! organic ranking. On a sharded deployment the coordinator applies its own rules to the
+//! merged results.
+
+use crate::explain::{MatchKind, ScoreExplanation};
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock};
+use crate::tokenizer;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Rules allowed per data directory
+pub const MAX_CURATION_RULES: usize = 500;
+
+/// Candidates ranked per result requested when a boost applies
+pub const BOOST_WINDOW: usize = 3;
+
+/// Longest accepted rule note
+pub const MAX_NOTE_LENGTH: usize = 500;
+
+/// Searches a rule applies to; every search when empty
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RuleScope {
+    /// Searches for this query, compared after normalization, so case, accents and
+    /// punctuation don't matter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Searches filtered to this product
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+}
+
+impl RuleScope {
+    fn is_empty(&self) -> bool {
+        self.query.is_none() && self.product_id.is_none()
+    }
+
+    fn matches(&self, search_request: &SearchRequest) -> bool {
+        let query_matches = self
+            .query
+            .as_ref()
+            .is_none_or(|query| normalized_query(query) == normalized_query(&search_request.query));
+        let searched_product = search_request.filters.as_ref().and_then(|filters| filters.product_id.as_ref());
+        let product_matches = self.product_id.as_ref().is_none_or(|product_id| searched_product == Some(product_id));
+        query_matches && product_matches
+    }
+}
+
+fn normalized_query(query: &str) -> String {
+    tokenizer::analyze(query).join(" ")
+}
+
+/// Reviews a boost applies to: those matching every field given
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BoostTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer_id: Option<String>,
+}
+
+impl BoostTarget {
+    fn matches(&self, review: &ReviewMetadata) -> bool {
+        self.product_id.as_ref().is_none_or(|product_id| *product_id == review.product_id)
+            && self.reviewer_id.as_ref().is_none_or(|reviewer_id| review.reviewer_id.as_ref() == Some(reviewer_id))
+    }
+}
+
+/// What a rule does to the results of the searches in its scope
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CurationAction {
+    /// Show `review_id` at `position`, counted from 1
+    Pin { review_id: String, position: usize },
+    /// Add `weight`, from -1 to 1, to the scores of matching reviews
+    Boost { target: BoostTarget, weight: f32 },
+}
+
+/// A stored curation rule
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct CurationRule {
+    pub id: String,
+    #[serde(default)]
+    pub scope: RuleScope,
+    pub action: CurationAction,
+    /// Why the rule exists, for other admins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body of `POST /admin/curation/rules`
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct CreateCurationRuleRequest {
+    #[serde(default)]
+    pub scope: RuleScope,
+    pub action: CurationAction,
+    pub note: Option<String>,
+}
+
+impl CreateCurationRuleRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let invalid = |field: &str, reason: &str| ValidationError::InvalidValue {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if let Some(query) = &self.scope.query {
+            if normalized_query(query).is_empty() || query.len() > 500 {
+                return Err(invalid("scope", "query must have a word to match and at most 500 characters"));
+            }
+        }
+        if self.scope.product_id.as_ref().is_some_and(|product_id| product_id.trim().is_empty()) {
+            return Err(invalid("scope", "product_id must not be empty"));
+        }
+
+        match &self.action {
+            CurationAction::Pin { review_id, position } => {
+                if self.scope.is_empty() {
+                    return Err(invalid("scope", "a pin needs a query or product_id"));
+                }
+                if review_id.trim().is_empty() {
+                    return Err(invalid("action", "review_id must not be empty"));
+                }
+                if !(1..=MAX_SEARCH_LIMIT).contains(position) {
+                    return Err(invalid("position", &format!("must be between 1 and {}", MAX_SEARCH_LIMIT)));
+                }
+            }
+            CurationAction::Boost { target, weight } => {
+                if *target == BoostTarget::default() {
+                    return Err(invalid("target", "must name a product_id or reviewer_id"));
+                }
+                if !weight.is_finite() || *weight == 0.0 || weight.abs() > 1.0 {
+                    return Err(invalid("weight", "must be between -1 and 1, and not 0"));
+                }
+            }
+        }
+
+        if self.note.as_ref().is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH) {
+            return Err(ValidationError::TooLong {
+                field: "note".to_string(),
+                max_length: MAX_NOTE_LENGTH,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// JSONL file of curation rules
+pub struct CurationStorage {
+    file_path: PathBuf,
+}
+
+impl CurationStorage {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Read every rule, oldest first
+    pub fn read_all(&self) -> Result<Vec<CurationRule>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(File::open(&self.file_path)?);
+        let mut rules = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                rules.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Append a rule. The caller must hold the FileLock.
+    pub fn append(&self, rule: &CurationRule) -> Result<(), AppError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.file_path)?;
+        writeln!(file, "{}", serde_json::to_string(rule)?)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Atomically drop the rule with `id`, returning it. The caller must hold the FileLock.
+    pub fn remove(&self, id: &str) -> Result<Option<CurationRule>, AppError> {
+        let (removed, kept): (Vec<CurationRule>, Vec<CurationRule>) =
+            self.read_all()?.into_iter().partition(|rule| rule.id == id);
+        if removed.is_empty() {
+            return Ok(None);
+        }
+
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for rule in &kept {
+                writeln!(writer, "{}", serde_json::to_string(rule)?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, &self.file_path)?;
+
+        Ok(removed.into_iter().next())
+    }
+}
+
+/// Validate and store a rule
+pub fn create(data_paths: &DataPaths, request: CreateCurationRuleRequest) -> Result<CurationRule, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let storage = CurationStorage::new(&data_paths.curation_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "create_curation_rule")?;
+    if storage.read_all()?.len() >= MAX_CURATION_RULES {
+        return Err(AppError::Conflict {
+            message: format!("At most {} curation rules can be stored", MAX_CURATION_RULES),
+        });
+    }
+
+    let rule = CurationRule {
+        id: uuid::Uuid::new_v4().to_string(),
+        scope: request.scope,
+        action: request.action,
+        note: request.note,
+        created_at: Utc::now(),
+    };
+    storage.append(&rule)?;
+    Ok(rule)
+}
+
+/// The rules that apply to one search
+#[derive(Clone, Debug, Default)]
+pub struct Curation {
+    rules: Vec<CurationRule>,
+    /// Whether the search explains its scores, so pins and boosts are recorded
+    explain: bool,
+}
+
+impl Curation {
+    /// Rules in scope for `search_request`, read from the data directory; none when the
+    /// search opts out. Readers should hold the FileLock shared.
+    pub fn load(data_paths: &DataPaths, search_request: &SearchRequest) -> Result<Curation, AppError> {
+        if search_request.curate == Some(false) {
+            return Ok(Curation::default());
+        }
+        let rules = CurationStorage::new(&data_paths.curation_jsonl).read_all()?;
+        Ok(Curation::for_search(rules, search_request))
+    }
+
+    /// `load` under the shared lock, for callers that don't hold it
+    pub fn load_shared(data_paths: &DataPaths, search_request: &SearchRequest) -> Result<Curation, AppError> {
+        if search_request.curate == Some(false) || !data_paths.curation_jsonl.exists() {
+            return Ok(Curation::default());
+        }
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "load_curation")?;
+        Curation::load(data_paths, search_request)
+    }
+
+    pub fn for_search(rules: Vec<CurationRule>, search_request: &SearchRequest) -> Curation {
+        Curation {
+            rules: rules.into_iter().filter(|rule| rule.scope.matches(search_request)).collect(),
+            explain: search_request.explain,
+        }
+    }
+
+    /// Results to rank before curation, for a page of `limit`
+    pub fn window(&self, limit: usize) -> usize {
+        let boosts = self.rules.iter().any(|rule| matches!(rule.action, CurationAction::Boost { .. }));
+        match boosts {
+            true => limit * BOOST_WINDOW,
+            false => limit,
+        }
+    }
+
+    /// Reviews pinned for this search, for the caller to look up
+    pub fn pinned_ids(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter_map(|rule| match &rule.action {
+                CurationAction::Pin { review_id, .. } => Some(review_id.as_str()),
+                CurationAction::Boost { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Boost `results`, cut them to `limit` and place the `pinned` reviews, those of
+    /// `pinned_ids` that exist and pass the search's filters. A pinned review keeps its
+    /// score if it ranked in the window, otherwise it scores 0. When two pins name the
+    /// same review or position, the older rule wins. Returns the ids of the rules that
+    /// changed the results.
+    pub fn apply(&self, results: &mut Vec<SearchResult>, pinned: &[ReviewMetadata], limit: usize) -> Vec<String> {
+        let mut applied = Vec::new();
+
+        for rule in &self.rules {
+            let CurationAction::Boost { target, weight } = &rule.action else {
+                continue;
+            };
+            let mut boosted = false;
+            for result in results.iter_mut().filter(|result| target.matches(&result.review)) {
+                result.similarity_score = (result.similarity_score + weight).clamp(0.0, 1.0);
+                if let Some(explanation) = &mut result.explanation {
+                    *explanation.curation_boost.get_or_insert(0.0) += weight;
+                }
+                boosted = true;
+            }
+            if boosted {
+                applied.push(rule.id.clone());
+            }
+        }
+        results.sort_by(SearchResult::rank_order);
+
+        // Rules are stored oldest first, so a stable sort keeps the older of two equal pins first
+        let pinned: HashMap<&str, &ReviewMetadata> = pinned.iter().map(|review| (review.id.as_str(), review)).collect();
+        let mut pins: Vec<(usize, &CurationRule, &ReviewMetadata)> = self
+            .rules
+            .iter()
+            .filter_map(|rule| match &rule.action {
+                CurationAction::Pin { review_id, position } => Some((*position, rule, *pinned.get(review_id.as_str())?)),
+                CurationAction::Boost { .. } => None,
+            })
+            .collect();
+        pins.sort_by_key(|(position, _, _)| *position);
+        let mut placed = HashSet::new();
+        pins.retain(|(_, _, review)| placed.insert(review.id.clone()));
+
+        // Take pinned reviews out first, so placing one can't shift another
+        let mut organic = HashMap::new();
+        results.retain(|result| match placed.contains(&result.review.id) {
+            true => {
+                organic.insert(result.review.id.clone(), (result.similarity_score, result.explanation.clone()));
+                false
+            }
+            false => true,
+        });
+        results.truncate(limit);
+        for (position, rule, review) in pins {
+            let index = (position - 1).min(results.len());
+            let (score, explanation) = organic.remove(&review.id).unwrap_or((0.0, None));
+            let explanation = match self.explain {
+                true => Some(ScoreExplanation {
+                    pinned_by: Some(rule.id.clone()),
+                    ..explanation.unwrap_or_else(|| ScoreExplanation::new(MatchKind::Pinned, 0.0))
+                }),
+                false => None,
+            };
+            results.insert(
+                index,
+                SearchResult {
+                    review: review.clone(),
+                    similarity_score: score,
+                    product_hits: None,
+                    explanation,
+                    collection: None,
+                },
+            );
+            applied.push(rule.id.clone());
+        }
+        results.truncate(limit);
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(id: &str, product_id: &str) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Kettle".to_string(),
+            body: "Boils water.".to_string(),
+            product_id: product_id.to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    fn rule(id: &str, scope: RuleScope, action: CurationAction) -> CurationRule {
+        CurationRule {
+            id: id.to_string(),
+            scope,
+            action,
+            note: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn search(query: &str) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            limit: Some(3),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pins_and_boosts_reorder_results() {
+        let query_scope = RuleScope {
+            query: Some("Electric Kettle!".to_string()),
+            product_id: None,
+        };
+        let rules = vec![
+            rule("sink", RuleScope::default(), CurationAction::Boost {
+                target: BoostTarget { product_id: Some("recalled".to_string()), reviewer_id: None },
+                weight: -1.0,
+            }),
+            rule("pin", query_scope.clone(), CurationAction::Pin { review_id: "p".to_string(), position: 2 }),
+            rule("other", RuleScope { query: Some("toaster".to_string()), product_id: None }, CurationAction::Pin {
+                review_id: "b".to_string(),
+                position: 1,
+            }),
+        ];
+        let curation = Curation::for_search(rules, &search("electric kettle"));
+        assert_eq!(curation.pinned_ids(), vec!["p"]);
+        assert_eq!(curation.window(3), 9);
+
+        let mut results: Vec<SearchResult> = [("r", "recalled", 0.9), ("a", "kettle", 0.8), ("b", "kettle", 0.7), ("c", "kettle", 0.6)]
+            .into_iter()
+            .map(|(id, product_id, score)| SearchResult {
+                review: review(id, product_id),
+                similarity_score: score,
+                product_hits: None,
+                explanation: None,
+                collection: None,
+            })
+            .collect();
+        let applied = curation.apply(&mut results, &[review("p", "kettle")], 3);
+
+        let ids: Vec<&str> = results.iter().map(|result| result.review.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "p", "b"]);
+        assert_eq!(results[1].similarity_score, 0.0);
+        assert_eq!(applied, vec!["sink", "pin"]);
+
+        // Opting out, or a different query, leaves no rule in scope
+        let mut opted_out = search("electric kettle");
+        opted_out.curate = Some(false);
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(Curation::load(&DataPaths::new(temp_dir.path()), &opted_out).unwrap().pinned_ids().is_empty());
+        assert_eq!(Curation::for_search(vec![rule("pin", query_scope, CurationAction::Pin {
+            review_id: "p".to_string(),
+            position: 1,
+        })], &search("kettle")).window(3), 3);
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let pin = |scope: RuleScope, position: usize| CreateCurationRuleRequest {
+            scope,
+            action: CurationAction::Pin { review_id: "r".to_string(), position },
+            note: None,
+        };
+        let product_scope = RuleScope { query: None, product_id: Some("kettle".to_string()) };
+        assert!(pin(product_scope.clone(), 1).validate().is_ok());
+        assert!(pin(RuleScope::default(), 1).validate().is_err());
+        assert!(pin(product_scope, 0).validate().is_err());
+
+        let boost = |target: BoostTarget, weight: f32| CreateCurationRuleRequest {
+            scope: RuleScope::default(),
+            action: CurationAction::Boost { target, weight },
+            note: None,
+        };
+        let reviewer = BoostTarget { product_id: None, reviewer_id: Some("trusted".to_string()) };
+        assert!(boost(reviewer.clone(), 0.2).validate().is_ok());
+        assert!(boost(reviewer, 1.5).validate().is_err());
+        assert!(boost(BoostTarget::default(), 0.2).validate().is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Fingerprint of the review file, so sync tooling can tell whether its last export is
+//! still current without downloading a new one.
+//!
+//! The content hash is SHA-256 over the raw bytes of reviews.jsonl. Every write
+//! (create, update, vote, delete, compaction) changes those bytes, and so does every
+//! change to the export. Hashing reads the whole file, so the result is cached per file
+//! and reused while the file's length and modification time are unchanged.
+
+use crate::models::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+/// Size, age and content hash of the review file
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct DatasetMeta {
+    /// Live reviews, as in the export
+    pub review_count: usize,
+    /// Lines in reviews.jsonl, tombstones included
+    pub jsonl_lines: usize,
+    pub size_bytes: u64,
+    /// `None` before the first review is written
+    pub last_modified: Option<DateTime<Utc>>,
+    /// `sha256:` followed by the hex digest of the file
+    pub content_hash: String,
+}
+
+impl DatasetMeta {
+    /// Quoted strong ETag for the content hash
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.content_hash)
+    }
+}
+
+/// Only the tombstone is needed to count live reviews
+#[derive(Deserialize)]
+struct Tombstone {
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+type Fingerprint = (u64, SystemTime);
+
+fn cache() -> &'static Mutex<HashMap<PathBuf, (Fingerprint, DatasetMeta)>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, (Fingerprint, DatasetMeta)>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Metadata of `reviews_jsonl`. Callers should hold the FileLock shared, so a
+/// write can't land between the hash and the counts.
+pub fn dataset_meta(reviews_jsonl: &Path) -> Result<DatasetMeta, AppError> {
+    let metadata = match std::fs::metadata(reviews_jsonl) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(empty()),
+        Err(e) => return Err(e.into()),
+    };
+    let fingerprint = (metadata.len(), metadata.modified()?);
+
+    let mut cache = cache().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((cached_fingerprint, meta)) = cache.get(reviews_jsonl) {
+        if *cached_fingerprint == fingerprint {
+            return Ok(meta.clone());
+        }
+    }
+
+    let meta = scan(reviews_jsonl, fingerprint)?;
+    cache.insert(reviews_jsonl.to_path_buf(), (fingerprint, meta.clone()));
+    Ok(meta)
+}
+
+fn empty() -> DatasetMeta {
+    DatasetMeta {
+        review_count: 0,
+        jsonl_lines: 0,
+        size_bytes: 0,
+        last_modified: None,
+        content_hash: format!("sha256:{:x}", Sha256::digest(b"")),
+    }
+}
+
+/// Hash and count the file in one streaming pass
+fn scan(reviews_jsonl: &Path, (size_bytes, modified): Fingerprint) -> Result<DatasetMeta, AppError> {
+    let mut reader = BufReader::new(File::open(reviews_jsonl)?);
+    let mut hasher = Sha256::new();
+    let mut line = String::new();
+    let (mut review_count, mut jsonl_lines) = (0, 0);
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        hasher.update(line.as_bytes());
+        if line.trim().is_empty() {
+            continue;
+        }
+        jsonl_lines += 1;
+        let tombstone: Tombstone = serde_json::from_str(&line)?;
+        if tombstone.deleted_at.is_none() {
+            review_count += 1;
+        }
+    }
+
+    Ok(DatasetMeta {
+        review_count,
+        jsonl_lines,
+        size_bytes,
+        last_modified: Some(DateTime::<Utc>::from(modified)),
+        content_hash: format!("sha256:{:x}", hasher.finalize()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::JsonlStorage;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dataset_meta_tracks_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let reviews_jsonl = temp_dir.path().join("reviews.jsonl");
+        assert_eq!(dataset_meta(&reviews_jsonl).unwrap(), empty());
+
+        let storage = JsonlStorage::new(&reviews_jsonl);
+        let reviews: Vec<ReviewMetadata> = (0..3)
+            .map(|index| {
+                ReviewData {
+                    title: format!("Kettle {}", index),
+                    body: "Boils quickly and switches itself off.".to_string(),
+                    product_id: "kettle".to_string(),
+                    rating: 4,
+                    reviewer_id: None,
+                    source: None,
+                    license: None,
+                    attribution: None,
+                }
+                .to_metadata(index)
+                .unwrap()
+            })
+            .collect();
+        storage.append_reviews(&reviews).unwrap();
+
+        let written = dataset_meta(&reviews_jsonl).unwrap();
+        assert_eq!((written.review_count, written.jsonl_lines), (3, 3));
+        assert_eq!(written.size_bytes, std::fs::metadata(&reviews_jsonl).unwrap().len());
+        assert!(written.content_hash.starts_with("sha256:") && written.content_hash.len() == 7 + 64);
+        assert_eq!(dataset_meta(&reviews_jsonl).unwrap(), written);
+
+        storage.delete_review(&reviews[1].id).unwrap();
+        let deleted = dataset_meta(&reviews_jsonl).unwrap();
+        assert_eq!((deleted.review_count, deleted.jsonl_lines), (2, 3));
+        assert_ne!(deleted.content_hash, written.content_hash);
+    }
+}
@@ -0,0 +1,286 @@
+//! Near-duplicate reviews: the draft check behind `POST /reviews/check-duplicate`, and
+//! the check every new review goes through at ingest.
+//!
+//! At ingest a review is compared with the live reviews of the same product, and with
+//! those written before it in the same request, by the overlap of their word shingles:
+//! runs of `SHINGLE_WORDS` normalized words, hashed. Shingles keep word order, so two
+//! reviews using the same words differently don't match, while case, punctuation and
+//! spacing don't count. A review at or above `DUPLICATE_THRESHOLD` is handled per
+//! `DUPLICATE_REVIEWS`: `flag` (the default) stores it with `duplicate_of` naming the
+//! review it repeats, `reject` refuses it with 409 `E_DUPLICATE_REVIEW` and the
+//! conflicting id in the error details, and `off` skips the check.
+
+use crate::models::*;
+use crate::storage::JsonlStorage;
+use crate::tokenizer;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Similarity at or above which a draft is reported as a likely duplicate
+pub const DUPLICATE_THRESHOLD: f32 = 0.8;
+
+/// Words per shingle when comparing reviews at ingest
+const SHINGLE_WORDS: usize = 3;
+
+/// Normalized tokens of a text, deduplicated
+fn word_set(text: &str) -> HashSet<String> {
+    tokenizer::analyze(text).into_iter().collect()
+}
+
+/// Jaccard similarity between the word sets of two texts (0-1)
+pub fn jaccard_similarity(a: &str, b: &str) -> f32 {
+    let words_a = word_set(a);
+    let words_b = word_set(b);
+
+    if words_a.is_empty() && words_b.is_empty() {
+        return 0.0;
+    }
+
+    let intersection = words_a.intersection(&words_b).count() as f32;
+    let union = words_a.union(&words_b).count() as f32;
+
+    intersection / union
+}
+
+/// Find the existing review most similar to a draft, if it reaches the threshold.
+/// When the draft carries a reviewer_id only that reviewer's reviews are considered.
+pub fn find_duplicate<'a>(
+    draft: &DuplicateCheckRequest,
+    reviews: &'a [ReviewMetadata],
+    threshold: f32,
+) -> Option<(&'a ReviewMetadata, f32)> {
+    let draft_text = format!("{} {}", draft.title, draft.body);
+
+    reviews
+        .iter()
+        .filter(|review| match &draft.reviewer_id {
+            Some(reviewer_id) => review.reviewer_id.as_ref() == Some(reviewer_id),
+            None => true,
+        })
+        .map(|review| {
+            let review_text = format!("{} {}", review.title, review.body);
+            (review, jaccard_similarity(&draft_text, &review_text))
+        })
+        .filter(|(_, score)| *score >= threshold)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// What happens to a new review that nearly repeats one of its product's reviews
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    Off,
+    #[default]
+    Flag,
+    Reject,
+}
+
+impl DuplicatePolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(DuplicatePolicy::Off),
+            "flag" => Some(DuplicatePolicy::Flag),
+            "reject" => Some(DuplicatePolicy::Reject),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DuplicatePolicy::Off => "off",
+            DuplicatePolicy::Flag => "flag",
+            DuplicatePolicy::Reject => "reject",
+        }
+    }
+
+    /// The policy set by `DUPLICATE_REVIEWS`
+    pub fn from_env() -> Self {
+        std::env::var("DUPLICATE_REVIEWS")
+            .ok()
+            .and_then(|value| DuplicatePolicy::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Hashed runs of `SHINGLE_WORDS` normalized words of a review's title and body; a
+/// shorter review is one shingle
+fn shingles(title: &str, body: &str) -> HashSet<u64> {
+    let words = tokenizer::analyze(&format!("{} {}", title, body));
+    let hash = |shingle: &[String]| {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        hasher.finish()
+    };
+    match words.len() {
+        0 => HashSet::new(),
+        length if length < SHINGLE_WORDS => HashSet::from([hash(&words)]),
+        _ => words.windows(SHINGLE_WORDS).map(hash).collect(),
+    }
+}
+
+/// Jaccard similarity between two shingle sets (0-1)
+fn shingle_similarity(a: &HashSet<u64>, b: &HashSet<u64>) -> f32 {
+    let union = a.union(b).count();
+    match union {
+        0 => 0.0,
+        _ => a.intersection(b).count() as f32 / union as f32,
+    }
+}
+
+/// The duplicate check of one write, holding the shingles of every live review by
+/// product. The caller must hold the FileLock for as long as it checks reviews.
+pub struct IngestCheck {
+    policy: DuplicatePolicy,
+    products: HashMap<String, Vec<(String, HashSet<u64>)>>,
+}
+
+impl IngestCheck {
+    /// Shingle the live reviews new ones are compared with; none are read when the
+    /// policy is off
+    pub fn load(jsonl_storage: &JsonlStorage, policy: DuplicatePolicy) -> Result<Self, AppError> {
+        let mut check = IngestCheck {
+            policy,
+            products: HashMap::new(),
+        };
+        if policy != DuplicatePolicy::Off {
+            for review in jsonl_storage.read_live_reviews()? {
+                check.remember(&review);
+            }
+        }
+        Ok(check)
+    }
+
+    fn remember(&mut self, review: &ReviewMetadata) {
+        let shingles = shingles(&review.title, &review.body);
+        self.products.entry(review.product_id.clone()).or_default().push((review.id.clone(), shingles));
+    }
+
+    /// The most similar review of the same product, if it reaches the threshold
+    fn find(&self, review: &ReviewMetadata) -> Option<(&str, f32)> {
+        let shingles = shingles(&review.title, &review.body);
+        self.products
+            .get(&review.product_id)?
+            .iter()
+            .map(|(id, other)| (id.as_str(), shingle_similarity(&shingles, other)))
+            .filter(|(_, similarity)| *similarity >= DUPLICATE_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Check a review about to be stored: flag it in `duplicate_of`, or refuse it, per
+    /// the policy. A review let through is compared with the ones checked after it.
+    pub fn check(&mut self, review: &mut ReviewMetadata) -> Result<(), AppError> {
+        if self.policy == DuplicatePolicy::Off {
+            return Ok(());
+        }
+        if let Some((review_id, similarity)) = self.find(review) {
+            if self.policy == DuplicatePolicy::Reject {
+                return Err(AppError::Duplicate {
+                    review_id: review_id.to_string(),
+                    similarity,
+                });
+            }
+            review.duplicate_of = Some(review_id.to_string());
+        }
+        self.remember(review);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn create_review(id: &str, body: &str, reviewer_id: Option<&str>) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Battery review".to_string(),
+            body: body.to_string(),
+            product_id: "phone_001".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: reviewer_id.map(|r| r.to_string()),
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_jaccard_similarity() {
+        assert_eq!(jaccard_similarity("Great battery life", "great BATTERY life!"), 1.0);
+        assert_eq!(jaccard_similarity("battery", "screen"), 0.0);
+        assert_eq!(jaccard_similarity("", ""), 0.0);
+
+        let partial = jaccard_similarity("great battery life", "great screen");
+        assert!(partial > 0.0 && partial < 1.0);
+    }
+
+    #[test]
+    fn test_find_duplicate_scoped_to_reviewer() {
+        let reviews = vec![
+            create_review("rev_001", "The battery lasts two full days.", Some("client_a")),
+            create_review("rev_002", "The screen is bright and sharp.", Some("client_b")),
+        ];
+
+        let draft = DuplicateCheckRequest {
+            title: "Battery review".to_string(),
+            body: "The battery lasts two full days!".to_string(),
+            reviewer_id: Some("client_a".to_string()),
+        };
+        let (matching, score) = find_duplicate(&draft, &reviews, DUPLICATE_THRESHOLD).unwrap();
+        assert_eq!(matching.id, "rev_001");
+        assert!(score >= DUPLICATE_THRESHOLD);
+
+        // Same text from another client is not flagged
+        let other_client = DuplicateCheckRequest {
+            reviewer_id: Some("client_b".to_string()),
+            ..draft
+        };
+        assert!(find_duplicate(&other_client, &reviews, DUPLICATE_THRESHOLD).is_none());
+    }
+
+    #[test]
+    fn test_ingest_check_flags_or_rejects_near_duplicates_of_the_product() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let jsonl_storage = JsonlStorage::new(temp_dir.path().join("reviews.jsonl"));
+        jsonl_storage.append_review(&create_review("rev_001", "The battery lasts two full days.", None)).unwrap();
+
+        // Case and punctuation don't count; word order does
+        let mut resubmitted = create_review("rev_002", "the BATTERY lasts two full days!!", None);
+        let mut reordered = create_review("rev_003", "Two full days the battery lasts.", None);
+        let mut other_product = ReviewMetadata {
+            product_id: "phone_002".to_string(),
+            ..create_review("rev_004", "The battery lasts two full days.", None)
+        };
+        let mut check = IngestCheck::load(&jsonl_storage, DuplicatePolicy::Flag).unwrap();
+        for review in [&mut resubmitted, &mut reordered, &mut other_product] {
+            check.check(review).unwrap();
+        }
+        assert_eq!(resubmitted.duplicate_of.as_deref(), Some("rev_001"));
+        assert_eq!((reordered.duplicate_of, other_product.duplicate_of), (None, None));
+
+        // Rejected, naming the review repeated, which may be earlier in the same write
+        let mut check = IngestCheck::load(&jsonl_storage, DuplicatePolicy::Reject).unwrap();
+        check.check(&mut create_review("rev_005", "Charges from flat in under an hour.", None)).unwrap();
+        let error = check.check(&mut create_review("rev_006", "Charges from flat in under an hour", None)).unwrap_err();
+        assert!(matches!(&error, AppError::Duplicate { review_id, .. } if review_id == "rev_005"));
+        let error_response = ErrorResponse::from(error);
+        assert_eq!(error_response.details.unwrap()["review_id"], "rev_005");
+
+        let mut unchecked = create_review("rev_007", "The battery lasts two full days.", None);
+        IngestCheck::load(&jsonl_storage, DuplicatePolicy::Off).unwrap().check(&mut unchecked).unwrap();
+        assert_eq!(unchecked.duplicate_of, None);
+        assert_eq!(DuplicatePolicy::parse(" Reject"), Some(DuplicatePolicy::Reject));
+    }
+}
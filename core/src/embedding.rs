@@ -0,0 +1,138 @@
+//! Text embeddings for reviews.
+//!
+//! The built-in embedder hashes words and word pairs into a fixed number of signed
+//! buckets (the "hashing trick"). It needs no model files and is deterministic across
+//! processes and restarts, so vectors written by one server are valid for all of them.
+//! A learned model can replace it behind the `Embedder` trait.
+
+use crate::models::{ReviewMetadata, SearchField};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Dimension of the vectors stored in reviews.index
+pub const EMBEDDING_DIM: usize = 256;
+
+/// Turns text into a fixed-length, L2-normalized vector
+pub trait Embedder: Send + Sync {
+    fn dimension(&self) -> usize;
+
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Feature-hashing embedder over token unigrams and bigrams
+pub struct HashingEmbedder {
+    dimension: usize,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimension: usize) -> Self {
+        Self { dimension }
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(EMBEDDING_DIM)
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimension];
+        let words = crate::tokenizer::tokenize(text);
+
+        let mut add = |feature: &[u8], weight: f32| {
+            let hash = fnv1a(feature);
+            let bucket = (hash % self.dimension as u64) as usize;
+            // The top bit picks the sign, so unrelated features cancel out on average
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[bucket] += sign * weight;
+        };
+        for word in &words {
+            add(word.as_bytes(), 1.0);
+        }
+        for pair in words.windows(2) {
+            add(format!("{} {}", pair[0], pair[1]).as_bytes(), 0.5);
+        }
+
+        let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+        vector
+    }
+}
+
+/// 64-bit FNV-1a; unlike std's hasher its output is fixed across builds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The embedder used by the server
+pub fn default_embedder() -> HashingEmbedder {
+    HashingEmbedder::default()
+}
+
+/// The text a review's vector is computed from
+pub fn review_text(review: &ReviewMetadata) -> String {
+    format!("{}\n{}", review.title, review.body)
+}
+
+/// The text of one field of a review, embedded at query time for searches restricted
+/// to it; reviews.index only holds vectors of the whole review
+pub fn field_text(review: &ReviewMetadata, field: SearchField) -> &str {
+    match field {
+        SearchField::Title => &review.title,
+        SearchField::Body => &review.body,
+    }
+}
+
+/// Whether a review's vector slot holds a vector for its current text
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingStatus {
+    /// Queued for the embedding worker; found by keyword scoring only until then
+    Pending,
+    Embedded,
+}
+
+impl EmbeddingStatus {
+    /// Status of a review given how many vector slots are stored
+    pub fn of(review: &ReviewMetadata, stored_slots: usize) -> Self {
+        if review.needs_reembedding || review.vector_index >= stored_slots {
+            EmbeddingStatus::Pending
+        } else {
+            EmbeddingStatus::Embedded
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_hashing_embedder_is_normalized_and_deterministic() {
+        let embedder = HashingEmbedder::default();
+        let vector = embedder.embed("Great battery life, great screen");
+        assert_eq!(vector.len(), EMBEDDING_DIM);
+        assert!((cosine(&vector, &vector) - 1.0).abs() < 1e-5);
+        assert_eq!(vector, embedder.embed("great BATTERY life great screen!"));
+        assert!(embedder.embed("").iter().all(|value| *value == 0.0));
+
+        // Shared words score higher than unrelated text
+        let related = embedder.embed("The battery life is great");
+        let unrelated = embedder.embed("Shipping took three weeks");
+        assert!(cosine(&vector, &related) > cosine(&vector, &unrelated));
+    }
+}
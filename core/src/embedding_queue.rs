@@ -0,0 +1,182 @@
+//! Two-tier queue of vector slots waiting for the embedding worker.
+//!
+//! Reviews a person just wrote or edited are `Interactive`; bulk uploads and anything
+//! the worker finds pending on its own (restarts, other processes, compaction) are
+//! `Backfill`. Interactive work goes first, but while backfill is waiting every batch
+//! reserves `1 / BACKFILL_SHARE` of its slots for it, so a stream of single reviews
+//! can slow a backfill down but never stall it.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use utoipa::ToSchema;
+
+/// Fraction of each batch kept for backfill while it has work
+pub const BACKFILL_SHARE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Priority {
+    Interactive,
+    Backfill,
+}
+
+/// Queue depths and embeddings done since startup, per tier
+#[derive(Clone, Debug, Default, PartialEq, Serialize, ToSchema)]
+pub struct EmbeddingQueueMetrics {
+    pub interactive_depth: usize,
+    pub backfill_depth: usize,
+    pub embedded_interactive: u64,
+    pub embedded_backfill: u64,
+}
+
+#[derive(Default)]
+pub struct EmbeddingQueue {
+    interactive: VecDeque<usize>,
+    backfill: VecDeque<usize>,
+    /// Tier each queued slot currently belongs to; deque entries that disagree are stale
+    queued: HashMap<usize, Priority>,
+    embedded_interactive: u64,
+    embedded_backfill: u64,
+}
+
+impl EmbeddingQueue {
+    /// Queue slots, promoting any already waiting as backfill to interactive
+    pub fn push(&mut self, priority: Priority, slots: impl IntoIterator<Item = usize>) {
+        for slot in slots {
+            match (self.queued.get(&slot), priority) {
+                (Some(Priority::Interactive), _) | (Some(Priority::Backfill), Priority::Backfill) => continue,
+                _ => {}
+            }
+            self.queued.insert(slot, priority);
+            match priority {
+                Priority::Interactive => self.interactive.push_back(slot),
+                Priority::Backfill => self.backfill.push_back(slot),
+            }
+        }
+    }
+
+    /// Make the queue match the slots that are actually pending: drop slots embedded
+    /// elsewhere and add unqueued ones as backfill
+    pub fn sync(&mut self, pending: &HashSet<usize>) {
+        self.queued.retain(|slot, _| pending.contains(slot));
+        let queued = &self.queued;
+        self.interactive.retain(|slot| queued.get(slot) == Some(&Priority::Interactive));
+        self.backfill.retain(|slot| queued.get(slot) == Some(&Priority::Backfill));
+        let mut unqueued: Vec<usize> = pending.iter().copied().filter(|slot| !self.queued.contains_key(slot)).collect();
+        unqueued.sort_unstable();
+        self.push(Priority::Backfill, unqueued);
+    }
+
+    /// Take up to `size` slots, interactive first but with backfill's reserved share
+    pub fn take_batch(&mut self, size: usize) -> Vec<(usize, Priority)> {
+        let reserved = if self.backfill_depth() > 0 { size.div_ceil(BACKFILL_SHARE) } else { 0 };
+        let mut batch = Vec::with_capacity(size);
+        while batch.len() < size.saturating_sub(reserved) {
+            match self.pop(Priority::Interactive) {
+                Some(slot) => batch.push((slot, Priority::Interactive)),
+                None => break,
+            }
+        }
+        // Backfill fills its share and anything interactive left over
+        while batch.len() < size {
+            let next = self
+                .pop(Priority::Backfill)
+                .map(|slot| (slot, Priority::Backfill))
+                .or_else(|| self.pop(Priority::Interactive).map(|slot| (slot, Priority::Interactive)));
+            match next {
+                Some(entry) => batch.push(entry),
+                None => break,
+            }
+        }
+        batch
+    }
+
+    /// Count embeddings done, for the metrics
+    pub fn record_embedded(&mut self, priority: Priority) {
+        match priority {
+            Priority::Interactive => self.embedded_interactive += 1,
+            Priority::Backfill => self.embedded_backfill += 1,
+        }
+    }
+
+    pub fn metrics(&self) -> EmbeddingQueueMetrics {
+        EmbeddingQueueMetrics {
+            interactive_depth: self.queued.values().filter(|tier| **tier == Priority::Interactive).count(),
+            backfill_depth: self.backfill_depth(),
+            embedded_interactive: self.embedded_interactive,
+            embedded_backfill: self.embedded_backfill,
+        }
+    }
+
+    fn backfill_depth(&self) -> usize {
+        self.queued.values().filter(|tier| **tier == Priority::Backfill).count()
+    }
+
+    /// Next live slot of a tier, skipping entries that were promoted or dropped
+    fn pop(&mut self, priority: Priority) -> Option<usize> {
+        let deque = match priority {
+            Priority::Interactive => &mut self.interactive,
+            Priority::Backfill => &mut self.backfill,
+        };
+        while let Some(slot) = deque.pop_front() {
+            if self.queued.get(&slot) == Some(&priority) {
+                self.queued.remove(&slot);
+                return Some(slot);
+            }
+        }
+        None
+    }
+}
+
+fn queues() -> &'static Mutex<HashMap<PathBuf, EmbeddingQueue>> {
+    static QUEUES: OnceLock<Mutex<HashMap<PathBuf, EmbeddingQueue>>> = OnceLock::new();
+    QUEUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Run `f` on the queue for one review file
+pub fn with_queue<T>(reviews_jsonl: &Path, f: impl FnOnce(&mut EmbeddingQueue) -> T) -> T {
+    let mut queues = queues().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(queues.entry(reviews_jsonl.to_path_buf()).or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interactive_goes_first_but_backfill_keeps_its_share() {
+        let mut queue = EmbeddingQueue::default();
+        queue.push(Priority::Backfill, 0..10);
+        queue.push(Priority::Interactive, 10..20);
+
+        let batch = queue.take_batch(8);
+        assert_eq!(batch.iter().filter(|(_, tier)| *tier == Priority::Interactive).count(), 6);
+        assert_eq!(batch.iter().filter(|(_, tier)| *tier == Priority::Backfill).count(), 2);
+        assert_eq!(batch[0], (10, Priority::Interactive));
+
+        // Once interactive runs dry, backfill gets whole batches
+        let batch = queue.take_batch(8);
+        assert_eq!(batch.len(), 8);
+        assert_eq!(&batch[..4], &[(16, Priority::Interactive), (17, Priority::Interactive), (18, Priority::Interactive), (19, Priority::Interactive)]);
+        assert_eq!(queue.metrics().backfill_depth, 4);
+    }
+
+    #[test]
+    fn test_push_promotes_and_sync_drops_embedded_slots() {
+        let mut queue = EmbeddingQueue::default();
+        queue.push(Priority::Backfill, [1, 2, 3]);
+        queue.push(Priority::Interactive, [3]);
+        queue.push(Priority::Backfill, [3]);
+        let metrics = queue.metrics();
+        assert_eq!((metrics.interactive_depth, metrics.backfill_depth), (1, 2));
+
+        // Slot 1 was embedded elsewhere; slot 7 turned up pending
+        queue.sync(&HashSet::from([2, 3, 7]));
+        assert_eq!(
+            queue.take_batch(4),
+            vec![(3, Priority::Interactive), (2, Priority::Backfill), (7, Priority::Backfill)]
+        );
+        assert_eq!(queue.metrics(), EmbeddingQueueMetrics::default());
+    }
+}
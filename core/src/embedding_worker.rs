@@ -0,0 +1,305 @@
+//! Background embedding of reviews, so writes never wait on the embedder.
+//!
+//! A review is pending while its vector slot is past the end of reviews.index or it is
+//! flagged `needs_reembedding`. Handlers queue the slots they write (see
+//! `embedding_queue` for the priority tiers) and each batch also picks up any pending
+//! slot nobody queued. The worker snapshots under the shared lock, embeds without any
+//! lock, then takes the exclusive lock to write vectors and clear flags, skipping
+//! reviews whose text changed in between; those stay pending for a later batch. Every
+//! process sharing the data directory runs a worker. Embeddings are deterministic, so
+//! two workers racing on the same slots write the same vectors. Each batch goes through
+//! the default collection, then every other collection in turn.
+
+use crate::collections;
+use crate::embedding::{self, Embedder, EmbeddingStatus};
+use crate::embedding_queue::{self, EmbeddingQueueMetrics, Priority};
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Reviews embedded per batch, between which writers can take the lock
+pub const EMBEDDING_BATCH_SIZE: usize = 64;
+
+/// How often an idle worker looks for reviews written by other processes
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Back-off after a failed batch
+const ERROR_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+fn wake_signal() -> &'static Notify {
+    static WAKE: OnceLock<Notify> = OnceLock::new();
+    WAKE.get_or_init(Notify::new)
+}
+
+/// Tell the worker new reviews are pending, so it doesn't wait for its next poll
+pub fn notify() {
+    wake_signal().notify_one();
+}
+
+/// Run the worker for `data_dir` and its collections until the process exits
+pub fn spawn(data_dir: PathBuf, embedder: Arc<dyn Embedder>) {
+    tokio::spawn(async move {
+        loop {
+            let data_dir = data_dir.clone();
+            let embedder = embedder.clone();
+            let batch = tokio::task::spawn_blocking(move || {
+                let root = DataPaths::new(data_dir);
+                let mut embedded = 0;
+                for name in collections::names(&root)? {
+                    let data_paths = collections::data_paths(&root, &name)?;
+                    embedded += embed_pending(&data_paths, embedder.as_ref(), EMBEDDING_BATCH_SIZE)?;
+                }
+                Ok::<usize, AppError>(embedded)
+            })
+            .await;
+
+            match batch {
+                Ok(Ok(embedded)) if embedded > 0 => continue,
+                Ok(Ok(_)) => {
+                    let _ = tokio::time::timeout(IDLE_POLL_INTERVAL, wake_signal().notified()).await;
+                }
+                Ok(Err(e)) => {
+                    tracing::error!("Embedding batch failed: {}", e);
+                    tokio::time::sleep(ERROR_RETRY_INTERVAL).await;
+                }
+                Err(e) => {
+                    tracing::error!("Embedding worker panicked: {}", e);
+                    tokio::time::sleep(ERROR_RETRY_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
+
+/// Queue slots for embedding and wake the worker
+pub fn enqueue(data_paths: &DataPaths, priority: Priority, slots: impl IntoIterator<Item = usize>) {
+    embedding_queue::with_queue(&data_paths.reviews_jsonl, |queue| queue.push(priority, slots));
+    notify();
+}
+
+/// Queue depths and totals for the data directory's embedding queue
+pub fn queue_metrics(data_paths: &DataPaths) -> EmbeddingQueueMetrics {
+    embedding_queue::with_queue(&data_paths.reviews_jsonl, |queue| queue.metrics())
+}
+
+/// Embed up to `batch_size` pending reviews taken from the queue. Returns how many
+/// vectors were written.
+pub fn embed_pending(data_paths: &DataPaths, embedder: &dyn Embedder, batch_size: usize) -> Result<usize, AppError> {
+    if !data_paths.reviews_jsonl.exists() {
+        return Ok(0);
+    }
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let store = VectorStore::new(&data_paths.reviews_index, embedder.dimension());
+
+    let (reviews, stored) = {
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "embedding_snapshot")?;
+        (storage.read_all_reviews()?, store.len()?)
+    };
+    let pending: HashSet<usize> = reviews
+        .iter()
+        .filter(|review| EmbeddingStatus::of(review, stored) == EmbeddingStatus::Pending)
+        .map(|review| review.vector_index)
+        .collect();
+    let batch = embedding_queue::with_queue(&data_paths.reviews_jsonl, |queue| {
+        queue.sync(&pending);
+        queue.take_batch(batch_size)
+    });
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    // Tombstoned reviews are never searched; a zero vector just fills their slot
+    let work: Vec<(&ReviewMetadata, Priority, Vec<f32>)> = batch
+        .iter()
+        .filter_map(|&(slot, priority)| {
+            let review = reviews.get(slot).filter(|review| review.vector_index == slot)?;
+            Some((review, priority))
+        })
+        .map(|(review, priority)| {
+            let vector = match review.is_deleted() {
+                true => vec![0.0; embedder.dimension()],
+                false => embedder.embed(&embedding::review_text(review)),
+            };
+            (review, priority, vector)
+        })
+        .collect();
+
+    let _lock = FileLock::acquire(&data_paths.lock_file, "embedding_write")?;
+    let stored_now = store.len()?;
+    if stored_now < stored {
+        // The index was cut back (compaction), so these slots may mean other reviews now
+        embedding_queue::with_queue(&data_paths.reviews_jsonl, |queue| {
+            for &(slot, priority) in &batch {
+                queue.push(priority, [slot]);
+            }
+        });
+        return Ok(0);
+    }
+
+    // Skip reviews whose text changed since the snapshot; they are queued again
+    let slots: Vec<usize> = work.iter().map(|(review, _, _)| review.vector_index).collect();
+    let current = storage.get_reviews_by_indices(&slots)?;
+    let unchanged: Vec<&(&ReviewMetadata, Priority, Vec<f32>)> = work
+        .iter()
+        .zip(current)
+        .filter(|((review, _, _), current)| {
+            current.as_ref().is_some_and(|current| {
+                current.id == review.id
+                    && current.title == review.title
+                    && current.body == review.body
+                    && current.is_deleted() == review.is_deleted()
+            })
+        })
+        .map(|(work, _)| work)
+        .collect();
+    if unchanged.is_empty() {
+        return Ok(0);
+    }
+
+    // Interactive slots can land past the end of the index. The slots skipped on the way
+    // are zero-filled and flagged, so they read as pending rather than embedded.
+    let written: HashSet<usize> = unchanged.iter().map(|(review, _, _)| review.vector_index).collect();
+    let new_len = written.iter().map(|slot| slot + 1).max().unwrap_or(0).max(stored_now);
+    let gap: HashSet<usize> = (stored_now..new_len).filter(|slot| !written.contains(slot)).collect();
+    store.extend_to(new_len)?;
+    for (review, _, vector) in &unchanged {
+        store.write_slots(review.vector_index, std::slice::from_ref(vector))?;
+    }
+
+    let cleared: HashSet<&str> = unchanged
+        .iter()
+        .filter(|(review, _, _)| review.needs_reembedding)
+        .map(|(review, _, _)| review.id.as_str())
+        .collect();
+    if !cleared.is_empty() || !gap.is_empty() {
+        storage.rewrite_reviews(|mut review| {
+            if cleared.contains(review.id.as_str()) {
+                review.needs_reembedding = false;
+            } else if gap.contains(&review.vector_index) && !review.is_deleted() {
+                review.needs_reembedding = true;
+            }
+            Some(review)
+        })?;
+    }
+
+    embedding_queue::with_queue(&data_paths.reviews_jsonl, |queue| {
+        for (_, priority, _) in &unchanged {
+            queue.record_embedded(*priority);
+        }
+    });
+    tracing::debug!("Embedded {} of {} queued reviews", unchanged.len(), batch.len());
+    Ok(unchanged.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::HashingEmbedder;
+    use tempfile::TempDir;
+
+    fn store_reviews(storage: &JsonlStorage, titles: &[&str]) -> Vec<ReviewMetadata> {
+        let reviews: Vec<ReviewMetadata> = titles
+            .iter()
+            .enumerate()
+            .map(|(index, title)| {
+                ReviewData {
+                    title: title.to_string(),
+                    body: "A review body long enough to store.".to_string(),
+                    product_id: "fan".to_string(),
+                    rating: 3,
+                    reviewer_id: None,
+                    source: None,
+                    license: None,
+                    attribution: None,
+                }
+                .to_metadata(index)
+                .unwrap()
+            })
+            .collect();
+        storage.append_reviews(&reviews).unwrap();
+        reviews
+    }
+
+    #[test]
+    fn test_embed_pending_fills_tail_and_reembeds_updates() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+        let embedder = HashingEmbedder::default();
+        let store = VectorStore::new(&data_paths.reviews_index, embedder.dimension());
+        let reviews = store_reviews(&storage, &["Loud fan", "Quiet fan", "Broken fan"]);
+
+        // Batches advance through the tail in slot order
+        assert_eq!(embed_pending(&data_paths, &embedder, 2).unwrap(), 2);
+        assert_eq!(store.len().unwrap(), 2);
+        assert_eq!(embed_pending(&data_paths, &embedder, 2).unwrap(), 1);
+        assert_eq!(embed_pending(&data_paths, &embedder, 2).unwrap(), 0);
+        assert_eq!(
+            store.read(1).unwrap().unwrap(),
+            embedder.embed(&embedding::review_text(&reviews[1]))
+        );
+
+        // An updated review is re-embedded in place and its flag cleared
+        let update = ReviewData {
+            title: "Quiet fan, rattles".to_string(),
+            body: "It developed a rattle after a month.".to_string(),
+            product_id: "fan".to_string(),
+            rating: 2,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        let updated = storage.update_review(&reviews[1].id, &update).unwrap().unwrap();
+        assert_eq!(EmbeddingStatus::of(&updated, 3), EmbeddingStatus::Pending);
+        assert_eq!(embed_pending(&data_paths, &embedder, 2).unwrap(), 1);
+
+        let updated = storage.get_review_by_id(&reviews[1].id).unwrap().unwrap();
+        assert_eq!(EmbeddingStatus::of(&updated, store.len().unwrap()), EmbeddingStatus::Embedded);
+        assert_eq!(store.read(1).unwrap().unwrap(), embedder.embed(&embedding::review_text(&updated)));
+    }
+
+    #[test]
+    fn test_interactive_review_is_embedded_ahead_of_backfill() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+        let embedder = HashingEmbedder::default();
+        let store = VectorStore::new(&data_paths.reviews_index, embedder.dimension());
+
+        let titles = ["Fan one", "Fan two", "Fan three", "Fan four", "Fan five", "Fan six"];
+        let reviews = store_reviews(&storage, &titles);
+        enqueue(&data_paths, Priority::Backfill, 0..5);
+        enqueue(&data_paths, Priority::Interactive, [5]);
+
+        // A batch of four takes the interactive slot plus backfill's share
+        assert_eq!(embed_pending(&data_paths, &embedder, 4).unwrap(), 4);
+        assert_eq!(store.len().unwrap(), 6);
+        assert_eq!(store.read(5).unwrap().unwrap(), embedder.embed(&embedding::review_text(&reviews[5])));
+
+        // The slots skipped to reach it read as pending, not as embedded zero vectors
+        let stored = store.len().unwrap();
+        let statuses: Vec<EmbeddingStatus> = storage
+            .read_all_reviews()
+            .unwrap()
+            .iter()
+            .map(|review| EmbeddingStatus::of(review, stored))
+            .collect();
+        assert_eq!(statuses.iter().filter(|status| **status == EmbeddingStatus::Pending).count(), 2);
+        assert_eq!(statuses[5], EmbeddingStatus::Embedded);
+
+        let metrics = queue_metrics(&data_paths);
+        assert_eq!((metrics.embedded_interactive, metrics.embedded_backfill), (1, 3));
+        assert_eq!(metrics.backfill_depth, 2);
+
+        assert_eq!(embed_pending(&data_paths, &embedder, 4).unwrap(), 2);
+        assert_eq!(embed_pending(&data_paths, &embedder, 4).unwrap(), 0);
+        assert_eq!(store.read(4).unwrap().unwrap(), embedder.embed(&embedding::review_text(&reviews[4])));
+    }
+}
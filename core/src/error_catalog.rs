@@ -0,0 +1,291 @@
+//! Stable, machine-readable error codes carried by every `ErrorResponse`.
+//!
+//! Messages are for people and may be reworded; codes are part of the API contract.
+//! Never rename or reuse a code: add a new variant instead.
+
+use crate::models::{AppError, ValidationError};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ErrorCode {
+    // Review fields
+    #[serde(rename = "E_VALIDATION_TITLE_MISSING")]
+    TitleMissing,
+    #[serde(rename = "E_VALIDATION_TITLE_SHORT")]
+    TitleShort,
+    #[serde(rename = "E_VALIDATION_TITLE_LONG")]
+    TitleLong,
+    #[serde(rename = "E_VALIDATION_BODY_MISSING")]
+    BodyMissing,
+    #[serde(rename = "E_VALIDATION_BODY_SHORT")]
+    BodyShort,
+    #[serde(rename = "E_VALIDATION_BODY_LONG")]
+    BodyLong,
+    #[serde(rename = "E_VALIDATION_PRODUCT_ID_MISSING")]
+    ProductIdMissing,
+    #[serde(rename = "E_VALIDATION_PRODUCT_ID_LONG")]
+    ProductIdLong,
+    #[serde(rename = "E_VALIDATION_RATING_INVALID")]
+    RatingInvalid,
+    #[serde(rename = "E_VALIDATION_REVIEWER_ID_LONG")]
+    ReviewerIdLong,
+    #[serde(rename = "E_VALIDATION_SOURCE_LONG")]
+    SourceLong,
+    #[serde(rename = "E_VALIDATION_LICENSE_INVALID")]
+    LicenseInvalid,
+    #[serde(rename = "E_VALIDATION_ATTRIBUTION_MISSING")]
+    AttributionMissing,
+    #[serde(rename = "E_VALIDATION_ATTRIBUTION_LONG")]
+    AttributionLong,
+
+    // Search and listing parameters
+    #[serde(rename = "E_VALIDATION_QUERY_MISSING")]
+    QueryMissing,
+    #[serde(rename = "E_VALIDATION_QUERY_LONG")]
+    QueryLong,
+    #[serde(rename = "E_VALIDATION_LIMIT_INVALID")]
+    LimitInvalid,
+    #[serde(rename = "E_VALIDATION_FILTERS_INVALID")]
+    FiltersInvalid,
+    #[serde(rename = "E_VALIDATION_PAGINATION_INVALID")]
+    PaginationInvalid,
+    #[serde(rename = "E_VALIDATION_SORT_INVALID")]
+    SortInvalid,
+    #[serde(rename = "E_VALIDATION_FORMAT_INVALID")]
+    FormatInvalid,
+    #[serde(rename = "E_VALIDATION_API_VERSION_INVALID")]
+    ApiVersionInvalid,
+    #[serde(rename = "E_VALIDATION_BATCH_INVALID")]
+    BatchInvalid,
+    #[serde(rename = "E_VALIDATION_IDEMPOTENCY_KEY_INVALID")]
+    IdempotencyKeyInvalid,
+    #[serde(rename = "E_VALIDATION_SESSION_ID_INVALID")]
+    SessionIdInvalid,
+    #[serde(rename = "E_VALIDATION_SANDBOX_ID_INVALID")]
+    SandboxIdInvalid,
+    #[serde(rename = "E_VALIDATION_COLLECTION_INVALID")]
+    CollectionInvalid,
+    #[serde(rename = "E_VALIDATION_FIELDS_INVALID")]
+    FieldsInvalid,
+
+    // Bulk uploads
+    #[serde(rename = "E_VALIDATION_BULK_DATA_INVALID")]
+    BulkDataInvalid,
+    #[serde(rename = "E_VALIDATION_FILE_NAME_LONG")]
+    FileNameLong,
+
+    // Webhooks
+    #[serde(rename = "E_VALIDATION_WEBHOOK_INVALID")]
+    WebhookInvalid,
+
+    // Curation rules
+    #[serde(rename = "E_VALIDATION_CURATION_RULE_INVALID")]
+    CurationRuleInvalid,
+
+    // Blocklist entries
+    #[serde(rename = "E_VALIDATION_BLOCKLIST_ENTRY_INVALID")]
+    BlocklistEntryInvalid,
+
+    // Synonym file
+    #[serde(rename = "E_VALIDATION_SYNONYMS_INVALID")]
+    SynonymsInvalid,
+
+    // Validation failures without a more specific code
+    #[serde(rename = "E_VALIDATION_MISSING_FIELD")]
+    ValidationMissingField,
+    #[serde(rename = "E_VALIDATION_FAILED")]
+    ValidationFailed,
+
+    // Resources
+    #[serde(rename = "E_REVIEW_NOT_FOUND")]
+    ReviewNotFound,
+    #[serde(rename = "E_JOB_NOT_FOUND")]
+    JobNotFound,
+    #[serde(rename = "E_PRODUCT_NOT_FOUND")]
+    ProductNotFound,
+    #[serde(rename = "E_WEBHOOK_NOT_FOUND")]
+    WebhookNotFound,
+    #[serde(rename = "E_CURATION_RULE_NOT_FOUND")]
+    CurationRuleNotFound,
+    #[serde(rename = "E_BLOCKLIST_ENTRY_NOT_FOUND")]
+    BlocklistEntryNotFound,
+    #[serde(rename = "E_SANDBOX_NOT_FOUND")]
+    SandboxNotFound,
+    #[serde(rename = "E_COLLECTION_NOT_FOUND")]
+    CollectionNotFound,
+    #[serde(rename = "E_NOT_FOUND")]
+    NotFound,
+    #[serde(rename = "E_CONFLICT")]
+    Conflict,
+    #[serde(rename = "E_BLOCKLISTED")]
+    Blocklisted,
+    #[serde(rename = "E_DUPLICATE_REVIEW")]
+    DuplicateReview,
+
+    // Service conditions
+    #[serde(rename = "E_RATE_LIMITED")]
+    RateLimited,
+    #[serde(rename = "E_LOCK_UNAVAILABLE")]
+    LockUnavailable,
+    #[serde(rename = "E_LOCK_TIMEOUT")]
+    LockTimeout,
+    #[serde(rename = "E_MALFORMED_JSON")]
+    MalformedJson,
+    #[serde(rename = "E_MALFORMED_MSGPACK")]
+    MalformedMsgpack,
+    #[serde(rename = "E_UNSUPPORTED_MEDIA_TYPE")]
+    UnsupportedMediaType,
+    #[serde(rename = "E_PAYLOAD_TOO_LARGE")]
+    PayloadTooLarge,
+    #[serde(rename = "E_REQUEST_TIMEOUT")]
+    RequestTimeout,
+    #[serde(rename = "E_SHARDS_UNAVAILABLE")]
+    ShardsUnavailable,
+    #[serde(rename = "E_CORPUS_FULL")]
+    CorpusFull,
+
+    // Internal failures
+    #[serde(rename = "E_STORAGE_IO")]
+    StorageIo,
+    #[serde(rename = "E_SERIALIZATION")]
+    Serialization,
+    #[serde(rename = "E_EMBEDDING")]
+    Embedding,
+    #[serde(rename = "E_VECTOR_SEARCH")]
+    VectorSearch,
+    #[serde(rename = "E_INTERNAL")]
+    Internal,
+}
+
+impl ErrorCode {
+    /// The code for an application error
+    pub fn for_error(error: &AppError) -> Self {
+        match error {
+            AppError::Validation(validation_error) => Self::for_validation(validation_error),
+            AppError::FileOperation(_) => ErrorCode::StorageIo,
+            AppError::Serialization(_) => ErrorCode::Serialization,
+            AppError::Uuid(_) => ErrorCode::Internal,
+            AppError::Embedding { .. } => ErrorCode::Embedding,
+            AppError::VectorSearch { .. } => ErrorCode::VectorSearch,
+            AppError::NotFound { resource, .. } => match resource.as_str() {
+                "Review" => ErrorCode::ReviewNotFound,
+                "Job" => ErrorCode::JobNotFound,
+                "Product" => ErrorCode::ProductNotFound,
+                "Webhook" => ErrorCode::WebhookNotFound,
+                "Curation rule" => ErrorCode::CurationRuleNotFound,
+                "Blocklist entry" => ErrorCode::BlocklistEntryNotFound,
+                "Sandbox" => ErrorCode::SandboxNotFound,
+                "Collection" => ErrorCode::CollectionNotFound,
+                _ => ErrorCode::NotFound,
+            },
+            AppError::Conflict { .. } => ErrorCode::Conflict,
+            AppError::Blocked { .. } => ErrorCode::Blocklisted,
+            AppError::Duplicate { .. } => ErrorCode::DuplicateReview,
+            AppError::Concurrency { .. } => ErrorCode::LockUnavailable,
+            AppError::LockTimeout { .. } => ErrorCode::LockTimeout,
+            AppError::RateLimited { .. } => ErrorCode::RateLimited,
+            AppError::MalformedJson { .. } => ErrorCode::MalformedJson,
+            AppError::MalformedMsgpack { .. } => ErrorCode::MalformedMsgpack,
+            AppError::UnsupportedMediaType { .. } => ErrorCode::UnsupportedMediaType,
+            AppError::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
+            AppError::RequestTimeout { .. } => ErrorCode::RequestTimeout,
+            AppError::ShardsUnavailable { .. } => ErrorCode::ShardsUnavailable,
+            AppError::CorpusFull { .. } => ErrorCode::CorpusFull,
+            AppError::Internal { .. } => ErrorCode::Internal,
+        }
+    }
+
+    /// The code for a validation error, specific to the offending field where one is defined
+    pub fn for_validation(error: &ValidationError) -> Self {
+        match error {
+            ValidationError::MissingField { field } => match field.as_str() {
+                "title" => ErrorCode::TitleMissing,
+                "body" => ErrorCode::BodyMissing,
+                "product_id" => ErrorCode::ProductIdMissing,
+                "query" | "prefix" | "question" => ErrorCode::QueryMissing,
+                "attribution" => ErrorCode::AttributionMissing,
+                _ => ErrorCode::ValidationMissingField,
+            },
+            ValidationError::TooShort { field, .. } => match field.as_str() {
+                "title" => ErrorCode::TitleShort,
+                "body" => ErrorCode::BodyShort,
+                _ => ErrorCode::ValidationFailed,
+            },
+            ValidationError::TooLong { field, .. } => match field.as_str() {
+                "title" => ErrorCode::TitleLong,
+                "body" => ErrorCode::BodyLong,
+                "product_id" => ErrorCode::ProductIdLong,
+                "reviewer_id" => ErrorCode::ReviewerIdLong,
+                "source" => ErrorCode::SourceLong,
+                "attribution" => ErrorCode::AttributionLong,
+                "query" | "prefix" | "question" => ErrorCode::QueryLong,
+                "file_name" => ErrorCode::FileNameLong,
+                "url" => ErrorCode::WebhookInvalid,
+                "note" => ErrorCode::CurationRuleInvalid,
+                "value" | "reason" => ErrorCode::BlocklistEntryInvalid,
+                "synonyms" => ErrorCode::SynonymsInvalid,
+                _ => ErrorCode::ValidationFailed,
+            },
+            ValidationError::InvalidRating => ErrorCode::RatingInvalid,
+            ValidationError::InvalidValue { field, .. } => match field.as_str() {
+                "limit" | "sentences_per_review" => ErrorCode::LimitInvalid,
+                "filters" | "min_rating" | "max_rating" | "date_from" | "date_to" | "sentiment" | "language"
+                | "min_score" => ErrorCode::FiltersInvalid,
+                "page" | "page_size" | "offset" | "cursor" => ErrorCode::PaginationInvalid,
+                "sort" | "order" => ErrorCode::SortInvalid,
+                "format" => ErrorCode::FormatInvalid,
+                "license" => ErrorCode::LicenseInvalid,
+                "accept" => ErrorCode::ApiVersionInvalid,
+                "ids" | "indices" | "searches" => ErrorCode::BatchInvalid,
+                "idempotency_key" => ErrorCode::IdempotencyKeyInvalid,
+                "session_id" => ErrorCode::SessionIdInvalid,
+                "sandbox_id" => ErrorCode::SandboxIdInvalid,
+                "collection" | "collections" => ErrorCode::CollectionInvalid,
+                "fields" | "search_fields" => ErrorCode::FieldsInvalid,
+                "url" | "events" | "secret" => ErrorCode::WebhookInvalid,
+                "scope" | "action" | "position" | "target" | "weight" => ErrorCode::CurationRuleInvalid,
+                "value" => ErrorCode::BlocklistEntryInvalid,
+                "synonyms" => ErrorCode::SynonymsInvalid,
+                "reviews" | "bulk_data" => ErrorCode::BulkDataInvalid,
+                field if field.starts_with("line_") => ErrorCode::BulkDataInvalid,
+                _ => ErrorCode::ValidationFailed,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_field_specific() {
+        let too_short = ValidationError::TooShort {
+            field: "title".to_string(),
+            min_length: 3,
+        };
+        assert_eq!(ErrorCode::for_validation(&too_short), ErrorCode::TitleShort);
+        assert_eq!(ErrorCode::for_validation(&ValidationError::InvalidRating), ErrorCode::RatingInvalid);
+
+        let unknown_field = ValidationError::InvalidValue {
+            field: "colour".to_string(),
+            reason: "unsupported".to_string(),
+        };
+        assert_eq!(ErrorCode::for_validation(&unknown_field), ErrorCode::ValidationFailed);
+
+        let missing_job = AppError::NotFound {
+            resource: "Job".to_string(),
+            id: "job_1".to_string(),
+        };
+        assert_eq!(ErrorCode::for_error(&missing_job), ErrorCode::JobNotFound);
+    }
+
+    #[test]
+    fn test_codes_serialize_to_stable_strings() {
+        assert_eq!(serde_json::to_value(ErrorCode::TitleShort).unwrap(), "E_VALIDATION_TITLE_SHORT");
+        assert_eq!(serde_json::to_value(ErrorCode::RateLimited).unwrap(), "E_RATE_LIMITED");
+        assert_eq!(serde_json::to_value(ErrorCode::LockUnavailable).unwrap(), "E_LOCK_UNAVAILABLE");
+        assert_eq!(serde_json::to_value(ErrorCode::LockTimeout).unwrap(), "E_LOCK_TIMEOUT");
+    }
+}
@@ -0,0 +1,239 @@
+//! Query expansion: short queries gain the vocabulary terms nearest to them in
+//! embedding space, so a search for "battery" also finds reviews that only say
+//! "charge" when reviewers use the two words together.
+//!
+//! A term's embedding is the normalized sum of the embeddings of the live reviews
+//! containing it, so terms from similar reviews sit close together even though the
+//! hashing embedder knows nothing about meaning. Term embeddings are computed once per
+//! version of the review set and cached; the query is embedded with the same embedder
+//! and compared to every term by cosine similarity.
+//!
+//! Expansion is off unless `QUERY_EXPANSION` is true, 1 or on. Expanded terms score
+//! less than the query's own words, so direct matches still rank first.
+
+use crate::embedding::{self, Embedder};
+use crate::models::ReviewMetadata;
+use crate::tokenizer;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Terms added to a query when `QUERY_EXPANSION_TERMS` is not set
+pub const DEFAULT_EXPANSION_TERMS: usize = 3;
+
+/// Queries with more terms than this are specific enough already
+pub const SPARSE_QUERY_TERMS: usize = 3;
+
+/// Least cosine similarity between the query and an added term
+const MIN_TERM_SIMILARITY: f32 = 0.3;
+
+/// Terms in fewer reviews than this are too rare to generalize from
+const MIN_DOCUMENT_FREQUENCY: usize = 2;
+
+/// Most terms kept, by document frequency, bounding memory at 1 KiB per term
+const MAX_VOCABULARY: usize = 20_000;
+
+/// Once there are this many reviews, terms in more than half of them are dropped as
+/// too common to say anything ("the", "product")
+const COMMON_TERM_MIN_REVIEWS: usize = 10;
+
+/// Expansion settings, read from the environment once
+#[derive(Clone, Copy, Debug)]
+pub struct ExpansionConfig {
+    pub enabled: bool,
+    pub terms: usize,
+}
+
+pub fn config() -> ExpansionConfig {
+    static CONFIG: OnceLock<ExpansionConfig> = OnceLock::new();
+    *CONFIG.get_or_init(|| ExpansionConfig {
+        enabled: std::env::var("QUERY_EXPANSION")
+            .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "true" | "1" | "on"))
+            .unwrap_or(false),
+        terms: std::env::var("QUERY_EXPANSION_TERMS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_EXPANSION_TERMS),
+    })
+}
+
+/// Embeddings of the vocabulary of a review set
+pub struct TermVectors {
+    terms: Vec<String>,
+    vectors: Vec<Vec<f32>>,
+}
+
+impl TermVectors {
+    pub fn build(reviews: &[ReviewMetadata], embedder: &dyn Embedder) -> Self {
+        let mut sums: HashMap<String, (usize, Vec<f32>)> = HashMap::new();
+        for review in reviews {
+            let text = embedding::review_text(review);
+            let vector = embedder.embed(&text);
+            let terms: HashSet<String> = tokenizer::analyze(&text).into_iter().filter(|term| is_candidate(term)).collect();
+            for term in terms {
+                let (count, sum) = sums.entry(term).or_insert_with(|| (0, vec![0.0; vector.len()]));
+                *count += 1;
+                sum.iter_mut().zip(&vector).for_each(|(total, value)| *total += value);
+            }
+        }
+
+        let max_frequency = match reviews.len() >= COMMON_TERM_MIN_REVIEWS {
+            true => reviews.len() / 2,
+            false => usize::MAX,
+        };
+        let mut kept: Vec<(String, usize, Vec<f32>)> = sums
+            .into_iter()
+            .filter(|(_, (count, _))| (MIN_DOCUMENT_FREQUENCY..=max_frequency).contains(count))
+            .map(|(term, (count, sum))| (term, count, sum))
+            .collect();
+        kept.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        kept.truncate(MAX_VOCABULARY);
+
+        let (terms, vectors) = kept.into_iter().map(|(term, _, sum)| (term, normalized(sum))).unzip();
+        TermVectors { terms, vectors }
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Up to `n` terms most similar to `query_vector`, best first, leaving out terms that
+    /// keyword matching would find anyway: the query's own terms and words containing
+    /// them or contained in them
+    pub fn nearest(&self, query_vector: &[f32], query_terms: &[String], n: usize) -> Vec<String> {
+        let mut scored: Vec<(f32, &String)> = self
+            .terms
+            .iter()
+            .zip(&self.vectors)
+            .filter(|(term, _)| {
+                !query_terms.iter().any(|query_term| term.contains(query_term.as_str()) || query_term.contains(term.as_str()))
+            })
+            .map(|(term, vector)| (cosine(query_vector, vector), term))
+            .filter(|(similarity, _)| *similarity >= MIN_TERM_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.1.cmp(b.1)));
+        scored.into_iter().take(n).map(|(_, term)| term.clone()).collect()
+    }
+}
+
+/// Whether a term can be added to queries: numbers, stopwords and one- or two-letter
+/// words carry too little meaning. CJK bigrams are two characters and always qualify.
+fn is_candidate(term: &str) -> bool {
+    let chars = term.chars().count();
+    !term.chars().all(|c| c.is_ascii_digit())
+        && !tokenizer::stopwords().contains(term)
+        && (chars >= 3 || tokenizer::detect_language(term) == tokenizer::Language::Cjk)
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+    vector
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Identifies a review set by the ids and edit times of its reviews
+pub fn version(reviews: &[ReviewMetadata]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    reviews.len().hash(&mut hasher);
+    for review in reviews {
+        review.id.hash(&mut hasher);
+        review.updated_at.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Term vectors for `reviews`, rebuilt only when the review set changed
+fn term_vectors(reviews: &[ReviewMetadata]) -> Arc<TermVectors> {
+    static CACHE: Mutex<Option<(u64, Arc<TermVectors>)>> = Mutex::new(None);
+
+    let version = version(reviews);
+    let mut cached = CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match cached.as_ref() {
+        Some((cached_version, terms)) if *cached_version == version => terms.clone(),
+        _ => {
+            let terms = Arc::new(TermVectors::build(reviews, &embedding::default_embedder()));
+            tracing::info!("Built query expansion vocabulary of {} terms", terms.len());
+            *cached = Some((version, terms.clone()));
+            terms
+        }
+    }
+}
+
+/// Terms to add to `query`, searched over `reviews` (all live reviews, before filters).
+/// Empty when expansion is off or the query is not sparse.
+pub fn expand(query: &str, reviews: &[ReviewMetadata]) -> Vec<String> {
+    let config = config();
+    if !config.enabled || config.terms == 0 {
+        return Vec::new();
+    }
+    expand_with(query, &term_vectors(reviews), config.terms)
+}
+
+pub fn expand_with(query: &str, term_vectors: &TermVectors, n: usize) -> Vec<String> {
+    let query_terms = tokenizer::analyze(query);
+    if query_terms.is_empty() || query_terms.len() > SPARSE_QUERY_TERMS {
+        return Vec::new();
+    }
+    let query_vector = embedding::default_embedder().embed(query);
+    term_vectors.nearest(&query_vector, &query_terms, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn review(id: &str, body: &str) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Phone".to_string(),
+            body: body.to_string(),
+            product_id: "phone".to_string(),
+            rating: 3,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_sparse_queries_gain_co_occurring_terms() {
+        let reviews = vec![
+            review("1", "Battery needs a charge twice a day"),
+            review("2", "Battery holds its charge well"),
+            review("3", "Slow charge but the battery is huge"),
+            review("4", "Screen is bright and sharp"),
+            review("5", "Sharp screen, bright colours"),
+        ];
+        let term_vectors = TermVectors::build(&reviews, &embedding::default_embedder());
+
+        let expanded = expand_with("battery", &term_vectors, 2);
+        assert_eq!(expanded[0], "charge");
+        assert!(!expanded.iter().any(|term| term == "battery" || term == "screen"));
+
+        // Specific queries are left alone
+        assert!(expand_with("battery screen bright sharp", &term_vectors, 2).is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+//! Score explanations: how a search arrived at each result's `similarity_score`.
+//!
+//! A search with `explain: true` carries an `explanation` on every result, built while
+//! the result is ranked rather than recomputed afterwards, so it always adds up to the
+//! score served. It names how the review matched (vector similarity, or keyword
+//! matching for reviews waiting to be embedded) with the parts of that match score,
+//! then every step that moved the score after it: the helpfulness and recency blends,
+//! session personalization, curation boosts and pins.
+//!
+//! A blend replaces the score with `score × (1 − weight) + value × weight`; a boost adds
+//! its weight and clamps to 0-1. Results merged from shards keep their shard's
+//! explanation.
+
+use crate::models::SearchField;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How a result came to be ranked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// Cosine similarity of the review's vector to the query's
+    Vector,
+    /// Keyword matching, for a review not yet embedded
+    Keyword,
+    /// Placed by a pin without ranking on its own
+    Pinned,
+}
+
+/// A query word found in a review
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TermMatch {
+    pub term: String,
+    /// The field it counted for; the title when in both
+    pub field: SearchField,
+    pub score: f32,
+}
+
+/// Parts of a keyword match score, summed then clamped to 0-1
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct KeywordScore {
+    /// 1 when the whole query appears as written
+    pub phrase_match: f32,
+    /// Query words found, directly or through a synonym
+    pub term_matches: Vec<TermMatch>,
+    /// Query expansion terms found
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded_terms: Vec<String>,
+    pub expanded_score: f32,
+    /// Bonus for the share of query words found
+    pub coverage: f32,
+    /// Added per star above 3, taken per star below
+    pub rating_boost: f32,
+    /// Sum of the parts, before clamping
+    pub total: f32,
+}
+
+/// A blend of `value` into the score, taking `weight` of it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Blend {
+    pub value: f32,
+    pub weight: f32,
+}
+
+impl Blend {
+    pub fn apply(self, score: f32) -> f32 {
+        score * (1.0 - self.weight) + self.value * self.weight
+    }
+}
+
+/// How one result's score was reached
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScoreExplanation {
+    #[serde(rename = "match")]
+    pub match_kind: MatchKind,
+    /// Raw cosine similarity, for vector matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector_similarity: Option<f32>,
+    /// Parts of the score, for keyword matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<KeywordScore>,
+    /// The match score, 0-1, before anything was blended in
+    pub match_score: f32,
+    /// With `rank_by_helpfulness`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub helpfulness: Option<Blend>,
+    /// With a `ranking.recency_boost`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recency: Option<Blend>,
+    /// Resemblance to the session's clicks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub personalization: Option<Blend>,
+    /// Total weight added by curation boost rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curation_boost: Option<f32>,
+    /// Id of the curation rule that pinned the result in place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_by: Option<String>,
+}
+
+impl ScoreExplanation {
+    /// A vector match of raw cosine `similarity`, scoring `match_score`
+    pub fn vector(similarity: f32, match_score: f32) -> Self {
+        ScoreExplanation {
+            vector_similarity: Some(similarity),
+            ..ScoreExplanation::new(MatchKind::Vector, match_score)
+        }
+    }
+
+    pub fn new(match_kind: MatchKind, match_score: f32) -> Self {
+        ScoreExplanation {
+            match_kind,
+            vector_similarity: None,
+            keyword: None,
+            match_score,
+            helpfulness: None,
+            recency: None,
+            personalization: None,
+            curation_boost: None,
+            pinned_by: None,
+        }
+    }
+}
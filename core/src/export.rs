@@ -0,0 +1,335 @@
+use crate::arrow_ipc;
+use crate::models::*;
+use crate::storage::DataPaths;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+/// Bytes collected before a chunk is handed to the response body
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks buffered between the file reader and the client
+const CHANNEL_CAPACITY: usize = 8;
+
+/// Column order of CSV exports
+const CSV_HEADERS: [&str; 13] = [
+    "id",
+    "title",
+    "body",
+    "product_id",
+    "rating",
+    "timestamp",
+    "vector_index",
+    "reviewer_id",
+    "batch_id",
+    "updated_at",
+    "source",
+    "license",
+    "attribution",
+];
+
+/// Formats the review dataset can be exported as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+    /// Arrow IPC stream, with each review's vector
+    Arrow,
+}
+
+impl ExportFormat {
+    /// Parse the `format` query parameter; JSONL when absent
+    pub fn parse(format: Option<&str>) -> Result<Self, ValidationError> {
+        match format.map(str::to_lowercase).as_deref() {
+            None | Some("jsonl") => Ok(ExportFormat::Jsonl),
+            Some("csv") => Ok(ExportFormat::Csv),
+            Some("arrow") => Ok(ExportFormat::Arrow),
+            Some(_) => Err(ValidationError::InvalidValue {
+                field: "format".to_string(),
+                reason: "must be one of: jsonl, csv, arrow".to_string(),
+            }),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "application/x-ndjson; charset=utf-8",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Arrow => arrow_ipc::ARROW_STREAM_CONTENT_TYPE,
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "reviews_export.jsonl",
+            ExportFormat::Csv => "reviews_export.csv",
+            ExportFormat::Arrow => "reviews_export.arrows",
+        }
+    }
+}
+
+/// Licenses an export is restricted to, from the `license` query parameter
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LicenseFilter {
+    /// Lowercased licenses, `none` standing for reviews without one; empty exports everything
+    licenses: Vec<String>,
+}
+
+impl LicenseFilter {
+    /// Parse a comma-separated list of licenses; no restriction when absent
+    pub fn parse(license: Option<&str>) -> Result<Self, ValidationError> {
+        let Some(license) = license else {
+            return Ok(LicenseFilter::default());
+        };
+
+        let licenses: Vec<String> = license
+            .split(',')
+            .map(str::trim)
+            .filter(|license| !license.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if licenses.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "license".to_string(),
+                reason: "must list at least one license, or none".to_string(),
+            });
+        }
+        for license in &licenses {
+            validate_provenance(None, Some(license), None)?;
+        }
+        Ok(LicenseFilter { licenses })
+    }
+
+    pub fn matches(&self, review: &ReviewMetadata) -> bool {
+        self.licenses.is_empty()
+            || match &review.license {
+                Some(license) => self.licenses.iter().any(|wanted| wanted.eq_ignore_ascii_case(license)),
+                None => self.licenses.iter().any(|wanted| wanted == "none"),
+            }
+    }
+}
+
+/// Write every live review from `reader` the license filter lets through in the given
+/// format, passing output to `emit` in chunks of roughly `CHUNK_SIZE` bytes, or a record
+/// batch at a time for Arrow. Stops early if `emit` returns false.
+pub fn write_format<R: BufRead>(
+    reader: R,
+    format: ExportFormat,
+    licenses: &LicenseFilter,
+    data_paths: &DataPaths,
+    emit: impl FnMut(Vec<u8>) -> bool,
+) -> Result<(), AppError> {
+    match format {
+        ExportFormat::Arrow => arrow_ipc::write_export(reader, licenses, data_paths, emit),
+        format => write_export(reader, format, licenses, emit),
+    }
+}
+
+/// Write the reviews from `reader` in a text format, one JSONL line or CSV row each
+pub fn write_export<R: BufRead>(
+    reader: R,
+    format: ExportFormat,
+    licenses: &LicenseFilter,
+    mut emit: impl FnMut(Vec<u8>) -> bool,
+) -> Result<(), AppError> {
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+    if format == ExportFormat::Csv {
+        write_csv_row(&mut buffer, CSV_HEADERS)?;
+    }
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let review: ReviewMetadata = serde_json::from_str(&line)?;
+        if review.is_deleted() || !licenses.matches(&review) {
+            continue;
+        }
+
+        match format {
+            ExportFormat::Jsonl => {
+                buffer.extend_from_slice(line.as_bytes());
+                buffer.push(b'\n');
+            }
+            ExportFormat::Csv => write_csv_row(
+                &mut buffer,
+                [
+                    review.id,
+                    review.title,
+                    review.body,
+                    review.product_id,
+                    review.rating.to_string(),
+                    review.timestamp.to_rfc3339(),
+                    review.vector_index.to_string(),
+                    review.reviewer_id.unwrap_or_default(),
+                    review.batch_id.unwrap_or_default(),
+                    review.updated_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+                    review.source.unwrap_or_default(),
+                    review.license.unwrap_or_default(),
+                    review.attribution.unwrap_or_default(),
+                ],
+            )?,
+            ExportFormat::Arrow => unreachable!("Arrow exports are written by write_format"),
+        }
+
+        if buffer.len() >= CHUNK_SIZE && !emit(std::mem::take(&mut buffer)) {
+            return Ok(());
+        }
+    }
+
+    if !buffer.is_empty() {
+        emit(buffer);
+    }
+
+    Ok(())
+}
+
+/// Stream the review file in the data directory as export chunks. The file is read on a
+/// blocking thread and chunks are handed over through a bounded channel, so only a few
+/// chunks are ever in memory.
+///
+/// No data lock is held while reading reviews: rewrites replace the file atomically, so
+/// an export in progress keeps reading the snapshot it opened.
+pub fn stream_export(
+    data_dir: PathBuf,
+    format: ExportFormat,
+    licenses: LicenseFilter,
+) -> mpsc::Receiver<Result<Vec<u8>, std::io::Error>> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+
+    tokio::task::spawn_blocking(move || {
+        let data_paths = DataPaths::new(&data_dir);
+        let file = match File::open(&data_paths.reviews_jsonl) {
+            Ok(file) => file,
+            // Nothing stored yet: export just the CSV header or Arrow schema, if any
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let _ = write_format(std::io::empty(), format, &licenses, &data_paths, |chunk| {
+                    sender.blocking_send(Ok(chunk)).is_ok()
+                });
+                return;
+            }
+            Err(e) => {
+                let _ = sender.blocking_send(Err(e));
+                return;
+            }
+        };
+
+        let result = write_format(BufReader::new(file), format, &licenses, &data_paths, |chunk| {
+            // A failed send means the client went away
+            sender.blocking_send(Ok(chunk)).is_ok()
+        });
+        if let Err(e) = result {
+            tracing::error!("Review export failed: {}", e);
+            let _ = sender.blocking_send(Err(std::io::Error::other(e.to_string())));
+        }
+    });
+
+    receiver
+}
+
+/// Append one CSV-encoded row to the output buffer
+fn write_csv_row<I, T>(buffer: &mut Vec<u8>, record: I) -> Result<(), AppError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(buffer);
+    writer
+        .write_record(record)
+        .map_err(|e| AppError::FileOperation(std::io::Error::other(e.to_string())))?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn review_line(id: &str, deleted: bool) -> String {
+        licensed_review_line(id, deleted, None)
+    }
+
+    fn licensed_review_line(id: &str, deleted: bool, license: Option<&str>) -> String {
+        let review = ReviewMetadata {
+            id: id.to_string(),
+            title: "Export review".to_string(),
+            body: "Body, with a comma".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: license.map(|_| "Example Reviews dataset".to_string()),
+            license: license.map(str::to_string),
+            attribution: license.map(|_| "Example Reviews, CC BY 4.0".to_string()),
+            deleted_at: deleted.then(Utc::now),
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        };
+        serde_json::to_string(&review).unwrap()
+    }
+
+    #[test]
+    fn test_write_export_skips_deleted_reviews() {
+        let content = format!("{}\n{}\n\n{}\n", review_line("a", false), review_line("b", true), review_line("c", false));
+
+        let mut jsonl = Vec::new();
+        write_export(content.as_bytes(), ExportFormat::Jsonl, &LicenseFilter::default(), |chunk| {
+            jsonl.extend(chunk);
+            true
+        })
+        .unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        assert!(!jsonl.contains("\"id\":\"b\""));
+
+        let mut csv_output = Vec::new();
+        write_export(content.as_bytes(), ExportFormat::Csv, &LicenseFilter::default(), |chunk| {
+            csv_output.extend(chunk);
+            true
+        })
+        .unwrap();
+        let mut reader = csv::Reader::from_reader(csv_output.as_slice());
+        assert_eq!(reader.headers().unwrap().iter().collect::<Vec<_>>(), CSV_HEADERS);
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[1][0], "c");
+        assert_eq!(&rows[1][2], "Body, with a comma");
+    }
+
+    #[test]
+    fn test_write_export_filters_by_license() {
+        let content = format!(
+            "{}\n{}\n{}\n",
+            licensed_review_line("a", false, Some("CC-BY-4.0")),
+            licensed_review_line("b", false, Some("CC0-1.0")),
+            review_line("c", false)
+        );
+        let export = |license: Option<&str>| {
+            let mut output = Vec::new();
+            write_export(content.as_bytes(), ExportFormat::Csv, &LicenseFilter::parse(license).unwrap(), |chunk| {
+                output.extend(chunk);
+                true
+            })
+            .unwrap();
+            let mut reader = csv::Reader::from_reader(output.as_slice());
+            reader.records().map(|row| row.unwrap()[0].to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(export(None), ["a", "b", "c"]);
+        assert_eq!(export(Some("cc-by-4.0")), ["a"]);
+        assert_eq!(export(Some("CC0-1.0, none")), ["b", "c"]);
+        assert!(LicenseFilter::parse(Some(" , ")).is_err());
+        assert!(LicenseFilter::parse(Some("CC BY")).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Zero-result fallback: a search that finds nothing is retried with its constraints
+//! relaxed, and the response says which.
+//!
+//! Relaxations are tried in order, least intrusive first and each keeping the ones
+//! before it, until a search finds something:
+//!
+//! 1. `expanded_synonyms`: the query's synonyms (synonyms.txt) are added to it, so the
+//!    embedding and keyword matching look for them as well as the words typed
+//! 2. `lowered_threshold`: `min_score` is dropped
+//! 3. `dropped_filters`: `filters` are dropped
+//!
+//! A step that would change nothing, such as dropping filters from a search without
+//! any, is skipped. Only a first page is relaxed, as an empty later page just means the
+//! results ran out, and a search with `fallback: false` never is. Relaxed results come
+//! as one page without `next_cursor`, since a cursor would continue the original search.
+//! On a sharded deployment the coordinator relaxes the merged search; shards never do.
+
+use crate::models::*;
+use crate::synonyms::Synonyms;
+use crate::{query_syntax, tokenizer};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A constraint of a search loosened to find results
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Relaxation {
+    ExpandedSynonyms,
+    LoweredThreshold,
+    DroppedFilters,
+}
+
+impl Relaxation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Relaxation::ExpandedSynonyms => "expanded_synonyms",
+            Relaxation::LoweredThreshold => "lowered_threshold",
+            Relaxation::DroppedFilters => "dropped_filters",
+        }
+    }
+}
+
+/// How a search that found nothing was relaxed to find its results
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SearchFallback {
+    /// The relaxations applied, in the order they were tried
+    pub relaxations: Vec<Relaxation>,
+    /// Synonyms added to the query
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_terms: Vec<String>,
+    /// The `min_score` dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped_min_score: Option<f32>,
+    /// The filters dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped_filters: Option<SearchFilters>,
+}
+
+/// Whether a search's response calls for relaxing it: a first page with no results,
+/// unless the search opted out
+pub fn applies(search_request: &SearchRequest, response: &SearchResponse) -> bool {
+    response.results.is_empty()
+        && search_request.fallback != Some(false)
+        && search_request.cursor.is_none()
+        && search_request.get_offset() == 0
+}
+
+/// The relaxed searches to try in turn, each with what it relaxed. They never fall
+/// back themselves.
+pub fn steps(search_request: &SearchRequest, synonyms: &Synonyms) -> Vec<(SearchRequest, SearchFallback)> {
+    let mut relaxed = SearchRequest {
+        fallback: Some(false),
+        ..search_request.clone()
+    };
+    let mut fallback = SearchFallback {
+        relaxations: Vec::new(),
+        added_terms: Vec::new(),
+        dropped_min_score: None,
+        dropped_filters: None,
+    };
+    let mut steps = Vec::new();
+
+    let added_terms = synonyms_of(&search_request.query, synonyms);
+    if !added_terms.is_empty() {
+        relaxed.query = format!("{} {}", search_request.query, added_terms.join(" "));
+        fallback.relaxations.push(Relaxation::ExpandedSynonyms);
+        fallback.added_terms = added_terms;
+        steps.push((relaxed.clone(), fallback.clone()));
+    }
+    if let Some(min_score) = relaxed.min_score.take().filter(|min_score| *min_score > 0.0) {
+        fallback.relaxations.push(Relaxation::LoweredThreshold);
+        fallback.dropped_min_score = Some(min_score);
+        steps.push((relaxed.clone(), fallback.clone()));
+    }
+    if let Some(filters) = relaxed.filters.take().filter(|filters| *filters != SearchFilters::default()) {
+        fallback.relaxations.push(Relaxation::DroppedFilters);
+        fallback.dropped_filters = Some(filters);
+        steps.push((relaxed, fallback));
+    }
+    steps
+}
+
+/// Synonyms of the query's words and of the whole query, not in it already, as many as
+/// fit within the longest query allowed
+fn synonyms_of(query: &str, synonyms: &Synonyms) -> Vec<String> {
+    let text = query_syntax::ParsedQuery::parse(query).text;
+    let words = tokenizer::analyze(&text);
+    let mut length = query.len();
+    let mut added: Vec<String> = Vec::new();
+    for term in std::iter::once(words.join(" ")).chain(words.iter().cloned()) {
+        for alternative in synonyms.alternatives(&term) {
+            let fits = length + 1 + alternative.len() <= MAX_QUERY_LENGTH;
+            if fits && !words.contains(alternative) && !added.contains(alternative) {
+                length += 1 + alternative.len();
+                added.push(alternative.clone());
+            }
+        }
+    }
+    added
+}
+
+/// Present a relaxed search's response as the answer to the original search
+pub fn finish(response: &mut SearchResponse, search_request: &SearchRequest, fallback: SearchFallback) {
+    response.query = search_request.query.clone();
+    response.next_cursor = None;
+    response.fallback = Some(fallback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(query: &str) -> SearchRequest {
+        serde_json::from_value(serde_json::json!({ "query": query, "limit": 5 })).unwrap()
+    }
+
+    #[test]
+    fn test_steps_relax_one_constraint_at_a_time() {
+        let synonyms = Synonyms::parse_text("phone = smartphone = mobile").unwrap();
+        let mut search_request = request("Phone case");
+        search_request.min_score = Some(0.5);
+        search_request.filters = Some(SearchFilters::parse("min_rating:4").unwrap());
+
+        let steps = steps(&search_request, &synonyms);
+        let relaxations: Vec<&[Relaxation]> =
+            steps.iter().map(|(_, fallback)| fallback.relaxations.as_slice()).collect();
+        assert_eq!(
+            relaxations,
+            [
+                &[Relaxation::ExpandedSynonyms][..],
+                &[Relaxation::ExpandedSynonyms, Relaxation::LoweredThreshold],
+                &[Relaxation::ExpandedSynonyms, Relaxation::LoweredThreshold, Relaxation::DroppedFilters],
+            ]
+        );
+        let (last, fallback) = steps.last().unwrap();
+        assert_eq!(last.query, "Phone case smartphone mobile");
+        assert_eq!((last.min_score, last.filters.is_none(), last.fallback), (None, true, Some(false)));
+        assert_eq!(fallback.added_terms, ["smartphone", "mobile"]);
+        assert_eq!(fallback.dropped_min_score, Some(0.5));
+
+        // Nothing to relax
+        assert!(super::steps(&request("kettle"), &synonyms).is_empty());
+    }
+}
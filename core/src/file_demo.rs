@@ -49,24 +49,36 @@ impl FileSystemDemo {
         println!("\n📄 Requirement 4.3: JSONL Format Implementation");
         
         // Create sample reviews to demonstrate JSONL format
-        let sample_reviews = vec![
+        let sample_reviews = [
             ReviewData {
                 title: "Great product!".to_string(),
                 body: "This product exceeded my expectations. Great quality and fast delivery.".to_string(),
                 product_id: "prod_123".to_string(),
                 rating: 5,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             },
             ReviewData {
                 title: "Good value".to_string(),
                 body: "Decent product for the price. Would recommend to others.".to_string(),
                 product_id: "prod_124".to_string(),
                 rating: 4,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             },
             ReviewData {
                 title: "Average experience".to_string(),
                 body: "The product is okay but nothing special. Could be improved.".to_string(),
                 product_id: "prod_125".to_string(),
                 rating: 3,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             },
         ];
         
@@ -166,7 +178,7 @@ impl FileSystemDemo {
         
         // Demonstrate file locking for concurrent operations
         println!("Acquiring file lock for safe concurrent access...");
-        let _lock = FileLock::acquire(&self.data_paths.lock_file)?;
+        let _lock = FileLock::acquire(&self.data_paths.lock_file, "demo")?;
         println!("✅ File lock acquired successfully");
         
         // The lock will be automatically released when _lock goes out of scope
@@ -245,6 +257,10 @@ mod tests {
             body: "This is a test review for JSONL format verification.".to_string(),
             product_id: "test_prod".to_string(),
             rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
         };
         
         let metadata = review_data.to_metadata(0).unwrap();
@@ -273,18 +289,30 @@ mod tests {
                 body: "First review".to_string(),
                 product_id: "prod_0".to_string(),
                 rating: 5,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             }.to_metadata(0).unwrap(),
             ReviewData {
                 title: "Review 1".to_string(),
                 body: "Second review".to_string(),
                 product_id: "prod_1".to_string(),
                 rating: 4,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             }.to_metadata(1).unwrap(),
             ReviewData {
                 title: "Review 2".to_string(),
                 body: "Third review".to_string(),
                 product_id: "prod_2".to_string(),
                 rating: 3,
+                reviewer_id: None,
+                source: None,
+                license: None,
+                attribution: None,
             }.to_metadata(2).unwrap(),
         ];
         
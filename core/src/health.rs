@@ -0,0 +1,124 @@
+//! Readiness checks behind `/health` and `/health/ready`.
+//!
+//! `/health/live` only shows the process is serving requests, so a Kubernetes liveness
+//! probe restarts a hung server but not one whose disk is full. `/health/ready` runs
+//! every check below and answers 503 when one fails, taking the pod out of service
+//! until it passes again.
+
+use crate::canary;
+use crate::embedding::{self, Embedder, EMBEDDING_DIM};
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use serde_json::{Map, Value};
+
+/// Outcome of every readiness check, by name
+pub struct Readiness {
+    checks: Vec<(&'static str, Result<(), String>)>,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// `{"storage": "ok", "vector_index": "<error>", ...}`
+    pub fn checks_json(&self) -> Value {
+        let checks: Map<String, Value> = self
+            .checks
+            .iter()
+            .map(|(name, result)| {
+                let status = match result {
+                    Ok(()) => "ok".to_string(),
+                    Err(e) => e.clone(),
+                };
+                (name.to_string(), Value::String(status))
+            })
+            .collect();
+        Value::Object(checks)
+    }
+}
+
+/// Run every readiness check
+pub fn readiness(data_paths: &DataPaths) -> Readiness {
+    Readiness {
+        checks: vec![
+            ("storage", data_paths.check_storage().map_err(|e| e.to_string())),
+            ("embedding", check_embedder(&embedding::default_embedder())),
+            ("vector_index", check_vector_index(data_paths)),
+            ("canaries", canary::check(data_paths)),
+        ],
+    }
+}
+
+/// The embedder produces unit vectors of the stored dimension
+fn check_embedder(embedder: &dyn Embedder) -> Result<(), String> {
+    if embedder.dimension() != EMBEDDING_DIM {
+        return Err(format!(
+            "embedder produces {}-dimensional vectors but reviews.index holds {}",
+            embedder.dimension(),
+            EMBEDDING_DIM
+        ));
+    }
+    let vector = embedder.embed("readiness probe");
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if vector.len() != EMBEDDING_DIM || !norm.is_finite() || (norm - 1.0).abs() > 1e-3 {
+        return Err("embedder returned an invalid vector for a probe text".to_string());
+    }
+    Ok(())
+}
+
+/// reviews.index holds whole vectors, and no more of them than there are review lines.
+/// Fewer is normal while the embedding worker catches up.
+fn check_vector_index(data_paths: &DataPaths) -> Result<(), String> {
+    if !data_paths.reviews_index.exists() {
+        return Ok(());
+    }
+
+    // Read under the shared lock unless a writer holds it; never wait for one
+    let _lock = FileLock::try_acquire(&data_paths.lock_file, "health_check").map_err(|e| e.to_string())?;
+    let slot_bytes = (EMBEDDING_DIM * std::mem::size_of::<f32>()) as u64;
+    let index_bytes = std::fs::metadata(&data_paths.reviews_index).map_err(|e| e.to_string())?.len();
+    if index_bytes % slot_bytes != 0 {
+        return Err(format!(
+            "reviews.index is {} bytes, not a whole number of {}-byte vectors; run POST /admin/reindex",
+            index_bytes, slot_bytes
+        ));
+    }
+
+    let vectors = VectorStore::new(&data_paths.reviews_index, EMBEDDING_DIM).len().map_err(|e| e.to_string())?;
+    let lines = JsonlStorage::new(&data_paths.reviews_jsonl).count_reviews().map_err(|e| e.to_string())?;
+    if vectors > lines {
+        return Err(format!(
+            "reviews.index holds {} vectors for {} reviews; run POST /admin/reindex",
+            vectors, lines
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_readiness_flags_a_corrupt_vector_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        let report = readiness(&data_paths);
+        assert!(report.is_ready());
+        assert_eq!(report.checks_json()["embedding"], "ok");
+
+        // A partial vector, as a crash mid-write leaves behind
+        std::fs::write(&data_paths.reviews_index, [0u8; 10]).unwrap();
+        let report = readiness(&data_paths);
+        assert!(!report.is_ready());
+        let vector_index = report.checks_json()["vector_index"].as_str().unwrap().to_string();
+        assert!(vector_index.contains("not a whole number"), "{}", vector_index);
+
+        // Vectors for reviews that do not exist
+        std::fs::write(&data_paths.reviews_index, vec![0u8; EMBEDDING_DIM * 4]).unwrap();
+        assert!(!readiness(&data_paths).is_ready());
+    }
+}
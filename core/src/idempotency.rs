@@ -0,0 +1,202 @@
+use crate::models::*;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Request header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set when a stored response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// How long keys are remembered when `IDEMPOTENCY_TTL_SECS` is not set
+pub const DEFAULT_IDEMPOTENCY_TTL_SECS: i64 = 24 * 60 * 60;
+
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// A completed request remembered under its idempotency key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    /// The request body, so a key reused for a different request can be refused
+    pub request: Value,
+    pub review_id: String,
+    /// The response body returned the first time, replayed verbatim
+    pub response: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    fn is_expired(&self, now: DateTime<Utc>, ttl: Duration) -> bool {
+        now - self.created_at >= ttl
+    }
+}
+
+/// Key retention from `IDEMPOTENCY_TTL_SECS`, falling back to the default
+pub fn idempotency_ttl() -> Duration {
+    let secs = std::env::var("IDEMPOTENCY_TTL_SECS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_IDEMPOTENCY_TTL_SECS);
+    Duration::seconds(secs)
+}
+
+/// A client's idempotency key, trimmed, or why it can't be used
+pub fn validate_key(key: &str) -> Result<String, ValidationError> {
+    let invalid = |reason: &str| ValidationError::InvalidValue {
+        field: "idempotency_key".to_string(),
+        reason: reason.to_string(),
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(invalid("must not be empty"));
+    }
+    if key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(invalid(&format!("must be at most {} characters", MAX_IDEMPOTENCY_KEY_LENGTH)));
+    }
+
+    Ok(key.to_string())
+}
+
+/// JSONL file of idempotency records (key → created review and its response)
+pub struct IdempotencyStore {
+    file_path: PathBuf,
+}
+
+impl IdempotencyStore {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The unexpired record for `key`, if any. The caller must hold the FileLock,
+    /// so a retry racing the original request sees its record.
+    pub fn find(&self, key: &str, now: DateTime<Utc>, ttl: Duration) -> Result<Option<IdempotencyRecord>, AppError> {
+        Ok(self
+            .read_all()?
+            .into_iter()
+            .rev()
+            .find(|record| record.key == key && !record.is_expired(now, ttl)))
+    }
+
+    /// Remember a completed request, dropping expired records along the way.
+    /// The caller must hold the FileLock.
+    pub fn record(&self, record: &IdempotencyRecord, ttl: Duration) -> Result<(), AppError> {
+        let existing = self.read_all()?;
+        let live: Vec<&IdempotencyRecord> = existing
+            .iter()
+            .filter(|existing| !existing.is_expired(record.created_at, ttl))
+            .collect();
+
+        if live.len() == existing.len() {
+            let mut file = OpenOptions::new().create(true).append(true).open(&self.file_path)?;
+            writeln!(file, "{}", serde_json::to_string(record)?)?;
+            file.flush()?;
+            return Ok(());
+        }
+
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for existing in live.into_iter().chain(std::iter::once(record)) {
+                writeln!(writer, "{}", serde_json::to_string(existing)?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+
+        std::fs::rename(&temp_path, &self.file_path)?;
+        Ok(())
+    }
+
+    /// Atomically drop every record matching `predicate`, expired or not, returning them.
+    /// The caller must hold the FileLock.
+    pub fn remove_where<P>(&self, predicate: P) -> Result<Vec<IdempotencyRecord>, AppError>
+    where
+        P: Fn(&IdempotencyRecord) -> bool,
+    {
+        let (removed, kept): (Vec<IdempotencyRecord>, Vec<IdempotencyRecord>) =
+            self.read_all()?.into_iter().partition(|record| predicate(record));
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for record in &kept {
+                writeln!(writer, "{}", serde_json::to_string(record)?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+
+        std::fs::rename(&temp_path, &self.file_path)?;
+        Ok(removed)
+    }
+
+    /// Every stored record, expired ones included, oldest first
+    pub fn read_all(&self) -> Result<Vec<IdempotencyRecord>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(File::open(&self.file_path)?);
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                records.push(serde_json::from_str(&line)?);
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn record(key: &str, created_at: DateTime<Utc>) -> IdempotencyRecord {
+        IdempotencyRecord {
+            key: key.to_string(),
+            request: json!({ "title": key }),
+            review_id: format!("review-{}", key),
+            response: json!({ "success": true }),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_records_expire_and_are_pruned() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IdempotencyStore::new(temp_dir.path().join("idempotency.jsonl"));
+        let ttl = Duration::hours(1);
+        let now = Utc::now();
+
+        store.record(&record("old", now - Duration::hours(2)), ttl).unwrap();
+        assert!(store.find("old", now, ttl).unwrap().is_none());
+
+        store.record(&record("new", now), ttl).unwrap();
+        assert_eq!(store.find("new", now, ttl).unwrap().unwrap().review_id, "review-new");
+        assert_eq!(store.read_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_idempotency_key_is_validated() {
+        assert_eq!(validate_key(" retry-1 ").unwrap(), "retry-1");
+        assert!(validate_key("  ").is_err());
+        assert!(validate_key(&"x".repeat(MAX_IDEMPOTENCY_KEY_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,668 @@
+//! Writing new reviews: validation, the ingestion blocklist, the duplicate check, corpus
+//! limits, the append to reviews.jsonl and queueing for the embedding worker.
+//!
+//! `create_review` stores one review, replaying the first response for a repeated
+//! idempotency key; `create_reviews` stores a batch all-or-nothing; `bulk_upload` stores
+//! every valid review of an upload and records the upload as a job. `update_review`,
+//! `delete_review` and `vote_review` change a stored review in place, and `bulk_delete`
+//! tombstones every review a `BulkDeleteRequest` names or matches. All hold the data
+//! lock for the whole write, so vector indices stay dense however many writers run.
+//! None publishes events or counts metrics: the caller does, from what they return.
+
+use crate::blocklist::{BlockScope, Blocklist};
+use crate::corpus_limits::{CorpusLimits, Eviction};
+use crate::duplicates::{DuplicatePolicy, IngestCheck};
+use crate::embedding::EmbeddingStatus;
+use crate::embedding_queue::Priority;
+use crate::embedding_worker;
+use crate::idempotency::{self, IdempotencyRecord, IdempotencyStore};
+use crate::jobs::JobStorage;
+use crate::lock::FileLock;
+use crate::models::*;
+use crate::storage::{DataPaths, JsonlStorage};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+/// Outcome of `create_review`
+#[derive(Debug)]
+pub enum Created {
+    /// The review was stored, evicting `eviction` to make room for it
+    Stored {
+        review: Box<ReviewMetadata>,
+        eviction: Eviction,
+        response: Value,
+    },
+    /// The idempotency key was used before for the same review, which is not stored again
+    Replayed { response: Value },
+}
+
+impl Created {
+    /// The response body, the same for a replay as for the original request
+    pub fn response(&self) -> &Value {
+        match self {
+            Created::Stored { response, .. } | Created::Replayed { response } => response,
+        }
+    }
+}
+
+/// A batch stored by `create_reviews`
+#[derive(Debug)]
+pub struct BatchCreated {
+    pub reviews: Vec<ReviewMetadata>,
+    pub eviction: Eviction,
+}
+
+/// Why `create_reviews` stored none of a batch
+#[derive(Debug)]
+pub enum BatchCreateError {
+    /// Every review failing the first check that failed any, by position in the batch.
+    /// Validation runs first, then the blocklist, then the duplicate check; `problem`
+    /// says which, as in "Review 2 is blocked".
+    Rejected {
+        problem: &'static str,
+        errors: Vec<(usize, AppError)>,
+    },
+    /// The batch was the wrong size, or couldn't be checked or written
+    Failed(AppError),
+}
+
+impl From<AppError> for BatchCreateError {
+    fn from(error: AppError) -> Self {
+        BatchCreateError::Failed(error)
+    }
+}
+
+/// A bulk upload once stored
+#[derive(Debug)]
+pub struct BulkUploaded {
+    pub result: BulkUploadResult,
+    pub starting_vector_index: usize,
+    /// Slot of the last stored review; `None` when none were stored
+    pub ending_vector_index: Option<usize>,
+    /// The upload's entry in the job history; `None` if it couldn't be recorded
+    pub job_id: Option<String>,
+    /// Tags the stored reviews for rollback; `None` when none were stored
+    pub batch_id: Option<String>,
+    pub eviction: Eviction,
+}
+
+/// A bulk delete once stored
+#[derive(Debug)]
+pub struct BulkDeleted {
+    pub deleted: Vec<ReviewMetadata>,
+    /// Requested ids that were unknown or already deleted, in request order
+    pub not_found: Vec<String>,
+}
+
+/// Store one review. With an idempotency key, a retry of the same review replays the
+/// first response and a different review under the key is a conflict.
+pub fn create_review(
+    data_paths: &DataPaths,
+    review_data: &ReviewData,
+    idempotency_key: Option<String>,
+) -> Result<Created, AppError> {
+    review_data.validate()?;
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let idempotency_store = IdempotencyStore::new(&data_paths.idempotency_jsonl);
+    let idempotency_ttl = idempotency::idempotency_ttl();
+    let request_json = json!(review_data);
+
+    // A retry racing the original request waits here and then finds its idempotency record
+    let _lock = FileLock::acquire(&data_paths.lock_file, "create_review")?;
+
+    if let Some(key) = &idempotency_key {
+        match idempotency_store.find(key, chrono::Utc::now(), idempotency_ttl)? {
+            Some(record) if record.request == request_json => {
+                tracing::info!("Replaying response for idempotency key {} (review {})", key, record.review_id);
+                return Ok(Created::Replayed { response: record.response });
+            }
+            Some(_) => {
+                return Err(AppError::Conflict {
+                    message: format!("Idempotency key {} was already used for a different review", key),
+                })
+            }
+            None => {}
+        }
+    }
+
+    // A replay above still answers; a new review of a blocked product or reviewer is refused
+    Blocklist::load(data_paths, BlockScope::Ingestion)?.check(review_data)?;
+
+    let vector_index = jsonl_storage.count_reviews()?;
+    let mut review_metadata = review_data.to_metadata(vector_index)?;
+    IngestCheck::load(&jsonl_storage, DuplicatePolicy::from_env())?.check(&mut review_metadata)?;
+
+    // Over a corpus limit the review is refused, or the oldest reviews make room for it
+    let eviction = CorpusLimits::from_env().make_room(&jsonl_storage, std::slice::from_ref(&review_metadata))?;
+    jsonl_storage.append_review(&review_metadata)?;
+
+    // The embedding worker fills the vector slot; until then keyword search still finds it
+    embedding_worker::enqueue(data_paths, Priority::Interactive, [vector_index]);
+    tracing::info!("Review stored successfully. Vector index {} queued for embedding", vector_index);
+
+    let mut response = json!({
+        "success": true,
+        "message": "Review created successfully",
+        "review_id": review_metadata.id,
+        "vector_index": vector_index,
+        "timestamp": review_metadata.timestamp,
+        "embedding_status": EmbeddingStatus::Pending
+    });
+    if let Some(duplicate_of) = &review_metadata.duplicate_of {
+        response["duplicate_of"] = json!(duplicate_of);
+    }
+
+    if let Some(key) = idempotency_key {
+        let record = IdempotencyRecord {
+            key,
+            request: request_json,
+            review_id: review_metadata.id.clone(),
+            response: response.clone(),
+            created_at: chrono::Utc::now(),
+        };
+        // The review is already stored; failing to remember the key only loses replay protection
+        if let Err(e) = idempotency_store.record(&record, idempotency_ttl) {
+            tracing::error!("Failed to record idempotency key {}: {}", record.key, e);
+        }
+    }
+
+    Ok(Created::Stored {
+        review: Box::new(review_metadata),
+        eviction,
+        response,
+    })
+}
+
+/// Create every review or none: all are checked before any is written, and they are
+/// appended in one write under the lock
+pub fn create_reviews(data_paths: &DataPaths, reviews: &[ReviewData]) -> Result<BatchCreated, BatchCreateError> {
+    if reviews.is_empty() || reviews.len() > MAX_BATCH_CREATE {
+        return Err(AppError::Validation(ValidationError::InvalidValue {
+            field: "reviews".to_string(),
+            reason: format!("must contain between 1 and {} reviews", MAX_BATCH_CREATE),
+        })
+        .into());
+    }
+    let invalid: Vec<(usize, AppError)> = reviews
+        .iter()
+        .enumerate()
+        .filter_map(|(position, review_data)| review_data.validate().err().map(|e| (position, e.into())))
+        .collect();
+    if !invalid.is_empty() {
+        return Err(BatchCreateError::Rejected { problem: "invalid", errors: invalid });
+    }
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "batch_create_reviews")?;
+
+    let blocklist = Blocklist::load(data_paths, BlockScope::Ingestion)?;
+    let blocked: Vec<(usize, AppError)> = reviews
+        .iter()
+        .enumerate()
+        .filter_map(|(position, review_data)| blocklist.check(review_data).err().map(|e| (position, e)))
+        .collect();
+    if !blocked.is_empty() {
+        return Err(BatchCreateError::Rejected { problem: "blocked", errors: blocked });
+    }
+
+    let starting_vector_index = jsonl_storage.count_reviews()?;
+    let mut created = reviews
+        .iter()
+        .enumerate()
+        .map(|(offset, review_data)| review_data.to_metadata(starting_vector_index + offset))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Each review is compared with the stored ones and those before it in the batch
+    let mut check = IngestCheck::load(&jsonl_storage, DuplicatePolicy::from_env())?;
+    let duplicates: Vec<(usize, AppError)> = created
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(position, review)| check.check(review).err().map(|e| (position, e)))
+        .collect();
+    if !duplicates.is_empty() {
+        return Err(BatchCreateError::Rejected { problem: "a duplicate", errors: duplicates });
+    }
+
+    // Over a corpus limit the batch is refused, or the oldest reviews make room for it
+    let eviction = CorpusLimits::from_env().make_room(&jsonl_storage, &created)?;
+    jsonl_storage.append_reviews_all_or_nothing(&created)?;
+
+    embedding_worker::enqueue(data_paths, Priority::Interactive, created.iter().map(|review| review.vector_index));
+    tracing::info!(
+        "Batch create: {} reviews stored. Vector indices {}-{} queued for embedding",
+        created.len(),
+        starting_vector_index,
+        starting_vector_index + created.len() - 1
+    );
+
+    Ok(BatchCreated { reviews: created, eviction })
+}
+
+/// Store the valid reviews of an upload, reporting the rest by line, and record the
+/// upload in the job history. The upload's provenance fills in what reviews leave out.
+pub fn bulk_upload(
+    data_paths: &DataPaths,
+    reviews: Vec<ReviewData>,
+    upload_query: BulkUploadQuery,
+) -> Result<BulkUploaded, AppError> {
+    upload_query.validate()?;
+    let review_data_list: Vec<ReviewData> = reviews
+        .into_iter()
+        .map(|review| {
+            review.with_provenance_defaults(
+                upload_query.source.as_deref(),
+                upload_query.license.as_deref(),
+                upload_query.attribution.as_deref(),
+            )
+        })
+        .collect();
+    if review_data_list.is_empty() {
+        return Err(AppError::Validation(ValidationError::InvalidValue {
+            field: "reviews".to_string(),
+            reason: "No valid reviews found in bulk data".to_string(),
+        }));
+    }
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "bulk_upload")?;
+    let starting_vector_index = jsonl_storage.count_reviews()?;
+    let blocklist = Blocklist::load(data_paths, BlockScope::Ingestion)?;
+    let mut duplicates = IngestCheck::load(&jsonl_storage, DuplicatePolicy::from_env())?;
+
+    // Process each review and collect results, tagging them with a batch id for rollback
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let mut successful_reviews = Vec::new();
+    let mut failed_reviews = Vec::new();
+    let mut current_vector_index = starting_vector_index;
+
+    for (line_number, review_data) in review_data_list.iter().enumerate() {
+        match process_single_review(review_data, current_vector_index, &blocklist, &mut duplicates) {
+            Ok(mut metadata) => {
+                metadata.batch_id = Some(batch_id.clone());
+                successful_reviews.push(metadata);
+                current_vector_index += 1;
+            }
+            Err(e) => {
+                failed_reviews.push(BulkError {
+                    line_number: line_number + 1,
+                    error: e.to_string(),
+                    data: Some(serde_json::to_value(review_data).unwrap_or(Value::Null)),
+                });
+            }
+        }
+    }
+
+    // Over a corpus limit the upload is refused, or the oldest reviews make room for it
+    let eviction = CorpusLimits::from_env().make_room(&jsonl_storage, &successful_reviews)?;
+
+    // Store all successful reviews in batch
+    if !successful_reviews.is_empty() {
+        jsonl_storage.append_reviews(&successful_reviews)?;
+        embedding_worker::enqueue(
+            data_paths,
+            Priority::Backfill,
+            successful_reviews.iter().map(|review| review.vector_index),
+        );
+        tracing::info!(
+            "Bulk upload: {} reviews stored successfully. Vector indices {}-{} queued for embedding",
+            successful_reviews.len(),
+            starting_vector_index,
+            current_vector_index - 1
+        );
+    }
+
+    let result = BulkUploadResult {
+        total_processed: review_data_list.len(),
+        successful: successful_reviews.len(),
+        failed: failed_reviews,
+    };
+
+    // Record the upload in the job history; the reviews are already stored, so only warn on failure
+    let job = JobRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        job_type: JobType::BulkUpload,
+        status: JobStatus::Completed,
+        created_at: chrono::Utc::now(),
+        file_name: upload_query.file_name,
+        total_processed: result.total_processed,
+        successful: result.successful,
+        failed: result.failed.len(),
+        batch_id: (!successful_reviews.is_empty()).then_some(batch_id),
+        rolled_back_at: None,
+        progress: None,
+        error: None,
+    };
+    let job_storage = JobStorage::new(&data_paths.jobs_jsonl);
+    let job_id = match job_storage.append_job(&job) {
+        Ok(()) => Some(job.id),
+        Err(e) => {
+            tracing::warn!("Failed to record bulk upload job: {}", e);
+            None
+        }
+    };
+
+    Ok(BulkUploaded {
+        result,
+        starting_vector_index,
+        ending_vector_index: (!successful_reviews.is_empty()).then(|| current_vector_index - 1),
+        job_id,
+        batch_id: job.batch_id,
+        eviction,
+    })
+}
+
+/// Replace a live review's content and queue it for re-embedding. The review keeps
+/// its id and vector index; the blocklist applies as it does to a new review.
+pub fn update_review(
+    data_paths: &DataPaths,
+    review_id: &str,
+    review_data: &ReviewData,
+) -> Result<ReviewMetadata, AppError> {
+    review_data.validate()?;
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "update_review")?;
+    Blocklist::load(data_paths, BlockScope::Ingestion)?.check(review_data)?;
+
+    let updated_review = jsonl_storage
+        .update_review(review_id, review_data)?
+        .ok_or_else(|| review_not_found(review_id))?;
+
+    embedding_worker::enqueue(data_paths, Priority::Interactive, [updated_review.vector_index]);
+    tracing::info!(
+        "Review {} updated. Vector index {} flagged for re-embedding",
+        updated_review.id,
+        updated_review.vector_index
+    );
+    Ok(updated_review)
+}
+
+/// Tombstone a live review, returning it as deleted
+pub fn delete_review(data_paths: &DataPaths, review_id: &str) -> Result<ReviewMetadata, AppError> {
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "delete_review")?;
+
+    // Tombstone the JSONL line; its vector slot stays reserved so indices remain aligned
+    let deleted_review = jsonl_storage.delete_review(review_id)?.ok_or_else(|| review_not_found(review_id))?;
+    tracing::info!(
+        "Review {} deleted. Vector index {} is tombstoned and excluded from search",
+        deleted_review.id,
+        deleted_review.vector_index
+    );
+    Ok(deleted_review)
+}
+
+/// Tombstone every live review the request names by id, or else matches by filter, in
+/// one rewrite. Lines stay, so vector indices remain aligned.
+pub fn bulk_delete(data_paths: &DataPaths, delete_request: &BulkDeleteRequest) -> Result<BulkDeleted, AppError> {
+    delete_request.validate()?;
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "bulk_delete_reviews")?;
+
+    let requested_ids: Option<HashSet<&str>> = delete_request
+        .ids
+        .as_ref()
+        .map(|ids| ids.iter().map(String::as_str).collect());
+    let deleted = match &requested_ids {
+        Some(ids) => jsonl_storage.delete_reviews_where(|review| ids.contains(review.id.as_str()))?,
+        None => jsonl_storage.delete_reviews_where(|review| delete_request.matches(review))?,
+    };
+
+    let deleted_ids: HashSet<&str> = deleted.iter().map(|review| review.id.as_str()).collect();
+    let mut reported = HashSet::new();
+    let not_found: Vec<String> = delete_request
+        .ids
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|id| !deleted_ids.contains(id) && reported.insert(*id))
+        .map(str::to_string)
+        .collect();
+
+    tracing::info!(
+        "Bulk delete tombstoned {} reviews ({} requested ids not found)",
+        deleted.len(),
+        not_found.len()
+    );
+    Ok(BulkDeleted { deleted, not_found })
+}
+
+/// Count a helpful or unhelpful vote on a live review, returning its new totals
+pub fn vote_review(data_paths: &DataPaths, review_id: &str, helpful: bool) -> Result<ReviewMetadata, AppError> {
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "vote_review")?;
+    jsonl_storage.record_vote(review_id, helpful)?.ok_or_else(|| review_not_found(review_id))
+}
+
+fn review_not_found(review_id: &str) -> AppError {
+    AppError::NotFound {
+        resource: "Review".to_string(),
+        id: review_id.to_string(),
+    }
+}
+
+/// Parse bulk data from various formats (JSON array, JSONL, etc.)
+pub fn parse_bulk_data(bulk_data: &Value) -> Result<Vec<ReviewData>, AppError> {
+    match bulk_data {
+        // Handle JSON array format: [{"title": "...", ...}, ...]
+        Value::Array(reviews) => {
+            let mut parsed_reviews = Vec::new();
+            for review_value in reviews {
+                match serde_json::from_value::<ReviewData>(review_value.clone()) {
+                    Ok(review) => parsed_reviews.push(review),
+                    Err(e) => {
+                        return Err(AppError::Serialization(e));
+                    }
+                }
+            }
+            Ok(parsed_reviews)
+        }
+        // Handle single object wrapped in array
+        Value::Object(_) => {
+            match serde_json::from_value::<ReviewData>(bulk_data.clone()) {
+                Ok(review) => Ok(vec![review]),
+                Err(e) => Err(AppError::Serialization(e)),
+            }
+        }
+        // Handle string format (JSONL or CSV with a header row)
+        Value::String(content) => {
+            let first_line = content
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'));
+            if first_line.is_some_and(|line| !line.starts_with('{')) {
+                return parse_csv_reviews(content);
+            }
+
+            let mut parsed_reviews = Vec::new();
+            for (line_num, line) in content.lines().enumerate() {
+                let line = line.trim();
+                // Skip blank lines and template comments
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                match serde_json::from_str::<ReviewData>(line) {
+                    Ok(review) => parsed_reviews.push(review),
+                    Err(e) => {
+                        return Err(AppError::Validation(ValidationError::InvalidValue {
+                            field: format!("line_{}", line_num + 1),
+                            reason: format!("Invalid JSON: {}", e),
+                        }));
+                    }
+                }
+            }
+            Ok(parsed_reviews)
+        }
+        _ => Err(AppError::Validation(ValidationError::InvalidValue {
+            field: "bulk_data".to_string(),
+            reason: "Expected JSON array, object, or JSONL string".to_string(),
+        })),
+    }
+}
+
+/// Parse CSV content with a `title,body,product_id,rating` header row
+fn parse_csv_reviews(content: &str) -> Result<Vec<ReviewData>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut parsed_reviews = Vec::new();
+    for record in reader.deserialize::<ReviewData>() {
+        match record {
+            Ok(review) => parsed_reviews.push(review),
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or(0);
+                return Err(AppError::Validation(ValidationError::InvalidValue {
+                    field: format!("line_{}", line),
+                    reason: format!("Invalid CSV: {}", e),
+                }));
+            }
+        }
+    }
+    Ok(parsed_reviews)
+}
+
+/// Process a single review and convert to metadata
+fn process_single_review(
+    review_data: &ReviewData,
+    vector_index: usize,
+    blocklist: &Blocklist,
+    duplicates: &mut IngestCheck,
+) -> Result<ReviewMetadata, AppError> {
+    // Validate the review data
+    review_data.validate()?;
+    blocklist.check(review_data)?;
+
+    // Convert to metadata with generated ID and timestamp
+    let mut review_metadata = review_data.to_metadata(vector_index)?;
+    duplicates.check(&mut review_metadata)?;
+    Ok(review_metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn review(title: &str, rating: u8) -> ReviewData {
+        ReviewData {
+            title: title.to_string(),
+            body: format!("{} in every way.", title),
+            product_id: "prod_1".to_string(),
+            rating,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    #[test]
+    fn test_create_replays_and_bulk_reports_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        let created = create_review(&data_paths, &review("Great kettle", 5), Some("retry-1".to_string())).unwrap();
+        let Created::Stored { review: stored, .. } = &created else { panic!("expected a stored review") };
+        assert_eq!(stored.vector_index, 0);
+
+        let replayed = create_review(&data_paths, &review("Great kettle", 5), Some("retry-1".to_string())).unwrap();
+        assert!(matches!(replayed, Created::Replayed { .. }));
+        assert_eq!(replayed.response(), created.response());
+        let conflict = create_review(&data_paths, &review("Other kettle", 5), Some("retry-1".to_string()));
+        assert!(matches!(conflict, Err(AppError::Conflict { .. })));
+
+        let upload_query = BulkUploadQuery { source: Some("import".to_string()), ..Default::default() };
+        let uploaded =
+            bulk_upload(&data_paths, vec![review("Quiet toaster", 4), review("Broken", 9)], upload_query).unwrap();
+        assert_eq!((uploaded.result.successful, uploaded.result.failed.len()), (1, 1));
+        assert_eq!(uploaded.result.failed[0].line_number, 2);
+        assert_eq!((uploaded.starting_vector_index, uploaded.ending_vector_index), (1, Some(1)));
+        assert!(uploaded.job_id.is_some() && uploaded.batch_id.is_some());
+
+        let stored = JsonlStorage::new(&data_paths.reviews_jsonl).read_all_reviews().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].source.as_deref(), Some("import"));
+    }
+
+    #[test]
+    fn test_bulk_delete_reports_ids_it_did_not_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let created = create_reviews(&data_paths, &[review("Great kettle", 5), review("Quiet toaster", 4)]).unwrap();
+        let kept = created.reviews[1].id.clone();
+
+        let delete_request = BulkDeleteRequest {
+            ids: Some(vec![created.reviews[0].id.clone(), "missing".to_string(), "missing".to_string()]),
+            ..Default::default()
+        };
+        let bulk_deleted = bulk_delete(&data_paths, &delete_request).unwrap();
+        assert_eq!(bulk_deleted.deleted.len(), 1);
+        assert!(bulk_deleted.deleted[0].is_deleted());
+        assert_eq!(bulk_deleted.not_found, vec!["missing"]);
+
+        let live = JsonlStorage::new(&data_paths.reviews_jsonl).read_live_reviews().unwrap();
+        assert_eq!(live.iter().map(|review| review.id.as_str()).collect::<Vec<_>>(), vec![kept.as_str()]);
+    }
+
+    #[test]
+    fn test_bulk_upload_of_only_invalid_reviews_to_an_empty_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        let uploaded =
+            bulk_upload(&data_paths, vec![review("Broken", 9), review("", 4)], BulkUploadQuery::default()).unwrap();
+        assert_eq!((uploaded.result.successful, uploaded.result.failed.len()), (0, 2));
+        assert_eq!((uploaded.starting_vector_index, uploaded.ending_vector_index), (0, None));
+        assert!(uploaded.batch_id.is_none());
+        assert_eq!(JsonlStorage::new(&data_paths.reviews_jsonl).count_reviews().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_batch_create_is_all_or_nothing_and_stored_reviews_change_in_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+
+        let rejected = create_reviews(&data_paths, &[review("Great kettle", 5), review("Broken", 9)]);
+        let Err(BatchCreateError::Rejected { problem, errors }) = rejected else { panic!("expected a rejected batch") };
+        assert_eq!((problem, errors.len(), errors[0].0), ("invalid", 1, 1));
+        assert!(matches!(create_reviews(&data_paths, &[]), Err(BatchCreateError::Failed(AppError::Validation(_)))));
+
+        let created = create_reviews(&data_paths, &[review("Great kettle", 5), review("Quiet toaster", 4)]).unwrap();
+        assert_eq!(created.reviews.iter().map(|r| r.vector_index).collect::<Vec<_>>(), vec![0, 1]);
+        let id = created.reviews[0].id.clone();
+
+        let updated = update_review(&data_paths, &id, &review("Decent kettle", 3)).unwrap();
+        assert_eq!((updated.title.as_str(), updated.vector_index), ("Decent kettle", 0));
+        assert_eq!(vote_review(&data_paths, &id, true).unwrap().helpful_votes, 1);
+
+        assert!(delete_review(&data_paths, &id).unwrap().is_deleted());
+        assert!(matches!(delete_review(&data_paths, &id), Err(AppError::NotFound { .. })));
+        assert!(matches!(vote_review(&data_paths, &id, false), Err(AppError::NotFound { .. })));
+        assert!(matches!(update_review(&data_paths, &id, &review("Kettle", 2)), Err(AppError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_parse_bulk_data_reads_jsonl_and_csv() {
+        let jsonl = Value::String(
+            "# template comment\n{\"title\":\"A\",\"body\":\"B\",\"product_id\":\"p\",\"rating\":4}\n".to_string(),
+        );
+        assert_eq!(parse_bulk_data(&jsonl).unwrap()[0].title, "A");
+
+        let csv = Value::String("title,body,product_id,rating\nA,B,p,4\n".to_string());
+        assert_eq!(parse_bulk_data(&csv).unwrap()[0].rating, 4);
+        assert!(parse_bulk_data(&Value::Bool(true)).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Startup repair of the review file, run before the server accepts requests.
+//!
+//! Vector index N must be the Nth review line. Two things break that: a crash in the
+//! middle of an append leaves a torn final line that the next append would be glued
+//! onto, and appends that counted lines outside the lock (before it was held for the
+//! count) could hand two reviews the same index. The fixer drops a torn tail and
+//! renumbers skewed lines, flagging them for re-embedding so a vector search skips
+//! their slots until they are embedded again.
+//!
+//! Lines past the end of reviews.index (a crash before embedding) need no repair: they
+//! are pending and the embedding worker fills them in. Vectors past the last line
+//! (a crash during compaction) are cut off.
+
+use crate::models::*;
+use crate::embedding::EMBEDDING_DIM;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// What the startup check found and repaired
+#[derive(Debug, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// Bytes of a partial final line that were cut off
+    pub torn_tail_bytes: u64,
+    /// Reviews whose vector_index did not match their line and were renumbered
+    pub renumbered: usize,
+    /// Vectors past the last review line that were cut off
+    pub orphaned_vectors: usize,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        *self == IntegrityReport::default()
+    }
+}
+
+/// Check the review file and repair it in place. Takes the exclusive lock, so other
+/// processes sharing the data directory never see a half-repaired file.
+pub fn repair(data_paths: &DataPaths) -> Result<IntegrityReport, AppError> {
+    if !data_paths.reviews_jsonl.exists() {
+        return Ok(IntegrityReport::default());
+    }
+
+    let _lock = FileLock::acquire(&data_paths.lock_file, "startup_integrity_check")?;
+    let mut report = IntegrityReport {
+        torn_tail_bytes: repair_tail(data_paths)?,
+        ..IntegrityReport::default()
+    };
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let reviews = storage.read_all_reviews()?;
+    let vector_store = VectorStore::new(&data_paths.reviews_index, EMBEDDING_DIM);
+    let stored = vector_store.len()?;
+    if stored > reviews.len() {
+        tracing::warn!("Cutting {} vectors with no review line from reviews.index", stored - reviews.len());
+        vector_store.truncate(reviews.len())?;
+        report.orphaned_vectors = stored - reviews.len();
+    }
+
+    let skewed = reviews
+        .iter()
+        .enumerate()
+        .any(|(line, review)| review.vector_index != line);
+    if skewed {
+        let mut line = 0;
+        storage.rewrite_reviews(|mut review| {
+            if review.vector_index != line {
+                tracing::warn!(
+                    "Review {} had vector index {} on line {}; renumbered and queued for re-embedding",
+                    review.id,
+                    review.vector_index,
+                    line
+                );
+                review.vector_index = line;
+                review.needs_reembedding = true;
+                report.renumbered += 1;
+            }
+            line += 1;
+            Some(review)
+        })?;
+    }
+
+    Ok(report)
+}
+
+/// Cut a final line that is not a whole review, or terminate one that is.
+/// Returns the number of bytes removed.
+fn repair_tail(data_paths: &DataPaths) -> Result<u64, AppError> {
+    let contents = std::fs::read(&data_paths.reviews_jsonl)?;
+    if contents.is_empty() || contents.ends_with(b"\n") {
+        return Ok(0);
+    }
+
+    let tail_start = contents.iter().rposition(|&byte| byte == b'\n').map_or(0, |newline| newline + 1);
+    let tail = &contents[tail_start..];
+
+    if serde_json::from_slice::<ReviewMetadata>(tail).is_ok() {
+        // The review was written in full, only its newline is missing
+        let mut file = OpenOptions::new().append(true).open(&data_paths.reviews_jsonl)?;
+        file.write_all(b"\n")?;
+        file.sync_all()?;
+        return Ok(0);
+    }
+
+    tracing::warn!(
+        "Dropping a partial final line ({} bytes) from {}",
+        tail.len(),
+        data_paths.reviews_jsonl.display()
+    );
+    let file = OpenOptions::new().write(true).open(&data_paths.reviews_jsonl)?;
+    file.set_len(tail_start as u64)?;
+    file.sync_all()?;
+    Ok(tail.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn review(id: &str, vector_index: usize) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Integrity review".to_string(),
+            body: "A review body long enough to store.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_repair_drops_torn_tail_and_renumbers_skew() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+
+        // Two appends raced for index 1, then a crash cut the next append short
+        storage.append_reviews(&[review("a", 0), review("b", 1), review("c", 1)]).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&data_paths.reviews_jsonl).unwrap();
+        file.write_all(br#"{"id":"d","title":"Cut of"#).unwrap();
+
+        let report = repair(&data_paths).unwrap();
+        assert_eq!(report.torn_tail_bytes, 25);
+        assert_eq!(report.renumbered, 1);
+
+        let reviews = storage.read_all_reviews().unwrap();
+        let slots: Vec<(&str, usize, bool)> = reviews
+            .iter()
+            .map(|r| (r.id.as_str(), r.vector_index, r.needs_reembedding))
+            .collect();
+        assert_eq!(slots, vec![("a", 0, false), ("b", 1, false), ("c", 2, true)]);
+
+        // A second run finds nothing to do
+        assert!(repair(&data_paths).unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_repair_terminates_whole_final_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let line = serde_json::to_string(&review("a", 0)).unwrap();
+        std::fs::write(&data_paths.reviews_jsonl, &line).unwrap();
+
+        assert!(repair(&data_paths).unwrap().is_clean());
+        assert_eq!(std::fs::read_to_string(&data_paths.reviews_jsonl).unwrap(), format!("{}\n", line));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::lock::FileLock;
+use crate::models::*;
+use crate::storage::{DataPaths, JsonlStorage};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// JSONL file operations for JobRecord (upload history and other recorded jobs)
+pub struct JobStorage {
+    file_path: PathBuf,
+}
+
+impl JobStorage {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Append a job record. The caller must hold the FileLock.
+    pub fn append_job(&self, job: &JobRecord) -> Result<(), AppError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)?;
+
+        let json_line = serde_json::to_string(job)?;
+        writeln!(file, "{}", json_line)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Read all job records in the order they were recorded
+    pub fn read_all_jobs(&self) -> Result<Vec<JobRecord>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+
+        let mut jobs = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                jobs.push(serde_json::from_str(&line)?);
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    /// Read a page of jobs, newest first, optionally filtered by type.
+    /// Returns the page and the total number of matching jobs.
+    pub fn list_jobs(
+        &self,
+        job_type: Option<JobType>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<JobRecord>, usize), AppError> {
+        let matching: Vec<JobRecord> = self
+            .read_all_jobs()?
+            .into_iter()
+            .rev()
+            .filter(|job| job_type.is_none_or(|t| job.job_type == t))
+            .collect();
+
+        let total = matching.len();
+        let page = matching.into_iter().skip(offset).take(limit).collect();
+
+        Ok((page, total))
+    }
+
+    /// Find a job by id
+    pub fn get_job(&self, id: &str) -> Result<Option<JobRecord>, AppError> {
+        Ok(self.read_all_jobs()?.into_iter().find(|job| job.id == id))
+    }
+
+    /// Atomically replace the stored record with the same id. The caller must hold the FileLock.
+    pub fn update_job(&self, updated: &JobRecord) -> Result<(), AppError> {
+        let jobs = self.read_all_jobs()?;
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for job in jobs {
+                let job = if job.id == updated.id { updated.clone() } else { job };
+                writeln!(writer, "{}", serde_json::to_string(&job)?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+
+        std::fs::rename(&temp_path, &self.file_path)?;
+        Ok(())
+    }
+}
+
+/// A bulk upload job once rolled back
+#[derive(Debug)]
+pub struct RolledBack {
+    pub job: JobRecord,
+    /// Reviews from the upload that were tombstoned
+    pub reviews_rolled_back: usize,
+}
+
+/// Tombstone the reviews a bulk upload stored and mark its job rolled back. Other jobs,
+/// and uploads already rolled back, are a conflict.
+pub fn rollback(data_paths: &DataPaths, job_id: &str) -> Result<RolledBack, AppError> {
+    data_paths.ensure_directories()?;
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let job_storage = JobStorage::new(&data_paths.jobs_jsonl);
+    let _lock = FileLock::acquire(&data_paths.lock_file, "rollback_job")?;
+
+    let mut job = job_storage.get_job(job_id)?.ok_or_else(|| AppError::NotFound {
+        resource: "Job".to_string(),
+        id: job_id.to_string(),
+    })?;
+    if job.job_type != JobType::BulkUpload {
+        return Err(AppError::Conflict {
+            message: format!("Job {} is not a bulk upload and cannot be rolled back", job.id),
+        });
+    }
+    if job.status == JobStatus::RolledBack {
+        return Err(AppError::Conflict {
+            message: format!("Job {} has already been rolled back", job.id),
+        });
+    }
+
+    // Tombstone the batch's reviews; their vector slots stay reserved
+    let reviews_rolled_back = match &job.batch_id {
+        Some(batch_id) => jsonl_storage.rollback_batch(batch_id)?,
+        None => 0,
+    };
+
+    job.status = JobStatus::RolledBack;
+    job.rolled_back_at = Some(chrono::Utc::now());
+    job_storage.update_job(&job)?;
+
+    tracing::info!("Rolled back job {}: {} reviews tombstoned", job.id, reviews_rolled_back);
+    Ok(RolledBack { job, reviews_rolled_back })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn create_test_job(id: &str) -> JobRecord {
+        JobRecord {
+            id: id.to_string(),
+            job_type: JobType::BulkUpload,
+            status: JobStatus::Completed,
+            created_at: Utc::now(),
+            file_name: Some(format!("{}.csv", id)),
+            total_processed: 3,
+            successful: 2,
+            failed: 1,
+            batch_id: Some(format!("batch_{}", id)),
+            rolled_back_at: None,
+            progress: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_list_and_update_jobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = JobStorage::new(temp_dir.path().join("jobs.jsonl"));
+
+        assert_eq!(storage.list_jobs(None, 0, 10).unwrap().1, 0);
+
+        for id in ["job_1", "job_2", "job_3"] {
+            storage.append_job(&create_test_job(id)).unwrap();
+        }
+
+        // Newest first, paginated
+        let (page, total) = storage.list_jobs(Some(JobType::BulkUpload), 1, 1).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, "job_2");
+
+        let mut job = storage.get_job("job_1").unwrap().unwrap();
+        job.status = JobStatus::RolledBack;
+        job.rolled_back_at = Some(Utc::now());
+        storage.update_job(&job).unwrap();
+
+        let reloaded = storage.get_job("job_1").unwrap().unwrap();
+        assert_eq!(reloaded.status, JobStatus::RolledBack);
+        assert_eq!(storage.read_all_jobs().unwrap().len(), 3);
+        assert!(storage.get_job("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rollback_tombstones_the_upload_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let review_data = |title: &str| ReviewData {
+            title: title.to_string(),
+            body: format!("The {} works as described.", title),
+            product_id: "prod_1".to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        let reviews = JsonlStorage::new(&data_paths.reviews_jsonl);
+        let upload = vec![review_data("Kettle"), review_data("Toaster")];
+        let job_id = crate::ingest::bulk_upload(&data_paths, upload, Default::default()).unwrap().job_id.unwrap();
+        crate::ingest::create_review(&data_paths, &review_data("Blender"), None).unwrap();
+
+        let rolled_back = rollback(&data_paths, &job_id).unwrap();
+        assert_eq!(rolled_back.reviews_rolled_back, 2);
+        assert_eq!(rolled_back.job.status, JobStatus::RolledBack);
+        assert_eq!(reviews.count_live_reviews().unwrap(), 1);
+
+        assert!(matches!(rollback(&data_paths, &job_id), Err(AppError::Conflict { .. })));
+        assert!(matches!(rollback(&data_paths, "missing"), Err(AppError::NotFound { .. })));
+    }
+}
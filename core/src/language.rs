@@ -0,0 +1,144 @@
+//! Language detection: the language a review is written in, tagged at ingest.
+//!
+//! Reviews arrive mostly in Thai and English, with some Chinese, Japanese and Korean.
+//! These are told apart by script, which needs no model and is reliable for them: each
+//! letter counts for its script and the script with the most letters decides, so an
+//! English product name inside a Thai review leaves it Thai. A Han, kana or Hangul
+//! character counts as `SYLLABLE_WEIGHT` letters, since it writes a whole syllable.
+//! Japanese mixes kana with Han, so Han text containing any kana is Japanese and
+//! Chinese otherwise. Latin text is taken as English, the only Latin-script language in
+//! the catalog; text in any other script, or with no letters at all, gets no language.
+//!
+//! Every review is tagged as it is created or updated. Reviews stored before tagging
+//! existed are detected when a search filters on language, so the filter covers them.
+
+use crate::models::ReviewMetadata;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Letters a Han, kana or Hangul character counts as: each writes a syllable or a
+/// word, where Latin and Thai need several letters
+const SYLLABLE_WEIGHT: usize = 3;
+
+/// A review's language, as its ISO 639-1 code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum Language {
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "th")]
+    Thai,
+    #[serde(rename = "zh")]
+    Chinese,
+    #[serde(rename = "ja")]
+    Japanese,
+    #[serde(rename = "ko")]
+    Korean,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] =
+        [Language::English, Language::Thai, Language::Chinese, Language::Japanese, Language::Korean];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Thai => "th",
+            Language::Chinese => "zh",
+            Language::Japanese => "ja",
+            Language::Korean => "ko",
+        }
+    }
+
+    /// A language from its code, or its English name
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code() == value || format!("{:?}", language).to_ascii_lowercase() == value)
+    }
+}
+
+/// Scripts the detector counts letters of
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Thai,
+    Han,
+    Kana,
+    Hangul,
+}
+
+fn script(c: char) -> Option<Script> {
+    match c {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Some(Script::Latin),
+        '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' => Some(Script::Latin), // Full-width Latin
+        '\u{0E00}'..='\u{0E7F}' => Some(Script::Thai),
+        '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => Some(Script::Kana),
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2FA1F}' => {
+            Some(Script::Han)
+        }
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => Some(Script::Hangul),
+        _ => None,
+    }
+}
+
+/// The language of `text`, if its letters are in a script of a known language
+pub fn detect(text: &str) -> Option<Language> {
+    let (mut latin, mut thai, mut han, mut kana, mut hangul) = (0, 0, 0, 0, 0);
+    for c in text.chars() {
+        match script(c) {
+            Some(Script::Latin) => latin += 1,
+            Some(Script::Thai) => thai += 1,
+            Some(Script::Han) => han += 1,
+            Some(Script::Kana) => kana += 1,
+            Some(Script::Hangul) => hangul += 1,
+            None => {}
+        }
+    }
+
+    // Han and kana count together: both are needed to write Japanese
+    let cjk = han + kana;
+    let counts = [
+        (latin, Language::English),
+        (thai, Language::Thai),
+        (cjk * SYLLABLE_WEIGHT, if kana > 0 { Language::Japanese } else { Language::Chinese }),
+        (hangul * SYLLABLE_WEIGHT, Language::Korean),
+    ];
+    // The last of equal counts wins, so ties go to the non-Latin script
+    counts
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .max_by_key(|(count, _)| *count)
+        .map(|(_, language)| language)
+}
+
+/// The language of a review from its title and body
+pub fn of_text(title: &str, body: &str) -> Option<Language> {
+    detect(&format!("{} {}", title, body))
+}
+
+/// A review's language: its stored tag, or its text detected now when untagged
+pub fn of(review: &ReviewMetadata) -> Option<Language> {
+    review.language.or_else(|| of_text(&review.title, &review.body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_goes_by_the_dominant_script() {
+        assert_eq!(detect("Great kettle, boils fast"), Some(Language::English));
+        // "Battery lasts a long time": the Latin product name does not outweigh the Thai
+        assert_eq!(detect("แบตเตอรี่ของ iPhone ใช้งานได้นานมาก"), Some(Language::Thai));
+        assert_eq!(detect("电池很好"), Some(Language::Chinese));
+        assert_eq!(detect("iPhone用のケース"), Some(Language::Japanese));
+        assert_eq!(detect("배터리가 좋아요"), Some(Language::Korean));
+        assert_eq!(detect("Отличный чайник"), None);
+        assert_eq!(detect("10/10 !!"), None);
+
+        assert_eq!(Language::parse(" TH"), Some(Language::Thai));
+        assert_eq!(Language::parse("english"), Some(Language::English));
+        assert_eq!(Language::parse("klingon"), None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Review storage, ingest and search, with no transport attached.
+//!
+//! Everything a review goes through between a request and the data directory lives
+//! here: validation, the blocklist and corpus limits, reviews.jsonl and the vector
+//! store, the embedding worker, and ranking. `ingest` and `search` are the entry
+//! points the HTTP API, the gRPC server and tests call alike; they take a `DataPaths`
+//! and return an `AppError` on failure, leaving status codes, headers, events and
+//! metrics to the interface serving the request.
+
+pub mod analytics;
+pub mod arrow_ipc;
+pub mod ask;
+pub mod blocklist;
+pub mod canary;
+pub mod collections;
+pub mod compaction;
+pub mod corpus_limits;
+pub mod curation;
+pub mod dataset_meta;
+pub mod duplicates;
+pub mod embedding;
+pub mod embedding_queue;
+pub mod embedding_worker;
+pub mod error_catalog;
+pub mod expansion;
+pub mod explain;
+pub mod fallback;
+pub mod export;
+pub mod file_demo;
+pub mod health;
+pub mod idempotency;
+pub mod ingest;
+pub mod integrity;
+pub mod jobs;
+pub mod language;
+pub mod lock;
+pub mod models;
+pub mod personalization;
+pub mod projection;
+pub mod query_log;
+pub mod query_syntax;
+pub mod reindex;
+pub mod sampling;
+pub mod sandbox;
+pub mod search;
+pub mod sentiment;
+pub mod similar;
+pub mod similarity_matrix;
+pub mod spelling;
+pub mod storage;
+pub mod suggest;
+pub mod synonyms;
+pub mod templates;
+pub mod tokenizer;
+pub mod vectors;
@@ -0,0 +1,596 @@
+use crate::models::*;
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// How long writers wait for the data lock when `LOCK_TIMEOUT_MS` is not set
+pub const DEFAULT_LOCK_TIMEOUT_MS: u64 = 5_000;
+
+/// Suggested wait before retrying after a lock timeout
+pub const LOCK_RETRY_AFTER_SECS: u64 = 1;
+
+/// Polling backoff while the lock is held elsewhere
+const POLL_INTERVAL_MIN: Duration = Duration::from_millis(5);
+const POLL_INTERVAL_MAX: Duration = Duration::from_millis(100);
+
+/// Who holds the data lock; written into the lock file so other processes can report it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LockHolder {
+    pub operation: String,
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// Lock contention counters for this process since startup
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct LockMetrics {
+    pub acquisitions: u64,
+    /// Acquisitions in shared (reader) mode, included in `acquisitions`
+    pub shared_acquisitions: u64,
+    /// Acquisitions that had to wait for another holder
+    pub contended_acquisitions: u64,
+    pub timeouts: u64,
+    /// Read-side try-locks that found the lock taken and went ahead without it
+    pub try_lock_misses: u64,
+    pub total_wait_ms: u64,
+    pub max_wait_ms: u64,
+    /// Readers in this process currently holding the shared lock
+    pub active_readers: u64,
+    /// The writer currently holding the exclusive lock
+    pub current_holder: Option<LockHolder>,
+    /// Lock files recovered after a writer ended without releasing them
+    pub stale_locks_recovered: u64,
+}
+
+static METRICS: Mutex<LockMetrics> = Mutex::new(LockMetrics {
+    acquisitions: 0,
+    shared_acquisitions: 0,
+    contended_acquisitions: 0,
+    timeouts: 0,
+    try_lock_misses: 0,
+    total_wait_ms: 0,
+    max_wait_ms: 0,
+    active_readers: 0,
+    current_holder: None,
+    stale_locks_recovered: 0,
+});
+
+fn with_metrics(update: impl FnOnce(&mut LockMetrics)) {
+    // Metrics are best effort; a poisoned mutex still holds usable counters
+    let mut metrics = METRICS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    update(&mut metrics);
+}
+
+/// Snapshot of the lock metrics
+pub fn lock_metrics() -> LockMetrics {
+    METRICS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Writer lock timeout from `LOCK_TIMEOUT_MS`, falling back to the default
+pub fn lock_timeout() -> Duration {
+    let millis = std::env::var("LOCK_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT_MS);
+    Duration::from_millis(millis)
+}
+
+/// How the data lock is held: any number of readers share it, writers hold it alone
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// File locking utilities for concurrent access
+pub struct FileLock {
+    lock_file: PathBuf,
+    mode: LockMode,
+    _lock: File,
+}
+
+impl FileLock {
+    /// Take the exclusive data lock for `operation`, waiting up to the configured timeout
+    pub fn acquire<P: AsRef<Path>>(lock_file: P, operation: &str) -> Result<Self, AppError> {
+        Self::acquire_with_timeout(lock_file, operation, LockMode::Exclusive, lock_timeout())
+    }
+
+    /// Take the data lock for reading, alongside other readers, waiting up to the configured
+    /// timeout for any writer to finish
+    pub fn acquire_shared<P: AsRef<Path>>(lock_file: P, operation: &str) -> Result<Self, AppError> {
+        Self::acquire_with_timeout(lock_file, operation, LockMode::Shared, lock_timeout())
+    }
+
+    /// Take the data lock in `mode`, giving up with `AppError::LockTimeout` after `timeout`
+    pub fn acquire_with_timeout<P: AsRef<Path>>(
+        lock_file: P,
+        operation: &str,
+        mode: LockMode,
+        timeout: Duration,
+    ) -> Result<Self, AppError> {
+        let lock_file = lock_file.as_ref().to_path_buf();
+        let mut file = open_lock_file(&lock_file)?;
+
+        let started = Instant::now();
+        let mut delay = POLL_INTERVAL_MIN;
+        let mut checked_holder = false;
+        while !try_lock(&file, mode)? {
+            // A lock still held for a writer that is gone can only be broken
+            if !checked_holder {
+                checked_holder = true;
+                let status = inspect(&lock_file)?;
+                if let (LockState::Exclusive, true, Some(holder)) = (status.state, status.stale, status.holder) {
+                    tracing::warn!(
+                        "Breaking {:?}: still held for {} by process {}, which is no longer running (since {})",
+                        lock_file,
+                        holder.operation,
+                        holder.pid,
+                        holder.acquired_at
+                    );
+                    replace_lock_file(&lock_file)?;
+                    with_metrics(|metrics| metrics.stale_locks_recovered += 1);
+                    file = open_lock_file(&lock_file)?;
+                    continue;
+                }
+            }
+            let waited = started.elapsed();
+            if waited >= timeout {
+                let holder = read_holder(&file);
+                with_metrics(|metrics| metrics.timeouts += 1);
+                tracing::warn!(
+                    "Timed out after {:?} waiting for {:?} to {} (holder: {:?})",
+                    waited,
+                    lock_file,
+                    operation,
+                    holder
+                );
+                return Err(AppError::LockTimeout {
+                    waited_ms: waited.as_millis() as u64,
+                    holder,
+                });
+            }
+            std::thread::sleep(delay.min(timeout - waited));
+            delay = (delay * 2).min(POLL_INTERVAL_MAX);
+            // Reopen, in case the lock file was broken and replaced meanwhile
+            file = open_lock_file(&lock_file)?;
+        }
+
+        let waited_ms = started.elapsed().as_millis() as u64;
+        with_metrics(|metrics| {
+            if waited_ms > 0 {
+                metrics.contended_acquisitions += 1;
+            }
+            metrics.total_wait_ms += waited_ms;
+            metrics.max_wait_ms = metrics.max_wait_ms.max(waited_ms);
+        });
+
+        Ok(Self::held(lock_file, file, operation, mode))
+    }
+
+    /// Take the shared lock only if no writer holds it right now. Read operations use this as
+    /// a fast path: `None` means a writer holds it, and the caller goes ahead without it.
+    pub fn try_acquire<P: AsRef<Path>>(lock_file: P, operation: &str) -> Result<Option<Self>, AppError> {
+        let lock_file = lock_file.as_ref().to_path_buf();
+        let file = open_lock_file(&lock_file)?;
+
+        if !try_lock(&file, LockMode::Shared)? {
+            with_metrics(|metrics| metrics.try_lock_misses += 1);
+            return Ok(None);
+        }
+
+        Ok(Some(Self::held(lock_file, file, operation, LockMode::Shared)))
+    }
+
+    /// Record the new holder in the metrics, and for writers in the lock file
+    fn held(lock_file: PathBuf, mut file: File, operation: &str, mode: LockMode) -> Self {
+        // Writers clear their record on release, so one found now outlived its writer
+        if let Some(stale) = read_holder(&file) {
+            tracing::warn!(
+                "Recovered {:?} from {} (process {}, since {}), which ended without releasing it",
+                lock_file,
+                stale.operation,
+                stale.pid,
+                stale.acquired_at
+            );
+            let _ = file.set_len(0);
+            with_metrics(|metrics| metrics.stale_locks_recovered += 1);
+        }
+
+        match mode {
+            LockMode::Shared => with_metrics(|metrics| {
+                metrics.acquisitions += 1;
+                metrics.shared_acquisitions += 1;
+                metrics.active_readers += 1;
+            }),
+            LockMode::Exclusive => {
+                let holder = LockHolder {
+                    operation: operation.to_string(),
+                    pid: std::process::id(),
+                    acquired_at: Utc::now(),
+                };
+
+                // Holder details are informational; failing to write them must not fail the operation
+                if let Ok(json) = serde_json::to_vec(&holder) {
+                    let _ = file.set_len(0).and_then(|_| file.write_all(&json));
+                }
+                with_metrics(|metrics| {
+                    metrics.acquisitions += 1;
+                    metrics.current_holder = Some(holder);
+                });
+            }
+        }
+
+        Self {
+            lock_file,
+            mode,
+            _lock: file,
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        match self.mode {
+            LockMode::Shared => with_metrics(|metrics| metrics.active_readers = metrics.active_readers.saturating_sub(1)),
+            LockMode::Exclusive => {
+                let _ = self._lock.set_len(0);
+                with_metrics(|metrics| metrics.current_holder = None);
+            }
+        }
+        let _ = FileExt::unlock(&self._lock);
+        tracing::debug!("Released {:?} file lock {:?}", self.mode, self.lock_file);
+    }
+}
+
+/// Check that the filesystem holding `lock_file` enforces advisory locks between open
+/// handles. Several server processes may share one data directory only because writers
+/// exclude each other through this lock; on filesystems where locking is a silent no-op
+/// (some network mounts) they would corrupt the review file, so the server refuses to start.
+pub fn verify_lock_support<P: AsRef<Path>>(lock_file: P) -> Result<(), AppError> {
+    let held = FileLock::acquire(&lock_file, "startup_lock_check")?;
+    let second_handle = open_lock_file(lock_file.as_ref())?;
+
+    let enforced = !try_lock(&second_handle, LockMode::Shared)?;
+    if !enforced {
+        let _ = FileExt::unlock(&second_handle);
+    }
+    drop(held);
+
+    if enforced {
+        Ok(())
+    } else {
+        Err(AppError::Concurrency {
+            message: format!(
+                "File locks on {:?} are not enforced; the data directory must be on a local filesystem with flock support",
+                lock_file.as_ref()
+            ),
+        })
+    }
+}
+
+/// Whether process `pid` on this host is running; `None` where that can't be told
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> Option<bool> {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return Some(false);
+    };
+    if pid <= 0 {
+        return Some(false);
+    }
+    // Signal 0 only checks that the process exists
+    // SAFETY: kill with signal 0 sends nothing and touches no memory
+    match unsafe { libc::kill(pid, 0) } {
+        0 => Some(true),
+        _ => match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => Some(false),
+            // EPERM: it exists, under another user
+            _ => Some(true),
+        },
+    }
+}
+
+#[cfg(not(unix))]
+pub fn process_alive(pid: u32) -> Option<bool> {
+    (pid == std::process::id()).then_some(true)
+}
+
+fn holder_alive(holder: &LockHolder) -> Option<bool> {
+    match holder.pid == std::process::id() {
+        true => Some(true),
+        false => process_alive(holder.pid),
+    }
+}
+
+/// How the data lock is held right now
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LockState {
+    Free,
+    /// Held by readers
+    Shared,
+    /// Held by a writer
+    Exclusive,
+}
+
+/// The data lock as any process sees it
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct LockStatus {
+    pub state: LockState,
+    /// Writer recorded in the lock file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<LockHolder>,
+    /// Whether the recorded writer is still running; absent when it can't be told
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder_alive: Option<bool>,
+    /// The record outlived its writer: the next acquisition recovers it, or for a lock
+    /// still held, breaking it does
+    pub stale: bool,
+}
+
+/// Query of `POST /admin/lock/break`
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BreakLockQuery {
+    /// Break the lock even though its writer is still running
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Look at the data lock without waiting for it
+pub fn inspect<P: AsRef<Path>>(lock_file: P) -> Result<LockStatus, AppError> {
+    let file = open_lock_file(lock_file.as_ref())?;
+    let holder = read_holder(&file);
+    let state = if try_lock(&file, LockMode::Exclusive)? {
+        LockState::Free
+    } else if try_lock(&file, LockMode::Shared)? {
+        LockState::Shared
+    } else {
+        LockState::Exclusive
+    };
+    let _ = FileExt::unlock(&file);
+
+    let holder_alive = holder.as_ref().and_then(holder_alive);
+    let stale = match state {
+        // Nothing holds the lock, so a recorded writer is gone
+        LockState::Free | LockState::Shared => holder.is_some(),
+        LockState::Exclusive => holder_alive == Some(false),
+    };
+    Ok(LockStatus { state, holder, holder_alive, stale })
+}
+
+/// Break the data lock by replacing the lock file, so new acquisitions no longer wait
+/// for its holder. Only a lock held for a writer is broken: one whose writer is gone,
+/// or with `force` one whose writer is running elsewhere (it then writes unprotected,
+/// so only force a writer known to be hung). A lock held by this process is never
+/// broken. Returns the status before, and whether the lock was broken.
+pub fn break_lock<P: AsRef<Path>>(lock_file: P, force: bool) -> Result<(LockStatus, bool), AppError> {
+    let lock_file = lock_file.as_ref();
+    let status = inspect(lock_file)?;
+    if status.state != LockState::Exclusive {
+        return Ok((status, false));
+    }
+
+    match (&status.holder, status.holder_alive) {
+        (Some(holder), _) if holder.pid == std::process::id() => {
+            return Err(AppError::Conflict {
+                message: format!("The data lock is held by this server for {}; it is released when that finishes", holder.operation),
+            })
+        }
+        (_, Some(false)) => {}
+        (Some(holder), _) if !force => {
+            return Err(AppError::Conflict {
+                message: format!(
+                    "The data lock is held for {} by process {}, which is still running; pass force=true to break it anyway",
+                    holder.operation, holder.pid
+                ),
+            })
+        }
+        (None, _) if !force => {
+            return Err(AppError::Conflict {
+                message: "The data lock is held by a process that left no record; pass force=true to break it anyway".to_string(),
+            })
+        }
+        _ => {}
+    }
+
+    tracing::warn!("Breaking {:?} on request (holder: {:?}, force: {})", lock_file, status.holder, force);
+    replace_lock_file(lock_file)?;
+    with_metrics(|metrics| metrics.stale_locks_recovered += 1);
+    Ok((status, true))
+}
+
+/// Swap in a fresh lock file. Locks belong to the old file, so whoever holds them no
+/// longer excludes anyone.
+fn replace_lock_file(lock_file: &Path) -> Result<(), AppError> {
+    match std::fs::remove_file(lock_file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    open_lock_file(lock_file)?;
+    Ok(())
+}
+
+fn open_lock_file(lock_file: &Path) -> Result<File, AppError> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(lock_file)?)
+}
+
+/// Non-blocking lock attempt; `false` when a conflicting holder has it
+fn try_lock(file: &File, mode: LockMode) -> Result<bool, AppError> {
+    let result = match mode {
+        LockMode::Shared => FileExt::try_lock_shared(file),
+        LockMode::Exclusive => FileExt::try_lock_exclusive(file),
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => Ok(false),
+        Err(e) => Err(AppError::Concurrency {
+            message: format!("Failed to acquire file lock: {}", e),
+        }),
+    }
+}
+
+/// The holder recorded in the lock file, if it can be read
+fn read_holder(mut file: &File) -> Option<LockHolder> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_times_out_and_reports_holder() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join(".lock");
+
+        let held = FileLock::acquire(&lock_path, "bulk_upload").unwrap();
+
+        let result = FileLock::acquire_with_timeout(
+            &lock_path,
+            "create_review",
+            LockMode::Exclusive,
+            Duration::from_millis(30),
+        );
+        match result {
+            Err(AppError::LockTimeout { waited_ms, holder }) => {
+                assert!(waited_ms >= 30);
+                let holder = holder.unwrap();
+                assert_eq!(holder.operation, "bulk_upload");
+                assert_eq!(holder.pid, std::process::id());
+            }
+            other => panic!("expected a lock timeout, got {:?}", other.map(|_| ())),
+        }
+
+        // Readers don't wait for the writer
+        assert!(FileLock::try_acquire(&lock_path, "stats").unwrap().is_none());
+
+        drop(held);
+        assert!(FileLock::try_acquire(&lock_path, "stats").unwrap().is_some());
+        assert!(lock_metrics().timeouts >= 1);
+    }
+
+    /// The pid of a process that has exited
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    /// Hold `lock_path` exclusively through a handle of this process, recorded as `holder`
+    fn hold_as(lock_path: &Path, holder: &LockHolder) -> File {
+        let mut file = open_lock_file(lock_path).unwrap();
+        assert!(try_lock(&file, LockMode::Exclusive).unwrap());
+        file.write_all(&serde_json::to_vec(holder).unwrap()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_stale_locks_are_recovered() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join(".lock");
+        let crashed = LockHolder {
+            operation: "bulk_upload".to_string(),
+            pid: dead_pid(),
+            acquired_at: Utc::now(),
+        };
+        assert_eq!(process_alive(crashed.pid), Some(false));
+        assert_eq!(process_alive(std::process::id()), Some(true));
+
+        // The writer died and its lock went with it, but not its record
+        std::fs::write(&lock_path, serde_json::to_vec(&crashed).unwrap()).unwrap();
+        let status = inspect(&lock_path).unwrap();
+        assert_eq!((status.state, status.stale), (LockState::Free, true));
+        let recovered_before = lock_metrics().stale_locks_recovered;
+        let reader = FileLock::acquire_shared(&lock_path, "search").unwrap();
+        assert!(lock_metrics().stale_locks_recovered > recovered_before);
+        drop(reader);
+        assert!(!inspect(&lock_path).unwrap().stale);
+
+        // The lock outlived the writer (here: a handle still open); the next writer breaks it
+        let orphaned = hold_as(&lock_path, &crashed);
+        let status = inspect(&lock_path).unwrap();
+        assert_eq!((status.state, status.holder_alive, status.stale), (LockState::Exclusive, Some(false), true));
+        let writer = FileLock::acquire_with_timeout(&lock_path, "create_review", LockMode::Exclusive, Duration::from_secs(5));
+        assert!(writer.is_ok());
+        drop(writer);
+        drop(orphaned);
+    }
+
+    #[test]
+    fn test_break_lock_only_breaks_what_is_safe() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join(".lock");
+        let holder = |pid| LockHolder {
+            operation: "reindex".to_string(),
+            pid,
+            acquired_at: Utc::now(),
+        };
+
+        // Nothing to break
+        let (status, broken) = break_lock(&lock_path, true).unwrap();
+        assert_eq!((status.state, broken), (LockState::Free, false));
+
+        // A running writer in another process needs force; pid 1 always runs
+        let running = hold_as(&lock_path, &holder(1));
+        assert!(matches!(break_lock(&lock_path, false), Err(AppError::Conflict { .. })));
+        let (status, broken) = break_lock(&lock_path, true).unwrap();
+        assert_eq!((status.holder.unwrap().pid, broken), (1, true));
+        assert_eq!(inspect(&lock_path).unwrap().state, LockState::Free);
+        drop(running);
+
+        // A dead writer's lock is broken without force; this server's own never is
+        let orphaned = hold_as(&lock_path, &holder(dead_pid()));
+        assert!(break_lock(&lock_path, false).unwrap().1);
+        drop(orphaned);
+        let ours = FileLock::acquire(&lock_path, "compaction").unwrap();
+        assert!(matches!(break_lock(&lock_path, true), Err(AppError::Conflict { .. })));
+        drop(ours);
+    }
+
+    #[test]
+    fn test_lock_support_is_detected() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(verify_lock_support(temp_dir.path().join(".lock")).is_ok());
+    }
+
+    #[test]
+    fn test_readers_share_and_exclude_writers() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join(".lock");
+        let short = Duration::from_millis(20);
+
+        let first_reader = FileLock::acquire_shared(&lock_path, "search").unwrap();
+        let second_reader = FileLock::acquire_with_timeout(&lock_path, "search", LockMode::Shared, short).unwrap();
+
+        let writer = FileLock::acquire_with_timeout(&lock_path, "create_review", LockMode::Exclusive, short);
+        assert!(matches!(writer, Err(AppError::LockTimeout { .. })));
+
+        drop(first_reader);
+        drop(second_reader);
+        let writer = FileLock::acquire_with_timeout(&lock_path, "create_review", LockMode::Exclusive, short).unwrap();
+
+        let reader = FileLock::acquire_with_timeout(&lock_path, "search", LockMode::Shared, short);
+        match reader {
+            Err(AppError::LockTimeout { holder, .. }) => assert_eq!(holder.unwrap().operation, "create_review"),
+            other => panic!("expected a lock timeout, got {:?}", other.map(|_| ())),
+        }
+        drop(writer);
+    }
+}
@@ -0,0 +1,1890 @@
+use crate::error_catalog::ErrorCode;
+use crate::language::Language;
+use crate::sentiment::Sentiment;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+/// Core review data structure for input
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReviewData {
+    pub title: String,
+    pub body: String,
+    pub product_id: String,
+    pub rating: u8, // 1-5 scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer_id: Option<String>, // Anonymous client/reviewer identifier
+    /// Where an imported review came from, such as a site or dataset name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// License the review's text is under, such as "CC-BY-4.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Credit shown with the review; required when the license requires attribution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+}
+
+/// Review metadata stored in JSONL file
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReviewMetadata {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub product_id: String,
+    pub rating: u8,
+    pub timestamp: DateTime<Utc>,
+    pub vector_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer_id: Option<String>,
+    /// Where an imported review came from, such as a site or dataset name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// License the review's text is under, such as "CC-BY-4.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Credit shown with the review; required when the license requires attribution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>, // Tombstone marker; the line keeps its vector_index slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>, // Bulk upload batch this review arrived in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_reembedding: bool, // Text changed since the vector slot was last embedded
+    #[serde(default)]
+    pub helpful_votes: u32,
+    #[serde(default)]
+    pub unhelpful_votes: u32,
+    /// How the text reads, tagged at ingest; absent on reviews stored before tagging or with it off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<Sentiment>,
+    /// Language of the text, detected at ingest; absent on reviews stored before detection
+    /// or in a language it does not know
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    /// Id of the review of the same product this one nearly repeats, flagged at ingest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+/// Search result with similarity score
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchResult {
+    pub review: ReviewMetadata,
+    pub similarity_score: f32,
+    /// With `group_by_product`, how many of the product's reviews matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_hits: Option<usize>,
+    /// With `explain`, how `similarity_score` was reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<crate::explain::ScoreExplanation>,
+    /// With `collections`, the collection the review belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+}
+
+impl SearchResult {
+    /// Ranking order: best score first, ties going to the earlier stored review (then
+    /// the lower id, across shards), so identical searches order and page the same way
+    pub fn rank_order(a: &SearchResult, b: &SearchResult) -> std::cmp::Ordering {
+        b.similarity_score
+            .partial_cmp(&a.similarity_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.review.vector_index.cmp(&b.review.vector_index))
+            .then_with(|| a.review.id.cmp(&b.review.id))
+    }
+
+    /// Keep each product's first result, in ranking order, counting the results it
+    /// stands for. Results already grouped (by a shard) count as their `product_hits`.
+    pub fn group_by_product(ranked: Vec<SearchResult>) -> Vec<SearchResult> {
+        let mut grouped: Vec<SearchResult> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for result in ranked {
+            let hits = result.product_hits.unwrap_or(1);
+            match positions.get(&result.review.product_id) {
+                Some(&position) => *grouped[position].product_hits.get_or_insert(0) += hits,
+                None => {
+                    positions.insert(result.review.product_id.clone(), grouped.len());
+                    grouped.push(SearchResult { product_hits: Some(hits), ..result });
+                }
+            }
+        }
+        grouped
+    }
+}
+
+/// Longest query a search accepts, in bytes
+pub const MAX_QUERY_LENGTH: usize = 500;
+
+/// Most results one search may ask for
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Deepest a search can page: `offset + limit` may not go past this result
+pub const MAX_SEARCH_DEPTH: usize = 1000;
+
+/// Matches a search grouped by product ranks and counts, so products past the first
+/// page are still found and their hit counts are not cut off at the page
+pub const GROUPED_SEARCH_WINDOW: usize = 10_000;
+
+/// Search request structure
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchRequest {
+    pub query: String,
+    pub limit: Option<usize>, // Default: 10
+    /// Results to skip, for paging past the first `limit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// `next_cursor` of the previous page, in place of `offset`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<SearchFilters>,
+    /// Blend helpful votes into the ranking, so well-rated answers rise among similar matches
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rank_by_helpfulness: bool,
+    /// Overrides of the scoring weights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<RankingWeights>,
+    /// Client session whose clicked results (`POST /search/clicks`) boost similar reviews
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Set to false to rank without the session's clicks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub personalize: Option<bool>,
+    /// Set to false to rank without curation rules (pins and boosts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curate: Option<bool>,
+    /// Set to false to search the query as typed, without correcting misspelled words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correct_typos: Option<bool>,
+    /// Show each product at most once, by its best review, with its `product_hits`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub group_by_product: bool,
+    /// Review fields the query is scored against; both when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<SearchField>,
+    /// Break down each result's score in its `explanation`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub explain: bool,
+    /// Lowest score a result may have (0 to 1); weaker matches are left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    /// Set to false to return no results rather than relax a search that finds none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<bool>,
+    /// Collections to search with their rankings merged, `*` for all of them; the
+    /// default collection alone when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<String>,
+}
+
+/// A review field a search can be restricted to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Title,
+    Body,
+}
+
+impl SearchField {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "title" => Some(SearchField::Title),
+            "body" => Some(SearchField::Body),
+            _ => None,
+        }
+    }
+}
+
+/// Restrictions applied to reviews before they are ranked
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SearchFilters {
+    pub product_id: Option<String>,
+    pub min_rating: Option<u8>,
+    pub max_rating: Option<u8>,
+    /// Earliest review timestamp, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_from: Option<DateTime<Utc>>,
+    /// Latest review timestamp, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_to: Option<DateTime<Utc>>,
+    /// Only reviews whose text reads this way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<Sentiment>,
+    /// Only reviews written in this language
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+}
+
+/// Scoring weights a search may set. Omitted weights take their default and
+/// out-of-range ones are clamped, so any values rank without an error.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RankingWeights {
+    /// Score for each query word found in the title (default 0.8, 0 to 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_weight: Option<f32>,
+    /// Score for each query word found only in the body (default 0.5, 0 to 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_weight: Option<f32>,
+    /// Score per star above or below 3 for keyword matches (default 0.1, 0 to 0.5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating_boost: Option<f32>,
+    /// Share of the score given to how recent a review is (default 0, 0 to 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recency_boost: Option<f32>,
+}
+
+/// Response of both search endpoints
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchResponse {
+    pub success: bool,
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub total_results: usize,
+    pub limit: usize,
+    /// Position of the first result in the full ranking
+    #[serde(default)]
+    pub offset: usize,
+    /// Cursor for the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<SearchFilters>,
+    pub search_type: String,
+    /// The query actually searched, when misspelled words were corrected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_query: Option<String>,
+    /// Terms added to a sparse query by query expansion, when enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded_terms: Vec<String>,
+    /// True when the session's clicks boosted the ranking
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub personalized: bool,
+    /// Ids of the curation rules that changed the results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied_rules: Vec<String>,
+    /// How the query was read, when it used phrases or exclusions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed_query: Option<crate::query_syntax::ParsedQuery>,
+    /// How the search was relaxed, when it found nothing as asked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<crate::fallback::SearchFallback>,
+}
+
+/// Query-string form of a search, used by `GET /search` so searches can be linked and prefetched
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+    pub limit: Option<usize>, // Default: 10
+    pub offset: Option<usize>,
+    pub cursor: Option<String>,
+    /// Comma-separated `key:value` pairs, e.g. `product_id:phone_001,min_rating:4,date_from:2024-01-01`
+    pub filters: Option<String>,
+    #[serde(default)]
+    pub rank_by_helpfulness: bool,
+    pub session_id: Option<String>,
+    pub personalize: Option<bool>,
+    pub curate: Option<bool>,
+    pub correct_typos: Option<bool>,
+    #[serde(default)]
+    pub group_by_product: bool,
+    /// Comma-separated review fields to search, `title` and/or `body`; named apart from
+    /// `fields`, which selects the fields returned
+    pub search_fields: Option<String>,
+    #[serde(default)]
+    pub explain: bool,
+    pub min_score: Option<f32>,
+    pub fallback: Option<bool>,
+    /// Comma-separated collections to search, or `*` for all of them
+    pub collections: Option<String>,
+}
+
+impl TryFrom<SearchQuery> for SearchRequest {
+    type Error = ValidationError;
+
+    fn try_from(query: SearchQuery) -> Result<Self, Self::Error> {
+        let filters = match query.filters.as_deref().map(str::trim) {
+            Some(filters) if !filters.is_empty() => Some(SearchFilters::parse(filters)?),
+            _ => None,
+        };
+        let fields = query
+            .search_fields
+            .iter()
+            .flat_map(|fields| fields.split(','))
+            .filter(|field| !field.trim().is_empty())
+            .map(|field| {
+                SearchField::parse(field).ok_or_else(|| ValidationError::InvalidValue {
+                    field: "search_fields".to_string(),
+                    reason: format!("unknown field {:?}; expected title or body", field.trim()),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let collections = query
+            .collections
+            .iter()
+            .flat_map(|collections| collections.split(','))
+            .map(str::trim)
+            .filter(|collection| !collection.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Ok(SearchRequest {
+            query: query.q,
+            limit: query.limit,
+            offset: query.offset,
+            cursor: query.cursor,
+            filters,
+            rank_by_helpfulness: query.rank_by_helpfulness,
+            ranking: None,
+            session_id: query.session_id,
+            personalize: query.personalize,
+            curate: query.curate,
+            correct_typos: query.correct_typos,
+            group_by_product: query.group_by_product,
+            fields,
+            explain: query.explain,
+            min_score: query.min_score,
+            fallback: query.fallback,
+            collections,
+        })
+    }
+}
+
+/// A helpful / not helpful vote on a review
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct VoteRequest {
+    pub helpful: bool,
+}
+
+/// A search result the user opened, reported to personalize the session's later searches
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ClickReport {
+    pub session_id: String,
+    pub review_id: String,
+}
+
+/// Largest number of reviews a single batch lookup may request
+pub const MAX_BATCH_LOOKUP: usize = 100;
+
+/// Largest number of reviews a single all-or-nothing batch create may carry
+pub const MAX_BATCH_CREATE: usize = 100;
+
+/// Batch lookup of reviews, either by id or by vector index (exactly one of the two)
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchGetRequest {
+    pub ids: Option<Vec<String>>,
+    pub indices: Option<Vec<usize>>,
+}
+
+/// Largest number of ids a single bulk delete may name
+pub const MAX_BULK_DELETE_IDS: usize = 1000;
+
+/// Bulk delete, either by id or by filter (exactly one of the two).
+/// Filter fields combine with AND; `from` and `to` bound the review timestamp, inclusive.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct BulkDeleteRequest {
+    pub ids: Option<Vec<String>>,
+    pub product_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Pagination query for listing reviews
+#[derive(Clone, Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListReviewsQuery {
+    pub page: Option<usize>,      // 1-based, default: 1
+    pub page_size: Option<usize>, // Default: 20
+}
+
+/// Query for sampling random reviews
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RandomReviewsQuery {
+    pub count: Option<usize>, // Default: 10
+    /// Seed for a reproducible sample; the response echoes the seed used
+    pub seed: Option<u64>,
+}
+
+/// Most reviews one `GET /reviews/random` returns
+pub const MAX_RANDOM_REVIEWS: usize = 100;
+
+/// Sorting and pagination query for listing a product's reviews
+#[derive(Clone, Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductReviewsQuery {
+    pub sort: Option<String>,     // "timestamp", "rating" or "helpfulness", default: "timestamp"
+    pub order: Option<String>,    // "asc" or "desc", default: "desc"
+    pub page: Option<usize>,      // 1-based, default: 1
+    pub page_size: Option<usize>, // Default: 20
+}
+
+/// Query for the review export endpoint
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    pub format: Option<String>, // "jsonl", "csv" or "arrow", default: "jsonl"
+    /// Comma-separated licenses to export, case-insensitive; `none` matches reviews without one
+    pub license: Option<String>,
+}
+
+/// Query for the bulk upload endpoint
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BulkUploadQuery {
+    pub file_name: Option<String>, // Original file name, recorded in the upload history
+    /// Source given to uploaded reviews that do not name their own
+    pub source: Option<String>,
+    /// License given to uploaded reviews that do not name their own
+    pub license: Option<String>,
+    /// Attribution given to uploaded reviews that do not carry their own
+    pub attribution: Option<String>,
+}
+
+/// Kinds of recorded jobs
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    BulkUpload,
+    Compaction,
+    Reindex,
+}
+
+/// Lifecycle state of a recorded job
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    RolledBack,
+}
+
+/// Progress of a long-running job, refreshed as it works
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct JobProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Seconds spent working, excluding time paused
+    pub active_secs: f64,
+    /// Items per second of active time, once there is a measurement
+    pub throughput_per_sec: Option<f64>,
+    /// Estimated seconds of work left at the current throughput
+    pub eta_secs: Option<u64>,
+}
+
+impl JobProgress {
+    pub fn new(total: usize, now: DateTime<Utc>) -> Self {
+        JobProgress {
+            processed: 0,
+            total,
+            started_at: now,
+            updated_at: now,
+            active_secs: 0.0,
+            throughput_per_sec: None,
+            eta_secs: (total == 0).then_some(0),
+        }
+    }
+
+    /// Record `processed` items done after `active_secs` of work and re-estimate the time left
+    pub fn advance(&mut self, processed: usize, active_secs: f64, now: DateTime<Utc>) {
+        self.processed = processed.min(self.total);
+        self.active_secs = active_secs;
+        self.updated_at = now;
+        self.throughput_per_sec = (active_secs > 0.0).then(|| self.processed as f64 / active_secs);
+        self.eta_secs = match self.throughput_per_sec {
+            _ if self.processed == self.total => Some(0),
+            Some(throughput) if throughput > 0.0 => {
+                Some(((self.total - self.processed) as f64 / throughput).ceil() as u64)
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Job record stored in jobs.jsonl
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct JobRecord {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub job_type: JobType,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    pub total_processed: usize,
+    pub successful: usize,
+    pub failed: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_back_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Filter and pagination query for listing jobs
+#[derive(Clone, Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListJobsQuery {
+    #[serde(rename = "type")]
+    pub job_type: Option<JobType>,
+    pub page: Option<usize>,      // 1-based, default: 1
+    pub page_size: Option<usize>, // Default: 20
+}
+
+/// Draft review submitted for a duplicate check before creation
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateCheckRequest {
+    #[serde(default)]
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub reviewer_id: Option<String>,
+}
+
+/// Bulk upload result
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkUploadResult {
+    pub total_processed: usize,
+    pub successful: usize,
+    pub failed: Vec<BulkError>,
+}
+
+/// Individual bulk upload error
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkError {
+    pub line_number: usize,
+    pub error: String,
+    pub data: Option<serde_json::Value>,
+}
+
+/// Standard API error response
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    /// Stable machine-readable code; see `error_catalog`
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Validation errors
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("Missing required field: {field}")]
+    MissingField { field: String },
+
+    #[error("Invalid field value: {field} - {reason}")]
+    InvalidValue { field: String, reason: String },
+
+    #[error("Field too short: {field} must be at least {min_length} characters")]
+    TooShort { field: String, min_length: usize },
+
+    #[error("Field too long: {field} must be at most {max_length} characters")]
+    TooLong { field: String, max_length: usize },
+
+    #[error("Invalid rating: must be between 1 and 5")]
+    InvalidRating,
+}
+
+/// Application errors
+#[allow(dead_code)]
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("Validation error: {0}")]
+    Validation(#[from] ValidationError),
+
+    #[error("File operation error: {0}")]
+    FileOperation(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("UUID generation error: {0}")]
+    Uuid(#[from] uuid::Error),
+
+    #[error("Embedding generation error: {message}")]
+    Embedding { message: String },
+
+    #[error("Vector search error: {message}")]
+    VectorSearch { message: String },
+
+    #[error("{resource} not found: {id}")]
+    NotFound { resource: String, id: String },
+
+    #[error("Conflict: {message}")]
+    Conflict { message: String },
+
+    #[error("Concurrency error: {message}")]
+    Concurrency { message: String },
+
+    #[error("Timed out after {waited_ms} ms waiting for the data lock")]
+    LockTimeout {
+        waited_ms: u64,
+        holder: Option<crate::lock::LockHolder>,
+    },
+
+    #[error("Too many requests: retry after {retry_after_secs} seconds")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("Malformed JSON body: {message}")]
+    MalformedJson { message: String },
+
+    #[error("Malformed MessagePack body: {message}")]
+    MalformedMsgpack { message: String },
+
+    #[error("Unsupported media type: {message}")]
+    UnsupportedMediaType { message: String },
+
+    #[error("Request body exceeds the {limit_bytes} byte limit")]
+    PayloadTooLarge { limit_bytes: usize },
+
+    #[error("Request did not complete within {timeout_secs} seconds")]
+    RequestTimeout { timeout_secs: u64 },
+
+    #[error("{field} {value} is blocklisted")]
+    Blocked { field: String, value: String },
+
+    #[error("Near-duplicate of review {review_id} (similarity {similarity:.2})")]
+    Duplicate { review_id: String, similarity: f32 },
+
+    #[error("Corpus is full: {reason}")]
+    CorpusFull {
+        reason: String,
+        max_reviews: Option<usize>,
+        max_bytes: Option<u64>,
+    },
+
+    #[error("No shard answered: {message}")]
+    ShardsUnavailable { message: String },
+
+    #[error("Internal server error: {message}")]
+    Internal { message: String },
+}
+
+/// Whether content under `license` may only be shown with its attribution: the
+/// Creative Commons BY family and the Open Data Commons attribution licenses
+pub fn license_requires_attribution(license: &str) -> bool {
+    let license = license.trim().to_ascii_uppercase();
+    license.starts_with("CC-BY") || license.starts_with("ODC-BY") || license.starts_with("ODBL")
+}
+
+/// Check the lengths and license format of a review's or an import's provenance
+pub fn validate_provenance(source: Option<&str>, license: Option<&str>, attribution: Option<&str>) -> Result<(), ValidationError> {
+    if source.is_some_and(|source| source.len() > 200) {
+        return Err(ValidationError::TooLong {
+            field: "source".to_string(),
+            max_length: 200,
+        });
+    }
+
+    if let Some(license) = license {
+        if license.is_empty()
+            || license.len() > 64
+            || !license.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '+'))
+        {
+            return Err(ValidationError::InvalidValue {
+                field: "license".to_string(),
+                reason: "must be a license identifier such as CC-BY-4.0, up to 64 characters".to_string(),
+            });
+        }
+    }
+
+    if attribution.is_some_and(|attribution| attribution.len() > 500) {
+        return Err(ValidationError::TooLong {
+            field: "attribution".to_string(),
+            max_length: 500,
+        });
+    }
+
+    Ok(())
+}
+
+impl ReviewData {
+    /// Validate review data according to requirements
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        // Check required fields
+        if self.title.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "title".to_string(),
+            });
+        }
+
+        if self.body.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "body".to_string(),
+            });
+        }
+
+        if self.product_id.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "product_id".to_string(),
+            });
+        }
+
+        // Check field lengths
+        if self.title.len() < 3 {
+            return Err(ValidationError::TooShort {
+                field: "title".to_string(),
+                min_length: 3,
+            });
+        }
+
+        if self.title.len() > 200 {
+            return Err(ValidationError::TooLong {
+                field: "title".to_string(),
+                max_length: 200,
+            });
+        }
+
+        if self.body.len() < 10 {
+            return Err(ValidationError::TooShort {
+                field: "body".to_string(),
+                min_length: 10,
+            });
+        }
+
+        if self.body.len() > 2000 {
+            return Err(ValidationError::TooLong {
+                field: "body".to_string(),
+                max_length: 2000,
+            });
+        }
+
+        if self.product_id.len() > 100 {
+            return Err(ValidationError::TooLong {
+                field: "product_id".to_string(),
+                max_length: 100,
+            });
+        }
+
+        // Check rating range
+        if self.rating < 1 || self.rating > 5 {
+            return Err(ValidationError::InvalidRating);
+        }
+
+        if let Some(reviewer_id) = &self.reviewer_id {
+            if reviewer_id.len() > 100 {
+                return Err(ValidationError::TooLong {
+                    field: "reviewer_id".to_string(),
+                    max_length: 100,
+                });
+            }
+        }
+
+        validate_provenance(self.source.as_deref(), self.license.as_deref(), self.attribution.as_deref())?;
+        let attributed = self.attribution.as_deref().is_some_and(|attribution| !attribution.trim().is_empty());
+        if !attributed && self.license.as_deref().is_some_and(license_requires_attribution) {
+            return Err(ValidationError::MissingField {
+                field: "attribution".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Fill in source, license and attribution the review does not set itself
+    pub fn with_provenance_defaults(mut self, source: Option<&str>, license: Option<&str>, attribution: Option<&str>) -> Self {
+        self.source = self.source.or_else(|| source.map(str::to_string));
+        self.license = self.license.or_else(|| license.map(str::to_string));
+        self.attribution = self.attribution.or_else(|| attribution.map(str::to_string));
+        self
+    }
+
+    /// Convert to ReviewMetadata with generated ID and timestamp
+    pub fn to_metadata(&self, vector_index: usize) -> Result<ReviewMetadata, AppError> {
+        self.validate()?;
+
+        Ok(ReviewMetadata {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: self.title.clone(),
+            body: self.body.clone(),
+            product_id: self.product_id.clone(),
+            rating: self.rating,
+            timestamp: Utc::now(),
+            vector_index,
+            reviewer_id: self.reviewer_id.clone(),
+            source: self.source.clone(),
+            license: self.license.clone(),
+            attribution: self.attribution.clone(),
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: crate::sentiment::tag(&self.title, &self.body),
+            language: crate::language::of_text(&self.title, &self.body),
+            duplicate_of: None,
+        })
+    }
+}
+
+impl ReviewMetadata {
+    /// Whether this review has been tombstoned
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Helpfulness from votes in 0-1: the lower bound of the 95% Wilson score interval
+    /// of the helpful share, so a few votes count for less than many. 0 with no votes.
+    pub fn helpfulness(&self) -> f64 {
+        let total = f64::from(self.helpful_votes) + f64::from(self.unhelpful_votes);
+        if total == 0.0 {
+            return 0.0;
+        }
+
+        const Z: f64 = 1.96;
+        let share = f64::from(self.helpful_votes) / total;
+        let z_squared = Z * Z;
+        let centre = share + z_squared / (2.0 * total);
+        let margin = Z * ((share * (1.0 - share) + z_squared / (4.0 * total)) / total).sqrt();
+        (centre - margin) / (1.0 + z_squared / total)
+    }
+}
+
+impl SearchFilters {
+    /// Parse the `key:value,key:value` form used in query strings
+    pub fn parse(filters: &str) -> Result<Self, ValidationError> {
+        let mut parsed = SearchFilters::default();
+
+        for pair in filters.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let Some((key, value)) = pair.split_once(':') else {
+                return Err(ValidationError::InvalidValue {
+                    field: "filters".to_string(),
+                    reason: format!("expected key:value, got '{}'", pair),
+                });
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "product_id" => parsed.product_id = Some(value.to_string()),
+                "min_rating" | "max_rating" => {
+                    let rating = value.parse::<u8>().map_err(|_| ValidationError::InvalidValue {
+                        field: key.to_string(),
+                        reason: format!("'{}' is not a rating", value),
+                    })?;
+                    if key == "min_rating" {
+                        parsed.min_rating = Some(rating);
+                    } else {
+                        parsed.max_rating = Some(rating);
+                    }
+                }
+                "date_from" => parsed.date_from = Some(Self::parse_date(key, value, false)?),
+                "date_to" => parsed.date_to = Some(Self::parse_date(key, value, true)?),
+                "sentiment" => parsed.sentiment = Some(Self::parse_sentiment(value)?),
+                "language" => parsed.language = Some(Self::parse_language(value)?),
+                _ => {
+                    return Err(ValidationError::InvalidValue {
+                        field: "filters".to_string(),
+                        reason: format!("unknown filter '{}'", key),
+                    });
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn parse_sentiment(value: &str) -> Result<Sentiment, ValidationError> {
+        Sentiment::parse(value).ok_or_else(|| ValidationError::InvalidValue {
+            field: "sentiment".to_string(),
+            reason: format!("'{}' is not positive, neutral or negative", value),
+        })
+    }
+
+    pub fn parse_language(value: &str) -> Result<Language, ValidationError> {
+        Language::parse(value).ok_or_else(|| ValidationError::InvalidValue {
+            field: "language".to_string(),
+            reason: format!("'{}' is not one of en, th, zh, ja or ko", value),
+        })
+    }
+
+    /// Parse an RFC 3339 timestamp, or a `YYYY-MM-DD` date standing for the start of
+    /// that day (UTC), or its end when `end_of_day` is set, so `date_to` includes the day
+    pub fn parse_date(field: &str, value: &str, end_of_day: bool) -> Result<DateTime<Utc>, ValidationError> {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+            return Ok(timestamp.with_timezone(&Utc));
+        }
+
+        let day = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| ValidationError::InvalidValue {
+            field: field.to_string(),
+            reason: format!("'{}' is not a date (YYYY-MM-DD) or RFC 3339 timestamp", value),
+        })?;
+        let time = match end_of_day {
+            true => day.and_hms_nano_opt(23, 59, 59, 999_999_999),
+            false => day.and_hms_opt(0, 0, 0),
+        };
+        Ok(time.expect("valid time of day").and_utc())
+    }
+
+    /// Validate filter values
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        for rating in [self.min_rating, self.max_rating].into_iter().flatten() {
+            if !(1..=5).contains(&rating) {
+                return Err(ValidationError::InvalidRating);
+            }
+        }
+
+        if let (Some(min), Some(max)) = (self.min_rating, self.max_rating) {
+            if min > max {
+                return Err(ValidationError::InvalidValue {
+                    field: "min_rating".to_string(),
+                    reason: "must not exceed max_rating".to_string(),
+                });
+            }
+        }
+
+        if let (Some(from), Some(to)) = (self.date_from, self.date_to) {
+            if from > to {
+                return Err(ValidationError::InvalidValue {
+                    field: "date_from".to_string(),
+                    reason: "must not be after date_to".to_string(),
+                });
+            }
+        }
+
+        if self.sentiment.is_some() && !crate::sentiment::enabled() {
+            return Err(ValidationError::InvalidValue {
+                field: "sentiment".to_string(),
+                reason: "sentiment analysis is turned off (SENTIMENT_ANALYSIS)".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether a review passes every filter that is set
+    pub fn matches(&self, review: &ReviewMetadata) -> bool {
+        self.product_id.as_ref().is_none_or(|product_id| &review.product_id == product_id)
+            && self.min_rating.is_none_or(|min| review.rating >= min)
+            && self.max_rating.is_none_or(|max| review.rating <= max)
+            && self.date_from.is_none_or(|from| review.timestamp >= from)
+            && self.date_to.is_none_or(|to| review.timestamp <= to)
+            && self.sentiment.is_none_or(|sentiment| crate::sentiment::of(review) == sentiment)
+            && self.language.is_none_or(|language| crate::language::of(review) == Some(language))
+    }
+}
+
+impl RankingWeights {
+    pub fn get_title_weight(&self) -> f32 {
+        Self::clamped(self.title_weight, 0.8, 2.0)
+    }
+
+    pub fn get_body_weight(&self) -> f32 {
+        Self::clamped(self.body_weight, 0.5, 2.0)
+    }
+
+    pub fn get_rating_boost(&self) -> f32 {
+        Self::clamped(self.rating_boost, 0.1, 0.5)
+    }
+
+    pub fn get_recency_boost(&self) -> f32 {
+        Self::clamped(self.recency_boost, 0.0, 1.0)
+    }
+
+    fn clamped(weight: Option<f32>, default: f32, max: f32) -> f32 {
+        match weight {
+            Some(weight) if weight.is_finite() => weight.clamp(0.0, max),
+            _ => default,
+        }
+    }
+}
+
+impl SearchRequest {
+    /// Validate search request
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.query.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "query".to_string(),
+            });
+        }
+
+        if self.query.len() > MAX_QUERY_LENGTH {
+            return Err(ValidationError::TooLong {
+                field: "query".to_string(),
+                max_length: MAX_QUERY_LENGTH,
+            });
+        }
+
+        if crate::query_syntax::ParsedQuery::parse(&self.query).text.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                field: "query".to_string(),
+                reason: "needs a word to search for besides excluded terms".to_string(),
+            });
+        }
+
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_SEARCH_LIMIT {
+                return Err(ValidationError::InvalidValue {
+                    field: "limit".to_string(),
+                    reason: "must be between 1 and 100".to_string(),
+                });
+            }
+        }
+
+        if let Some(filters) = &self.filters {
+            filters.validate()?;
+        }
+
+        if self.min_score.is_some_and(|min_score| !(0.0..=1.0).contains(&min_score)) {
+            return Err(ValidationError::InvalidValue {
+                field: "min_score".to_string(),
+                reason: "must be between 0 and 1".to_string(),
+            });
+        }
+
+        let offset = match (&self.cursor, self.offset) {
+            (Some(_), Some(_)) => {
+                return Err(ValidationError::InvalidValue {
+                    field: "cursor".to_string(),
+                    reason: "provide offset or cursor, not both".to_string(),
+                });
+            }
+            (Some(cursor), None) => self.decode_cursor(cursor)?,
+            (None, offset) => offset.unwrap_or(0),
+        };
+        if offset + self.get_limit() > MAX_SEARCH_DEPTH {
+            return Err(ValidationError::InvalidValue {
+                field: "offset".to_string(),
+                reason: format!("offset + limit must not exceed {}", MAX_SEARCH_DEPTH),
+            });
+        }
+
+        if let Some(session_id) = &self.session_id {
+            crate::personalization::validate_session_id(session_id)?;
+        }
+
+        crate::collections::validate_selection(&self.collections)?;
+
+        Ok(())
+    }
+
+    /// Get the limit with default value
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(10)
+    }
+
+    /// Position of the page in the full ranking, from `offset` or the cursor
+    pub fn get_offset(&self) -> usize {
+        match &self.cursor {
+            Some(cursor) => self.decode_cursor(cursor).unwrap_or(0),
+            None => self.offset.unwrap_or(0),
+        }
+    }
+
+    /// Results to rank for this page: through its end, plus one to tell whether another follows
+    pub fn ranking_depth(&self) -> usize {
+        (self.get_offset() + self.get_limit() + 1).min(MAX_SEARCH_DEPTH)
+    }
+
+    /// Cut the page out of `ranked`, the best `ranking_depth()` results in order.
+    /// Returns the page and the cursor of the next one, if there is one.
+    pub fn page(&self, ranked: Vec<SearchResult>) -> (Vec<SearchResult>, Option<String>) {
+        let (offset, end) = (self.get_offset(), self.get_offset() + self.get_limit());
+        let next_cursor = (ranked.len() > end && end < MAX_SEARCH_DEPTH).then(|| self.cursor_at(end));
+        (ranked.into_iter().skip(offset).take(self.get_limit()).collect(), next_cursor)
+    }
+
+    /// Opaque cursor for the page starting at `offset`. It carries a fingerprint of the
+    /// query, filters and ranking, so it only continues the search it came from.
+    pub fn cursor_at(&self, offset: usize) -> String {
+        format!("{:08x}{:016x}", offset, self.fingerprint())
+    }
+
+    fn decode_cursor(&self, cursor: &str) -> Result<usize, ValidationError> {
+        let invalid = || ValidationError::InvalidValue {
+            field: "cursor".to_string(),
+            reason: "not a cursor of this search".to_string(),
+        };
+        if cursor.len() != 24 || !cursor.is_ascii() {
+            return Err(invalid());
+        }
+        let offset = usize::from_str_radix(&cursor[..8], 16).map_err(|_| invalid())?;
+        let fingerprint = u64::from_str_radix(&cursor[8..], 16).map_err(|_| invalid())?;
+        match fingerprint == self.fingerprint() {
+            true => Ok(offset),
+            false => Err(invalid()),
+        }
+    }
+
+    /// Hash of what decides the ranking order; paging options are left out
+    fn fingerprint(&self) -> u64 {
+        use sha2::{Digest, Sha256};
+        let mut key = serde_json::json!([self.query.trim(), self.filters, self.rank_by_helpfulness, self.ranking]);
+        // Left out when unset, so cursors of searches not using them stay valid
+        let parts = key.as_array_mut().expect("the key is an array");
+        if self.group_by_product {
+            parts.push(true.into());
+        }
+        if !self.searches_whole_review() {
+            parts.push(serde_json::json!(self.fields));
+        }
+        if let Some(min_score) = self.min_score.filter(|min_score| *min_score > 0.0) {
+            parts.push(serde_json::json!({ "min_score": min_score }));
+        }
+        if !self.collections.is_empty() {
+            parts.push(serde_json::json!({ "collections": self.collections }));
+        }
+        let digest = Sha256::digest(key.to_string().as_bytes());
+        u64::from_be_bytes(digest[..8].try_into().expect("a SHA-256 digest is 32 bytes"))
+    }
+
+    /// Whether the query is scored against both title and body
+    pub fn searches_whole_review(&self) -> bool {
+        self.fields.is_empty() || (self.searches(SearchField::Title) && self.searches(SearchField::Body))
+    }
+
+    /// Whether the query is scored against `field`
+    pub fn searches(&self, field: SearchField) -> bool {
+        self.fields.is_empty() || self.fields.contains(&field)
+    }
+
+    /// The session to personalize the ranking for, unless the request opted out
+    pub fn personalization_session(&self) -> Option<&str> {
+        match self.personalize {
+            Some(false) => None,
+            _ => self.session_id.as_deref(),
+        }
+    }
+}
+
+impl BatchGetRequest {
+    /// Validate batch lookup request
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let requested = match (&self.ids, &self.indices) {
+            (Some(ids), None) => ("ids", ids.len()),
+            (None, Some(indices)) => ("indices", indices.len()),
+            _ => {
+                return Err(ValidationError::InvalidValue {
+                    field: "ids".to_string(),
+                    reason: "provide exactly one of ids or indices".to_string(),
+                });
+            }
+        };
+
+        if requested.1 == 0 || requested.1 > MAX_BATCH_LOOKUP {
+            return Err(ValidationError::InvalidValue {
+                field: requested.0.to_string(),
+                reason: format!("must contain between 1 and {} entries", MAX_BATCH_LOOKUP),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl BulkDeleteRequest {
+    fn has_filter(&self) -> bool {
+        self.product_id.is_some() || self.from.is_some() || self.to.is_some()
+    }
+
+    /// Validate bulk delete request
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match &self.ids {
+            Some(_) if self.has_filter() => {
+                return Err(ValidationError::InvalidValue {
+                    field: "ids".to_string(),
+                    reason: "provide either ids or a filter, not both".to_string(),
+                });
+            }
+            Some(ids) if ids.is_empty() || ids.len() > MAX_BULK_DELETE_IDS => {
+                return Err(ValidationError::InvalidValue {
+                    field: "ids".to_string(),
+                    reason: format!("must contain between 1 and {} entries", MAX_BULK_DELETE_IDS),
+                });
+            }
+            Some(_) => return Ok(()),
+            // An empty filter would match every review
+            None if !self.has_filter() => {
+                return Err(ValidationError::InvalidValue {
+                    field: "ids".to_string(),
+                    reason: "provide ids or at least one of product_id, from, to".to_string(),
+                });
+            }
+            None => {}
+        }
+
+        if matches!(&self.product_id, Some(product_id) if product_id.trim().is_empty()) {
+            return Err(ValidationError::InvalidValue {
+                field: "product_id".to_string(),
+                reason: "must not be empty".to_string(),
+            });
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(ValidationError::InvalidValue {
+                    field: "from".to_string(),
+                    reason: "must not be after to".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a review matches the filter fields
+    pub fn matches(&self, review: &ReviewMetadata) -> bool {
+        self.product_id.as_ref().is_none_or(|product_id| &review.product_id == product_id)
+            && self.from.is_none_or(|from| review.timestamp >= from)
+            && self.to.is_none_or(|to| review.timestamp <= to)
+    }
+}
+
+impl ListReviewsQuery {
+    /// Validate pagination parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.page == Some(0) {
+            return Err(ValidationError::InvalidValue {
+                field: "page".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+
+        if let Some(page_size) = self.page_size {
+            if page_size == 0 || page_size > 100 {
+                return Err(ValidationError::InvalidValue {
+                    field: "page_size".to_string(),
+                    reason: "must be between 1 and 100".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the page number with default value
+    pub fn get_page(&self) -> usize {
+        self.page.unwrap_or(1)
+    }
+
+    /// Get the page size with default value
+    pub fn get_page_size(&self) -> usize {
+        self.page_size.unwrap_or(20)
+    }
+
+    /// Number of reviews to skip before this page
+    pub fn offset(&self) -> usize {
+        (self.get_page() - 1) * self.get_page_size()
+    }
+}
+
+impl RandomReviewsQuery {
+    /// Validate the sample size
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(count) = self.count {
+            if count == 0 || count > MAX_RANDOM_REVIEWS {
+                return Err(ValidationError::InvalidValue {
+                    field: "count".to_string(),
+                    reason: format!("must be between 1 and {}", MAX_RANDOM_REVIEWS),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the sample size with default value
+    pub fn get_count(&self) -> usize {
+        self.count.unwrap_or(10)
+    }
+}
+
+impl ProductReviewsQuery {
+    /// Validate sort and pagination parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(sort) = &self.sort {
+            if sort != "timestamp" && sort != "rating" && sort != "helpfulness" {
+                return Err(ValidationError::InvalidValue {
+                    field: "sort".to_string(),
+                    reason: "must be one of: timestamp, rating, helpfulness".to_string(),
+                });
+            }
+        }
+
+        if let Some(order) = &self.order {
+            if order != "asc" && order != "desc" {
+                return Err(ValidationError::InvalidValue {
+                    field: "order".to_string(),
+                    reason: "must be one of: asc, desc".to_string(),
+                });
+            }
+        }
+
+        self.pagination().validate()
+    }
+
+    /// Get the sort field with default value
+    pub fn get_sort(&self) -> &str {
+        self.sort.as_deref().unwrap_or("timestamp")
+    }
+
+    /// Get the sort order with default value
+    pub fn get_order(&self) -> &str {
+        self.order.as_deref().unwrap_or("desc")
+    }
+
+    /// Pagination part of the query, sharing the review listing rules
+    pub fn pagination(&self) -> ListReviewsQuery {
+        ListReviewsQuery {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    /// Sort reviews in place; ties fall back to newest first
+    pub fn sort_reviews(&self, reviews: &mut [ReviewMetadata]) {
+        let descending = self.get_order() == "desc";
+        reviews.sort_by(|a, b| {
+            let ordering = match self.get_sort() {
+                "rating" => a.rating.cmp(&b.rating),
+                "helpfulness" => a.helpfulness().total_cmp(&b.helpfulness()),
+                _ => a.timestamp.cmp(&b.timestamp),
+            };
+            let ordering = if descending { ordering.reverse() } else { ordering };
+            ordering.then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+    }
+}
+
+impl BulkUploadQuery {
+    /// Validate bulk upload query
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(file_name) = &self.file_name {
+            if file_name.len() > 255 {
+                return Err(ValidationError::TooLong {
+                    field: "file_name".to_string(),
+                    max_length: 255,
+                });
+            }
+        }
+
+        // Whether a review needs an attribution is checked per review, as it may carry its own
+        validate_provenance(self.source.as_deref(), self.license.as_deref(), self.attribution.as_deref())?;
+        Ok(())
+    }
+}
+
+impl ListJobsQuery {
+    /// Pagination part of the query, sharing the review listing rules
+    pub fn pagination(&self) -> ListReviewsQuery {
+        ListReviewsQuery {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+impl DuplicateCheckRequest {
+    /// Validate duplicate check request
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.body.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "body".to_string(),
+            });
+        }
+
+        if self.title.len() > 200 {
+            return Err(ValidationError::TooLong {
+                field: "title".to_string(),
+                max_length: 200,
+            });
+        }
+
+        if self.body.len() > 2000 {
+            return Err(ValidationError::TooLong {
+                field: "body".to_string(),
+                max_length: 2000,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl From<AppError> for ErrorResponse {
+    fn from(error: AppError) -> Self {
+        let (error_type, message, details) = match &error {
+            AppError::Validation(validation_error) => (
+                "validation_error".to_string(),
+                validation_error.to_string(),
+                None,
+            ),
+            AppError::FileOperation(io_error) => (
+                "file_operation_error".to_string(),
+                "File operation failed".to_string(),
+                Some(serde_json::json!({ "io_error": io_error.to_string() })),
+            ),
+            AppError::Serialization(serde_error) => (
+                "serialization_error".to_string(),
+                "Data serialization failed".to_string(),
+                Some(serde_json::json!({ "serde_error": serde_error.to_string() })),
+            ),
+            AppError::Embedding { message } => {
+                ("embedding_error".to_string(), message.clone(), None)
+            }
+            AppError::VectorSearch { message } => {
+                ("vector_search_error".to_string(), message.clone(), None)
+            }
+            AppError::NotFound { .. } => ("not_found".to_string(), error.to_string(), None),
+            AppError::Conflict { message } => ("conflict".to_string(), message.clone(), None),
+            AppError::Concurrency { message } => {
+                ("concurrency_error".to_string(), message.clone(), None)
+            }
+            AppError::LockTimeout { waited_ms, holder } => (
+                "lock_timeout".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({
+                    "waited_ms": waited_ms,
+                    "holder": holder,
+                    "retry_after_secs": crate::lock::LOCK_RETRY_AFTER_SECS,
+                })),
+            ),
+            AppError::RateLimited { retry_after_secs } => (
+                "rate_limited".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({ "retry_after_secs": retry_after_secs })),
+            ),
+            AppError::MalformedJson { message } => ("malformed_json".to_string(), message.clone(), None),
+            AppError::MalformedMsgpack { .. } => ("malformed_msgpack".to_string(), error.to_string(), None),
+            AppError::UnsupportedMediaType { message } => {
+                ("unsupported_media_type".to_string(), message.clone(), None)
+            }
+            AppError::PayloadTooLarge { limit_bytes } => (
+                "payload_too_large".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({ "limit_bytes": limit_bytes })),
+            ),
+            AppError::RequestTimeout { timeout_secs } => (
+                "request_timeout".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({ "timeout_secs": timeout_secs })),
+            ),
+            AppError::Blocked { field, value } => (
+                "blocked".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({ "field": field, "value": value })),
+            ),
+            AppError::Duplicate { review_id, similarity } => (
+                "duplicate".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({
+                    "review_id": review_id,
+                    "similarity": similarity,
+                    "threshold": crate::duplicates::DUPLICATE_THRESHOLD,
+                })),
+            ),
+            AppError::CorpusFull { max_reviews, max_bytes, .. } => (
+                "corpus_full".to_string(),
+                error.to_string(),
+                Some(serde_json::json!({ "max_reviews": max_reviews, "max_bytes": max_bytes })),
+            ),
+            AppError::ShardsUnavailable { .. } => ("shards_unavailable".to_string(), error.to_string(), None),
+            AppError::Internal { message } => ("internal_error".to_string(), message.clone(), None),
+            _ => ("unknown_error".to_string(), error.to_string(), None),
+        };
+
+        ErrorResponse {
+            error: error_type,
+            code: ErrorCode::for_error(&error),
+            message,
+            details,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_data_validation() {
+        // Valid review
+        let valid_review = ReviewData {
+            title: "Great product".to_string(),
+            body: "This is a great product that I really enjoyed using.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 5,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        assert!(valid_review.validate().is_ok());
+
+        // Missing title
+        let invalid_review = ReviewData {
+            title: "".to_string(),
+            body: "This is a great product.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 5,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        assert!(invalid_review.validate().is_err());
+
+        // Invalid rating
+        let invalid_rating = ReviewData {
+            title: "Great product".to_string(),
+            body: "This is a great product.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 6,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        assert!(invalid_rating.validate().is_err());
+    }
+
+    #[test]
+    fn test_review_provenance_validation() {
+        let review = ReviewData {
+            title: "Great product".to_string(),
+            body: "This is a great product that I really enjoyed using.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 5,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        let licensed = review.clone().with_provenance_defaults(Some("Example Reviews"), Some("CC-BY-SA-4.0"), None);
+        assert_eq!(licensed.source.as_deref(), Some("Example Reviews"));
+        assert!(matches!(
+            licensed.validate(),
+            Err(ValidationError::MissingField { field }) if field == "attribution"
+        ));
+
+        // A review's own attribution wins over the import's
+        let attributed = ReviewData {
+            attribution: Some("Jo, via Example Reviews".to_string()),
+            ..licensed
+        }
+        .with_provenance_defaults(None, None, Some("Example Reviews"));
+        assert_eq!(attributed.attribution.as_deref(), Some("Jo, via Example Reviews"));
+        assert!(attributed.validate().is_ok());
+
+        assert!(license_requires_attribution("odbl-1.0"));
+        assert!(!license_requires_attribution("CC0-1.0"));
+        let bad_license = ReviewData {
+            license: Some("CC BY 4.0".to_string()),
+            ..review
+        };
+        assert!(bad_license.validate().is_err());
+    }
+
+    #[test]
+    fn test_search_request_validation() {
+        // Valid search
+        let valid_search = SearchRequest {
+            query: "great product".to_string(),
+            limit: Some(10),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+        assert!(valid_search.validate().is_ok());
+
+        // Empty query
+        let invalid_search = SearchRequest {
+            query: "".to_string(),
+            limit: Some(10),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+        assert!(invalid_search.validate().is_err());
+
+        // Invalid limit
+        let invalid_limit = SearchRequest {
+            query: "great product".to_string(),
+            limit: Some(0),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+        assert!(invalid_limit.validate().is_err());
+    }
+
+    #[test]
+    fn test_search_cursor_continues_only_its_search() {
+        let mut search = SearchRequest {
+            query: "battery".to_string(),
+            limit: Some(2),
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        };
+        let ranked = |count: usize| -> Vec<SearchResult> {
+            (0..count)
+                .map(|i| {
+                    let mut review = ReviewData {
+                        title: "Battery".to_string(),
+                        body: "Battery lasts all day.".to_string(),
+                        product_id: "phone_001".to_string(),
+                        rating: 4,
+                        reviewer_id: None,
+                        source: None,
+                        license: None,
+                        attribution: None,
+                    }
+                    .to_metadata(i)
+                    .unwrap();
+                    review.id = format!("review-{}", i);
+                    SearchResult {
+                        review,
+                        similarity_score: 0.5,
+                        product_hits: None,
+                        explanation: None,
+                        collection: None,
+                    }
+                })
+                .collect()
+        };
+        assert_eq!(search.ranking_depth(), 3);
+
+        let (page, next_cursor) = search.page(ranked(3));
+        assert_eq!(page.len(), 2);
+        let next_cursor = next_cursor.unwrap();
+        search.cursor = Some(next_cursor.clone());
+        assert!(search.validate().is_ok());
+        assert_eq!(search.get_offset(), 2);
+        let (page, next_cursor) = search.page(ranked(3));
+        assert_eq!(page[0].review.id, "review-2");
+        assert!(next_cursor.is_none());
+
+        // A cursor is tied to its query, and replaces offset
+        search.offset = Some(2);
+        assert!(search.validate().is_err());
+        search.offset = None;
+        search.query = "camera".to_string();
+        assert!(search.validate().is_err());
+        search.cursor = Some("not-a-cursor".to_string());
+        assert!(search.validate().is_err());
+
+        search.cursor = None;
+        search.offset = Some(MAX_SEARCH_DEPTH - 1);
+        assert!(search.validate().is_err());
+    }
+
+    #[test]
+    fn test_ranking_weights_default_and_clamp() {
+        let defaults = RankingWeights::default();
+        assert_eq!(defaults.get_title_weight(), 0.8);
+        assert_eq!(defaults.get_body_weight(), 0.5);
+        assert_eq!(defaults.get_rating_boost(), 0.1);
+        assert_eq!(defaults.get_recency_boost(), 0.0);
+
+        let weights: RankingWeights =
+            serde_json::from_str(r#"{"title_weight": 5, "body_weight": -1, "recency_boost": 0.25}"#).unwrap();
+        assert_eq!(weights.get_title_weight(), 2.0);
+        assert_eq!(weights.get_body_weight(), 0.0);
+        assert_eq!(weights.get_rating_boost(), 0.1);
+        assert_eq!(weights.get_recency_boost(), 0.25);
+        let not_a_number = RankingWeights { rating_boost: Some(f32::NAN), ..RankingWeights::default() };
+        assert_eq!(not_a_number.get_rating_boost(), 0.1);
+    }
+
+    #[test]
+    fn test_search_filters_from_query_string() {
+        let query = SearchQuery {
+            q: "battery".to_string(),
+            limit: None,
+            offset: None,
+            cursor: None,
+            filters: Some("product_id:phone_001, min_rating:4".to_string()),
+            rank_by_helpfulness: false,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            search_fields: None,
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: None,
+        };
+        let request = SearchRequest::try_from(query).unwrap();
+        let filters = request.filters.clone().unwrap();
+        assert_eq!(filters.product_id.as_deref(), Some("phone_001"));
+        assert_eq!(filters.min_rating, Some(4));
+        assert!(request.validate().is_ok());
+
+        assert!(SearchFilters::parse("colour:red").is_err());
+        assert!(SearchFilters::parse("min_rating:high").is_err());
+        assert!(SearchFilters::parse("min_rating:5,max_rating:2").unwrap().validate().is_err());
+
+        let dated = SearchFilters::parse("date_from:2024-03-01,date_to:2024-03-31").unwrap();
+        assert_eq!(dated.date_from.unwrap().to_rfc3339(), "2024-03-01T00:00:00+00:00");
+        assert_eq!(dated.date_to.unwrap().date_naive().to_string(), "2024-03-31");
+        assert!(dated.date_to.unwrap() > "2024-03-31T23:59:59Z".parse::<DateTime<Utc>>().unwrap());
+        let timestamp = SearchFilters::parse("date_from:2024-03-01T12:00:00Z").unwrap();
+        assert_eq!(timestamp.date_from.unwrap().to_rfc3339(), "2024-03-01T12:00:00+00:00");
+        assert!(SearchFilters::parse("date_from:March").is_err());
+        assert!(SearchFilters::parse("date_from:2024-04-01,date_to:2024-03-01").unwrap().validate().is_err());
+
+        assert_eq!(SearchFilters::parse("sentiment:negative").unwrap().sentiment, Some(Sentiment::Negative));
+        assert!(SearchFilters::parse("sentiment:angry").is_err());
+
+        assert_eq!(SearchFilters::parse("language:th").unwrap().language, Some(Language::Thai));
+        assert!(SearchFilters::parse("language:xx").is_err());
+    }
+
+    #[test]
+    fn test_list_reviews_query_validation() {
+        // Defaults
+        let default_query = ListReviewsQuery {
+            page: None,
+            page_size: None,
+        };
+        assert!(default_query.validate().is_ok());
+        assert_eq!(default_query.get_page(), 1);
+        assert_eq!(default_query.get_page_size(), 20);
+        assert_eq!(default_query.offset(), 0);
+
+        // Offset of a later page
+        let third_page = ListReviewsQuery {
+            page: Some(3),
+            page_size: Some(10),
+        };
+        assert_eq!(third_page.offset(), 20);
+
+        // Invalid page and page size
+        let zero_page = ListReviewsQuery {
+            page: Some(0),
+            page_size: None,
+        };
+        assert!(zero_page.validate().is_err());
+
+        let oversized_page = ListReviewsQuery {
+            page: None,
+            page_size: Some(101),
+        };
+        assert!(oversized_page.validate().is_err());
+    }
+
+    #[test]
+    fn test_product_reviews_query_sorting() {
+        let review = |id: &str, rating: u8, minutes: i64| ReviewMetadata {
+            id: id.to_string(),
+            title: "Review".to_string(),
+            body: "Review body".to_string(),
+            product_id: "prod_123".to_string(),
+            rating,
+            timestamp: DateTime::<Utc>::from_timestamp(minutes * 60, 0).unwrap(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        };
+        let mut reviews = vec![review("old_5", 5, 1), review("new_3", 3, 3), review("mid_5", 5, 2)];
+        let ids = |reviews: &[ReviewMetadata]| reviews.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+
+        let by_timestamp = ProductReviewsQuery { sort: None, order: None, page: None, page_size: None };
+        assert!(by_timestamp.validate().is_ok());
+        by_timestamp.sort_reviews(&mut reviews);
+        assert_eq!(ids(&reviews), vec!["new_3", "mid_5", "old_5"]);
+
+        // Rating ties are broken newest first in either direction
+        let by_rating = ProductReviewsQuery { sort: Some("rating".to_string()), ..by_timestamp.clone() };
+        by_rating.sort_reviews(&mut reviews);
+        assert_eq!(ids(&reviews), vec!["mid_5", "old_5", "new_3"]);
+
+        let by_rating_asc = ProductReviewsQuery { order: Some("asc".to_string()), ..by_rating.clone() };
+        by_rating_asc.sort_reviews(&mut reviews);
+        assert_eq!(ids(&reviews), vec!["new_3", "mid_5", "old_5"]);
+
+        // Many mostly-helpful votes outrank a single helpful one
+        reviews[0].helpful_votes = 1;
+        reviews[1].helpful_votes = 40;
+        reviews[1].unhelpful_votes = 5;
+        let by_helpfulness = ProductReviewsQuery { sort: Some("helpfulness".to_string()), ..by_timestamp.clone() };
+        assert!(by_helpfulness.validate().is_ok());
+        by_helpfulness.sort_reviews(&mut reviews);
+        assert_eq!(ids(&reviews), vec!["mid_5", "new_3", "old_5"]);
+        assert_eq!(reviews[2].helpfulness(), 0.0);
+
+        let invalid_sort = ProductReviewsQuery { sort: Some("title".to_string()), ..by_timestamp.clone() };
+        assert!(invalid_sort.validate().is_err());
+        let invalid_order = ProductReviewsQuery { order: Some("up".to_string()), ..by_timestamp };
+        assert!(invalid_order.validate().is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Session-scoped personalization of search rankings.
+//!
+//! Clients report the results their user opened with `POST /search/clicks`, under a
+//! session id of their choosing, and send the same `session_id` with later searches.
+//! Each search result then gets a boost for resembling a recent click: a review of the
+//! same product, or one sharing many terms with the clicked review. A click's weight
+//! halves every `CLICK_HALF_LIFE`, so the ranking drifts back to the unpersonalized one
+//! within the hour unless the user keeps clicking; `DELETE /search/clicks/{session_id}`
+//! forgets a session at once, and `personalize: false` on a search ignores it.
+//!
+//! Sessions live in this process's memory only: they are lost on restart and not shared
+//! between servers. On a sharded deployment the coordinator, which receives both the
+//! clicks and the searches, applies the boost to the merged results.
+
+use crate::explain::Blend;
+use crate::models::*;
+use crate::tokenizer;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest session id accepted
+pub const MAX_SESSION_ID_LENGTH: usize = 128;
+
+/// Clicks remembered per session; older ones are forgotten first
+pub const MAX_CLICKS_PER_SESSION: usize = 20;
+
+/// Time for a click's influence to halve
+pub const CLICK_HALF_LIFE: Duration = Duration::from_secs(10 * 60);
+
+/// Sessions without clicks or searches for this long are dropped
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Above this many sessions, idle ones are dropped, then the least recently used
+const MAX_SESSIONS: usize = 10_000;
+
+/// Share of the ranking score taken by the session boost
+pub const PERSONALIZATION_WEIGHT: f32 = 0.15;
+
+/// Candidates ranked per result requested, so boosted reviews from just below the
+/// cut can move up into it
+pub const RERANK_WINDOW: usize = 3;
+
+/// A result the user opened
+struct Click {
+    product_id: String,
+    terms: HashSet<String>,
+    clicked_at: Instant,
+}
+
+struct Session {
+    clicks: VecDeque<Click>,
+    last_used: Instant,
+}
+
+fn sessions() -> &'static Mutex<HashMap<String, Session>> {
+    static SESSIONS: OnceLock<Mutex<HashMap<String, Session>>> = OnceLock::new();
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Check a client-chosen session id
+pub fn validate_session_id(session_id: &str) -> Result<(), ValidationError> {
+    let invalid = |reason: String| ValidationError::InvalidValue {
+        field: "session_id".to_string(),
+        reason,
+    };
+    if session_id.trim().is_empty() {
+        return Err(invalid("must not be empty".to_string()));
+    }
+    if session_id.len() > MAX_SESSION_ID_LENGTH {
+        return Err(invalid(format!("must be at most {} characters", MAX_SESSION_ID_LENGTH)));
+    }
+    Ok(())
+}
+
+fn review_terms(review: &ReviewMetadata) -> HashSet<String> {
+    tokenizer::analyze(&format!("{} {}", review.title, review.body)).into_iter().collect()
+}
+
+/// Remember that the session opened `review`. Returns the number of clicks remembered.
+pub fn record_click(session_id: &str, review: &ReviewMetadata, now: Instant) -> usize {
+    let mut sessions = sessions().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if sessions.len() >= MAX_SESSIONS && !sessions.contains_key(session_id) {
+        evict(&mut sessions, now);
+    }
+
+    let session = sessions.entry(session_id.to_string()).or_insert_with(|| Session {
+        clicks: VecDeque::new(),
+        last_used: now,
+    });
+    session.last_used = now;
+    session.clicks.push_back(Click {
+        product_id: review.product_id.clone(),
+        terms: review_terms(review),
+        clicked_at: now,
+    });
+    if session.clicks.len() > MAX_CLICKS_PER_SESSION {
+        session.clicks.pop_front();
+    }
+    session.clicks.len()
+}
+
+/// Forget a session. Returns the number of clicks it held.
+pub fn clear(session_id: &str) -> usize {
+    let mut sessions = sessions().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    sessions.remove(session_id).map_or(0, |session| session.clicks.len())
+}
+
+/// Drop idle sessions, or the least recently used one if none is idle
+fn evict(sessions: &mut HashMap<String, Session>, now: Instant) {
+    sessions.retain(|_, session| now.duration_since(session.last_used) < SESSION_IDLE_TIMEOUT);
+    if sessions.len() >= MAX_SESSIONS {
+        if let Some(oldest) = sessions.iter().min_by_key(|(_, session)| session.last_used).map(|(id, _)| id.clone()) {
+            sessions.remove(&oldest);
+        }
+    }
+}
+
+/// A session's clicks as of one search, weighted by age
+pub struct Profile {
+    clicks: Vec<(f32, String, HashSet<String>)>,
+}
+
+impl Profile {
+    /// The profile of a session with clicks, or None
+    pub fn load(session_id: &str, now: Instant) -> Option<Profile> {
+        let mut sessions = sessions().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let session = sessions.get_mut(session_id)?;
+        if now.duration_since(session.last_used) >= SESSION_IDLE_TIMEOUT {
+            sessions.remove(session_id);
+            return None;
+        }
+        session.last_used = now;
+
+        let clicks: Vec<(f32, String, HashSet<String>)> = session
+            .clicks
+            .iter()
+            .map(|click| (decay(now.duration_since(click.clicked_at)), click.product_id.clone(), click.terms.clone()))
+            .collect();
+        (!clicks.is_empty()).then_some(Profile { clicks })
+    }
+
+    /// How strongly `review` resembles the session's clicks, 0-1: the best match over
+    /// recent clicks, counting the same product and shared terms equally
+    pub fn boost(&self, review: &ReviewMetadata) -> f32 {
+        let terms = review_terms(review);
+        self.clicks
+            .iter()
+            .map(|(weight, product_id, click_terms)| {
+                let same_product = if *product_id == review.product_id { 1.0 } else { 0.0 };
+                weight * (0.5 * same_product + 0.5 * jaccard(&terms, click_terms))
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Blend the boost into every score and sort again, best first
+    pub fn rerank(&self, results: &mut [SearchResult]) {
+        for result in results.iter_mut() {
+            let blend = Blend {
+                value: self.boost(&result.review),
+                weight: PERSONALIZATION_WEIGHT,
+            };
+            result.similarity_score = blend.apply(result.similarity_score);
+            if let Some(explanation) = &mut result.explanation {
+                explanation.personalization = Some(blend);
+            }
+        }
+        results.sort_by(SearchResult::rank_order);
+    }
+}
+
+/// Weight of a click of the given age: 1 when fresh, halving every `CLICK_HALF_LIFE`
+fn decay(age: Duration) -> f32 {
+    0.5f32.powf(age.as_secs_f32() / CLICK_HALF_LIFE.as_secs_f32())
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    match union {
+        0 => 0.0,
+        _ => a.intersection(b).count() as f32 / union as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn review(id: &str, product_id: &str, body: &str) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: String::new(),
+            body: body.to_string(),
+            product_id: product_id.to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_clicks_boost_similar_reviews_and_decay() {
+        let now = Instant::now();
+        let clicked = review("a", "steel_kettle", "Quiet steel kettle, boils fast");
+        let same_product = review("b", "steel_kettle", "Handle gets warm");
+        let similar_text = review("c", "glass_kettle", "Quiet kettle that boils fast");
+        let unrelated = review("d", "toaster", "Burns the bread");
+
+        assert!(Profile::load("test-session-boost", now).is_none());
+        assert_eq!(record_click("test-session-boost", &clicked, now), 1);
+        let profile = Profile::load("test-session-boost", now).unwrap();
+        assert!(profile.boost(&clicked) > 0.99);
+        assert!(profile.boost(&same_product) >= 0.5);
+        assert!(profile.boost(&similar_text) > profile.boost(&unrelated));
+        assert_eq!(profile.boost(&unrelated), 0.0);
+
+        // Equal scores are reordered by resemblance to the click
+        let mut results: Vec<SearchResult> = [&unrelated, &similar_text, &same_product]
+            .into_iter()
+            .map(|review| SearchResult {
+                review: review.clone(),
+                similarity_score: 1.0,
+                product_hits: None,
+                explanation: None,
+                collection: None,
+            })
+            .collect();
+        profile.rerank(&mut results);
+        let ids: Vec<&str> = results.iter().map(|result| result.review.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c", "d"]);
+
+        // One half-life later the boost has halved
+        let later = Profile::load("test-session-boost", now + CLICK_HALF_LIFE).unwrap();
+        assert!((later.boost(&clicked) - 0.5).abs() < 1e-3);
+
+        assert_eq!(clear("test-session-boost"), 1);
+        assert!(Profile::load("test-session-boost", now).is_none());
+    }
+}
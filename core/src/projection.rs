@@ -0,0 +1,208 @@
+//! 2D projection of review embeddings, for the admin dashboard's scatter plot.
+//!
+//! PCA by power iteration on the centred sample: two passes over the vectors per
+//! iteration, no matrix library and nothing bigger than one vector per component.
+//! The second component is kept orthogonal to the first by Gram-Schmidt on every
+//! iteration. Signs are fixed so the same sample always plots the same way up.
+
+use crate::models::ReviewMetadata;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Reviews projected when the request doesn't say
+pub const DEFAULT_PROJECTION_SAMPLE: usize = 500;
+
+/// Most reviews one projection may sample
+pub const MAX_PROJECTION_SAMPLE: usize = 2000;
+
+/// Power iteration stops after this many rounds even if not yet converged
+const MAX_ITERATIONS: usize = 100;
+
+/// Relative change in a component's eigenvalue below which it counts as converged
+const CONVERGENCE_TOLERANCE: f32 = 1e-6;
+
+/// Query for `GET /analyze/projection`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProjectionQuery {
+    pub sample: Option<usize>, // Default: 500
+    /// Seed for a reproducible sample; the response echoes the seed used
+    pub seed: Option<u64>,
+}
+
+impl ProjectionQuery {
+    pub fn validate(&self) -> Result<(), crate::models::ValidationError> {
+        if let Some(sample) = self.sample {
+            if sample == 0 || sample > MAX_PROJECTION_SAMPLE {
+                return Err(crate::models::ValidationError::InvalidValue {
+                    field: "sample".to_string(),
+                    reason: format!("must be between 1 and {}", MAX_PROJECTION_SAMPLE),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_sample(&self) -> usize {
+        self.sample.unwrap_or(DEFAULT_PROJECTION_SAMPLE)
+    }
+}
+
+/// One review placed on the plot, with the labels the dashboard colours by
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ProjectionPoint {
+    pub review_id: String,
+    pub product_id: String,
+    pub rating: u8,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Coordinates of each vector on the first two principal components, and the share of
+/// the sample's variance each component explains
+pub fn pca_2d(vectors: &[Vec<f32>]) -> (Vec<[f32; 2]>, [f32; 2]) {
+    let Some(dimension) = vectors.first().map(Vec::len) else {
+        return (Vec::new(), [0.0, 0.0]);
+    };
+
+    let mut mean = vec![0.0f32; dimension];
+    for vector in vectors {
+        for (sum, value) in mean.iter_mut().zip(vector) {
+            *sum += value;
+        }
+    }
+    mean.iter_mut().for_each(|sum| *sum /= vectors.len() as f32);
+    let centred: Vec<Vec<f32>> = vectors
+        .iter()
+        .map(|vector| vector.iter().zip(&mean).map(|(value, mean)| value - mean).collect())
+        .collect();
+
+    let total_variance: f32 = centred.iter().flatten().map(|value| value * value).sum();
+    let mut components: Vec<Vec<f32>> = Vec::with_capacity(2);
+    let mut explained = [0.0f32; 2];
+    for (rank, share) in explained.iter_mut().enumerate() {
+        let (component, eigenvalue) = principal_component(&centred, dimension, rank, &components);
+        if total_variance > 0.0 {
+            *share = eigenvalue / total_variance;
+        }
+        components.push(component);
+    }
+
+    let coordinates = centred
+        .iter()
+        .map(|vector| [dot(vector, &components[0]), dot(vector, &components[1])])
+        .collect();
+    (coordinates, explained)
+}
+
+/// Leading eigenvector of the sample covariance orthogonal to `found`, and its eigenvalue
+fn principal_component(centred: &[Vec<f32>], dimension: usize, rank: usize, found: &[Vec<f32>]) -> (Vec<f32>, f32) {
+    // A fixed, uneven starting vector, so results are reproducible and unlikely to be
+    // orthogonal to the answer
+    let mut component: Vec<f32> = (0..dimension)
+        .map(|i| ((i * 7919 + rank * 104_729) % 1000) as f32 / 1000.0 - 0.5)
+        .collect();
+    orthogonalize(&mut component, found);
+    if normalize(&mut component) == 0.0 {
+        return (component, 0.0);
+    }
+
+    let mut eigenvalue = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        // (XᵀX)v without forming XᵀX
+        let mut next = vec![0.0f32; dimension];
+        for vector in centred {
+            let weight = dot(vector, &component);
+            for (sum, value) in next.iter_mut().zip(vector) {
+                *sum += weight * value;
+            }
+        }
+        orthogonalize(&mut next, found);
+        let norm = normalize(&mut next);
+        if norm == 0.0 {
+            // No variance left in any direction orthogonal to `found`
+            return (component, 0.0);
+        }
+        component = next;
+        let converged = (norm - eigenvalue).abs() <= CONVERGENCE_TOLERANCE * norm;
+        eigenvalue = norm;
+        if converged {
+            break;
+        }
+    }
+
+    // Point the component at its largest coordinate, so the sign is stable
+    let largest = component.iter().copied().fold(0.0f32, |largest, value| if value.abs() > largest.abs() { value } else { largest });
+    if largest < 0.0 {
+        component.iter_mut().for_each(|value| *value = -*value);
+    }
+    (component, eigenvalue)
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn orthogonalize(vector: &mut [f32], basis: &[Vec<f32>]) {
+    for axis in basis {
+        let overlap = dot(vector, axis);
+        for (value, axis) in vector.iter_mut().zip(axis) {
+            *value -= overlap * axis;
+        }
+    }
+}
+
+/// Scale to unit length, returning the original length
+fn normalize(vector: &mut [f32]) -> f32 {
+    let norm = dot(vector, vector).sqrt();
+    if norm > f32::EPSILON {
+        vector.iter_mut().for_each(|value| *value /= norm);
+        norm
+    } else {
+        0.0
+    }
+}
+
+/// Pair sampled reviews with their projected coordinates
+pub fn points(reviews: &[ReviewMetadata], coordinates: &[[f32; 2]]) -> Vec<ProjectionPoint> {
+    reviews
+        .iter()
+        .zip(coordinates)
+        .map(|(review, [x, y])| ProjectionPoint {
+            review_id: review.id.clone(),
+            product_id: review.product_id.clone(),
+            rating: review.rating,
+            x: *x,
+            y: *y,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pca_finds_the_spread_out_axes() {
+        // Points spread mostly along dimension 2, a little along 0, not at all along 1
+        let vectors: Vec<Vec<f32>> = (0..20)
+            .map(|i| {
+                let t = i as f32 - 9.5;
+                vec![0.1 * t * (i % 2) as f32, 3.0, t]
+            })
+            .collect();
+
+        let (coordinates, explained) = pca_2d(&vectors);
+        assert_eq!(coordinates.len(), 20);
+        assert!(explained[0] > 0.95 && explained[1] > 0.0, "{:?}", explained);
+        assert!((explained[0] + explained[1] - 1.0).abs() < 1e-3, "{:?}", explained);
+
+        // The first axis orders points the way dimension 2 does, centred on zero
+        assert!(coordinates[0][0] < 0.0 && coordinates[19][0] > 0.0);
+        let mean_x: f32 = coordinates.iter().map(|[x, _]| x).sum::<f32>() / 20.0;
+        assert!(mean_x.abs() < 1e-3);
+
+        assert_eq!(pca_2d(&[]).0.len(), 0);
+        assert_eq!(pca_2d(&[vec![1.0, 2.0]]), (vec![[0.0, 0.0]], [0.0, 0.0]));
+    }
+}
@@ -0,0 +1,349 @@
+//! Query log: every search served, and the search analytics computed from it.
+//!
+//! Each search is one line of queries.jsonl in the data directory: when it ran, the
+//! query as sent, how long it took, how many results its page held and the id of the
+//! top result. Once the file reaches `QUERY_LOG_MAX_BYTES` (default 10 MiB; 0 turns the
+//! log off) it is rotated to queries.1.jsonl, pushing older files up to
+//! `ROTATED_FILES`, so the log is bounded and analytics reach back only as far as the
+//! files kept. Logging never fails a search: write errors are only traced.
+//!
+//! Analytics group queries by their normalized text, so "Battery" and "battery " count
+//! as one query. On a sharded deployment each shard logs the searches it served.
+
+use crate::models::*;
+use crate::storage::DataPaths;
+use crate::tokenizer;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use utoipa::{IntoParams, ToSchema};
+
+/// Size queries.jsonl is rotated at when `QUERY_LOG_MAX_BYTES` is not set
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated files kept besides queries.jsonl
+pub const ROTATED_FILES: usize = 3;
+
+/// Most queries one analytics list may hold
+pub const MAX_TOP_QUERIES: usize = 100;
+
+/// Window analytics cover when `from` is not given
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+/// Serializes appends and rotation within this process
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Size queries.jsonl is rotated at, from `QUERY_LOG_MAX_BYTES`; 0 means no logging
+pub fn max_bytes() -> u64 {
+    std::env::var("QUERY_LOG_MAX_BYTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_BYTES)
+}
+
+/// One logged search
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    pub at: DateTime<Utc>,
+    pub query: String,
+    pub latency_ms: f64,
+    /// Results on the page served; 0 when only a relaxed search found any, so the query
+    /// still counts as finding nothing
+    pub results: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_result_id: Option<String>,
+}
+
+impl QueryLogEntry {
+    /// The log entry of a search answered with `response` after `latency_ms`
+    pub fn of(query: &str, response: &SearchResponse, latency_ms: f64) -> Self {
+        Self {
+            at: Utc::now(),
+            query: query.to_string(),
+            latency_ms,
+            results: if response.fallback.is_some() { 0 } else { response.results.len() },
+            top_result_id: response.results.first().map(|result| result.review.id.clone()),
+        }
+    }
+}
+
+/// queries.jsonl, then its rotated files from newest to oldest
+fn log_files(data_paths: &DataPaths) -> Vec<PathBuf> {
+    let mut files = vec![data_paths.queries_jsonl.clone()];
+    files.extend((1..=ROTATED_FILES).map(|n| data_paths.data_dir.join(format!("queries.{}.jsonl", n))));
+    files
+}
+
+/// Shift every log file one place older, dropping the oldest
+fn rotate(files: &[PathBuf]) -> std::io::Result<()> {
+    for pair in files.windows(2).rev() {
+        if pair[0].exists() {
+            std::fs::rename(&pair[0], &pair[1])?;
+        }
+    }
+    Ok(())
+}
+
+fn append(data_paths: &DataPaths, entry: &QueryLogEntry, max_bytes: u64) -> Result<(), AppError> {
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let files = log_files(data_paths);
+    if std::fs::metadata(&files[0]).is_ok_and(|metadata| metadata.len() >= max_bytes) {
+        rotate(&files)?;
+    }
+
+    // One write per line, so concurrent processes appending to the file never interleave within a line
+    let line = format!("{}\n", serde_json::to_string(entry)?);
+    let mut file = OpenOptions::new().create(true).append(true).open(&files[0])?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Append a search to the log, rotating it when full
+pub fn record(data_paths: &DataPaths, entry: &QueryLogEntry) {
+    let max_bytes = max_bytes();
+    if max_bytes == 0 {
+        return;
+    }
+    if let Err(e) = append(data_paths, entry, max_bytes) {
+        tracing::warn!("Failed to log search: {}", e);
+    }
+}
+
+/// Every logged search from `from` up to and including `to`, oldest file first
+pub fn read_window(
+    data_paths: &DataPaths,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<QueryLogEntry>, AppError> {
+    let mut entries = Vec::new();
+    for file_path in log_files(data_paths).into_iter().rev() {
+        let file = match File::open(&file_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for line in BufReader::new(file).lines() {
+            // A line torn by a crash mid-write is skipped rather than failing the report
+            let Ok(entry) = serde_json::from_str::<QueryLogEntry>(&line?) else {
+                continue;
+            };
+            if entry.at >= from && entry.at <= to {
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Query for `GET /analytics/searches`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchAnalyticsQuery {
+    /// Start of the window, YYYY-MM-DD or RFC 3339; default 24 hours before `to`
+    pub from: Option<String>,
+    /// End of the window, YYYY-MM-DD (inclusive) or RFC 3339; default now
+    pub to: Option<String>,
+    /// Queries per list; default 10
+    pub limit: Option<usize>,
+}
+
+impl SearchAnalyticsQuery {
+    /// The window and list length asked for
+    pub fn resolve(&self, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>, usize), ValidationError> {
+        let to = match &self.to {
+            Some(to) => SearchFilters::parse_date("to", to, true)?,
+            None => now,
+        };
+        let from = match &self.from {
+            Some(from) => SearchFilters::parse_date("from", from, false)?,
+            None => to - Duration::hours(DEFAULT_WINDOW_HOURS),
+        };
+        if from > to {
+            return Err(ValidationError::InvalidValue {
+                field: "from".to_string(),
+                reason: "must not be after 'to'".to_string(),
+            });
+        }
+
+        let limit = self.limit.unwrap_or(10);
+        if limit == 0 || limit > MAX_TOP_QUERIES {
+            return Err(ValidationError::InvalidValue {
+                field: "limit".to_string(),
+                reason: format!("must be between 1 and {}", MAX_TOP_QUERIES),
+            });
+        }
+        Ok((from, to, limit))
+    }
+}
+
+/// Search latency percentiles, nearest rank, in milliseconds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencyPercentiles {
+    /// Percentiles of `latencies`, or None when there are none
+    pub fn of(mut latencies: Vec<f64>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_by(f64::total_cmp);
+        let rank = |percentile: f64| {
+            let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+            latencies[rank.clamp(1, latencies.len()) - 1]
+        };
+        Some(Self {
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p95: rank(95.0),
+            p99: rank(99.0),
+            max: latencies[latencies.len() - 1],
+        })
+    }
+}
+
+/// How often one query was searched in the window
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct QueryCount {
+    /// The query, normalized
+    pub query: String,
+    pub searches: usize,
+    /// Searches of the query that found nothing
+    pub zero_result_searches: usize,
+}
+
+/// Response of `GET /analytics/searches`
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchAnalytics {
+    pub success: bool,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_searches: usize,
+    pub zero_result_searches: usize,
+    /// None when no search ran in the window
+    pub latency_ms: Option<LatencyPercentiles>,
+    /// Most searched queries, most searches first
+    pub top_queries: Vec<QueryCount>,
+    /// Queries that found nothing most often, most zero-result searches first
+    pub zero_result_queries: Vec<QueryCount>,
+}
+
+/// Analytics over `entries`, the searches of the window `from`..=`to`
+pub fn summarize(entries: &[QueryLogEntry], from: DateTime<Utc>, to: DateTime<Utc>, limit: usize) -> SearchAnalytics {
+    let mut counts: HashMap<String, QueryCount> = HashMap::new();
+    for entry in entries {
+        let query = tokenizer::normalize(&entry.query).split_whitespace().collect::<Vec<_>>().join(" ");
+        let count = counts.entry(query.clone()).or_insert(QueryCount {
+            query,
+            searches: 0,
+            zero_result_searches: 0,
+        });
+        count.searches += 1;
+        if entry.results == 0 {
+            count.zero_result_searches += 1;
+        }
+    }
+
+    let ranked = |key: fn(&QueryCount) -> usize| {
+        let mut queries: Vec<QueryCount> = counts.values().filter(|count| key(count) > 0).cloned().collect();
+        queries.sort_by(|a, b| key(b).cmp(&key(a)).then_with(|| a.query.cmp(&b.query)));
+        queries.truncate(limit);
+        queries
+    };
+
+    SearchAnalytics {
+        success: true,
+        from,
+        to,
+        total_searches: entries.len(),
+        zero_result_searches: entries.iter().filter(|entry| entry.results == 0).count(),
+        latency_ms: LatencyPercentiles::of(entries.iter().map(|entry| entry.latency_ms).collect()),
+        top_queries: ranked(|count| count.searches),
+        zero_result_queries: ranked(|count| count.zero_result_searches),
+    }
+}
+
+/// Search analytics over the logged searches of the window `query` asks for
+pub fn analyze(data_paths: &DataPaths, query: &SearchAnalyticsQuery) -> Result<SearchAnalytics, AppError> {
+    let (from, to, limit) = query.resolve(Utc::now())?;
+    let entries = read_window(data_paths, from, to)?;
+    Ok(summarize(&entries, from, to, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(query: &str, latency_ms: f64, results: usize) -> QueryLogEntry {
+        QueryLogEntry {
+            at: Utc::now(),
+            query: query.to_string(),
+            latency_ms,
+            results,
+            top_result_id: (results > 0).then(|| "review-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_summarize_groups_queries_and_ranks_them() {
+        let entries = vec![
+            entry("Battery life", 10.0, 3),
+            entry("battery  life ", 20.0, 0),
+            entry("battery life", 30.0, 1),
+            entry("screen", 40.0, 0),
+            entry("screen", 50.0, 0),
+            entry("price", 60.0, 2),
+        ];
+        let now = Utc::now();
+        let analytics = summarize(&entries, now - Duration::hours(1), now, 2);
+
+        assert_eq!(analytics.total_searches, 6);
+        assert_eq!(analytics.zero_result_searches, 3);
+        assert_eq!(
+            analytics.top_queries,
+            vec![
+                QueryCount { query: "battery life".to_string(), searches: 3, zero_result_searches: 1 },
+                QueryCount { query: "screen".to_string(), searches: 2, zero_result_searches: 2 },
+            ]
+        );
+        assert_eq!(analytics.zero_result_queries[0].query, "screen");
+        assert_eq!(analytics.zero_result_queries[1].query, "battery life");
+
+        let latency = analytics.latency_ms.unwrap();
+        assert_eq!((latency.p50, latency.p90, latency.max), (30.0, 60.0, 60.0));
+        assert!(summarize(&[], now, now, 10).latency_ms.is_none());
+    }
+
+    #[test]
+    fn test_log_rotates_and_reads_back_every_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        for n in 0..20 {
+            append(&data_paths, &entry(&format!("query {}", n), 1.0, 1), 200).unwrap();
+        }
+
+        assert!(temp_dir.path().join("queries.1.jsonl").exists());
+        assert!(!temp_dir.path().join(format!("queries.{}.jsonl", ROTATED_FILES + 1)).exists());
+        let entries = read_window(&data_paths, Utc::now() - Duration::hours(1), Utc::now()).unwrap();
+        assert!(!entries.is_empty() && entries.len() < 20);
+        // Oldest kept first, newest last
+        assert_eq!(entries.last().unwrap().query, "query 19");
+
+        let query = SearchAnalyticsQuery {
+            from: Some("2024-02-01".to_string()),
+            to: Some("2024-01-01".to_string()),
+            limit: None,
+        };
+        assert!(query.resolve(Utc::now()).is_err());
+    }
+}
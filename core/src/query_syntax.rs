@@ -0,0 +1,138 @@
+//! A minimal query syntax layered over semantic ranking: `"quoted phrases"` a review
+//! must contain word for word, and `-term` (or `-"a phrase"`) a review must not contain.
+//!
+//! Operators only filter. The words of the query, phrases included and exclusions left
+//! out, are what gets embedded and matched by keyword, so `"battery life" -charger`
+//! ranks like `battery life` among the reviews that say "battery life" and never
+//! "charger". Phrases and exclusions are compared on the keyword analyzer's tokens, so
+//! case, accents and quantity spellings don't matter, but synonyms and typo correction
+//! don't apply. A `-` inside a word (`wi-fi`) is not an operator, and a quote left open
+//! runs to the end of the query.
+
+use crate::models::{ReviewMetadata, SearchField};
+use crate::tokenizer;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A query split into the text to rank by and the operators to filter by
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ParsedQuery {
+    /// The words searched: the query without operators, phrase words kept
+    pub text: String,
+    /// Phrases every result contains
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phrases: Vec<String>,
+    /// Terms and phrases no result contains
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded: Vec<String>,
+}
+
+impl ParsedQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = ParsedQuery::default();
+        let mut words: Vec<&str> = Vec::new();
+        let mut rest = query.trim_start();
+        while !rest.is_empty() {
+            let negated = rest.len() > 1 && rest.starts_with('-') && !rest[1..].starts_with(char::is_whitespace);
+            let term = if negated { &rest[1..] } else { rest };
+            let (value, quoted, remainder) = match term.strip_prefix('"') {
+                Some(quoted) => match quoted.find('"') {
+                    Some(end) => (&quoted[..end], true, &quoted[end + 1..]),
+                    None => (quoted, true, ""),
+                },
+                None => {
+                    let end = term.find(char::is_whitespace).unwrap_or(term.len());
+                    (&term[..end], false, &term[end..])
+                }
+            };
+            let value = value.trim();
+            if !value.is_empty() {
+                match (negated, quoted) {
+                    (true, _) => parsed.excluded.push(value.to_string()),
+                    (false, true) => {
+                        parsed.phrases.push(value.to_string());
+                        words.push(value);
+                    }
+                    (false, false) => words.push(value),
+                }
+            }
+            rest = remainder.trim_start();
+        }
+        parsed.text = words.join(" ");
+        parsed
+    }
+
+    /// Whether the query used any operator
+    pub fn has_operators(&self) -> bool {
+        !self.phrases.is_empty() || !self.excluded.is_empty()
+    }
+
+    /// Whether a review contains every phrase and none of the exclusions, in the
+    /// searched fields (both when `fields` is empty)
+    pub fn admits(&self, review: &ReviewMetadata, fields: &[SearchField]) -> bool {
+        if !self.has_operators() {
+            return true;
+        }
+        let searched = |field: SearchField| fields.is_empty() || fields.contains(&field);
+        let mut tokens = Vec::new();
+        if searched(SearchField::Title) {
+            tokens.extend(tokenizer::analyze(&review.title));
+        }
+        if searched(SearchField::Body) {
+            tokens.extend(tokenizer::analyze(&review.body));
+        }
+        self.phrases.iter().all(|phrase| contains_phrase(&tokens, phrase))
+            && !self.excluded.iter().any(|term| contains_phrase(&tokens, term))
+    }
+}
+
+/// Whether the phrase's tokens appear one after another in `tokens`
+fn contains_phrase(tokens: &[String], phrase: &str) -> bool {
+    let phrase = tokenizer::analyze(phrase);
+    !phrase.is_empty() && tokens.windows(phrase.len()).any(|window| window == phrase.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(title: &str, body: &str) -> ReviewMetadata {
+        crate::models::ReviewData {
+            title: title.to_string(),
+            body: body.to_string(),
+            product_id: "prod_1".to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+        .to_metadata(0)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_splits_phrases_and_exclusions() {
+        let parsed = ParsedQuery::parse(r#" "battery life"  great -charger -"fast charge" wi-fi - "unclosed quote"#);
+        assert_eq!(parsed.text, "battery life great wi-fi - unclosed quote");
+        assert_eq!(parsed.phrases, ["battery life", "unclosed quote"]);
+        assert_eq!(parsed.excluded, ["charger", "fast charge"]);
+
+        let plain = ParsedQuery::parse("battery life");
+        assert_eq!(plain.text, "battery life");
+        assert!(!plain.has_operators());
+        assert_eq!(ParsedQuery::parse("-charger").text, "");
+    }
+
+    #[test]
+    fn test_admits_by_phrase_and_exclusion() {
+        let parsed = ParsedQuery::parse(r#""Battery Life" -charger"#);
+        assert!(parsed.admits(&review("Great battery life", "Lasts for days."), &[]));
+        assert!(!parsed.admits(&review("Great battery", "Its life is long."), &[]));
+        assert!(!parsed.admits(&review("Great battery life", "The charger broke."), &[]));
+        // The charger is only in the body, which the search leaves out
+        assert!(parsed.admits(&review("Great battery life", "The charger broke."), &[SearchField::Title]));
+        assert!(!ParsedQuery::parse("-charge").admits(&review("Quick charge", "Full in an hour."), &[]));
+        assert!(ParsedQuery::parse("-charge").admits(&review("Quick charger", "Full in an hour."), &[]));
+    }
+}
@@ -0,0 +1,233 @@
+//! Rebuilding the derived indexes from reviews.jsonl, for recovery after reviews.index
+//! is corrupted or lost.
+//!
+//! A reindex runs as a background job recorded in jobs.jsonl. It embeds every review of
+//! a snapshot into a fresh index file without holding the data lock, reporting progress
+//! as it goes, then takes the exclusive lock to swap the file in. Writes made meanwhile
+//! are reconciled at the swap: slots whose review changed are embedded again, and
+//! reviews appended after the snapshot are left pending for the embedding worker. The
+//! in-memory id and product line-offset indexes are rebuilt at the same time.
+
+use crate::embedding::{self, Embedder};
+use crate::embedding_worker;
+use crate::jobs::JobStorage;
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Reviews embedded between progress updates
+pub const REINDEX_CHUNK_SIZE: usize = 500;
+
+/// Set while this process is reindexing
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Record a reindex job and start it in the background. Fails with a conflict if this
+/// process is already reindexing.
+pub fn start(data_dir: PathBuf) -> Result<JobRecord, AppError> {
+    let data_paths = DataPaths::new(&data_dir);
+    data_paths.ensure_directories()?;
+
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(AppError::Conflict {
+            message: "A reindex is already running".to_string(),
+        });
+    }
+
+    let job = match record_job(&data_paths) {
+        Ok(job) => job,
+        Err(e) => {
+            RUNNING.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+    };
+
+    let mut background_job = job.clone();
+    tokio::task::spawn_blocking(move || {
+        run(&data_paths, &mut background_job, Arc::new(embedding::default_embedder()));
+        RUNNING.store(false, Ordering::SeqCst);
+    });
+
+    Ok(job)
+}
+
+fn record_job(data_paths: &DataPaths) -> Result<JobRecord, AppError> {
+    let _lock = FileLock::acquire(&data_paths.lock_file, "start_reindex")?;
+    let total = JsonlStorage::new(&data_paths.reviews_jsonl).count_reviews()?;
+    let now = Utc::now();
+    let job = JobRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        job_type: JobType::Reindex,
+        status: JobStatus::Running,
+        created_at: now,
+        file_name: None,
+        total_processed: 0,
+        successful: 0,
+        failed: 0,
+        batch_id: None,
+        rolled_back_at: None,
+        progress: Some(JobProgress::new(total, now)),
+        error: None,
+    };
+    JobStorage::new(&data_paths.jobs_jsonl).append_job(&job)?;
+    Ok(job)
+}
+
+/// Run a reindex to the end, recording the outcome on the job
+fn run(data_paths: &DataPaths, job: &mut JobRecord, embedder: Arc<dyn Embedder>) {
+    match reindex(data_paths, job, embedder.as_ref()) {
+        Ok(slots) => {
+            job.status = JobStatus::Completed;
+            job.successful = slots;
+            tracing::info!("Reindex {} finished: rebuilt {} vector slots", job.id, slots);
+        }
+        Err(e) => {
+            tracing::error!("Reindex {} failed: {}", job.id, e);
+            job.status = JobStatus::Failed;
+            job.error = Some(e.to_string());
+        }
+    }
+
+    if let Err(e) = save_job(data_paths, job) {
+        tracing::error!("Failed to record the outcome of reindex {}: {}", job.id, e);
+    }
+}
+
+/// The vector for a review; tombstoned reviews are never searched, so theirs is zero
+fn vector_for(embedder: &dyn Embedder, review: &ReviewMetadata) -> Vec<f32> {
+    match review.is_deleted() {
+        true => vec![0.0; embedder.dimension()],
+        false => embedder.embed(&embedding::review_text(review)),
+    }
+}
+
+/// Embed a snapshot into a new index file, then reconcile and swap it in under the
+/// exclusive lock. Returns the number of slots in the new index.
+fn reindex(data_paths: &DataPaths, job: &mut JobRecord, embedder: &dyn Embedder) -> Result<usize, AppError> {
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let snapshot = {
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "reindex_snapshot")?;
+        storage.read_all_reviews()?
+    };
+
+    let temp_path = data_paths.reviews_index.with_extension("index.rebuild");
+    std::fs::File::create(&temp_path)?;
+    let rebuilt = VectorStore::new(&temp_path, embedder.dimension());
+
+    let mut progress = JobProgress::new(snapshot.len(), Utc::now());
+    let mut active = Duration::ZERO;
+    for (chunk_number, chunk) in snapshot.chunks(REINDEX_CHUNK_SIZE).enumerate() {
+        let chunk_started = Instant::now();
+        let start = chunk_number * REINDEX_CHUNK_SIZE;
+        // Slot N belongs to line N; a line claiming another slot gets a placeholder
+        let vectors: Vec<Vec<f32>> = chunk
+            .iter()
+            .enumerate()
+            .map(|(offset, review)| match review.vector_index == start + offset {
+                true => vector_for(embedder, review),
+                false => vec![0.0; embedder.dimension()],
+            })
+            .collect();
+        rebuilt.write_slots(start, &vectors)?;
+        active += chunk_started.elapsed();
+
+        let processed = start + chunk.len();
+        progress.advance(processed, active.as_secs_f64(), Utc::now());
+        job.total_processed = processed;
+        job.successful = processed;
+        job.progress = Some(progress.clone());
+        save_job(data_paths, job)?;
+    }
+
+    let _lock = FileLock::acquire(&data_paths.lock_file, "reindex_swap")?;
+    let current = storage.read_all_reviews()?;
+    let slots = snapshot.len().min(current.len());
+    rebuilt.truncate(slots)?;
+    for (slot, review) in current.iter().enumerate().take(slots) {
+        let unchanged = snapshot[slot].id == review.id
+            && snapshot[slot].vector_index == review.vector_index
+            && snapshot[slot].deleted_at == review.deleted_at
+            && embedding::review_text(&snapshot[slot]) == embedding::review_text(review);
+        if !unchanged {
+            let vector = match review.vector_index == slot {
+                true => vector_for(embedder, review),
+                false => vec![0.0; embedder.dimension()],
+            };
+            rebuilt.write_slots(slot, &[vector])?;
+        }
+    }
+    std::fs::rename(&temp_path, &data_paths.reviews_index)?;
+
+    storage.rebuild_id_index()?;
+    embedding_worker::notify();
+    Ok(slots)
+}
+
+fn save_job(data_paths: &DataPaths, job: &JobRecord) -> Result<(), AppError> {
+    let _lock = FileLock::acquire(&data_paths.lock_file, "reindex_progress")?;
+    JobStorage::new(&data_paths.jobs_jsonl).update_job(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{HashingEmbedder, EMBEDDING_DIM};
+    use tempfile::TempDir;
+
+    fn review(id: &str, vector_index: usize, deleted: bool) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: format!("Review {}", id),
+            body: "A review body long enough to store.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: deleted.then(Utc::now),
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_reindex_replaces_a_corrupt_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_paths = DataPaths::new(temp_dir.path());
+        let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+        let reviews = vec![review("a", 0, false), review("b", 1, true), review("c", 2, false)];
+        storage.append_reviews(&reviews).unwrap();
+
+        // Garbage of the wrong length where the vectors should be
+        std::fs::write(&data_paths.reviews_index, vec![0xffu8; 1234]).unwrap();
+
+        let mut job = record_job(&data_paths).unwrap();
+        let embedder = Arc::new(HashingEmbedder::default());
+        run(&data_paths, &mut job, embedder.clone());
+
+        let finished = JobStorage::new(&data_paths.jobs_jsonl).get_job(&job.id).unwrap().unwrap();
+        assert_eq!(finished.job_type, JobType::Reindex);
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!((finished.total_processed, finished.successful), (3, 3));
+        assert_eq!(finished.progress.unwrap().eta_secs, Some(0));
+
+        let store = VectorStore::new(&data_paths.reviews_index, EMBEDDING_DIM);
+        assert_eq!(store.len().unwrap(), 3);
+        assert_eq!(store.read(0).unwrap().unwrap(), embedder.embed(&embedding::review_text(&reviews[0])));
+        assert!(store.read(1).unwrap().unwrap().iter().all(|value| *value == 0.0));
+        assert!(storage.get_review_by_id("c").unwrap().is_some());
+    }
+}
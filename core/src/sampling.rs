@@ -0,0 +1,89 @@
+//! Uniform random samples from a stream of unknown length, for `GET /reviews/random`.
+//!
+//! Reservoir sampling keeps at most `count` items in memory however long the stream
+//! is. The generator is a small seeded SplitMix64 rather than an OS source, so a
+//! sample can be reproduced by passing the same seed over the same data.
+
+/// SplitMix64: fast, seedable, and good enough for picking sample positions
+pub struct SampleRng {
+    state: u64,
+}
+
+impl SampleRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A seed from the v4 UUID generator, for callers that didn't choose one
+    pub fn random_seed() -> u64 {
+        uuid::Uuid::new_v4().as_u64_pair().0
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound`, by widening multiply rather than a biased modulo
+    pub fn below(&mut self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+/// Pick `count` items uniformly from `items` in one pass (Algorithm R), returned in
+/// random order. Fewer come back when the stream is shorter than `count`.
+pub fn reservoir_sample<T, E>(
+    items: impl IntoIterator<Item = Result<T, E>>,
+    count: usize,
+    rng: &mut SampleRng,
+) -> Result<Vec<T>, E> {
+    let mut reservoir = Vec::with_capacity(count);
+    for (seen, item) in items.into_iter().enumerate() {
+        let item = item?;
+        if reservoir.len() < count {
+            reservoir.push(item);
+        } else {
+            let slot = rng.below(seen + 1);
+            if slot < count {
+                reservoir[slot] = item;
+            }
+        }
+    }
+
+    // The first `count` items sit in stream order unless replaced; shuffle them
+    for i in (1..reservoir.len()).rev() {
+        reservoir.swap(i, rng.below(i + 1));
+    }
+    Ok(reservoir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(seed: u64, count: usize, len: usize) -> Vec<usize> {
+        reservoir_sample((0..len).map(Ok::<_, ()>), count, &mut SampleRng::new(seed)).unwrap()
+    }
+
+    #[test]
+    fn test_reservoir_sample_is_reproducible_and_roughly_uniform() {
+        assert_eq!(sample(7, 5, 1000), sample(7, 5, 1000));
+        assert_ne!(sample(7, 5, 1000), sample(8, 5, 1000));
+
+        let mut short = sample(1, 10, 3);
+        short.sort();
+        assert_eq!(short, vec![0, 1, 2]);
+
+        // 1000 samples of 3 from 10 should pick each item about 300 times
+        let mut hits = [0usize; 10];
+        for seed in 0..1000 {
+            for item in sample(seed, 3, 10) {
+                hits[item] += 1;
+            }
+        }
+        assert!(hits.iter().all(|&count| (240..360).contains(&count)), "{:?}", hits);
+    }
+}
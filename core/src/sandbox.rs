@@ -0,0 +1,365 @@
+//! Sandboxes: throwaway review sets for demos, held in memory only.
+//!
+//! With `SANDBOX_MODE=true`, `/sandboxes/{id}/...` serves a private namespace per id.
+//! The first review added to an id creates its sandbox; searches there see only its
+//! reviews, embedded as they are added, and nothing is written to the data directory.
+//! Each sandbox holds at most `SANDBOX_MAX_REVIEWS` reviews and is dropped after
+//! `SANDBOX_IDLE_SECS` without a request, so a demo can't fill the server or leave
+//! anything behind. Sandboxes are lost on restart and not shared between servers.
+
+use crate::embedding::{self, Embedder};
+use crate::explain::ScoreExplanation;
+use crate::models::*;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Sandboxes alive at once; creating another first drops idle ones
+pub const MAX_SANDBOXES: usize = 100;
+
+/// Longest sandbox id accepted
+pub const MAX_SANDBOX_ID_LENGTH: usize = 64;
+
+const DEFAULT_MAX_REVIEWS: usize = 200;
+const DEFAULT_IDLE_SECS: u64 = 30 * 60;
+
+/// How often the collector looks for idle sandboxes
+const GC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sandbox limits, read from the environment once
+#[derive(Clone, Copy, Debug)]
+pub struct SandboxConfig {
+    pub max_reviews: usize,
+    pub idle_timeout: Duration,
+}
+
+pub fn config() -> SandboxConfig {
+    static CONFIG: OnceLock<SandboxConfig> = OnceLock::new();
+    *CONFIG.get_or_init(|| SandboxConfig {
+        max_reviews: std::env::var("SANDBOX_MAX_REVIEWS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_REVIEWS),
+        idle_timeout: Duration::from_secs(
+            std::env::var("SANDBOX_IDLE_SECS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_IDLE_SECS),
+        ),
+    })
+}
+
+/// Whether `SANDBOX_MODE` turns the sandbox routes on; read when the router is built
+pub fn enabled() -> bool {
+    std::env::var("SANDBOX_MODE")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "true" | "1" | "on"))
+        .unwrap_or(false)
+}
+
+struct Sandbox {
+    reviews: Vec<(ReviewMetadata, Vec<f32>)>,
+    created_at: DateTime<Utc>,
+    last_used: Instant,
+}
+
+impl Sandbox {
+    fn info(&self, id: &str, config: SandboxConfig, now: Instant) -> SandboxInfo {
+        SandboxInfo {
+            id: id.to_string(),
+            review_count: self.reviews.len(),
+            max_reviews: config.max_reviews,
+            created_at: self.created_at,
+            expires_in_secs: config.idle_timeout.saturating_sub(now.duration_since(self.last_used)).as_secs(),
+        }
+    }
+}
+
+/// What a sandbox holds and when it will be dropped
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct SandboxInfo {
+    pub id: String,
+    pub review_count: usize,
+    pub max_reviews: usize,
+    pub created_at: DateTime<Utc>,
+    /// Seconds until the sandbox is dropped unless it is used again
+    pub expires_in_secs: u64,
+}
+
+/// The sandboxes of this process, by id
+pub struct SandboxStore {
+    config: SandboxConfig,
+    sandboxes: Mutex<HashMap<String, Sandbox>>,
+}
+
+/// The process-wide store the sandbox routes use
+pub fn store() -> &'static SandboxStore {
+    static STORE: OnceLock<SandboxStore> = OnceLock::new();
+    STORE.get_or_init(|| SandboxStore::new(config()))
+}
+
+/// Check a client-chosen sandbox id: letters, digits, `-` and `_`
+pub fn validate_sandbox_id(sandbox_id: &str) -> Result<(), ValidationError> {
+    let valid_chars = sandbox_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if sandbox_id.is_empty() || sandbox_id.len() > MAX_SANDBOX_ID_LENGTH || !valid_chars {
+        return Err(ValidationError::InvalidValue {
+            field: "sandbox_id".to_string(),
+            reason: format!("must be 1-{} letters, digits, '-' or '_'", MAX_SANDBOX_ID_LENGTH),
+        });
+    }
+    Ok(())
+}
+
+fn not_found(sandbox_id: &str) -> AppError {
+    AppError::NotFound {
+        resource: "Sandbox".to_string(),
+        id: sandbox_id.to_string(),
+    }
+}
+
+/// Collect idle sandboxes in the background, so their memory is freed even when no
+/// request comes in
+pub fn spawn_gc() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(GC_INTERVAL);
+        loop {
+            interval.tick().await;
+            let dropped = store().sweep(Instant::now());
+            if dropped > 0 {
+                tracing::info!("Dropped {} idle sandboxes", dropped);
+            }
+        }
+    });
+}
+
+impl SandboxStore {
+    pub fn new(config: SandboxConfig) -> Self {
+        Self {
+            config,
+            sandboxes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Sandbox>> {
+        self.sandboxes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Drop sandboxes idle past the timeout. Returns how many were dropped.
+    pub fn sweep(&self, now: Instant) -> usize {
+        let mut sandboxes = self.lock();
+        let before = sandboxes.len();
+        sandboxes.retain(|_, sandbox| now.duration_since(sandbox.last_used) < self.config.idle_timeout);
+        before - sandboxes.len()
+    }
+
+    /// Validate, embed and add reviews to a sandbox, creating it if needed. All are
+    /// added or, when one is invalid or they would overfill the sandbox, none.
+    pub fn add_reviews(
+        &self,
+        sandbox_id: &str,
+        reviews: &[ReviewData],
+        now: Instant,
+    ) -> Result<(SandboxInfo, Vec<ReviewMetadata>), AppError> {
+        validate_sandbox_id(sandbox_id)?;
+        if reviews.is_empty() {
+            return Err(AppError::Validation(ValidationError::InvalidValue {
+                field: "reviews".to_string(),
+                reason: "must contain at least one review".to_string(),
+            }));
+        }
+        for review_data in reviews {
+            review_data.validate()?;
+        }
+
+        // Embed before taking the lock; slots are numbered once the sandbox is known
+        let embedder = embedding::default_embedder();
+        let mut embedded = Vec::with_capacity(reviews.len());
+        for review_data in reviews {
+            let review = review_data.to_metadata(0)?;
+            let vector = embedder.embed(&embedding::review_text(&review));
+            embedded.push((review, vector));
+        }
+
+        self.sweep(now);
+        let mut sandboxes = self.lock();
+        if !sandboxes.contains_key(sandbox_id) && sandboxes.len() >= MAX_SANDBOXES {
+            return Err(AppError::Conflict {
+                message: format!("At most {} sandboxes can be open; try again later", MAX_SANDBOXES),
+            });
+        }
+        let held = sandboxes.get(sandbox_id).map_or(0, |sandbox| sandbox.reviews.len());
+        if held + embedded.len() > self.config.max_reviews {
+            return Err(AppError::Conflict {
+                message: format!(
+                    "Sandbox {} holds {} of at most {} reviews",
+                    sandbox_id, held, self.config.max_reviews
+                ),
+            });
+        }
+        let sandbox = sandboxes.entry(sandbox_id.to_string()).or_insert_with(|| Sandbox {
+            reviews: Vec::new(),
+            created_at: Utc::now(),
+            last_used: now,
+        });
+        sandbox.last_used = now;
+
+        let mut created = Vec::with_capacity(embedded.len());
+        for (mut review, vector) in embedded {
+            review.vector_index = sandbox.reviews.len();
+            created.push(review.clone());
+            sandbox.reviews.push((review, vector));
+        }
+        Ok((sandbox.info(sandbox_id, self.config, now), created))
+    }
+
+    /// A sandbox's description and reviews, oldest first
+    pub fn reviews(&self, sandbox_id: &str, now: Instant) -> Result<(SandboxInfo, Vec<ReviewMetadata>), AppError> {
+        validate_sandbox_id(sandbox_id)?;
+        self.sweep(now);
+        let mut sandboxes = self.lock();
+        let sandbox = sandboxes.get_mut(sandbox_id).ok_or_else(|| not_found(sandbox_id))?;
+        sandbox.last_used = now;
+        let reviews = sandbox.reviews.iter().map(|(review, _)| review.clone()).collect();
+        Ok((sandbox.info(sandbox_id, self.config, now), reviews))
+    }
+
+    /// Rank a sandbox's reviews by cosine similarity to the query. Filters and `min_score`
+    /// apply; the shared index's extras (helpfulness, personalization, curation, expansion,
+    /// typo correction, fallback) don't.
+    pub fn search(&self, sandbox_id: &str, search_request: SearchRequest, now: Instant) -> Result<SearchResponse, AppError> {
+        validate_sandbox_id(sandbox_id)?;
+        search_request.validate()?;
+        let query_vector = embedding::default_embedder().embed(&search_request.query);
+        let limit = search_request.get_limit();
+
+        self.sweep(now);
+        let mut sandboxes = self.lock();
+        let sandbox = sandboxes.get_mut(sandbox_id).ok_or_else(|| not_found(sandbox_id))?;
+        sandbox.last_used = now;
+
+        let mut results: Vec<SearchResult> = sandbox
+            .reviews
+            .iter()
+            .filter(|(review, _)| search_request.filters.as_ref().is_none_or(|filters| filters.matches(review)))
+            .filter_map(|(review, vector)| {
+                let similarity: f32 = query_vector.iter().zip(vector).map(|(a, b)| a * b).sum();
+                let explanation = ScoreExplanation::vector(similarity, similarity.min(1.0));
+                (similarity > 0.0).then(|| SearchResult {
+                    review: review.clone(),
+                    similarity_score: similarity.min(1.0),
+                    product_hits: None,
+                    explanation: search_request.explain.then_some(explanation),
+                    collection: None,
+                })
+            })
+            .collect();
+        drop(sandboxes);
+        results.retain(|result| search_request.min_score.is_none_or(|min_score| result.similarity_score >= min_score));
+        results.sort_by(SearchResult::rank_order);
+        let (results, next_cursor) = search_request.page(results);
+
+        Ok(SearchResponse {
+            success: true,
+            total_results: results.len(),
+            limit,
+            offset: search_request.get_offset(),
+            next_cursor,
+            query: search_request.query,
+            results,
+            filters: search_request.filters,
+            search_type: "vector_similarity".to_string(),
+            corrected_query: None,
+            expanded_terms: Vec::new(),
+            personalized: false,
+            applied_rules: Vec::new(),
+            parsed_query: None,
+            fallback: None,
+        })
+    }
+
+    /// Drop a sandbox at once. Returns the number of reviews it held.
+    pub fn remove(&self, sandbox_id: &str) -> Result<usize, AppError> {
+        validate_sandbox_id(sandbox_id)?;
+        self.lock()
+            .remove(sandbox_id)
+            .map(|sandbox| sandbox.reviews.len())
+            .ok_or_else(|| not_found(sandbox_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(title: &str, body: &str, product_id: &str) -> ReviewData {
+        ReviewData {
+            title: title.to_string(),
+            body: body.to_string(),
+            product_id: product_id.to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    fn search_for(query: &str) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            limit: None,
+            offset: None,
+            cursor: None,
+            filters: None,
+            rank_by_helpfulness: false,
+            ranking: None,
+            session_id: None,
+            personalize: None,
+            curate: None,
+            correct_typos: None,
+            group_by_product: false,
+            fields: Vec::new(),
+            explain: false,
+            min_score: None,
+            fallback: None,
+            collections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sandboxes_are_isolated_capped_and_expire() {
+        let store = SandboxStore::new(SandboxConfig {
+            max_reviews: 3,
+            idle_timeout: Duration::from_secs(60),
+        });
+        let now = Instant::now();
+        let kettle = review("Kettle", "Boils water quickly and quietly.", "kettle");
+        let tent = review("Tent", "Stayed dry through heavy rain.", "tent");
+
+        let (info, created) = store.add_reviews("a", &[kettle.clone(), tent], now).unwrap();
+        assert_eq!((info.review_count, created[1].vector_index), (2, 1));
+        store.add_reviews("b", std::slice::from_ref(&kettle), now).unwrap();
+
+        let response = store.search("a", search_for("heavy rain tent"), now).unwrap();
+        assert_eq!(response.results[0].review.product_id, "tent");
+        assert_eq!(store.search("b", search_for("heavy rain tent"), now).unwrap().total_results, 0);
+        assert!(matches!(store.search("missing", search_for("tent"), now), Err(AppError::NotFound { .. })));
+
+        // An invalid review or an overfull sandbox adds nothing
+        let mut invalid = kettle.clone();
+        invalid.rating = 9;
+        assert!(store.add_reviews("a", &[kettle.clone(), invalid], now).is_err());
+        assert!(matches!(store.add_reviews("a", &[kettle.clone(), kettle], now), Err(AppError::Conflict { .. })));
+        assert_eq!(store.reviews("a", now).unwrap().0.review_count, 2);
+        assert!(validate_sandbox_id("../data").is_err());
+
+        // A used sandbox stays; an idle one is dropped
+        let later = now + Duration::from_secs(40);
+        store.reviews("a", later).unwrap();
+        assert_eq!(store.sweep(now + Duration::from_secs(70)), 1);
+        assert!(store.reviews("b", later).is_err());
+        assert_eq!(store.remove("a").unwrap(), 2);
+    }
+}
@@ -0,0 +1,504 @@
+//! Ranking reviews for a search: vector similarity over embedded reviews, keyword
+//! matching for those still waiting for the embedding worker, then personalization,
+//! curation, grouping and paging.
+//!
+//! `run` is the whole search behind `POST /search`, `GET /search`, the search session
+//! socket, GraphQL and gRPC, relaxed per `fallback` when it finds nothing; a coordinator
+//! runs it on every shard. The lower-level ranking functions are shared with "more like
+//! this" and the shard fan-out.
+
+use crate::blocklist::{BlockScope, Blocklist};
+use crate::embedding::{self, Embedder, EmbeddingStatus};
+use crate::explain::{Blend, KeywordScore, MatchKind, ScoreExplanation, TermMatch};
+use crate::lock::FileLock;
+use crate::models::*;
+use crate::storage::*;
+use crate::vectors::VectorStore;
+use crate::{
+    collections, curation, expansion, fallback, personalization, query_syntax, spelling, suggest, synonyms, tokenizer,
+};
+
+/// Search the reviews in `data_paths`, relaxing the search if it finds nothing
+pub fn run(data_paths: &DataPaths, search_request: SearchRequest) -> Result<SearchResponse, AppError> {
+    search_request.validate()?;
+    data_paths.ensure_directories()?;
+
+    let response = search_once(data_paths, search_request.clone())?;
+    // Only first pages of searches as asked count, so paging through one search doesn't
+    // make it popular and relaxed searches don't suggest queries that find nothing
+    if !response.results.is_empty() && search_request.get_offset() == 0 {
+        let searched_query =
+            response.corrected_query.clone().unwrap_or_else(|| query_syntax::ParsedQuery::parse(&response.query).text);
+        suggest::record_query(data_paths, &searched_query);
+    }
+    if !fallback::applies(&search_request, &response) {
+        return Ok(response);
+    }
+    for (relaxed, relaxation) in fallback::steps(&search_request, &synonyms::current(data_paths)) {
+        let mut relaxed_response = search_once(data_paths, relaxed)?;
+        if !relaxed_response.results.is_empty() {
+            tracing::info!("Search for '{}' relaxed: {:?}", search_request.query, relaxation.relaxations);
+            fallback::finish(&mut relaxed_response, &search_request, relaxation);
+            return Ok(relaxed_response);
+        }
+    }
+    Ok(response)
+}
+
+/// Search the reviews in `data_paths` exactly as asked, or the collections the search names
+pub(crate) fn search_once(data_paths: &DataPaths, search_request: SearchRequest) -> Result<SearchResponse, AppError> {
+    if !search_request.collections.is_empty() {
+        return collections::search(data_paths, search_request);
+    }
+
+    let jsonl_storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
+    let scoring = Scoring::of(&search_request, chrono::Utc::now());
+    // Phrases and exclusions filter; the rest of the query is what gets ranked by
+    let parsed_query = query_syntax::ParsedQuery::parse(&search_request.query);
+
+    let limit = search_request.get_limit();
+    // Everything up to the end of the page is ranked, so pages of one search never overlap
+    let depth = search_request.ranking_depth();
+    let profile = search_request
+        .personalization_session()
+        .and_then(|session_id| personalization::Profile::load(session_id, std::time::Instant::now()));
+
+    // Searches share the lock with each other and only wait for writers
+    let lock = FileLock::acquire_shared(&data_paths.lock_file, "search")?;
+
+    // Slots are read and hydrated under one lock, so compaction can't renumber them between
+    let searched = Blocklist::load(data_paths, BlockScope::Search).and_then(|blocklist| {
+        // Blocked reviews are dropped before ranking, so they never take a slot on the page
+        let mut all_reviews = jsonl_storage.read_live_reviews()?;
+        let stored_slots = vector_store.len()?;
+        let mut excluded = jsonl_storage.excluded_vector_slots()?;
+        blocklist.remove_blocked(&mut all_reviews, &mut excluded);
+
+        // Typos are corrected against every searchable review, so filters do not change the
+        // correction. The corrected query is searched, embedded and matched to curation rules.
+        let corrected_query = match search_request.correct_typos {
+            Some(false) => None,
+            _ => spelling::correct(&parsed_query.text, &all_reviews),
+        };
+        let searched_request = SearchRequest {
+            query: corrected_query.clone().unwrap_or_else(|| parsed_query.text.clone()),
+            ..search_request.clone()
+        };
+        let query_vector = embedding::default_embedder().embed(&searched_request.query);
+
+        let curation = curation::Curation::load(data_paths, &searched_request)?;
+        // With clicks or boosts to go on, rank a wider window so boosted reviews can move into
+        // the page; grouped by product, rank enough to fill the page and count each product's hits
+        let window = match profile {
+            Some(_) => depth * personalization::RERANK_WINDOW,
+            None => depth,
+        }
+        .max(curation.window(depth))
+        .max(if search_request.group_by_product { GROUPED_SEARCH_WINDOW } else { 0 });
+
+        // Filters rule out slots from metadata up front; hydration checks them again
+        if let Some(filters) = &search_request.filters {
+            for review in all_reviews.iter().filter(|review| !filters.matches(review)) {
+                excluded.set(review.vector_index);
+            }
+        }
+        // So do phrases and exclusions, from the text
+        if parsed_query.has_operators() {
+            for review in all_reviews.iter().filter(|review| !parsed_query.admits(review, &search_request.fields)) {
+                excluded.set(review.vector_index);
+            }
+        }
+        // Restricted to one field, every entry of `fields` names it
+        let vector_results = match search_request.searches_whole_review() {
+            true => perform_vector_search(
+                &jsonl_storage,
+                &vector_store,
+                &query_vector,
+                &excluded,
+                search_request.filters.as_ref(),
+                window,
+                &scoring,
+            )?,
+            false => {
+                let field = search_request.fields[0];
+                perform_field_vector_search(&all_reviews, stored_slots, field, &query_vector, &excluded, window, &scoring)
+            }
+        };
+
+        // A pin never shows a deleted or blocked review, or one the filters or query operators rule out
+        let mut pinned = Vec::new();
+        for review_id in curation.pinned_ids() {
+            if let Some(review) = jsonl_storage.get_review_by_id(review_id)? {
+                if !review.is_deleted()
+                    && !blocklist.blocks(&review)
+                    && search_request.filters.as_ref().is_none_or(|filters| filters.matches(&review))
+                    && parsed_query.admits(&review, &search_request.fields)
+                {
+                    pinned.push(review);
+                }
+            }
+        }
+        Ok((searched_request.query, corrected_query, curation, window, all_reviews, stored_slots, vector_results, pinned))
+    });
+    drop(lock);
+    let (searched_query, corrected_query, curation, window, all_reviews, stored_slots, mut search_results, pinned) =
+        searched?;
+
+    // Vocabulary comes from every searchable review, so filters do not change the expansion
+    let expanded_terms = expansion::expand(&searched_query, &all_reviews);
+
+    // Reviews without a current vector are found by keyword until the worker embeds them
+    let pending: Vec<ReviewMetadata> = all_reviews
+        .into_iter()
+        .filter(|review| EmbeddingStatus::of(review, stored_slots) == EmbeddingStatus::Pending)
+        .filter(|review| search_request.filters.as_ref().is_none_or(|filters| filters.matches(review)))
+        .filter(|review| parsed_query.admits(review, &search_request.fields))
+        .collect();
+    search_results.extend(perform_text_search(
+        &searched_query,
+        &expanded_terms,
+        &synonyms::current(data_paths),
+        &pending,
+        &search_request.fields,
+        window,
+        &scoring,
+    ));
+    search_results.sort_by(SearchResult::rank_order);
+    search_results.truncate(window);
+    if let Some(min_score) = search_request.min_score {
+        search_results.retain(|result| result.similarity_score >= min_score);
+    }
+
+    if let Some(profile) = &profile {
+        profile.rerank(&mut search_results);
+    }
+    let applied_rules = grouped_curation(&search_request, &curation, &mut search_results, &pinned, depth);
+    let (search_results, next_cursor) = search_request.page(search_results);
+
+    tracing::info!(
+        "Search performed for query: '{}', found {} results",
+        search_request.query,
+        search_results.len()
+    );
+
+    // Return search results
+    Ok(SearchResponse {
+        success: true,
+        total_results: search_results.len(),
+        limit,
+        offset: search_request.get_offset(),
+        next_cursor,
+        query: search_request.query,
+        results: search_results,
+        filters: search_request.filters,
+        search_type: "vector_similarity".to_string(),
+        corrected_query,
+        expanded_terms,
+        personalized: profile.is_some(),
+        applied_rules,
+        parsed_query: Some(parsed_query).filter(query_syntax::ParsedQuery::has_operators),
+        fallback: None,
+    })
+}
+
+/// Apply curation, then collapse the results to one per product if the search asks.
+/// Grouping counts every ranked match, so curation keeps the whole window rather than
+/// cutting it to `depth`.
+pub fn grouped_curation(
+    search_request: &SearchRequest,
+    curation: &curation::Curation,
+    results: &mut Vec<SearchResult>,
+    pinned: &[ReviewMetadata],
+    depth: usize,
+) -> Vec<String> {
+    if !search_request.group_by_product {
+        return curation.apply(results, pinned, depth);
+    }
+    let applied_rules = curation.apply(results, pinned, results.len().max(depth));
+    *results = SearchResult::group_by_product(std::mem::take(results));
+    applied_rules
+}
+
+/// Rank embedded reviews by the cosine similarity of their vector to the query's.
+/// The nearest slots are hydrated from reviews.jsonl and filtered; when filters leave
+/// fewer than `limit`, a wider neighbourhood is searched. The caller must hold the
+/// FileLock shared.
+pub fn perform_vector_search(
+    jsonl_storage: &JsonlStorage,
+    vector_store: &VectorStore,
+    query_vector: &[f32],
+    excluded: &SlotBitmap,
+    filters: Option<&SearchFilters>,
+    limit: usize,
+    scoring: &Scoring,
+) -> Result<Vec<SearchResult>, AppError> {
+    let mut k = limit;
+    loop {
+        let neighbors = vector_store.nearest(query_vector, k, excluded)?;
+        let slots: Vec<usize> = neighbors.iter().map(|(slot, _)| *slot).collect();
+        let reviews = jsonl_storage.get_reviews_by_indices(&slots)?;
+
+        let mut results: Vec<SearchResult> = neighbors
+            .iter()
+            .zip(reviews)
+            .filter_map(|(&(slot, similarity), review)| {
+                let review = review.filter(|review| review.vector_index == slot && !review.is_deleted())?;
+                if filters.is_some_and(|filters| !filters.matches(&review)) {
+                    return None;
+                }
+                Some(scoring.result(review, ScoreExplanation::vector(similarity, similarity.clamp(0.0, 1.0))))
+            })
+            .collect();
+
+        // Done once the page is full or every matching slot has been seen
+        if results.len() >= limit || neighbors.len() < k {
+            results.sort_by(SearchResult::rank_order);
+            results.truncate(limit);
+            return Ok(results);
+        }
+        k = k.saturating_mul(4);
+    }
+}
+
+/// Rank embedded reviews by the similarity of one field to the query. The field's
+/// vector is computed here, as reviews.index only holds whole-review vectors; reviews
+/// in `excluded` slots (deleted or ruled out by filters) are skipped.
+fn perform_field_vector_search(
+    reviews: &[ReviewMetadata],
+    stored_slots: usize,
+    field: SearchField,
+    query_vector: &[f32],
+    excluded: &SlotBitmap,
+    limit: usize,
+    scoring: &Scoring,
+) -> Vec<SearchResult> {
+    let embedder = embedding::default_embedder();
+    let mut results: Vec<SearchResult> = reviews
+        .iter()
+        .filter(|review| !excluded.contains(review.vector_index))
+        .filter(|review| EmbeddingStatus::of(review, stored_slots) != EmbeddingStatus::Pending)
+        .filter_map(|review| {
+            let field_vector = embedder.embed(embedding::field_text(review, field));
+            let similarity: f32 = query_vector.iter().zip(&field_vector).map(|(a, b)| a * b).sum();
+            let explanation = ScoreExplanation::vector(similarity, similarity.min(1.0));
+            (similarity > 0.0).then(|| scoring.result(review.clone(), explanation))
+        })
+        .collect();
+    results.sort_by(SearchResult::rank_order);
+    results.truncate(limit);
+    results
+}
+
+/// Keyword search, for reviews that have no current vector yet; `fields` restricts
+/// which of title and body are matched, both when empty
+fn perform_text_search(
+    query: &str,
+    expanded_terms: &[String],
+    synonyms: &synonyms::Synonyms,
+    reviews: &[ReviewMetadata],
+    fields: &[SearchField],
+    limit: usize,
+    scoring: &Scoring,
+) -> Vec<SearchResult> {
+    let keyword_query = KeywordQuery::new(query, expanded_terms, synonyms);
+    
+    if keyword_query.words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<SearchResult> = reviews
+        .iter()
+        .filter_map(|review| {
+            let keyword = keyword_score(&keyword_query, fields, &scoring.weights, review);
+            let match_score = keyword.total.clamp(0.0, 1.0);
+            // Only reviews matching the query are adjusted, so none enters on helpfulness or age alone
+            (match_score > 0.0).then(|| {
+                let explanation = ScoreExplanation {
+                    keyword: Some(keyword),
+                    ..ScoreExplanation::new(MatchKind::Keyword, match_score)
+                };
+                scoring.result(review.clone(), explanation)
+            })
+        })
+        .filter(|result| result.similarity_score > 0.0) // Only include reviews with some similarity
+        .collect();
+
+    // Sort by similarity score in descending order
+    results.sort_by(|a, b| b.similarity_score.partial_cmp(&a.similarity_score).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Take top results up to limit
+    results.truncate(limit);
+    results
+}
+
+/// Share of the ranking score taken by helpfulness when a search asks for it
+const HELPFULNESS_WEIGHT: f32 = 0.2;
+
+/// Age at which a review's recency is worth half that of a new one
+const RECENCY_HALF_LIFE_DAYS: f32 = 30.0;
+
+/// What a search blends into each hit's match score
+pub struct Scoring {
+    rank_by_helpfulness: bool,
+    weights: RankingWeights,
+    now: chrono::DateTime<chrono::Utc>,
+    explain: bool,
+}
+
+impl Scoring {
+    /// Match scores as they are, for callers with no search options
+    pub fn plain(now: chrono::DateTime<chrono::Utc>) -> Self {
+        Scoring {
+            rank_by_helpfulness: false,
+            weights: RankingWeights::default(),
+            now,
+            explain: false,
+        }
+    }
+
+    pub fn of(search_request: &SearchRequest, now: chrono::DateTime<chrono::Utc>) -> Self {
+        Scoring {
+            rank_by_helpfulness: search_request.rank_by_helpfulness,
+            weights: search_request.ranking.clone().unwrap_or_default(),
+            now,
+            explain: search_request.explain,
+        }
+    }
+
+    /// Blend helpfulness and recency into a match score, as the search asked
+    pub fn adjust(&self, score: f32, review: &ReviewMetadata) -> f32 {
+        self.blend(score, review).0
+    }
+
+    /// A ranked result: the explained match score, adjusted, with the explanation kept
+    /// when the search asked for it
+    pub fn result(&self, review: ReviewMetadata, explanation: ScoreExplanation) -> SearchResult {
+        let (score, helpfulness, recency) = self.blend(explanation.match_score, &review);
+        SearchResult {
+            review,
+            similarity_score: score,
+            product_hits: None,
+            explanation: self.explain.then_some(ScoreExplanation { helpfulness, recency, ..explanation }),
+            collection: None,
+        }
+    }
+
+    fn blend(&self, mut score: f32, review: &ReviewMetadata) -> (f32, Option<Blend>, Option<Blend>) {
+        let helpfulness = self.rank_by_helpfulness.then(|| Blend {
+            value: review.helpfulness() as f32,
+            weight: HELPFULNESS_WEIGHT,
+        });
+        let recency_boost = self.weights.get_recency_boost();
+        let recency = (recency_boost > 0.0).then(|| {
+            let age_days = (self.now - review.timestamp).num_seconds().max(0) as f32 / 86_400.0;
+            Blend {
+                value: 0.5_f32.powf(age_days / RECENCY_HALF_LIFE_DAYS),
+                weight: recency_boost,
+            }
+        });
+        for blend in [helpfulness, recency].into_iter().flatten() {
+            score = blend.apply(score);
+        }
+        (score, helpfulness, recency)
+    }
+}
+
+/// Score added for each expanded term a review contains; below a title or body match
+/// of the query's own words
+const EXPANDED_TERM_WEIGHT: f32 = 0.3;
+
+/// A query analyzed for keyword scoring, once per search
+struct KeywordQuery {
+    /// The normalized query, for phrase matching
+    phrase: String,
+    /// Query words, each with the stemmed terms that count as finding it: its own stem
+    /// and its synonyms'
+    words: Vec<(String, Vec<String>)>,
+    /// Expansion terms, with their stemmed form
+    expanded: Vec<(String, String)>,
+}
+
+impl KeywordQuery {
+    fn new(query: &str, expanded_terms: &[String], synonyms: &synonyms::Synonyms) -> Self {
+        let phrase = tokenizer::normalize(query);
+        let tokens = tokenizer::tokenize(&phrase);
+        // Stopwords say nothing about a review, unless the query has nothing else
+        let stopwords = tokenizer::stopwords();
+        let words: Vec<String> = match tokens.iter().all(|token| stopwords.contains(token)) {
+            true => tokens,
+            false => tokens.into_iter().filter(|token| !stopwords.contains(token)).collect(),
+        };
+        let stemmed = |term: &str| Some(tokenizer::keyword_terms(term).join(" ")).filter(|terms| !terms.is_empty());
+
+        let words = words
+            .into_iter()
+            .map(|word| {
+                let mut terms = vec![tokenizer::stem(&word)];
+                terms.extend(synonyms.alternatives(&word).iter().filter_map(|term| stemmed(term)));
+                (word, terms)
+            })
+            .collect();
+        let expanded = expanded_terms.iter().filter_map(|term| Some((term.clone(), stemmed(term)?))).collect();
+        KeywordQuery { phrase, words, expanded }
+    }
+}
+
+/// Keyword match of a review to the query, in parts; the score is their total clamped to 0-1.
+/// Words and expansion terms match the stemmed, stopword-free terms of the review's text,
+/// the phrase its normalized text.
+fn keyword_score(
+    query: &KeywordQuery,
+    fields: &[SearchField],
+    weights: &RankingWeights,
+    review: &ReviewMetadata,
+) -> KeywordScore {
+    // A field left out of the search reads as empty
+    let searched = |field: SearchField| fields.is_empty() || fields.contains(&field);
+    let title_lower = if searched(SearchField::Title) { tokenizer::normalize(&review.title) } else { String::new() };
+    let body_lower = if searched(SearchField::Body) { tokenizer::normalize(&review.body) } else { String::new() };
+    let combined_text = format!("{} {}", title_lower, body_lower);
+    let title_terms = tokenizer::keyword_terms(&title_lower).join(" ");
+    let combined_terms = format!("{} {}", title_terms, tokenizer::keyword_terms(&body_lower).join(" "));
+    
+    let mut keyword = KeywordScore::default();
+    let total_words = query.words.len() as f32;
+    
+    // Exact phrase matching (highest weight)
+    if combined_text.contains(&query.phrase) {
+        keyword.phrase_match = 1.0;
+    }
+    keyword.total += keyword.phrase_match;
+    
+    // Individual word matching; a synonym of the word counts as the word itself
+    for (word, terms) in &query.words {
+        let found_in = |text: &str| terms.iter().any(|term| text.contains(term.as_str()));
+        if found_in(&combined_terms) {
+            // Higher weight for title matches
+            let (field, score) = match found_in(&title_terms) {
+                true => (SearchField::Title, weights.get_title_weight()),
+                false => (SearchField::Body, weights.get_body_weight()),
+            };
+            keyword.total += score;
+            keyword.term_matches.push(TermMatch { term: word.clone(), field, score });
+        }
+    }
+    
+    // Expanded terms widen recall but do not count towards coverage
+    for (term, stemmed) in &query.expanded {
+        if combined_terms.contains(stemmed.as_str()) {
+            keyword.total += EXPANDED_TERM_WEIGHT;
+            keyword.expanded_score += EXPANDED_TERM_WEIGHT;
+            keyword.expanded_terms.push(term.clone());
+        }
+    }
+    
+    // Bonus for high word match ratio
+    keyword.coverage = keyword.term_matches.len() as f32 / total_words * 0.5;
+    keyword.total += keyword.coverage;
+    
+    // Bonus for rating (slight preference for higher-rated reviews)
+    keyword.rating_boost = (review.rating as f32 - 3.0) * weights.get_rating_boost();
+    keyword.total += keyword.rating_boost;
+    keyword
+}
@@ -0,0 +1,165 @@
+//! Sentiment tagging: whether a review's text reads positive, neutral or negative.
+//!
+//! A lexicon classifier, so it needs no model files and tags a review in microseconds
+//! at ingest. Title and body are analyzed like keyword text and each word found in the
+//! positive or negative lexicon counts one way; a negation ("not", "never", the "t" of
+//! "don't") flips the next `NEGATION_SCOPE` words, so "not good" counts as negative.
+//! A review is positive or negative when one side outweighs the other by more than
+//! `MIXED_MARGIN` of the words counted, and neutral otherwise, including mixed reviews
+//! ("great screen, terrible battery") and those with no opinion words at all.
+//!
+//! The tag reflects the text only; the star rating has filters of its own. Reviews are
+//! tagged as they are created or updated while `SENTIMENT_ANALYSIS` is on (the default).
+//! Reviews stored without a tag, before tagging existed, are classified when a search
+//! filters on sentiment, so the filter covers every review. With `SENTIMENT_ANALYSIS`
+//! off nothing is tagged and searches filtering on sentiment are refused.
+
+use crate::models::ReviewMetadata;
+use crate::tokenizer;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use utoipa::ToSchema;
+
+/// Words after a negation whose polarity it flips
+const NEGATION_SCOPE: usize = 3;
+
+/// Share of the opinion words one side must lead by for the review to lean that way
+const MIXED_MARGIN: f32 = 0.25;
+
+const POSITIVE: &[&str] = &[
+    "amazing", "awesome", "beautiful", "best", "brilliant", "comfortable", "convenient", "delighted", "durable",
+    "easy", "effective", "excellent", "fantastic", "fast", "favorite", "favourite", "fine", "flawless", "good",
+    "gorgeous", "great", "happy", "helpful", "impressed", "impressive", "incredible", "love", "loved", "loves",
+    "lovely", "nice", "outstanding", "perfect", "perfectly", "pleased", "quick", "quiet", "recommend",
+    "recommended", "reliable", "satisfied", "smooth", "solid", "sturdy", "superb", "terrific", "wonderful",
+    "works", "worth",
+];
+
+const NEGATIVE: &[&str] = &[
+    "annoying", "awful", "bad", "broke", "broken", "cheap", "complaint", "crap", "damaged", "defective",
+    "difficult", "disappointed", "disappointing", "dissatisfied", "faulty", "flimsy", "garbage", "hate", "hated",
+    "horrible", "junk", "late", "leaks", "loud", "mediocre", "missing", "noisy", "overpriced", "poor", "poorly",
+    "problem", "problems", "refund", "returned", "rubbish", "slow", "sucks", "terrible", "unhappy",
+    "unreliable", "unusable", "useless", "waste", "worse", "worst", "wrong",
+];
+
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "nothing", "nor", "neither", "hardly", "barely", "without", "cannot", "t",
+];
+
+/// How a review's text reads
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+impl Sentiment {
+    pub fn name(self) -> &'static str {
+        match self {
+            Sentiment::Positive => "positive",
+            Sentiment::Neutral => "neutral",
+            Sentiment::Negative => "negative",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "positive" => Some(Sentiment::Positive),
+            "neutral" => Some(Sentiment::Neutral),
+            "negative" => Some(Sentiment::Negative),
+            _ => None,
+        }
+    }
+}
+
+/// Whether reviews are tagged and searches may filter on sentiment; on unless
+/// `SENTIMENT_ANALYSIS` is false, 0 or off
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("SENTIMENT_ANALYSIS")
+            .map(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "false" | "0" | "off"))
+            .unwrap_or(true)
+    })
+}
+
+/// Positive and negative opinion words in `text`, with negations applied
+fn opinion_counts(text: &str) -> (usize, usize) {
+    let (mut positive, mut negative) = (0, 0);
+    let mut negated_for: usize = 0;
+    for term in tokenizer::analyze(text) {
+        let negated = negated_for > 0;
+        negated_for = negated_for.saturating_sub(1);
+        if NEGATIONS.contains(&term.as_str()) {
+            negated_for = NEGATION_SCOPE;
+            continue;
+        }
+
+        let polarity = match (POSITIVE.contains(&term.as_str()), NEGATIVE.contains(&term.as_str())) {
+            (true, _) => 1,
+            (_, true) => -1,
+            _ => continue,
+        };
+        match (polarity > 0) != negated {
+            true => positive += 1,
+            false => negative += 1,
+        }
+    }
+    (positive, negative)
+}
+
+/// Classify a review from its title and body
+pub fn classify(title: &str, body: &str) -> Sentiment {
+    // Counted apart so a negation ending the title does not reach into the body
+    let (title_positive, title_negative) = opinion_counts(title);
+    let (body_positive, body_negative) = opinion_counts(body);
+    let (positive, negative) = (title_positive + body_positive, title_negative + body_negative);
+
+    let total = positive + negative;
+    if total == 0 {
+        return Sentiment::Neutral;
+    }
+    let lean = (positive as f32 - negative as f32) / total as f32;
+    if lean > MIXED_MARGIN {
+        Sentiment::Positive
+    } else if lean < -MIXED_MARGIN {
+        Sentiment::Negative
+    } else {
+        Sentiment::Neutral
+    }
+}
+
+/// The tag to store with a review being created or updated, if tagging is on
+pub fn tag(title: &str, body: &str) -> Option<Sentiment> {
+    enabled().then(|| classify(title, body))
+}
+
+/// A review's sentiment: its stored tag, or its text classified now when untagged
+pub fn of(review: &ReviewMetadata) -> Sentiment {
+    review.sentiment.unwrap_or_else(|| classify(&review.title, &review.body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_reads_opinion_words_and_negations() {
+        assert_eq!(classify("Love it", "Great sound and excellent battery."), Sentiment::Positive);
+        assert_eq!(classify("Broke in a week", "Terrible quality, total waste of money."), Sentiment::Negative);
+        assert_eq!(classify("Kettle", "Holds 1.7 litres."), Sentiment::Neutral);
+        // Mixed opinions even out
+        let mixed = classify("Good and bad", "Great screen but awful battery and a slow charger, nice case.");
+        assert_eq!(mixed, Sentiment::Neutral);
+
+        assert_eq!(classify("Not good", "It does not work and was not worth it."), Sentiment::Negative);
+        assert_eq!(classify("Fine", "Shipping wasn't slow at all, no problems."), Sentiment::Positive);
+        assert_eq!(classify("Don't bother", "The blender isn't reliable."), Sentiment::Negative);
+
+        assert_eq!(Sentiment::parse(" Negative"), Some(Sentiment::Negative));
+        assert_eq!(Sentiment::parse("angry"), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! "More like this": the reviews most similar to a stored one.
+//!
+//! An embedded review is compared by its stored vector. Until the embedding worker
+//! reaches it, its text is embedded on the spot instead, which gives the same vector
+//! the worker will store. Candidates are the embedded reviews, by vector search, and
+//! the pending ones, embedded on the spot too, so a review written a moment ago can
+//! match and be matched. Deleted and search-blocklisted reviews are never returned.
+
+use crate::blocklist::{BlockScope, Blocklist};
+use crate::embedding::{self, Embedder, EmbeddingStatus};
+use crate::models::*;
+use crate::search;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of `POST /reviews/{id}/similar`; every field is optional
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct SimilarReviewsRequest {
+    pub limit: Option<usize>, // Default: 10
+    /// Restrictions on the similar reviews, as in a search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<SearchFilters>,
+}
+
+impl SimilarReviewsRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_SEARCH_LIMIT {
+                return Err(ValidationError::InvalidValue {
+                    field: "limit".to_string(),
+                    reason: format!("must be between 1 and {}", MAX_SEARCH_LIMIT),
+                });
+            }
+        }
+        if let Some(filters) = &self.filters {
+            filters.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(10)
+    }
+}
+
+/// Response of `POST /reviews/{id}/similar`
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimilarReviewsResponse {
+    pub success: bool,
+    pub review_id: String,
+    /// `embedded` when the review's stored vector was compared, `pending` when its text
+    /// was embedded for the request
+    pub embedding_status: EmbeddingStatus,
+    /// Most similar first; `similarity_score` is the cosine similarity
+    pub results: Vec<SearchResult>,
+    pub total_results: usize,
+    pub limit: usize,
+}
+
+/// The reviews most similar to the live review `review_id`, best first
+pub fn find_similar(
+    data_paths: &DataPaths,
+    review_id: &str,
+    request: &SimilarReviewsRequest,
+) -> Result<SimilarReviewsResponse, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
+    let embedder = embedding::default_embedder();
+    let limit = request.get_limit();
+
+    let _lock = FileLock::acquire_shared(&data_paths.lock_file, "similar_reviews")?;
+    let review = match storage.get_review_by_id(review_id)? {
+        Some(review) if !review.is_deleted() => review,
+        _ => {
+            return Err(AppError::NotFound {
+                resource: "Review".to_string(),
+                id: review_id.to_string(),
+            })
+        }
+    };
+
+    let stored_slots = vector_store.len()?;
+    let embedding_status = EmbeddingStatus::of(&review, stored_slots);
+    let stored_vector = match embedding_status {
+        EmbeddingStatus::Embedded => vector_store.read_slots(&[review.vector_index])?.pop().flatten(),
+        EmbeddingStatus::Pending => None,
+    };
+    let vector = stored_vector.unwrap_or_else(|| embedder.embed(&embedding::review_text(&review)));
+
+    // The review itself, blocked reviews and those the filters rule out never match
+    let blocklist = Blocklist::load(data_paths, BlockScope::Search)?;
+    let mut candidates = storage.read_live_reviews()?;
+    let mut excluded = storage.excluded_vector_slots()?;
+    excluded.set(review.vector_index);
+    blocklist.remove_blocked(&mut candidates, &mut excluded);
+    candidates.retain(|candidate| match &request.filters {
+        Some(filters) if !filters.matches(candidate) => {
+            excluded.set(candidate.vector_index);
+            false
+        }
+        _ => candidate.id != review.id,
+    });
+
+    let scoring = search::Scoring::plain(chrono::Utc::now());
+    let mut results =
+        search::perform_vector_search(&storage, &vector_store, &vector, &excluded, request.filters.as_ref(), limit, &scoring)?;
+    results.extend(
+        candidates
+            .into_iter()
+            .filter(|candidate| EmbeddingStatus::of(candidate, stored_slots) == EmbeddingStatus::Pending)
+            .filter_map(|candidate| {
+                let candidate_vector = embedder.embed(&embedding::review_text(&candidate));
+                let similarity: f32 = vector.iter().zip(&candidate_vector).map(|(a, b)| a * b).sum();
+                (similarity > 0.0).then(|| SearchResult {
+                    review: candidate,
+                    similarity_score: similarity.min(1.0),
+                    product_hits: None,
+                    explanation: None,
+                    collection: None,
+                })
+            }),
+    );
+    results.sort_by(SearchResult::rank_order);
+    results.truncate(limit);
+
+    Ok(SimilarReviewsResponse {
+        success: true,
+        review_id: review.id,
+        embedding_status,
+        total_results: results.len(),
+        results,
+        limit,
+    })
+}
@@ -0,0 +1,154 @@
+//! Pairwise similarity of a chosen set of reviews, for clustering views and notebooks
+//! that want the whole matrix rather than one review's neighbours.
+//!
+//! Each review is compared by its stored vector; one the embedding worker hasn't
+//! reached yet is embedded on the spot, as for "more like this", and listed as such.
+//! Entries are cosine similarities, so the matrix is symmetric with ones on the
+//! diagonal. Every id must name a live review: the matrix is all or nothing.
+
+use crate::embedding::{self, Embedder, EmbeddingStatus};
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock, JsonlStorage};
+use crate::vectors::VectorStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+/// Most reviews one matrix may compare; the response grows with the square
+pub const MAX_MATRIX_REVIEWS: usize = 200;
+
+/// Body of `POST /analyze/similarity-matrix`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct SimilarityMatrixRequest {
+    /// Reviews to compare, 2-200 distinct ids; rows and columns follow this order
+    pub ids: Vec<String>,
+}
+
+impl SimilarityMatrixRequest {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.ids.len() < 2 || self.ids.len() > MAX_MATRIX_REVIEWS {
+            return Err(ValidationError::InvalidValue {
+                field: "ids".to_string(),
+                reason: format!("must contain between 2 and {} review ids", MAX_MATRIX_REVIEWS),
+            });
+        }
+        let mut seen = HashSet::new();
+        if let Some(repeated) = self.ids.iter().find(|id| !seen.insert(id.as_str())) {
+            return Err(ValidationError::InvalidValue {
+                field: "ids".to_string(),
+                reason: format!("review {} is listed more than once", repeated),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Response of `POST /analyze/similarity-matrix`
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimilarityMatrixResponse {
+    pub success: bool,
+    /// The requested ids, in the order of the matrix's rows and columns
+    pub review_ids: Vec<String>,
+    /// `matrix[i][j]` is the cosine similarity of reviews `i` and `j`
+    pub matrix: Vec<Vec<f32>>,
+    /// Reviews not yet embedded by the worker, whose text was embedded for the request
+    pub pending: Vec<String>,
+}
+
+/// Cosine similarity of every pair of vectors, each pair computed once
+pub fn matrix(vectors: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    let norms: Vec<f32> = vectors
+        .iter()
+        .map(|vector| vector.iter().map(|value| value * value).sum::<f32>().sqrt())
+        .collect();
+    let mut matrix = vec![vec![0.0f32; vectors.len()]; vectors.len()];
+    for i in 0..vectors.len() {
+        for j in i..vectors.len() {
+            let similarity = if norms[i] == 0.0 || norms[j] == 0.0 {
+                0.0
+            } else if i == j {
+                1.0
+            } else {
+                let dot: f32 = vectors[i].iter().zip(&vectors[j]).map(|(a, b)| a * b).sum();
+                (dot / (norms[i] * norms[j])).clamp(-1.0, 1.0)
+            };
+            matrix[i][j] = similarity;
+            matrix[j][i] = similarity;
+        }
+    }
+    matrix
+}
+
+/// The similarity matrix of the live reviews `request.ids`
+pub fn compute(data_paths: &DataPaths, request: &SimilarityMatrixRequest) -> Result<SimilarityMatrixResponse, AppError> {
+    request.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let storage = JsonlStorage::new(&data_paths.reviews_jsonl);
+    let vector_store = VectorStore::new(&data_paths.reviews_index, embedding::EMBEDDING_DIM);
+
+    // Hold the lock shared so the slots read belong to the reviews looked up
+    let lock = FileLock::acquire_shared(&data_paths.lock_file, "similarity_matrix")?;
+    let mut reviews = Vec::with_capacity(request.ids.len());
+    for id in &request.ids {
+        match storage.get_review_by_id(id)? {
+            Some(review) if !review.is_deleted() => reviews.push(review),
+            _ => {
+                return Err(AppError::NotFound {
+                    resource: "Review".to_string(),
+                    id: id.clone(),
+                })
+            }
+        }
+    }
+    let stored_slots = vector_store.len()?;
+    let embedded: Vec<usize> = reviews
+        .iter()
+        .filter(|review| EmbeddingStatus::of(review, stored_slots) == EmbeddingStatus::Embedded)
+        .map(|review| review.vector_index)
+        .collect();
+    let mut stored = vector_store.read_slots(&embedded)?.into_iter();
+    drop(lock);
+
+    let embedder = embedding::default_embedder();
+    let mut pending = Vec::new();
+    let vectors: Vec<Vec<f32>> = reviews
+        .iter()
+        .map(|review| {
+            let vector = match EmbeddingStatus::of(review, stored_slots) {
+                EmbeddingStatus::Embedded => stored.next().flatten(),
+                EmbeddingStatus::Pending => None,
+            };
+            vector.unwrap_or_else(|| {
+                pending.push(review.id.clone());
+                embedder.embed(&embedding::review_text(review))
+            })
+        })
+        .collect();
+
+    Ok(SimilarityMatrixResponse {
+        success: true,
+        review_ids: request.ids.clone(),
+        matrix: matrix(&vectors),
+        pending,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_is_symmetric_cosine() {
+        let vectors = vec![vec![1.0, 0.0], vec![3.0, 4.0], vec![0.0, 0.0]];
+        let matrix = matrix(&vectors);
+        assert_eq!(matrix[0], vec![1.0, 0.6, 0.0]);
+        assert_eq!(matrix[1][0], 0.6);
+        assert_eq!(matrix[1][1], 1.0);
+        assert_eq!(matrix[2], vec![0.0, 0.0, 0.0]);
+
+        let repeated = SimilarityMatrixRequest { ids: vec!["a".into(), "b".into(), "a".into()] };
+        assert!(repeated.validate().unwrap_err().to_string().contains("more than once"));
+        assert!(SimilarityMatrixRequest { ids: vec!["a".into()] }.validate().is_err());
+    }
+}
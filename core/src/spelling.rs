@@ -0,0 +1,223 @@
+//! Typo tolerance: query words missing from the review vocabulary are replaced by the
+//! closest word that reviews do use, so "batery life" searches for "battery life".
+//!
+//! The vocabulary is every analyzed term of the live reviews' titles and bodies, with
+//! the number of reviews using it, built once per version of the review set. A word is
+//! corrected only when it is not in the vocabulary, has at least four letters and a
+//! vocabulary term lies within one edit of it (two from eight letters on). An edit is
+//! an insertion, deletion, substitution or swap of neighbouring letters. Among equally
+//! close terms the one used by the most reviews wins.
+//!
+//! Correction is on unless `SPELL_CORRECTION` is false, 0 or off; a search can opt out
+//! with `correct_typos: false`.
+
+use crate::embedding;
+use crate::expansion;
+use crate::models::ReviewMetadata;
+use crate::tokenizer;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Shortest word corrected; shorter ones have too many neighbours to guess between
+const MIN_WORD_LENGTH: usize = 4;
+
+/// Words at least this long may be two edits from their correction
+const TWO_EDIT_LENGTH: usize = 8;
+
+/// Most terms kept, by the number of reviews using them
+const MAX_VOCABULARY: usize = 50_000;
+
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("SPELL_CORRECTION")
+            .map(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "false" | "0" | "off"))
+            .unwrap_or(true)
+    })
+}
+
+/// Analyzed terms of a review set, each with the number of reviews using it
+pub struct Vocabulary {
+    frequencies: HashMap<String, usize>,
+}
+
+impl Vocabulary {
+    pub fn build(reviews: &[ReviewMetadata]) -> Self {
+        let mut frequencies: HashMap<String, usize> = HashMap::new();
+        for review in reviews {
+            let terms: HashSet<String> = tokenizer::analyze(&embedding::review_text(review)).into_iter().collect();
+            for term in terms {
+                *frequencies.entry(term).or_insert(0) += 1;
+            }
+        }
+
+        if frequencies.len() > MAX_VOCABULARY {
+            let mut ranked: Vec<(String, usize)> = frequencies.into_iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            ranked.truncate(MAX_VOCABULARY);
+            frequencies = ranked.into_iter().collect();
+        }
+        Vocabulary { frequencies }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+
+    /// The vocabulary term `term` was probably meant to be, if it is misspelled
+    pub fn correction(&self, term: &str) -> Option<&str> {
+        let length = term.chars().count();
+        if length < MIN_WORD_LENGTH
+            || self.frequencies.contains_key(term)
+            || !term.chars().all(char::is_alphabetic)
+            || tokenizer::detect_language(term) == tokenizer::Language::Cjk
+        {
+            return None;
+        }
+        let max_edits = if length >= TWO_EDIT_LENGTH { 2 } else { 1 };
+
+        self.frequencies
+            .iter()
+            .filter(|(candidate, _)| candidate.chars().count().abs_diff(length) <= max_edits)
+            .filter_map(|(candidate, &frequency)| {
+                let edits = edit_distance(term, candidate);
+                (edits <= max_edits).then_some((edits, frequency, candidate.as_str()))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)).then_with(|| a.2.cmp(b.2)))
+            .map(|(_, _, candidate)| candidate)
+    }
+
+    /// `query` with each misspelled word replaced by its correction, or `None` when
+    /// every word is spelled as reviews spell it
+    pub fn correct(&self, query: &str) -> Option<String> {
+        let mut changed = false;
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| {
+                // Words the analyzer splits (e.g. "usb-c") are left as they are
+                let correction = match tokenizer::analyze(word).as_slice() {
+                    [term] => self.correction(term),
+                    _ => None,
+                };
+                match correction {
+                    Some(correction) => {
+                        changed = true;
+                        correction.to_string()
+                    }
+                    None => word.to_string(),
+                }
+            })
+            .collect();
+        changed.then(|| words.join(" "))
+    }
+}
+
+/// Edits turning `a` into `b`, counting a swap of neighbouring letters as one
+/// (optimal string alignment distance)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Three rows: the previous two and the current one
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Vocabulary of `reviews`, rebuilt only when the review set changed
+fn vocabulary(reviews: &[ReviewMetadata]) -> Arc<Vocabulary> {
+    static CACHE: Mutex<Option<(u64, Arc<Vocabulary>)>> = Mutex::new(None);
+
+    let version = expansion::version(reviews);
+    let mut cached = CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match cached.as_ref() {
+        Some((cached_version, vocabulary)) if *cached_version == version => vocabulary.clone(),
+        _ => {
+            let vocabulary = Arc::new(Vocabulary::build(reviews));
+            tracing::info!("Built spelling vocabulary of {} terms", vocabulary.len());
+            *cached = Some((version, vocabulary.clone()));
+            vocabulary
+        }
+    }
+}
+
+/// `query` with its typos corrected against `reviews` (all searchable reviews, before
+/// filters); `None` when correction is off or there is nothing to correct
+pub fn correct(query: &str, reviews: &[ReviewMetadata]) -> Option<String> {
+    if !enabled() {
+        return None;
+    }
+    vocabulary(reviews).correct(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn review(id: &str, title: &str, body: &str) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            product_id: "phone".to_string(),
+            rating: 4,
+            timestamp: Utc::now(),
+            vector_index: 0,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_misspelled_words_are_corrected_from_the_vocabulary() {
+        assert_eq!(edit_distance("batery", "battery"), 1);
+        assert_eq!(edit_distance("recieve", "receive"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+        let vocabulary = Vocabulary::build(&[
+            review("1", "Great battery", "The battery life is long"),
+            review("2", "Battery", "Battery lasts all day, screen is sharp"),
+            review("3", "Bitter taste", "The coffee is bitter"),
+            review("4", "Headphones", "Comfortable headphones with clear sound"),
+        ]);
+
+        assert_eq!(vocabulary.correct("batery life").as_deref(), Some("battery life"));
+        assert_eq!(vocabulary.correct("Batery LIFE").as_deref(), Some("battery LIFE"));
+        // Two edits are allowed from eight letters
+        assert_eq!(vocabulary.correct("headfones comfortible").as_deref(), Some("headphones comfortable"));
+        // Known, short and numeric words are left alone
+        assert_eq!(vocabulary.correct("battery life"), None);
+        assert_eq!(vocabulary.correct("lif 1080p"), None);
+        // Nothing within reach
+        assert_eq!(vocabulary.correct("keyboard"), None);
+    }
+}
@@ -0,0 +1,1111 @@
+use crate::models::*;
+use crate::sampling::{self, SampleRng};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write, BufWriter};
+use std::sync::{Mutex, OnceLock};
+
+pub use crate::lock::FileLock;
+
+/// Data directory structure constants
+pub struct DataPaths {
+    pub data_dir: PathBuf,
+    pub reviews_jsonl: PathBuf,
+    pub reviews_index: PathBuf,
+    pub jobs_jsonl: PathBuf,
+    pub idempotency_jsonl: PathBuf,
+    pub webhooks_jsonl: PathBuf,
+    pub curation_jsonl: PathBuf,
+    pub blocklist_jsonl: PathBuf,
+    pub blocklist_audit_jsonl: PathBuf,
+    pub erasure_receipts_jsonl: PathBuf,
+    pub queries_jsonl: PathBuf,
+    pub synonyms_txt: PathBuf,
+    pub synonyms_json: PathBuf,
+    pub lock_file: PathBuf,
+}
+
+impl DataPaths {
+    /// Create new DataPaths with the given data directory
+    pub fn new<P: AsRef<Path>>(data_dir: P) -> Self {
+        let data_dir = data_dir.as_ref().to_path_buf();
+        
+        Self {
+            reviews_jsonl: data_dir.join("reviews.jsonl"),
+            reviews_index: data_dir.join("reviews.index"),
+            jobs_jsonl: data_dir.join("jobs.jsonl"),
+            idempotency_jsonl: data_dir.join("idempotency.jsonl"),
+            webhooks_jsonl: data_dir.join("webhooks.jsonl"),
+            curation_jsonl: data_dir.join("curation.jsonl"),
+            blocklist_jsonl: data_dir.join("blocklist.jsonl"),
+            blocklist_audit_jsonl: data_dir.join("blocklist_audit.jsonl"),
+            erasure_receipts_jsonl: data_dir.join("erasure_receipts.jsonl"),
+            queries_jsonl: data_dir.join("queries.jsonl"),
+            synonyms_txt: data_dir.join("synonyms.txt"),
+            synonyms_json: data_dir.join("synonyms.json"),
+            lock_file: data_dir.join(".lock"),
+            data_dir,
+        }
+    }
+    
+    /// Ensure all necessary directories exist
+    pub fn ensure_directories(&self) -> Result<(), AppError> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        Ok(())
+    }
+    
+    /// Check that the data directory is usable: it exists and is writable,
+    /// and the review file, if any, can be opened for reading
+    pub fn check_storage(&self) -> Result<(), AppError> {
+        self.ensure_directories()?;
+        if std::fs::metadata(&self.data_dir)?.permissions().readonly() {
+            return Err(AppError::FileOperation(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is read-only", self.data_dir.display()),
+            )));
+        }
+        // The permission bits do not tell the whole story (root, read-only mounts, a full disk)
+        let probe = self.data_dir.join(format!(".write-probe-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&probe, b"ok")?;
+        std::fs::remove_file(&probe)?;
+        if self.reviews_jsonl.exists() {
+            File::open(&self.reviews_jsonl)?;
+        }
+        Ok(())
+    }
+    
+    /// Check if data files exist
+    pub fn files_exist(&self) -> (bool, bool) {
+        (
+            self.reviews_jsonl.exists(),
+            self.reviews_index.exists(),
+        )
+    }
+}
+
+/// In-memory id -> byte offset and product_id -> byte offsets indexes for one JSONL file,
+/// plus the vector slots searches must skip. Extended incrementally as the file grows,
+/// rebuilt when it shrinks or is rewritten.
+#[derive(Default)]
+struct IdIndex {
+    /// Identity of the indexed file. Rewrites rename a new file into place, so this
+    /// catches them even when another process sharing the data dir made them.
+    identity: Option<u64>,
+    indexed_len: u64,
+    offsets: HashMap<String, u64>,
+    product_offsets: HashMap<String, Vec<u64>>,
+    excluded: SlotBitmap,
+}
+
+impl IdIndex {
+    fn record(&mut self, offset: u64, id: String, product_id: String, vector_index: usize, excluded: bool) {
+        self.offsets.insert(id, offset);
+        self.product_offsets.entry(product_id).or_default().push(offset);
+        if excluded {
+            self.excluded.set(vector_index);
+        }
+    }
+
+    /// Index lines appended since the last refresh, falling back to a full
+    /// rebuild if the tail doesn't parse (the file was rewritten and grew)
+    fn refresh(&mut self, file_path: &Path) -> Result<(), AppError> {
+        if self.index_tail(file_path).is_err() {
+            *self = IdIndex::default();
+            self.index_tail(file_path)?;
+        }
+        Ok(())
+    }
+
+    fn index_tail(&mut self, file_path: &Path) -> Result<(), AppError> {
+        #[derive(Deserialize)]
+        struct IndexedFields {
+            id: String,
+            product_id: String,
+            vector_index: usize,
+            #[serde(default)]
+            deleted_at: Option<serde::de::IgnoredAny>,
+            #[serde(default)]
+            needs_reembedding: bool,
+        }
+
+        let metadata = std::fs::metadata(file_path)?;
+        let (file_len, identity) = (metadata.len(), file_identity(&metadata));
+        if file_len < self.indexed_len || identity != self.identity {
+            *self = IdIndex {
+                identity,
+                ..IdIndex::default()
+            };
+        }
+        if file_len == self.indexed_len {
+            return Ok(());
+        }
+
+        let mut file = File::open(file_path)?;
+        file.seek(SeekFrom::Start(self.indexed_len))?;
+        let mut reader = BufReader::new(file);
+
+        let mut offset = self.indexed_len;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes_read = reader.read_line(&mut line)?;
+            // Stop at EOF or at a partially written trailing line
+            if bytes_read == 0 || !line.ends_with('\n') {
+                break;
+            }
+
+            if !line.trim().is_empty() {
+                let record: IndexedFields = serde_json::from_str(line.trim_end())?;
+                let excluded = record.deleted_at.is_some() || record.needs_reembedding;
+                self.record(offset, record.id, record.product_id, record.vector_index, excluded);
+            }
+            offset += bytes_read as u64;
+        }
+
+        self.indexed_len = offset;
+        Ok(())
+    }
+}
+
+/// The file's inode, which changes when another file is renamed over it. `None` where
+/// the platform has no stable equivalent; a shrink or unparseable tail still triggers a rebuild.
+fn file_identity(metadata: &std::fs::Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Process-wide id indexes keyed by JSONL file path
+fn id_indexes() -> &'static Mutex<HashMap<PathBuf, IdIndex>> {
+    static ID_INDEXES: OnceLock<Mutex<HashMap<PathBuf, IdIndex>>> = OnceLock::new();
+    ID_INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// JSONL file operations for ReviewMetadata
+pub struct JsonlStorage {
+    file_path: PathBuf,
+}
+
+impl JsonlStorage {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+    
+    /// Append a single ReviewMetadata to the JSONL file
+    pub fn append_review(&self, review: &ReviewMetadata) -> Result<(), AppError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)?;
+            
+        let json_line = serde_json::to_string(review)?;
+        writeln!(file, "{}", json_line)?;
+        file.flush()?;
+        
+        Ok(())
+    }
+    
+    /// Append multiple ReviewMetadata to the JSONL file
+    pub fn append_reviews(&self, reviews: &[ReviewMetadata]) -> Result<(), AppError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)?;
+            
+        let mut writer = BufWriter::new(&mut file);
+        
+        for review in reviews {
+            let json_line = serde_json::to_string(review)?;
+            writeln!(writer, "{}", json_line)?;
+        }
+        
+        writer.flush()?;
+        Ok(())
+    }
+    
+    /// Append reviews as one write, cutting the file back to its old length if the
+    /// write fails, so either every review is stored or none is. The caller must hold
+    /// the FileLock.
+    pub fn append_reviews_all_or_nothing(&self, reviews: &[ReviewMetadata]) -> Result<(), AppError> {
+        let mut bytes = Vec::new();
+        for review in reviews {
+            serde_json::to_writer(&mut bytes, review)?;
+            bytes.push(b'\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)?;
+        let original_len = file.metadata()?.len();
+        if let Err(e) = file.write_all(&bytes).and_then(|_| file.sync_data()) {
+            if let Err(truncate_error) = file.set_len(original_len) {
+                tracing::error!("Failed to cut back a partial batch write: {}", truncate_error);
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    
+    /// Read a specific review by line index (0-based)
+    pub fn get_review_by_index(&self, index: usize) -> Result<Option<ReviewMetadata>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(None);
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        for (line_index, line) in reader.lines().enumerate() {
+            if line_index == index {
+                let line = line?;
+                if line.trim().is_empty() {
+                    return Ok(None);
+                }
+                let review: ReviewMetadata = serde_json::from_str(&line)?;
+                return Ok(Some(review));
+            }
+        }
+        
+        Ok(None)
+    }
+    
+    /// Look up a review by id using the id -> line-offset index
+    pub fn get_review_by_id(&self, id: &str) -> Result<Option<ReviewMetadata>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(None);
+        }
+        
+        let mut indexes = id_indexes().lock().map_err(|_| AppError::Concurrency {
+            message: "ID index lock poisoned".to_string(),
+        })?;
+        let index = indexes.entry(self.file_path.clone()).or_default();
+        // A rewrite renames a new file into place, so an id missing after a refresh isn't stored
+        index.refresh(&self.file_path)?;
+        if !index.offsets.contains_key(id) {
+            return Ok(None);
+        }
+        if let Some(review) = self.read_indexed_review(index, id)? {
+            return Ok(Some(review));
+        }
+
+        // The offset is stale: the file changed in place since indexing; rebuild once and retry
+        *index = IdIndex::default();
+        index.refresh(&self.file_path)?;
+        self.read_indexed_review(index, id)
+    }
+    
+    /// Read all live reviews for a product, in file order, using the product -> line-offsets index
+    pub fn get_reviews_by_product(&self, product_id: &str) -> Result<Vec<ReviewMetadata>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let mut indexes = id_indexes().lock().map_err(|_| AppError::Concurrency {
+            message: "ID index lock poisoned".to_string(),
+        })?;
+        let index = indexes.entry(self.file_path.clone()).or_default();
+        index.refresh(&self.file_path)?;
+        
+        if let Some(reviews) = self.read_product_reviews(index, product_id)? {
+            return Ok(reviews);
+        }
+        
+        // An offset is stale: the file changed in place since indexing; rebuild once and retry
+        *index = IdIndex::default();
+        index.refresh(&self.file_path)?;
+        Ok(self.read_product_reviews(index, product_id)?.unwrap_or_default())
+    }
+    
+    /// Read a product's reviews at their indexed offsets, or `None` if any offset is stale
+    fn read_product_reviews(&self, index: &IdIndex, product_id: &str) -> Result<Option<Vec<ReviewMetadata>>, AppError> {
+        let Some(offsets) = index.product_offsets.get(product_id) else {
+            return Ok(Some(Vec::new()));
+        };
+        
+        let mut reader = BufReader::new(File::open(&self.file_path)?);
+        let mut reviews = Vec::with_capacity(offsets.len());
+        let mut line = String::new();
+        for &offset in offsets {
+            reader.seek(SeekFrom::Start(offset))?;
+            line.clear();
+            reader.read_line(&mut line)?;
+            
+            match serde_json::from_str::<ReviewMetadata>(line.trim_end()) {
+                Ok(review) if review.product_id == product_id => {
+                    if !review.is_deleted() {
+                        reviews.push(review);
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+        
+        Ok(Some(reviews))
+    }
+    
+    /// Rebuild the id and product line-offset indexes from the file now, rather than on
+    /// the next lookup. Returns the number of reviews indexed.
+    pub fn rebuild_id_index(&self) -> Result<usize, AppError> {
+        let mut indexes = id_indexes().lock().map_err(|_| AppError::Concurrency {
+            message: "ID index lock poisoned".to_string(),
+        })?;
+        let index = indexes.entry(self.file_path.clone()).or_default();
+        *index = IdIndex::default();
+        if self.file_path.exists() {
+            index.refresh(&self.file_path)?;
+        }
+        Ok(index.offsets.len())
+    }
+
+    /// Drop the cached id index, e.g. after the file has been rewritten
+    pub fn invalidate_id_index(&self) {
+        if let Ok(mut indexes) = id_indexes().lock() {
+            indexes.remove(&self.file_path);
+        }
+    }
+    
+    /// Read the review at an indexed offset, if it still carries the expected id
+    fn read_indexed_review(&self, index: &IdIndex, id: &str) -> Result<Option<ReviewMetadata>, AppError> {
+        let Some(&offset) = index.offsets.get(id) else {
+            return Ok(None);
+        };
+        
+        let mut file = File::open(&self.file_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        BufReader::new(file).read_line(&mut line)?;
+        
+        match serde_json::from_str::<ReviewMetadata>(line.trim_end()) {
+            Ok(review) if review.id == id => Ok(Some(review)),
+            _ => Ok(None),
+        }
+    }
+    
+    /// Read multiple reviews by their line indices
+    pub fn get_reviews_by_indices(&self, indices: &[usize]) -> Result<Vec<Option<ReviewMetadata>>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(vec![None; indices.len()]);
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        let mut results = vec![None; indices.len()];
+        let mut target_indices: std::collections::HashMap<usize, Vec<usize>> = std::collections::HashMap::new();
+        
+        // Group result indices by line index for efficient lookup
+        for (result_idx, &line_idx) in indices.iter().enumerate() {
+            target_indices.entry(line_idx).or_default().push(result_idx);
+        }
+        
+        for (line_index, line) in reader.lines().enumerate() {
+            if let Some(result_indices) = target_indices.get(&line_index) {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    let review: ReviewMetadata = serde_json::from_str(&line)?;
+                    for &result_idx in result_indices {
+                        results[result_idx] = Some(review.clone());
+                    }
+                }
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    /// Count total number of reviews in the file
+    pub fn count_reviews(&self) -> Result<usize, AppError> {
+        if !self.file_path.exists() {
+            return Ok(0);
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        let mut count = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                count += 1;
+            }
+        }
+        
+        Ok(count)
+    }
+    
+    /// Read all reviews from the file (use with caution for large files)
+    pub fn read_all_reviews(&self) -> Result<Vec<ReviewMetadata>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        let mut reviews = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                let review: ReviewMetadata = serde_json::from_str(&line)?;
+                reviews.push(review);
+            }
+        }
+        
+        Ok(reviews)
+    }
+    
+    /// Read all reviews that have not been tombstoned
+    pub fn read_live_reviews(&self) -> Result<Vec<ReviewMetadata>, AppError> {
+        let mut reviews = self.read_all_reviews()?;
+        reviews.retain(|review| !review.is_deleted());
+        Ok(reviews)
+    }
+
+    /// Vector slots a vector search must skip: tombstoned reviews, and updated reviews
+    /// whose stored vector no longer matches their text. Kept in the id index, so a search
+    /// only reads lines appended since the last one.
+    pub fn excluded_vector_slots(&self) -> Result<SlotBitmap, AppError> {
+        if !self.file_path.exists() {
+            return Ok(SlotBitmap::default());
+        }
+        let mut indexes = id_indexes().lock().map_err(|_| AppError::Concurrency {
+            message: "ID index lock poisoned".to_string(),
+        })?;
+        let index = indexes.entry(self.file_path.clone()).or_default();
+        index.refresh(&self.file_path)?;
+        Ok(index.excluded.clone())
+    }
+    
+    /// A uniform sample of up to `count` live reviews, read in one streaming pass so
+    /// only the sample is held in memory
+    pub fn sample_live_reviews(&self, count: usize, rng: &mut SampleRng) -> Result<Vec<ReviewMetadata>, AppError> {
+        self.sample_reviews_where(count, rng, |review| !review.is_deleted())
+    }
+
+    /// Like `sample_live_reviews`, sampling only among reviews matching `predicate`
+    pub fn sample_reviews_where<P>(&self, count: usize, rng: &mut SampleRng, predicate: P) -> Result<Vec<ReviewMetadata>, AppError>
+    where
+        P: Fn(&ReviewMetadata) -> bool,
+    {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(File::open(&self.file_path)?);
+        let matching = reader
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| Ok::<ReviewMetadata, AppError>(serde_json::from_str(&line?)?))
+            .filter(|review| review.as_ref().map_or(true, &predicate));
+        sampling::reservoir_sample(matching, count, rng)
+    }
+
+    /// Count reviews that have not been tombstoned
+    pub fn count_live_reviews(&self) -> Result<usize, AppError> {
+        Ok(self.read_live_reviews()?.len())
+    }
+    
+    /// Read a page of live reviews in file order, skipping the first `offset` live reviews
+    pub fn read_reviews_page(&self, offset: usize, limit: usize) -> Result<Vec<ReviewMetadata>, AppError> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        let mut reviews = Vec::new();
+        let mut seen = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            
+            let review: ReviewMetadata = serde_json::from_str(&line)?;
+            if review.is_deleted() {
+                continue;
+            }
+            
+            if seen >= offset {
+                reviews.push(review);
+                if reviews.len() >= limit {
+                    break;
+                }
+            }
+            seen += 1;
+        }
+        
+        Ok(reviews)
+    }
+    
+    /// Atomically rewrite the file, passing every review through `transform`.
+    /// Returning `None` drops the line. The caller must hold the FileLock.
+    pub fn rewrite_reviews<F>(&self, mut transform: F) -> Result<(), AppError>
+    where
+        F: FnMut(ReviewMetadata) -> Option<ReviewMetadata>,
+    {
+        let reviews = self.read_all_reviews()?;
+        let temp_path = self.file_path.with_extension("jsonl.tmp");
+        
+        // Index the new file as it is written, so deletes and updates don't cost a re-read
+        let mut index = IdIndex::default();
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(&file);
+            for review in reviews.into_iter().filter_map(&mut transform) {
+                let json_line = serde_json::to_string(&review)?;
+                writeln!(writer, "{}", json_line)?;
+                let excluded = review.is_deleted() || review.needs_reembedding;
+                index.record(index.indexed_len, review.id, review.product_id, review.vector_index, excluded);
+                index.indexed_len += json_line.len() as u64 + 1;
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+        }
+        
+        std::fs::rename(&temp_path, &self.file_path)?;
+        index.identity = file_identity(&std::fs::metadata(&self.file_path)?);
+        if let Ok(mut indexes) = id_indexes().lock() {
+            indexes.insert(self.file_path.clone(), index);
+        }
+        Ok(())
+    }
+    
+    /// Tombstone a review in place, keeping its line so vector indices stay aligned.
+    /// Returns the tombstoned review, or `None` if it doesn't exist or is already deleted.
+    /// The caller must hold the FileLock.
+    pub fn delete_review(&self, id: &str) -> Result<Option<ReviewMetadata>, AppError> {
+        match self.get_review_by_id(id)? {
+            Some(review) if !review.is_deleted() => {}
+            _ => return Ok(None),
+        }
+        
+        let deleted_at = chrono::Utc::now();
+        let mut tombstoned = None;
+        self.rewrite_reviews(|mut review| {
+            if review.id == id {
+                review.deleted_at = Some(deleted_at);
+                tombstoned = Some(review.clone());
+            }
+            Some(review)
+        })?;
+        
+        Ok(tombstoned)
+    }
+    
+    /// Replace a live review's content in place, keeping its id, timestamp and vector slot.
+    /// The slot is flagged for re-embedding since the text changed.
+    /// Returns `None` if the review doesn't exist or is deleted. The caller must hold the FileLock.
+    pub fn update_review(&self, id: &str, data: &ReviewData) -> Result<Option<ReviewMetadata>, AppError> {
+        match self.get_review_by_id(id)? {
+            Some(review) if !review.is_deleted() => {}
+            _ => return Ok(None),
+        }
+        
+        let updated_at = chrono::Utc::now();
+        let mut updated = None;
+        self.rewrite_reviews(|mut review| {
+            if review.id == id {
+                review.title = data.title.clone();
+                review.body = data.body.clone();
+                review.product_id = data.product_id.clone();
+                review.rating = data.rating;
+                if data.reviewer_id.is_some() {
+                    review.reviewer_id = data.reviewer_id.clone();
+                }
+                // Provenance is kept unless the update gives it anew
+                if data.source.is_some() {
+                    review.source = data.source.clone();
+                }
+                if data.license.is_some() {
+                    review.license = data.license.clone();
+                }
+                if data.attribution.is_some() {
+                    review.attribution = data.attribution.clone();
+                }
+                review.sentiment = crate::sentiment::tag(&data.title, &data.body);
+                review.language = crate::language::of_text(&data.title, &data.body);
+                review.updated_at = Some(updated_at);
+                review.needs_reembedding = true;
+                updated = Some(review.clone());
+            }
+            Some(review)
+        })?;
+        
+        Ok(updated)
+    }
+    
+    /// Count a helpful or unhelpful vote on a live review.
+    /// Returns `None` if the review doesn't exist or is deleted. The caller must hold the FileLock.
+    pub fn record_vote(&self, id: &str, helpful: bool) -> Result<Option<ReviewMetadata>, AppError> {
+        match self.get_review_by_id(id)? {
+            Some(review) if !review.is_deleted() => {}
+            _ => return Ok(None),
+        }
+
+        let mut voted = None;
+        self.rewrite_reviews(|mut review| {
+            if review.id == id {
+                if helpful {
+                    review.helpful_votes = review.helpful_votes.saturating_add(1);
+                } else {
+                    review.unhelpful_votes = review.unhelpful_votes.saturating_add(1);
+                }
+                voted = Some(review.clone());
+            }
+            Some(review)
+        })?;
+
+        Ok(voted)
+    }
+
+    /// Tombstone every live review that arrived in the given bulk upload batch.
+    /// Returns the number of reviews tombstoned. The caller must hold the FileLock.
+    pub fn rollback_batch(&self, batch_id: &str) -> Result<usize, AppError> {
+        let deleted_at = chrono::Utc::now();
+        let mut rolled_back = 0;
+        self.rewrite_reviews(|mut review| {
+            if !review.is_deleted() && review.batch_id.as_deref() == Some(batch_id) {
+                review.deleted_at = Some(deleted_at);
+                rolled_back += 1;
+            }
+            Some(review)
+        })?;
+        
+        Ok(rolled_back)
+    }
+    
+    /// Tombstone every live review matching `predicate` in a single rewrite.
+    /// Returns the tombstoned reviews. The caller must hold the FileLock.
+    pub fn delete_reviews_where<P>(&self, predicate: P) -> Result<Vec<ReviewMetadata>, AppError>
+    where
+        P: Fn(&ReviewMetadata) -> bool,
+    {
+        let deleted_at = chrono::Utc::now();
+        let mut deleted = Vec::new();
+        self.rewrite_reviews(|mut review| {
+            if !review.is_deleted() && predicate(&review) {
+                review.deleted_at = Some(deleted_at);
+                deleted.push(review.clone());
+            }
+            Some(review)
+        })?;
+
+        Ok(deleted)
+    }
+
+    /// Validate the integrity of the JSONL file
+    pub fn validate_file(&self) -> Result<ValidationResult, AppError> {
+        if !self.file_path.exists() {
+            return Ok(ValidationResult {
+                is_valid: true,
+                total_lines: 0,
+                valid_lines: 0,
+                errors: Vec::new(),
+            });
+        }
+        
+        let file = File::open(&self.file_path)?;
+        let reader = BufReader::new(file);
+        
+        let mut total_lines = 0;
+        let mut valid_lines = 0;
+        let mut errors = Vec::new();
+        
+        for (line_number, line) in reader.lines().enumerate() {
+            total_lines += 1;
+            let line = line?;
+            
+            if line.trim().is_empty() {
+                continue;
+            }
+            
+            match serde_json::from_str::<ReviewMetadata>(&line) {
+                Ok(_) => valid_lines += 1,
+                Err(e) => errors.push(ValidationError::InvalidValue {
+                    field: format!("line_{}", line_number + 1),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        
+        Ok(ValidationResult {
+            is_valid: errors.is_empty(),
+            total_lines,
+            valid_lines,
+            errors,
+        })
+    }
+}
+
+/// Result of file validation
+#[derive(Debug)]
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub total_lines: usize,
+    pub valid_lines: usize,
+    pub errors: Vec<ValidationError>,
+}
+
+/// One bit per vector slot (JSONL line). Cheap to consult for every candidate an
+/// index returns, so deleted and stale vectors can be dropped from results
+/// without rebuilding the index on each delete.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SlotBitmap {
+    words: Vec<u64>,
+}
+
+impl SlotBitmap {
+    pub fn with_slots(slots: usize) -> Self {
+        Self {
+            words: vec![0; slots.div_ceil(64)],
+        }
+    }
+
+    pub fn set(&mut self, slot: usize) {
+        let word = slot / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (slot % 64);
+    }
+
+    pub fn contains(&self, slot: usize) -> bool {
+        self.words
+            .get(slot / 64)
+            .is_some_and(|word| word & (1 << (slot % 64)) != 0)
+    }
+
+    /// Number of slots set
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use chrono::Utc;
+
+    fn create_test_review(id: &str, vector_index: usize) -> ReviewMetadata {
+        ReviewMetadata {
+            id: id.to_string(),
+            title: "Test Review".to_string(),
+            body: "This is a test review body.".to_string(),
+            product_id: "test_product".to_string(),
+            rating: 5,
+            timestamp: Utc::now(),
+            vector_index,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+            deleted_at: None,
+            batch_id: None,
+            updated_at: None,
+            needs_reembedding: false,
+            helpful_votes: 0,
+            unhelpful_votes: 0,
+            sentiment: None,
+            language: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_jsonl_storage_operations() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        // Test append single review
+        let review1 = create_test_review("rev_001", 0);
+        storage.append_review(&review1).unwrap();
+
+        // Test append multiple reviews
+        let reviews = vec![
+            create_test_review("rev_002", 1),
+            create_test_review("rev_003", 2),
+        ];
+        storage.append_reviews(&reviews).unwrap();
+
+        // Test count
+        assert_eq!(storage.count_reviews().unwrap(), 3);
+
+        // Test get by index
+        let retrieved = storage.get_review_by_index(0).unwrap().unwrap();
+        assert_eq!(retrieved.id, "rev_001");
+
+        // Test get multiple by indices
+        let retrieved_multiple = storage.get_reviews_by_indices(&[0, 2]).unwrap();
+        assert_eq!(retrieved_multiple.len(), 2);
+        assert_eq!(retrieved_multiple[0].as_ref().unwrap().id, "rev_001");
+        assert_eq!(retrieved_multiple[1].as_ref().unwrap().id, "rev_003");
+
+        // Test validation
+        let validation = storage.validate_file().unwrap();
+        assert!(validation.is_valid);
+        assert_eq!(validation.valid_lines, 3);
+    }
+
+    #[test]
+    fn test_read_reviews_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        // Missing file yields an empty page
+        assert!(storage.read_reviews_page(0, 10).unwrap().is_empty());
+
+        let reviews: Vec<ReviewMetadata> = (0..5)
+            .map(|i| create_test_review(&format!("rev_{:03}", i), i))
+            .collect();
+        storage.append_reviews(&reviews).unwrap();
+
+        let page = storage.read_reviews_page(2, 2).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].id, "rev_002");
+        assert_eq!(page[1].id, "rev_003");
+
+        // Last partial page and past-the-end page
+        assert_eq!(storage.read_reviews_page(4, 2).unwrap().len(), 1);
+        assert!(storage.read_reviews_page(10, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_review_by_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        // Missing file
+        assert!(storage.get_review_by_id("rev_001").unwrap().is_none());
+
+        storage.append_reviews(&[
+            create_test_review("rev_001", 0),
+            create_test_review("rev_002", 1),
+        ]).unwrap();
+
+        assert_eq!(storage.get_review_by_id("rev_002").unwrap().unwrap().vector_index, 1);
+        assert!(storage.get_review_by_id("missing").unwrap().is_none());
+
+        // Appends after the index was built are picked up incrementally
+        storage.append_review(&create_test_review("rev_003", 2)).unwrap();
+        assert_eq!(storage.get_review_by_id("rev_003").unwrap().unwrap().vector_index, 2);
+
+        // A rewritten file with shifted offsets is detected and reindexed
+        std::fs::remove_file(&jsonl_path).unwrap();
+        storage.append_reviews(&[
+            create_test_review("rev_new_long_identifier", 0),
+            create_test_review("rev_002", 1),
+        ]).unwrap();
+        assert_eq!(storage.get_review_by_id("rev_002").unwrap().unwrap().vector_index, 1);
+        assert!(storage.get_review_by_id("rev_001").unwrap().is_none());
+
+        // A rewritten file that grew is reindexed too
+        std::fs::remove_file(&jsonl_path).unwrap();
+        let grown: Vec<ReviewMetadata> = (0..5)
+            .map(|i| create_test_review(&format!("rev_grown_{}", i), i))
+            .collect();
+        storage.append_reviews(&grown).unwrap();
+        assert_eq!(storage.get_review_by_id("rev_grown_4").unwrap().unwrap().vector_index, 4);
+        assert!(storage.get_review_by_id("rev_002").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_review_by_id_sees_a_rewrite_from_another_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+        storage.append_reviews(&[
+            create_test_review("rev_001", 0),
+            create_test_review("rev_002", 1),
+            create_test_review("rev_003", 2),
+        ]).unwrap();
+        assert_eq!(storage.get_review_by_id("rev_003").unwrap().unwrap().vector_index, 2);
+
+        // Another process rewrites the file to the same length and renames it into place,
+        // without this process's index hearing of it
+        let rewritten_path = temp_dir.path().join("reviews.jsonl.other");
+        JsonlStorage::new(&rewritten_path).append_reviews(&[
+            create_test_review("rev_003", 0),
+            create_test_review("rev_002", 1),
+            create_test_review("rev_009", 2),
+        ]).unwrap();
+        assert_eq!(std::fs::metadata(&rewritten_path).unwrap().len(), std::fs::metadata(&jsonl_path).unwrap().len());
+        std::fs::rename(&rewritten_path, &jsonl_path).unwrap();
+
+        assert_eq!(storage.get_review_by_id("rev_009").unwrap().unwrap().vector_index, 2);
+        assert_eq!(storage.get_review_by_id("rev_003").unwrap().unwrap().vector_index, 0);
+        assert!(storage.get_review_by_id("rev_001").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_reviews_by_product() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        let mut other_product = create_test_review("rev_002", 1);
+        other_product.product_id = "other_product".to_string();
+        storage.append_reviews(&[create_test_review("rev_001", 0), other_product]).unwrap();
+
+        let ids = |reviews: Vec<ReviewMetadata>| reviews.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(storage.get_reviews_by_product("test_product").unwrap()), vec!["rev_001"]);
+        assert!(storage.get_reviews_by_product("missing").unwrap().is_empty());
+
+        // Appends are picked up incrementally
+        storage.append_review(&create_test_review("rev_003", 2)).unwrap();
+        assert_eq!(ids(storage.get_reviews_by_product("test_product").unwrap()), vec!["rev_001", "rev_003"]);
+
+        // Rewrites (tombstones, product changes) are reflected
+        storage.delete_review("rev_001").unwrap();
+        let data = ReviewData {
+            title: "Moved".to_string(),
+            body: "This review now belongs to the other product.".to_string(),
+            product_id: "other_product".to_string(),
+            rating: 3,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        storage.update_review("rev_003", &data).unwrap();
+        assert!(storage.get_reviews_by_product("test_product").unwrap().is_empty());
+        assert_eq!(ids(storage.get_reviews_by_product("other_product").unwrap()), vec!["rev_002", "rev_003"]);
+    }
+
+    #[test]
+    fn test_delete_review_tombstones_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        storage.append_reviews(&[
+            create_test_review("rev_001", 0),
+            create_test_review("rev_002", 1),
+            create_test_review("rev_003", 2),
+        ]).unwrap();
+
+        let deleted = storage.delete_review("rev_002").unwrap().unwrap();
+        assert!(deleted.is_deleted());
+        assert_eq!(deleted.vector_index, 1);
+
+        // Deleting twice or deleting an unknown id is a no-op
+        assert!(storage.delete_review("rev_002").unwrap().is_none());
+        assert!(storage.delete_review("missing").unwrap().is_none());
+
+        // The line is kept, so line count and vector correlation are unchanged
+        assert_eq!(storage.count_reviews().unwrap(), 3);
+        assert_eq!(storage.get_review_by_index(2).unwrap().unwrap().vector_index, 2);
+        assert!(storage.get_review_by_index(1).unwrap().unwrap().is_deleted());
+
+        // Live views skip the tombstone
+        assert_eq!(storage.count_live_reviews().unwrap(), 2);
+        let page = storage.read_reviews_page(1, 10).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, "rev_003");
+
+        // The excluded slots follow the delete and later appends without a rescan
+        let excluded = storage.excluded_vector_slots().unwrap();
+        assert!(excluded.contains(1));
+        assert_eq!(excluded.count(), 1);
+        let mut tombstoned = create_test_review("rev_004", 3);
+        tombstoned.deleted_at = Some(Utc::now());
+        storage.append_reviews(&[tombstoned, create_test_review("rev_005", 4)]).unwrap();
+        let excluded = storage.excluded_vector_slots().unwrap();
+        assert!(excluded.contains(1) && excluded.contains(3));
+        assert_eq!(excluded.count(), 2);
+    }
+
+    #[test]
+    fn test_update_review_in_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        storage.append_reviews(&[create_test_review("rev_001", 0), create_test_review("rev_002", 1)]).unwrap();
+
+        let data = ReviewData {
+            title: "Fixed title".to_string(),
+            body: "The body without the typo.".to_string(),
+            product_id: "test_product".to_string(),
+            rating: 4,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        };
+        let updated = storage.update_review("rev_001", &data).unwrap().unwrap();
+        assert_eq!(updated.vector_index, 0);
+        assert!(updated.updated_at.is_some());
+        assert!(updated.needs_reembedding);
+
+        let stored = storage.get_review_by_index(0).unwrap().unwrap();
+        assert_eq!(stored.title, "Fixed title");
+        assert_eq!(stored.rating, 4);
+        assert!(!storage.get_review_by_index(1).unwrap().unwrap().needs_reembedding);
+
+        // The updated review's vector is stale until it is re-embedded
+        let excluded = storage.excluded_vector_slots().unwrap();
+        assert!(excluded.contains(0));
+        assert!(!excluded.contains(1));
+        assert_eq!(excluded.count(), 1);
+
+        // Deleted or unknown reviews can't be updated
+        storage.delete_review("rev_002").unwrap();
+        assert!(storage.update_review("rev_002", &data).unwrap().is_none());
+        assert!(storage.update_review("missing", &data).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rollback_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let jsonl_path = temp_dir.path().join("test_reviews.jsonl");
+        let storage = JsonlStorage::new(&jsonl_path);
+
+        let mut batched = create_test_review("rev_002", 1);
+        batched.batch_id = Some("batch_a".to_string());
+        let mut other_batch = create_test_review("rev_003", 2);
+        other_batch.batch_id = Some("batch_b".to_string());
+
+        storage.append_reviews(&[create_test_review("rev_001", 0), batched, other_batch]).unwrap();
+
+        assert_eq!(storage.rollback_batch("batch_a").unwrap(), 1);
+        assert_eq!(storage.rollback_batch("batch_a").unwrap(), 0);
+
+        let live: Vec<String> = storage.read_live_reviews().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(live, vec!["rev_001", "rev_003"]);
+        assert_eq!(storage.count_reviews().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_data_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = DataPaths::new(temp_dir.path());
+
+        // Test directory creation
+        paths.ensure_directories().unwrap();
+        assert!(paths.data_dir.exists());
+
+        // Test file existence check
+        let (jsonl_exists, index_exists) = paths.files_exist();
+        assert!(!jsonl_exists);
+        assert!(!index_exists);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,352 @@
+//! Search suggestions: completions for what a user has typed so far.
+//!
+//! Completions come from review titles and from past searches. Titles are indexed as
+//! word n-grams (up to `MAX_NGRAM_WORDS` words, starting at every word) in a sorted map,
+//! so the completions of a prefix are one range scan. The index follows reviews.jsonl
+//! the way the id index does: each request reads only the lines appended since the
+//! last, and a rewritten file (a delete, update or compaction) is indexed afresh.
+//!
+//! Searches are counted by their analyzed text as they run, first pages with results
+//! only. A query searched `MIN_QUERY_SEARCHES` times is popular enough to be offered, so
+//! a one-off search never shows up in someone else's suggestions. The counts live in
+//! this process's memory: they are lost on restart and not shared between servers.
+//!
+//! Titles of deleted and search-blocklisted reviews are never suggested.
+
+use crate::blocklist::{BlockScope, Blocklist};
+use crate::models::*;
+use crate::storage::{DataPaths, FileLock};
+use crate::tokenizer;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use utoipa::{IntoParams, ToSchema};
+
+/// Longest prefix accepted, in bytes
+pub const MAX_PREFIX_LENGTH: usize = 100;
+
+/// Most suggestions one request may ask for
+pub const MAX_SUGGESTIONS: usize = 20;
+
+/// Longest title phrase indexed, in words
+const MAX_NGRAM_WORDS: usize = 3;
+
+/// Searches before a query is offered as a suggestion
+pub const MIN_QUERY_SEARCHES: u32 = 2;
+
+/// Distinct queries counted per data directory; the least searched are forgotten first
+const MAX_QUERIES: usize = 10_000;
+
+/// Query for `GET /search/suggest`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestQuery {
+    /// What the user has typed so far; a trailing space asks for the next word
+    #[serde(default)]
+    pub prefix: String,
+    pub limit: Option<usize>, // Default: 8
+}
+
+impl SuggestQuery {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.prefix.trim().is_empty() {
+            return Err(ValidationError::MissingField {
+                field: "prefix".to_string(),
+            });
+        }
+        if self.prefix.len() > MAX_PREFIX_LENGTH {
+            return Err(ValidationError::TooLong {
+                field: "prefix".to_string(),
+                max_length: MAX_PREFIX_LENGTH,
+            });
+        }
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_SUGGESTIONS {
+                return Err(ValidationError::InvalidValue {
+                    field: "limit".to_string(),
+                    reason: format!("must be between 1 and {}", MAX_SUGGESTIONS),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(8)
+    }
+}
+
+/// One completion, in analyzed (lowercased, normalized) form
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Suggestion {
+    pub text: String,
+    /// Live reviews with the phrase in their title
+    pub reviews: usize,
+    /// Times the phrase was searched, once popular
+    pub searches: u32,
+}
+
+/// Response of `GET /search/suggest`
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SuggestResponse {
+    pub success: bool,
+    pub prefix: String,
+    /// Most reviews plus searches first
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Title n-grams of one review, kept to take them out again
+struct IndexedTitle {
+    product_id: String,
+    reviewer_id: Option<String>,
+    ngrams: Vec<String>,
+}
+
+/// Title n-gram index of one reviews.jsonl. Extended with appended lines, rebuilt when
+/// the file shrinks, is rewritten in place or its new tail doesn't parse.
+#[derive(Default)]
+struct TitleIndex {
+    indexed_len: u64,
+    indexed_modified: Option<SystemTime>,
+    titles: HashMap<String, IndexedTitle>,
+    ngrams: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TitleIndex {
+    fn refresh(&mut self, file_path: &Path) -> Result<(), AppError> {
+        if self.index_tail(file_path).is_err() {
+            *self = TitleIndex::default();
+            self.index_tail(file_path)?;
+        }
+        Ok(())
+    }
+
+    fn index_tail(&mut self, file_path: &Path) -> Result<(), AppError> {
+        #[derive(Deserialize)]
+        struct IndexedFields {
+            id: String,
+            title: String,
+            product_id: String,
+            #[serde(default)]
+            reviewer_id: Option<String>,
+            #[serde(default)]
+            deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+        }
+
+        let metadata = match std::fs::metadata(file_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                *self = TitleIndex::default();
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let modified = metadata.modified().ok();
+        if metadata.len() < self.indexed_len || (metadata.len() == self.indexed_len && modified != self.indexed_modified) {
+            *self = TitleIndex::default();
+        }
+        if metadata.len() == self.indexed_len {
+            return Ok(());
+        }
+
+        let mut file = File::open(file_path)?;
+        file.seek(SeekFrom::Start(self.indexed_len))?;
+        let mut reader = BufReader::new(file);
+
+        let mut offset = self.indexed_len;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes_read = reader.read_line(&mut line)?;
+            // Stop at EOF or at a partially written trailing line
+            if bytes_read == 0 || !line.ends_with('\n') {
+                break;
+            }
+
+            if !line.trim().is_empty() {
+                let record: IndexedFields = serde_json::from_str(line.trim_end())?;
+                self.remove(&record.id);
+                if record.deleted_at.is_none() {
+                    self.insert(record.id, &record.title, record.product_id, record.reviewer_id);
+                }
+            }
+            offset += bytes_read as u64;
+        }
+
+        self.indexed_len = offset;
+        self.indexed_modified = modified;
+        Ok(())
+    }
+
+    fn insert(&mut self, id: String, title: &str, product_id: String, reviewer_id: Option<String>) {
+        let ngrams = title_ngrams(title);
+        for ngram in &ngrams {
+            self.ngrams.entry(ngram.clone()).or_default().insert(id.clone());
+        }
+        self.titles.insert(id, IndexedTitle { product_id, reviewer_id, ngrams });
+    }
+
+    fn remove(&mut self, id: &str) {
+        let Some(indexed) = self.titles.remove(id) else {
+            return;
+        };
+        for ngram in indexed.ngrams {
+            if let Some(ids) = self.ngrams.get_mut(&ngram) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.ngrams.remove(&ngram);
+                }
+            }
+        }
+    }
+
+    /// Phrases starting with `prefix` and the number of unblocked reviews with each
+    fn completions(&self, prefix: &str, blocklist: &Blocklist) -> Vec<(String, usize)> {
+        self.ngrams
+            .range(prefix.to_string()..)
+            .take_while(|(ngram, _)| ngram.starts_with(prefix))
+            .filter_map(|(ngram, ids)| {
+                let reviews = ids
+                    .iter()
+                    .filter_map(|id| self.titles.get(id))
+                    .filter(|title| !blocklist.blocks_fields(&title.product_id, title.reviewer_id.as_ref()))
+                    .count();
+                (reviews > 0).then(|| (ngram.clone(), reviews))
+            })
+            .collect()
+    }
+}
+
+/// Distinct phrases of one to `MAX_NGRAM_WORDS` consecutive title words
+fn title_ngrams(title: &str) -> Vec<String> {
+    let words = tokenizer::analyze(title);
+    let mut ngrams: Vec<String> = (0..words.len())
+        .flat_map(|start| {
+            let words = &words;
+            (start + 1..=(start + MAX_NGRAM_WORDS).min(words.len())).map(move |end| words[start..end].join(" "))
+        })
+        .collect();
+    ngrams.sort();
+    ngrams.dedup();
+    ngrams
+}
+
+/// Analyzed form of typed text, as the index holds it. A trailing space is kept, so
+/// "great " completes to phrases with a word after "great", not to "greater".
+fn analyze_prefix(prefix: &str) -> String {
+    let analyzed = tokenizer::analyze(prefix).join(" ");
+    if prefix.ends_with(char::is_whitespace) && !analyzed.is_empty() {
+        analyzed + " "
+    } else {
+        analyzed
+    }
+}
+
+/// Process-wide title indexes keyed by JSONL file path
+fn title_indexes() -> &'static Mutex<HashMap<PathBuf, TitleIndex>> {
+    static TITLE_INDEXES: OnceLock<Mutex<HashMap<PathBuf, TitleIndex>>> = OnceLock::new();
+    TITLE_INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Process-wide search counts keyed by data directory
+fn query_counts() -> &'static Mutex<HashMap<PathBuf, HashMap<String, u32>>> {
+    static QUERY_COUNTS: OnceLock<Mutex<HashMap<PathBuf, HashMap<String, u32>>>> = OnceLock::new();
+    QUERY_COUNTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Count a search that found results, so popular queries can be suggested
+pub fn record_query(data_paths: &DataPaths, query: &str) {
+    let query = tokenizer::analyze(query).join(" ");
+    if query.is_empty() || query.len() > MAX_PREFIX_LENGTH {
+        return;
+    }
+    let Ok(mut counts) = query_counts().lock() else {
+        return;
+    };
+    let counts = counts.entry(data_paths.data_dir.clone()).or_default();
+    if !counts.contains_key(&query) && counts.len() >= MAX_QUERIES {
+        if let Some(least) = counts.iter().min_by_key(|(_, searches)| **searches).map(|(query, _)| query.clone()) {
+            counts.remove(&least);
+        }
+    }
+    *counts.entry(query).or_default() += 1;
+}
+
+/// Completions of `query.prefix`: title phrases and popular past queries
+pub fn suggest(data_paths: &DataPaths, query: &SuggestQuery) -> Result<SuggestResponse, AppError> {
+    query.validate().map_err(AppError::Validation)?;
+    data_paths.ensure_directories()?;
+
+    let prefix = analyze_prefix(&query.prefix);
+    // Typed text made only of punctuation has nothing to complete
+    if prefix.is_empty() {
+        return Ok(SuggestResponse {
+            success: true,
+            prefix: query.prefix.clone(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    let mut suggestions: BTreeMap<String, Suggestion> = BTreeMap::new();
+    {
+        let _lock = FileLock::acquire_shared(&data_paths.lock_file, "search_suggest")?;
+        let blocklist = Blocklist::load(data_paths, BlockScope::Search)?;
+        let mut indexes = title_indexes().lock().map_err(|_| AppError::Concurrency {
+            message: "Title index lock poisoned".to_string(),
+        })?;
+        let index = indexes.entry(data_paths.reviews_jsonl.clone()).or_default();
+        index.refresh(&data_paths.reviews_jsonl)?;
+        for (text, reviews) in index.completions(&prefix, &blocklist) {
+            suggestions.insert(text.clone(), Suggestion { text, reviews, searches: 0 });
+        }
+    }
+
+    if let Ok(counts) = query_counts().lock() {
+        let popular = counts
+            .get(&data_paths.data_dir)
+            .into_iter()
+            .flatten()
+            .filter(|(text, searches)| **searches >= MIN_QUERY_SEARCHES && text.starts_with(&prefix));
+        for (text, &searches) in popular {
+            suggestions
+                .entry(text.clone())
+                .or_insert_with(|| Suggestion { text: text.clone(), reviews: 0, searches: 0 })
+                .searches = searches;
+        }
+    }
+
+    // The prefix itself is what the user already has, so it's no completion
+    let mut suggestions: Vec<Suggestion> = suggestions.into_values().filter(|suggestion| suggestion.text != prefix.trim_end()).collect();
+    suggestions.sort_by(|a, b| {
+        (b.reviews + b.searches as usize)
+            .cmp(&(a.reviews + a.searches as usize))
+            .then_with(|| a.text.len().cmp(&b.text.len()))
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    suggestions.truncate(query.get_limit());
+
+    Ok(SuggestResponse {
+        success: true,
+        prefix: query.prefix.clone(),
+        suggestions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_ngrams_and_prefixes() {
+        assert_eq!(
+            title_ngrams("Great battery life"),
+            ["battery", "battery life", "great", "great battery", "great battery life", "life"]
+        );
+        assert_eq!(analyze_prefix("Great  Bat"), "great bat");
+        assert_eq!(analyze_prefix("great "), "great ");
+    }
+}
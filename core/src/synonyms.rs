@@ -0,0 +1,196 @@
+//! Operator-managed synonyms: groups of equivalent terms, so a keyword search for
+//! "phone" also matches reviews that only say "smartphone" or "mobile".
+//!
+//! Groups are read from synonyms.txt in the data directory, one group per line with
+//! terms separated by `=` or commas (`phone = smartphone = mobile`), blank lines and
+//! `#` comments ignored. Without that file synonyms.json is read instead, an array of
+//! groups (`[["phone", "smartphone", "mobile"]]`). Terms are normalized like review
+//! text and may be phrases. A term in several groups is equivalent to all of them.
+//!
+//! The file is loaded at startup and again on `POST /admin/synonyms/reload`; edits
+//! take effect only then. A file that fails to parse is refused, keeping the groups
+//! already loaded.
+
+use crate::models::{AppError, ValidationError};
+use crate::storage::DataPaths;
+use crate::tokenizer;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use utoipa::ToSchema;
+
+/// Longest synonym file read, to bound what a reload can pull into memory
+pub const MAX_SYNONYM_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Equivalent terms, by term
+#[derive(Clone, Debug, Default)]
+pub struct Synonyms {
+    groups: Vec<Vec<String>>,
+    alternatives: HashMap<String, Vec<String>>,
+    source: Option<PathBuf>,
+}
+
+/// What a (re)load found
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct SynonymsSummary {
+    /// File the groups came from; absent when there is no synonym file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub groups: usize,
+    pub terms: usize,
+}
+
+impl Synonyms {
+    fn from_groups(groups: Vec<Vec<String>>, source: Option<PathBuf>) -> Self {
+        let mut alternatives: HashMap<String, Vec<String>> = HashMap::new();
+        for group in &groups {
+            for term in group {
+                let known = alternatives.entry(term.clone()).or_default();
+                for other in group.iter().filter(|other| *other != term) {
+                    if !known.contains(other) {
+                        known.push(other.clone());
+                    }
+                }
+            }
+        }
+        Synonyms { groups, alternatives, source }
+    }
+
+    /// Parse the text format: one group per line
+    pub fn parse_text(text: &str) -> Result<Self, ValidationError> {
+        let groups = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(index, line)| group(line.split(['=', ',']), format!("line {}", index + 1)))
+            .collect::<Result<_, _>>()?;
+        Ok(Synonyms::from_groups(groups, None))
+    }
+
+    /// Parse the JSON format: an array of groups
+    pub fn parse_json(text: &str) -> Result<Self, ValidationError> {
+        let raw: Vec<Vec<String>> = serde_json::from_str(text).map_err(|e| ValidationError::InvalidValue {
+            field: "synonyms".to_string(),
+            reason: format!("not an array of term arrays: {}", e),
+        })?;
+        let groups = raw
+            .iter()
+            .enumerate()
+            .map(|(index, terms)| group(terms.iter().map(String::as_str), format!("group {}", index + 1)))
+            .collect::<Result<_, _>>()?;
+        Ok(Synonyms::from_groups(groups, None))
+    }
+
+    /// Read the data directory's synonym file; empty when there is none
+    pub fn load(data_paths: &DataPaths) -> Result<Self, AppError> {
+        let (path, json) = match (data_paths.synonyms_txt.exists(), data_paths.synonyms_json.exists()) {
+            (true, _) => (&data_paths.synonyms_txt, false),
+            (false, true) => (&data_paths.synonyms_json, true),
+            (false, false) => return Ok(Synonyms::default()),
+        };
+        if std::fs::metadata(path)?.len() > MAX_SYNONYM_FILE_BYTES {
+            return Err(AppError::Validation(ValidationError::TooLong {
+                field: "synonyms".to_string(),
+                max_length: MAX_SYNONYM_FILE_BYTES as usize,
+            }));
+        }
+
+        let text = std::fs::read_to_string(path)?;
+        let parsed = match json {
+            true => Synonyms::parse_json(&text),
+            false => Synonyms::parse_text(&text),
+        };
+        let mut synonyms = parsed.map_err(AppError::Validation)?;
+        synonyms.source = Some(path.clone());
+        Ok(synonyms)
+    }
+
+    /// Other terms equivalent to a normalized query word
+    pub fn alternatives(&self, word: &str) -> &[String] {
+        self.alternatives.get(word).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn summary(&self) -> SynonymsSummary {
+        SynonymsSummary {
+            source: self.source.as_ref().map(|path| path.display().to_string()),
+            groups: self.groups.len(),
+            terms: self.alternatives.len(),
+        }
+    }
+}
+
+/// Normalize one group's terms; `location` names it in errors
+fn group<'a>(terms: impl Iterator<Item = &'a str>, location: String) -> Result<Vec<String>, ValidationError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for term in terms {
+        let term = tokenizer::normalize(term).split_whitespace().collect::<Vec<_>>().join(" ");
+        if !term.is_empty() && !normalized.contains(&term) {
+            normalized.push(term);
+        }
+    }
+    match normalized.len() {
+        0 | 1 => Err(ValidationError::InvalidValue {
+            field: "synonyms".to_string(),
+            reason: format!("{} needs at least two different terms", location),
+        }),
+        _ => Ok(normalized),
+    }
+}
+
+fn loaded() -> &'static Mutex<HashMap<PathBuf, Arc<Synonyms>>> {
+    static LOADED: OnceLock<Mutex<HashMap<PathBuf, Arc<Synonyms>>>> = OnceLock::new();
+    LOADED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// (Re)load the data directory's synonym file, replacing the groups in use. On error
+/// the groups already in use stay.
+pub fn reload(data_paths: &DataPaths) -> Result<SynonymsSummary, AppError> {
+    let synonyms = Arc::new(Synonyms::load(data_paths)?);
+    let summary = synonyms.summary();
+    loaded()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(data_paths.data_dir.clone(), synonyms);
+    Ok(summary)
+}
+
+/// The synonyms in use for a data directory, loading its file on first use
+pub fn current(data_paths: &DataPaths) -> Arc<Synonyms> {
+    let mut loaded = loaded().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    loaded
+        .entry(data_paths.data_dir.clone())
+        .or_insert_with(|| {
+            Arc::new(Synonyms::load(data_paths).unwrap_or_else(|e| {
+                tracing::error!("Ignoring synonym file in {}: {}", data_paths.data_dir.display(), e);
+                Synonyms::default()
+            }))
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synonym_groups_parse_and_merge() {
+        let synonyms = Synonyms::parse_text(
+            "# Devices\nphone = Smartphone = mobile\n\nheadphones, earphones\nmobile = cell phone\n",
+        )
+        .unwrap();
+        assert_eq!(synonyms.alternatives("phone"), ["smartphone", "mobile"]);
+        // A term in two groups is equivalent to both
+        assert_eq!(synonyms.alternatives("mobile"), ["phone", "smartphone", "cell phone"]);
+        assert_eq!(synonyms.alternatives("earphones"), ["headphones"]);
+        assert!(synonyms.alternatives("battery").is_empty());
+        assert_eq!(synonyms.summary().groups, 3);
+
+        let json = Synonyms::parse_json(r#"[["tv", "television"]]"#).unwrap();
+        assert_eq!(json.alternatives("television"), ["tv"]);
+
+        let error = Synonyms::parse_text("phone = smartphone\nlaptop =\n").unwrap_err();
+        assert!(error.to_string().contains("line 2"), "{}", error);
+        assert!(Synonyms::parse_json(r#"{"phone": "mobile"}"#).is_err());
+    }
+}
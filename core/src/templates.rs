@@ -0,0 +1,115 @@
+use crate::models::*;
+
+/// Validation notes included at the top of every template (as `#` comment lines)
+const VALIDATION_NOTES: [&str; 10] = [
+    "Bulk upload template - lines starting with # are ignored",
+    "title: required, 3-200 characters",
+    "body: required, 10-2000 characters",
+    "product_id: required, max 100 characters",
+    "rating: required, whole number from 1 to 5",
+    "reviewer_id: optional, max 100 characters",
+    "source: optional, max 200 characters",
+    "license: optional identifier such as CC-BY-4.0; CC-BY, ODC-BY and ODbL need an attribution",
+    "attribution: optional, max 500 characters",
+    "Replace the example rows below with your own reviews",
+];
+
+/// Downloadable bulk upload template formats
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TemplateFormat {
+    Csv,
+    Jsonl,
+}
+
+impl TemplateFormat {
+    /// Parse a format name from the request path
+    pub fn parse(format: &str) -> Result<Self, ValidationError> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(TemplateFormat::Csv),
+            "jsonl" => Ok(TemplateFormat::Jsonl),
+            _ => Err(ValidationError::InvalidValue {
+                field: "format".to_string(),
+                reason: "must be one of: csv, jsonl".to_string(),
+            }),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TemplateFormat::Csv => "text/csv; charset=utf-8",
+            TemplateFormat::Jsonl => "application/x-ndjson; charset=utf-8",
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            TemplateFormat::Csv => "reviews_template.csv",
+            TemplateFormat::Jsonl => "reviews_template.jsonl",
+        }
+    }
+}
+
+/// Example rows that pass validation
+fn example_reviews() -> Vec<ReviewData> {
+    vec![
+        ReviewData {
+            title: "Great product!".to_string(),
+            body: "This product exceeded my expectations. Great quality, and fast delivery.".to_string(),
+            product_id: "prod_123".to_string(),
+            rating: 5,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        },
+        ReviewData {
+            title: "Average experience".to_string(),
+            body: "The product is okay but nothing special. Could be improved.".to_string(),
+            product_id: "prod_124".to_string(),
+            rating: 3,
+            reviewer_id: None,
+            source: None,
+            license: None,
+            attribution: None,
+        },
+    ]
+}
+
+/// Render a ready-to-fill template with notes, headers and example rows
+pub fn render(format: TemplateFormat) -> Result<String, AppError> {
+    let mut content: String = VALIDATION_NOTES
+        .iter()
+        .map(|note| format!("# {}\n", note))
+        .collect();
+
+    match format {
+        TemplateFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record(["title", "body", "product_id", "rating"])
+                .map_err(|e| AppError::Internal { message: e.to_string() })?;
+            for review in example_reviews() {
+                writer
+                    .write_record([
+                        review.title,
+                        review.body,
+                        review.product_id,
+                        review.rating.to_string(),
+                    ])
+                    .map_err(|e| AppError::Internal { message: e.to_string() })?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| AppError::Internal { message: e.to_string() })?;
+            content.push_str(&String::from_utf8_lossy(&bytes));
+        }
+        TemplateFormat::Jsonl => {
+            for review in example_reviews() {
+                content.push_str(&serde_json::to_string(&review)?);
+                content.push('\n');
+            }
+        }
+    }
+
+    Ok(content)
+}
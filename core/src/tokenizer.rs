@@ -0,0 +1,560 @@
+//! Tokenizers shared by keyword search, duplicate detection and the embedder.
+//!
+//! Splitting on non-alphanumeric characters works for languages that separate words
+//! with spaces, but a Chinese or Japanese sentence comes out as a single "word", so a
+//! query only matched if it repeated the review's sentence exactly. Text containing
+//! Han or kana characters therefore goes to `CjkTokenizer`, which cuts those runs into
+//! overlapping character bigrams (the approach of Lucene's CJK analyzer). Bigrams need
+//! no dictionary and cover every two-character word; a dictionary segmenter such as
+//! jieba or lindera can replace it behind the `Tokenizer` trait.
+//!
+//! Text without CJK characters tokenizes exactly as before, so stored vectors for it
+//! stay valid.
+//!
+//! Keyword matching runs text through `normalize` first (`analyze` does both steps),
+//! for documents and queries alike: Unicode compatibility normalization, so composed
+//! and decomposed "é" or full-width letters compare equal, then diacritic folding, so
+//! "café" matches "cafe". Folding loses meaning in languages where marks distinguish
+//! words (Vietnamese tones, for one), so `FOLD_DIACRITICS=false` turns it off and keeps
+//! only the normalization. The embedder tokenizes unnormalized text, leaving stored
+//! vectors unchanged.
+//!
+//! Lowercasing follows Unicode's full case mappings, context rules such as the Greek
+//! final sigma included; Thai has no case and passes through. Before the compatibility
+//! normalization, `normalize_thai` drops invisible characters (zero-width spaces are
+//! common word-break hints in Thai text) and rewrites Thai spellings that display the
+//! same as the standard one but differ in code points: two sara e typed for sara ae, a
+//! tone mark typed between the parts of sara am, a mark typed twice. A Thai query then
+//! matches a review however either was typed.
+//!
+//! Normalization ends with `normalize_quantities`, which gives every quantity one
+//! spelling: "10h", "10 hrs", "10-hour" and "ten hours" all become "10 hour", so a
+//! query matches however a review phrases a number. Unit words are only rewritten
+//! right after a number, leaving "in", "m" or "h" alone elsewhere.
+//!
+//! The keyword scorer goes one step further with `keyword_terms`, for review text and
+//! queries alike: stopwords are dropped, so "the" and "is" no longer count as matches,
+//! and the remaining words are stemmed with a Snowball stemmer, so "batteries" matches
+//! "battery". `STOPWORDS` lists where stopwords come from, comma-separated: `english`,
+//! the built-in list (the default), or files of one word per line; `none` for none.
+//! `STEMMER` names the Snowball language (`english` by default), or `none`. Words in
+//! scripts written without spaces are never stemmed. Other analysis (suggestions,
+//! spelling, duplicates) keeps whole words.
+
+use rust_stemmers::{Algorithm, Stemmer};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+
+/// Splits text into lowercase search terms
+pub trait Tokenizer: Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<String>;
+}
+
+/// Words separated by anything that is not a letter or digit
+pub struct WordTokenizer;
+
+impl Tokenizer for WordTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+/// Character bigrams for runs of Han and kana, words for everything else
+pub struct CjkTokenizer;
+
+impl Tokenizer for CjkTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut run: Vec<char> = Vec::new();
+        let mut word = String::new();
+
+        for c in text.chars() {
+            if is_cjk(c) {
+                push_word(&mut tokens, &mut word);
+                run.push(c);
+            } else {
+                push_bigrams(&mut tokens, &mut run);
+                if c.is_alphanumeric() {
+                    word.push(c);
+                } else {
+                    push_word(&mut tokens, &mut word);
+                }
+            }
+        }
+        push_word(&mut tokens, &mut word);
+        push_bigrams(&mut tokens, &mut run);
+        tokens
+    }
+}
+
+fn push_word(tokens: &mut Vec<String>, word: &mut String) {
+    if !word.is_empty() {
+        tokens.push(word.to_lowercase());
+        word.clear();
+    }
+}
+
+/// A lone character stays a token of its own, so one-character queries still match
+fn push_bigrams(tokens: &mut Vec<String>, run: &mut Vec<char>) {
+    match run.len() {
+        0 => {}
+        1 => tokens.push(run[0].to_string()),
+        _ => tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    run.clear();
+}
+
+/// Languages the tokenizers distinguish
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// Chinese or Japanese: words are not separated by spaces
+    Cjk,
+    /// Anything written with spaces between words
+    Spaced,
+}
+
+/// Whether a character is Thai, a script written without spaces between words
+fn is_thai(c: char) -> bool {
+    ('\u{0E00}'..='\u{0E7F}').contains(&c)
+}
+
+/// Whether a character is Han (CJK ideographs) or Japanese kana
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{31F0}'..='\u{31FF}'   // Katakana phonetic extensions
+        | '\u{3400}'..='\u{4DBF}'   // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Halfwidth katakana
+        | '\u{20000}'..='\u{2FA1F}' // CJK extensions B-F, compatibility supplement
+    )
+}
+
+/// Language of a text, from its script. Any Han or kana makes it CJK, since mixed
+/// text such as a Latin product name in a Chinese review needs the CJK tokenizer.
+pub fn detect_language(text: &str) -> Language {
+    if text.chars().any(is_cjk) {
+        Language::Cjk
+    } else {
+        Language::Spaced
+    }
+}
+
+/// The tokenizer for a language
+pub fn tokenizer_for(language: Language) -> &'static dyn Tokenizer {
+    match language {
+        Language::Cjk => &CjkTokenizer,
+        Language::Spaced => &WordTokenizer,
+    }
+}
+
+/// Tokenize text with the tokenizer for its detected language
+pub fn tokenize(text: &str) -> Vec<String> {
+    tokenizer_for(detect_language(text)).tokenize(text)
+}
+
+/// Normalize then tokenize: the analyzer chain for keyword matching
+pub fn analyze(text: &str) -> Vec<String> {
+    tokenize(&normalize(text))
+}
+
+/// Stemmed, stopword-free terms of normalized text: the analyzer chain for keyword scoring
+pub fn keyword_terms(normalized: &str) -> Vec<String> {
+    let stopwords = stopwords();
+    tokenize(normalized).into_iter().filter(|term| !stopwords.contains(term)).map(|term| stem(&term)).collect()
+}
+
+/// A term's stem. Terms in scripts written without spaces have no affixes to strip.
+pub fn stem(term: &str) -> String {
+    match stemmer() {
+        Some((_, stemmer)) if !term.chars().any(|c| is_cjk(c) || is_thai(c)) => stemmer.stem(term).into_owned(),
+        _ => term.to_string(),
+    }
+}
+
+/// Words too common to tell reviews apart. Negations are kept: "not working" is not
+/// "working".
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "all", "am", "an", "and", "any", "are", "as", "at", "be", "because",
+    "been", "before", "being", "below", "between", "both", "but", "by", "could", "did", "do", "does", "doing",
+    "down", "during", "each", "few", "for", "from", "further", "had", "has", "have", "having", "he", "her", "here",
+    "hers", "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "it", "its", "itself", "just",
+    "me", "more", "most", "my", "myself", "of", "off", "on", "once", "only", "or", "other", "our", "ours",
+    "ourselves", "out", "over", "own", "s", "same", "she", "should", "so", "some", "such", "than", "that", "the",
+    "their", "theirs", "them", "themselves", "then", "there", "these", "they", "this", "those", "through", "to",
+    "too", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where", "which", "while", "who",
+    "whom", "why", "will", "with", "would", "you", "your", "yours", "yourself", "yourselves",
+];
+
+/// Stopwords dropped from keyword scoring, as `STOPWORDS` lists them
+pub fn stopwords() -> &'static HashSet<String> {
+    static STOPWORDS: OnceLock<HashSet<String>> = OnceLock::new();
+    STOPWORDS.get_or_init(|| load_stopwords(&std::env::var("STOPWORDS").unwrap_or_else(|_| "english".to_string())))
+}
+
+/// Stopwords from comma-separated sources: `english`, files of one word per line
+/// (blank lines and `#` comments ignored), or `none`. Unreadable files are skipped.
+pub fn load_stopwords(sources: &str) -> HashSet<String> {
+    let mut stopwords = HashSet::new();
+    for source in sources.split(',').map(str::trim).filter(|source| !source.is_empty()) {
+        match source.to_ascii_lowercase().as_str() {
+            "none" | "off" | "false" | "0" => {}
+            "english" => stopwords.extend(ENGLISH_STOPWORDS.iter().map(|word| word.to_string())),
+            _ => match std::fs::read_to_string(source) {
+                Ok(text) => {
+                    for line in text.lines() {
+                        stopwords.extend(analyze(line.split('#').next().unwrap_or_default()));
+                    }
+                }
+                Err(e) => tracing::warn!("Could not read stopwords from {}: {}", source, e),
+            },
+        }
+    }
+    stopwords
+}
+
+/// Snowball stemmers, by language
+const STEMMERS: [(&str, Algorithm); 18] = [
+    ("arabic", Algorithm::Arabic),
+    ("danish", Algorithm::Danish),
+    ("dutch", Algorithm::Dutch),
+    ("english", Algorithm::English),
+    ("finnish", Algorithm::Finnish),
+    ("french", Algorithm::French),
+    ("german", Algorithm::German),
+    ("greek", Algorithm::Greek),
+    ("hungarian", Algorithm::Hungarian),
+    ("italian", Algorithm::Italian),
+    ("norwegian", Algorithm::Norwegian),
+    ("portuguese", Algorithm::Portuguese),
+    ("romanian", Algorithm::Romanian),
+    ("russian", Algorithm::Russian),
+    ("spanish", Algorithm::Spanish),
+    ("swedish", Algorithm::Swedish),
+    ("tamil", Algorithm::Tamil),
+    ("turkish", Algorithm::Turkish),
+];
+
+fn stemmer_algorithm(name: &str) -> Option<(&'static str, Algorithm)> {
+    let name = name.trim().to_ascii_lowercase();
+    STEMMERS.into_iter().find(|(language, _)| *language == name)
+}
+
+/// Language of a Snowball stemmer, by name
+pub fn stemmer_language(name: &str) -> Option<&'static str> {
+    stemmer_algorithm(name).map(|(language, _)| language)
+}
+
+/// The keyword scorer's stemmer and its language, as `STEMMER` names it; none when
+/// `STEMMER` is none or unknown
+pub fn stemmer() -> Option<&'static (&'static str, Stemmer)> {
+    static STEMMER: OnceLock<Option<(&'static str, Stemmer)>> = OnceLock::new();
+    STEMMER
+        .get_or_init(|| {
+            let (language, algorithm) =
+                stemmer_algorithm(&std::env::var("STEMMER").unwrap_or_else(|_| "english".to_string()))?;
+            Some((language, Stemmer::create(algorithm)))
+        })
+        .as_ref()
+}
+
+/// Whether `normalize` folds diacritics; on unless `FOLD_DIACRITICS` is false, 0 or off
+pub fn folding_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("FOLD_DIACRITICS")
+            .map(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "false" | "0" | "off"))
+            .unwrap_or(true)
+    })
+}
+
+/// Lowercase text for keyword matching, folding diacritics unless disabled
+pub fn normalize(text: &str) -> String {
+    normalize_with(text, folding_enabled())
+}
+
+pub fn normalize_with(text: &str, fold_diacritics: bool) -> String {
+    let text = &*normalize_thai(text);
+    if !fold_diacritics {
+        return normalize_quantities(&text.nfkc().collect::<String>().to_lowercase());
+    }
+
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfkd().filter(|c| !is_diacritic(*c)) {
+        // Letters with a stroke or ligatures have no decomposition to strip
+        match c {
+            'ø' | 'Ø' => folded.push('o'),
+            'đ' | 'Đ' => folded.push('d'),
+            'ł' | 'Ł' => folded.push('l'),
+            'æ' | 'Æ' => folded.push_str("ae"),
+            'œ' | 'Œ' => folded.push_str("oe"),
+            'ß' => folded.push_str("ss"),
+            _ => folded.push(c),
+        }
+    }
+    // Recompose what remains, e.g. Hangul syllables and kana with voicing marks
+    normalize_quantities(&folded.nfc().collect::<String>().to_lowercase())
+}
+
+/// Characters with no visible form
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{00AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Thai vowel and tone marks written above or below a consonant
+fn is_thai_mark(c: char) -> bool {
+    matches!(c, '\u{0E31}' | '\u{0E34}'..='\u{0E3A}' | '\u{0E47}'..='\u{0E4E}')
+}
+
+/// Drop invisible characters and give Thai text its standard spelling
+pub fn normalize_thai(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| is_invisible(c) || is_thai(c)) {
+        return Cow::Borrowed(text);
+    }
+
+    let chars: Vec<char> = text.chars().filter(|c| !is_invisible(*c)).collect();
+    let mut normalized = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        match (c, chars.get(index + 1).copied(), chars.get(index + 2).copied()) {
+            // Sara e twice for sara ae
+            ('\u{0E40}', Some('\u{0E40}'), _) => {
+                normalized.push('\u{0E41}');
+                index += 2;
+            }
+            // Nikhahit, tone mark, sara aa for tone mark, sara am
+            ('\u{0E4D}', Some(tone @ '\u{0E48}'..='\u{0E4B}'), Some('\u{0E32}')) => {
+                normalized.push(tone);
+                normalized.push('\u{0E33}');
+                index += 3;
+            }
+            // A mark typed twice displays once
+            _ if is_thai_mark(c) && normalized.ends_with(c) => index += 1,
+            _ => {
+                normalized.push(c);
+                index += 1;
+            }
+        }
+    }
+    Cow::Owned(normalized)
+}
+
+/// Combining diacritical marks. Kana voicing marks (U+3099, U+309A) are outside these
+/// blocks, so "ガ" does not fold to "カ".
+fn is_diacritic(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'   // Combining diacritical marks
+        | '\u{1AB0}'..='\u{1AFF}' // Extended
+        | '\u{1DC0}'..='\u{1DFF}' // Supplement
+        | '\u{20D0}'..='\u{20FF}' // For symbols
+        | '\u{FE20}'..='\u{FE2F}' // Half marks
+    )
+}
+
+/// Canonical spelling of a unit of measure
+fn canonical_unit(word: &str) -> Option<&'static str> {
+    Some(match word {
+        "h" | "hr" | "hrs" | "hour" | "hours" => "hour",
+        "min" | "mins" | "minute" | "minutes" => "minute",
+        "s" | "sec" | "secs" | "second" | "seconds" => "second",
+        "day" | "days" => "day",
+        "wk" | "wks" | "week" | "weeks" => "week",
+        "mo" | "mos" | "month" | "months" => "month",
+        "yr" | "yrs" | "year" | "years" => "year",
+        "mah" => "mah",
+        "wh" => "wh",
+        "mb" | "megabyte" | "megabytes" => "mb",
+        "gb" | "gig" | "gigs" | "gigabyte" | "gigabytes" => "gb",
+        "tb" | "terabyte" | "terabytes" => "tb",
+        "g" | "gram" | "grams" => "g",
+        "kg" | "kgs" | "kilo" | "kilos" | "kilogram" | "kilograms" => "kg",
+        "lb" | "lbs" | "pound" | "pounds" => "lb",
+        "oz" | "ounce" | "ounces" => "oz",
+        "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => "mm",
+        "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => "cm",
+        "m" | "meter" | "meters" | "metre" | "metres" => "m",
+        "km" | "kilometer" | "kilometers" | "kilometre" | "kilometres" => "km",
+        "in" | "inch" | "inches" => "inch",
+        "ft" | "foot" | "feet" => "ft",
+        "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => "ml",
+        "l" | "liter" | "liters" | "litre" | "litres" => "l",
+        "w" | "watt" | "watts" => "w",
+        "v" | "volt" | "volts" => "v",
+        _ => return None,
+    })
+}
+
+/// Digits for a spelled-out number
+fn number_word(word: &str) -> Option<&'static str> {
+    const WORDS: [&str; 21] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+        "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen", "twenty",
+    ];
+    const DIGITS: [&str; 21] = [
+        "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16", "17", "18", "19",
+        "20",
+    ];
+    WORDS.iter().position(|number| *number == word).map(|index| DIGITS[index])
+}
+
+fn is_number(word: &str) -> bool {
+    !word.is_empty() && word.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Split a word such as "10h" or "2000mah" into its number and unit
+fn split_quantity(word: &str) -> Option<(&str, &'static str)> {
+    let digits = word.bytes().take_while(u8::is_ascii_digit).count();
+    match digits {
+        0 => None,
+        _ => canonical_unit(&word[digits..]).map(|unit| (&word[..digits], unit)),
+    }
+}
+
+/// Token filter for quantities: spelled-out numbers before a unit become digits,
+/// thousands separators are dropped ("1,000" is "1000"), and a unit after a number
+/// is separated from it and given its canonical spelling. Expects lowercase text.
+pub fn normalize_quantities(text: &str) -> String {
+    // Alternating runs of word and separator characters
+    let mut spans: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (index, c) in text.char_indices().skip(1) {
+        let previous = text[..index].chars().next_back().unwrap();
+        if previous.is_alphanumeric() != c.is_alphanumeric() {
+            spans.push(&text[start..index]);
+            start = index;
+        }
+    }
+    if start < text.len() {
+        spans.push(&text[start..]);
+    }
+
+    let is_word = |span: &str| span.chars().next().is_some_and(char::is_alphanumeric);
+    // A number and its unit may be separated by spaces or one hyphen
+    let joins = |span: &str| span == "-" || span.chars().all(char::is_whitespace);
+
+    let mut normalized = String::with_capacity(text.len());
+    let mut after_number = false;
+    for (index, span) in spans.iter().enumerate() {
+        if !is_word(span) {
+            let thousands = *span == ","
+                && index > 0
+                && is_number(spans[index - 1])
+                && spans.get(index + 1).is_some_and(|next| {
+                    next.bytes().take_while(u8::is_ascii_digit).count() == 3 && (is_number(next) || split_quantity(next).is_some())
+                });
+            if !thousands {
+                normalized.push_str(span);
+                after_number &= joins(span);
+            }
+            continue;
+        }
+
+        let next_is_unit = || {
+            spans.get(index + 1).is_some_and(|separator| joins(separator))
+                && spans.get(index + 2).is_some_and(|next| canonical_unit(next).is_some())
+        };
+        if is_number(span) {
+            normalized.push_str(span);
+            after_number = true;
+        } else if let Some((number, unit)) = split_quantity(span) {
+            normalized.push_str(number);
+            normalized.push(' ');
+            normalized.push_str(unit);
+            after_number = false;
+        } else if let Some(unit) = canonical_unit(span).filter(|_| after_number) {
+            normalized.push_str(unit);
+            after_number = false;
+        } else if let Some(digits) = number_word(span).filter(|_| next_is_unit()) {
+            normalized.push_str(digits);
+            after_number = true;
+        } else {
+            normalized.push_str(span);
+            after_number = false;
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spaced_text_splits_on_non_alphanumerics() {
+        assert_eq!(detect_language("Great battery, 10/10!"), Language::Spaced);
+        assert_eq!(tokenize("Great battery, 10/10!"), vec!["great", "battery", "10", "10"]);
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn test_cjk_text_splits_into_bigrams_around_words() {
+        // Chinese: "battery life is very good"
+        assert_eq!(detect_language("电池很好"), Language::Cjk);
+        assert_eq!(tokenize("电池很好"), vec!["电池", "池很", "很好"]);
+
+        // Japanese mixes kana and kanji in one run; Latin words and digits stay whole
+        assert_eq!(tokenize("iPhone用のケース"), vec!["iphone", "用の", "のケ", "ケー", "ース"]);
+        assert_eq!(tokenize("好，USB-C 3个"), vec!["好", "usb", "c", "3", "个"]);
+    }
+
+    #[test]
+    fn test_normalize_folds_diacritics_unless_disabled() {
+        // Precomposed and decomposed accents, full-width letters, strokes and ligatures
+        assert_eq!(normalize_with("Café CAFE\u{301}", true), "cafe cafe");
+        assert_eq!(normalize_with("Ｓøren Straße Œuvre", true), "soren strasse oeuvre");
+        assert_eq!(analyze("crème brûlée"), vec!["creme", "brulee"]);
+
+        // Kana voicing marks and Hangul survive the round trip
+        assert_eq!(normalize_with("ガラス 한국어", true), "ガラス 한국어");
+
+        // Without folding only the normalization applies
+        assert_eq!(normalize_with("Phở CAFE\u{301}", false), "phở café");
+    }
+
+    #[test]
+    fn test_thai_variants_normalize_alike() {
+        // "Water", typed with the tone mark inside sara am, and with a zero-width space
+        assert_eq!(normalize("น\u{0E4D}\u{0E49}\u{0E32}"), normalize("น้ำ"));
+        assert_eq!(normalize("น้\u{200B}ำ"), normalize("น้ำ"));
+        // "Bad", typed with two sara e and a doubled tone mark
+        assert_eq!(normalize("\u{0E40}\u{0E40}ย่\u{0E48}"), normalize("แย่"));
+        assert_eq!(normalize_thai("Plain text"), Cow::Borrowed("Plain text"));
+    }
+
+    #[test]
+    fn test_keyword_terms_drop_stopwords_and_stem() {
+        assert_eq!(keyword_terms("the batteries are lasting"), vec!["batteri", "last"]);
+        assert_eq!(keyword_terms("battery lasts"), vec!["batteri", "last"]);
+        // Negations stay, and CJK bigrams are left whole
+        assert_eq!(keyword_terms("not working"), vec!["not", "work"]);
+        assert_eq!(keyword_terms("电池很好"), vec!["电池", "池很", "很好"]);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("stopwords.txt");
+        std::fs::write(&path, "# Product words\nkettle\n\nItem # trailing comment\n").unwrap();
+        let stopwords = load_stopwords(&format!("{}, english", path.display()));
+        assert!(["kettle", "item", "the"].iter().all(|word| stopwords.contains(*word)));
+        assert!(load_stopwords("none").is_empty());
+        assert_eq!(stemmer_language(" French"), Some("french"));
+        assert_eq!(stemmer_language("klingon"), None);
+    }
+
+    #[test]
+    fn test_quantities_share_one_spelling() {
+        assert_eq!(normalize("Battery lasts 10 hours"), "battery lasts 10 hour");
+        assert_eq!(normalize("10h battery, 10-hrs"), "10 hour battery, 10-hour");
+        assert_eq!(normalize("Ten hours, 1,000mAh and 2.5 KG"), "10 hour, 1000 mah and 2.5 kg");
+
+        // Unit words stand alone unless a number precedes them
+        assert_eq!(normalize("fits in 2 in. of space"), "fits in 2 inch. of space");
+        assert_eq!(normalize("one of the best, 10/10"), "one of the best, 10/10");
+        assert_eq!(normalize("sizes 1,2,3"), "sizes 1,2,3");
+    }
+}
@@ -0,0 +1,214 @@
+//! reviews.index: one fixed-size vector per JSONL line, stored back to back as
+//! little-endian f32s. Slot N is the vector for the review with vector_index N, so the
+//! slot count is simply the file length divided by the vector size.
+
+use crate::models::AppError;
+use crate::storage::SlotBitmap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+pub struct VectorStore {
+    file_path: PathBuf,
+    dimension: usize,
+}
+
+impl VectorStore {
+    pub fn new<P: AsRef<Path>>(file_path: P, dimension: usize) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+            dimension,
+        }
+    }
+
+    fn slot_bytes(&self) -> u64 {
+        (self.dimension * std::mem::size_of::<f32>()) as u64
+    }
+
+    /// Number of whole vectors stored; a partial trailing vector is not counted
+    pub fn len(&self) -> Result<usize, AppError> {
+        match std::fs::metadata(&self.file_path) {
+            Ok(metadata) => Ok((metadata.len() / self.slot_bytes()) as usize),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether no whole vector is stored yet
+    pub fn is_empty(&self) -> Result<bool, AppError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Whether the index file has been created
+    pub fn exists(&self) -> bool {
+        self.file_path.exists()
+    }
+
+    /// Write vectors into consecutive slots starting at `start`, overwriting existing
+    /// slots and extending the file. `start` may not leave a gap past the last slot.
+    /// The caller must hold the FileLock.
+    pub fn write_slots(&self, start: usize, vectors: &[Vec<f32>]) -> Result<(), AppError> {
+        let stored = self.len()?;
+        if start > stored {
+            return Err(AppError::Conflict {
+                message: format!("Vector slot {} would leave a gap after slot {}", start, stored),
+            });
+        }
+
+        let mut bytes = Vec::with_capacity(vectors.len() * self.slot_bytes() as usize);
+        for vector in vectors {
+            if vector.len() != self.dimension {
+                return Err(AppError::Conflict {
+                    message: format!("Expected a {}-dimensional vector, got {}", self.dimension, vector.len()),
+                });
+            }
+            bytes.extend(vector.iter().flat_map(|value| value.to_le_bytes()));
+        }
+
+        let mut file = OpenOptions::new().create(true).truncate(false).write(true).open(&self.file_path)?;
+        // Drop any partial vector left by an interrupted write before extending
+        if start == stored {
+            file.set_len(stored as u64 * self.slot_bytes())?;
+        }
+        file.seek(SeekFrom::Start(start as u64 * self.slot_bytes()))?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Grow the index to `slots` vectors, zero-filling the new slots.
+    /// The caller must hold the FileLock.
+    pub fn extend_to(&self, slots: usize) -> Result<(), AppError> {
+        let stored = self.len()?;
+        if slots <= stored {
+            return Ok(());
+        }
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(&self.file_path)?;
+        file.set_len(slots as u64 * self.slot_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// The vector in `slot`, if it has been written
+    pub fn read(&self, slot: usize) -> Result<Option<Vec<f32>>, AppError> {
+        Ok(self.read_slots(&[slot])?.pop().flatten())
+    }
+
+    /// The vectors in `slots`, in the order asked, `None` for slots not yet written.
+    /// Readers should hold the FileLock shared, so slots aren't rewritten mid-read.
+    pub fn read_slots(&self, slots: &[usize]) -> Result<Vec<Option<Vec<f32>>>, AppError> {
+        let stored = self.len()?;
+        if slots.iter().all(|&slot| slot >= stored) {
+            return Ok(vec![None; slots.len()]);
+        }
+
+        let mut file = File::open(&self.file_path)?;
+        let mut bytes = vec![0u8; self.slot_bytes() as usize];
+        let mut vectors = Vec::with_capacity(slots.len());
+        for &slot in slots {
+            if slot >= stored {
+                vectors.push(None);
+                continue;
+            }
+            file.seek(SeekFrom::Start(slot as u64 * self.slot_bytes()))?;
+            file.read_exact(&mut bytes)?;
+            vectors.push(Some(
+                bytes
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect(),
+            ));
+        }
+        Ok(vectors)
+    }
+
+    /// The `k` slots most similar to `query`, best first, with their cosine similarity.
+    /// Stored vectors and the query are unit length, so the dot product is the cosine.
+    /// Slots in `excluded` and vectors with no positive similarity are skipped. Readers
+    /// should hold the FileLock shared.
+    pub fn nearest(&self, query: &[f32], k: usize, excluded: &SlotBitmap) -> Result<Vec<(usize, f32)>, AppError> {
+        if query.len() != self.dimension {
+            return Err(AppError::Conflict {
+                message: format!("Expected a {}-dimensional query vector, got {}", self.dimension, query.len()),
+            });
+        }
+        let stored = self.len()?;
+        if k == 0 || stored == 0 {
+            return Ok(Vec::new());
+        }
+
+        // One sequential pass over the file; only slots with a positive score are kept
+        let mut reader = BufReader::new(File::open(&self.file_path)?);
+        let mut bytes = vec![0u8; self.slot_bytes() as usize];
+        let mut scored = Vec::new();
+        for slot in 0..stored {
+            reader.read_exact(&mut bytes)?;
+            if excluded.contains(slot) {
+                continue;
+            }
+            let similarity: f32 = bytes
+                .chunks_exact(4)
+                .zip(query)
+                .map(|(chunk, value)| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) * value)
+                .sum();
+            if similarity > 0.0 {
+                scored.push((slot, similarity));
+            }
+        }
+
+        let best_first = |a: &(usize, f32), b: &(usize, f32)| {
+            b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0))
+        };
+        if scored.len() > k {
+            scored.select_nth_unstable_by(k, best_first);
+            scored.truncate(k);
+        }
+        scored.sort_by(best_first);
+        Ok(scored)
+    }
+
+    /// Keep only the first `slots` vectors. The caller must hold the FileLock.
+    pub fn truncate(&self, slots: usize) -> Result<(), AppError> {
+        if !self.exists() {
+            return Ok(());
+        }
+        let file = OpenOptions::new().write(true).open(&self.file_path)?;
+        file.set_len(slots as u64 * self.slot_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_vector_store_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VectorStore::new(temp_dir.path().join("reviews.index"), 2);
+        assert_eq!(store.len().unwrap(), 0);
+        assert!(store.read(0).unwrap().is_none());
+
+        store.write_slots(0, &[vec![1.0, 0.0], vec![0.0, 1.0]]).unwrap();
+        store.write_slots(1, &[vec![0.5, 0.5], vec![-1.0, 0.0]]).unwrap();
+        assert_eq!(store.len().unwrap(), 3);
+        assert_eq!(store.read(1).unwrap(), Some(vec![0.5, 0.5]));
+
+        // Slots stay dense
+        assert!(store.write_slots(5, &[vec![0.0, 0.0]]).is_err());
+        assert!(store.write_slots(3, &[vec![0.0]]).is_err());
+
+        // Nearest neighbours skip excluded slots and vectors pointing away
+        let mut excluded = SlotBitmap::with_slots(3);
+        assert_eq!(store.nearest(&[1.0, 0.0], 2, &excluded).unwrap(), vec![(0, 1.0), (1, 0.5)]);
+        excluded.set(0);
+        assert_eq!(store.nearest(&[1.0, 0.0], 2, &excluded).unwrap(), vec![(1, 0.5)]);
+        assert!(store.nearest(&[1.0], 2, &excluded).is_err());
+
+        store.truncate(1).unwrap();
+        assert_eq!(store.len().unwrap(), 1);
+        assert_eq!(store.read(0).unwrap(), Some(vec![1.0, 0.0]));
+    }
+}
@@ -0,0 +1,18 @@
+// Parses and validates upload files off the main thread using the same wasm module.
+import init, { parse_upload_preview } from './pkg/semantic_search_frontend.js';
+
+const ready = init();
+
+self.onmessage = async (event) => {
+    const { fileName, content } = event.data;
+
+    try {
+        await ready;
+        const summary = parse_upload_preview(fileName, content, (processed, total) => {
+            self.postMessage({ type: 'progress', processed, total });
+        });
+        self.postMessage({ type: 'done', summary });
+    } catch (error) {
+        self.postMessage({ type: 'error', message: String(error) });
+    }
+};
@@ -0,0 +1,252 @@
+use serde::Deserialize;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event, HtmlInputElement, HtmlSelectElement};
+
+use crate::{api_error, describe_error, format, make_api_request, modal};
+
+const PANEL_ID: &str = "blocklist";
+
+/// Audit records shown under the entries
+const AUDIT_SHOWN: usize = 10;
+
+#[derive(Deserialize)]
+struct BlocklistEntry {
+    id: String,
+    kind: String,
+    value: String,
+    scope: String,
+    reason: Option<String>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct BlocklistResponse {
+    entries: Vec<BlocklistEntry>,
+}
+
+#[derive(Deserialize)]
+struct AuditRecord {
+    action: String,
+    entry: BlocklistEntry,
+    recorded_at: String,
+}
+
+#[derive(Deserialize)]
+struct AuditResponse {
+    records: Vec<AuditRecord>,
+}
+
+async fn fetch<T: for<'de> Deserialize<'de>>(endpoint: &str) -> Result<T, JsValue> {
+    let response = make_api_request("GET", endpoint, None).await?;
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Send an entry change: create (POST with a body) or delete
+async fn send(method: &str, endpoint: &str, body: Option<String>) -> Result<(), JsValue> {
+    let response = make_api_request(method, endpoint, body).await?;
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    Ok(())
+}
+
+/// Load and render the entries and recent changes into `#blocklist`
+pub async fn refresh() {
+    let Some(panel) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID)) else {
+        return;
+    };
+
+    let loaded = match fetch::<BlocklistResponse>("/admin/blocklist").await {
+        Ok(entries) => fetch::<AuditResponse>("/admin/blocklist/audit").await.map(|audit| (entries, audit)),
+        Err(error) => Err(error),
+    };
+    match loaded {
+        Ok((entries, audit)) => panel.set_inner_html(&render(&entries.entries, &audit.records)),
+        Err(error) => {
+            console::error_1(&format!("Failed to load blocklist: {:?}", error).into());
+            panel.set_inner_html(r#"<h3>Blocklist</h3><p class="history-empty">The blocklist is unavailable.</p>"#);
+        }
+    }
+}
+
+fn render(entries: &[BlocklistEntry], audit: &[AuditRecord]) -> String {
+    let table = if entries.is_empty() {
+        r#"<p class="history-empty">Nothing is blocked.</p>"#.to_string()
+    } else {
+        format!(
+            r#"<table class="history-table">
+                   <thead><tr><th>Blocked</th><th>Enforced at</th><th>Reason</th><th>Created</th><th></th></tr></thead>
+                   <tbody>{}</tbody>
+               </table>"#,
+            entries.iter().map(render_entry).collect::<String>()
+        )
+    };
+
+    let changes = if audit.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#"<h4>Recent changes</h4><ul class="blocklist-audit">{}</ul>"#,
+            audit.iter().take(AUDIT_SHOWN).map(render_audit).collect::<String>()
+        )
+    };
+
+    format!(
+        r#"<h3>Blocklist</h3>
+           <p class="maintenance-help">Refuse new reviews of a product or from a reviewer, hide their stored reviews from search, or both. Stored reviews are kept and reappear when the entry is deleted.</p>
+           {}
+           <form id="blocklist-form" class="blocklist-form">
+               <select id="blocklist-kind">
+                   <option value="product">Product</option>
+                   <option value="reviewer">Reviewer</option>
+               </select>
+               <input id="blocklist-value" type="text" placeholder="Product or reviewer id" required>
+               <select id="blocklist-scope">
+                   <option value="all">Ingestion and search</option>
+                   <option value="ingestion">Ingestion only</option>
+                   <option value="search">Search only</option>
+               </select>
+               <input id="blocklist-reason" type="text" placeholder="Reason">
+               <button type="submit">Block</button>
+           </form>
+           <p class="blocklist-status"></p>
+           {}"#,
+        table, changes
+    )
+}
+
+fn describe_scope(scope: &str) -> &'static str {
+    match scope {
+        "ingestion" => "Ingestion",
+        "search" => "Search",
+        _ => "Ingestion and search",
+    }
+}
+
+fn render_entry(entry: &BlocklistEntry) -> String {
+    format!(
+        r#"<tr class="history-row"><td>{} <code>{}</code></td><td>{}</td><td>{}</td><td>{}</td>
+               <td><button type="button" class="rollback-btn" data-entry-id="{}">Delete</button></td></tr>"#,
+        format::escape_html(&entry.kind),
+        format::escape_html(&entry.value),
+        describe_scope(&entry.scope),
+        format::escape_html(entry.reason.as_deref().unwrap_or("")),
+        format::format_timestamp(&entry.created_at),
+        format::escape_html(&entry.id)
+    )
+}
+
+fn render_audit(record: &AuditRecord) -> String {
+    format!(
+        "<li>{} · {} {} <code>{}</code> ({})</li>",
+        format::format_timestamp(&record.recorded_at),
+        format::escape_html(&record.action),
+        format::escape_html(&record.entry.kind),
+        format::escape_html(&record.entry.value),
+        describe_scope(&record.entry.scope)
+    )
+}
+
+fn select_value(document: &web_sys::Document, id: &str) -> String {
+    document
+        .get_element_by_id(id)
+        .and_then(|e| e.dyn_into::<HtmlSelectElement>().ok())
+        .map(|select| select.value())
+        .unwrap_or_default()
+}
+
+fn input_value(document: &web_sys::Document, id: &str) -> Option<String> {
+    let value = document.get_element_by_id(id)?.dyn_into::<HtmlInputElement>().ok()?.value();
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn show_status(message: &str) {
+    if let Some(status) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.query_selector(&format!("#{} .blocklist-status", PANEL_ID)).ok().flatten())
+    {
+        // describe_error escapes the backend message
+        status.set_inner_html(message);
+    }
+}
+
+/// Handle entry creation and deletion on the panel
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let on_submit = Closure::wrap(Box::new(move |event: Event| {
+        let Some(form) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        if form.id() != "blocklist-form" {
+            return;
+        }
+        event.prevent_default();
+        let Some(document) = window().and_then(|w| w.document()) else {
+            return;
+        };
+        let body = json!({
+            "kind": select_value(&document, "blocklist-kind"),
+            "value": input_value(&document, "blocklist-value").unwrap_or_default(),
+            "scope": select_value(&document, "blocklist-scope"),
+            "reason": input_value(&document, "blocklist-reason"),
+        })
+        .to_string();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(error) = send("POST", "/admin/blocklist", Some(body)).await {
+                console::error_1(&format!("Creating blocklist entry failed: {:?}", error).into());
+                show_status(&describe_error(&error, "❌ The entry could not be saved."));
+                return;
+            }
+            refresh().await;
+        });
+    }) as Box<dyn FnMut(_)>);
+    panel.add_event_listener_with_callback("submit", on_submit.as_ref().unchecked_ref())?;
+    on_submit.forget(); // Keep the closure alive
+
+    let on_click = Closure::wrap(Box::new(move |event: Event| {
+        let Some(entry_id) = event
+            .target()
+            .and_then(|t| t.dyn_into::<Element>().ok())
+            .and_then(|target| target.get_attribute("data-entry-id"))
+        else {
+            return;
+        };
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let confirmed = modal::confirm(
+                "Unblock?",
+                "<p>New reviews will be accepted and stored ones will show in search again.</p>",
+                "Unblock",
+                "Cancel",
+            )
+            .await;
+            if !confirmed {
+                return;
+            }
+
+            let endpoint = format!("/admin/blocklist/{}", js_sys::encode_uri_component(&entry_id));
+            if let Err(error) = send("DELETE", &endpoint, None).await {
+                console::error_1(&format!("Deleting blocklist entry failed: {:?}", error).into());
+                show_status(&describe_error(&error, "❌ The entry could not be deleted."));
+                return;
+            }
+            refresh().await;
+        });
+    }) as Box<dyn FnMut(_)>);
+    panel.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+    on_click.forget(); // Keep the closure alive
+
+    Ok(())
+}
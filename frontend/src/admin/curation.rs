@@ -0,0 +1,268 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event, HtmlInputElement, HtmlSelectElement};
+
+use crate::{api_error, describe_error, format, make_api_request, modal};
+
+const PANEL_ID: &str = "curation";
+
+#[derive(Deserialize)]
+struct RuleScope {
+    query: Option<String>,
+    product_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BoostTarget {
+    product_id: Option<String>,
+    reviewer_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CurationAction {
+    Pin { review_id: String, position: u32 },
+    Boost { target: BoostTarget, weight: f32 },
+}
+
+#[derive(Deserialize)]
+struct CurationRule {
+    id: String,
+    scope: RuleScope,
+    action: CurationAction,
+    note: Option<String>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct ListRulesResponse {
+    rules: Vec<CurationRule>,
+}
+
+async fn fetch_rules() -> Result<ListRulesResponse, JsValue> {
+    let response = make_api_request("GET", "/admin/curation/rules", None).await?;
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Send a rule change: create (POST with a body) or delete
+async fn send(method: &str, endpoint: &str, body: Option<String>) -> Result<(), JsValue> {
+    let response = make_api_request(method, endpoint, body).await?;
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    Ok(())
+}
+
+/// Load and render the rules into `#curation`
+pub async fn refresh() {
+    let Some(panel) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID)) else {
+        return;
+    };
+
+    match fetch_rules().await {
+        Ok(response) => panel.set_inner_html(&render(&response.rules)),
+        Err(error) => {
+            console::error_1(&format!("Failed to load curation rules: {:?}", error).into());
+            panel.set_inner_html(r#"<h3>Search Curation</h3><p class="history-empty">Curation rules are unavailable.</p>"#);
+        }
+    }
+}
+
+fn render(rules: &[CurationRule]) -> String {
+    let table = if rules.is_empty() {
+        r#"<p class="history-empty">No rules; searches rank organically.</p>"#.to_string()
+    } else {
+        format!(
+            r#"<table class="history-table">
+                   <thead><tr><th>Searches</th><th>Rule</th><th>Note</th><th>Created</th><th></th></tr></thead>
+                   <tbody>{}</tbody>
+               </table>"#,
+            rules.iter().map(render_rule).collect::<String>()
+        )
+    };
+
+    format!(
+        r#"<h3>Search Curation</h3>
+           <p class="maintenance-help">Pin a review to a position for a query or product, or boost (positive weight) or demote (negative weight) a product's or reviewer's reviews in every search.</p>
+           {}
+           <form id="curation-form" class="curation-form">
+               <select id="curation-type">
+                   <option value="pin">Pin a review</option>
+                   <option value="boost">Boost or demote</option>
+               </select>
+               <input id="curation-query" type="text" placeholder="For query (optional)">
+               <input id="curation-product" type="text" placeholder="For product filter (optional)">
+               <input id="curation-review" type="text" placeholder="Pin: review id">
+               <input id="curation-position" type="number" min="1" max="100" value="1" title="Pin: position">
+               <input id="curation-target-product" type="text" placeholder="Boost: product id">
+               <input id="curation-target-reviewer" type="text" placeholder="Boost: reviewer id">
+               <input id="curation-weight" type="number" min="-1" max="1" step="0.1" value="-1" title="Boost: weight from -1 to 1">
+               <input id="curation-note" type="text" placeholder="Note">
+               <button type="submit">Add rule</button>
+           </form>
+           <p class="curation-status"></p>"#,
+        table
+    )
+}
+
+fn render_rule(rule: &CurationRule) -> String {
+    let mut scope = Vec::new();
+    if let Some(query) = &rule.scope.query {
+        scope.push(format!("“{}”", format::escape_html(query)));
+    }
+    if let Some(product_id) = &rule.scope.product_id {
+        scope.push(format!("product <code>{}</code>", format::escape_html(product_id)));
+    }
+    let scope = if scope.is_empty() { "All".to_string() } else { scope.join(", ") };
+
+    let action = match &rule.action {
+        CurationAction::Pin { review_id, position } => {
+            format!("📌 Pin <code>{}</code> at #{}", format::escape_html(review_id), position)
+        }
+        CurationAction::Boost { target, weight } => {
+            let mut matched = Vec::new();
+            if let Some(product_id) = &target.product_id {
+                matched.push(format!("product <code>{}</code>", format::escape_html(product_id)));
+            }
+            if let Some(reviewer_id) = &target.reviewer_id {
+                matched.push(format!("reviewer <code>{}</code>", format::escape_html(reviewer_id)));
+            }
+            let verb = if *weight < 0.0 { "⬇️ Demote" } else { "⬆️ Boost" };
+            format!("{} {} by {:+.2}", verb, matched.join(", "), weight)
+        }
+    };
+
+    format!(
+        r#"<tr class="history-row"><td>{}</td><td>{}</td><td>{}</td><td>{}</td>
+               <td><button type="button" class="rollback-btn" data-rule-id="{}">Delete</button></td></tr>"#,
+        scope,
+        action,
+        format::escape_html(rule.note.as_deref().unwrap_or("")),
+        format::format_timestamp(&rule.created_at),
+        format::escape_html(&rule.id)
+    )
+}
+
+fn input_value(document: &web_sys::Document, id: &str) -> Option<String> {
+    let value = document.get_element_by_id(id)?.dyn_into::<HtmlInputElement>().ok()?.value();
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// The create request for the form's current values
+fn form_request(document: &web_sys::Document) -> Value {
+    let rule_type = document
+        .get_element_by_id("curation-type")
+        .and_then(|e| e.dyn_into::<HtmlSelectElement>().ok())
+        .map(|select| select.value())
+        .unwrap_or_default();
+
+    let action = match rule_type.as_str() {
+        "boost" => json!({
+            "type": "boost",
+            "target": {
+                "product_id": input_value(document, "curation-target-product"),
+                "reviewer_id": input_value(document, "curation-target-reviewer"),
+            },
+            "weight": input_value(document, "curation-weight").and_then(|w| w.parse::<f32>().ok()).unwrap_or(0.0),
+        }),
+        _ => json!({
+            "type": "pin",
+            "review_id": input_value(document, "curation-review").unwrap_or_default(),
+            "position": input_value(document, "curation-position").and_then(|p| p.parse::<u32>().ok()).unwrap_or(1),
+        }),
+    };
+
+    json!({
+        "scope": {
+            "query": input_value(document, "curation-query"),
+            "product_id": input_value(document, "curation-product"),
+        },
+        "action": action,
+        "note": input_value(document, "curation-note"),
+    })
+}
+
+fn show_status(message: &str) {
+    if let Some(status) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.query_selector(&format!("#{} .curation-status", PANEL_ID)).ok().flatten())
+    {
+        // describe_error escapes the backend message
+        status.set_inner_html(message);
+    }
+}
+
+/// Handle rule creation and deletion on the panel
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let on_submit = Closure::wrap(Box::new(move |event: Event| {
+        let Some(form) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        if form.id() != "curation-form" {
+            return;
+        }
+        event.prevent_default();
+        let Some(document) = window().and_then(|w| w.document()) else {
+            return;
+        };
+        let body = form_request(&document).to_string();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(error) = send("POST", "/admin/curation/rules", Some(body)).await {
+                console::error_1(&format!("Creating curation rule failed: {:?}", error).into());
+                show_status(&describe_error(&error, "❌ The rule could not be saved."));
+                return;
+            }
+            refresh().await;
+        });
+    }) as Box<dyn FnMut(_)>);
+    panel.add_event_listener_with_callback("submit", on_submit.as_ref().unchecked_ref())?;
+    on_submit.forget(); // Keep the closure alive
+
+    let on_click = Closure::wrap(Box::new(move |event: Event| {
+        let Some(rule_id) = event
+            .target()
+            .and_then(|t| t.dyn_into::<Element>().ok())
+            .and_then(|target| target.get_attribute("data-rule-id"))
+        else {
+            return;
+        };
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let confirmed = modal::confirm(
+                "Delete rule?",
+                "<p>Searches will rank without this rule from now on.</p>",
+                "Delete",
+                "Cancel",
+            )
+            .await;
+            if !confirmed {
+                return;
+            }
+
+            let endpoint = format!("/admin/curation/rules/{}", js_sys::encode_uri_component(&rule_id));
+            if let Err(error) = send("DELETE", &endpoint, None).await {
+                console::error_1(&format!("Deleting curation rule failed: {:?}", error).into());
+                show_status(&describe_error(&error, "❌ The rule could not be deleted."));
+                return;
+            }
+            refresh().await;
+        });
+    }) as Box<dyn FnMut(_)>);
+    panel.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+    on_click.forget(); // Keep the closure alive
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use serde::Deserialize;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event};
+
+use crate::{api_error, format, make_api_request};
+
+const PANEL_ID: &str = "embedding-map";
+
+/// Reviews sampled for the plot
+const SAMPLE_SIZE: u32 = 500;
+
+/// Side of the square plot area, in SVG units
+const PLOT_SIZE: f32 = 320.0;
+
+/// Blank border inside the plot so edge points aren't clipped
+const PLOT_MARGIN: f32 = 8.0;
+
+#[derive(Clone, Deserialize)]
+struct ProjectionPoint {
+    review_id: String,
+    product_id: String,
+    rating: u8,
+    x: f32,
+    y: f32,
+}
+
+#[derive(Clone, Deserialize)]
+struct ProjectionResponse {
+    seed: u64,
+    explained_variance: [f32; 2],
+    points: Vec<ProjectionPoint>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ColorBy {
+    Rating,
+    Product,
+}
+
+thread_local! {
+    /// Last projection and colouring, so switching colours doesn't refetch
+    static VIEW: RefCell<Option<(ProjectionResponse, ColorBy)>> = const { RefCell::new(None) };
+}
+
+async fn fetch_projection() -> Result<ProjectionResponse, JsValue> {
+    let endpoint = format!("/analyze/projection?sample={}", SAMPLE_SIZE);
+    let response = make_api_request("GET", &endpoint, None).await?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn panel() -> Option<Element> {
+    window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID))
+}
+
+/// Draw a fresh sample of the embedding space
+pub async fn refresh() {
+    let Some(panel) = panel() else {
+        return;
+    };
+
+    match fetch_projection().await {
+        Ok(projection) => {
+            let color_by = VIEW.with(|view| view.borrow().as_ref().map_or(ColorBy::Rating, |(_, color_by)| *color_by));
+            panel.set_inner_html(&render(&projection, color_by));
+            VIEW.with(|view| *view.borrow_mut() = Some((projection, color_by)));
+        }
+        Err(error) => {
+            console::error_1(&format!("Failed to load the embedding projection: {:?}", error).into());
+            panel.set_inner_html(&render_unavailable());
+        }
+    }
+}
+
+fn render_unavailable() -> String {
+    r#"<h3>Embedding Map</h3>
+       <p class="history-empty">The embedding projection is unavailable.</p>
+       <button type="button" class="job-btn" data-action="resample">Retry</button>"#
+        .to_string()
+}
+
+fn render(projection: &ProjectionResponse, color_by: ColorBy) -> String {
+    let toggle = |value: ColorBy, label: &str| {
+        format!(
+            r#"<button type="button" class="job-btn{}" data-action="color" data-color-by="{}">{}</button>"#,
+            if value == color_by { " active" } else { "" },
+            if value == ColorBy::Rating { "rating" } else { "product" },
+            label
+        )
+    };
+    let controls = format!(
+        r#"<div class="map-controls">Colour by {} {} <button type="button" class="job-btn" data-action="resample">Resample</button></div>"#,
+        toggle(ColorBy::Rating, "rating"),
+        toggle(ColorBy::Product, "product")
+    );
+
+    if projection.points.is_empty() {
+        return format!(
+            r#"<h3>Embedding Map</h3>{}<p class="history-empty">No embedded reviews to plot yet.</p>"#,
+            controls
+        );
+    }
+
+    format!(
+        r#"<h3>Embedding Map</h3>
+           {}
+           <svg class="embedding-map-plot" viewBox="0 0 {size} {size}" width="{size}" height="{size}" role="img" aria-label="Reviews projected onto two principal components">{}</svg>
+           <p class="job-meta">{} reviews, PCA explains {} (x) and {} (y) of the variance. Sample seed {}. Hover a point for its review.</p>"#,
+        controls,
+        render_points(&projection.points, color_by),
+        format::format_number(projection.points.len() as f64, 0),
+        format::format_percent(projection.explained_variance[0] as f64, 0),
+        format::format_percent(projection.explained_variance[1] as f64, 0),
+        projection.seed,
+        size = PLOT_SIZE
+    )
+}
+
+fn render_points(points: &[ProjectionPoint], color_by: ColorBy) -> String {
+    let bounds = |axis: fn(&ProjectionPoint) -> f32| {
+        points.iter().map(axis).fold((f32::MAX, f32::MIN), |(low, high), value| (low.min(value), high.max(value)))
+    };
+    let (min_x, max_x) = bounds(|point| point.x);
+    let (min_y, max_y) = bounds(|point| point.y);
+    let scale = |value: f32, low: f32, high: f32| {
+        let span = (high - low).max(f32::EPSILON);
+        PLOT_MARGIN + (value - low) / span * (PLOT_SIZE - 2.0 * PLOT_MARGIN)
+    };
+
+    points
+        .iter()
+        .map(|point| {
+            let color = match color_by {
+                ColorBy::Rating => rating_color(point.rating),
+                ColorBy::Product => product_color(&point.product_id),
+            };
+            format!(
+                r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="{}"><title>{} · {}★ · {}</title></circle>"#,
+                scale(point.x, min_x, max_x),
+                // SVG y grows downwards
+                PLOT_SIZE - scale(point.y, min_y, max_y),
+                color,
+                format::escape_html(&point.product_id),
+                point.rating,
+                format::escape_html(&point.review_id)
+            )
+        })
+        .collect()
+}
+
+/// Red for 1 star through green for 5
+fn rating_color(rating: u8) -> String {
+    let hue = (rating.clamp(1, 5) as u32 - 1) * 30;
+    format!("hsl({}, 70%, 45%)", hue)
+}
+
+/// A stable hue per product, so a product keeps its colour across samples
+fn product_color(product_id: &str) -> String {
+    let hash = product_id.bytes().fold(2166136261u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(16777619));
+    format!("hsl({}, 65%, 50%)", hash % 360)
+}
+
+/// Handle the colour toggle and resample button
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(map_panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+
+        match target.get_attribute("data-action").as_deref() {
+            Some("resample") => wasm_bindgen_futures::spawn_local(refresh()),
+            Some("color") => {
+                let color_by = match target.get_attribute("data-color-by").as_deref() {
+                    Some("product") => ColorBy::Product,
+                    _ => ColorBy::Rating,
+                };
+                VIEW.with(|view| {
+                    if let Some((projection, current)) = view.borrow_mut().as_mut() {
+                        *current = color_by;
+                        if let Some(panel) = panel() {
+                            panel.set_inner_html(&render(projection, color_by));
+                        }
+                    }
+                });
+            }
+            _ => {}
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    map_panel.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
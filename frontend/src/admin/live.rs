@@ -0,0 +1,225 @@
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+use web_sys::{console, window, Element, MessageEvent};
+
+use crate::chart::{self, Datum};
+use crate::format;
+
+const PANEL_ID: &str = "live-activity";
+
+/// Samples kept for the throughput chart; the backend sends one a second
+const HISTORY_SAMPLES: usize = 60;
+
+/// Finished bulk uploads listed under the counters
+const RECENT_UPLOADS: usize = 5;
+
+/// Recent errors listed under the counters
+const SHOWN_ERRORS: usize = 5;
+
+#[derive(Clone, Deserialize)]
+struct RecentError {
+    at: String,
+    method: String,
+    path: String,
+    status: u16,
+    code: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+struct QueueDepths {
+    embedding_interactive: usize,
+    embedding_backfill: usize,
+    uploads_in_progress: u64,
+    lock_readers: u64,
+    lock_writer: bool,
+}
+
+#[derive(Clone, Deserialize)]
+struct LiveMetrics {
+    sampled_at: String,
+    reviews_per_sec: f64,
+    searches_per_sec: f64,
+    errors_per_sec: f64,
+    reviews_stored: u64,
+    searches: u64,
+    errors: u64,
+    queues: QueueDepths,
+    recent_errors: Vec<RecentError>,
+}
+
+#[derive(Deserialize)]
+struct BulkUploadCompleted {
+    successful: usize,
+    failed: usize,
+}
+
+struct Upload {
+    finished_at: String,
+    successful: usize,
+    failed: usize,
+}
+
+#[derive(Default)]
+struct LiveView {
+    latest: Option<LiveMetrics>,
+    /// Reviews per second of the last `HISTORY_SAMPLES` samples, oldest first
+    history: VecDeque<f64>,
+    uploads: VecDeque<Upload>,
+}
+
+thread_local! {
+    static VIEW: RefCell<LiveView> = RefCell::new(LiveView::default());
+}
+
+fn panel() -> Option<Element> {
+    window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID))
+}
+
+/// Follow the live metrics stream; the browser reconnects it if the backend restarts
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(live_panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+    live_panel.set_inner_html(r#"<h3>Live Activity</h3><p class="history-empty">Connecting...</p>"#);
+
+    let events = web_sys::EventSource::new(&format!("{}/stats/live/stream", crate::API_BASE_URL))?;
+    let closure = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let Some(data) = event.data().as_string() else {
+            return;
+        };
+        match serde_json::from_str::<LiveMetrics>(&data) {
+            Ok(metrics) => VIEW.with(|view| {
+                let mut view = view.borrow_mut();
+                view.history.push_back(metrics.reviews_per_sec);
+                while view.history.len() > HISTORY_SAMPLES {
+                    view.history.pop_front();
+                }
+                view.latest = Some(metrics);
+                redraw(&view);
+            }),
+            Err(error) => console::error_1(&format!("Unreadable live metrics: {}", error).into()),
+        }
+    }) as Box<dyn FnMut(_)>);
+    events.add_event_listener_with_callback("metrics", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
+
+/// Note a finished bulk upload from the review event stream
+pub fn record_event(event: &web_sys::Event) {
+    if event.type_() != "bulk_upload_completed" {
+        return;
+    }
+    let Some(data) = event.dyn_ref::<MessageEvent>().and_then(|message| message.data().as_string()) else {
+        return;
+    };
+    let Ok(upload) = serde_json::from_str::<BulkUploadCompleted>(&data) else {
+        return;
+    };
+
+    VIEW.with(|view| {
+        let mut view = view.borrow_mut();
+        view.uploads.push_front(Upload {
+            finished_at: String::from(js_sys::Date::new_0().to_iso_string()),
+            successful: upload.successful,
+            failed: upload.failed,
+        });
+        view.uploads.truncate(RECENT_UPLOADS);
+        redraw(&view);
+    });
+}
+
+fn redraw(view: &LiveView) {
+    if let (Some(panel), Some(metrics)) = (panel(), &view.latest) {
+        panel.set_inner_html(&render(metrics, view));
+    }
+}
+
+fn render(metrics: &LiveMetrics, view: &LiveView) -> String {
+    let counter = |label: &str, rate: f64, total: u64| {
+        format!(
+            r#"<div class="live-counter"><span class="live-rate">{}</span><span class="live-label">{}/sec</span><span class="job-meta">{} total</span></div>"#,
+            format::format_number(rate, 1),
+            label,
+            format::format_number(total as f64, 0)
+        )
+    };
+    let queues = &metrics.queues;
+    let lock = match (queues.lock_writer, queues.lock_readers) {
+        (true, _) => "held by a writer".to_string(),
+        (false, 0) => "free".to_string(),
+        (false, readers) => format!("{} reader{}", readers, if readers == 1 { "" } else { "s" }),
+    };
+
+    let samples = view.history.len();
+    let points: Vec<Datum> = view
+        .history
+        .iter()
+        .enumerate()
+        .map(|(i, rate)| {
+            let seconds_ago = samples - 1 - i;
+            let tooltip = format!("{}s ago: {} reviews/sec", seconds_ago, format::format_number(*rate, 1));
+            Datum::new(format!("-{}s", seconds_ago), *rate, tooltip)
+        })
+        .collect();
+
+    let uploads = match view.uploads.is_empty() {
+        true => r#"<p class="history-empty">No bulk uploads finished since this page opened.</p>"#.to_string(),
+        false => format!(
+            r#"<ul class="live-list">{}</ul>"#,
+            view.uploads
+                .iter()
+                .map(|upload| format!(
+                    "<li>{} · {} stored, {} failed</li>",
+                    format::format_timestamp(&upload.finished_at),
+                    upload.successful,
+                    upload.failed
+                ))
+                .collect::<String>()
+        ),
+    };
+    let errors = match metrics.recent_errors.is_empty() {
+        true => r#"<p class="history-empty">No errors since the backend started.</p>"#.to_string(),
+        false => format!(
+            r#"<ul class="live-list live-errors">{}</ul>"#,
+            metrics
+                .recent_errors
+                .iter()
+                .take(SHOWN_ERRORS)
+                .map(|error| format!(
+                    "<li>{} · {} {} {} {}</li>",
+                    format::format_timestamp(&error.at),
+                    error.status,
+                    format::escape_html(&error.method),
+                    format::escape_html(&error.path),
+                    format::escape_html(error.code.as_deref().unwrap_or(""))
+                ))
+                .collect::<String>()
+        ),
+    };
+
+    format!(
+        r#"<h3>Live Activity</h3>
+           <div class="live-counters">{}{}{}</div>
+           {}
+           <p class="job-meta">Embedding queue: {} new, {} re-embedding · Uploads in progress: {} · Data lock: {} · Updated {}</p>
+           <h4>Recent bulk uploads</h4>
+           {}
+           <h4>Recent errors</h4>
+           {}"#,
+        counter("reviews", metrics.reviews_per_sec, metrics.reviews_stored),
+        counter("searches", metrics.searches_per_sec, metrics.searches),
+        counter("errors", metrics.errors_per_sec, metrics.errors),
+        chart::line_chart("Reviews stored per second over the last minute", &points),
+        queues.embedding_interactive,
+        queues.embedding_backfill,
+        queues.uploads_in_progress,
+        lock,
+        format::format_timestamp(&metrics.sampled_at),
+        uploads,
+        errors
+    )
+}
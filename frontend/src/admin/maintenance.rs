@@ -0,0 +1,261 @@
+use serde::Deserialize;
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event};
+
+use crate::{api_error, describe_error, format, make_api_request};
+
+const PANEL_ID: &str = "maintenance";
+
+/// How often running jobs are re-fetched
+const POLL_INTERVAL_MS: u32 = 2000;
+
+/// Compaction jobs shown, newest first
+const RECENT_JOBS: u32 = 3;
+
+#[derive(Deserialize)]
+struct JobProgress {
+    processed: u32,
+    total: u32,
+    throughput_per_sec: Option<f64>,
+    eta_secs: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct JobRecord {
+    id: String,
+    status: String,
+    created_at: String,
+    successful: u32,
+    total_processed: u32,
+    progress: Option<JobProgress>,
+    error: Option<String>,
+}
+
+impl JobRecord {
+    fn is_active(&self) -> bool {
+        self.status == "running" || self.status == "paused"
+    }
+}
+
+#[derive(Deserialize)]
+struct ListJobsResponse {
+    jobs: Vec<JobRecord>,
+}
+
+thread_local! {
+    static POLLING: Cell<bool> = const { Cell::new(false) };
+}
+
+async fn fetch_compactions() -> Result<ListJobsResponse, JsValue> {
+    let endpoint = format!("/jobs?type=compaction&page_size={}", RECENT_JOBS);
+    let response = make_api_request("GET", &endpoint, None).await?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// POST a job action: start a compaction, pause or resume
+async fn post_action(endpoint: &str) -> Result<(), JsValue> {
+    let response = make_api_request("POST", endpoint, None).await?;
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    Ok(())
+}
+
+/// Load and render recent compactions, polling while one is in progress
+pub async fn refresh() {
+    let Some(panel) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID)) else {
+        return;
+    };
+
+    match fetch_compactions().await {
+        Ok(response) => {
+            panel.set_inner_html(&render(&response.jobs));
+            if response.jobs.iter().any(JobRecord::is_active) {
+                start_polling();
+            }
+        }
+        Err(error) => {
+            console::error_1(&format!("Failed to load maintenance jobs: {:?}", error).into());
+            panel.set_inner_html(&render_with_status(&[], "Job status is unavailable."));
+        }
+    }
+}
+
+/// Keep refreshing until no job is running; only one poll loop runs at a time
+fn start_polling() {
+    if POLLING.with(|polling| polling.replace(true)) {
+        return;
+    }
+
+    wasm_bindgen_futures::spawn_local(async {
+        loop {
+            sleep(POLL_INTERVAL_MS).await;
+            let still_active = fetch_compactions()
+                .await
+                .map(|response| {
+                    let active = response.jobs.iter().any(JobRecord::is_active);
+                    if let Some(panel) =
+                        window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID))
+                    {
+                        panel.set_inner_html(&render(&response.jobs));
+                    }
+                    active
+                })
+                .unwrap_or(false);
+            if !still_active {
+                break;
+            }
+        }
+        POLLING.with(|polling| polling.set(false));
+    });
+}
+
+/// Resolve after `ms` milliseconds
+async fn sleep(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        if let Some(window) = window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+fn render(jobs: &[JobRecord]) -> String {
+    render_with_status(jobs, "")
+}
+
+fn render_with_status(jobs: &[JobRecord], status: &str) -> String {
+    let busy = jobs.iter().any(JobRecord::is_active);
+    let rows: String = if jobs.is_empty() {
+        r#"<p class="history-empty">No compactions yet.</p>"#.to_string()
+    } else {
+        jobs.iter().map(render_job).collect()
+    };
+
+    format!(
+        r#"<h3>Maintenance</h3>
+           <p class="maintenance-help">Compaction removes deleted reviews from storage and renumbers the rest.</p>
+           <button type="button" class="compact-btn" data-action="compact" {}>🧹 Compact reviews</button>
+           <p class="maintenance-status">{}</p>
+           {}"#,
+        if busy { "disabled" } else { "" },
+        format::escape_html(status),
+        rows
+    )
+}
+
+fn render_job(job: &JobRecord) -> String {
+    let (processed, total) = match &job.progress {
+        Some(progress) => (progress.processed, progress.total),
+        None => (job.total_processed, job.total_processed),
+    };
+    let share = if total == 0 { 1.0 } else { processed as f64 / total as f64 };
+
+    let rate = job
+        .progress
+        .as_ref()
+        .and_then(|progress| progress.throughput_per_sec)
+        .map(|rate| format!("{} reviews/s", format::format_number(rate, 0)))
+        .unwrap_or_default();
+    let eta = match (job.status.as_str(), job.progress.as_ref().and_then(|progress| progress.eta_secs)) {
+        ("running", Some(eta)) if eta > 0.0 => format!("done {}", format::format_relative(eta)),
+        _ => String::new(),
+    };
+
+    let job_id = format::escape_html(&job.id);
+    let (label, action) = match job.status.as_str() {
+        "running" => (
+            "Running".to_string(),
+            format!(r#"<button type="button" class="job-btn" data-action="pause" data-job-id="{}">Pause</button>"#, job_id),
+        ),
+        "paused" => (
+            "Paused".to_string(),
+            format!(r#"<button type="button" class="job-btn" data-action="resume" data-job-id="{}">Resume</button>"#, job_id),
+        ),
+        "completed" => (
+            format!(
+                "Completed: kept {} of {}",
+                format::format_number(job.successful as f64, 0),
+                format::format_number(job.total_processed as f64, 0)
+            ),
+            String::new(),
+        ),
+        "failed" => (
+            format!("Failed: {}", job.error.as_deref().unwrap_or("unknown error")),
+            String::new(),
+        ),
+        other => (other.to_string(), String::new()),
+    };
+
+    format!(
+        r#"<div class="job-row {}">
+               <div class="job-header"><span>{}</span><span class="history-status">{}</span>{}</div>
+               <progress max="{}" value="{}"></progress>
+               <div class="job-meta">{} of {} ({}) {} {}</div>
+           </div>"#,
+        format::escape_html(&job.status),
+        format::format_timestamp(&job.created_at),
+        format::escape_html(&label),
+        action,
+        total.max(1),
+        processed,
+        format::format_number(processed as f64, 0),
+        format::format_number(total as f64, 0),
+        format::format_percent(share, 0),
+        rate,
+        eta
+    )
+}
+
+/// Handle compact, pause and resume clicks on the panel
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        let Some(action) = target.get_attribute("data-action") else {
+            return;
+        };
+
+        let endpoint = match (action.as_str(), target.get_attribute("data-job-id")) {
+            ("compact", _) => "/jobs/compaction".to_string(),
+            ("pause" | "resume", Some(job_id)) => {
+                format!("/jobs/{}/{}", js_sys::encode_uri_component(&job_id), action)
+            }
+            _ => return,
+        };
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(error) = post_action(&endpoint).await {
+                console::error_1(&format!("Job action {} failed: {:?}", endpoint, error).into());
+                let message = describe_error(&error, "❌ The job action failed.");
+                if let Some(status) = window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.query_selector(&format!("#{} .maintenance-status", PANEL_ID)).ok().flatten())
+                {
+                    // describe_error escapes the backend message
+                    status.set_inner_html(&message);
+                }
+                return;
+            }
+            refresh().await;
+        });
+    }) as Box<dyn FnMut(_)>);
+
+    panel.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Operator-only features, compiled only with the `admin` cargo feature so the
+//! public search bundle stays small. Build with `wasm-pack build -- --features admin`.
+
+use wasm_bindgen::prelude::*;
+
+pub mod blocklist;
+pub mod curation;
+pub mod embedding_map;
+pub mod live;
+pub mod maintenance;
+pub mod trends;
+pub mod upload_history;
+
+/// Markup for the admin section appended below the public UI
+pub fn section_html() -> String {
+    r#"
+                <div class="section admin-section">
+                    <h2>Admin</h2>
+                    <div class="component-placeholder">
+                        <div id="live-activity" class="live-activity"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="upload-history" class="upload-history"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="maintenance" class="maintenance"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="curation" class="curation"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="blocklist" class="blocklist"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="embedding-map" class="embedding-map"></div>
+                    </div>
+                    <div class="component-placeholder">
+                        <div id="review-trends" class="review-trends"></div>
+                    </div>
+                </div>
+    "#
+    .to_string()
+}
+
+/// Attach admin listeners and load initial data
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    live::attach(document)?;
+    upload_history::attach(document)?;
+    maintenance::attach(document)?;
+    curation::attach(document)?;
+    blocklist::attach(document)?;
+    embedding_map::attach(document)?;
+    trends::attach(document)?;
+    wasm_bindgen_futures::spawn_local(upload_history::refresh(1));
+    wasm_bindgen_futures::spawn_local(maintenance::refresh());
+    wasm_bindgen_futures::spawn_local(curation::refresh());
+    wasm_bindgen_futures::spawn_local(blocklist::refresh());
+    wasm_bindgen_futures::spawn_local(embedding_map::refresh());
+    wasm_bindgen_futures::spawn_local(trends::refresh());
+    listen_for_uploads()
+}
+
+/// Refresh the upload history when any client finishes a bulk upload. The browser
+/// reconnects the event stream on its own if the backend restarts.
+fn listen_for_uploads() -> Result<(), JsValue> {
+    let events = web_sys::EventSource::new(&format!("{}/events", crate::API_BASE_URL))?;
+
+    let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        live::record_event(&event);
+        on_data_changed();
+    }) as Box<dyn FnMut(_)>);
+    // A lagged stream may have missed an upload, so refresh then too
+    for event_name in ["bulk_upload_completed", "lagged"] {
+        events.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())?;
+    }
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
+
+/// Refresh admin views after data changes elsewhere in the app
+pub fn on_data_changed() {
+    wasm_bindgen_futures::spawn_local(upload_history::refresh(1));
+    wasm_bindgen_futures::spawn_local(trends::refresh());
+}
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event};
+
+use crate::chart::{self, Datum};
+use crate::{api_error, format, make_api_request};
+
+const PANEL_ID: &str = "review-trends";
+
+#[derive(Deserialize)]
+struct DailyCount {
+    date: String,
+    count: usize,
+}
+
+#[derive(Deserialize)]
+struct ServiceStats {
+    total_reviews: usize,
+    total_products: usize,
+    reviews_per_day: Vec<DailyCount>,
+}
+
+#[derive(Deserialize)]
+struct StatsResponse {
+    stats: ServiceStats,
+}
+
+async fn fetch_stats() -> Result<StatsResponse, JsValue> {
+    let response = make_api_request("GET", "/stats", None).await?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn panel() -> Option<Element> {
+    window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(PANEL_ID))
+}
+
+/// Redraw the reviews-per-day trend
+pub async fn refresh() {
+    let Some(panel) = panel() else {
+        return;
+    };
+
+    match fetch_stats().await {
+        Ok(response) => panel.set_inner_html(&render(&response.stats)),
+        Err(error) => {
+            console::error_1(&format!("Failed to load service stats: {:?}", error).into());
+            panel.set_inner_html(
+                r#"<h3>Review Trends</h3>
+                   <p class="history-empty">Service statistics are unavailable.</p>
+                   <button type="button" class="job-btn" data-action="refresh">Retry</button>"#,
+            );
+        }
+    }
+}
+
+fn render(stats: &ServiceStats) -> String {
+    let days = stats.reviews_per_day.len();
+    let recent: usize = stats.reviews_per_day.iter().map(|day| day.count).sum();
+    let points: Vec<Datum> = stats
+        .reviews_per_day
+        .iter()
+        .map(|day| {
+            // Dates are YYYY-MM-DD; the axis only has room for MM-DD
+            let label = day.date.get(5..).unwrap_or(&day.date);
+            Datum::new(
+                label,
+                day.count as f64,
+                format!(
+                    "{}: {} review{}",
+                    format::format_date(&day.date),
+                    format::format_number(day.count as f64, 0),
+                    if day.count == 1 { "" } else { "s" }
+                ),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<h3>Review Trends</h3>
+           {}
+           <p class="job-meta">{} reviews across {} products; {} in the last {} days. Hover a point for its day.
+           <button type="button" class="job-btn" data-action="refresh">Refresh</button></p>"#,
+        chart::line_chart(&format!("Reviews created per day over the last {} days", days), &points),
+        format::format_number(stats.total_reviews as f64, 0),
+        format::format_number(stats.total_products as f64, 0),
+        format::format_number(recent as f64, 0),
+        days
+    )
+}
+
+/// Handle the refresh button
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(trends_panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let is_refresh = event
+            .target()
+            .and_then(|t| t.dyn_into::<Element>().ok())
+            .is_some_and(|target| target.get_attribute("data-action").as_deref() == Some("refresh"));
+        if is_refresh {
+            wasm_bindgen_futures::spawn_local(refresh());
+        }
+    }) as Box<dyn FnMut(_)>);
+    trends_panel.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event};
+
+use crate::{format, make_api_request, modal};
+
+const PAGE_SIZE: u32 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct JobRecord {
+    id: String,
+    status: String,
+    created_at: String,
+    file_name: Option<String>,
+    total_processed: u32,
+    successful: u32,
+    failed: u32,
+    batch_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ListJobsResponse {
+    success: bool,
+    jobs: Vec<JobRecord>,
+    page: u32,
+    page_size: u32,
+    total_jobs: u32,
+    total_pages: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RollbackResponse {
+    success: bool,
+    message: String,
+}
+
+/// Fetch one page of past bulk uploads, newest first
+async fn fetch_jobs(page: u32) -> Result<ListJobsResponse, JsValue> {
+    let endpoint = format!("/jobs?type=bulk_upload&page={}&page_size={}", page, PAGE_SIZE);
+    let response = make_api_request("GET", &endpoint, None).await?;
+
+    if !response.ok() {
+        let error_text = JsFuture::from(response.text()?).await?;
+        return Err(JsValue::from_str(&format!("API Error: {}", error_text.as_string().unwrap_or_default())));
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Roll back the reviews created by a bulk upload job
+async fn rollback_job(job_id: &str) -> Result<RollbackResponse, JsValue> {
+    let endpoint = format!("/jobs/{}/rollback", js_sys::encode_uri_component(job_id));
+    let response = make_api_request("POST", &endpoint, None).await?;
+
+    if !response.ok() {
+        let error_text = JsFuture::from(response.text()?).await?;
+        return Err(JsValue::from_str(&format!("API Error: {}", error_text.as_string().unwrap_or_default())));
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Load and render a page of the upload history into `#upload-history`
+pub async fn refresh(page: u32) {
+    let Some(panel) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id("upload-history")) else {
+        return;
+    };
+
+    match fetch_jobs(page).await {
+        Ok(response) => panel.set_inner_html(&render(&response)),
+        Err(error) => {
+            console::error_1(&format!("Failed to load upload history: {:?}", error).into());
+            panel.set_inner_html(r#"<p class="history-empty">Upload history is unavailable.</p>"#);
+        }
+    }
+}
+
+fn render(response: &ListJobsResponse) -> String {
+    if response.jobs.is_empty() {
+        return r#"<h3>Upload History</h3><p class="history-empty">No uploads yet.</p>"#.to_string();
+    }
+
+    let rows: String = response.jobs.iter().map(render_job).collect();
+
+    let previous = if response.page > 1 {
+        format!(r#"<button type="button" class="history-page" data-page="{}">‹ Newer</button>"#, response.page - 1)
+    } else {
+        String::new()
+    };
+    let next = if response.page < response.total_pages {
+        format!(r#"<button type="button" class="history-page" data-page="{}">Older ›</button>"#, response.page + 1)
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"<h3>Upload History</h3>
+           <table class="history-table">
+               <thead><tr><th>File</th><th>Uploaded</th><th>Added</th><th>Failed</th><th>Batch</th><th></th></tr></thead>
+               <tbody>{}</tbody>
+           </table>
+           <div class="history-pagination">{}<span>Page {} of {}</span>{}</div>"#,
+        rows,
+        previous,
+        response.page,
+        response.total_pages.max(1),
+        next
+    )
+}
+
+fn render_job(job: &JobRecord) -> String {
+    let action = match (&job.batch_id, job.status.as_str()) {
+        (_, "rolled_back") => r#"<span class="history-status">Rolled back</span>"#.to_string(),
+        (Some(_), _) => format!(
+            r#"<button type="button" class="rollback-btn" data-job-id="{}">Roll back</button>"#,
+            format::escape_html(&job.id)
+        ),
+        (None, _) => String::new(),
+    };
+
+    format!(
+        r#"<tr class="history-row {}">
+               <td>{}</td><td>{}</td><td>{} of {}</td><td>{}</td><td><code>{}</code></td><td>{}</td>
+           </tr>"#,
+        format::escape_html(&job.status),
+        format::escape_html(job.file_name.as_deref().unwrap_or("(unnamed)")),
+        format::format_timestamp(&job.created_at),
+        format::format_number(job.successful as f64, 0),
+        format::format_number(job.total_processed as f64, 0),
+        format::format_number(job.failed as f64, 0),
+        format::escape_html(job.batch_id.as_deref().map(|id| &id[..id.len().min(8)]).unwrap_or("-")),
+        action
+    )
+}
+
+/// Handle pagination and rollback clicks on the history panel
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(panel) = document.get_element_by_id("upload-history") else {
+        return Ok(());
+    };
+
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+
+        if let Some(page) = target.get_attribute("data-page").and_then(|p| p.parse::<u32>().ok()) {
+            wasm_bindgen_futures::spawn_local(refresh(page));
+            return;
+        }
+
+        let Some(job_id) = target.get_attribute("data-job-id") else {
+            return;
+        };
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let confirmed = modal::confirm(
+                "Roll back upload?",
+                "<p>All reviews added by this upload will be deleted. This cannot be undone.</p>",
+                "Roll back",
+                "Cancel",
+            )
+            .await;
+            if !confirmed {
+                return;
+            }
+
+            match rollback_job(&job_id).await {
+                Ok(response) => {
+                    console::log_1(&format!("Rollback completed: {}", response.message).into());
+                    crate::show_message("upload-status", &format!("✅ {}", response.message), false);
+                }
+                Err(error) => {
+                    console::error_1(&format!("Rollback failed: {:?}", error).into());
+                    crate::show_message("upload-status", "❌ Failed to roll back upload.", true);
+                }
+            }
+
+            refresh(1).await;
+        });
+    }) as Box<dyn FnMut(_)>);
+
+    panel.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Small SVG charts rendered as markup, so pages can plot without a JS chart library.
+//!
+//! Every bar and point carries a `<title>`, which browsers show as a tooltip on hover
+//! and screen readers read out. Labels and tooltips are escaped here.
+
+use crate::format;
+
+/// Drawing area of every chart, in SVG units; charts scale to their container
+const WIDTH: f64 = 360.0;
+const HEIGHT: f64 = 180.0;
+
+/// Room left of the plot for the value axis and below it for category labels
+const LEFT_GUTTER: f64 = 32.0;
+const BOTTOM_GUTTER: f64 = 22.0;
+const TOP_GUTTER: f64 = 8.0;
+
+/// One bar or point: its axis label, value and hover text
+pub struct Datum {
+    pub label: String,
+    pub value: f64,
+    pub tooltip: String,
+}
+
+impl Datum {
+    pub fn new(label: impl Into<String>, value: f64, tooltip: impl Into<String>) -> Self {
+        Datum {
+            label: label.into(),
+            value,
+            tooltip: tooltip.into(),
+        }
+    }
+}
+
+/// Vertical bars, one per datum, from zero up to the largest value
+pub fn bar_chart(aria_label: &str, data: &[Datum]) -> String {
+    let max = axis_max(data);
+    let slot = plot_width() / data.len().max(1) as f64;
+    let bar_width = slot * 0.7;
+
+    let bars: String = data
+        .iter()
+        .enumerate()
+        .map(|(i, datum)| {
+            let x = LEFT_GUTTER + i as f64 * slot + (slot - bar_width) / 2.0;
+            let height = datum.value.max(0.0) / max * plot_height();
+            format!(
+                r#"<rect class="chart-bar" x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}"><title>{}</title></rect>"#,
+                x,
+                baseline() - height,
+                bar_width,
+                height,
+                format::escape_html(&datum.tooltip)
+            )
+        })
+        .collect();
+
+    svg(aria_label, max, &format!("{}{}", bars, category_labels(data, slot)))
+}
+
+/// A line through the data in order, with a hoverable marker on each point
+#[cfg_attr(not(feature = "admin"), allow(dead_code))] // Trends are admin-only for now
+pub fn line_chart(aria_label: &str, data: &[Datum]) -> String {
+    let max = axis_max(data);
+    // Points sit at the centre of equal slots, like bars, so labels line up the same way
+    let slot = plot_width() / data.len().max(1) as f64;
+    let position = |i: usize, value: f64| {
+        (
+            LEFT_GUTTER + (i as f64 + 0.5) * slot,
+            baseline() - value.max(0.0) / max * plot_height(),
+        )
+    };
+
+    let path: Vec<String> = data
+        .iter()
+        .enumerate()
+        .map(|(i, datum)| {
+            let (x, y) = position(i, datum.value);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    let markers: String = data
+        .iter()
+        .enumerate()
+        .map(|(i, datum)| {
+            let (x, y) = position(i, datum.value);
+            format!(
+                r#"<circle class="chart-point" cx="{:.1}" cy="{:.1}" r="3"><title>{}</title></circle>"#,
+                x,
+                y,
+                format::escape_html(&datum.tooltip)
+            )
+        })
+        .collect();
+
+    svg(
+        aria_label,
+        max,
+        &format!(
+            r#"<polyline class="chart-line" points="{}"/>{}{}"#,
+            path.join(" "),
+            markers,
+            category_labels(data, slot)
+        ),
+    )
+}
+
+fn plot_width() -> f64 {
+    WIDTH - LEFT_GUTTER
+}
+
+fn plot_height() -> f64 {
+    HEIGHT - BOTTOM_GUTTER - TOP_GUTTER
+}
+
+fn baseline() -> f64 {
+    HEIGHT - BOTTOM_GUTTER
+}
+
+/// Top of the value axis: the largest value, or 1 so an all-zero chart still draws
+fn axis_max(data: &[Datum]) -> f64 {
+    data.iter().map(|datum| datum.value).fold(0.0, f64::max).max(1.0)
+}
+
+/// Category labels under the plot, thinned out so at most about eight are shown
+fn category_labels(data: &[Datum], slot: f64) -> String {
+    let every = data.len().div_ceil(8).max(1);
+    data.iter()
+        .enumerate()
+        .filter(|(i, _)| i % every == 0)
+        .map(|(i, datum)| {
+            format!(
+                r#"<text class="chart-label" x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+                LEFT_GUTTER + (i as f64 + 0.5) * slot,
+                HEIGHT - 6.0,
+                format::escape_html(&datum.label)
+            )
+        })
+        .collect()
+}
+
+/// The chart frame: value axis with its top and zero marks around the plotted content
+fn svg(aria_label: &str, max: f64, content: &str) -> String {
+    format!(
+        r#"<svg class="chart" viewBox="0 0 {width} {height}" role="img" aria-label="{}">
+               <line class="chart-axis" x1="{left}" y1="{top}" x2="{left}" y2="{base}"/>
+               <line class="chart-axis" x1="{left}" y1="{base}" x2="{width}" y2="{base}"/>
+               <text class="chart-label" x="{tick}" y="{top_label}" text-anchor="end">{}</text>
+               <text class="chart-label" x="{tick}" y="{base}" text-anchor="end">0</text>
+               {}
+           </svg>"#,
+        format::escape_html(aria_label),
+        format_value(max),
+        content,
+        width = WIDTH,
+        height = HEIGHT,
+        left = LEFT_GUTTER,
+        top = TOP_GUTTER,
+        base = baseline(),
+        tick = LEFT_GUTTER - 4.0,
+        top_label = TOP_GUTTER + 8.0
+    )
+}
+
+/// Axis values as whole numbers when they are, else to one decimal
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
@@ -0,0 +1,149 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Document, Event};
+
+use crate::{check_health, format, offline, API_BASE_URL};
+
+/// How often the backend health endpoint is probed
+const PROBE_INTERVAL_MS: i32 = 30_000;
+
+const BANNER_ID: &str = "connectivity-banner";
+
+/// Backend reachability as last observed
+#[derive(Clone, Debug, PartialEq)]
+enum Connectivity {
+    Healthy,
+    /// The browser itself has no network
+    Offline,
+    /// The health endpoint could not be reached
+    Unreachable,
+    /// The backend answered but reported failing readiness checks
+    Degraded(Vec<String>),
+}
+
+#[derive(Default)]
+struct BannerState {
+    current: Option<Connectivity>,
+    /// The state the user dismissed; the banner stays hidden until the state changes
+    dismissed: Option<Connectivity>,
+}
+
+thread_local! {
+    static BANNER_STATE: RefCell<BannerState> = RefCell::new(BannerState::default());
+}
+
+/// Banner container markup; filled in and shown only when there is a problem
+pub fn banner_html() -> String {
+    format!(r#"<div id="{}" class="connectivity-banner" role="status" hidden></div>"#, BANNER_ID)
+}
+
+/// Probe the backend now, every `PROBE_INTERVAL_MS`, and whenever the browser
+/// goes on- or offline, keeping the banner in step with the result
+pub fn start(document: &Document) -> Result<(), JsValue> {
+    let window = window().ok_or("No global window exists")?;
+
+    let probe = Closure::wrap(Box::new(move || {
+        wasm_bindgen_futures::spawn_local(probe_backend());
+    }) as Box<dyn FnMut()>);
+    window.set_interval_with_callback_and_timeout_and_arguments_0(probe.as_ref().unchecked_ref(), PROBE_INTERVAL_MS)?;
+    for event_name in ["online", "offline"] {
+        window.add_event_listener_with_callback(event_name, probe.as_ref().unchecked_ref())?;
+    }
+    probe.forget(); // Keep the closure alive
+
+    if let Some(banner) = document.get_element_by_id(BANNER_ID) {
+        let on_click = Closure::wrap(Box::new(move |event: Event| {
+            let is_dismiss = event
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .is_some_and(|element| element.has_attribute("data-dismiss"));
+            if is_dismiss {
+                BANNER_STATE.with(|state| {
+                    let mut state = state.borrow_mut();
+                    state.dismissed = state.current.clone();
+                });
+                render();
+            }
+        }) as Box<dyn FnMut(_)>);
+        banner.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+        on_click.forget(); // Keep the closure alive
+    }
+
+    wasm_bindgen_futures::spawn_local(probe_backend());
+    Ok(())
+}
+
+/// Check the health endpoint once and update the banner
+async fn probe_backend() {
+    let connectivity = if offline::is_offline() {
+        Connectivity::Offline
+    } else {
+        match check_health().await {
+            Ok(health) if health.status == "healthy" => Connectivity::Healthy,
+            Ok(health) => Connectivity::Degraded(health.failing_checks()),
+            Err(_) => Connectivity::Unreachable,
+        }
+    };
+
+    BANNER_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.dismissed.as_ref().is_some_and(|dismissed| *dismissed != connectivity) {
+            state.dismissed = None;
+        }
+        state.current = Some(connectivity);
+    });
+    render();
+}
+
+/// Guidance shown for a problem state, or `None` when everything is fine
+fn message(connectivity: &Connectivity) -> Option<String> {
+    match connectivity {
+        Connectivity::Healthy => None,
+        Connectivity::Offline => Some(
+            "📴 You are offline. Searches show your last saved results, and new reviews are queued until you reconnect."
+                .to_string(),
+        ),
+        Connectivity::Unreachable => Some(format!(
+            "⚠️ Cannot reach the search service at <code>{}</code>. Check that the backend is running \
+             (<code>docker compose up backend</code>) and that this address is reachable from your browser. \
+             Retrying every {} seconds.",
+            format::escape_html(API_BASE_URL),
+            PROBE_INTERVAL_MS / 1000
+        )),
+        Connectivity::Degraded(checks) => Some(format!(
+            "⚠️ The search service is running but not ready ({}). Adding and uploading reviews may fail; \
+             check the backend logs and that its data directory is writable.",
+            format::escape_html(&checks.join("; "))
+        )),
+    }
+}
+
+fn render() {
+    let Some(banner) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(BANNER_ID))
+    else {
+        return;
+    };
+
+    let visible_message = BANNER_STATE.with(|state| {
+        let state = state.borrow();
+        match &state.current {
+            Some(current) if state.dismissed.as_ref() != Some(current) => message(current),
+            _ => None,
+        }
+    });
+
+    match visible_message {
+        Some(message) => {
+            banner.set_inner_html(&format!(
+                r#"<span class="banner-text">{}</span><button type="button" class="banner-dismiss" data-dismiss aria-label="Dismiss">✕</button>"#,
+                message
+            ));
+            let _ = banner.remove_attribute("hidden");
+        }
+        None => {
+            let _ = banner.set_attribute("hidden", "");
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use js_sys::{Array, Date, Intl, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+/// Escape text for safe insertion into HTML markup
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Relative-time buckets, largest first: (unit name for Intl, seconds per unit)
+const RELATIVE_UNITS: [(&str, f64); 7] = [
+    ("year", 365.0 * 24.0 * 3600.0),
+    ("month", 30.0 * 24.0 * 3600.0),
+    ("week", 7.0 * 24.0 * 3600.0),
+    ("day", 24.0 * 3600.0),
+    ("hour", 3600.0),
+    ("minute", 60.0),
+    ("second", 1.0),
+];
+
+/// Render an RFC3339 timestamp as a `<time>` element showing relative time
+/// ("3 days ago") with the exact local date and time available on hover.
+///
+/// Falls back to the raw string if the browser cannot parse the timestamp.
+pub fn format_timestamp(timestamp: &str) -> String {
+    let date = Date::new(&JsValue::from_str(timestamp));
+    let millis = date.get_time();
+
+    if millis.is_nan() {
+        return format!(r#"<time class="timestamp">{}</time>"#, timestamp);
+    }
+
+    let delta_seconds = (millis - Date::now()) / 1000.0;
+
+    format!(
+        r#"<time class="timestamp" datetime="{}" title="{}">{}</time>"#,
+        timestamp,
+        format_exact(&date),
+        format_relative(delta_seconds)
+    )
+}
+
+/// Exact date and time in the user's locale and timezone, including the zone name
+pub fn format_exact(date: &Date) -> String {
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"dateStyle".into(), &"medium".into());
+    let _ = Reflect::set(&options, &"timeStyle".into(), &"long".into());
+
+    String::from(date.to_locale_string("default", &options))
+}
+
+/// Locale-aware relative time for a signed offset in seconds (negative = past)
+pub fn format_relative(delta_seconds: f64) -> String {
+    let (value, unit) = relative_unit(delta_seconds);
+
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"numeric".into(), &"auto".into());
+    let formatter = Intl::RelativeTimeFormat::new(&Array::new(), &options);
+
+    String::from(formatter.format(value, unit))
+}
+
+/// A number in the user's locale, e.g. "12,345.6", with at most `max_fraction_digits` decimals
+pub fn format_number(value: f64, max_fraction_digits: u32) -> String {
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"maximumFractionDigits".into(), &max_fraction_digits.into());
+    format_with(value, &options)
+}
+
+/// A 0-1 fraction as a percentage in the user's locale, e.g. "87.5%"
+pub fn format_percent(fraction: f64, max_fraction_digits: u32) -> String {
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"style".into(), &"percent".into());
+    let _ = Reflect::set(&options, &"maximumFractionDigits".into(), &max_fraction_digits.into());
+    format_with(fraction, &options)
+}
+
+/// The date of an RFC3339 timestamp or `YYYY-MM-DD` day in the user's locale,
+/// falling back to the raw string if the browser cannot parse it
+pub fn format_date(date: &str) -> String {
+    let parsed = Date::new(&JsValue::from_str(date));
+    if parsed.get_time().is_nan() {
+        return date.to_string();
+    }
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"dateStyle".into(), &"medium".into());
+    // A bare day is midnight UTC, which is the previous day west of Greenwich
+    if date.len() == 10 {
+        let _ = Reflect::set(&options, &"timeZone".into(), &"UTC".into());
+    }
+    String::from(parsed.to_locale_date_string("default", &options))
+}
+
+fn format_with(value: f64, options: &Object) -> String {
+    let formatter = Intl::NumberFormat::new(&Array::new(), options);
+    formatter
+        .format()
+        .call1(&JsValue::NULL, &JsValue::from_f64(value))
+        .ok()
+        .and_then(|formatted| formatted.as_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Pick the largest unit the offset spans at least once, rounded toward zero
+fn relative_unit(delta_seconds: f64) -> (f64, &'static str) {
+    let magnitude = delta_seconds.abs();
+
+    for (unit, unit_seconds) in RELATIVE_UNITS {
+        if magnitude >= unit_seconds {
+            return ((delta_seconds / unit_seconds).trunc(), unit);
+        }
+    }
+
+    (0.0, "second")
+}
@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::{console, window, Document, Event};
+
+thread_local! {
+    /// The deferred `beforeinstallprompt` event, kept until the user asks to install
+    static DEFERRED_PROMPT: RefCell<Option<Event>> = const { RefCell::new(None) };
+}
+
+/// Install button markup; hidden until the browser offers installation
+pub fn button_html(button_id: &str) -> String {
+    format!(r#"<button type="button" id="{}" class="install-btn" hidden>📲 Install app</button>"#, button_id)
+}
+
+/// Wire the install button to the browser's install prompt.
+/// Browsers without PWA install support never fire the event, so the button stays hidden.
+pub fn attach(document: &Document, button_id: &str) -> Result<(), JsValue> {
+    let window = window().ok_or("No global window exists")?;
+
+    let id = button_id.to_string();
+    let on_before_install = Closure::wrap(Box::new(move |event: Event| {
+        // Suppress the mini-infobar and offer our own button instead
+        event.prevent_default();
+        DEFERRED_PROMPT.with(|prompt| *prompt.borrow_mut() = Some(event));
+        set_button_hidden(&id, false);
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("beforeinstallprompt", on_before_install.as_ref().unchecked_ref())?;
+    on_before_install.forget(); // Keep the closure alive
+
+    let id = button_id.to_string();
+    let on_installed = Closure::wrap(Box::new(move |_event: Event| {
+        console::log_1(&"✅ App installed".into());
+        DEFERRED_PROMPT.with(|prompt| prompt.borrow_mut().take());
+        set_button_hidden(&id, true);
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("appinstalled", on_installed.as_ref().unchecked_ref())?;
+    on_installed.forget(); // Keep the closure alive
+
+    if let Some(button) = document.get_element_by_id(button_id) {
+        let id = button_id.to_string();
+        let on_click = Closure::wrap(Box::new(move |_event: Event| {
+            // A deferred prompt can only be shown once
+            let Some(event) = DEFERRED_PROMPT.with(|prompt| prompt.borrow_mut().take()) else {
+                return;
+            };
+            set_button_hidden(&id, true);
+
+            // BeforeInstallPromptEvent is not exposed by web-sys, so call prompt() reflectively
+            let prompt = js_sys::Reflect::get(&event, &"prompt".into())
+                .ok()
+                .and_then(|value| value.dyn_into::<js_sys::Function>().ok());
+            if let Some(prompt) = prompt {
+                if let Err(error) = prompt.call0(&event) {
+                    console::warn_1(&format!("Install prompt failed: {:?}", error).into());
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+        button.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+        on_click.forget(); // Keep the closure alive
+    }
+
+    Ok(())
+}
+
+fn set_button_hidden(button_id: &str, hidden: bool) {
+    if let Some(button) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(button_id))
+    {
+        if hidden {
+            let _ = button.set_attribute("hidden", "");
+        } else {
+            let _ = button.remove_attribute("hidden");
+        }
+    }
+}
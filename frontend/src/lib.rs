@@ -3,6 +3,23 @@ use wasm_bindgen_futures::JsFuture;
 use web_sys::{console, window, Request, RequestInit, RequestMode, Response, Headers, HtmlInputElement, HtmlTextAreaElement, HtmlSelectElement, FileReader, HtmlFormElement};
 use js_sys::Promise;
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+
+#[cfg(feature = "admin")]
+mod admin;
+mod chart;
+mod connectivity;
+mod format;
+mod install;
+mod modal;
+mod offline;
+mod permalink;
+mod preview;
+mod print;
+mod product;
+mod star_rating;
+mod validation;
+mod virtual_list;
 
 // API Configuration - Use environment variable or fallback to default
 const API_BASE_URL: &str = match option_env!("BACKEND_URL") {
@@ -10,6 +27,25 @@ const API_BASE_URL: &str = match option_env!("BACKEND_URL") {
     None => "http://192.168.1.2:8000",
 };
 
+/// Results fetched per search page; more are fetched as the list is scrolled
+const SEARCH_PAGE_SIZE: u32 = 20;
+
+/// Query behind the current result list, used to fetch further pages
+#[derive(Default)]
+struct SearchState {
+    query: String,
+    filters: Option<SearchFilters>,
+    /// Where the next page starts; `None` once every page is shown
+    next_cursor: Option<String>,
+    loading: bool,
+}
+
+thread_local! {
+    static SEARCH_STATE: RefCell<SearchState> = RefCell::new(SearchState::default());
+    /// Set while queued offline reviews are being submitted
+    static FLUSHING_QUEUE: Cell<bool> = const { Cell::new(false) };
+}
+
 // API Models based on README.md specification
 #[derive(Serialize, Deserialize)]
 struct CreateReviewRequest {
@@ -17,6 +53,25 @@ struct CreateReviewRequest {
     body: String,
     product_id: String,
     rating: u8,
+    reviewer_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HealthResponse {
+    status: String,
+    #[serde(default)]
+    checks: std::collections::BTreeMap<String, String>,
+}
+
+impl HealthResponse {
+    /// Readiness checks that did not report "ok", as "name: detail"
+    fn failing_checks(&self) -> Vec<String> {
+        self.checks
+            .iter()
+            .filter(|(_, result)| result.as_str() != "ok")
+            .map(|(name, result)| format!("{}: {}", name, result))
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,10 +83,43 @@ struct CreateReviewResponse {
     timestamp: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct DuplicateCheckRequest {
+    title: String,
+    body: String,
+    reviewer_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DuplicateCheckResponse {
+    success: bool,
+    is_duplicate: bool,
+    similarity_score: Option<f64>,
+    matching_review: Option<ReviewData>,
+    threshold: f64,
+}
+
 #[derive(Serialize, Deserialize)]
 struct SearchRequest {
     query: String,
     limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filters: Option<SearchFilters>,
+}
+
+/// Restrictions on the reviews a search ranks, from the filter row under the search box
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SearchFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    product_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_rating: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_to: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -41,7 +129,12 @@ struct SearchResponse {
     results: Vec<SearchResult>,
     total_results: u32,
     limit: u32,
+    #[serde(default)]
+    next_cursor: Option<String>,
     search_type: String,
+    /// Set when the backend corrected misspelled words before searching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    corrected_query: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -59,6 +152,24 @@ struct ReviewData {
     rating: u8,
     timestamp: String,
     vector_index: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    attribution: Option<String>,
+}
+
+impl ReviewData {
+    /// Credit line for an imported review: its attribution, or its source when it has none,
+    /// followed by its license
+    fn credit(&self) -> Option<String> {
+        let credit = self.attribution.as_ref().or(self.source.as_ref())?;
+        Some(match &self.license {
+            Some(license) => format!("{} ({})", credit, license),
+            None => credit.clone(),
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,7 +178,9 @@ struct BulkUploadResponse {
     message: String,
     result: BulkUploadResult,
     starting_vector_index: u32,
-    ending_vector_index: u32,
+    ending_vector_index: Option<u32>,
+    job_id: Option<String>,
+    batch_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -84,14 +197,25 @@ struct BulkUploadError {
     data: serde_json::Value,
 }
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize)]
 struct ApiError {
     error: String,
+    /// Stable error code from the backend's error catalog, e.g. "E_RATE_LIMITED"
+    #[serde(default)]
+    code: String,
     message: String,
     details: Option<serde_json::Value>,
     timestamp: String,
 }
 
+impl ApiError {
+    /// The backend error carried by a failed request, if it sent one
+    fn from_js(error: &JsValue) -> Option<Self> {
+        serde_wasm_bindgen::from_value(error.clone()).ok()
+    }
+}
+
 /// Entry point for the WebAssembly module
 /// This function is called from JavaScript to initialize and start the application
 #[wasm_bindgen]
@@ -116,7 +240,10 @@ pub fn main() {
 /// This can be used if automatic initialization is preferred
 #[wasm_bindgen(start)]
 pub fn start() {
-    main();
+    // The module is also loaded by the parse worker, which has no window to mount into
+    if window().is_some() {
+        main();
+    }
 }
 
 /// Create and mount the Semantic Search Platform application
@@ -130,11 +257,13 @@ fn create_app() -> Result<(), JsValue> {
         .ok_or("Should have an element with id 'app'")?;
     
     // Create the main application HTML
-    let app_html = r#"
+    let app_html = format!(r#"
         <div class="home-page">
+            {connectivity_banner}
             <header class="header">
                 <h1>🔍 Semantic Search Platform</h1>
                 <p class="subtitle">Search product reviews using natural language</p>
+                {install_button}
             </header>
             
             <div class="main-content">
@@ -145,21 +274,19 @@ fn create_app() -> Result<(), JsValue> {
                             <div class="form-group">
                                 <label for="product-name">Product Name:</label>
                                 <input type="text" id="product-name" name="product-name" required>
+                                <small class="char-counter" id="product-name-counter"></small>
                             </div>
                             <div class="form-group">
                                 <label for="review-text">Review:</label>
                                 <textarea id="review-text" name="review-text" rows="4" required></textarea>
+                                <div class="field-hints">
+                                    <small class="char-counter" id="review-text-counter"></small>
+                                    <small class="word-counter" id="review-text-words"></small>
+                                </div>
                             </div>
                             <div class="form-group">
-                                <label for="rating">Rating:</label>
-                                <select id="rating" name="rating" required>
-                                    <option value="">Select rating</option>
-                                    <option value="1">1 Star</option>
-                                    <option value="2">2 Stars</option>
-                                    <option value="3">3 Stars</option>
-                                    <option value="4">4 Stars</option>
-                                    <option value="5">5 Stars</option>
-                                </select>
+                                <label for="rating-widget">Rating:</label>
+                                {rating_widget}
                             </div>
                             <button type="submit">Add Review</button>
                         </form>
@@ -170,8 +297,13 @@ fn create_app() -> Result<(), JsValue> {
                     <h2>Bulk Upload</h2>
                     <div class="component-placeholder">
                         <div id="bulk-upload">
-                            <input type="file" id="file-input" accept=".csv,.json" multiple>
+                            <input type="file" id="file-input" accept=".csv,.json,.jsonl" multiple>
                             <button id="upload-btn">Upload Files</button>
+                            <div class="template-links">
+                                <span>Download template:</span>
+                                <a class="template-btn" href="{api_base_url}/reviews/templates/csv" download>CSV</a>
+                                <a class="template-btn" href="{api_base_url}/reviews/templates/jsonl" download>JSONL</a>
+                            </div>
                             <div id="upload-status"></div>
                         </div>
                     </div>
@@ -184,26 +316,87 @@ fn create_app() -> Result<(), JsValue> {
                             <div class="search-form">
                                 <input type="text" id="search-input" placeholder="Search reviews using natural language...">
                                 <button id="search-btn">Search</button>
+                                <button id="copy-link-btn" type="button" class="copy-link-btn" title="Copy a link to this search">🔗 Copy link</button>
+                                <button id="print-btn" type="button" class="copy-link-btn" title="Print or save the loaded results as PDF">🖨 Print results</button>
+                            </div>
+                            <div class="search-filters">
+                                <input type="text" id="filter-product" placeholder="Product ID (optional)">
+                                <select id="filter-min-rating" title="Lowest rating">
+                                    <option value="">Any rating</option>
+                                    <option value="2">2★ and up</option>
+                                    <option value="3">3★ and up</option>
+                                    <option value="4">4★ and up</option>
+                                    <option value="5">5★ only</option>
+                                </select>
+                                <label>From <input type="date" id="filter-date-from"></label>
+                                <label>To <input type="date" id="filter-date-to"></label>
                             </div>
                             <div id="search-results"></div>
                         </div>
                     </div>
                 </div>
+
+                <div class="section">
+                    <h2>Product Ratings</h2>
+                    <div class="component-placeholder">
+                        {product_ratings}
+                    </div>
+                </div>
+                {admin_section}
             </div>
         </div>
-    "#;
+    "#,
+        rating_widget = star_rating::render("rating-widget", "rating", false),
+        api_base_url = API_BASE_URL,
+        product_ratings = product::section_html(),
+        admin_section = admin_section_html(),
+        install_button = install::button_html("install-btn"),
+        connectivity_banner = connectivity::banner_html(),
+    );
     
     // Set the HTML content
-    app_container.set_inner_html(app_html);
+    app_container.set_inner_html(&app_html);
     
     // Add event listeners
+    star_rating::attach(&document, "rating-widget")?;
+    install::attach(&document, "install-btn")?;
+    connectivity::start(&document)?;
     setup_event_listeners(&document)?;
+    product::attach(&document)?;
+    #[cfg(feature = "admin")]
+    admin::attach(&document)?;
+    update_review_form_state(&document);
+    run_linked_search(&document);
+    wasm_bindgen_futures::spawn_local(offline::register_service_worker());
+    wasm_bindgen_futures::spawn_local(flush_queued_reviews());
     
     console::log_1(&"✅ Application HTML created and event listeners attached".into());
     
     Ok(())
 }
 
+/// Run the search carried in the page URL when the app is opened from a shared link
+fn run_linked_search(document: &web_sys::Document) {
+    let Some(query) = permalink::current_query() else {
+        return;
+    };
+    if let Some(input) = document.get_element_by_id("search-input").and_then(|e| e.dyn_into::<HtmlInputElement>().ok()) {
+        input.set_value(&query);
+    }
+    if let Some(button) = document.get_element_by_id("search-btn").and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok()) {
+        button.click();
+    }
+}
+
+/// Admin section markup; empty in the public build
+fn admin_section_html() -> String {
+    #[cfg(feature = "admin")]
+    return admin::section_html();
+    
+    #[cfg(not(feature = "admin"))]
+    String::new()
+}
+
 /// HTTP client functions for API communication
 async fn make_api_request(method: &str, endpoint: &str, body: Option<String>) -> Result<Response, JsValue> {
     let url = format!("{}{}", API_BASE_URL, endpoint);
@@ -231,14 +424,55 @@ async fn make_api_request(method: &str, endpoint: &str, body: Option<String>) ->
     Ok(resp)
 }
 
+/// Error value for a failed response: the backend's ErrorResponse as an object when it sent one,
+/// so callers can branch on its `code`, otherwise the raw body text
+async fn api_error(response: Response) -> JsValue {
+    let text = match response.text() {
+        Ok(promise) => JsFuture::from(promise).await.ok().and_then(|text| text.as_string()).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+    
+    serde_json::from_str::<ApiError>(&text)
+        .ok()
+        .and_then(|api_error| serde_wasm_bindgen::to_value(&api_error).ok())
+        .unwrap_or_else(|| JsValue::from_str(&format!("API Error: {}", text)))
+}
+
+/// User-facing text for a failed request, chosen by the backend's error code
+fn describe_error(error: &JsValue, fallback: &str) -> String {
+    match ApiError::from_js(error) {
+        Some(api_error) if api_error.code.starts_with("E_VALIDATION_") => {
+            format!("❌ {}", format::escape_html(&api_error.message))
+        }
+        Some(api_error) if api_error.code == "E_RATE_LIMITED" => {
+            "⏳ Too many requests. Please wait a moment and try again.".to_string()
+        }
+        Some(api_error) if api_error.code == "E_LOCK_UNAVAILABLE" => {
+            "⏳ The service is busy saving other changes. Please try again shortly.".to_string()
+        }
+        _ => fallback.to_string(),
+    }
+}
+
+/// Fetch the backend health status, including its readiness checks
+async fn check_health() -> Result<HealthResponse, JsValue> {
+    let response = make_api_request("GET", "/health", None).await?;
+    
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("Health check returned HTTP {}", response.status())));
+    }
+    
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
 /// Create a new review
 async fn create_review(request: CreateReviewRequest) -> Result<CreateReviewResponse, JsValue> {
     let body = serde_json::to_string(&request).map_err(|e| JsValue::from_str(&e.to_string()))?;
     let response = make_api_request("POST", "/reviews", Some(body)).await?;
     
     if !response.ok() {
-        let error_text = JsFuture::from(response.text()?).await?;
-        return Err(JsValue::from_str(&format!("API Error: {}", error_text.as_string().unwrap_or_default())));
+        return Err(api_error(response).await);
     }
     
     let json = JsFuture::from(response.json()?).await?;
@@ -248,14 +482,58 @@ async fn create_review(request: CreateReviewRequest) -> Result<CreateReviewRespo
     Ok(result)
 }
 
+/// Submit reviews queued while offline. Anything that still fails stays queued for the next attempt.
+async fn flush_queued_reviews() {
+    let queued = offline::pending_reviews();
+    if queued.is_empty() || offline::is_offline() || FLUSHING_QUEUE.with(|flushing| flushing.replace(true)) {
+        return;
+    }
+    
+    let queued_count = queued.len();
+    let mut remaining = Vec::new();
+    for review in queued {
+        let sent = match serde_json::from_value::<CreateReviewRequest>(review.clone()) {
+            Ok(request) => create_review(request).await.is_ok(),
+            // Unreadable entries would never succeed; drop them
+            Err(_) => true,
+        };
+        if !sent {
+            remaining.push(review);
+        }
+    }
+    
+    // Keep anything queued while this flush was in flight
+    let submitted = queued_count - remaining.len();
+    remaining.extend(offline::pending_reviews().into_iter().skip(queued_count));
+    offline::set_pending_reviews(&remaining);
+    FLUSHING_QUEUE.with(|flushing| flushing.set(false));
+    
+    console::log_1(&format!("Submitted {} queued review(s), {} still pending", submitted, remaining.len()).into());
+}
+
+/// Check whether a draft review closely matches one this client already submitted
+async fn check_duplicate(request: DuplicateCheckRequest) -> Result<DuplicateCheckResponse, JsValue> {
+    let body = serde_json::to_string(&request).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let response = make_api_request("POST", "/reviews/check-duplicate", Some(body)).await?;
+    
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    
+    let json = JsFuture::from(response.json()?).await?;
+    let result: DuplicateCheckResponse = serde_wasm_bindgen::from_value(json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    
+    Ok(result)
+}
+
 /// Search reviews
 async fn search_reviews(request: SearchRequest) -> Result<SearchResponse, JsValue> {
     let body = serde_json::to_string(&request).map_err(|e| JsValue::from_str(&e.to_string()))?;
     let response = make_api_request("POST", "/search", Some(body)).await?;
     
     if !response.ok() {
-        let error_text = JsFuture::from(response.text()?).await?;
-        return Err(JsValue::from_str(&format!("API Error: {}", error_text.as_string().unwrap_or_default())));
+        return Err(api_error(response).await);
     }
     
     let json = JsFuture::from(response.json()?).await?;
@@ -265,13 +543,13 @@ async fn search_reviews(request: SearchRequest) -> Result<SearchResponse, JsValu
     Ok(result)
 }
 
-/// Bulk upload reviews
-async fn bulk_upload_reviews(data: String) -> Result<BulkUploadResponse, JsValue> {
-    let response = make_api_request("POST", "/reviews/bulk", Some(data)).await?;
+/// Bulk upload reviews, recording the source file name in the upload history
+async fn bulk_upload_reviews(data: String, file_name: &str) -> Result<BulkUploadResponse, JsValue> {
+    let endpoint = format!("/reviews/bulk?file_name={}", js_sys::encode_uri_component(file_name));
+    let response = make_api_request("POST", &endpoint, Some(data)).await?;
     
     if !response.ok() {
-        let error_text = JsFuture::from(response.text()?).await?;
-        return Err(JsValue::from_str(&format!("API Error: {}", error_text.as_string().unwrap_or_default())));
+        return Err(api_error(response).await);
     }
     
     let json = JsFuture::from(response.json()?).await?;
@@ -281,6 +559,71 @@ async fn bulk_upload_reviews(data: String) -> Result<BulkUploadResponse, JsValue
     Ok(result)
 }
 
+/// Anonymous per-browser reviewer id, generated once and kept in localStorage
+fn reviewer_id() -> Option<String> {
+    let storage = window()?.local_storage().ok()??;
+    
+    if let Ok(Some(existing)) = storage.get_item("reviewer_id") {
+        return Some(existing);
+    }
+    
+    let generated = format!(
+        "client_{:08x}{:08x}",
+        (js_sys::Math::random() * u32::MAX as f64) as u32,
+        (js_sys::Math::random() * u32::MAX as f64) as u32
+    );
+    storage.set_item("reviewer_id", &generated).ok()?;
+    Some(generated)
+}
+
+/// Warn about a likely duplicate and ask whether to submit anyway
+async fn confirm_duplicate_submission(response: &DuplicateCheckResponse) -> bool {
+    let Some(matching) = &response.matching_review else {
+        return true;
+    };
+    
+    let body_html = format!(
+        r#"<p>You already submitted a very similar review ({} match):</p>
+           <blockquote><strong>{}</strong><br>{}</blockquote>
+           <p>Submit this one anyway?</p>"#,
+        format::format_percent(response.similarity_score.unwrap_or(response.threshold), 0),
+        format::escape_html(&matching.title),
+        format::escape_html(&matching.body)
+    );
+    
+    modal::confirm("Possible duplicate review", &body_html, "Submit anyway", "Cancel").await
+}
+
+/// Report a file's preview and decide whether to upload it.
+/// Files without valid rows are skipped; files with some invalid rows need confirmation.
+async fn confirm_upload_preview(file_name: &str, summary: &preview::PreviewSummary) -> bool {
+    let file_name = format::escape_html(file_name);
+    
+    if summary.valid_rows == 0 {
+        let reason = summary.errors.first()
+            .map(|e| format!(" (line {}: {})", e.line, format::escape_html(&e.message)))
+            .unwrap_or_default();
+        show_message("upload-status", &format!("❌ No valid reviews in {}{}", file_name, reason), true);
+        return false;
+    }
+    
+    if summary.invalid_rows() == 0 {
+        return true;
+    }
+    
+    let error_items: String = summary.errors.iter()
+        .map(|e| format!("<li>Line {}: {}</li>", e.line, format::escape_html(&e.message)))
+        .collect();
+    let body_html = format!(
+        r#"<p>{} of {} rows in <strong>{}</strong> will be rejected:</p>
+           <ul class="preview-errors">{}</ul>
+           <p>Upload the {} valid rows?</p>"#,
+        summary.invalid_rows(), summary.total_rows, file_name, error_items, summary.valid_rows
+    );
+    
+    modal::confirm("Some rows have errors", &body_html, "Upload valid rows", "Cancel").await
+}
+
 /// Display success message
 fn show_message(element_id: &str, message: &str, is_error: bool) {
     if let Some(element) = window().unwrap().document().unwrap().get_element_by_id(element_id) {
@@ -289,7 +632,77 @@ fn show_message(element_id: &str, message: &str, is_error: bool) {
     }
 }
 
-/// Display search results
+/// Markup for one search result card. Imported reviews carry their credit line; the
+/// backend refuses attribution licenses without one, so every such card shows it.
+fn render_result_card(result: &SearchResult) -> String {
+    let stars = "★".repeat(result.review.rating as usize) + &"☆".repeat(5 - result.review.rating as usize);
+    // Credited cards give up a line of body so the credit fits a virtual row
+    let (card_class, credit) = match result.review.credit() {
+        Some(credit) => (
+            "result-item credited",
+            format!(r#"<p class="result-credit">{}</p>"#, format::escape_html(&credit)),
+        ),
+        None => ("result-item", String::new()),
+    };
+    format!(r#"
+        <div class="{}">
+            <div class="result-header">
+                <h4 class="result-title">{}</h4>
+                <div class="result-meta">
+                    <span class="similarity-score">{} match</span>
+                    <span class="rating">{}</span>
+                </div>
+            </div>
+            <p class="result-body">{}</p>
+            {}
+            <div class="result-footer">
+                <span class="product-id">Product: {}</span>
+                {}
+            </div>
+        </div>
+    "#, 
+        card_class,
+        format::escape_html(&result.review.title),
+        format::format_percent(result.similarity_score, 1),
+        stars,
+        format::escape_html(&result.review.body),
+        credit,
+        format::escape_html(&result.review.product_id),
+        format::format_timestamp(&result.review.timestamp)
+    )
+}
+
+/// Heading of printed results: the query, its filters and when it was printed
+fn print_heading_html() -> String {
+    let (query, filters) = SEARCH_STATE.with(|state| {
+        let state = state.borrow();
+        (state.query.clone(), state.filters.clone().unwrap_or_default())
+    });
+
+    let mut conditions = Vec::new();
+    if let Some(product_id) = &filters.product_id {
+        conditions.push(format!("product {}", product_id));
+    }
+    if let Some(min_rating) = filters.min_rating {
+        conditions.push(format!("{}★ and up", min_rating));
+    }
+    if let Some(date_from) = &filters.date_from {
+        conditions.push(format!("from {}", format::format_date(date_from.get(..10).unwrap_or(date_from))));
+    }
+    if let Some(date_to) = &filters.date_to {
+        conditions.push(format!("to {}", format::format_date(date_to.get(..10).unwrap_or(date_to))));
+    }
+    let conditions = if conditions.is_empty() { String::new() } else { format!(" ({})", conditions.join(", ")) };
+
+    format!(
+        r#"<h1>Search results for "{}"{}</h1><p class="print-meta">Printed {}</p>"#,
+        format::escape_html(&query),
+        format::escape_html(&conditions),
+        format::format_exact(&js_sys::Date::new_0())
+    )
+}
+
+/// Display search results in a virtualized list so only visible cards are in the DOM
 fn display_search_results(results: Vec<SearchResult>) {
     let document = window().unwrap().document().unwrap();
     if let Some(results_div) = document.get_element_by_id("search-results") {
@@ -303,37 +716,156 @@ fn display_search_results(results: Vec<SearchResult>) {
             return;
         }
         
-        let mut html = String::from(r#"<h3>Search Results</h3><div class="results-list">"#);
+        results_div.set_inner_html(&format!(
+            "<h3>Search Results</h3>{}",
+            virtual_list::render("search-results-list")
+        ));
         
-        for result in results {
-            let stars = "★".repeat(result.review.rating as usize) + &"☆".repeat(5 - result.review.rating as usize);
-            html.push_str(&format!(r#"
-                <div class="result-item">
-                    <div class="result-header">
-                        <h4 class="result-title">{}</h4>
-                        <div class="result-meta">
-                            <span class="similarity-score">{:.1}% match</span>
-                            <span class="rating">{}</span>
-                        </div>
-                    </div>
-                    <p class="result-body">{}</p>
-                    <div class="result-footer">
-                        <span class="product-id">Product: {}</span>
-                        <span class="timestamp">{}</span>
-                    </div>
-                </div>
-            "#, 
-                result.review.title,
-                result.similarity_score * 100.0,
-                stars,
-                result.review.body,
-                result.review.product_id,
-                result.review.timestamp
-            ));
+        let cards = results.iter().map(render_result_card).collect();
+        let on_near_end = || wasm_bindgen_futures::spawn_local(load_more_results());
+        if let Err(error) = virtual_list::attach(&document, "search-results-list", cards, on_near_end) {
+            console::error_1(&format!("Failed to render results: {:?}", error).into());
+        }
+    }
+}
+
+/// Show the last saved search while offline; returns false if nothing was saved
+fn display_cached_search_results() -> bool {
+    let Some(cached) = offline::load_last_search()
+        .and_then(|json| serde_json::from_str::<SearchResponse>(&json).ok()) else {
+        return false;
+    };
+    
+    // Cached results can't be extended without the backend
+    SEARCH_STATE.with(|state| state.borrow_mut().next_cursor = None);
+    let query = cached.query.clone();
+    display_search_results(cached.results);
+    
+    if let Some(results_div) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id("search-results")) {
+        let notice = format!(
+            r#"<div class="offline-notice">📴 You're offline. Showing the last results for "{}".</div>"#,
+            format::escape_html(&query)
+        );
+        let _ = results_div.insert_adjacent_html("afterbegin", &notice);
+    }
+    true
+}
+
+/// The filters set in the filter row; `None` when every field is empty.
+/// Dates are whole days, so `date_to` runs to the end of its day.
+fn search_filters(document: &web_sys::Document) -> Option<SearchFilters> {
+    let value = |id: &str| {
+        let value = document.get_element_by_id(id).and_then(|e| e.dyn_into::<HtmlInputElement>().ok())?.value();
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    let min_rating = document
+        .get_element_by_id("filter-min-rating")
+        .and_then(|e| e.dyn_into::<web_sys::HtmlSelectElement>().ok())
+        .and_then(|select| select.value().parse::<u8>().ok());
+
+    let filters = SearchFilters {
+        product_id: value("filter-product"),
+        min_rating,
+        date_from: value("filter-date-from").map(|day| format!("{}T00:00:00Z", day)),
+        date_to: value("filter-date-to").map(|day| format!("{}T23:59:59.999Z", day)),
+    };
+    (filters != SearchFilters::default()).then_some(filters)
+}
+
+/// Fetch the next page of results for the current query and append it to the list
+async fn load_more_results() {
+    let Some((query, filters, cursor)) = SEARCH_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.loading || state.query.is_empty() {
+            return None;
+        }
+        let cursor = state.next_cursor.clone()?;
+        state.loading = true;
+        Some((state.query.clone(), state.filters.clone(), cursor))
+    }) else {
+        return;
+    };
+    
+    let request = SearchRequest {
+        query: query.clone(),
+        limit: Some(SEARCH_PAGE_SIZE),
+        cursor: Some(cursor),
+        filters: filters.clone(),
+    };
+    let result = search_reviews(request).await;
+    
+    let still_current = SEARCH_STATE.with(|state| {
+        let state = state.borrow();
+        state.query == query && state.filters == filters
+    });
+    match result {
+        Ok(response) if still_current => {
+            let new_cards = response.results.iter().map(render_result_card).collect();
+            virtual_list::append("search-results-list", new_cards);
+            SEARCH_STATE.with(|state| state.borrow_mut().next_cursor = response.next_cursor);
+        }
+        Ok(_) => {}
+        Err(error) => {
+            console::error_1(&format!("Loading more results failed: {:?}", error).into());
+        }
+    }
+    
+    SEARCH_STATE.with(|state| state.borrow_mut().loading = false);
+}
+
+/// Read the current value of an input, textarea or select by element id
+fn field_value(document: &web_sys::Document, id: &str) -> String {
+    let Some(element) = document.get_element_by_id(id) else {
+        return String::new();
+    };
+
+    if let Some(input) = element.dyn_ref::<HtmlInputElement>() {
+        input.value()
+    } else if let Some(textarea) = element.dyn_ref::<HtmlTextAreaElement>() {
+        textarea.value()
+    } else if let Some(select) = element.dyn_ref::<HtmlSelectElement>() {
+        select.value()
+    } else {
+        String::new()
+    }
+}
+
+/// Render a character counter and return whether the field satisfies its rule
+fn render_counter(document: &web_sys::Document, counter_id: &str, value: &str, rule: &validation::LengthRule) -> bool {
+    let is_valid = rule.is_satisfied(value);
+
+    if let Some(counter) = document.get_element_by_id(counter_id) {
+        counter.set_text_content(Some(&rule.counter_text(value)));
+        let class = if is_valid || value.is_empty() { "char-counter" } else { "char-counter invalid" };
+        counter.set_class_name(class);
+    }
+
+    is_valid
+}
+
+/// Refresh review form counters and only enable submit once all backend rules are met
+fn update_review_form_state(document: &web_sys::Document) {
+    let product_name = field_value(document, "product-name");
+    let review_text = field_value(document, "review-text");
+    let rating = field_value(document, "rating");
+
+    let name_ok = render_counter(document, "product-name-counter", &product_name, &validation::PRODUCT_NAME);
+    let text_ok = render_counter(document, "review-text-counter", &review_text, &validation::REVIEW_TEXT);
+
+    if let Some(words) = document.get_element_by_id("review-text-words") {
+        let count = validation::word_count(&review_text);
+        let label = if count == 1 { "word" } else { "words" };
+        words.set_text_content(Some(&format!("{} {}", count, label)));
+    }
+
+    if let Some(button) = document.get_element_by_id("review-form")
+        .and_then(|form| form.query_selector("button[type='submit']").ok().flatten()) {
+        if name_ok && text_ok && !rating.is_empty() {
+            let _ = button.remove_attribute("disabled");
+        } else {
+            let _ = button.set_attribute("disabled", "");
         }
-        
-        html.push_str("</div>");
-        results_div.set_inner_html(&html);
     }
 }
 
@@ -359,10 +891,7 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                     .map(|textarea| textarea.value())
                     .unwrap_or_default();
                 
-                let rating_str = document.get_element_by_id("rating")
-                    .and_then(|e| e.dyn_into::<HtmlSelectElement>().ok())
-                    .map(|select| select.value())
-                    .unwrap_or_default();
+                let rating_str = field_value(&document, "rating");
                 
                 // Validate inputs
                 if product_name.trim().is_empty() || review_text.trim().is_empty() || rating_str.is_empty() {
@@ -371,19 +900,22 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                 }
                 
                 let rating = match rating_str.parse::<u8>() {
-                    Ok(r) if r >= 1 && r <= 5 => r,
+                    Ok(r) if (1..=5).contains(&r) => r,
                     _ => {
                         show_message("review-form", "Please select a valid rating", true);
                         return;
                     }
                 };
                 
+                let reviewer_id = reviewer_id();
+                
                 // Create review request
                 let request = CreateReviewRequest {
                     title: product_name.clone(),
                     body: review_text,
                     product_id: product_name,
                     rating,
+                    reviewer_id: reviewer_id.clone(),
                 };
                 
                 // Show loading state
@@ -392,6 +924,36 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                     button.set_text_content(Some("Adding Review..."));
                 }
                 
+                // Warn before submitting a near-copy of an earlier review from this browser
+                let duplicate_check = DuplicateCheckRequest {
+                    title: request.title.clone(),
+                    body: request.body.clone(),
+                    reviewer_id,
+                };
+                match check_duplicate(duplicate_check).await {
+                    Ok(response) if response.is_duplicate => {
+                        if !confirm_duplicate_submission(&response).await {
+                            if let Some(button) = document.get_element_by_id("review-form")
+                                .and_then(|form| form.query_selector("button[type='submit']").ok().flatten()) {
+                                button.set_text_content(Some("Add Review"));
+                            }
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        // The check is advisory; never block a submission on it
+                        console::warn_1(&format!("Duplicate check failed: {:?}", error).into());
+                    }
+                }
+                
+                // Keep the submission for later when there is no connection to send it over
+                let offline_copy = if offline::is_offline() {
+                    serde_json::to_value(&request).ok()
+                } else {
+                    None
+                };
+                
                 // Make API call
                 match create_review(request).await {
                     Ok(response) => {
@@ -403,11 +965,19 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                             .and_then(|e| e.dyn_into::<HtmlFormElement>().ok()) {
                             form.reset();
                         }
+                        star_rating::set_value(&document, "rating-widget", 0.0);
+                        update_review_form_state(&document);
                     }
-                    Err(error) => {
-                        console::error_1(&format!("Failed to create review: {:?}", error).into());
-                        show_message("review-form", "❌ Failed to add review. Please try again.", true);
-                    }
+                    Err(error) => match offline_copy {
+                        Some(review) => {
+                            offline::queue_review(review);
+                            show_message("review-form", "📥 You are offline. The review will be submitted when the connection returns.", false);
+                        }
+                        None => {
+                            console::error_1(&format!("Failed to create review: {:?}", error).into());
+                            show_message("review-form", &describe_error(&error, "❌ Failed to add review. Please try again."), true);
+                        }
+                    },
                 }
                 
                 // Reset button text
@@ -422,6 +992,28 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
         closure.forget(); // Keep the closure alive
     }
     
+    // Live character counters and submit gating for the review form
+    for (field_id, event_name) in [("product-name", "input"), ("review-text", "input"), ("rating", "change")] {
+        if let Some(field) = document.get_element_by_id(field_id) {
+            let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+                if let Some(document) = window().and_then(|w| w.document()) {
+                    update_review_form_state(&document);
+                }
+            }) as Box<dyn FnMut(_)>);
+            
+            field.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())?;
+            closure.forget(); // Keep the closure alive
+        }
+    }
+    
+    // Send reviews queued while offline as soon as the connection returns
+    let on_online = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        wasm_bindgen_futures::spawn_local(flush_queued_reviews());
+    }) as Box<dyn FnMut(_)>);
+    window().ok_or("No global window exists")?
+        .add_event_listener_with_callback("online", on_online.as_ref().unchecked_ref())?;
+    on_online.forget(); // Keep the closure alive
+    
     // Search button
     if let Some(search_btn) = document.get_element_by_id("search-btn") {
         let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
@@ -453,18 +1045,46 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                 // Create search request
                 let request = SearchRequest {
                     query: query.trim().to_string(),
-                    limit: Some(10),
+                    limit: Some(SEARCH_PAGE_SIZE),
+                    cursor: None,
+                    filters: search_filters(&document),
                 };
+                permalink::record_search(&request.query);
+                SEARCH_STATE.with(|state| {
+                    *state.borrow_mut() = SearchState {
+                        query: request.query.clone(),
+                        filters: request.filters.clone(),
+                        next_cursor: None,
+                        loading: true,
+                    };
+                });
                 
                 // Make API call
-                match search_reviews(request).await {
+                let result = search_reviews(request).await;
+                SEARCH_STATE.with(|state| state.borrow_mut().loading = false);
+                
+                match result {
                     Ok(response) => {
                         console::log_1(&format!("Search completed: {} results", response.total_results).into());
+                        if let Ok(response_json) = serde_json::to_string(&response) {
+                            offline::save_last_search(&response_json);
+                        }
+                        SEARCH_STATE.with(|state| state.borrow_mut().next_cursor = response.next_cursor.clone());
+                        let corrected_query = response.corrected_query.clone();
                         display_search_results(response.results);
+                        if let (Some(corrected), Some(results_div)) = (corrected_query, document.get_element_by_id("search-results")) {
+                            let notice = format!(
+                                r#"<div class="spelling-notice">Showing results for "<strong>{}</strong>".</div>"#,
+                                format::escape_html(&corrected)
+                            );
+                            let _ = results_div.insert_adjacent_html("afterbegin", &notice);
+                        }
                     }
                     Err(error) => {
                         console::error_1(&format!("Search failed: {:?}", error).into());
-                        show_message("search-results", "❌ Search failed. Please try again.", true);
+                        if !(offline::is_offline() && display_cached_search_results()) {
+                            show_message("search-results", &describe_error(&error, "❌ Search failed. Please try again."), true);
+                        }
                     }
                 }
                 
@@ -479,6 +1099,62 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
         closure.forget(); // Keep the closure alive
     }
     
+    // Copy a shareable link to the current search
+    if let Some(copy_btn) = document.get_element_by_id("copy-link-btn") {
+        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            wasm_bindgen_futures::spawn_local(async move {
+                let Some(button) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id("copy-link-btn")) else {
+                    return;
+                };
+                match permalink::copy_current_link().await {
+                    Ok(()) => button.set_text_content(Some("✅ Link copied")),
+                    Err(error) => {
+                        console::warn_1(&format!("Copying link failed: {:?}", error).into());
+                        button.set_text_content(Some("❌ Copy failed"));
+                    }
+                }
+            });
+        }) as Box<dyn FnMut(_)>);
+        
+        copy_btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        closure.forget(); // Keep the closure alive
+        
+        // Restore the label once the pointer leaves after a copy
+        let reset = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            if let Some(button) = event.current_target().and_then(|t| t.dyn_into::<web_sys::Element>().ok()) {
+                button.set_text_content(Some("🔗 Copy link"));
+            }
+        }) as Box<dyn FnMut(_)>);
+        copy_btn.add_event_listener_with_callback("mouseleave", reset.as_ref().unchecked_ref())?;
+        reset.forget(); // Keep the closure alive
+    }
+    
+    // Print the loaded results, with full bodies and no controls
+    print::attach("search-results-list", print_heading_html)?;
+    if let Some(print_btn) = document.get_element_by_id("print-btn") {
+        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            let Some(button) = event.current_target().and_then(|t| t.dyn_into::<web_sys::Element>().ok()) else {
+                return;
+            };
+            if !print::has_results("search-results-list") {
+                button.set_text_content(Some("Nothing to print yet"));
+            } else if let Err(error) = print::print() {
+                console::warn_1(&format!("Printing failed: {:?}", error).into());
+            }
+        }) as Box<dyn FnMut(_)>);
+        
+        print_btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        closure.forget(); // Keep the closure alive
+        
+        let reset = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            if let Some(button) = event.current_target().and_then(|t| t.dyn_into::<web_sys::Element>().ok()) {
+                button.set_text_content(Some("🖨 Print results"));
+            }
+        }) as Box<dyn FnMut(_)>);
+        print_btn.add_event_listener_with_callback("mouseleave", reset.as_ref().unchecked_ref())?;
+        reset.forget(); // Keep the closure alive
+    }
+    
     // Upload button
     if let Some(upload_btn) = document.get_element_by_id("upload-btn") {
         let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
@@ -492,7 +1168,7 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                     .and_then(|e| e.dyn_into::<web_sys::HtmlInputElement>().ok())
                     .and_then(|input| input.files());
                 
-                if files.as_ref().map_or(true, |f| f.length() == 0) {
+                if files.as_ref().is_none_or(|f| f.length() == 0) {
                     show_message("upload-status", "Please select files to upload", true);
                     return;
                 }
@@ -533,15 +1209,33 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                                 Ok(content) => {
                                     let content_str = content.as_string().unwrap_or_default();
                                     
+                                    // Check rows against the validation rules off the main thread
+                                    let progress_name = file_name.clone();
+                                    let summary = preview::preview_file(&file_name, &content_str, move |processed, total| {
+                                        show_message("upload-status", &format!("🔎 Checking {}: {} of {} rows", format::escape_html(&progress_name), processed, total), false);
+                                    }).await;
+                                    
+                                    if !confirm_upload_preview(&file_name, &summary).await {
+                                        continue;
+                                    }
+                                    
+                                    // JSON files are sent as-is; JSONL and CSV go as a JSON string
+                                    let payload = if file_name.to_lowercase().ends_with(".json") {
+                                        content_str
+                                    } else {
+                                        serde_json::to_string(&content_str).unwrap_or_default()
+                                    };
+                                    
                                     // Make bulk upload API call
-                                    match bulk_upload_reviews(content_str).await {
+                                    match bulk_upload_reviews(payload, &file_name).await {
                                         Ok(response) => {
                                             console::log_1(&format!("Bulk upload completed: {}", response.message).into());
                                             show_message("upload-status", &format!("✅ {}", response.message), false);
                                         }
                                         Err(error) => {
                                             console::error_1(&format!("Bulk upload failed: {:?}", error).into());
-                                            show_message("upload-status", &format!("❌ Failed to upload {}", file_name), true);
+                                            let fallback = format!("❌ Failed to upload {}", format::escape_html(&file_name));
+                                            show_message("upload-status", &describe_error(&error, &fallback), true);
                                         }
                                     }
                                 }
@@ -558,6 +1252,9 @@ fn setup_event_listeners(document: &web_sys::Document) -> Result<(), JsValue> {
                 if let Some(button) = document.get_element_by_id("upload-btn") {
                     button.set_text_content(Some("Upload Files"));
                 }
+                
+                #[cfg(feature = "admin")]
+                admin::on_data_changed();
             });
         }) as Box<dyn FnMut(_)>);
         
@@ -0,0 +1,71 @@
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Event};
+
+/// Show a blocking confirmation dialog and resolve to the user's choice.
+///
+/// `body_html` is inserted as-is, so callers must escape any user content.
+pub async fn confirm(title: &str, body_html: &str, confirm_label: &str, cancel_label: &str) -> bool {
+    let Some(document) = window().and_then(|w| w.document()) else {
+        return false;
+    };
+    let Some(body) = document.body() else {
+        return false;
+    };
+    let Ok(overlay) = document.create_element("div") else {
+        return false;
+    };
+
+    overlay.set_class_name("modal-overlay");
+    let _ = overlay.set_attribute("role", "dialog");
+    let _ = overlay.set_attribute("aria-modal", "true");
+    let _ = overlay.set_attribute("aria-labelledby", "modal-title");
+    overlay.set_inner_html(&format!(
+        r#"
+        <div class="modal">
+            <h3 id="modal-title">{}</h3>
+            <div class="modal-body">{}</div>
+            <div class="modal-actions">
+                <button type="button" class="modal-cancel" id="modal-cancel">{}</button>
+                <button type="button" class="modal-confirm" id="modal-confirm">{}</button>
+            </div>
+        </div>
+    "#,
+        title, body_html, cancel_label, confirm_label
+    ));
+
+    if body.append_child(&overlay).is_err() {
+        return false;
+    }
+
+    let promise = Promise::new(&mut |resolve, _reject| {
+        for (button_id, choice) in [("modal-confirm", true), ("modal-cancel", false)] {
+            if let Some(button) = document.get_element_by_id(button_id) {
+                let resolve = resolve.clone();
+                let closure = Closure::wrap(Box::new(move |_event: Event| {
+                    let _ = resolve.call1(&JsValue::NULL, &JsValue::from_bool(choice));
+                }) as Box<dyn FnMut(_)>);
+
+                let _ = button.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref());
+                closure.forget(); // Keep the closure alive
+            }
+        }
+    });
+
+    // Move focus into the dialog so keyboard users land on the safe choice
+    if let Some(cancel) = document
+        .get_element_by_id("modal-cancel")
+        .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+    {
+        let _ = cancel.focus();
+    }
+
+    let choice = JsFuture::from(promise)
+        .await
+        .map(|value| value.as_bool().unwrap_or(false))
+        .unwrap_or(false);
+
+    overlay.remove();
+    choice
+}
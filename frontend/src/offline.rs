@@ -0,0 +1,74 @@
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window};
+
+/// Service worker script served next to index.html
+const SERVICE_WORKER_SCRIPT: &str = "sw.js";
+
+/// localStorage key holding the last successful search response
+const LAST_SEARCH_KEY: &str = "last_search";
+
+/// Register the service worker that caches the app shell.
+/// The crate version is passed along so each release gets a fresh cache.
+pub async fn register_service_worker() {
+    let Some(window) = window() else {
+        return;
+    };
+    let navigator = window.navigator();
+    if !window.is_secure_context() || !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        return;
+    }
+
+    let script_url = format!("{}?v={}", SERVICE_WORKER_SCRIPT, env!("CARGO_PKG_VERSION"));
+    match JsFuture::from(navigator.service_worker().register(&script_url)).await {
+        Ok(_) => console::log_1(&"✅ Service worker registered".into()),
+        Err(error) => console::warn_1(&format!("Service worker registration failed: {:?}", error).into()),
+    }
+}
+
+/// Remember the last search response so it can be shown while offline
+pub fn save_last_search(response_json: &str) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(LAST_SEARCH_KEY, response_json);
+    }
+}
+
+/// The last saved search response, if any
+pub fn load_last_search() -> Option<String> {
+    window()?.local_storage().ok()??.get_item(LAST_SEARCH_KEY).ok()?
+}
+
+/// Whether the browser reports being offline
+pub fn is_offline() -> bool {
+    window().is_some_and(|w| !w.navigator().on_line())
+}
+
+/// localStorage key holding reviews submitted while offline, as a JSON array of request bodies
+const PENDING_REVIEWS_KEY: &str = "pending_reviews";
+
+/// Reviews waiting to be sent once the browser is back online
+pub fn pending_reviews() -> Vec<serde_json::Value> {
+    window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(PENDING_REVIEWS_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Replace the pending review queue; an empty queue clears the key
+pub fn set_pending_reviews(reviews: &[serde_json::Value]) {
+    let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return;
+    };
+    if reviews.is_empty() {
+        let _ = storage.remove_item(PENDING_REVIEWS_KEY);
+    } else if let Ok(json) = serde_json::to_string(reviews) {
+        let _ = storage.set_item(PENDING_REVIEWS_KEY, &json);
+    }
+}
+
+/// Append a review submission to the offline queue
+pub fn queue_review(review: serde_json::Value) {
+    let mut reviews = pending_reviews();
+    reviews.push(review);
+    set_pending_reviews(&reviews);
+}
@@ -0,0 +1,37 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Url, UrlSearchParams};
+
+/// Query-string parameter carrying the search text, matching `GET /search?q=`
+const QUERY_PARAM: &str = "q";
+
+/// Search text from the page URL, if the page was opened from a search link
+pub fn current_query() -> Option<String> {
+    let search = window()?.location().search().ok()?;
+    let query = UrlSearchParams::new_with_str(&search).ok()?.get(QUERY_PARAM)?;
+    (!query.trim().is_empty()).then_some(query)
+}
+
+/// Put a search into the page URL so it can be bookmarked or shared.
+/// Replaces the current history entry rather than adding one per search.
+pub fn record_search(query: &str) {
+    let Some(window) = window() else {
+        return;
+    };
+    let Ok(url) = window.location().href().and_then(|href| Url::new(&href)) else {
+        return;
+    };
+    url.search_params().set(QUERY_PARAM, query);
+
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url.href()));
+    }
+}
+
+/// Copy the current page URL, including any recorded search, to the clipboard
+pub async fn copy_current_link() -> Result<(), JsValue> {
+    let window = window().ok_or("No global window exists")?;
+    let href = window.location().href()?;
+    JsFuture::from(window.navigator().clipboard().write_text(&href)).await?;
+    Ok(())
+}
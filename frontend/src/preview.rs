@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, MessageEvent, Worker, WorkerOptions, WorkerType};
+
+use crate::validation;
+
+/// Script that runs `parse_upload_preview` off the main thread
+const WORKER_SCRIPT: &str = "parse-worker.js";
+
+/// Rows parsed between progress reports
+const PROGRESS_EVERY: u32 = 250;
+
+/// Only the first few row errors are kept for display
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Outcome of checking an upload file against the backend's validation rules
+#[derive(Serialize, Deserialize, Default)]
+pub struct PreviewSummary {
+    pub total_rows: u32,
+    pub valid_rows: u32,
+    pub errors: Vec<PreviewError>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PreviewError {
+    pub line: u32,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+struct UploadRow {
+    title: String,
+    body: String,
+    product_id: String,
+    rating: u8,
+}
+
+impl PreviewSummary {
+    fn record(&mut self, line: u32, result: Result<UploadRow, String>) {
+        self.total_rows += 1;
+        match result.and_then(|row| validate_row(&row)) {
+            Ok(()) => self.valid_rows += 1,
+            Err(message) => {
+                if self.errors.len() < MAX_REPORTED_ERRORS {
+                    self.errors.push(PreviewError { line, message });
+                }
+            }
+        }
+    }
+
+    pub fn invalid_rows(&self) -> u32 {
+        self.total_rows - self.valid_rows
+    }
+}
+
+/// Mirror of the backend's `ReviewData::validate`
+fn validate_row(row: &UploadRow) -> Result<(), String> {
+    let fields = [
+        ("title", &row.title, &validation::TITLE),
+        ("body", &row.body, &validation::REVIEW_TEXT),
+        ("product_id", &row.product_id, &validation::PRODUCT_ID),
+    ];
+    for (field, value, rule) in fields {
+        if !rule.is_satisfied(value) {
+            return Err(format!("{} must be {}-{} characters", field, rule.min, rule.max));
+        }
+    }
+
+    if !(1..=5).contains(&row.rating) {
+        return Err("rating must be between 1 and 5".to_string());
+    }
+
+    Ok(())
+}
+
+/// Parse and validate an upload file the way the bulk endpoint will,
+/// reporting `(processed, total)` rows as it goes
+pub fn parse(file_name: &str, content: &str, on_progress: &dyn Fn(u32, u32)) -> PreviewSummary {
+    let mut summary = PreviewSummary::default();
+
+    if file_name.to_lowercase().ends_with(".json") {
+        let rows = match serde_json::from_str::<Value>(content) {
+            Ok(Value::Array(rows)) => rows,
+            Ok(row @ Value::Object(_)) => vec![row],
+            Ok(_) => {
+                summary.errors.push(PreviewError { line: 1, message: "Expected a JSON array or object".to_string() });
+                return summary;
+            }
+            Err(e) => {
+                summary.errors.push(PreviewError { line: e.line() as u32, message: format!("Invalid JSON: {}", e) });
+                return summary;
+            }
+        };
+
+        let total = rows.len() as u32;
+        for (i, row) in rows.into_iter().enumerate() {
+            summary.record(i as u32 + 1, serde_json::from_value(row).map_err(|e| e.to_string()));
+            report(&summary, total, on_progress);
+        }
+        return summary;
+    }
+
+    // Same detection as the backend: JSONL when the first content line is an object, CSV otherwise
+    let content_lines: Vec<(u32, &str)> = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i as u32 + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    if content_lines.first().is_some_and(|(_, line)| line.starts_with('{')) {
+        let total = content_lines.len() as u32;
+        for (line_number, line) in content_lines {
+            summary.record(line_number, serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e)));
+            report(&summary, total, on_progress);
+        }
+    } else {
+        // The header row is not a review
+        let total = content_lines.len().saturating_sub(1) as u32;
+        let mut reader = csv::ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+
+        let headers = reader.headers().cloned().unwrap_or_default();
+        for record in reader.records() {
+            let (line, row) = match record {
+                Ok(record) => (
+                    record.position().map_or(0, |p| p.line() as u32),
+                    record.deserialize::<UploadRow>(Some(&headers)).map_err(|e| format!("Invalid CSV: {}", e)),
+                ),
+                Err(e) => (e.position().map_or(0, |p| p.line() as u32), Err(format!("Invalid CSV: {}", e))),
+            };
+            summary.record(line, row);
+            report(&summary, total, on_progress);
+        }
+    }
+
+    on_progress(summary.total_rows, summary.total_rows);
+    summary
+}
+
+fn report(summary: &PreviewSummary, total: u32, on_progress: &dyn Fn(u32, u32)) {
+    if summary.total_rows.checked_rem(PROGRESS_EVERY) == Some(0) || summary.total_rows == total {
+        on_progress(summary.total_rows, total);
+    }
+}
+
+/// Worker entry point: parse a file and post progress through `on_progress(processed, total)`
+#[wasm_bindgen]
+pub fn parse_upload_preview(file_name: &str, content: &str, on_progress: &js_sys::Function) -> Result<JsValue, JsValue> {
+    let report = |processed: u32, total: u32| {
+        let _ = on_progress.call2(&JsValue::NULL, &processed.into(), &total.into());
+    };
+    let summary = parse(file_name, content, &report);
+    serde_wasm_bindgen::to_value(&summary).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Check a file in a Web Worker so large files don't freeze the page,
+/// falling back to the main thread where module workers are unavailable
+pub async fn preview_file(file_name: &str, content: &str, on_progress: impl Fn(u32, u32) + 'static) -> PreviewSummary {
+    match preview_in_worker(file_name, content, on_progress).await {
+        Ok(summary) => summary,
+        Err((error, on_progress)) => {
+            console::warn_1(&format!("Parsing on the main thread: {:?}", error).into());
+            parse(file_name, content, &*on_progress)
+        }
+    }
+}
+
+type ProgressFn = Rc<dyn Fn(u32, u32)>;
+
+async fn preview_in_worker(
+    file_name: &str,
+    content: &str,
+    on_progress: impl Fn(u32, u32) + 'static,
+) -> Result<PreviewSummary, (JsValue, ProgressFn)> {
+    let on_progress: ProgressFn = Rc::new(on_progress);
+
+    let options = WorkerOptions::new();
+    options.set_type(WorkerType::Module);
+    let worker = Worker::new_with_options(WORKER_SCRIPT, &options).map_err(|e| (e, on_progress.clone()))?;
+
+    // Handlers live until the worker answers; the worker is terminated afterwards
+    let mut handlers = Vec::new();
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let progress = on_progress.clone();
+        let reject_on_error = reject.clone();
+        let on_message = Closure::wrap(Box::new(move |event: JsValue| {
+            let Some(data) = event.dyn_ref::<MessageEvent>().map(|e| e.data()) else {
+                return;
+            };
+            let field = |name: &str| js_sys::Reflect::get(&data, &JsValue::from_str(name)).unwrap_or(JsValue::UNDEFINED);
+
+            match field("type").as_string().as_deref() {
+                Some("progress") => progress(
+                    field("processed").as_f64().unwrap_or(0.0) as u32,
+                    field("total").as_f64().unwrap_or(0.0) as u32,
+                ),
+                Some("done") => {
+                    let _ = resolve.call1(&JsValue::NULL, &field("summary"));
+                }
+                _ => {
+                    let _ = reject.call1(&JsValue::NULL, &field("message"));
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        let on_error = Closure::wrap(Box::new(move |event: JsValue| {
+            let _ = reject_on_error.call1(&JsValue::NULL, &event);
+        }) as Box<dyn FnMut(_)>);
+
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        handlers.extend([on_message, on_error]);
+    });
+
+    let message = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&message, &"fileName".into(), &file_name.into());
+    let _ = js_sys::Reflect::set(&message, &"content".into(), &content.into());
+    if let Err(e) = worker.post_message(&message) {
+        worker.terminate();
+        return Err((e, on_progress));
+    }
+
+    let result = JsFuture::from(promise).await;
+    worker.terminate();
+    drop(handlers);
+
+    let value = result.map_err(|e| (e, on_progress.clone()))?;
+    serde_wasm_bindgen::from_value(value).map_err(|e| (JsValue::from_str(&e.to_string()), on_progress))
+}
@@ -0,0 +1,75 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Event};
+
+use crate::virtual_list;
+
+/// Container the print stylesheet shows in place of the app
+const CONTAINER_ID: &str = "print-results";
+
+/// Body attribute telling the print stylesheet a result set is being printed
+const PRINT_ATTRIBUTE: &str = "data-print";
+
+/// Print a clean copy of a result list whenever the page is printed, whether from a
+/// "Print results" button or the browser's own print command.
+///
+/// The list is virtualized, so only the rows in view are in the DOM. Just before
+/// printing, every loaded card is copied under `heading_html()` into a container that
+/// the print stylesheet shows instead of the app. With no list on the page the page
+/// prints as usual.
+pub fn attach(list_id: &'static str, heading_html: impl Fn() -> String + 'static) -> Result<(), JsValue> {
+    let window = window().ok_or("No global window exists")?;
+
+    let before_print = Closure::wrap(Box::new(move |_event: Event| {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+            return;
+        };
+        if let Some(stale) = document.get_element_by_id(CONTAINER_ID) {
+            stale.remove();
+        }
+        if !has_results(list_id) {
+            return;
+        }
+        let cards = virtual_list::items(list_id);
+        let (Some(body), Ok(container)) = (document.body(), document.create_element("div")) else {
+            return;
+        };
+
+        container.set_id(CONTAINER_ID);
+        container.set_inner_html(&format!(r#"{}<div class="results-list">{}</div>"#, heading_html(), cards.concat()));
+        if body.append_child(&container).is_ok() {
+            let _ = body.set_attribute(PRINT_ATTRIBUTE, "results");
+        }
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("beforeprint", before_print.as_ref().unchecked_ref())?;
+    before_print.forget(); // Keep the closure alive
+
+    let after_print = Closure::wrap(Box::new(move |_event: Event| {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+            return;
+        };
+        if let Some(container) = document.get_element_by_id(CONTAINER_ID) {
+            container.remove();
+        }
+        if let Some(body) = document.body() {
+            let _ = body.remove_attribute(PRINT_ATTRIBUTE);
+        }
+    }) as Box<dyn FnMut(_)>);
+    window.add_event_listener_with_callback("afterprint", after_print.as_ref().unchecked_ref())?;
+    after_print.forget(); // Keep the closure alive
+
+    Ok(())
+}
+
+/// Whether the list is on the page with results to print
+pub fn has_results(list_id: &str) -> bool {
+    // An earlier list stays registered after the results area is replaced
+    let on_page = window()
+        .and_then(|w| w.document())
+        .is_some_and(|document| document.get_element_by_id(list_id).is_some());
+    on_page && !virtual_list::items(list_id).is_empty()
+}
+
+/// Open the browser's print dialog (which also offers saving as PDF)
+pub fn print() -> Result<(), JsValue> {
+    window().ok_or("No global window exists")?.print()
+}
@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{console, window, Element, Event, HtmlInputElement, KeyboardEvent};
+
+use crate::chart::{self, Datum};
+use crate::{api_error, describe_error, format, make_api_request};
+
+const PANEL_ID: &str = "product-ratings";
+const INPUT_ID: &str = "product-ratings-input";
+const RESULT_ID: &str = "product-ratings-result";
+
+#[derive(Deserialize)]
+struct ReviewStats {
+    review_count: usize,
+    average_rating: Option<f64>,
+    rating_histogram: BTreeMap<u8, usize>,
+    first_review_at: Option<String>,
+    last_review_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProductStatsResponse {
+    product_id: String,
+    stats: ReviewStats,
+}
+
+/// Markup for the product ratings lookup
+pub fn section_html() -> String {
+    format!(
+        r#"<div id="{PANEL_ID}" class="product-ratings">
+               <div class="search-form">
+                   <input type="text" id="{INPUT_ID}" placeholder="Product ID">
+                   <button type="button" data-action="show">Show ratings</button>
+               </div>
+               <div id="{RESULT_ID}"></div>
+           </div>"#
+    )
+}
+
+async fn fetch_stats(product_id: &str) -> Result<ProductStatsResponse, JsValue> {
+    let endpoint = format!("/products/{}/stats", js_sys::encode_uri_component(product_id));
+    let response = make_api_request("GET", &endpoint, None).await?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    let json = JsFuture::from(response.json()?).await?;
+    serde_wasm_bindgen::from_value(json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn result_area() -> Option<Element> {
+    window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(RESULT_ID))
+}
+
+/// Look up a product's ratings and draw its histogram
+pub async fn show(product_id: String) {
+    let Some(result) = result_area() else {
+        return;
+    };
+    let product_id = product_id.trim().to_string();
+    if product_id.is_empty() {
+        result.set_inner_html(r#"<p class="history-empty">Enter a product ID to see its ratings.</p>"#);
+        return;
+    }
+
+    result.set_inner_html(r#"<p class="history-empty">Loading ratings...</p>"#);
+    match fetch_stats(&product_id).await {
+        Ok(response) => result.set_inner_html(&render(&response)),
+        Err(error) => {
+            console::error_1(&format!("Failed to load product stats: {:?}", error).into());
+            result.set_inner_html(&format!(
+                r#"<p class="history-empty">{}</p>"#,
+                describe_error(&error, "❌ Ratings could not be loaded. Please try again.")
+            ));
+        }
+    }
+}
+
+fn render(response: &ProductStatsResponse) -> String {
+    let stats = &response.stats;
+    let product = format::escape_html(&response.product_id);
+    if stats.review_count == 0 {
+        return format!(r#"<p class="history-empty">No reviews for {} yet.</p>"#, product);
+    }
+
+    let bars: Vec<Datum> = (1..=5u8)
+        .map(|rating| {
+            let count = stats.rating_histogram.get(&rating).copied().unwrap_or(0);
+            let share = count as f64 / stats.review_count as f64;
+            Datum::new(
+                format!("{}★", rating),
+                count as f64,
+                format!(
+                    "{}★: {} review{} ({})",
+                    rating,
+                    format::format_number(count as f64, 0),
+                    if count == 1 { "" } else { "s" },
+                    format::format_percent(share, 0)
+                ),
+            )
+        })
+        .collect();
+    let span = match (&stats.first_review_at, &stats.last_review_at) {
+        (Some(first), Some(last)) => format!(
+            " First review {}, latest {}.",
+            format::format_timestamp(first),
+            format::format_timestamp(last)
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        r#"<h3>{product}</h3>
+           {}
+           <p class="job-meta">{} review{}, averaging {}★.{} Hover a bar for its count.</p>"#,
+        chart::bar_chart(&format!("Rating distribution for {}", response.product_id), &bars),
+        format::format_number(stats.review_count as f64, 0),
+        if stats.review_count == 1 { "" } else { "s" },
+        format::format_number(stats.average_rating.unwrap_or(0.0), 1),
+        span
+    )
+}
+
+/// Handle the lookup button and Enter in the product field
+pub fn attach(document: &web_sys::Document) -> Result<(), JsValue> {
+    let Some(panel) = document.get_element_by_id(PANEL_ID) else {
+        return Ok(());
+    };
+
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        let submitted = match event.dyn_ref::<KeyboardEvent>() {
+            // Enter on the button already clicks it
+            Some(key) => key.key() == "Enter" && target.id() == INPUT_ID,
+            None => target.get_attribute("data-action").as_deref() == Some("show"),
+        };
+        if !submitted {
+            return;
+        }
+
+        let product_id = window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(INPUT_ID))
+            .and_then(|e| e.dyn_into::<HtmlInputElement>().ok())
+            .map(|input| input.value())
+            .unwrap_or_default();
+        wasm_bindgen_futures::spawn_local(show(product_id));
+    }) as Box<dyn FnMut(_)>);
+    panel.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    panel.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+    closure.forget(); // Keep the closure alive
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{Document, Element, Event, HtmlInputElement, KeyboardEvent, MouseEvent};
+
+const MAX_STARS: u8 = 5;
+
+/// Markup for an accessible star rating widget.
+///
+/// The selected value is mirrored into a hidden input (`input_id`) so forms can
+/// read it like any other field; a `change` event fires on that input whenever
+/// the rating changes. Half-star steps are only offered when `allow_half` is set.
+pub fn render(widget_id: &str, input_id: &str, allow_half: bool) -> String {
+    let stars: String = (1..=MAX_STARS)
+        .map(|i| format!(r#"<span class="star" data-star="{}" aria-hidden="true">★</span>"#, i))
+        .collect();
+
+    format!(
+        r#"<div class="star-rating" id="{widget_id}" role="slider" tabindex="0"
+               aria-label="Rating" aria-valuemin="0" aria-valuemax="{max}" aria-valuenow="0"
+               aria-valuetext="No rating" data-value="0" data-allow-half="{allow_half}" data-input="{input_id}">
+               {stars}
+           </div>
+           <input type="hidden" id="{input_id}" name="{input_id}" value="">"#,
+        widget_id = widget_id,
+        input_id = input_id,
+        max = MAX_STARS,
+        allow_half = allow_half,
+        stars = stars,
+    )
+}
+
+/// Attach pointer and keyboard handlers to a rendered widget
+pub fn attach(document: &Document, widget_id: &str) -> Result<(), JsValue> {
+    let widget = document
+        .get_element_by_id(widget_id)
+        .ok_or_else(|| JsValue::from_str(&format!("Missing star rating widget '{}'", widget_id)))?;
+
+    // Hover preview
+    let preview_widget = widget.clone();
+    let on_move = Closure::wrap(Box::new(move |event: MouseEvent| {
+        if let Some(value) = pointer_value(&preview_widget, &event) {
+            paint(&preview_widget, value);
+        }
+    }) as Box<dyn FnMut(_)>);
+    widget.add_event_listener_with_callback("mousemove", on_move.as_ref().unchecked_ref())?;
+    on_move.forget();
+
+    // Restore the committed value once the pointer leaves
+    let leave_widget = widget.clone();
+    let on_leave = Closure::wrap(Box::new(move |_event: Event| {
+        paint(&leave_widget, current_value(&leave_widget));
+    }) as Box<dyn FnMut(_)>);
+    widget.add_event_listener_with_callback("mouseleave", on_leave.as_ref().unchecked_ref())?;
+    on_leave.forget();
+
+    let click_widget = widget.clone();
+    let on_click = Closure::wrap(Box::new(move |event: MouseEvent| {
+        if let Some(value) = pointer_value(&click_widget, &event) {
+            commit(&click_widget, value);
+        }
+    }) as Box<dyn FnMut(_)>);
+    widget.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+    on_click.forget();
+
+    // Arrow keys step, Home/End jump, digits select directly
+    let key_widget = widget.clone();
+    let on_key = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        let current = current_value(&key_widget);
+        let step = step(&key_widget);
+
+        let next = match event.key().as_str() {
+            "ArrowRight" | "ArrowUp" => current + step,
+            "ArrowLeft" | "ArrowDown" => current - step,
+            "Home" => step,
+            "End" => MAX_STARS as f32,
+            key => match key.parse::<u8>() {
+                Ok(digit) if (1..=MAX_STARS).contains(&digit) => digit as f32,
+                _ => return,
+            },
+        };
+
+        event.prevent_default();
+        commit(&key_widget, next.clamp(step, MAX_STARS as f32));
+    }) as Box<dyn FnMut(_)>);
+    widget.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())?;
+    on_key.forget();
+
+    Ok(())
+}
+
+/// Set the widget value programmatically (0 clears it), e.g. after a form reset
+pub fn set_value(document: &Document, widget_id: &str, value: f32) {
+    if let Some(widget) = document.get_element_by_id(widget_id) {
+        commit(&widget, value);
+    }
+}
+
+fn current_value(widget: &Element) -> f32 {
+    widget
+        .get_attribute("data-value")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.0)
+}
+
+fn step(widget: &Element) -> f32 {
+    if widget.get_attribute("data-allow-half").as_deref() == Some("true") {
+        0.5
+    } else {
+        1.0
+    }
+}
+
+/// Value under the pointer; the left half of a star counts as a half step when enabled
+fn pointer_value(widget: &Element, event: &MouseEvent) -> Option<f32> {
+    let star = event
+        .target()
+        .and_then(|t| t.dyn_into::<Element>().ok())
+        .and_then(|t| t.closest(".star").ok().flatten())?;
+    let index: f32 = star.get_attribute("data-star")?.parse().ok()?;
+
+    if step(widget) < 1.0 && (event.offset_x() as f32) < star.client_width() as f32 / 2.0 {
+        Some(index - 0.5)
+    } else {
+        Some(index)
+    }
+}
+
+/// Store the value, update the hidden input and notify listeners
+fn commit(widget: &Element, value: f32) {
+    let _ = widget.set_attribute("data-value", &value.to_string());
+    paint(widget, value);
+
+    let Some(input) = widget
+        .get_attribute("data-input")
+        .and_then(|id| widget.owner_document()?.get_element_by_id(&id))
+        .and_then(|e| e.dyn_into::<HtmlInputElement>().ok())
+    else {
+        return;
+    };
+
+    input.set_value(&if value > 0.0 { value.to_string() } else { String::new() });
+
+    if let Ok(event) = Event::new("change") {
+        let _ = input.dispatch_event(&event);
+    }
+}
+
+/// Fill stars up to `value` and keep the ARIA state in sync
+fn paint(widget: &Element, value: f32) {
+    let stars = widget.query_selector_all(".star");
+
+    if let Ok(stars) = stars {
+        for i in 0..stars.length() {
+            let position = (i + 1) as f32;
+            let class = if value >= position {
+                "star full"
+            } else if value >= position - 0.5 {
+                "star half"
+            } else {
+                "star"
+            };
+
+            if let Some(star) = stars.item(i).and_then(|n| n.dyn_into::<Element>().ok()) {
+                star.set_class_name(class);
+            }
+        }
+    }
+
+    let _ = widget.set_attribute("aria-valuenow", &value.to_string());
+    let value_text = if value > 0.0 {
+        format!("{} out of {} stars", value, MAX_STARS)
+    } else {
+        "No rating".to_string()
+    };
+    let _ = widget.set_attribute("aria-valuetext", &value_text);
+}
@@ -0,0 +1,43 @@
+/// Length limits mirroring the backend's `ReviewData::validate` rules
+pub struct LengthRule {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Product name is sent as both `title` (3-200) and `product_id` (max 100)
+pub const PRODUCT_NAME: LengthRule = LengthRule { min: 3, max: 100 };
+
+/// Bulk upload `title` (3-200)
+pub const TITLE: LengthRule = LengthRule { min: 3, max: 200 };
+
+/// Bulk upload `product_id` (max 100)
+pub const PRODUCT_ID: LengthRule = LengthRule { min: 1, max: 100 };
+
+/// Review text is sent as `body` (10-2000)
+pub const REVIEW_TEXT: LengthRule = LengthRule { min: 10, max: 2000 };
+
+impl LengthRule {
+    /// Whether the value would pass backend validation.
+    /// Lengths are byte counts, as the backend checks `str::len`.
+    pub fn is_satisfied(&self, value: &str) -> bool {
+        !value.trim().is_empty() && (self.min..=self.max).contains(&value.len())
+    }
+
+    /// Counter text such as "12/2000" with a hint when out of range
+    pub fn counter_text(&self, value: &str) -> String {
+        let length = value.len();
+
+        if length < self.min {
+            format!("{}/{} · at least {} characters", length, self.max, self.min)
+        } else if length > self.max {
+            format!("{}/{} · {} characters over the limit", length, self.max, length - self.max)
+        } else {
+            format!("{}/{}", length, self.max)
+        }
+    }
+}
+
+/// Number of whitespace-separated words
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Document, Event};
+
+/// Row pitch in pixels: `.virtual-window .result-item` height plus the list gap
+const ROW_HEIGHT: f64 = 185.0;
+
+/// Extra rows kept in the DOM above and below the visible range
+const OVERSCAN: usize = 3;
+
+/// Rows from the end at which `on_near_end` fires
+const NEAR_END_ROWS: f64 = 2.0;
+
+struct VirtualList {
+    items: Vec<String>,
+    rendered: Option<(usize, usize)>,
+    on_near_end: Rc<dyn Fn()>,
+    _on_scroll: Closure<dyn FnMut(Event)>,
+}
+
+thread_local! {
+    static LISTS: RefCell<HashMap<String, VirtualList>> = RefCell::new(HashMap::new());
+}
+
+/// Markup for a scrollable, windowed list. Call `attach` once it is in the DOM.
+pub fn render(list_id: &str) -> String {
+    format!(
+        r#"<div class="virtual-viewport" id="{}">
+               <div class="virtual-spacer"><div class="results-list virtual-window"></div></div>
+           </div>"#,
+        list_id
+    )
+}
+
+/// Start windowed rendering of pre-rendered item markup.
+///
+/// Only the rows in view (plus a small overscan) are in the DOM. `on_near_end`
+/// runs when the user scrolls close to the last item, e.g. to fetch the next page.
+pub fn attach(document: &Document, list_id: &str, items: Vec<String>, on_near_end: impl Fn() + 'static) -> Result<(), JsValue> {
+    let viewport = document
+        .get_element_by_id(list_id)
+        .ok_or_else(|| JsValue::from_str(&format!("Missing virtual list '{}'", list_id)))?;
+
+    let scroll_id = list_id.to_string();
+    let on_scroll = Closure::wrap(Box::new(move |_event: Event| {
+        update(&scroll_id, false);
+    }) as Box<dyn FnMut(_)>);
+    viewport.add_event_listener_with_callback("scroll", on_scroll.as_ref().unchecked_ref())?;
+
+    // Replacing an earlier list with the same id drops its scroll handler
+    LISTS.with(|lists| {
+        lists.borrow_mut().insert(
+            list_id.to_string(),
+            VirtualList {
+                items,
+                rendered: None,
+                on_near_end: Rc::new(on_near_end),
+                _on_scroll: on_scroll,
+            },
+        )
+    });
+
+    update(list_id, true);
+    Ok(())
+}
+
+/// Append items (such as the next page) without moving the scroll position
+pub fn append(list_id: &str, items: Vec<String>) {
+    let found = LISTS.with(|lists| {
+        lists
+            .borrow_mut()
+            .get_mut(list_id)
+            .map(|list| list.items.extend(items))
+            .is_some()
+    });
+
+    if found {
+        update(list_id, true);
+    }
+}
+
+/// Markup of every item in the list, rendered or not
+pub fn items(list_id: &str) -> Vec<String> {
+    LISTS.with(|lists| lists.borrow().get(list_id).map(|list| list.items.clone()).unwrap_or_default())
+}
+
+/// Render the rows in view; `force` re-renders even if the range is unchanged
+fn update(list_id: &str, force: bool) {
+    let Some(viewport) = window().and_then(|w| w.document()).and_then(|d| d.get_element_by_id(list_id)) else {
+        return;
+    };
+    let (Ok(Some(spacer)), Ok(Some(window_el))) = (
+        viewport.query_selector(".virtual-spacer"),
+        viewport.query_selector(".virtual-window"),
+    ) else {
+        return;
+    };
+
+    let scroll_top = viewport.scroll_top() as f64;
+    let viewport_height = viewport.client_height() as f64;
+
+    let near_end = LISTS.with(|lists| {
+        let mut lists = lists.borrow_mut();
+        let list = lists.get_mut(list_id)?;
+        let total = list.items.len();
+
+        let last = (((scroll_top + viewport_height) / ROW_HEIGHT).ceil() as usize + OVERSCAN).min(total);
+        let first = ((scroll_top / ROW_HEIGHT) as usize).saturating_sub(OVERSCAN).min(last);
+
+        if force || list.rendered != Some((first, last)) {
+            let _ = spacer.set_attribute("style", &format!("height: {}px", total as f64 * ROW_HEIGHT));
+            let _ = window_el.set_attribute("style", &format!("transform: translateY({}px)", first as f64 * ROW_HEIGHT));
+            window_el.set_inner_html(&list.items[first..last].concat());
+            list.rendered = Some((first, last));
+        }
+
+        // Only user scrolling asks for more, so mounting never chains fetches
+        let remaining = total as f64 * ROW_HEIGHT - (scroll_top + viewport_height);
+        (!force && total > 0 && remaining <= NEAR_END_ROWS * ROW_HEIGHT).then(|| list.on_near_end.clone())
+    });
+
+    // Called outside the borrow so the callback may append to the list
+    if let Some(on_near_end) = near_end {
+        on_near_end();
+    }
+}
@@ -0,0 +1,60 @@
+// App shell cache. Registered from the wasm app as sw.js?v=<crate version>,
+// so every release installs into a fresh cache and old ones are dropped.
+const VERSION = new URL(self.location).searchParams.get('v') || 'dev';
+const CACHE_NAME = `semantic-search-shell-${VERSION}`;
+const SHELL_ASSETS = [
+    './',
+    './index.html',
+    './style.css',
+    './parse-worker.js',
+    './manifest.webmanifest',
+    './icons/icon-192.png',
+    './icons/icon-512.png',
+    './icons/icon-maskable-512.png',
+    './pkg/semantic_search_frontend.js',
+    './pkg/semantic_search_frontend_bg.wasm',
+];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil(
+        caches.open(CACHE_NAME)
+            .then((cache) => cache.addAll(SHELL_ASSETS))
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(
+                keys.filter((key) => key.startsWith('semantic-search-shell-') && key !== CACHE_NAME)
+                    .map((key) => caches.delete(key))
+            ))
+            .then(() => self.clients.claim())
+    );
+});
+
+// Serve same-origin GETs from the cache first and refresh it in the background.
+// API calls go to the backend origin and are never intercepted.
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+
+    event.respondWith(
+        caches.open(CACHE_NAME).then(async (cache) => {
+            const cached = await cache.match(request, { ignoreSearch: request.mode === 'navigate' });
+            const network = fetch(request)
+                .then((response) => {
+                    if (response.ok) {
+                        cache.put(request, response.clone());
+                    }
+                    return response;
+                })
+                .catch(() => cached || (request.mode === 'navigate' ? cache.match('./index.html') : Response.error()));
+
+            return cached || network;
+        })
+    );
+});